@@ -6,6 +6,7 @@ pub const OP_FORWARD: u8 = 0x00;
 pub const OP_MULTIPLEX: u8 = 0x01;
 pub const OP_BATCH: u8 = 0x02;
 pub const OP_FUNGIBLE_ASSET_ORDER: u8 = 0x03;
+pub const OP_NON_FUNGIBLE_ASSET_ORDER: u8 = 0x04;
 
 pub const ACK_ERR_ONLY_MAKER: &[u8] = &[0xDE, 0xAD, 0xC0, 0xDE];
 
@@ -58,6 +59,18 @@ alloy::sol! {
       uint256 quote_amount;
   }
 
+  struct NonFungibleAssetOrder {
+      bytes sender;
+      bytes receiver;
+      bytes base_collection;
+      string base_collection_name;
+      string base_collection_symbol;
+      uint256 base_collection_path;
+      string token_id;
+      string token_uri;
+      bytes quote_collection;
+  }
+
   struct Ack {
       uint256 tag;
       bytes inner_ack;
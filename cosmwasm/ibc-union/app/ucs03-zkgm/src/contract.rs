@@ -5,14 +5,16 @@ use base58::ToBase58;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_string, wasm_execute, Addr, BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo,
-    QueryRequest, Reply, Response, StdError, SubMsg, SubMsgResult, Uint128, Uint256,
+    to_json_binary, to_json_string, wasm_execute, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps,
+    DepsMut, Empty, Env, MessageInfo, Order, QueryRequest, Reply, Response, StdError, StdResult,
+    SubMsg, SubMsgResult, Uint128, Uint256, WasmMsg,
 };
+use cw_storage_plus::Bound;
 use ibc_union_msg::{
     module::IbcUnionMsg,
     msg::{MsgSendPacket, MsgWriteAcknowledgement},
 };
-use ibc_union_spec::types::Packet;
+use ibc_union_spec::types::{Channel, Packet};
 use token_factory_api::{Metadata, MetadataResponse, TokenFactoryMsg, TokenFactoryQuery};
 use unionlabs::{
     ethereum::keccak256,
@@ -21,14 +23,21 @@ use unionlabs::{
 
 use crate::{
     com::{
-        Ack, Batch, BatchAck, FungibleAssetOrder, FungibleAssetOrderAck, Instruction, Multiplex,
-        ZkgmPacket, ACK_ERR_ONLY_MAKER, FILL_TYPE_MARKETMAKER, FILL_TYPE_PROTOCOL, OP_BATCH,
-        OP_FUNGIBLE_ASSET_ORDER, OP_MULTIPLEX, TAG_ACK_FAILURE, TAG_ACK_SUCCESS, ZKGM_VERSION_0,
+        Ack, Batch, BatchAck, Forward, FungibleAssetOrder, FungibleAssetOrderAck, Instruction,
+        Multiplex, NonFungibleAssetOrder, ZkgmPacket, ACK_ERR_ONLY_MAKER, FILL_TYPE_MARKETMAKER,
+        FILL_TYPE_PROTOCOL, OP_BATCH, OP_FORWARD, OP_FUNGIBLE_ASSET_ORDER, OP_MULTIPLEX,
+        OP_NON_FUNGIBLE_ASSET_ORDER, TAG_ACK_FAILURE, TAG_ACK_SUCCESS, ZKGM_VERSION_0,
+    },
+    msg::{
+        ChannelBalancesResponse, Cw721ContractInfoResponse, Cw721ExecuteMsg, Cw721InstantiateMsg,
+        Cw721NftInfoResponse, Cw721OwnerOfResponse, Cw721QueryMsg, DenomBalance, EurekaMsg,
+        ExecuteMsg, InitMsg, MigrateMsg, PredictWrappedDenomResponse, QueryMsg, WrappedToken,
+        WrappedTokensResponse,
     },
-    msg::{EurekaMsg, ExecuteMsg, InitMsg, MigrateMsg},
     state::{
-        CHANNEL_BALANCE, CONFIG, EXECUTING_PACKET, EXECUTION_ACK, HASH_TO_FOREIGN_TOKEN,
-        TOKEN_ORIGIN,
+        PendingCollection, PendingMint, CHANNEL_BALANCE, COLLECTION_ORIGIN, COLLECTION_TO_FOREIGN,
+        CONFIG, EXECUTING_PACKET, EXECUTION_ACK, HASH_TO_FOREIGN_TOKEN, IN_FLIGHT_PACKET,
+        NFT_ESCROW, PENDING_COLLECTIONS, TOKEN_ORIGIN, WRAPPED_COLLECTION,
     },
     ContractError,
 };
@@ -36,6 +45,10 @@ use crate::{
 pub const PROTOCOL_VERSION: &str = "ucs03-zkgm-0";
 
 pub const REPLY_ID: u64 = 0x1337;
+pub const COLLECTION_REPLY_ID: u64 = 0x1338;
+
+pub const DEFAULT_QUERY_LIMIT: u32 = 10;
+pub const MAX_QUERY_LIMIT: u32 = 100;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -127,6 +140,22 @@ pub fn execute(
                 ),
             }
         }
+        ExecuteMsg::Send {
+            channel_id,
+            timeout_height,
+            timeout_timestamp,
+            salt,
+            instruction,
+        } => send(
+            deps,
+            env,
+            info,
+            channel_id,
+            timeout_height,
+            timeout_timestamp,
+            salt,
+            instruction,
+        ),
         ExecuteMsg::BatchExecute { msgs } => {
             if info.sender != env.contract.address {
                 Err(ContractError::OnlySelf)
@@ -169,6 +198,28 @@ pub fn execute(
             timeout_timestamp,
             salt,
         ),
+        ExecuteMsg::TransferNft {
+            channel_id,
+            receiver,
+            collection,
+            token_id,
+            quote_collection,
+            timeout_height,
+            timeout_timestamp,
+            salt,
+        } => transfer_nft(
+            deps,
+            env,
+            info,
+            channel_id,
+            receiver,
+            collection,
+            token_id,
+            quote_collection,
+            timeout_height,
+            timeout_timestamp,
+            salt,
+        ),
     }
 }
 
@@ -195,6 +246,26 @@ fn timeout_packet(
     packet: Packet,
     relayer: Addr,
 ) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    let packet_hash = commit_packet(&packet);
+    // Specific case of forwarding where the failure is threaded back directly.
+    if let Some(parent) = IN_FLIGHT_PACKET.may_load(deps.storage, packet_hash.clone())? {
+        IN_FLIGHT_PACKET.remove(deps.storage, packet_hash);
+        let ibc_host = CONFIG.load(deps.storage)?.ibc_host;
+        let zkgm_ack = Ack {
+            tag: TAG_ACK_FAILURE,
+            inner_ack: Default::default(),
+        }
+        .abi_encode_params();
+        return Ok(Response::new().add_message(wasm_execute(
+            &ibc_host,
+            &ibc_union_msg::msg::ExecuteMsg::WriteAcknowledgement(MsgWriteAcknowledgement {
+                channel_id: parent.destination_channel_id,
+                packet: parent,
+                acknowledgement: zkgm_ack.into(),
+            }),
+            vec![],
+        )?));
+    }
     let zkgm_packet = ZkgmPacket::abi_decode_params(&packet.data, true)?;
     timeout_internal(
         deps,
@@ -229,6 +300,10 @@ fn timeout_internal(
             let order = FungibleAssetOrder::abi_decode_params(&instruction.operand, true)?;
             refund(deps, packet.source_channel_id, order)
         }
+        OP_NON_FUNGIBLE_ASSET_ORDER => {
+            let order = NonFungibleAssetOrder::abi_decode_params(&instruction.operand, true)?;
+            refund_non_fungible_asset_order(deps, packet.source_channel_id, order)
+        }
         OP_BATCH => {
             let mut response = Response::new();
             let batch = Batch::abi_decode_params(&instruction.operand, true)?;
@@ -252,6 +327,21 @@ fn timeout_internal(
             }
             Ok(response)
         }
+        OP_FORWARD => {
+            // The forwarded packet never reached the next hop, refund the
+            // wrapped instruction as if it was sent directly.
+            let forward = Forward::abi_decode_params(&instruction.operand, true)?;
+            timeout_internal(
+                deps,
+                env,
+                info,
+                packet,
+                relayer,
+                keccak256(salt.get()),
+                _path,
+                forward.instruction,
+            )
+        }
         OP_MULTIPLEX => {
             let multiplex = Multiplex::abi_decode_params(&instruction.operand, true)?;
             if !multiplex.eureka {
@@ -275,6 +365,21 @@ fn acknowledge_packet(
     relayer: Addr,
     ack: Bytes,
 ) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    let packet_hash = commit_packet(&packet);
+    // Specific case of forwarding where the ack is threaded back directly.
+    if let Some(parent) = IN_FLIGHT_PACKET.may_load(deps.storage, packet_hash.clone())? {
+        IN_FLIGHT_PACKET.remove(deps.storage, packet_hash);
+        let ibc_host = CONFIG.load(deps.storage)?.ibc_host;
+        return Ok(Response::new().add_message(wasm_execute(
+            &ibc_host,
+            &ibc_union_msg::msg::ExecuteMsg::WriteAcknowledgement(MsgWriteAcknowledgement {
+                channel_id: parent.destination_channel_id,
+                packet: parent,
+                acknowledgement: ack,
+            }),
+            vec![],
+        )?));
+    }
     let zkgm_packet = ZkgmPacket::abi_decode_params(&packet.data, true)?;
     let ack = Ack::abi_decode_params(&ack, true)?;
     acknowledge_internal(
@@ -321,6 +426,15 @@ fn acknowledge_internal(
                 deps, env, info, packet, relayer, salt, path, order, order_ack,
             )
         }
+        OP_NON_FUNGIBLE_ASSET_ORDER => {
+            let order = NonFungibleAssetOrder::abi_decode_params(&instruction.operand, true)?;
+            if successful {
+                // The token has been minted or unescrowed on the destination.
+                Ok(Response::new())
+            } else {
+                refund_non_fungible_asset_order(deps, packet.source_channel_id, order)
+            }
+        }
         OP_BATCH => {
             let mut response = Response::new();
             let batch = Batch::abi_decode_params(&instruction.operand, true)?;
@@ -354,6 +468,23 @@ fn acknowledge_internal(
             }
             Ok(response)
         }
+        OP_FORWARD => {
+            // The ack of the last hop is threaded back verbatim by every
+            // intermediate hop, acknowledge the wrapped instruction with it.
+            let forward = Forward::abi_decode_params(&instruction.operand, true)?;
+            acknowledge_internal(
+                deps,
+                env,
+                info,
+                packet,
+                relayer,
+                keccak256(salt.get()),
+                path,
+                forward.instruction,
+                successful,
+                ack,
+            )
+        }
         OP_MULTIPLEX => {
             let multiplex = Multiplex::abi_decode_params(&instruction.operand, true)?;
             if !multiplex.eureka {
@@ -408,6 +539,51 @@ fn refund(
     Ok(Response::new().add_messages(messages))
 }
 
+fn refund_non_fungible_asset_order(
+    deps: DepsMut<TokenFactoryQuery>,
+    source_channel: u32,
+    order: NonFungibleAssetOrder,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    let sender = deps
+        .api
+        .addr_validate(str::from_utf8(&order.sender).map_err(|_| ContractError::InvalidSender)?)
+        .map_err(|_| ContractError::UnableToValidateSender)?;
+    let collection = String::from_utf8(order.base_collection.to_vec())
+        .map_err(|_| ContractError::InvalidBaseCollection)?;
+    let source_channel_path = alloy::primitives::U256::try_from(source_channel).map_err(|_| {
+        ContractError::InvalidChannelPath {
+            channel_id: source_channel,
+        }
+    })?;
+    let message = if order.base_collection_path == source_channel_path {
+        // The wrapped token was burnt when sent, mint it back.
+        wasm_execute(
+            collection,
+            &Cw721ExecuteMsg::Mint {
+                token_id: order.token_id,
+                owner: sender.into_string(),
+                token_uri: (!order.token_uri.is_empty()).then_some(order.token_uri),
+                extension: None,
+            },
+            vec![],
+        )?
+    } else {
+        NFT_ESCROW.remove(
+            deps.storage,
+            (source_channel, collection.clone(), order.token_id.clone()),
+        );
+        wasm_execute(
+            collection,
+            &Cw721ExecuteMsg::TransferNft {
+                recipient: sender.into_string(),
+                token_id: order.token_id,
+            },
+            vec![],
+        )?
+    };
+    Ok(Response::new().add_message(message))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn acknowledge_fungible_asset_order(
     deps: DepsMut<TokenFactoryQuery>,
@@ -528,6 +704,20 @@ fn execute_internal(
                 order,
             )
         }
+        OP_NON_FUNGIBLE_ASSET_ORDER => {
+            let order = NonFungibleAssetOrder::abi_decode_params(&instruction.operand, true)?;
+            execute_non_fungible_asset_order(
+                deps,
+                env,
+                info,
+                packet,
+                relayer,
+                relayer_msg,
+                salt,
+                path,
+                order,
+            )
+        }
         OP_BATCH => {
             let batch = Batch::abi_decode_params(&instruction.operand, true)?;
             execute_batch(
@@ -542,6 +732,20 @@ fn execute_internal(
                 batch,
             )
         }
+        OP_FORWARD => {
+            let forward = Forward::abi_decode_params(&instruction.operand, true)?;
+            execute_forward(
+                deps,
+                env,
+                info,
+                packet,
+                relayer,
+                relayer_msg,
+                salt,
+                path,
+                forward,
+            )
+        }
         OP_MULTIPLEX => {
             let multiplex = Multiplex::abi_decode_params(&instruction.operand, true)?;
             execute_multiplex(
@@ -590,6 +794,77 @@ fn predict_wrapped_denom(path: alloy::primitives::U256, channel: u32, token: Byt
     token_hash.to_string()
 }
 
+fn commit_packet(packet: &Packet) -> Vec<u8> {
+    keccak256(packet.abi_encode()).into_bytes().into_vec()
+}
+
+/// Append the next channel to the path. Every hop is encoded on 32 bits, the
+/// first hop being the least significant one.
+fn update_channel_path(
+    path: alloy::primitives::U256,
+    next_channel_id: u32,
+) -> Result<alloy::primitives::U256, ContractError> {
+    if path == alloy::primitives::U256::ZERO {
+        return Ok(alloy::primitives::U256::from(next_channel_id));
+    }
+    let next_hop_index = (path.bit_len() - 1) / 32 + 1;
+    if next_hop_index > 7 {
+        return Err(ContractError::InvalidHops);
+    }
+    Ok((alloy::primitives::U256::from(next_channel_id) << (32 * next_hop_index)) | path)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute_forward(
+    deps: DepsMut<TokenFactoryQuery>,
+    _env: Env,
+    _info: MessageInfo,
+    packet: Packet,
+    _relayer: Addr,
+    _relayer_msg: Bytes,
+    salt: H256,
+    path: alloy::primitives::U256,
+    forward: Forward,
+) -> Result<(Bytes, Response<TokenFactoryMsg>), ContractError> {
+    let ibc_host = CONFIG.load(deps.storage)?.ibc_host;
+    let next_channel = deps.querier.query_wasm_smart::<Channel>(
+        &ibc_host,
+        &ibc_union_msg::query::QueryMsg::GetChannel {
+            channel_id: forward.channel_id,
+        },
+    )?;
+    let next_packet = Packet {
+        source_channel_id: forward.channel_id,
+        destination_channel_id: next_channel.counterparty_channel_id,
+        data: ZkgmPacket {
+            salt: keccak256(salt.get()).into(),
+            path: update_channel_path(path, packet.destination_channel_id)?,
+            instruction: forward.instruction,
+        }
+        .abi_encode_params()
+        .into(),
+        timeout_height: forward.timeout_height,
+        timeout_timestamp: forward.timeout_timestamp,
+    };
+    // Guaranteed to be unique as the ibc host refuses to send the same packet twice.
+    IN_FLIGHT_PACKET.save(deps.storage, commit_packet(&next_packet), &packet)?;
+    // The acknowledgement is asynchronous, it will be written when the next
+    // hop acknowledges or times out the forwarded packet.
+    Ok((
+        Default::default(),
+        Response::new().add_message(wasm_execute(
+            &ibc_host,
+            &ibc_union_msg::msg::ExecuteMsg::PacketSend(MsgSendPacket {
+                source_channel: next_packet.source_channel_id,
+                timeout_height: next_packet.timeout_height,
+                timeout_timestamp: next_packet.timeout_timestamp,
+                data: next_packet.data,
+            }),
+            vec![],
+        )?),
+    ))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn execute_multiplex(
     deps: DepsMut<TokenFactoryQuery>,
@@ -647,6 +922,13 @@ fn execute_batch(
     let mut response = Response::new();
     let mut acks = Vec::<Bytes>::with_capacity(batch.instructions.len());
     for (i, instruction) in batch.instructions.into_iter().enumerate() {
+        // A forward is acknowledged asynchronously, it can't be part of the
+        // batch ack.
+        if instruction.opcode == OP_FORWARD {
+            return Err(ContractError::InvalidBatchInstruction {
+                opcode: instruction.opcode,
+            });
+        }
         let (ack, sub_response) = execute_internal(
             deps.branch(),
             env.clone(),
@@ -809,12 +1091,178 @@ fn execute_fungible_asset_order(
     ))
 }
 
+#[allow(clippy::too_many_arguments)]
+fn execute_non_fungible_asset_order(
+    deps: DepsMut<TokenFactoryQuery>,
+    env: Env,
+    _info: MessageInfo,
+    packet: Packet,
+    _relayer: Addr,
+    _relayer_msg: Bytes,
+    _salt: H256,
+    path: alloy::primitives::U256,
+    order: NonFungibleAssetOrder,
+) -> Result<(Bytes, Response<TokenFactoryMsg>), ContractError> {
+    let wrapped_collection = predict_wrapped_denom(
+        path,
+        packet.destination_channel_id,
+        Vec::from(order.base_collection.clone()).into(),
+    );
+    let receiver = deps
+        .api
+        .addr_validate(
+            str::from_utf8(order.receiver.as_ref()).map_err(|_| ContractError::InvalidReceiver)?,
+        )
+        .map_err(|_| ContractError::UnableToValidateReceiver)?;
+    let token_uri = (!order.token_uri.is_empty()).then_some(order.token_uri);
+    let mut messages = Vec::<SubMsg<TokenFactoryMsg>>::new();
+    if order.quote_collection.as_ref() == wrapped_collection.as_bytes() {
+        // TODO: handle forwarding path
+        match WRAPPED_COLLECTION.may_load(deps.storage, wrapped_collection.clone())? {
+            Some(collection) => messages.push(SubMsg::new(wasm_execute(
+                collection,
+                &Cw721ExecuteMsg::Mint {
+                    token_id: order.token_id,
+                    owner: receiver.into_string(),
+                    token_uri,
+                    extension: None,
+                },
+                vec![],
+            )?)),
+            // The collection doesn't exist yet, the tokens are minted once
+            // it has been instantiated.
+            None => {
+                let mint = PendingMint {
+                    token_id: order.token_id,
+                    owner: receiver,
+                    token_uri,
+                };
+                let mut pending = PENDING_COLLECTIONS
+                    .may_load(deps.storage)?
+                    .unwrap_or_default();
+                match pending
+                    .iter_mut()
+                    .find(|pending| pending.wrapped_collection == wrapped_collection)
+                {
+                    Some(pending) => pending.mints.push(mint),
+                    None => {
+                        let code_id = CONFIG
+                            .load(deps.storage)?
+                            .cw721_code_id
+                            .ok_or(ContractError::NonFungibleTransfersDisabled)?;
+                        messages.push(SubMsg::reply_on_success(
+                            WasmMsg::Instantiate {
+                                admin: Some(env.contract.address.to_string()),
+                                code_id,
+                                msg: to_json_binary(&Cw721InstantiateMsg {
+                                    name: order.base_collection_name,
+                                    symbol: order.base_collection_symbol,
+                                    minter: env.contract.address.to_string(),
+                                })?,
+                                funds: vec![],
+                                label: format!("zkgm-{}", wrapped_collection),
+                            },
+                            COLLECTION_REPLY_ID,
+                        ));
+                        pending.push(PendingCollection {
+                            wrapped_collection,
+                            origin: Uint256::from_u128(packet.destination_channel_id as _),
+                            foreign_collection: Vec::from(order.base_collection).into(),
+                            mints: vec![mint],
+                        });
+                    }
+                }
+                PENDING_COLLECTIONS.save(deps.storage, &pending)?;
+            }
+        }
+    } else if order.base_collection_path == alloy::primitives::U256::from(packet.source_channel_id)
+    {
+        let quote_collection = String::from_utf8(Vec::from(order.quote_collection))
+            .map_err(|_| ContractError::InvalidQuoteCollection)?;
+        let escrow_key = (
+            packet.destination_channel_id,
+            quote_collection.clone(),
+            order.token_id.clone(),
+        );
+        if !NFT_ESCROW.has(deps.storage, escrow_key.clone()) {
+            return Err(ContractError::TokenNotEscrowed);
+        }
+        NFT_ESCROW.remove(deps.storage, escrow_key);
+        messages.push(SubMsg::new(wasm_execute(
+            quote_collection,
+            &Cw721ExecuteMsg::TransferNft {
+                recipient: receiver.into_string(),
+                token_id: order.token_id,
+            },
+            vec![],
+        )?));
+    } else {
+        return Err(ContractError::InvalidQuoteCollection);
+    }
+    Ok((
+        TAG_ACK_SUCCESS.abi_encode().into(),
+        Response::new().add_submessages(messages),
+    ))
+}
+
+/// Register the wrapped collection that was just instantiated and mint the
+/// tokens that were waiting for it.
+fn collection_instantiated(
+    deps: DepsMut<TokenFactoryQuery>,
+    result: SubMsgResult,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    let response = result.into_result().map_err(StdError::generic_err)?;
+    let collection = response
+        .events
+        .iter()
+        .filter(|event| event.ty == "instantiate")
+        .flat_map(|event| &event.attributes)
+        .find(|attribute| attribute.key == "_contract_address")
+        .map(|attribute| deps.api.addr_validate(&attribute.value))
+        .ok_or_else(|| StdError::generic_err("missing instantiated collection address"))??;
+    let mut pending = PENDING_COLLECTIONS.load(deps.storage)?;
+    // Replies are processed in the order the instantiations were dispatched.
+    let PendingCollection {
+        wrapped_collection,
+        origin,
+        foreign_collection,
+        mints,
+    } = pending.remove(0);
+    if pending.is_empty() {
+        PENDING_COLLECTIONS.remove(deps.storage);
+    } else {
+        PENDING_COLLECTIONS.save(deps.storage, &pending)?;
+    }
+    WRAPPED_COLLECTION.save(deps.storage, wrapped_collection, &collection)?;
+    COLLECTION_ORIGIN.save(deps.storage, collection.to_string(), &origin)?;
+    COLLECTION_TO_FOREIGN.save(deps.storage, collection.to_string(), &foreign_collection)?;
+    let messages = mints
+        .into_iter()
+        .map(|mint| {
+            wasm_execute(
+                &collection,
+                &Cw721ExecuteMsg::Mint {
+                    token_id: mint.token_id,
+                    owner: mint.owner.into_string(),
+                    token_uri: mint.token_uri,
+                    extension: None,
+                },
+                vec![],
+            )
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(Response::new().add_messages(messages))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(
     deps: DepsMut<TokenFactoryQuery>,
     _env: Env,
     reply: Reply,
 ) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    if reply.id == COLLECTION_REPLY_ID {
+        return collection_instantiated(deps, reply.result);
+    }
     if reply.id != REPLY_ID {
         return Err(ContractError::UnknownReply { id: reply.id });
     }
@@ -877,9 +1325,95 @@ pub fn reply(
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(
+    deps: Deps<TokenFactoryQuery>,
+    env: Env,
+    msg: QueryMsg,
+) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::ChannelBalance { channel_id, denom } => {
+            let balance = CHANNEL_BALANCE
+                .may_load(deps.storage, (channel_id, denom))?
+                .unwrap_or_default();
+            Ok(to_json_binary(&balance)?)
+        }
+        QueryMsg::ChannelBalances {
+            channel_id,
+            start_after,
+            limit,
+        } => {
+            let balances = CHANNEL_BALANCE
+                .prefix(channel_id)
+                .range(
+                    deps.storage,
+                    start_after.map(Bound::exclusive),
+                    None,
+                    Order::Ascending,
+                )
+                .take(query_limit(limit))
+                .map(|r| r.map(|(denom, balance)| DenomBalance { denom, balance }))
+                .collect::<StdResult<Vec<_>>>()?;
+            Ok(to_json_binary(&ChannelBalancesResponse { balances })?)
+        }
+        QueryMsg::WrappedToken { denom } => {
+            let token = match TOKEN_ORIGIN.may_load(deps.storage, denom.clone())? {
+                Some(path) => Some(WrappedToken {
+                    foreign_token: HASH_TO_FOREIGN_TOKEN.load(deps.storage, denom.clone())?,
+                    denom,
+                    path,
+                }),
+                None => None,
+            };
+            Ok(to_json_binary(&token)?)
+        }
+        QueryMsg::WrappedTokens { start_after, limit } => {
+            let tokens = TOKEN_ORIGIN
+                .range(
+                    deps.storage,
+                    start_after.map(Bound::exclusive),
+                    None,
+                    Order::Ascending,
+                )
+                .take(query_limit(limit))
+                .map(|r| -> StdResult<_> {
+                    let (denom, path) = r?;
+                    Ok(WrappedToken {
+                        foreign_token: HASH_TO_FOREIGN_TOKEN.load(deps.storage, denom.clone())?,
+                        denom,
+                        path,
+                    })
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            Ok(to_json_binary(&WrappedTokensResponse { tokens })?)
+        }
+        QueryMsg::PredictWrappedDenom {
+            path,
+            channel_id,
+            token,
+        } => {
+            let wrapped_denom = factory_denom(
+                &predict_wrapped_denom(
+                    alloy::primitives::U256::from_be_bytes(path.to_be_bytes()),
+                    channel_id,
+                    token,
+                ),
+                env.contract.address.as_str(),
+            );
+            Ok(to_json_binary(&PredictWrappedDenomResponse {
+                wrapped_denom,
+            })?)
+        }
+    }
+}
+
+fn query_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize
+}
+
 #[allow(clippy::too_many_arguments)]
 fn transfer(
-    deps: DepsMut<TokenFactoryQuery>,
+    mut deps: DepsMut<TokenFactoryQuery>,
     env: Env,
     info: MessageInfo,
     channel_id: u32,
@@ -892,49 +1426,17 @@ fn transfer(
     timeout_timestamp: u64,
     salt: H256,
 ) -> Result<Response<TokenFactoryMsg>, ContractError> {
-    if base_amount.is_zero() {
-        return Err(ContractError::InvalidAmount);
-    }
-    let contains_base_token = info
-        .funds
-        .iter()
-        .any(|coin| coin.denom == base_token && coin.amount == base_amount);
-    if !contains_base_token {
-        return Err(ContractError::MissingFunds);
-    }
-    // If the origin exists, the preimage exists
-    let unwrapped_asset = HASH_TO_FOREIGN_TOKEN.may_load(deps.storage, base_token.clone())?;
-    let mut messages = Vec::<CosmosMsg<TokenFactoryMsg>>::new();
-    // TODO: handle forward path
-    let mut origin = TOKEN_ORIGIN.may_load(deps.storage, base_token.clone())?;
-    match origin {
-        // Burn as we are going to unescrow on the counterparty
-        Some(path)
-            if path == Uint256::from(channel_id)
-                && unwrapped_asset == Some(quote_token.clone()) =>
-        {
-            messages.push(
-                TokenFactoryMsg::BurnTokens {
-                    denom: base_token.clone(),
-                    amount: base_amount,
-                    burn_from_address: env.contract.address.into_string(),
-                }
-                .into(),
-            )
-        }
-        // Escrow and update the balance, the counterparty will mint the token
-        _ => {
-            origin = None;
-            CHANNEL_BALANCE.update(deps.storage, (channel_id, base_token.clone()), |balance| {
-                match balance {
-                    Some(value) => value
-                        .checked_add(base_amount.into())
-                        .map_err(|_| ContractError::InvalidChannelBalance),
-                    None => Ok(base_amount.into()),
-                }
-            })?;
-        }
-    };
+    let mut funds = info.funds.clone();
+    let (origin, mut messages) = escrow_or_burn(
+        deps.branch(),
+        &env,
+        &mut funds,
+        channel_id,
+        &base_token,
+        base_amount,
+        &quote_token,
+    )?;
+    ensure_funds_consumed(&funds)?;
     let denom_metadata =
         deps.querier
             .query::<MetadataResponse>(&QueryRequest::<TokenFactoryQuery>::Custom(
@@ -974,9 +1476,9 @@ fn transfer(
                             base_amount: base_amount.u128().try_into().expect("u256>u128"),
                             base_token_symbol,
                             base_token_name,
-                            base_token_path: origin
-                                .map(|x| alloy::primitives::U256::from_be_bytes(x.to_be_bytes()))
-                                .unwrap_or(alloy::primitives::U256::ZERO),
+                            base_token_path: alloy::primitives::U256::from_be_bytes(
+                                origin.to_be_bytes(),
+                            ),
                             quote_token: Vec::from(quote_token).into(),
                             quote_amount: alloy::primitives::U256::from_be_bytes(
                                 quote_amount.to_be_bytes(),
@@ -995,3 +1497,406 @@ fn transfer(
     );
     Ok(Response::new().add_messages(messages))
 }
+
+#[allow(clippy::too_many_arguments)]
+fn transfer_nft(
+    mut deps: DepsMut<TokenFactoryQuery>,
+    env: Env,
+    info: MessageInfo,
+    channel_id: u32,
+    receiver: Bytes,
+    collection: String,
+    token_id: String,
+    quote_collection: Bytes,
+    timeout_height: u64,
+    timeout_timestamp: u64,
+    salt: H256,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    let collection = deps
+        .api
+        .addr_validate(&collection)
+        .map_err(|_| ContractError::InvalidBaseCollection)?;
+    let (origin, messages) = escrow_or_burn_nft(
+        deps.branch(),
+        &env,
+        &info,
+        channel_id,
+        &collection,
+        &token_id,
+        &quote_collection,
+    )?;
+    let contract_info = deps.querier.query_wasm_smart::<Cw721ContractInfoResponse>(
+        &collection,
+        &Cw721QueryMsg::ContractInfo {},
+    )?;
+    let nft_info = deps.querier.query_wasm_smart::<Cw721NftInfoResponse>(
+        &collection,
+        &Cw721QueryMsg::NftInfo {
+            token_id: token_id.clone(),
+        },
+    )?;
+    let config = CONFIG.load(deps.storage)?;
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_message(wasm_execute(
+            &config.ibc_host,
+            &ibc_union_msg::msg::ExecuteMsg::PacketSend(MsgSendPacket {
+                source_channel: channel_id,
+                timeout_height,
+                timeout_timestamp,
+                data: ZkgmPacket {
+                    salt: salt.into(),
+                    path: alloy::primitives::U256::ZERO,
+                    instruction: Instruction {
+                        version: ZKGM_VERSION_0,
+                        opcode: OP_NON_FUNGIBLE_ASSET_ORDER,
+                        operand: NonFungibleAssetOrder {
+                            sender: info.sender.as_bytes().to_vec().into(),
+                            receiver: Vec::from(receiver).into(),
+                            base_collection: collection.as_bytes().to_vec().into(),
+                            base_collection_name: contract_info.name,
+                            base_collection_symbol: contract_info.symbol,
+                            base_collection_path: alloy::primitives::U256::from_be_bytes(
+                                origin.to_be_bytes(),
+                            ),
+                            token_id,
+                            token_uri: nft_info.token_uri.unwrap_or_default(),
+                            quote_collection: Vec::from(quote_collection).into(),
+                        }
+                        .abi_encode_params()
+                        .into(),
+                    },
+                }
+                .abi_encode_params()
+                .into(),
+            }),
+            vec![],
+        )?))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn send(
+    mut deps: DepsMut<TokenFactoryQuery>,
+    env: Env,
+    info: MessageInfo,
+    channel_id: u32,
+    timeout_height: u64,
+    timeout_timestamp: u64,
+    salt: H256,
+    instruction: Bytes,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    let instruction = Instruction::abi_decode_params(&instruction, true)?;
+    // Every order takes its amount out of the funds, such that the same funds
+    // can't pay for several orders.
+    let mut funds = info.funds.clone();
+    let response = verify_internal(
+        deps.branch(),
+        &env,
+        &info,
+        &mut funds,
+        channel_id,
+        alloy::primitives::U256::ZERO,
+        &instruction,
+    )?;
+    ensure_funds_consumed(&funds)?;
+    let config = CONFIG.load(deps.storage)?;
+    Ok(response.add_message(wasm_execute(
+        &config.ibc_host,
+        &ibc_union_msg::msg::ExecuteMsg::PacketSend(MsgSendPacket {
+            source_channel: channel_id,
+            timeout_height,
+            timeout_timestamp,
+            data: ZkgmPacket {
+                salt: salt.into(),
+                path: alloy::primitives::U256::ZERO,
+                instruction,
+            }
+            .abi_encode_params()
+            .into(),
+        }),
+        vec![],
+    )?))
+}
+
+fn verify_internal(
+    mut deps: DepsMut<TokenFactoryQuery>,
+    env: &Env,
+    info: &MessageInfo,
+    funds: &mut [Coin],
+    channel_id: u32,
+    path: alloy::primitives::U256,
+    instruction: &Instruction,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    if instruction.version != ZKGM_VERSION_0 {
+        return Err(ContractError::UnsupportedVersion {
+            version: instruction.version,
+        });
+    }
+    match instruction.opcode {
+        OP_FUNGIBLE_ASSET_ORDER => {
+            let order = FungibleAssetOrder::abi_decode_params(&instruction.operand, true)?;
+            verify_fungible_asset_order(deps, env, info, funds, channel_id, order)
+        }
+        OP_NON_FUNGIBLE_ASSET_ORDER => {
+            let order = NonFungibleAssetOrder::abi_decode_params(&instruction.operand, true)?;
+            verify_non_fungible_asset_order(deps, env, info, channel_id, order)
+        }
+        OP_BATCH => {
+            let mut response = Response::new();
+            let batch = Batch::abi_decode_params(&instruction.operand, true)?;
+            for instruction in batch.instructions {
+                if !matches!(
+                    instruction.opcode,
+                    OP_MULTIPLEX | OP_FUNGIBLE_ASSET_ORDER | OP_NON_FUNGIBLE_ASSET_ORDER
+                ) {
+                    return Err(ContractError::InvalidBatchInstruction {
+                        opcode: instruction.opcode,
+                    });
+                }
+                let sub_response = verify_internal(
+                    deps.branch(),
+                    env,
+                    info,
+                    funds,
+                    channel_id,
+                    path,
+                    &instruction,
+                )?;
+                response = response
+                    .add_attributes(sub_response.attributes)
+                    .add_events(sub_response.events)
+                    .add_submessages(sub_response.messages);
+            }
+            Ok(response)
+        }
+        OP_FORWARD => {
+            let forward = Forward::abi_decode_params(&instruction.operand, true)?;
+            verify_internal(
+                deps,
+                env,
+                info,
+                funds,
+                channel_id,
+                update_channel_path(path, forward.channel_id)?,
+                &forward.instruction,
+            )
+        }
+        OP_MULTIPLEX => {
+            let multiplex = Multiplex::abi_decode_params(&instruction.operand, true)?;
+            if multiplex.sender.as_ref() != info.sender.as_bytes() {
+                return Err(ContractError::InvalidMultiplexSender);
+            }
+            Ok(Response::new())
+        }
+        _ => Err(ContractError::UnknownOpcode {
+            opcode: instruction.opcode,
+        }),
+    }
+}
+
+fn verify_fungible_asset_order(
+    deps: DepsMut<TokenFactoryQuery>,
+    env: &Env,
+    info: &MessageInfo,
+    funds: &mut [Coin],
+    channel_id: u32,
+    order: FungibleAssetOrder,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    if order.sender.as_ref() != info.sender.as_bytes() {
+        return Err(ContractError::InvalidSender);
+    }
+    let base_amount =
+        u128::try_from(order.base_amount).map_err(|_| ContractError::AmountOverflow)?;
+    let base_token = String::from_utf8(order.base_token.to_vec())
+        .map_err(|_| ContractError::InvalidBaseToken)?;
+    let (origin, messages) = escrow_or_burn(
+        deps,
+        env,
+        funds,
+        channel_id,
+        &base_token,
+        base_amount.into(),
+        &order.quote_token,
+    )?;
+    if order.base_token_path != alloy::primitives::U256::from_be_bytes(origin.to_be_bytes()) {
+        return Err(ContractError::InvalidAssetOrigin);
+    }
+    Ok(Response::new().add_messages(messages))
+}
+
+/// Take `amount` of `denom` out of the remaining funds submitted along the
+/// transaction.
+fn take_funds(funds: &mut [Coin], denom: &str, amount: Uint128) -> Result<(), ContractError> {
+    let coin = funds
+        .iter_mut()
+        .find(|coin| coin.denom == denom)
+        .ok_or(ContractError::MissingFunds)?;
+    coin.amount = coin
+        .amount
+        .checked_sub(amount)
+        .map_err(|_| ContractError::MissingFunds)?;
+    Ok(())
+}
+
+/// All of the funds submitted along the transaction must have been taken by
+/// the orders.
+fn ensure_funds_consumed(funds: &[Coin]) -> Result<(), ContractError> {
+    if funds.iter().any(|coin| !coin.amount.is_zero()) {
+        return Err(ContractError::UnexpectedFunds);
+    }
+    Ok(())
+}
+
+/// Burn the tokens if they are going back to their origin, escrow them
+/// otherwise, taking them out of `funds`. Returns the origin path of the token
+/// along with the messages to execute.
+fn escrow_or_burn(
+    deps: DepsMut<TokenFactoryQuery>,
+    env: &Env,
+    funds: &mut [Coin],
+    channel_id: u32,
+    base_token: &str,
+    base_amount: Uint128,
+    quote_token: &[u8],
+) -> Result<(Uint256, Vec<CosmosMsg<TokenFactoryMsg>>), ContractError> {
+    if base_amount.is_zero() {
+        return Err(ContractError::InvalidAmount);
+    }
+    take_funds(funds, base_token, base_amount)?;
+    // If the origin exists, the preimage exists
+    let unwrapped_asset = HASH_TO_FOREIGN_TOKEN.may_load(deps.storage, base_token.to_string())?;
+    match TOKEN_ORIGIN.may_load(deps.storage, base_token.to_string())? {
+        // Burn as we are going to unescrow on the counterparty
+        Some(path)
+            if path == Uint256::from(channel_id)
+                && unwrapped_asset.as_deref() == Some(quote_token) =>
+        {
+            Ok((
+                path,
+                vec![TokenFactoryMsg::BurnTokens {
+                    denom: base_token.to_string(),
+                    amount: base_amount,
+                    burn_from_address: env.contract.address.to_string(),
+                }
+                .into()],
+            ))
+        }
+        // Escrow and update the balance, the counterparty will mint the token
+        _ => {
+            CHANNEL_BALANCE.update(
+                deps.storage,
+                (channel_id, base_token.to_string()),
+                |balance| match balance {
+                    Some(value) => value
+                        .checked_add(base_amount.into())
+                        .map_err(|_| ContractError::InvalidChannelBalance),
+                    None => Ok(base_amount.into()),
+                },
+            )?;
+            Ok((Uint256::zero(), vec![]))
+        }
+    }
+}
+
+fn verify_non_fungible_asset_order(
+    deps: DepsMut<TokenFactoryQuery>,
+    env: &Env,
+    info: &MessageInfo,
+    channel_id: u32,
+    order: NonFungibleAssetOrder,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    if order.sender.as_ref() != info.sender.as_bytes() {
+        return Err(ContractError::InvalidSender);
+    }
+    let collection = deps
+        .api
+        .addr_validate(
+            str::from_utf8(&order.base_collection)
+                .map_err(|_| ContractError::InvalidBaseCollection)?,
+        )
+        .map_err(|_| ContractError::InvalidBaseCollection)?;
+    let (origin, messages) = escrow_or_burn_nft(
+        deps,
+        env,
+        info,
+        channel_id,
+        &collection,
+        &order.token_id,
+        &order.quote_collection,
+    )?;
+    if order.base_collection_path != alloy::primitives::U256::from_be_bytes(origin.to_be_bytes()) {
+        return Err(ContractError::InvalidAssetOrigin);
+    }
+    Ok(Response::new().add_messages(messages))
+}
+
+/// Burn the token if it is going back to its origin, escrow it otherwise.
+/// Returns the origin path of the collection along with the messages to
+/// execute.
+fn escrow_or_burn_nft(
+    deps: DepsMut<TokenFactoryQuery>,
+    env: &Env,
+    info: &MessageInfo,
+    channel_id: u32,
+    collection: &Addr,
+    token_id: &str,
+    quote_collection: &[u8],
+) -> Result<(Uint256, Vec<CosmosMsg<TokenFactoryMsg>>), ContractError> {
+    // The contract is approved by the owner, make sure the sender is not
+    // transferring someone else's token.
+    let owner = deps.querier.query_wasm_smart::<Cw721OwnerOfResponse>(
+        collection,
+        &Cw721QueryMsg::OwnerOf {
+            token_id: token_id.to_string(),
+            include_expired: None,
+        },
+    )?;
+    if owner.owner != info.sender.as_str() {
+        return Err(ContractError::NotTokenOwner);
+    }
+    // If the origin exists, the preimage exists
+    let unwrapped_collection =
+        COLLECTION_TO_FOREIGN.may_load(deps.storage, collection.to_string())?;
+    match COLLECTION_ORIGIN.may_load(deps.storage, collection.to_string())? {
+        // Burn as we are going to unescrow on the counterparty
+        Some(path)
+            if path == Uint256::from(channel_id)
+                && unwrapped_collection.as_deref() == Some(quote_collection) =>
+        {
+            Ok((
+                path,
+                vec![wasm_execute(
+                    collection,
+                    &Cw721ExecuteMsg::Burn {
+                        token_id: token_id.to_string(),
+                    },
+                    vec![],
+                )?
+                .into()],
+            ))
+        }
+        // Escrow, the counterparty will mint the wrapped token
+        _ => {
+            NFT_ESCROW.save(
+                deps.storage,
+                (channel_id, collection.to_string(), token_id.to_string()),
+                &Empty {},
+            )?;
+            Ok((
+                Uint256::zero(),
+                vec![wasm_execute(
+                    collection,
+                    &Cw721ExecuteMsg::TransferNft {
+                        recipient: env.contract.address.to_string(),
+                        token_id: token_id.to_string(),
+                    },
+                    vec![],
+                )?
+                .into()],
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
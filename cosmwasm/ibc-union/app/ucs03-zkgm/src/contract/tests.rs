@@ -0,0 +1,1170 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{
+    coins, from_json,
+    testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+    ContractResult, Event, OwnedDeps, ReplyOn, SubMsgResponse, SystemResult, WasmQuery,
+};
+use ibc_union_spec::types::ChannelState;
+
+use super::*;
+use crate::state::Config;
+
+const IBC_HOST: &str = "ibchost";
+const RELAYER: &str = "relayer";
+const SENDER: &str = "sender";
+
+/// The channel the forwarded packet is received on.
+const SOURCE_CHANNEL: u32 = 1;
+/// The channel the packet is forwarded on.
+const NEXT_CHANNEL: u32 = 2;
+/// The counterparty of [`NEXT_CHANNEL`].
+const NEXT_COUNTERPARTY_CHANNEL: u32 = 20;
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier<TokenFactoryQuery>, TokenFactoryQuery>;
+
+fn setup() -> MockDeps {
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: MockQuerier::<TokenFactoryQuery>::new(&[]),
+        custom_query_type: PhantomData,
+    };
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == IBC_HOST => {
+            match from_json(msg).unwrap() {
+                ibc_union_msg::query::QueryMsg::GetChannel { channel_id } => {
+                    assert_eq!(channel_id, NEXT_CHANNEL);
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_json_binary(&Channel {
+                            state: ChannelState::Open,
+                            connection_id: 1,
+                            counterparty_channel_id: NEXT_COUNTERPARTY_CHANNEL,
+                            counterparty_port_id: vec![1].into(),
+                            version: PROTOCOL_VERSION.to_owned(),
+                        })
+                        .unwrap(),
+                    ))
+                }
+                _ => panic!("unexpected ibc host query"),
+            }
+        }
+        _ => panic!("unexpected wasm query"),
+    });
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(SENDER, &[]),
+        InitMsg {
+            config: Config {
+                ibc_host: Addr::unchecked(IBC_HOST),
+                cw721_code_id: None,
+            },
+        },
+    )
+    .unwrap();
+    deps
+}
+
+fn eureka_multiplex() -> Instruction {
+    Instruction {
+        version: ZKGM_VERSION_0,
+        opcode: OP_MULTIPLEX,
+        operand: Multiplex {
+            sender: SENDER.as_bytes().to_vec().into(),
+            eureka: true,
+            contract_address: b"target".to_vec().into(),
+            contract_calldata: Default::default(),
+        }
+        .abi_encode_params()
+        .into(),
+    }
+}
+
+fn forward(instruction: Instruction) -> Instruction {
+    Instruction {
+        version: ZKGM_VERSION_0,
+        opcode: OP_FORWARD,
+        operand: Forward {
+            channel_id: NEXT_CHANNEL,
+            timeout_height: 0,
+            timeout_timestamp: 100,
+            instruction,
+        }
+        .abi_encode_params()
+        .into(),
+    }
+}
+
+fn fungible_asset_order(base_amount: u128) -> Instruction {
+    Instruction {
+        version: ZKGM_VERSION_0,
+        opcode: OP_FUNGIBLE_ASSET_ORDER,
+        operand: FungibleAssetOrder {
+            sender: SENDER.as_bytes().to_vec().into(),
+            receiver: b"receiver".to_vec().into(),
+            base_token: b"denom".to_vec().into(),
+            base_amount: alloy::primitives::U256::from(base_amount),
+            base_token_symbol: "DENOM".into(),
+            base_token_name: "denom".into(),
+            base_token_path: alloy::primitives::U256::ZERO,
+            quote_token: b"quote".to_vec().into(),
+            quote_amount: alloy::primitives::U256::from(base_amount),
+        }
+        .abi_encode_params()
+        .into(),
+    }
+}
+
+/// A packet received on [`SOURCE_CHANNEL`].
+fn incoming_packet(instruction: Instruction) -> Packet {
+    Packet {
+        source_channel_id: 10,
+        destination_channel_id: SOURCE_CHANNEL,
+        data: ZkgmPacket {
+            salt: [1; 32].into(),
+            path: alloy::primitives::U256::ZERO,
+            instruction,
+        }
+        .abi_encode_params()
+        .into(),
+        timeout_height: 0,
+        timeout_timestamp: 1000,
+    }
+}
+
+fn execute_packet(
+    deps: &mut MockDeps,
+    packet: Packet,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    let env = mock_env();
+    let sender = env.contract.address.clone();
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(sender.as_str(), &[]),
+        ExecuteMsg::ExecutePacket {
+            packet,
+            relayer: Addr::unchecked(RELAYER),
+            relayer_msg: Default::default(),
+        },
+    )
+}
+
+fn ibc_host_msg(
+    deps: &mut MockDeps,
+    msg: IbcUnionMsg,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(IBC_HOST, &[]),
+        ExecuteMsg::IbcUnionMsg(msg),
+    )
+}
+
+/// The messages sent to the ibc host in `response`.
+fn ibc_host_msgs(response: &Response<TokenFactoryMsg>) -> Vec<ibc_union_msg::msg::ExecuteMsg> {
+    response
+        .messages
+        .iter()
+        .filter_map(|sub_msg| match &sub_msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) if contract_addr == IBC_HOST => Some(from_json(msg).unwrap()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Receive a forward of a multiplex, returning the packet sent to the next hop.
+fn receive_forward(deps: &mut MockDeps, parent: &Packet) -> Packet {
+    let response = execute_packet(deps, parent.clone()).unwrap();
+    // The ack is written once the next hop acknowledges
+    assert!(EXECUTION_ACK.load(&deps.storage).unwrap().is_empty());
+    let [ibc_union_msg::msg::ExecuteMsg::PacketSend(send)] = &ibc_host_msgs(&response)[..] else {
+        panic!("expected a single packet to be sent");
+    };
+    Packet {
+        source_channel_id: send.source_channel,
+        destination_channel_id: NEXT_COUNTERPARTY_CHANNEL,
+        data: send.data.clone(),
+        timeout_height: send.timeout_height,
+        timeout_timestamp: send.timeout_timestamp,
+    }
+}
+
+#[test]
+fn update_channel_path_appends_hops() {
+    let path = update_channel_path(alloy::primitives::U256::ZERO, 5).unwrap();
+    assert_eq!(path, alloy::primitives::U256::from(5));
+    let path = update_channel_path(path, 7).unwrap();
+    assert_eq!(
+        path,
+        (alloy::primitives::U256::from(7) << 32) | alloy::primitives::U256::from(5)
+    );
+}
+
+#[test]
+fn update_channel_path_max_hops() {
+    let path = (0..8).fold(alloy::primitives::U256::ZERO, |path, _| {
+        update_channel_path(path, u32::MAX).unwrap()
+    });
+    assert_eq!(path, alloy::primitives::U256::MAX);
+    assert_eq!(
+        update_channel_path(path, 1),
+        Err(ContractError::InvalidHops)
+    );
+}
+
+#[test]
+fn forward_sends_to_next_hop() {
+    let mut deps = setup();
+    let parent = incoming_packet(forward(eureka_multiplex()));
+
+    let next_packet = receive_forward(&mut deps, &parent);
+
+    assert_eq!(next_packet.source_channel_id, NEXT_CHANNEL);
+    assert_eq!(next_packet.timeout_timestamp, 100);
+    let zkgm_packet = ZkgmPacket::abi_decode_params(&next_packet.data, true).unwrap();
+    assert_eq!(
+        zkgm_packet.salt,
+        alloy::primitives::B256::from(keccak256([1; 32]))
+    );
+    assert_eq!(
+        zkgm_packet.path,
+        alloy::primitives::U256::from(SOURCE_CHANNEL)
+    );
+    assert_eq!(
+        zkgm_packet.instruction.abi_encode_params(),
+        eureka_multiplex().abi_encode_params()
+    );
+    assert_eq!(
+        IN_FLIGHT_PACKET
+            .load(&deps.storage, commit_packet(&next_packet))
+            .unwrap(),
+        parent
+    );
+}
+
+#[test]
+fn forward_ack_is_written_for_parent() {
+    let mut deps = setup();
+    let parent = incoming_packet(forward(eureka_multiplex()));
+    let next_packet = receive_forward(&mut deps, &parent);
+
+    let ack = Ack {
+        tag: TAG_ACK_SUCCESS,
+        inner_ack: vec![1, 2, 3].into(),
+    }
+    .abi_encode_params();
+    let response = ibc_host_msg(
+        &mut deps,
+        IbcUnionMsg::OnAcknowledgementPacket {
+            packet: next_packet.clone(),
+            acknowledgement: ack.clone().into(),
+            relayer: RELAYER.into(),
+        },
+    )
+    .unwrap();
+
+    let [ibc_union_msg::msg::ExecuteMsg::WriteAcknowledgement(write_ack)] =
+        &ibc_host_msgs(&response)[..]
+    else {
+        panic!("expected a single acknowledgement to be written");
+    };
+    assert_eq!(write_ack.channel_id, SOURCE_CHANNEL);
+    assert_eq!(write_ack.packet, parent);
+    assert_eq!(write_ack.acknowledgement, Bytes::from(ack));
+    assert!(!IN_FLIGHT_PACKET.has(&deps.storage, commit_packet(&next_packet)));
+}
+
+#[test]
+fn forward_timeout_writes_failure_ack_for_parent() {
+    let mut deps = setup();
+    let parent = incoming_packet(forward(eureka_multiplex()));
+    let next_packet = receive_forward(&mut deps, &parent);
+
+    let response = ibc_host_msg(
+        &mut deps,
+        IbcUnionMsg::OnTimeoutPacket {
+            packet: next_packet.clone(),
+            relayer: RELAYER.into(),
+        },
+    )
+    .unwrap();
+
+    let [ibc_union_msg::msg::ExecuteMsg::WriteAcknowledgement(write_ack)] =
+        &ibc_host_msgs(&response)[..]
+    else {
+        panic!("expected a single acknowledgement to be written");
+    };
+    assert_eq!(write_ack.packet, parent);
+    let ack = Ack::abi_decode_params(&write_ack.acknowledgement, true).unwrap();
+    assert_eq!(ack.tag, TAG_ACK_FAILURE);
+    assert!(!IN_FLIGHT_PACKET.has(&deps.storage, commit_packet(&next_packet)));
+}
+
+#[test]
+fn forward_in_batch_is_rejected() {
+    let mut deps = setup();
+    let batch = Instruction {
+        version: ZKGM_VERSION_0,
+        opcode: OP_BATCH,
+        operand: Batch {
+            instructions: vec![eureka_multiplex(), forward(eureka_multiplex())],
+        }
+        .abi_encode_params()
+        .into(),
+    };
+
+    assert_eq!(
+        execute_packet(&mut deps, incoming_packet(batch)),
+        Err(ContractError::InvalidBatchInstruction { opcode: OP_FORWARD })
+    );
+}
+
+#[test]
+fn send_forward_escrows_funds() {
+    let mut deps = setup();
+
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(SENDER, &coins(10, "denom")),
+        ExecuteMsg::Send {
+            channel_id: SOURCE_CHANNEL,
+            timeout_height: 0,
+            timeout_timestamp: 1000,
+            salt: [1; 32].into(),
+            instruction: forward(fungible_asset_order(10)).abi_encode_params().into(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        CHANNEL_BALANCE
+            .load(&deps.storage, (SOURCE_CHANNEL, "denom".into()))
+            .unwrap(),
+        Uint256::from(10u32)
+    );
+    let [ibc_union_msg::msg::ExecuteMsg::PacketSend(send)] = &ibc_host_msgs(&response)[..] else {
+        panic!("expected a single packet to be sent");
+    };
+    assert_eq!(send.source_channel, SOURCE_CHANNEL);
+    let zkgm_packet = ZkgmPacket::abi_decode_params(&send.data, true).unwrap();
+    assert_eq!(
+        zkgm_packet.instruction.abi_encode_params(),
+        forward(fungible_asset_order(10)).abi_encode_params()
+    );
+}
+
+#[test]
+fn send_forward_requires_funds() {
+    let mut deps = setup();
+
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(SENDER, &coins(9, "denom")),
+            ExecuteMsg::Send {
+                channel_id: SOURCE_CHANNEL,
+                timeout_height: 0,
+                timeout_timestamp: 1000,
+                salt: [1; 32].into(),
+                instruction: forward(fungible_asset_order(10)).abi_encode_params().into(),
+            },
+        ),
+        Err(ContractError::MissingFunds)
+    );
+}
+
+#[test]
+fn forward_timeout_refunds_on_origin() {
+    let mut deps = setup();
+    let sent = Packet {
+        source_channel_id: SOURCE_CHANNEL,
+        destination_channel_id: 10,
+        data: ZkgmPacket {
+            salt: [1; 32].into(),
+            path: alloy::primitives::U256::ZERO,
+            instruction: forward(fungible_asset_order(10)),
+        }
+        .abi_encode_params()
+        .into(),
+        timeout_height: 0,
+        timeout_timestamp: 1000,
+    };
+
+    let response = ibc_host_msg(
+        &mut deps,
+        IbcUnionMsg::OnTimeoutPacket {
+            packet: sent,
+            relayer: RELAYER.into(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: SENDER.into(),
+            amount: coins(10, "denom"),
+        })
+    );
+}
+
+fn non_fungible_asset_order(base_collection_path: u32) -> Instruction {
+    nft_order("collection", base_collection_path, "1", b"quote")
+}
+
+fn nft_order(
+    base_collection: &str,
+    base_collection_path: u32,
+    token_id: &str,
+    quote_collection: &[u8],
+) -> Instruction {
+    Instruction {
+        version: ZKGM_VERSION_0,
+        opcode: OP_NON_FUNGIBLE_ASSET_ORDER,
+        operand: NonFungibleAssetOrder {
+            sender: SENDER.as_bytes().to_vec().into(),
+            receiver: b"receiver".to_vec().into(),
+            base_collection: base_collection.as_bytes().to_vec().into(),
+            base_collection_name: base_collection.into(),
+            base_collection_symbol: "COLLECTION".into(),
+            base_collection_path: alloy::primitives::U256::from(base_collection_path),
+            token_id: token_id.into(),
+            token_uri: "uri".into(),
+            quote_collection: quote_collection.to_vec().into(),
+        }
+        .abi_encode_params()
+        .into(),
+    }
+}
+
+/// A packet sent on [`SOURCE_CHANNEL`].
+fn sent_packet(instruction: Instruction) -> Packet {
+    Packet {
+        source_channel_id: SOURCE_CHANNEL,
+        destination_channel_id: 10,
+        data: ZkgmPacket {
+            salt: [1; 32].into(),
+            path: alloy::primitives::U256::ZERO,
+            instruction,
+        }
+        .abi_encode_params()
+        .into(),
+        timeout_height: 0,
+        timeout_timestamp: 1000,
+    }
+}
+
+/// The cw721 message executed on `collection` in `response`.
+fn cw721_msg(response: &Response<TokenFactoryMsg>) -> Cw721ExecuteMsg {
+    let [sub_msg] = &response.messages[..] else {
+        panic!("expected a single message");
+    };
+    let CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr, msg, ..
+    }) = &sub_msg.msg
+    else {
+        panic!("expected a wasm message");
+    };
+    assert_eq!(contract_addr, "collection");
+    from_json(msg).unwrap()
+}
+
+#[test]
+fn non_fungible_ack_failure_mints_wrapped_token_back() {
+    let mut deps = setup();
+
+    let response = ibc_host_msg(
+        &mut deps,
+        IbcUnionMsg::OnAcknowledgementPacket {
+            packet: sent_packet(non_fungible_asset_order(SOURCE_CHANNEL)),
+            acknowledgement: Ack {
+                tag: TAG_ACK_FAILURE,
+                inner_ack: Default::default(),
+            }
+            .abi_encode_params()
+            .into(),
+            relayer: RELAYER.into(),
+        },
+    )
+    .unwrap();
+
+    let Cw721ExecuteMsg::Mint {
+        token_id,
+        owner,
+        token_uri,
+        ..
+    } = cw721_msg(&response)
+    else {
+        panic!("expected the token to be minted");
+    };
+    assert_eq!(token_id, "1");
+    assert_eq!(owner, SENDER);
+    assert_eq!(token_uri.as_deref(), Some("uri"));
+}
+
+#[test]
+fn non_fungible_timeout_unescrows_token() {
+    let mut deps = setup();
+    NFT_ESCROW
+        .save(
+            &mut deps.storage,
+            (SOURCE_CHANNEL, "collection".into(), "1".into()),
+            &Empty {},
+        )
+        .unwrap();
+
+    let response = ibc_host_msg(
+        &mut deps,
+        IbcUnionMsg::OnTimeoutPacket {
+            packet: sent_packet(non_fungible_asset_order(0)),
+            relayer: RELAYER.into(),
+        },
+    )
+    .unwrap();
+
+    let Cw721ExecuteMsg::TransferNft {
+        recipient,
+        token_id,
+    } = cw721_msg(&response)
+    else {
+        panic!("expected the token to be transferred back");
+    };
+    assert_eq!(recipient, SENDER);
+    assert_eq!(token_id, "1");
+    assert!(!NFT_ESCROW.has(
+        &deps.storage,
+        (SOURCE_CHANNEL, "collection".into(), "1".into())
+    ));
+}
+
+fn batch(instructions: Vec<Instruction>) -> Instruction {
+    Instruction {
+        version: ZKGM_VERSION_0,
+        opcode: OP_BATCH,
+        operand: Batch { instructions }.abi_encode_params().into(),
+    }
+}
+
+fn send(
+    deps: &mut MockDeps,
+    funds: &[Coin],
+    instruction: Instruction,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(SENDER, funds),
+        ExecuteMsg::Send {
+            channel_id: SOURCE_CHANNEL,
+            timeout_height: 0,
+            timeout_timestamp: 1000,
+            salt: [1; 32].into(),
+            instruction: instruction.abi_encode_params().into(),
+        },
+    )
+}
+
+#[test]
+fn send_batch_takes_funds_for_every_order() {
+    let mut deps = setup();
+
+    send(
+        &mut deps,
+        &coins(30, "denom"),
+        batch(vec![fungible_asset_order(10), fungible_asset_order(20)]),
+    )
+    .unwrap();
+
+    assert_eq!(
+        CHANNEL_BALANCE
+            .load(&deps.storage, (SOURCE_CHANNEL, "denom".into()))
+            .unwrap(),
+        Uint256::from(30u32)
+    );
+}
+
+#[test]
+fn send_batch_of_duplicated_orders_is_rejected() {
+    let mut deps = setup();
+
+    // A single payment can't be used for both orders
+    assert_eq!(
+        send(
+            &mut deps,
+            &coins(10, "denom"),
+            batch(vec![fungible_asset_order(10), fungible_asset_order(10)]),
+        ),
+        Err(ContractError::MissingFunds)
+    );
+}
+
+#[test]
+fn send_with_excess_funds_is_rejected() {
+    let mut deps = setup();
+
+    assert_eq!(
+        send(&mut deps, &coins(11, "denom"), fungible_asset_order(10)),
+        Err(ContractError::UnexpectedFunds)
+    );
+    assert_eq!(
+        send(
+            &mut deps,
+            &[Coin::new(10, "denom"), Coin::new(1, "other")],
+            fungible_asset_order(10)
+        ),
+        Err(ContractError::UnexpectedFunds)
+    );
+}
+
+fn query_as<T: serde::de::DeserializeOwned>(deps: &MockDeps, msg: QueryMsg) -> T {
+    from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+}
+
+/// Escrow `count` denoms on `channel_id`, named `denom000`, `denom001`, ...
+fn escrow_denoms(deps: &mut MockDeps, channel_id: u32, count: u32) {
+    for i in 0..count {
+        CHANNEL_BALANCE
+            .save(
+                &mut deps.storage,
+                (channel_id, format!("denom{i:03}")),
+                &Uint256::from(i + 1),
+            )
+            .unwrap();
+    }
+}
+
+/// Register `count` wrapped denoms, named `wrapped000`, `wrapped001`, ...
+fn wrap_denoms(deps: &mut MockDeps, count: u32) {
+    for i in 0..count {
+        let denom = format!("wrapped{i:03}");
+        TOKEN_ORIGIN
+            .save(&mut deps.storage, denom.clone(), &Uint256::from(i))
+            .unwrap();
+        HASH_TO_FOREIGN_TOKEN
+            .save(&mut deps.storage, denom, &i.to_be_bytes().to_vec().into())
+            .unwrap();
+    }
+}
+
+fn channel_balances(deps: &MockDeps, start_after: Option<&str>, limit: Option<u32>) -> Vec<String> {
+    query_as::<ChannelBalancesResponse>(
+        deps,
+        QueryMsg::ChannelBalances {
+            channel_id: SOURCE_CHANNEL,
+            start_after: start_after.map(Into::into),
+            limit,
+        },
+    )
+    .balances
+    .into_iter()
+    .map(|balance| balance.denom)
+    .collect()
+}
+
+fn wrapped_tokens(deps: &MockDeps, start_after: Option<&str>, limit: Option<u32>) -> Vec<String> {
+    query_as::<WrappedTokensResponse>(
+        deps,
+        QueryMsg::WrappedTokens {
+            start_after: start_after.map(Into::into),
+            limit,
+        },
+    )
+    .tokens
+    .into_iter()
+    .map(|token| token.denom)
+    .collect()
+}
+
+#[test]
+fn query_channel_balance() {
+    let mut deps = setup();
+    escrow_denoms(&mut deps, SOURCE_CHANNEL, 2);
+
+    let balance = |channel_id, denom: &str| {
+        query_as::<Uint256>(
+            &deps,
+            QueryMsg::ChannelBalance {
+                channel_id,
+                denom: denom.into(),
+            },
+        )
+    };
+
+    assert_eq!(balance(SOURCE_CHANNEL, "denom001"), Uint256::from(2u32));
+    assert_eq!(balance(SOURCE_CHANNEL, "unknown"), Uint256::zero());
+    assert_eq!(balance(NEXT_CHANNEL, "denom001"), Uint256::zero());
+}
+
+#[test]
+fn query_channel_balances_is_scoped_to_channel() {
+    let mut deps = setup();
+    escrow_denoms(&mut deps, SOURCE_CHANNEL, 2);
+    escrow_denoms(&mut deps, NEXT_CHANNEL, 3);
+
+    let response = query_as::<ChannelBalancesResponse>(
+        &deps,
+        QueryMsg::ChannelBalances {
+            channel_id: SOURCE_CHANNEL,
+            start_after: None,
+            limit: None,
+        },
+    );
+
+    assert_eq!(
+        response
+            .balances
+            .iter()
+            .map(|balance| (balance.denom.as_str(), balance.balance))
+            .collect::<Vec<_>>(),
+        [
+            ("denom000", Uint256::from(1u32)),
+            ("denom001", Uint256::from(2u32))
+        ]
+    );
+}
+
+#[test]
+fn query_channel_balances_paginates() {
+    let mut deps = setup();
+    escrow_denoms(&mut deps, SOURCE_CHANNEL, 105);
+
+    let page = channel_balances(&deps, None, None);
+    assert_eq!(page.len(), DEFAULT_QUERY_LIMIT as usize);
+    assert_eq!(page.first().unwrap(), "denom000");
+    assert_eq!(page.last().unwrap(), "denom009");
+
+    let page = channel_balances(&deps, Some("denom009"), Some(3));
+    assert_eq!(page, ["denom010", "denom011", "denom012"]);
+
+    let page = channel_balances(&deps, None, Some(1000));
+    assert_eq!(page.len(), MAX_QUERY_LIMIT as usize);
+
+    let page = channel_balances(&deps, Some("denom100"), Some(10));
+    assert_eq!(page, ["denom101", "denom102", "denom103", "denom104"]);
+}
+
+#[test]
+fn query_wrapped_token() {
+    let mut deps = setup();
+    wrap_denoms(&mut deps, 2);
+
+    let token = query_as::<Option<WrappedToken>>(
+        &deps,
+        QueryMsg::WrappedToken {
+            denom: "wrapped001".into(),
+        },
+    )
+    .unwrap();
+    assert_eq!(token.denom, "wrapped001");
+    assert_eq!(token.path, Uint256::from(1u32));
+    assert_eq!(
+        token.foreign_token,
+        Bytes::from(1u32.to_be_bytes().to_vec())
+    );
+
+    assert!(query_as::<Option<WrappedToken>>(
+        &deps,
+        QueryMsg::WrappedToken {
+            denom: "denom".into(),
+        },
+    )
+    .is_none());
+}
+
+#[test]
+fn query_wrapped_tokens_paginates() {
+    let mut deps = setup();
+    wrap_denoms(&mut deps, 105);
+
+    let page = wrapped_tokens(&deps, None, None);
+    assert_eq!(page.len(), DEFAULT_QUERY_LIMIT as usize);
+    assert_eq!(page.first().unwrap(), "wrapped000");
+    assert_eq!(page.last().unwrap(), "wrapped009");
+
+    let page = wrapped_tokens(&deps, Some("wrapped009"), Some(2));
+    assert_eq!(page, ["wrapped010", "wrapped011"]);
+
+    let page = wrapped_tokens(&deps, None, Some(1000));
+    assert_eq!(page.len(), MAX_QUERY_LIMIT as usize);
+
+    assert!(wrapped_tokens(&deps, Some("wrapped104"), None).is_empty());
+}
+
+#[test]
+fn query_predict_wrapped_denom_matches_received_token() {
+    let mut deps = setup();
+    let wrapped_denom = predict_wrapped_denom(
+        alloy::primitives::U256::ZERO,
+        SOURCE_CHANNEL,
+        b"denom".to_vec().into(),
+    );
+    let order = Instruction {
+        version: ZKGM_VERSION_0,
+        opcode: OP_FUNGIBLE_ASSET_ORDER,
+        operand: FungibleAssetOrder {
+            sender: SENDER.as_bytes().to_vec().into(),
+            receiver: b"receiver".to_vec().into(),
+            base_token: b"denom".to_vec().into(),
+            base_amount: alloy::primitives::U256::from(10),
+            base_token_symbol: "DENOM".into(),
+            base_token_name: "denom".into(),
+            base_token_path: alloy::primitives::U256::ZERO,
+            quote_token: wrapped_denom.as_bytes().to_vec().into(),
+            quote_amount: alloy::primitives::U256::from(10),
+        }
+        .abi_encode_params()
+        .into(),
+    };
+
+    let response = execute_packet(&mut deps, incoming_packet(order)).unwrap();
+
+    let predicted = query_as::<PredictWrappedDenomResponse>(
+        &deps,
+        QueryMsg::PredictWrappedDenom {
+            path: Uint256::zero(),
+            channel_id: SOURCE_CHANNEL,
+            token: b"denom".to_vec().into(),
+        },
+    )
+    .wrapped_denom;
+    assert!(response.messages.iter().any(|sub_msg| matches!(
+        &sub_msg.msg,
+        CosmosMsg::Custom(TokenFactoryMsg::MintTokens { denom, .. }) if *denom == predicted
+    )));
+
+    let token = query_as::<Option<WrappedToken>>(
+        &deps,
+        QueryMsg::WrappedToken {
+            denom: predicted.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(token.path, Uint256::from(SOURCE_CHANNEL));
+    assert_eq!(token.foreign_token, Bytes::from(b"denom".to_vec()));
+    assert_eq!(wrapped_tokens(&deps, None, None), [predicted]);
+}
+
+const CW721_CODE_ID: u64 = 7;
+
+fn enable_non_fungible_transfers(deps: &mut MockDeps) {
+    CONFIG
+        .update(&mut deps.storage, |config| -> StdResult<_> {
+            Ok(Config {
+                cw721_code_id: Some(CW721_CODE_ID),
+                ..config
+            })
+        })
+        .unwrap();
+}
+
+/// The wrapped collection minted when receiving `base_collection` on [`SOURCE_CHANNEL`].
+fn wrapped_collection(base_collection: &str) -> String {
+    predict_wrapped_denom(
+        alloy::primitives::U256::ZERO,
+        SOURCE_CHANNEL,
+        base_collection.as_bytes().to_vec().into(),
+    )
+}
+
+/// Receive `token_id` of the foreign `base_collection` on [`SOURCE_CHANNEL`].
+fn receive_nft(
+    deps: &mut MockDeps,
+    base_collection: &str,
+    token_id: &str,
+) -> Response<TokenFactoryMsg> {
+    execute_packet(
+        deps,
+        incoming_packet(nft_order(
+            base_collection,
+            0,
+            token_id,
+            wrapped_collection(base_collection).as_bytes(),
+        )),
+    )
+    .unwrap()
+}
+
+/// Reply to the instantiation of a wrapped collection at `address`.
+fn collection_instantiated_reply(
+    deps: &mut MockDeps,
+    address: &str,
+) -> Result<Response<TokenFactoryMsg>, ContractError> {
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: COLLECTION_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![Event::new("instantiate").add_attribute("_contract_address", address)],
+                data: None,
+            }),
+        },
+    )
+}
+
+/// The `(contract, message)` of the cw721 messages executed in `response`.
+fn cw721_msgs(response: &Response<TokenFactoryMsg>) -> Vec<(String, Cw721ExecuteMsg)> {
+    response
+        .messages
+        .iter()
+        .map(|sub_msg| match &sub_msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => (contract_addr.clone(), from_json(msg).unwrap()),
+            msg => panic!("unexpected message: {msg:?}"),
+        })
+        .collect()
+}
+
+/// The `(contract, token_id, owner)` of the mints in `response`.
+fn minted(response: &Response<TokenFactoryMsg>) -> Vec<(String, String, String)> {
+    cw721_msgs(response)
+        .into_iter()
+        .map(|(contract, msg)| match msg {
+            Cw721ExecuteMsg::Mint {
+                token_id, owner, ..
+            } => (contract, token_id, owner),
+            _ => panic!("expected a mint"),
+        })
+        .collect()
+}
+
+fn mint(contract: &str, token_id: &str) -> (String, String, String) {
+    (contract.into(), token_id.into(), "receiver".into())
+}
+
+fn pending_collections(deps: &MockDeps) -> Vec<(String, Vec<String>)> {
+    PENDING_COLLECTIONS
+        .may_load(&deps.storage)
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|pending| {
+            (
+                pending.wrapped_collection,
+                pending
+                    .mints
+                    .into_iter()
+                    .map(|mint| mint.token_id)
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn non_fungible_receive_requires_cw721_code_id() {
+    let mut deps = setup();
+
+    assert_eq!(
+        execute_packet(
+            &mut deps,
+            incoming_packet(nft_order(
+                "collection",
+                0,
+                "1",
+                wrapped_collection("collection").as_bytes(),
+            )),
+        ),
+        Err(ContractError::NonFungibleTransfersDisabled)
+    );
+}
+
+#[test]
+fn non_fungible_receive_instantiates_collection() {
+    let mut deps = setup();
+    enable_non_fungible_transfers(&mut deps);
+
+    let response = receive_nft(&mut deps, "collection", "1");
+
+    let [sub_msg] = &response.messages[..] else {
+        panic!("expected a single message");
+    };
+    assert_eq!(sub_msg.id, COLLECTION_REPLY_ID);
+    assert_eq!(sub_msg.reply_on, ReplyOn::Success);
+    let CosmosMsg::Wasm(WasmMsg::Instantiate {
+        code_id,
+        msg,
+        label,
+        ..
+    }) = &sub_msg.msg
+    else {
+        panic!("expected the collection to be instantiated");
+    };
+    assert_eq!(*code_id, CW721_CODE_ID);
+    assert_eq!(label, &format!("zkgm-{}", wrapped_collection("collection")));
+    let msg = from_json::<Cw721InstantiateMsg>(msg).unwrap();
+    assert_eq!(msg.name, "collection");
+    assert_eq!(msg.minter, mock_env().contract.address.as_str());
+    assert_eq!(
+        pending_collections(&deps),
+        [(wrapped_collection("collection"), vec!["1".to_owned()])]
+    );
+}
+
+#[test]
+fn non_fungible_collection_reply_mints_pending_tokens() {
+    let mut deps = setup();
+    enable_non_fungible_transfers(&mut deps);
+    receive_nft(&mut deps, "collection", "1");
+
+    let response = collection_instantiated_reply(&mut deps, "cw721").unwrap();
+
+    assert_eq!(minted(&response), [mint("cw721", "1")]);
+    assert!(!PENDING_COLLECTIONS.exists(&deps.storage));
+    assert_eq!(
+        WRAPPED_COLLECTION
+            .load(&deps.storage, wrapped_collection("collection"))
+            .unwrap(),
+        Addr::unchecked("cw721")
+    );
+    assert_eq!(
+        COLLECTION_ORIGIN
+            .load(&deps.storage, "cw721".into())
+            .unwrap(),
+        Uint256::from(SOURCE_CHANNEL)
+    );
+    assert_eq!(
+        COLLECTION_TO_FOREIGN
+            .load(&deps.storage, "cw721".into())
+            .unwrap(),
+        Bytes::from(b"collection".to_vec())
+    );
+
+    // Once instantiated, the tokens are minted directly
+    let response = receive_nft(&mut deps, "collection", "2");
+    assert_eq!(minted(&response), [mint("cw721", "2")]);
+}
+
+#[test]
+fn non_fungible_collection_reply_without_address_fails() {
+    let mut deps = setup();
+    enable_non_fungible_transfers(&mut deps);
+    receive_nft(&mut deps, "collection", "1");
+
+    let result = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: COLLECTION_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    );
+
+    assert!(matches!(result, Err(ContractError::Std(_))));
+}
+
+#[test]
+fn non_fungible_pending_collections_are_resolved_in_order() {
+    let mut deps = setup();
+    enable_non_fungible_transfers(&mut deps);
+
+    // Only the first token of each collection instantiates it
+    assert_eq!(
+        receive_nft(&mut deps, "collection-a", "1").messages.len(),
+        1
+    );
+    assert_eq!(
+        receive_nft(&mut deps, "collection-b", "2").messages.len(),
+        1
+    );
+    assert!(receive_nft(&mut deps, "collection-a", "3")
+        .messages
+        .is_empty());
+    assert_eq!(
+        pending_collections(&deps),
+        [
+            (
+                wrapped_collection("collection-a"),
+                vec!["1".to_owned(), "3".to_owned()]
+            ),
+            (wrapped_collection("collection-b"), vec!["2".to_owned()]),
+        ]
+    );
+
+    let response = collection_instantiated_reply(&mut deps, "cw721a").unwrap();
+    assert_eq!(
+        minted(&response),
+        [mint("cw721a", "1"), mint("cw721a", "3")]
+    );
+    assert_eq!(
+        pending_collections(&deps),
+        [(wrapped_collection("collection-b"), vec!["2".to_owned()])]
+    );
+
+    let response = collection_instantiated_reply(&mut deps, "cw721b").unwrap();
+    assert_eq!(minted(&response), [mint("cw721b", "2")]);
+    assert!(!PENDING_COLLECTIONS.exists(&deps.storage));
+    assert_eq!(
+        WRAPPED_COLLECTION
+            .load(&deps.storage, wrapped_collection("collection-a"))
+            .unwrap(),
+        Addr::unchecked("cw721a")
+    );
+    assert_eq!(
+        WRAPPED_COLLECTION
+            .load(&deps.storage, wrapped_collection("collection-b"))
+            .unwrap(),
+        Addr::unchecked("cw721b")
+    );
+}
+
+#[test]
+fn non_fungible_return_trip_unescrows_token() {
+    let mut deps = setup();
+    NFT_ESCROW
+        .save(
+            &mut deps.storage,
+            (SOURCE_CHANNEL, "collection".into(), "1".into()),
+            &Empty {},
+        )
+        .unwrap();
+
+    // The token was escrowed on SOURCE_CHANNEL, whose counterparty is channel 10
+    let response = execute_packet(
+        &mut deps,
+        incoming_packet(nft_order("wrapped", 10, "1", b"collection")),
+    )
+    .unwrap();
+
+    let [(
+        contract,
+        Cw721ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        },
+    )] = &cw721_msgs(&response)[..]
+    else {
+        panic!("expected the token to be transferred");
+    };
+    assert_eq!(contract, "collection");
+    assert_eq!(recipient, "receiver");
+    assert_eq!(token_id, "1");
+    assert!(!NFT_ESCROW.has(
+        &deps.storage,
+        (SOURCE_CHANNEL, "collection".into(), "1".into())
+    ));
+}
+
+#[test]
+fn non_fungible_return_trip_requires_escrowed_token() {
+    let mut deps = setup();
+
+    assert_eq!(
+        execute_packet(
+            &mut deps,
+            incoming_packet(nft_order("wrapped", 10, "1", b"collection")),
+        ),
+        Err(ContractError::TokenNotEscrowed)
+    );
+}
@@ -39,6 +39,10 @@ pub enum ContractError {
     InvalidAmount,
     #[error("transfer require funds to be submitted along the transaction")]
     MissingFunds,
+    #[error(
+        "the funds submitted along the transaction must exactly match the transferred amounts"
+    )]
+    UnexpectedFunds,
     #[error("receiver must be a valid address")]
     InvalidReceiver,
     #[error("receiver must be a valid address")]
@@ -59,6 +63,26 @@ pub enum ContractError {
         "the multiplex target contract address can't be validated, make sure the bech prefix matches the current chain"
     )]
     UnableToValidateMultiplexTarget,
+    #[error("forward path exceeds the maximum number of hops")]
+    InvalidHops,
+    #[error("instruction with opcode {opcode} is not allowed in a batch")]
+    InvalidBatchInstruction { opcode: u8 },
+    #[error("the base token path doesn't match the origin of the token")]
+    InvalidAssetOrigin,
+    #[error("the multiplex sender must be the message sender")]
+    InvalidMultiplexSender,
+    #[error("the base collection must be a valid address")]
+    InvalidBaseCollection,
+    #[error("the quote collection must be a valid address")]
+    InvalidQuoteCollection,
+    #[error("the sender must own the non-fungible token")]
+    NotTokenOwner,
+    #[error("the non-fungible token is not escrowed on this channel")]
+    TokenNotEscrowed,
+    #[error("no cw721 code id configured, non-fungible transfers are disabled")]
+    NonFungibleTransfersDisabled,
+    #[error("the channel {channel_id} can't be converted to a path")]
+    InvalidChannelPath { channel_id: u32 },
     #[error("feature is not yet implemented")]
     Unimplemented,
 }
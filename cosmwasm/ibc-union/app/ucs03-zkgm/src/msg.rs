@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, CosmosMsg, Uint128, Uint256};
+use cosmwasm_std::{Addr, CosmosMsg, Empty, Uint128, Uint256};
 use ibc_union_spec::types::Packet;
 use token_factory_api::TokenFactoryMsg;
 use unionlabs::primitives::{Bytes, H256};
@@ -25,6 +25,25 @@ pub enum ExecuteMsg {
         timeout_timestamp: u64,
         salt: H256,
     },
+    /// Transfer a cw721 token, the contract must be approved to transfer
+    /// `token_id` on `collection` beforehand.
+    TransferNft {
+        channel_id: u32,
+        receiver: Bytes,
+        collection: String,
+        token_id: String,
+        quote_collection: Bytes,
+        timeout_height: u64,
+        timeout_timestamp: u64,
+        salt: H256,
+    },
+    Send {
+        channel_id: u32,
+        timeout_height: u64,
+        timeout_timestamp: u64,
+        salt: H256,
+        instruction: Bytes,
+    },
     BatchExecute {
         msgs: Vec<CosmosMsg<TokenFactoryMsg>>,
     },
@@ -46,5 +65,115 @@ pub enum EurekaMsg {
     },
 }
 
+/// Subset of the cw721 interface used to escrow, mint and burn tokens.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721ExecuteMsg {
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    Mint {
+        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: Option<Empty>,
+    },
+    Burn {
+        token_id: String,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721QueryMsg {
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    ContractInfo {},
+    NftInfo {
+        token_id: String,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Cw721InstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub minter: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Cw721OwnerOfResponse {
+    pub owner: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Cw721ContractInfoResponse {
+    pub name: String,
+    pub symbol: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Cw721NftInfoResponse {
+    pub token_uri: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Amount of `denom` escrowed on `channel_id`, returns [`Uint256`].
+    ChannelBalance { channel_id: u32, denom: String },
+    /// Amounts escrowed on `channel_id`, ordered by denom.
+    ChannelBalances {
+        channel_id: u32,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Origin path and counterparty token of a wrapped denom, returns `None` if the denom
+    /// was not created by this contract.
+    WrappedToken { denom: String },
+    /// Wrapped denoms created by this contract, ordered by denom.
+    WrappedTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The denom that receiving `token` over `channel_id` with the given `path` would mint.
+    PredictWrappedDenom {
+        path: Uint256,
+        channel_id: u32,
+        token: Bytes,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct DenomBalance {
+    pub denom: String,
+    pub balance: Uint256,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ChannelBalancesResponse {
+    pub balances: Vec<DenomBalance>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct WrappedToken {
+    pub denom: String,
+    pub path: Uint256,
+    pub foreign_token: Bytes,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct WrappedTokensResponse {
+    pub tokens: Vec<WrappedToken>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PredictWrappedDenomResponse {
+    pub wrapped_denom: String,
+}
+
 #[cw_serde]
 pub struct MigrateMsg {}
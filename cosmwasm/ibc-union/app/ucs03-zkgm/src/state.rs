@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint256};
+use cosmwasm_std::{Addr, Empty, Uint256};
 use cw_storage_plus::{Item, Map};
 use ibc_union_spec::types::Packet;
 use unionlabs::primitives::Bytes;
@@ -7,6 +7,9 @@ use unionlabs::primitives::Bytes;
 #[cw_serde]
 pub struct Config {
     pub ibc_host: Addr,
+    /// Code of the cw721 contract instantiated for wrapped collections,
+    /// non-fungible transfers can't be received if not set.
+    pub cw721_code_id: Option<u64>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -20,3 +23,32 @@ pub const EXECUTING_PACKET: Item<Packet> = Item::new("executing_packet");
 pub const EXECUTION_ACK: Item<Bytes> = Item::new("execution_ack");
 
 pub const HASH_TO_FOREIGN_TOKEN: Map<String, Bytes> = Map::new("hash_to_foreign_token");
+
+pub const IN_FLIGHT_PACKET: Map<Vec<u8>, Packet> = Map::new("in_flight_packet");
+
+pub const COLLECTION_ORIGIN: Map<String, Uint256> = Map::new("collection_origin");
+
+pub const COLLECTION_TO_FOREIGN: Map<String, Bytes> = Map::new("collection_to_foreign");
+
+pub const WRAPPED_COLLECTION: Map<String, Addr> = Map::new("wrapped_collection");
+
+/// Non-fungible tokens escrowed, keyed by `(channel_id, collection, token_id)`.
+pub const NFT_ESCROW: Map<(u32, String, String), Empty> = Map::new("nft_escrow");
+
+/// Wrapped collections being instantiated, in submessage order.
+pub const PENDING_COLLECTIONS: Item<Vec<PendingCollection>> = Item::new("pending_collections");
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PendingCollection {
+    pub wrapped_collection: String,
+    pub origin: Uint256,
+    pub foreign_collection: Bytes,
+    pub mints: Vec<PendingMint>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PendingMint {
+    pub token_id: String,
+    pub owner: Addr,
+    pub token_uri: Option<String>,
+}
@@ -60,6 +60,10 @@ pub enum IbcClientError<T: IbcClient> {
     InvalidClientMessage(Vec<u8>),
     #[error("caller `{0}` is not a whitelisted relayer")]
     UnauthorizedCaller(String),
+    #[error("client upgrades are not supported by this client")]
+    UpgradeNotSupported,
+    #[error("client recovery is not supported by this client")]
+    RecoveryNotSupported,
 }
 
 impl<T: IbcClient + 'static> From<IbcClientError<T>> for StdError {
@@ -231,6 +235,38 @@ pub trait IbcClient: Sized {
         ctx: IbcClientCtx<Self>,
         misbehaviour: Self::Misbehaviour,
     ) -> Result<Self::ClientState, IbcClientError<Self>>;
+
+    /// Verify that the counterparty chain committed to upgrading to `upgrade_client_state` and
+    /// `upgrade_consensus_state` and return `(upgraded height, upgraded client state, upgraded
+    /// consensus state)`
+    fn verify_upgrade(
+        ctx: IbcClientCtx<Self>,
+        upgrade_client_state: Self::ClientState,
+        upgrade_consensus_state: Self::ConsensusState,
+        proof_upgrade_client: Self::StorageProof,
+        proof_upgrade_consensus_state: Self::StorageProof,
+    ) -> Result<(u64, Self::ClientState, Self::ConsensusState), IbcClientError<Self>> {
+        let _ = (
+            ctx,
+            upgrade_client_state,
+            upgrade_consensus_state,
+            proof_upgrade_client,
+            proof_upgrade_consensus_state,
+        );
+        Err(IbcClientError::UpgradeNotSupported)
+    }
+
+    /// Check that the client `substitute_client_id` can replace this expired or frozen client and
+    /// return `(recovered height, recovered client state, recovered consensus state)`. The host
+    /// already checked that both clients are of the same type, that this client is not active and
+    /// that the substitute is.
+    fn recover(
+        ctx: IbcClientCtx<Self>,
+        substitute_client_id: u32,
+    ) -> Result<(u64, Self::ClientState, Self::ConsensusState), IbcClientError<Self>> {
+        let _ = (ctx, substitute_client_id);
+        Err(IbcClientError::RecoveryNotSupported)
+    }
 }
 
 pub fn instantiate<T: IbcClient>(
@@ -368,6 +404,62 @@ pub fn query<T: IbcClient>(
             )
             .map_err(Into::into)
         }
+        QueryMsg::VerifyUpgrade {
+            client_id,
+            upgrade_client_state,
+            upgrade_consensus_state,
+            proof_upgrade_client,
+            proof_upgrade_consensus_state,
+        } => {
+            let upgrade_client_state =
+                T::ClientState::decode_as::<T::Encoding>(&upgrade_client_state)
+                    .map_err(DecodeError::ClientState)?;
+            let upgrade_consensus_state = T::ConsensusState::decode(&upgrade_consensus_state)
+                .map_err(DecodeError::ConsensusState)?;
+            let proof_upgrade_client =
+                T::StorageProof::decode_as::<T::Encoding>(&proof_upgrade_client)
+                    .map_err(DecodeError::StorageProof)?;
+            let proof_upgrade_consensus_state =
+                T::StorageProof::decode_as::<T::Encoding>(&proof_upgrade_consensus_state)
+                    .map_err(DecodeError::StorageProof)?;
+
+            let ibc_host = IBC_HOST.load(deps.storage)?;
+            let (height, client_state, consensus_state) = T::verify_upgrade(
+                IbcClientCtx::new(client_id, ibc_host, deps, env),
+                upgrade_client_state,
+                upgrade_consensus_state,
+                proof_upgrade_client,
+                proof_upgrade_consensus_state,
+            )?;
+
+            to_json_binary(
+                &(VerifyClientMessageUpdate {
+                    height,
+                    consensus_state: consensus_state.encode().into(),
+                    client_state: client_state.encode_as::<T::Encoding>().into(),
+                }),
+            )
+            .map_err(Into::into)
+        }
+        QueryMsg::Recover {
+            client_id,
+            substitute_client_id,
+        } => {
+            let ibc_host = IBC_HOST.load(deps.storage)?;
+            let (height, client_state, consensus_state) = T::recover(
+                IbcClientCtx::new(client_id, ibc_host, deps, env),
+                substitute_client_id,
+            )?;
+
+            to_json_binary(
+                &(VerifyClientMessageUpdate {
+                    height,
+                    consensus_state: consensus_state.encode().into(),
+                    client_state: client_state.encode_as::<T::Encoding>().into(),
+                }),
+            )
+            .map_err(Into::into)
+        }
     }
 }
 
@@ -1,6 +1,6 @@
 use unionlabs_primitives::Bytes;
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub enum Status {
     Active,
@@ -68,4 +68,15 @@ pub enum QueryMsg {
         client_id: u32,
         message: Bytes,
     },
+    VerifyUpgrade {
+        client_id: u32,
+        upgrade_client_state: Bytes,
+        upgrade_consensus_state: Bytes,
+        proof_upgrade_client: Bytes,
+        proof_upgrade_consensus_state: Bytes,
+    },
+    Recover {
+        client_id: u32,
+        substitute_client_id: u32,
+    },
 }
@@ -1,4 +1,4 @@
-use ibc_union_spec::types::{Channel, Packet};
+use ibc_union_spec::types::{Channel, ChannelOrder, Packet};
 use serde::{Deserialize, Serialize};
 use unionlabs_primitives::Bytes;
 
@@ -19,6 +19,9 @@ pub enum ExecuteMsg {
     RegisterClient(MsgRegisterClient),
     CreateClient(MsgCreateClient),
     UpdateClient(MsgUpdateClient),
+    SubmitMisbehaviour(MsgSubmitMisbehaviour),
+    UpgradeClient(MsgUpgradeClient),
+    RecoverClient(MsgRecoverClient),
     ConnectionOpenInit(MsgConnectionOpenInit),
     ConnectionOpenTry(MsgConnectionOpenTry),
     ConnectionOpenAck(MsgConnectionOpenAck),
@@ -66,6 +69,35 @@ pub struct MsgUpdateClient {
     pub relayer: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MsgSubmitMisbehaviour {
+    pub client_id: u32,
+    pub misbehaviour: Bytes,
+    pub relayer: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MsgUpgradeClient {
+    pub client_id: u32,
+    pub upgrade_client_state: Bytes,
+    pub upgrade_consensus_state: Bytes,
+    pub proof_upgrade_client: Bytes,
+    pub proof_upgrade_consensus_state: Bytes,
+    pub relayer: String,
+}
+
+/// Replace the state of an expired or frozen client (the subject) with the state of a healthy
+/// client (the substitute) tracking the same chain. Only the admin of the contract can recover a
+/// client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MsgRecoverClient {
+    pub subject_client_id: u32,
+    pub substitute_client_id: u32,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct MsgConnectionOpenInit {
@@ -111,6 +143,8 @@ pub struct MsgChannelOpenInit {
     pub counterparty_port_id: Bytes,
     pub connection_id: u32,
     pub version: String,
+    #[serde(default)]
+    pub ordering: ChannelOrder,
     pub relayer: String,
 }
 
@@ -120,6 +154,8 @@ pub struct MsgChannelOpenTry {
     pub port_id: String,
     pub channel: Channel,
     pub counterparty_version: String,
+    #[serde(default)]
+    pub ordering: ChannelOrder,
     pub proof_init: Bytes,
     pub proof_height: u64,
     pub relayer: String,
@@ -14,4 +14,9 @@ pub enum QueryMsg {
     GetChannels { contract: String },
     GetBatchPackets { channel_id: u32, batch_hash: H256 },
     GetBatchReceipts { channel_id: u32, batch_hash: H256 },
+    GetChannelOrder { channel_id: u32 },
+    GetNextSequenceSend { channel_id: u32 },
+    GetNextSequenceRecv { channel_id: u32 },
+    GetNextSequenceAck { channel_id: u32 },
+    GetPacketSequence { channel_id: u32, sequence: u64 },
 }
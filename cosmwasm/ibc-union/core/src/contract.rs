@@ -6,10 +6,11 @@ use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_json_binary, wasm_execute, Addr, Binary, Deps, DepsMut, Env, Event, MessageInfo, Response,
 };
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 use ibc_union_msg::{
     lightclient::{
-        QueryMsg as LightClientQuery, Status, VerifyClientMessageUpdate, VerifyCreationResponse,
+        MisbehaviourResponse, QueryMsg as LightClientQuery, Status, VerifyClientMessageUpdate,
+        VerifyCreationResponse,
     },
     module::{ExecuteMsg as ModuleMsg, IbcUnionMsg},
     msg::{
@@ -17,17 +18,20 @@ use ibc_union_msg::{
         MsgChannelCloseInit, MsgChannelOpenAck, MsgChannelOpenConfirm, MsgChannelOpenInit,
         MsgChannelOpenTry, MsgConnectionOpenAck, MsgConnectionOpenConfirm, MsgConnectionOpenInit,
         MsgConnectionOpenTry, MsgCreateClient, MsgIntentPacketRecv, MsgMigrateState,
-        MsgPacketAcknowledgement, MsgPacketRecv, MsgPacketTimeout, MsgRegisterClient,
-        MsgSendPacket, MsgUpdateClient, MsgWriteAcknowledgement,
+        MsgPacketAcknowledgement, MsgPacketRecv, MsgPacketTimeout, MsgRecoverClient,
+        MsgRegisterClient, MsgSendPacket, MsgSubmitMisbehaviour, MsgUpdateClient, MsgUpgradeClient,
+        MsgWriteAcknowledgement,
     },
     query::QueryMsg,
 };
 use ibc_union_spec::{
     path::{
-        BatchPacketsPath, BatchReceiptsPath, ChannelPath, ClientStatePath, ConnectionPath,
-        ConsensusStatePath, COMMITMENT_MAGIC,
+        commit_channel_order, commit_sequence, BatchPacketsPath, BatchReceiptsPath,
+        ChannelOrderPath, ChannelPath, ClientStatePath, ConnectionPath, ConsensusStatePath,
+        NextSequenceAckPath, NextSequenceRecvPath, NextSequenceSendPath, PacketSequencePath,
+        COMMITMENT_MAGIC,
     },
-    types::{Channel, ChannelState, Connection, ConnectionState, Packet},
+    types::{Channel, ChannelOrder, ChannelState, Connection, ConnectionState, Packet},
 };
 use serde::{Deserialize, Serialize};
 use unionlabs::{
@@ -37,9 +41,10 @@ use unionlabs::{
 
 use crate::{
     state::{
-        CHANNELS, CHANNEL_OWNER, CLIENT_CONSENSUS_STATES, CLIENT_IMPLS, CLIENT_REGISTRY,
-        CLIENT_STATES, CLIENT_TYPES, CONNECTIONS, CONTRACT_CHANNELS, NEXT_CHANNEL_ID,
-        NEXT_CLIENT_ID, NEXT_CONNECTION_ID, QUERY_STORE,
+        CHANNELS, CHANNEL_ORDER, CHANNEL_OWNER, CLIENT_CONSENSUS_STATES, CLIENT_IMPLS,
+        CLIENT_REGISTRY, CLIENT_STATES, CLIENT_TYPES, CONNECTIONS, CONTRACT_CHANNELS,
+        NEXT_CHANNEL_ID, NEXT_CLIENT_ID, NEXT_CONNECTION_ID, NEXT_SEQUENCE_ACK, NEXT_SEQUENCE_RECV,
+        NEXT_SEQUENCE_SEND, PACKET_SEQUENCE, QUERY_STORE,
     },
     ContractError,
 };
@@ -51,6 +56,9 @@ pub mod events {
         pub const REGISTER: &str = "register_client";
         pub const CREATE: &str = "create_client";
         pub const UPDATE: &str = "update_client";
+        pub const MISBEHAVIOUR: &str = "submit_misbehaviour";
+        pub const UPGRADE: &str = "upgrade_client";
+        pub const RECOVER: &str = "recover_client";
     }
     pub mod connection {
         pub const OPEN_INIT: &str = "connection_open_init";
@@ -77,6 +85,7 @@ pub mod events {
     }
     pub mod attribute {
         pub const CLIENT_ID: &str = "client_id";
+        pub const SUBSTITUTE_CLIENT_ID: &str = "substitute_client_id";
         pub const CONNECTION_ID: &str = "connection_id";
         pub const CHANNEL_ID: &str = "channel_id";
         pub const COUNTERPARTY_CHANNEL_ID: &str = "counterparty_channel_id";
@@ -95,6 +104,8 @@ pub mod events {
         pub const PORT_ID: &str = "port_id";
         pub const COUNTERPARTY_PORT_ID: &str = "counterparty_port_id";
         pub const VERSION: &str = "version";
+        pub const ORDERING: &str = "ordering";
+        pub const SEQUENCE: &str = "sequence";
     }
 }
 
@@ -150,6 +161,43 @@ pub fn execute(
             let relayer = deps.api.addr_validate(&relayer)?;
             update_client(deps.branch(), client_id, client_message.to_vec(), relayer)
         }
+        ExecuteMsg::SubmitMisbehaviour(MsgSubmitMisbehaviour {
+            client_id,
+            misbehaviour,
+            relayer,
+        }) => {
+            let relayer = deps.api.addr_validate(&relayer)?;
+            submit_misbehaviour(deps.branch(), client_id, misbehaviour.to_vec(), relayer)
+        }
+        ExecuteMsg::UpgradeClient(MsgUpgradeClient {
+            client_id,
+            upgrade_client_state,
+            upgrade_consensus_state,
+            proof_upgrade_client,
+            proof_upgrade_consensus_state,
+            relayer,
+        }) => {
+            let relayer = deps.api.addr_validate(&relayer)?;
+            upgrade_client(
+                deps.branch(),
+                client_id,
+                upgrade_client_state,
+                upgrade_consensus_state,
+                proof_upgrade_client,
+                proof_upgrade_consensus_state,
+                relayer,
+            )
+        }
+        ExecuteMsg::RecoverClient(MsgRecoverClient {
+            subject_client_id,
+            substitute_client_id,
+        }) => recover_client(
+            deps.branch(),
+            env,
+            info.sender,
+            subject_client_id,
+            substitute_client_id,
+        ),
         ExecuteMsg::ConnectionOpenInit(MsgConnectionOpenInit {
             client_id,
             counterparty_client_id,
@@ -214,6 +262,7 @@ pub fn execute(
             counterparty_port_id,
             connection_id,
             version,
+            ordering,
             relayer,
         }) => {
             let relayer = deps.api.addr_validate(&relayer)?;
@@ -223,6 +272,7 @@ pub fn execute(
                 counterparty_port_id,
                 connection_id,
                 version,
+                ordering,
                 relayer,
             )
         }
@@ -230,6 +280,7 @@ pub fn execute(
             port_id,
             channel,
             counterparty_version,
+            ordering,
             proof_init,
             proof_height,
             relayer,
@@ -240,6 +291,7 @@ pub fn execute(
                 port_id,
                 channel,
                 counterparty_version,
+                ordering,
                 proof_init.to_vec(),
                 proof_height,
                 relayer,
@@ -467,6 +519,9 @@ fn batch_send(deps: DepsMut, source_channel: u32, packets: Vec<Packet>) -> Contr
     if packets.len() < 2 {
         return Err(ContractError::NotEnoughPackets);
     }
+    if channel_order(deps.as_ref(), source_channel)? == ChannelOrder::Ordered {
+        return Err(ContractError::OrderedChannelBatching);
+    }
     for packet in &packets {
         let commitment_key = BatchPacketsPath {
             channel_id: source_channel,
@@ -510,6 +565,9 @@ fn batch_acks(
     if packets.len() < 2 {
         return Err(ContractError::NotEnoughPackets);
     }
+    if channel_order(deps.as_ref(), source_channel)? == ChannelOrder::Ordered {
+        return Err(ContractError::OrderedChannelBatching);
+    }
     for (packet, ack) in packets.iter().zip(acks.iter()) {
         let commitment_key = BatchReceiptsPath {
             channel_id: source_channel,
@@ -566,7 +624,7 @@ fn timeout_packet(
 ) -> ContractResult {
     let source_channel = packet.source_channel_id;
     let destination_channel = packet.destination_channel_id;
-    let channel = ensure_channel_state(deps.as_ref(), source_channel)?;
+    let mut channel = ensure_channel_state(deps.as_ref(), source_channel)?;
     let connection = ensure_connection_state(deps.as_ref(), channel.connection_id)?;
 
     let proof_timestamp =
@@ -575,22 +633,50 @@ fn timeout_packet(
         return Err(ContractError::TimeoutProofTimestampNotFound);
     }
 
-    let commitment_key = BatchReceiptsPath {
-        channel_id: destination_channel,
-        batch_hash: commit_packet(&packet),
-    }
-    .key();
-
     let client_impl = client_impl(deps.as_ref(), connection.client_id)?;
-    deps.querier.query_wasm_smart::<()>(
-        &client_impl,
-        &LightClientQuery::VerifyNonMembership {
-            client_id: connection.client_id,
-            height: proof_height,
-            proof: proof.to_vec().into(),
-            path: commitment_key.into_bytes(),
-        },
-    )?;
+    match channel_order(deps.as_ref(), source_channel)? {
+        ChannelOrder::Unordered => {
+            let commitment_key = BatchReceiptsPath {
+                channel_id: destination_channel,
+                batch_hash: commit_packet(&packet),
+            }
+            .key();
+
+            deps.querier.query_wasm_smart::<()>(
+                &client_impl,
+                &LightClientQuery::VerifyNonMembership {
+                    client_id: connection.client_id,
+                    height: proof_height,
+                    proof: proof.to_vec().into(),
+                    path: commitment_key.into_bytes(),
+                },
+            )?;
+        }
+        // The packet has not been received if the counterparty is still
+        // expecting it. As the following packets can't be received anymore,
+        // the channel is closed.
+        ChannelOrder::Ordered => {
+            let sequence = take_packet_sequence(deps.branch(), source_channel, &packet)?;
+
+            deps.querier.query_wasm_smart::<()>(
+                &client_impl,
+                &LightClientQuery::VerifyMembership {
+                    client_id: connection.client_id,
+                    height: proof_height,
+                    proof: proof.to_vec().into(),
+                    path: NextSequenceRecvPath {
+                        channel_id: destination_channel,
+                    }
+                    .key()
+                    .into_bytes(),
+                    value: commit_sequence(sequence).into_bytes(),
+                },
+            )?;
+
+            channel.state = ChannelState::Closed;
+            save_channel(deps.branch(), source_channel, &channel)?;
+        }
+    }
     delete_packet_commitment(deps.branch(), source_channel, &packet)?;
 
     if packet.timeout_timestamp == 0 && packet.timeout_height == 0 {
@@ -640,6 +726,28 @@ fn acknowledge_packet(
     let channel = ensure_channel_state(deps.as_ref(), source_channel)?;
     let connection = ensure_connection_state(deps.as_ref(), channel.connection_id)?;
 
+    if channel_order(deps.as_ref(), source_channel)? == ChannelOrder::Ordered {
+        if packets.len() != 1 {
+            return Err(ContractError::OrderedChannelBatching);
+        }
+        let sequence = take_packet_sequence(deps.branch(), source_channel, first)?;
+        let expected = increment_sequence(
+            deps.branch(),
+            NEXT_SEQUENCE_ACK,
+            NextSequenceAckPath {
+                channel_id: source_channel,
+            }
+            .key(),
+            source_channel,
+        )?;
+        if sequence != expected {
+            return Err(ContractError::PacketSequenceMismatch {
+                expected,
+                got: sequence,
+            });
+        }
+    }
+
     let (batch_hash, commitment_value) = match packets.len() {
         1 => (commit_packet(first), commit_ack(&acknowledgements[0])),
         _ => (commit_packets(&packets), commit_acks(&acknowledgements)),
@@ -811,6 +919,21 @@ fn update_client(
         QUERY_STORE.remove(deps.storage);
         update
     };
+    let height = update.height;
+    save_client_update(deps.branch(), client_id, update)?;
+    Ok(
+        Response::new().add_event(Event::new(events::client::UPDATE).add_attributes([
+            (events::attribute::CLIENT_ID, client_id.to_string()),
+            (events::attribute::COUNTERPARTY_HEIGHT, height.to_string()),
+        ])),
+    )
+}
+
+fn save_client_update(
+    mut deps: DepsMut,
+    client_id: u32,
+    update: VerifyClientMessageUpdate,
+) -> Result<(), ContractError> {
     CLIENT_STATES.save(
         deps.storage,
         client_id,
@@ -836,13 +959,148 @@ fn update_client(
         .key(),
         &commit(update.consensus_state),
     )?;
+    Ok(())
+}
+
+fn upgrade_client(
+    mut deps: DepsMut,
+    client_id: u32,
+    upgrade_client_state: Bytes,
+    upgrade_consensus_state: Bytes,
+    proof_upgrade_client: Bytes,
+    proof_upgrade_consensus_state: Bytes,
+    relayer: Addr,
+) -> ContractResult {
+    let client_impl = client_impl(deps.as_ref(), client_id)?;
+    let status = deps
+        .querier
+        .query_wasm_smart::<Status>(&client_impl, &LightClientQuery::GetStatus { client_id })?;
+    if status != Status::Active {
+        return Err(ContractError::ClientNotActive { client_id });
+    }
+    // the light client verifies that the counterparty committed to the upgraded states
+    let update = deps.querier.query_wasm_smart::<VerifyClientMessageUpdate>(
+        &client_impl,
+        &LightClientQuery::VerifyUpgrade {
+            client_id,
+            upgrade_client_state,
+            upgrade_consensus_state,
+            proof_upgrade_client,
+            proof_upgrade_consensus_state,
+        },
+    )?;
+    let height = update.height;
+    save_client_update(deps.branch(), client_id, update)?;
     Ok(
-        Response::new().add_event(Event::new(events::client::UPDATE).add_attributes([
+        Response::new().add_event(Event::new(events::client::UPGRADE).add_attributes([
             (events::attribute::CLIENT_ID, client_id.to_string()),
+            (events::attribute::COUNTERPARTY_HEIGHT, height.to_string()),
+            (events::attribute::MAKER, relayer.to_string()),
+        ])),
+    )
+}
+
+fn recover_client(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    subject_client_id: u32,
+    substitute_client_id: u32,
+) -> ContractResult {
+    let admin = deps
+        .querier
+        .query_wasm_contract_info(env.contract.address)?
+        .admin;
+    if admin.as_ref() != Some(&sender) {
+        return Err(ContractError::UnauthorizedRecovery { caller: sender });
+    }
+
+    let subject_client_type = CLIENT_TYPES.load(deps.storage, subject_client_id)?;
+    let substitute_client_type = CLIENT_TYPES.load(deps.storage, substitute_client_id)?;
+    if subject_client_type != substitute_client_type {
+        return Err(ContractError::RecoveryClientTypeMismatch {
+            subject_client_type,
+            substitute_client_type,
+        });
+    }
+
+    let client_impl = client_impl(deps.as_ref(), subject_client_id)?;
+    let subject_status = deps.querier.query_wasm_smart::<Status>(
+        &client_impl,
+        &LightClientQuery::GetStatus {
+            client_id: subject_client_id,
+        },
+    )?;
+    if subject_status == Status::Active {
+        return Err(ContractError::CannotRecoverActiveClient {
+            client_id: subject_client_id,
+        });
+    }
+    let substitute_status = deps.querier.query_wasm_smart::<Status>(
+        &client_impl,
+        &LightClientQuery::GetStatus {
+            client_id: substitute_client_id,
+        },
+    )?;
+    if substitute_status != Status::Active {
+        return Err(ContractError::SubstituteClientNotActive {
+            client_id: substitute_client_id,
+        });
+    }
+
+    let update = deps.querier.query_wasm_smart::<VerifyClientMessageUpdate>(
+        &client_impl,
+        &LightClientQuery::Recover {
+            client_id: subject_client_id,
+            substitute_client_id,
+        },
+    )?;
+    let height = update.height;
+    save_client_update(deps.branch(), subject_client_id, update)?;
+    Ok(
+        Response::new().add_event(Event::new(events::client::RECOVER).add_attributes([
+            (events::attribute::CLIENT_ID, subject_client_id.to_string()),
             (
-                events::attribute::COUNTERPARTY_HEIGHT,
-                update.height.to_string(),
+                events::attribute::SUBSTITUTE_CLIENT_ID,
+                substitute_client_id.to_string(),
             ),
+            (events::attribute::COUNTERPARTY_HEIGHT, height.to_string()),
+        ])),
+    )
+}
+
+fn submit_misbehaviour(
+    mut deps: DepsMut,
+    client_id: u32,
+    misbehaviour: Vec<u8>,
+    relayer: Addr,
+) -> ContractResult {
+    let client_impl = client_impl(deps.as_ref(), client_id)?;
+    let status = deps
+        .querier
+        .query_wasm_smart::<Status>(&client_impl, &LightClientQuery::GetStatus { client_id })?;
+    if status != Status::Active {
+        return Err(ContractError::ClientNotActive { client_id });
+    }
+    // the light client only returns a client state if the misbehaviour is valid, in which case the
+    // returned client state is frozen
+    let MisbehaviourResponse { client_state } = deps.querier.query_wasm_smart(
+        &client_impl,
+        &LightClientQuery::Misbehaviour {
+            client_id,
+            message: misbehaviour.into(),
+        },
+    )?;
+    CLIENT_STATES.save(deps.storage, client_id, &client_state.to_vec().into())?;
+    store_commit(
+        deps.branch(),
+        &ClientStatePath { client_id }.key(),
+        &commit(client_state),
+    )?;
+    Ok(
+        Response::new().add_event(Event::new(events::client::MISBEHAVIOUR).add_attributes([
+            (events::attribute::CLIENT_ID, client_id.to_string()),
+            (events::attribute::MAKER, relayer.to_string()),
         ])),
     )
 }
@@ -1049,6 +1307,7 @@ fn channel_open_init(
     counterparty_port_id: Bytes,
     connection_id: u32,
     version: String,
+    ordering: ChannelOrder,
     relayer: Addr,
 ) -> ContractResult {
     let port_id = deps.api.addr_validate(&port_id)?;
@@ -1061,6 +1320,7 @@ fn channel_open_init(
         0,
         counterparty_port_id.clone(),
         version.clone(),
+        ordering,
     )?;
     Ok(Response::new()
         .add_event(Event::new(events::channel::OPEN_INIT).add_attributes([
@@ -1072,6 +1332,7 @@ fn channel_open_init(
             ),
             (events::attribute::CONNECTION_ID, connection_id.to_string()),
             (events::attribute::VERSION, version.clone()),
+            (events::attribute::ORDERING, (ordering as u8).to_string()),
         ]))
         .add_message(wasm_execute(
             port_id,
@@ -1090,6 +1351,7 @@ fn channel_open_try(
     port_id: String,
     channel: Channel,
     counterparty_version: String,
+    ordering: ChannelOrder,
     proof_init: Vec<u8>,
     proof_height: u64,
     relayer: Addr,
@@ -1120,7 +1382,7 @@ fn channel_open_try(
             }
             .key()
             .into_bytes(),
-            value: commit(expected_channel.abi_encode()).into_bytes(),
+            value: commit_channel(&expected_channel, ordering).into_bytes(),
         },
     )?;
     let port_id = deps.api.addr_validate(&port_id)?;
@@ -1132,6 +1394,7 @@ fn channel_open_try(
         channel.counterparty_channel_id,
         channel.counterparty_port_id,
         channel.version,
+        ordering,
     )?;
     Ok(Response::new()
         .add_event(Event::new(events::channel::OPEN_TRY).add_attributes([
@@ -1147,6 +1410,7 @@ fn channel_open_try(
             ),
             ("connection_id", channel.connection_id.to_string()),
             ("counterparty_version", counterparty_version.clone()),
+            (events::attribute::ORDERING, (ordering as u8).to_string()),
         ]))
         .add_message(wasm_execute(
             port_id,
@@ -1198,7 +1462,8 @@ fn channel_open_ack(
             }
             .key()
             .into_bytes(),
-            value: commit(expected_channel.abi_encode()).into_bytes(),
+            value: commit_channel(&expected_channel, channel_order(deps.as_ref(), channel_id)?)
+                .into_bytes(),
         },
     )?;
     channel.state = ChannelState::Open;
@@ -1266,7 +1531,8 @@ fn channel_open_confirm(
             }
             .key()
             .into_bytes(),
-            value: commit(expected_channel.abi_encode()).into_bytes(),
+            value: commit_channel(&expected_channel, channel_order(deps.as_ref(), channel_id)?)
+                .into_bytes(),
         },
     )?;
     channel.state = ChannelState::Open;
@@ -1365,16 +1631,12 @@ fn channel_close_confirm(
             }
             .key()
             .into_bytes(),
-            value: commit(expected_channel.abi_encode()).into_bytes(),
+            value: commit_channel(&expected_channel, channel_order(deps.as_ref(), channel_id)?)
+                .into_bytes(),
         },
     )?;
     channel.state = ChannelState::Closed;
-    CHANNELS.save(deps.storage, channel_id, &channel)?;
-    store_commit(
-        deps.branch(),
-        &ChannelPath { channel_id }.key(),
-        &commit(channel.abi_encode()),
-    )?;
+    save_channel(deps.branch(), channel_id, &channel)?;
     Ok(Response::new()
         .add_event(Event::new(events::channel::CLOSE_CONFIRM).add_attributes([
             (events::attribute::PORT_ID, port_id.to_string()),
@@ -1390,7 +1652,7 @@ fn channel_close_confirm(
         ]))
         .add_message(wasm_execute(
             port_id,
-            &ModuleMsg::IbcUnionMsg(IbcUnionMsg::OnChannelOpenConfirm {
+            &ModuleMsg::IbcUnionMsg(IbcUnionMsg::OnChannelCloseConfirm {
                 channel_id,
                 relayer: relayer.into(),
             }),
@@ -1416,15 +1678,47 @@ fn process_receive(
     let channel = ensure_channel_state(deps.as_ref(), destination_channel)?;
     let connection = ensure_connection_state(deps.as_ref(), channel.connection_id)?;
 
-    if !intent {
-        let proof_commitment_key = BatchPacketsPath {
-            channel_id: source_channel,
-            batch_hash: match packets.len() {
-                1 => commit_packet(first),
-                _ => commit_packets(&packets),
-            },
+    let ordering = channel_order(deps.as_ref(), destination_channel)?;
+    if ordering == ChannelOrder::Ordered {
+        if intent {
+            return Err(ContractError::OrderedChannelIntent);
         }
-        .key();
+        if packets.len() != 1 {
+            return Err(ContractError::OrderedChannelBatching);
+        }
+    }
+
+    if !intent {
+        let (proof_commitment_key, proof_commitment_value) = match ordering {
+            ChannelOrder::Unordered => (
+                BatchPacketsPath {
+                    channel_id: source_channel,
+                    batch_hash: match packets.len() {
+                        1 => commit_packet(first),
+                        _ => commit_packets(&packets),
+                    },
+                }
+                .key(),
+                COMMITMENT_MAGIC,
+            ),
+            // The packet must have been sent with the sequence we expect next.
+            ChannelOrder::Ordered => (
+                PacketSequencePath {
+                    channel_id: source_channel,
+                    sequence: increment_sequence(
+                        deps.branch(),
+                        NEXT_SEQUENCE_RECV,
+                        NextSequenceRecvPath {
+                            channel_id: destination_channel,
+                        }
+                        .key(),
+                        destination_channel,
+                    )?,
+                }
+                .key(),
+                commit_packet(first),
+            ),
+        };
 
         let client_impl = client_impl(deps.as_ref(), connection.client_id)?;
         deps.querier.query_wasm_smart::<()>(
@@ -1434,7 +1728,7 @@ fn process_receive(
                 height: proof_height,
                 proof: proof.to_vec().into(),
                 path: proof_commitment_key.into_bytes(),
-                value: COMMITMENT_MAGIC.into_bytes(),
+                value: proof_commitment_value.into_bytes(),
             },
         )?;
     }
@@ -1615,11 +1909,41 @@ fn send_packet(
 
     store_commit(deps.branch(), &commitment_key, &COMMITMENT_MAGIC)?;
 
+    let mut event = Event::new(events::packet::SEND).add_attribute(
+        events::attribute::PACKET,
+        serde_json::to_string(&packet).expect("packet serialization is infallible; qed;"),
+    );
+
+    if channel_order(deps.as_ref(), source_channel_id)? == ChannelOrder::Ordered {
+        let sequence = increment_sequence(
+            deps.branch(),
+            NEXT_SEQUENCE_SEND,
+            NextSequenceSendPath {
+                channel_id: source_channel_id,
+            }
+            .key(),
+            source_channel_id,
+        )?;
+        let packet_hash = commit_packet(&packet);
+        store_commit(
+            deps.branch(),
+            &PacketSequencePath {
+                channel_id: source_channel_id,
+                sequence,
+            }
+            .key(),
+            &packet_hash,
+        )?;
+        PACKET_SEQUENCE.save(
+            deps.storage,
+            (source_channel_id, packet_hash.into_bytes().into_vec()),
+            &sequence,
+        )?;
+        event = event.add_attribute(events::attribute::SEQUENCE, sequence.to_string());
+    }
+
     Ok(Response::new()
-        .add_event(Event::new(events::packet::SEND).add_attribute(
-            events::attribute::PACKET,
-            serde_json::to_string(&packet).expect("packet serialization is infallible; qed;"),
-        ))
+        .add_event(event)
         .set_data(to_json_binary(&packet)?))
 }
 
@@ -1696,8 +2020,10 @@ fn create_channel(
     counterparty_channel_id: u32,
     counterparty_port_id: Bytes,
     version: String,
+    ordering: ChannelOrder,
 ) -> Result<(u32, Channel), ContractError> {
     let channel_id = next_channel_id(deps.branch())?;
+    save_channel_order(deps.branch(), channel_id, ordering)?;
     let channel = Channel {
         state,
         connection_id,
@@ -1722,14 +2048,117 @@ fn create_channel(
 
 fn save_channel(deps: DepsMut, channel_id: u32, channel: &Channel) -> Result<(), ContractError> {
     CHANNELS.save(deps.storage, channel_id, channel)?;
+    let ordering = channel_order(deps.as_ref(), channel_id)?;
     store_commit(
         deps,
         &ChannelPath { channel_id }.key(),
-        &commit(channel.abi_encode()),
+        &commit_channel(channel, ordering),
     )?;
     Ok(())
 }
 
+/// The commitment of unordered channels is kept identical to the one of
+/// implementations unaware of ordering, the ordering is only committed along
+/// with ordered channels.
+fn commit_channel(channel: &Channel, ordering: ChannelOrder) -> H256 {
+    match ordering {
+        ChannelOrder::Unordered => commit(channel.abi_encode()),
+        ChannelOrder::Ordered => commit((channel.clone(), ordering as u8).abi_encode()),
+    }
+}
+
+fn channel_order(deps: Deps, channel_id: u32) -> Result<ChannelOrder, ContractError> {
+    Ok(CHANNEL_ORDER
+        .may_load(deps.storage, channel_id)?
+        .unwrap_or_default())
+}
+
+fn save_channel_order(
+    mut deps: DepsMut,
+    channel_id: u32,
+    ordering: ChannelOrder,
+) -> Result<(), ContractError> {
+    CHANNEL_ORDER.save(deps.storage, channel_id, &ordering)?;
+    store_commit(
+        deps.branch(),
+        &ChannelOrderPath { channel_id }.key(),
+        &commit_channel_order(ordering),
+    )?;
+    if ordering == ChannelOrder::Ordered {
+        set_next_sequence(
+            deps.branch(),
+            NEXT_SEQUENCE_SEND,
+            NextSequenceSendPath { channel_id }.key(),
+            channel_id,
+            1,
+        )?;
+        set_next_sequence(
+            deps.branch(),
+            NEXT_SEQUENCE_RECV,
+            NextSequenceRecvPath { channel_id }.key(),
+            channel_id,
+            1,
+        )?;
+        set_next_sequence(
+            deps,
+            NEXT_SEQUENCE_ACK,
+            NextSequenceAckPath { channel_id }.key(),
+            channel_id,
+            1,
+        )?;
+    }
+    Ok(())
+}
+
+fn set_next_sequence(
+    deps: DepsMut,
+    sequences: Map<u32, u64>,
+    key: H256,
+    channel_id: u32,
+    sequence: u64,
+) -> Result<(), ContractError> {
+    sequences.save(deps.storage, channel_id, &sequence)?;
+    store_commit(deps, &key, &commit_sequence(sequence))
+}
+
+/// Increment the sequence of an ordered channel, returning the current one.
+fn increment_sequence(
+    deps: DepsMut,
+    sequences: Map<u32, u64>,
+    key: H256,
+    channel_id: u32,
+) -> Result<u64, ContractError> {
+    let sequence = sequences.load(deps.storage, channel_id)?;
+    let next_sequence = sequence
+        .checked_add(1)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    set_next_sequence(deps, sequences, key, channel_id, next_sequence)?;
+    Ok(sequence)
+}
+
+/// Remove and return the sequence a packet was sent with on an ordered channel, along with the
+/// commitment of the packet under its [`PacketSequencePath`].
+fn take_packet_sequence(
+    deps: DepsMut,
+    channel_id: u32,
+    packet: &Packet,
+) -> Result<u64, ContractError> {
+    let key = (channel_id, commit_packet(packet).into_bytes().into_vec());
+    let sequence = PACKET_SEQUENCE
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PacketSequenceNotFound)?;
+    PACKET_SEQUENCE.remove(deps.storage, key);
+    deps.storage.remove(
+        PacketSequencePath {
+            channel_id,
+            sequence,
+        }
+        .key()
+        .as_ref(),
+    );
+    Ok(sequence)
+}
+
 fn ensure_connection_state(deps: Deps, connection_id: u32) -> Result<Connection, ContractError> {
     let connection = CONNECTIONS.load(deps.storage, connection_id)?;
     if connection.state != ConnectionState::Open {
@@ -1848,6 +2277,33 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
             );
             Ok(to_json_binary(&commit)?)
         }
+        QueryMsg::GetChannelOrder { channel_id } => {
+            CHANNELS.load(deps.storage, channel_id)?;
+            Ok(to_json_binary(&channel_order(deps, channel_id)?)?)
+        }
+        QueryMsg::GetNextSequenceSend { channel_id } => Ok(to_json_binary(
+            &NEXT_SEQUENCE_SEND.load(deps.storage, channel_id)?,
+        )?),
+        QueryMsg::GetNextSequenceRecv { channel_id } => Ok(to_json_binary(
+            &NEXT_SEQUENCE_RECV.load(deps.storage, channel_id)?,
+        )?),
+        QueryMsg::GetNextSequenceAck { channel_id } => Ok(to_json_binary(
+            &NEXT_SEQUENCE_ACK.load(deps.storage, channel_id)?,
+        )?),
+        QueryMsg::GetPacketSequence {
+            channel_id,
+            sequence,
+        } => {
+            let commit = read_commit(
+                deps,
+                &PacketSequencePath {
+                    channel_id,
+                    sequence,
+                }
+                .key(),
+            );
+            Ok(to_json_binary(&commit)?)
+        }
     }
 }
 
@@ -118,6 +118,52 @@ pub enum ContractError {
         "{} cannot migrate the client {client_id} when there's no consensus state at height {height}", ContractErrorKind::from(self)
     )]
     CannotMigrateWithNoConsensusState { client_id: u32, height: u64 },
+    #[error(
+        "{} packets can't be batched on an ordered channel",
+        ContractErrorKind::from(self)
+    )]
+    OrderedChannelBatching,
+    #[error(
+        "{} intents are not supported on an ordered channel",
+        ContractErrorKind::from(self)
+    )]
+    OrderedChannelIntent,
+    #[error(
+        "{} packet sequence not found, the channel is unordered or the packet was not sent",
+        ContractErrorKind::from(self)
+    )]
+    PacketSequenceNotFound,
+    #[error(
+        "{} packets must be processed in order: expected sequence {expected}, got {got}",
+        ContractErrorKind::from(self)
+    )]
+    PacketSequenceMismatch { expected: u64, got: u64 },
+    #[error("{} client ({client_id}) is not active", ContractErrorKind::from(self))]
+    ClientNotActive { client_id: u32 },
+    #[error(
+        "{} caller ({caller}) is not the admin of the contract and can't recover clients",
+        ContractErrorKind::from(self)
+    )]
+    UnauthorizedRecovery { caller: Addr },
+    #[error(
+        "{} the subject client type ({subject_client_type}) doesn't match \
+        the substitute client type ({substitute_client_type})",
+        ContractErrorKind::from(self)
+    )]
+    RecoveryClientTypeMismatch {
+        subject_client_type: String,
+        substitute_client_type: String,
+    },
+    #[error(
+        "{} client ({client_id}) is active and can't be recovered",
+        ContractErrorKind::from(self)
+    )]
+    CannotRecoverActiveClient { client_id: u32 },
+    #[error(
+        "{} substitute client ({client_id}) must be active",
+        ContractErrorKind::from(self)
+    )]
+    SubstituteClientNotActive { client_id: u32 },
 }
 
 impl ContractErrorKind {
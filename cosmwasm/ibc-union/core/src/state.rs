@@ -2,7 +2,7 @@ use std::collections::BTreeSet;
 
 use cosmwasm_std::{Addr, Binary};
 use cw_storage_plus::{Item, Map};
-use ibc_union_spec::types::{Channel, Connection};
+use ibc_union_spec::types::{Channel, ChannelOrder, Connection};
 
 pub const QUERY_STORE: Item<Binary> = Item::new("query_store");
 
@@ -10,6 +10,19 @@ pub const CHANNEL_OWNER: Map<u32, Addr> = Map::new("channel_owner");
 
 pub const CHANNELS: Map<u32, Channel> = Map::new("channels");
 
+// Channels without an entry are unordered
+pub const CHANNEL_ORDER: Map<u32, ChannelOrder> = Map::new("channel_order");
+
+// Sequence tracking, only for ordered channels
+pub const NEXT_SEQUENCE_SEND: Map<u32, u64> = Map::new("next_sequence_send");
+
+pub const NEXT_SEQUENCE_RECV: Map<u32, u64> = Map::new("next_sequence_recv");
+
+pub const NEXT_SEQUENCE_ACK: Map<u32, u64> = Map::new("next_sequence_ack");
+
+// From (channel id, packet hash) to the sequence of a packet sent on an ordered channel
+pub const PACKET_SEQUENCE: Map<(u32, Vec<u8>), u64> = Map::new("packet_sequence");
+
 pub const CONTRACT_CHANNELS: Map<Addr, BTreeSet<u32>> = Map::new("contract_channels");
 
 pub const CONNECTIONS: Map<u32, Connection> = Map::new("connections");
@@ -11,6 +11,7 @@ use ibc_union_msg::{
         MsgConnectionOpenTry, MsgCreateClient, MsgRegisterClient,
     },
 };
+use ibc_union_spec::types::ChannelOrder;
 
 use super::*;
 
@@ -123,6 +124,7 @@ fn channel_open_init(deps: DepsMut) -> Result<Response, ContractError> {
         counterparty_port_id: vec![1].into(),
         connection_id: 1,
         version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         relayer: mock_addr(RELAYER).to_string(),
     };
     execute(
@@ -1,12 +1,15 @@
-use contract::instantiate;
-use cosmwasm_std::{testing::mock_dependencies, to_json_binary};
+use contract::{instantiate, query};
+use cosmwasm_std::{testing::mock_dependencies, to_json_binary, CosmosMsg, SubMsg, WasmMsg};
 use ibc_union_msg::{
     lightclient::VerifyCreationResponse,
+    module::{ExecuteMsg as ModuleMsg, IbcUnionMsg},
     msg::{
-        InitMsg, MsgChannelOpenAck, MsgChannelOpenConfirm, MsgChannelOpenInit, MsgChannelOpenTry,
+        InitMsg, MsgChannelCloseConfirm, MsgChannelOpenAck, MsgChannelOpenConfirm,
+        MsgChannelOpenInit, MsgChannelOpenTry,
     },
+    query::QueryMsg,
 };
-use ibc_union_spec::types::Channel;
+use ibc_union_spec::types::{Channel, ChannelOrder};
 
 use super::*;
 
@@ -44,6 +47,7 @@ fn channel_open_init_ok() {
         counterparty_port_id: vec![1].into(),
         connection_id: 1,
         version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         relayer: mock_addr(RELAYER).to_string(),
     };
     assert!(execute(
@@ -55,6 +59,68 @@ fn channel_open_init_ok() {
     .is_ok());
 }
 
+#[test]
+fn channel_open_init_ordered_ok() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        InitMsg {},
+    )
+    .unwrap();
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::VerifyMembership { .. } => to_json_binary(&()),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create client ok");
+
+    connection_open_try(deps.as_mut()).expect("connection open try is ok");
+    connection_open_confirm(deps.as_mut()).expect("connection open confirm is ok");
+
+    let msg = MsgChannelOpenInit {
+        port_id: mock_addr(SENDER).to_string(),
+        counterparty_port_id: vec![1].into(),
+        connection_id: 1,
+        version: VERSION.to_owned(),
+        ordering: ChannelOrder::Ordered,
+        relayer: mock_addr(RELAYER).to_string(),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ChannelOpenInit(msg),
+    )
+    .expect("channel open init is ok");
+
+    let ordering: ChannelOrder = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetChannelOrder { channel_id: 1 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(ordering, ChannelOrder::Ordered);
+
+    for msg in [
+        QueryMsg::GetNextSequenceSend { channel_id: 1 },
+        QueryMsg::GetNextSequenceRecv { channel_id: 1 },
+        QueryMsg::GetNextSequenceAck { channel_id: 1 },
+    ] {
+        let sequence: u64 = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(sequence, 1);
+    }
+}
+
 #[test]
 fn channel_open_init_channel_claimed() {
     let mut deps = mock_dependencies();
@@ -160,6 +226,7 @@ fn channel_open_try_ok() {
             version: VERSION.to_owned(),
         },
         counterparty_version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         proof_init: vec![1, 2, 3].into(),
         proof_height: 1,
         relayer: mock_addr(RELAYER).into_string(),
@@ -208,6 +275,7 @@ fn channel_open_try_invalid_state() {
             version: VERSION.to_owned(),
         },
         counterparty_version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         proof_init: vec![1, 2, 3].into(),
         proof_height: 1,
         relayer: mock_addr(RELAYER).into_string(),
@@ -265,6 +333,7 @@ fn channel_open_try_channel_claimed() {
             version: VERSION.to_owned(),
         },
         counterparty_version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         proof_init: vec![1, 2, 3].into(),
         proof_height: 1,
         relayer: mock_addr(RELAYER).into_string(),
@@ -318,6 +387,7 @@ fn channel_open_try_commitment_saved() {
             version: VERSION.to_owned(),
         },
         counterparty_version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         proof_init: vec![1, 2, 3].into(),
         proof_height: 1,
         relayer: mock_addr(RELAYER).into_string(),
@@ -372,6 +442,7 @@ fn channel_open_ack_ok() {
         counterparty_port_id: vec![1].into(),
         connection_id: 1,
         version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         relayer: mock_addr(RELAYER).to_string(),
     };
     execute(
@@ -480,6 +551,7 @@ fn channel_open_ack_commitment_saved() {
         counterparty_port_id: vec![1].into(),
         connection_id: 1,
         version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         relayer: mock_addr(RELAYER).to_string(),
     };
     execute(
@@ -554,6 +626,7 @@ fn channel_open_confirm_ok() {
             version: VERSION.to_owned(),
         },
         counterparty_version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         proof_init: vec![1, 2, 3].into(),
         proof_height: 1,
         relayer: mock_addr(RELAYER).into_string(),
@@ -663,6 +736,7 @@ fn channel_open_confirm_commitment_saved() {
             version: VERSION.to_owned(),
         },
         counterparty_version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
         proof_init: vec![1, 2, 3].into(),
         proof_height: 1,
         relayer: mock_addr(RELAYER).into_string(),
@@ -700,3 +774,153 @@ fn channel_open_confirm_commitment_saved() {
         }
     );
 }
+
+/// Open channel 1 through the try and confirm handshake steps.
+fn channel_open_try_confirm(mut deps: DepsMut) {
+    let msg = MsgChannelOpenTry {
+        port_id: mock_addr(SENDER).into_string(),
+        channel: Channel {
+            state: ChannelState::TryOpen,
+            connection_id: 1,
+            counterparty_channel_id: 0,
+            counterparty_port_id: vec![1].into(),
+            version: VERSION.to_owned(),
+        },
+        counterparty_version: VERSION.to_owned(),
+        ordering: ChannelOrder::Unordered,
+        proof_init: vec![1, 2, 3].into(),
+        proof_height: 1,
+        relayer: mock_addr(RELAYER).into_string(),
+    };
+    execute(
+        deps.branch(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ChannelOpenTry(msg),
+    )
+    .expect("channel open try is ok");
+
+    let msg = MsgChannelOpenConfirm {
+        channel_id: 1,
+        proof_ack: vec![1, 2, 3].into(),
+        proof_height: 1,
+        relayer: mock_addr(RELAYER).to_string(),
+    };
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ChannelOpenConfirm(msg),
+    )
+    .expect("channel open confirm is ok");
+}
+
+fn channel_close_confirm(deps: DepsMut) -> Result<Response, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ChannelCloseConfirm(MsgChannelCloseConfirm {
+            channel_id: 1,
+            proof_init: vec![1, 2, 3].into(),
+            proof_height: 1,
+            relayer: mock_addr(RELAYER).to_string(),
+        }),
+    )
+}
+
+#[test]
+fn channel_close_confirm_ok() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        InitMsg {},
+    )
+    .unwrap();
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::VerifyMembership { .. } => to_json_binary(&()),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create client ok");
+
+    connection_open_try(deps.as_mut()).expect("connection open try is ok");
+    connection_open_confirm(deps.as_mut()).expect("connection open confirm is ok");
+
+    channel_open_try_confirm(deps.as_mut());
+
+    let res = channel_close_confirm(deps.as_mut()).expect("channel close confirm is ok");
+
+    let [SubMsg {
+        msg: CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }),
+        ..
+    }] = &res.messages[..]
+    else {
+        panic!("expected a single message to the channel owner");
+    };
+    assert_eq!(contract_addr, mock_addr(SENDER).as_str());
+    assert!(matches!(
+        from_json(msg).unwrap(),
+        ModuleMsg::IbcUnionMsg(IbcUnionMsg::OnChannelCloseConfirm {
+            channel_id: 1,
+            relayer,
+        }) if relayer == mock_addr(RELAYER).as_str()
+    ));
+
+    assert_eq!(
+        crate::state::CHANNELS.load(&deps.storage, 1).unwrap(),
+        Channel {
+            state: ChannelState::Closed,
+            connection_id: 1,
+            counterparty_channel_id: 0,
+            counterparty_port_id: vec![1].into(),
+            version: VERSION.to_owned()
+        }
+    );
+}
+
+#[test]
+fn channel_close_confirm_invalid_state() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        InitMsg {},
+    )
+    .unwrap();
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::VerifyMembership { .. } => to_json_binary(&()),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create client ok");
+
+    connection_open_try(deps.as_mut()).expect("connection open try is ok");
+    connection_open_confirm(deps.as_mut()).expect("connection open confirm is ok");
+
+    channel_open_try_confirm(deps.as_mut());
+    channel_close_confirm(deps.as_mut()).expect("channel close confirm is ok");
+
+    assert_eq!(
+        channel_close_confirm(deps.as_mut()),
+        Err(ContractError::ChannelInvalidState {
+            got: ChannelState::Closed,
+            expected: ChannelState::Open,
+        })
+    );
+}
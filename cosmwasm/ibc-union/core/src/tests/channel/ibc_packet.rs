@@ -1 +1,378 @@
+use alloy::sol_types::SolValue;
+use contract::{instantiate, query};
+use cosmwasm_std::{
+    testing::{mock_dependencies, MockQuerier, MockStorage},
+    to_json_binary, Deps, OwnedDeps, StdError,
+};
+use ibc_union_msg::{
+    lightclient::VerifyCreationResponse,
+    msg::{
+        InitMsg, MsgChannelOpenAck, MsgChannelOpenConfirm, MsgChannelOpenTry, MsgPacketRecv,
+        MsgPacketTimeout, MsgSendPacket,
+    },
+    query::QueryMsg,
+};
+use ibc_union_spec::{
+    path::{commit_sequence, NextSequenceRecvPath, PacketSequencePath},
+    types::{Channel, Packet},
+};
+use unionlabs::{ethereum::keccak256, primitives::H256};
 
+use super::*;
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+/// The channel on the counterparty chain.
+const COUNTERPARTY_CHANNEL_ID: u32 = 2;
+
+/// Like [`wasm_query_handler`], but returns the errors of `querier` to the contract instead of
+/// panicking, such that failed proof verifications can be tested.
+fn fallible_wasm_query_handler<F: Fn(LightClientQueryMsg) -> StdResult<Binary> + 'static>(
+    querier: F,
+) -> impl Fn(&WasmQuery) -> QuerierResult + 'static {
+    move |msg| match msg {
+        WasmQuery::Smart { msg, .. } => {
+            let msg: LightClientQueryMsg = from_json(msg).unwrap();
+            QuerierResult::Ok(match querier(msg) {
+                Ok(res) => cosmwasm_std::ContractResult::Ok(res),
+                Err(err) => cosmwasm_std::ContractResult::Err(err.to_string()),
+            })
+        }
+        _ => panic!("Only smart queries should be possible now. Adjust this based on your needs."),
+    }
+}
+
+fn setup(deps: DepsMut) {
+    instantiate(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        InitMsg {},
+    )
+    .unwrap();
+}
+
+fn open_connection(deps: &mut MockDeps) {
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::VerifyMembership { .. } => to_json_binary(&()),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create client ok");
+
+    connection_open_try(deps.as_mut()).expect("connection open try is ok");
+    connection_open_confirm(deps.as_mut()).expect("connection open confirm is ok");
+}
+
+/// Open channel 1 as the receiving end of an ordered channel.
+fn ordered_channel_open_try(deps: DepsMut) {
+    let msg = MsgChannelOpenTry {
+        port_id: mock_addr(SENDER).into_string(),
+        channel: Channel {
+            state: ChannelState::TryOpen,
+            connection_id: 1,
+            counterparty_channel_id: COUNTERPARTY_CHANNEL_ID,
+            counterparty_port_id: vec![1].into(),
+            version: VERSION.to_owned(),
+        },
+        counterparty_version: VERSION.to_owned(),
+        ordering: ChannelOrder::Ordered,
+        proof_init: vec![1, 2, 3].into(),
+        proof_height: 1,
+        relayer: mock_addr(RELAYER).into_string(),
+    };
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ChannelOpenTry(msg),
+    )
+    .expect("channel open try is ok");
+}
+
+fn ordered_channel_open_confirm(deps: DepsMut) {
+    let msg = MsgChannelOpenConfirm {
+        channel_id: 1,
+        proof_ack: vec![1, 2, 3].into(),
+        proof_height: 1,
+        relayer: mock_addr(RELAYER).to_string(),
+    };
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ChannelOpenConfirm(msg),
+    )
+    .expect("channel open confirm is ok");
+}
+
+/// Open channel 1 as the sending end of an ordered channel.
+fn ordered_channel_open_init_ack(mut deps: DepsMut) {
+    let msg = MsgChannelOpenInit {
+        port_id: mock_addr(SENDER).to_string(),
+        counterparty_port_id: vec![1].into(),
+        connection_id: 1,
+        version: VERSION.to_owned(),
+        ordering: ChannelOrder::Ordered,
+        relayer: mock_addr(RELAYER).to_string(),
+    };
+    execute(
+        deps.branch(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ChannelOpenInit(msg),
+    )
+    .expect("channel open init is ok");
+
+    let msg = MsgChannelOpenAck {
+        channel_id: 1,
+        counterparty_version: VERSION.to_owned(),
+        counterparty_channel_id: COUNTERPARTY_CHANNEL_ID,
+        proof_try: vec![1, 2, 3].into(),
+        proof_height: 1,
+        relayer: mock_addr(RELAYER).to_string(),
+    };
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ChannelOpenAck(msg),
+    )
+    .expect("channel open ack is ok");
+}
+
+/// A packet sent from the counterparty channel to channel 1.
+fn incoming_packet(data: u8) -> Packet {
+    Packet {
+        source_channel_id: COUNTERPARTY_CHANNEL_ID,
+        destination_channel_id: 1,
+        data: vec![data].into(),
+        timeout_height: 0,
+        timeout_timestamp: u64::MAX,
+    }
+}
+
+fn commit_packet(packet: &Packet) -> H256 {
+    keccak256(packet.abi_encode())
+}
+
+/// Only verify the membership of the packets in `sent`, committed by the counterparty under
+/// their sequence (starting at 1).
+fn verify_packet_sequences(deps: &mut MockDeps, sent: Vec<Packet>) {
+    deps.querier
+        .update_wasm(fallible_wasm_query_handler(move |msg| match msg {
+            LightClientQueryMsg::VerifyMembership { path, value, .. } => {
+                let committed = sent.iter().zip(1..).any(|(packet, sequence)| {
+                    path == PacketSequencePath {
+                        channel_id: COUNTERPARTY_CHANNEL_ID,
+                        sequence,
+                    }
+                    .key()
+                    .into_bytes()
+                        && value == commit_packet(packet).into_bytes()
+                });
+
+                if committed {
+                    to_json_binary(&())
+                } else {
+                    Err(StdError::generic_err("invalid membership proof"))
+                }
+            }
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+}
+
+fn recv(deps: DepsMut, packets: Vec<Packet>) -> Result<Response, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(RELAYER), &[]),
+        ExecuteMsg::PacketRecv(MsgPacketRecv {
+            relayer_msgs: vec![vec![].into(); packets.len()],
+            packets,
+            relayer: mock_addr(RELAYER).into_string(),
+            proof: vec![1, 2, 3].into(),
+            proof_height: 1,
+        }),
+    )
+}
+
+#[test]
+fn packet_recv_ordered_in_sequence_ok() {
+    let mut deps = mock_dependencies();
+    setup(deps.as_mut());
+    open_connection(&mut deps);
+    ordered_channel_open_try(deps.as_mut());
+    ordered_channel_open_confirm(deps.as_mut());
+
+    verify_packet_sequences(&mut deps, vec![incoming_packet(1), incoming_packet(2)]);
+
+    recv(deps.as_mut(), vec![incoming_packet(1)]).expect("first packet is received");
+    recv(deps.as_mut(), vec![incoming_packet(2)]).expect("second packet is received");
+
+    let next_sequence_recv: u64 = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetNextSequenceRecv { channel_id: 1 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(next_sequence_recv, 3);
+}
+
+#[test]
+fn packet_recv_ordered_out_of_order() {
+    let mut deps = mock_dependencies();
+    setup(deps.as_mut());
+    open_connection(&mut deps);
+    ordered_channel_open_try(deps.as_mut());
+    ordered_channel_open_confirm(deps.as_mut());
+
+    verify_packet_sequences(&mut deps, vec![incoming_packet(1), incoming_packet(2)]);
+
+    // the packet sent with sequence 2 must be proven against sequence 1, which commits to a
+    // different packet
+    assert!(recv(deps.as_mut(), vec![incoming_packet(2)]).is_err());
+}
+
+#[test]
+fn packet_recv_ordered_batch() {
+    let mut deps = mock_dependencies();
+    setup(deps.as_mut());
+    open_connection(&mut deps);
+    ordered_channel_open_try(deps.as_mut());
+    ordered_channel_open_confirm(deps.as_mut());
+
+    verify_packet_sequences(&mut deps, vec![incoming_packet(1), incoming_packet(2)]);
+
+    assert!(matches!(
+        recv(deps.as_mut(), vec![incoming_packet(1), incoming_packet(2)]),
+        Err(ContractError::OrderedChannelBatching)
+    ));
+}
+
+/// Send a packet on channel 1 that times out at timestamp 100.
+fn send_timing_out_packet(deps: DepsMut) -> Packet {
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::PacketSend(MsgSendPacket {
+            source_channel: 1,
+            timeout_height: 0,
+            timeout_timestamp: 100,
+            data: vec![1].into(),
+        }),
+    )
+    .expect("send packet is ok");
+
+    Packet {
+        source_channel_id: 1,
+        destination_channel_id: COUNTERPARTY_CHANNEL_ID,
+        data: vec![1].into(),
+        timeout_height: 0,
+        timeout_timestamp: 100,
+    }
+}
+
+/// Only verify that the counterparty is expecting `next_sequence_recv` next.
+fn verify_next_sequence_recv(deps: &mut MockDeps, next_sequence_recv: u64) {
+    deps.querier
+        .update_wasm(fallible_wasm_query_handler(move |msg| match msg {
+            LightClientQueryMsg::GetTimestamp { .. } => to_json_binary(&200_u64),
+            LightClientQueryMsg::VerifyMembership { path, value, .. } => {
+                let expected_path = NextSequenceRecvPath {
+                    channel_id: COUNTERPARTY_CHANNEL_ID,
+                }
+                .key();
+
+                if path == expected_path.into_bytes()
+                    && value == commit_sequence(next_sequence_recv).into_bytes()
+                {
+                    to_json_binary(&())
+                } else {
+                    Err(StdError::generic_err("invalid membership proof"))
+                }
+            }
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+}
+
+fn timeout(deps: DepsMut, packet: Packet) -> Result<Response, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(RELAYER), &[]),
+        ExecuteMsg::PacketTimeout(MsgPacketTimeout {
+            packet,
+            proof: vec![1, 2, 3].into(),
+            proof_height: 10,
+            relayer: mock_addr(RELAYER).into_string(),
+        }),
+    )
+}
+
+fn packet_sequence_commitment(deps: Deps, sequence: u64) -> Option<H256> {
+    from_json(
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::GetPacketSequence {
+                channel_id: 1,
+                sequence,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn packet_timeout_ordered_ok() {
+    let mut deps = mock_dependencies();
+    setup(deps.as_mut());
+    open_connection(&mut deps);
+    ordered_channel_open_init_ack(deps.as_mut());
+
+    let packet = send_timing_out_packet(deps.as_mut());
+
+    assert_eq!(
+        packet_sequence_commitment(deps.as_ref(), 1),
+        Some(commit_packet(&packet))
+    );
+
+    verify_next_sequence_recv(&mut deps, 1);
+
+    timeout(deps.as_mut(), packet).expect("timeout is ok");
+
+    // the packet can't be relayed anymore
+    assert_eq!(packet_sequence_commitment(deps.as_ref(), 1), None);
+
+    // the following packets can't be received anymore, so the channel is closed
+    assert_eq!(
+        crate::state::CHANNELS.load(&deps.storage, 1).unwrap().state,
+        ChannelState::Closed
+    );
+}
+
+#[test]
+fn packet_timeout_ordered_received() {
+    let mut deps = mock_dependencies();
+    setup(deps.as_mut());
+    open_connection(&mut deps);
+    ordered_channel_open_init_ack(deps.as_mut());
+
+    let packet = send_timing_out_packet(deps.as_mut());
+
+    // the counterparty has already received the packet
+    verify_next_sequence_recv(&mut deps, 2);
+
+    assert!(timeout(deps.as_mut(), packet).is_err());
+}
@@ -1,12 +1,16 @@
 use cosmwasm_std::{
-    testing::{message_info, mock_dependencies, mock_env},
-    to_json_binary, Addr, Event,
+    testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+    to_json_binary, Addr, ContractInfoResponse, Event, OwnedDeps, QuerierResult, WasmQuery,
 };
 use ibc_union_msg::{
     lightclient::{
-        QueryMsg as LightClientQueryMsg, VerifyClientMessageUpdate, VerifyCreationResponse,
+        MisbehaviourResponse, QueryMsg as LightClientQueryMsg, Status, VerifyClientMessageUpdate,
+        VerifyCreationResponse,
+    },
+    msg::{
+        ExecuteMsg, InitMsg, MsgCreateClient, MsgRecoverClient, MsgRegisterClient,
+        MsgSubmitMisbehaviour, MsgUpdateClient, MsgUpgradeClient,
     },
-    msg::{ExecuteMsg, InitMsg, MsgUpdateClient},
 };
 
 use super::*;
@@ -320,3 +324,536 @@ fn update_client_commitments_saved() {
         vec![3, 2, 1]
     );
 }
+
+#[test]
+fn submit_misbehaviour_ok() {
+    let mut deps = mock_dependencies();
+    let sender = mock_addr(SENDER);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender, &[]),
+        InitMsg {},
+    )
+    .expect("instantiate ok");
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::GetStatus { .. } => to_json_binary(&Status::Active),
+            LightClientQueryMsg::Misbehaviour { .. } => to_json_binary(&MisbehaviourResponse {
+                client_state: vec![4, 5, 6].into(),
+            }),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+
+    register_client(deps.as_mut()).expect("register client ok");
+    let res = create_client(deps.as_mut()).expect("create client ok");
+    let client_id: u32 = res
+        .events
+        .iter()
+        .find(|event| event.ty.eq(events::client::CREATE))
+        .expect("create client event exists")
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key.eq(events::attribute::CLIENT_ID))
+        .expect("client type attribute exists")
+        .value
+        .parse()
+        .expect("client type string is u32");
+
+    let msg = ExecuteMsg::SubmitMisbehaviour(MsgSubmitMisbehaviour {
+        client_id,
+        misbehaviour: vec![3, 2, 1].into(),
+        relayer: mock_addr(RELAYER).into_string(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        msg,
+    )
+    .expect("submit misbehaviour ok");
+
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty.eq(events::client::MISBEHAVIOUR))
+        .expect("misbehaviour event is emitted");
+    assert!(event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == events::attribute::MAKER
+            && attr.value == mock_addr(RELAYER).to_string()));
+    assert_eq!(
+        crate::state::CLIENT_STATES
+            .load(&deps.storage, client_id)
+            .unwrap(),
+        vec![4, 5, 6]
+    );
+    // the consensus states are left untouched
+    assert_eq!(
+        crate::state::CLIENT_CONSENSUS_STATES
+            .load(&deps.storage, (client_id, 1))
+            .unwrap(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn submit_misbehaviour_ko() {
+    let mut deps = mock_dependencies();
+    let sender = mock_addr(SENDER);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender, &[]),
+        InitMsg {},
+    )
+    .expect("instantiate ok");
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::GetStatus { .. } => to_json_binary(&Status::Active),
+            LightClientQueryMsg::Misbehaviour { .. } => to_json_binary(&0),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+
+    register_client(deps.as_mut()).expect("register client ok");
+    let res = create_client(deps.as_mut()).expect("create client ok");
+    let client_id: u32 = res
+        .events
+        .iter()
+        .find(|event| event.ty.eq(events::client::CREATE))
+        .expect("create client event exists")
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key.eq(events::attribute::CLIENT_ID))
+        .expect("client type attribute exists")
+        .value
+        .parse()
+        .expect("client type string is u32");
+
+    let msg = ExecuteMsg::SubmitMisbehaviour(MsgSubmitMisbehaviour {
+        client_id,
+        misbehaviour: vec![3, 2, 1].into(),
+        relayer: mock_addr(RELAYER).into_string(),
+    });
+    assert!(execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        msg
+    )
+    .is_err());
+    assert_eq!(
+        crate::state::CLIENT_STATES
+            .load(&deps.storage, client_id)
+            .unwrap(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn submit_misbehaviour_verification_fails() {
+    let mut deps = mock_dependencies();
+    let sender = mock_addr(SENDER);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender, &[]),
+        InitMsg {},
+    )
+    .expect("instantiate ok");
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { msg, .. } => match from_json::<LightClientQueryMsg>(msg).unwrap() {
+            LightClientQueryMsg::VerifyCreation { .. } => QuerierResult::Ok(
+                to_json_binary(&VerifyCreationResponse {
+                    latest_height: 1,
+                    counterparty_chain_id: "testchain".to_owned(),
+                })
+                .into(),
+            ),
+            LightClientQueryMsg::GetStatus { .. } => {
+                QuerierResult::Ok(to_json_binary(&Status::Active).into())
+            }
+            // the light client rejects misbehaviour that it can't verify
+            LightClientQueryMsg::Misbehaviour { .. } => QuerierResult::Ok(
+                cosmwasm_std::ContractResult::Err("misbehaviour not found".to_owned()),
+            ),
+            msg => panic!("should not be called: {:?}", msg),
+        },
+        _ => panic!("only smart queries are expected"),
+    });
+
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create client ok");
+
+    let msg = ExecuteMsg::SubmitMisbehaviour(MsgSubmitMisbehaviour {
+        client_id: 1,
+        misbehaviour: vec![3, 2, 1].into(),
+        relayer: mock_addr(RELAYER).into_string(),
+    });
+    assert!(matches!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&mock_addr(SENDER), &[]),
+            msg
+        ),
+        Err(ContractError::Std(_))
+    ));
+    assert_eq!(
+        crate::state::CLIENT_STATES.load(&deps.storage, 1).unwrap(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn submit_misbehaviour_client_not_active() {
+    let mut deps = mock_dependencies();
+    let sender = mock_addr(SENDER);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender, &[]),
+        InitMsg {},
+    )
+    .expect("instantiate ok");
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::GetStatus { .. } => to_json_binary(&Status::Frozen),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create client ok");
+
+    let msg = ExecuteMsg::SubmitMisbehaviour(MsgSubmitMisbehaviour {
+        client_id: 1,
+        misbehaviour: vec![3, 2, 1].into(),
+        relayer: mock_addr(RELAYER).into_string(),
+    });
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&mock_addr(SENDER), &[]),
+            msg,
+        ),
+        Err(ContractError::ClientNotActive { client_id: 1 })
+    );
+    assert_eq!(
+        crate::state::CLIENT_STATES.load(&deps.storage, 1).unwrap(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn upgrade_client_ok() {
+    let mut deps = mock_dependencies();
+    let sender = mock_addr(SENDER);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender, &[]),
+        InitMsg {},
+    )
+    .expect("instantiate ok");
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::GetStatus { .. } => to_json_binary(&Status::Active),
+            LightClientQueryMsg::VerifyUpgrade { .. } => {
+                to_json_binary(&VerifyClientMessageUpdate {
+                    height: 10,
+                    consensus_state: vec![7, 8, 9].into(),
+                    client_state: vec![4, 5, 6].into(),
+                })
+            }
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create client ok");
+
+    let msg = ExecuteMsg::UpgradeClient(MsgUpgradeClient {
+        client_id: 1,
+        upgrade_client_state: vec![4, 5, 6].into(),
+        upgrade_consensus_state: vec![7, 8, 9].into(),
+        proof_upgrade_client: vec![1].into(),
+        proof_upgrade_consensus_state: vec![2].into(),
+        relayer: mock_addr(RELAYER).into_string(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        msg,
+    )
+    .expect("upgrade client ok");
+
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty.eq(events::client::UPGRADE))
+        .expect("upgrade event is emitted");
+    assert!(event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == events::attribute::MAKER
+            && attr.value == mock_addr(RELAYER).to_string()));
+    assert_eq!(
+        crate::state::CLIENT_STATES.load(&deps.storage, 1).unwrap(),
+        vec![4, 5, 6]
+    );
+    assert_eq!(
+        crate::state::CLIENT_CONSENSUS_STATES
+            .load(&deps.storage, (1, 10))
+            .unwrap(),
+        vec![7, 8, 9]
+    );
+}
+
+#[test]
+fn upgrade_client_not_active() {
+    let mut deps = mock_dependencies();
+    let sender = mock_addr(SENDER);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender, &[]),
+        InitMsg {},
+    )
+    .expect("instantiate ok");
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::GetStatus { .. } => to_json_binary(&Status::Frozen),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create client ok");
+
+    let msg = ExecuteMsg::UpgradeClient(MsgUpgradeClient {
+        client_id: 1,
+        upgrade_client_state: vec![4, 5, 6].into(),
+        upgrade_consensus_state: vec![7, 8, 9].into(),
+        proof_upgrade_client: vec![1].into(),
+        proof_upgrade_consensus_state: vec![2].into(),
+        relayer: mock_addr(RELAYER).into_string(),
+    });
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&mock_addr(SENDER), &[]),
+            msg,
+        ),
+        Err(ContractError::ClientNotActive { client_id: 1 })
+    );
+}
+
+/// The admin of the contract, the only address that can recover clients.
+const ADMIN: &str = SENDER;
+
+/// Instantiate the contract with [`ADMIN`] as it's admin, where the status of each client is given
+/// by `status`. A recovery always updates the subject client to height 10.
+fn setup_recovery(status: fn(u32) -> Status) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        InitMsg {},
+    )
+    .expect("instantiate ok");
+
+    let light_client = wasm_query_handler(move |msg| match msg {
+        LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+            latest_height: 1,
+            counterparty_chain_id: "testchain".to_owned(),
+        }),
+        LightClientQueryMsg::GetStatus { client_id } => to_json_binary(&status(client_id)),
+        LightClientQueryMsg::Recover { .. } => to_json_binary(&VerifyClientMessageUpdate {
+            height: 10,
+            consensus_state: vec![7, 8, 9].into(),
+            client_state: vec![4, 5, 6].into(),
+        }),
+        msg => panic!("should not be called: {:?}", msg),
+    });
+    deps.querier
+        .update_wasm(move |query: &WasmQuery| -> QuerierResult {
+            match query {
+                WasmQuery::ContractInfo { .. } => {
+                    QuerierResult::Ok(cosmwasm_std::ContractResult::Ok(
+                        to_json_binary(&ContractInfoResponse::new(
+                            1,
+                            mock_addr(SENDER),
+                            Some(mock_addr(ADMIN)),
+                            false,
+                            None,
+                        ))
+                        .unwrap(),
+                    ))
+                }
+                query => light_client(query),
+            }
+        });
+
+    register_client(deps.as_mut()).expect("register client ok");
+
+    deps
+}
+
+fn recover_client(
+    deps: DepsMut,
+    sender: &str,
+    subject_client_id: u32,
+    substitute_client_id: u32,
+) -> Result<Response, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(sender), &[]),
+        ExecuteMsg::RecoverClient(MsgRecoverClient {
+            subject_client_id,
+            substitute_client_id,
+        }),
+    )
+}
+
+/// Client 1 is frozen and client 2 is active.
+fn frozen_subject(client_id: u32) -> Status {
+    if client_id == 1 {
+        Status::Frozen
+    } else {
+        Status::Active
+    }
+}
+
+#[test]
+fn recover_client_ok() {
+    let mut deps = setup_recovery(frozen_subject);
+    create_client(deps.as_mut()).expect("create subject client ok");
+    create_client(deps.as_mut()).expect("create substitute client ok");
+
+    let res = recover_client(deps.as_mut(), ADMIN, 1, 2).expect("recover client ok");
+
+    assert!(res
+        .events
+        .iter()
+        .any(|event| event.ty.eq(events::client::RECOVER)));
+    assert_eq!(
+        crate::state::CLIENT_STATES.load(&deps.storage, 1).unwrap(),
+        vec![4, 5, 6]
+    );
+    assert_eq!(
+        crate::state::CLIENT_CONSENSUS_STATES
+            .load(&deps.storage, (1, 10))
+            .unwrap(),
+        vec![7, 8, 9]
+    );
+}
+
+#[test]
+fn recover_client_unauthorized() {
+    let mut deps = setup_recovery(frozen_subject);
+    create_client(deps.as_mut()).expect("create subject client ok");
+    create_client(deps.as_mut()).expect("create substitute client ok");
+
+    assert_eq!(
+        recover_client(deps.as_mut(), RELAYER, 1, 2),
+        Err(ContractError::UnauthorizedRecovery {
+            caller: mock_addr(RELAYER)
+        })
+    );
+    assert_eq!(
+        crate::state::CLIENT_STATES.load(&deps.storage, 1).unwrap(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn recover_client_type_mismatch() {
+    let mut deps = setup_recovery(frozen_subject);
+    create_client(deps.as_mut()).expect("create subject client ok");
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::RegisterClient(MsgRegisterClient {
+            client_type: "other".to_owned(),
+            client_address: mock_addr("otherclient").into_string(),
+        }),
+    )
+    .expect("register other client ok");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::CreateClient(MsgCreateClient {
+            client_type: "other".to_owned(),
+            client_state_bytes: vec![1, 2, 3].into(),
+            consensus_state_bytes: vec![1, 2, 3].into(),
+            relayer: mock_addr(RELAYER).into_string(),
+        }),
+    )
+    .expect("create substitute client ok");
+
+    assert_eq!(
+        recover_client(deps.as_mut(), ADMIN, 1, 2),
+        Err(ContractError::RecoveryClientTypeMismatch {
+            subject_client_type: CLIENT_TYPE.to_owned(),
+            substitute_client_type: "other".to_owned(),
+        })
+    );
+}
+
+#[test]
+fn recover_client_subject_active() {
+    let mut deps = setup_recovery(|_| Status::Active);
+    create_client(deps.as_mut()).expect("create subject client ok");
+    create_client(deps.as_mut()).expect("create substitute client ok");
+
+    assert_eq!(
+        recover_client(deps.as_mut(), ADMIN, 1, 2),
+        Err(ContractError::CannotRecoverActiveClient { client_id: 1 })
+    );
+}
+
+#[test]
+fn recover_client_substitute_not_active() {
+    let mut deps = setup_recovery(|_| Status::Expired);
+    create_client(deps.as_mut()).expect("create subject client ok");
+    create_client(deps.as_mut()).expect("create substitute client ok");
+
+    assert_eq!(
+        recover_client(deps.as_mut(), ADMIN, 1, 2),
+        Err(ContractError::SubstituteClientNotActive { client_id: 2 })
+    );
+}
@@ -1,47 +1,26 @@
-use cosmwasm_std::{Deps, DepsMut, Env};
-use ethereum_light_client::client::{canonicalize_stored_value, check_commitment_key};
+use cosmwasm_std::{Addr, Empty};
+use ethereum_light_client::client::{check_commitment_key, EthereumLightClient};
 use gnark_mimc::new_mimc_constants_bls12_377;
-use ics008_wasm_client::{
-    storage_utils::{
-        read_client_state, read_consensus_state, save_client_state, save_consensus_state,
-        update_client_state,
-    },
-    IbcClient, IbcClientError, Status, StorageState,
-};
-use linea_light_client_types::{
-    client_state::ClientState, consensus_state::ConsensusState, header::Header,
+use ibc_union_light_client::{IbcClient, IbcClientCtx, IbcClientError};
+use ibc_union_msg::lightclient::Status;
+use linea_light_client_types::{ClientState, ConsensusState, Header};
+use linea_types::{
+    account::ZkAccount,
+    proof::{InclusionProof, MerkleProof, NonInclusionProof},
 };
 use unionlabs::{
-    cosmwasm::wasm::union::custom_query::{query_consensus_state, UnionCustomQuery},
-    encoding::{DecodeAs, Proto},
-    ethereum::keccak256,
-    ibc::{
-        core::{
-            client::{genesis_metadata::GenesisMetadata, height::Height},
-            commitment::merkle_path::MerklePath,
-        },
-        lightclients::{ethereum, wasm},
-    },
-    linea::{
-        account::ZkAccount,
-        proof::{InclusionProof, NonInclusionProof},
-    },
+    encoding::Bincode,
     primitives::{H256, U256},
 };
 
 use crate::errors::Error;
 
-type WasmClientState = wasm::client_state::ClientState<ClientState>;
-type WasmConsensusState = wasm::consensus_state::ConsensusState<ConsensusState>;
-type WasmL1ConsensusState =
-    wasm::consensus_state::ConsensusState<ethereum::consensus_state::ConsensusState>;
-
-pub struct LineaLightClient;
+pub enum LineaLightClient {}
 
 impl IbcClient for LineaLightClient {
     type Error = Error;
 
-    type CustomQuery = UnionCustomQuery;
+    type CustomQuery = Empty;
 
     type Header = Header;
 
@@ -51,203 +30,151 @@ impl IbcClient for LineaLightClient {
 
     type ConsensusState = ConsensusState;
 
-    type Encoding = Proto;
+    type StorageProof = MerkleProof;
+
+    type Encoding = Bincode;
 
     fn verify_membership(
-        deps: Deps<Self::CustomQuery>,
-        height: Height,
-        _delay_time_period: u64,
-        _delay_block_period: u64,
-        proof: Vec<u8>,
-        mut path: MerklePath,
-        value: ics008_wasm_client::StorageState,
+        ctx: IbcClientCtx<Self>,
+        height: u64,
+        key: Vec<u8>,
+        storage_proof: Self::StorageProof,
+        value: Vec<u8>,
     ) -> Result<(), IbcClientError<Self>> {
-        let consensus_state: WasmConsensusState =
-            read_consensus_state(deps, &height)?.ok_or(Error::ConsensusStateNotFound(height))?;
-        let client_state: WasmClientState = read_client_state(deps)?;
+        let consensus_state = ctx.read_self_consensus_state(height)?;
 
-        let path = path.key_path.pop().ok_or(Error::EmptyIbcPath)?;
+        let MerkleProof::Inclusion(inclusion_proof) = storage_proof else {
+            return Err(Error::ExpectedInclusionProof.into());
+        };
 
         // This storage root is verified during the header update, so we don't need to verify it again.
-        let storage_root = consensus_state.data.ibc_storage_root;
-
-        match value {
-            StorageState::Occupied(value) => {
-                let inclusion_proof = InclusionProof::decode_as::<Proto>(&proof)
-                    .map_err(Error::InclusionProofDecode)?;
-                do_verify_membership(path, storage_root, inclusion_proof, value)?
-            }
-            StorageState::Empty => {
-                let noninclusion_proof = NonInclusionProof::decode_as::<Proto>(&proof)
-                    .map_err(Error::InclusionProofDecode)?;
-                do_verify_non_membership(path, storage_root, noninclusion_proof)?
-            }
-        }
-
-        Ok(())
+        Ok(do_verify_membership(
+            key,
+            consensus_state.ibc_storage_root,
+            inclusion_proof,
+            value,
+        )?)
     }
 
-    fn verify_header(
-        deps: Deps<Self::CustomQuery>,
-        env: Env,
-        header: Self::Header,
+    fn verify_non_membership(
+        ctx: IbcClientCtx<Self>,
+        height: u64,
+        key: Vec<u8>,
+        storage_proof: Self::StorageProof,
     ) -> Result<(), IbcClientError<Self>> {
-        let client_state: WasmClientState = read_client_state(deps)?;
-        let l1_consensus_state = query_consensus_state::<WasmL1ConsensusState>(
-            deps,
-            &env,
-            client_state.data.l1_client_id.clone(),
-            header.l1_height,
-        )
-        .map_err(Error::CustomQuery)?;
-        linea_verifier::verify_header(
-            client_state.data,
-            header,
-            l1_consensus_state.data.state_root,
-        )
-        .map_err(Error::Verify)?;
-        Ok(())
+        let consensus_state = ctx.read_self_consensus_state(height)?;
+
+        let MerkleProof::NonInclusion(noninclusion_proof) = storage_proof else {
+            return Err(Error::ExpectedNonInclusionProof.into());
+        };
+
+        Ok(do_verify_non_membership(
+            key,
+            consensus_state.ibc_storage_root,
+            noninclusion_proof,
+        )?)
     }
 
-    fn verify_misbehaviour(
-        _deps: Deps<Self::CustomQuery>,
-        _env: Env,
-        _misbehaviour: Self::Misbehaviour,
-    ) -> Result<(), IbcClientError<Self>> {
-        Err(Error::Unimplemented.into())
+    fn get_timestamp(consensus_state: &Self::ConsensusState) -> u64 {
+        consensus_state.timestamp
     }
 
-    fn update_state(
-        mut deps: DepsMut<Self::CustomQuery>,
-        _env: Env,
-        header: Self::Header,
-    ) -> Result<Vec<Height>, IbcClientError<Self>> {
-        let mut client_state: WasmClientState = read_client_state(deps.as_ref())?;
+    fn get_latest_height(client_state: &Self::ClientState) -> u64 {
+        client_state.l1_latest_height
+    }
 
-        let updated_height = Height {
-            revision_number: client_state.latest_height.revision_number,
-            revision_height: header.l1_height.revision_height,
-        };
+    fn get_counterparty_chain_id(client_state: &Self::ClientState) -> String {
+        client_state.chain_id.to_string()
+    }
 
-        if client_state.latest_height < header.l1_height {
-            client_state.data.l1_latest_height = updated_height;
-            update_client_state::<Self>(
-                deps.branch(),
-                client_state,
-                updated_height.revision_height,
-            );
+    fn status(client_state: &Self::ClientState) -> Status {
+        if client_state.frozen_height.height() != 0 {
+            Status::Frozen
+        } else {
+            Status::Active
         }
-
-        // Guaranteed to succeed as we previously verified the header
-        let zk_account =
-            ZkAccount::decode(header.l2_ibc_contract_proof.proof.value).expect("impossible");
-
-        let consensus_state = WasmConsensusState {
-            data: ConsensusState {
-                ibc_storage_root: zk_account.storage_root,
-                // must be nanos
-                timestamp: 1_000_000_000
-                    * u64::try_from(header.l2_timestamp_proof.value).expect("impossible"),
-            },
-        };
-        save_consensus_state::<Self>(deps, consensus_state, &updated_height);
-        Ok(vec![updated_height])
     }
 
-    fn update_state_on_misbehaviour(
-        deps: DepsMut<Self::CustomQuery>,
-        env: Env,
-        _client_message: Vec<u8>,
+    fn verify_creation(
+        _client_state: &Self::ClientState,
+        _consensus_state: &Self::ConsensusState,
     ) -> Result<(), IbcClientError<Self>> {
-        let mut client_state: WasmClientState = read_client_state(deps.as_ref())?;
-        client_state.data.frozen_height = Height {
-            revision_number: client_state.latest_height.revision_number,
-            revision_height: env.block.height,
-        };
-        save_client_state::<Self>(deps, client_state);
         Ok(())
     }
 
-    fn check_for_misbehaviour_on_header(
-        _deps: Deps<Self::CustomQuery>,
-        _header: Self::Header,
-    ) -> Result<bool, IbcClientError<Self>> {
-        Ok(false)
-    }
+    fn verify_header(
+        ctx: IbcClientCtx<Self>,
+        header: Self::Header,
+        _caller: Addr,
+    ) -> Result<(u64, Self::ClientState, Self::ConsensusState), IbcClientError<Self>> {
+        let client_state = ctx.read_self_client_state()?;
 
-    fn check_for_misbehaviour_on_misbehaviour(
-        _deps: Deps<Self::CustomQuery>,
-        _misbehaviour: Self::Misbehaviour,
-    ) -> Result<bool, IbcClientError<Self>> {
-        Err(Error::Unimplemented.into())
-    }
+        let l1_consensus_state = ctx
+            .read_consensus_state::<EthereumLightClient>(
+                client_state.l1_client_id,
+                header.l1_height.height(),
+            )
+            .map_err(Into::<Error>::into)?;
 
-    fn verify_upgrade_and_update_state(
-        _deps: DepsMut<Self::CustomQuery>,
-        _upgrade_client_state: Self::ClientState,
-        _upgrade_consensus_state: Self::ConsensusState,
-        _proof_upgrade_client: Vec<u8>,
-        _proof_upgrade_consensus_state: Vec<u8>,
-    ) -> Result<(), IbcClientError<Self>> {
-        Err(Error::Unimplemented.into())
+        linea_verifier::verify_header(&client_state, &header, l1_consensus_state.state_root)
+            .map_err(Error::Verify)?;
+
+        Ok(update_state(client_state, &header)?)
     }
 
-    fn migrate_client_store(_deps: DepsMut<Self::CustomQuery>) -> Result<(), IbcClientError<Self>> {
+    fn misbehaviour(
+        _ctx: IbcClientCtx<Self>,
+        _misbehaviour: Self::Misbehaviour,
+    ) -> Result<Self::ClientState, IbcClientError<Self>> {
         Err(Error::Unimplemented.into())
     }
+}
 
-    fn status(deps: Deps<Self::CustomQuery>, _env: &Env) -> Result<Status, IbcClientError<Self>> {
-        let client_state: WasmClientState = read_client_state(deps)?;
-
-        if client_state.data.frozen_height != Height::default() {
-            return Ok(Status::Frozen);
-        }
+/// Build the new states of the client from a verified `header`.
+fn update_state(
+    mut client_state: ClientState,
+    header: &Header,
+) -> Result<(u64, ClientState, ConsensusState), Error> {
+    let zk_account = ZkAccount::decode(&header.l2_ibc_contract_proof.proof.value)
+        .map_err(Error::InvalidL2AccountProof)?;
 
-        let Some(_) = read_consensus_state::<Self>(deps, &client_state.latest_height)? else {
-            return Ok(Status::Expired);
-        };
+    let timestamp = u64::try_from(header.l2_timestamp_proof.value)
+        .map_err(|_| Error::InvalidL2Timestamp(header.l2_timestamp_proof.value))?;
 
-        Ok(Status::Active)
+    if client_state.l1_latest_height < header.l1_height.height() {
+        client_state.l1_latest_height = header.l1_height.height();
     }
 
-    fn export_metadata(
-        _deps: Deps<Self::CustomQuery>,
-        _env: &Env,
-    ) -> Result<Vec<GenesisMetadata>, IbcClientError<Self>> {
-        Ok(Vec::new())
-    }
+    let consensus_state = ConsensusState {
+        ibc_storage_root: zk_account.storage_root,
+        // must be nanos
+        timestamp: 1_000_000_000 * timestamp,
+    };
 
-    fn timestamp_at_height(
-        deps: Deps<Self::CustomQuery>,
-        height: Height,
-    ) -> Result<u64, IbcClientError<Self>> {
-        Ok(read_consensus_state::<Self>(deps, &height)?
-            .ok_or(Error::ConsensusStateNotFound(height))?
-            .data
-            .timestamp)
-    }
+    Ok((header.l1_height.height(), client_state, consensus_state))
 }
 
 fn do_verify_membership(
-    path: String,
+    key: Vec<u8>,
     storage_root: H256,
     storage_proof: InclusionProof,
-    raw_value: Vec<u8>,
+    value: Vec<u8>,
 ) -> Result<(), Error> {
-    // TODO: handle error
-    let key = U256::try_from_be_bytes(&storage_proof.key).unwrap();
+    let proof_key = proof_key(&storage_proof.key)?;
 
-    check_commitment_key(&path, key)?;
+    check_commitment_key(
+        H256::try_from(&key).map_err(|_| Error::InvalidCommitmentKeyLength(key))?,
+        proof_key,
+    )?;
 
-    // we store the hash of the data, not the data itself to the commitments map
-    let expected_value_hash = keccak256(canonicalize_stored_value(path, raw_value)?);
+    let value = H256::try_from(&value).map_err(|_| Error::InvalidCommitmentValueLength(value))?;
 
-    // TODO: handle error
-    let proof_value = H256::new(storage_proof.proof.value.clone().try_into().unwrap());
+    let proof_value = H256::try_from(&*storage_proof.proof.value)
+        .map_err(|_| Error::InvalidProofValueLength(storage_proof.proof.value.to_vec()))?;
 
-    if expected_value_hash != proof_value {
+    if value != proof_value {
         return Err(Error::StoredValueMismatch {
-            expected: expected_value_hash,
+            expected: value,
             stored: proof_value,
         });
     }
@@ -257,29 +184,405 @@ fn do_verify_membership(
         storage_proof.leaf_index,
         &storage_proof.proof,
         storage_root,
-        key,
+        proof_key,
     )?;
 
     Ok(())
 }
 
-/// Verifies that no value is committed at `path` in the counterparty light client's storage.
+/// Verifies that no value is committed at `key` in the counterparty's IBC storage.
 fn do_verify_non_membership(
-    path: String,
+    key: Vec<u8>,
     storage_root: H256,
     noninclusion_proof: NonInclusionProof,
 ) -> Result<(), Error> {
-    // TODO: handle error
-    let key = U256::try_from_be_bytes(&noninclusion_proof.key).unwrap();
+    let proof_key = proof_key(&noninclusion_proof.key)?;
 
-    check_commitment_key(&path, key)?;
+    check_commitment_key(
+        H256::try_from(&key).map_err(|_| Error::InvalidCommitmentKeyLength(key))?,
+        proof_key,
+    )?;
 
     linea_zktrie::verify::verify_noninclusion::<U256>(
         &new_mimc_constants_bls12_377(),
         &noninclusion_proof,
         storage_root,
-        key,
+        proof_key,
     )?;
 
     Ok(())
 }
+
+fn proof_key(key: &[u8]) -> Result<U256, Error> {
+    U256::try_from_be_bytes(key).map_err(|_| Error::InvalidProofKeyLength(key.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use beacon_api_types::Slot;
+    use cosmwasm_std::{
+        testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+        to_json_binary, Binary, ContractResult, OwnedDeps, SystemResult, WasmQuery,
+    };
+    use ethereum_light_client_types::{AccountProof, StorageProof};
+    use ibc_union_light_client::{CLIENT_CONSENSUS_STATES, CLIENT_STATES};
+    use linea_types::proof::MerklePath;
+    use unionlabs::{
+        encoding::{EncodeAs, EthAbi},
+        ethereum::ibc_commitment_key,
+        ibc::core::client::height::Height,
+        primitives::{H160, H384},
+    };
+
+    use super::*;
+
+    const CLIENT_ID: u32 = 2;
+    const L1_CLIENT_ID: u32 = 1;
+    const L1_HEIGHT: u64 = 100;
+
+    fn client_state() -> ClientState {
+        ClientState {
+            chain_id: U256::from(59144u64),
+            l1_client_id: L1_CLIENT_ID,
+            l1_latest_height: 90,
+            l1_rollup_contract_address: H160::default(),
+            l1_rollup_current_l2_timestamp_slot: U256::from(1u64),
+            l1_rollup_current_l2_block_number_slot: U256::from(2u64),
+            l1_rollup_l2_state_root_hashes_slot: U256::from(3u64),
+            l2_ibc_contract_address: H160::default(),
+            frozen_height: Height::default(),
+        }
+    }
+
+    fn l1_consensus_state() -> ethereum_light_client_types::ConsensusState {
+        ethereum_light_client_types::ConsensusState {
+            slot: Slot::new(L1_HEIGHT),
+            state_root: H256::default(),
+            storage_root: H256::default(),
+            timestamp: 1,
+            current_sync_committee: H384::default(),
+            next_sync_committee: H384::default(),
+        }
+    }
+
+    fn storage_proof(value: U256) -> StorageProof {
+        StorageProof {
+            key: U256::ZERO,
+            value,
+            proof: vec![],
+        }
+    }
+
+    fn merkle_path(value: Vec<u8>) -> MerklePath {
+        MerklePath {
+            value: value.into(),
+            proof_related_nodes: vec![],
+        }
+    }
+
+    fn header(account: Vec<u8>, timestamp: U256) -> Header {
+        Header {
+            l1_height: Height::new(L1_HEIGHT),
+            l1_rollup_contract_proof: AccountProof {
+                storage_root: H256::default(),
+                proof: vec![],
+            },
+            l2_timestamp_proof: storage_proof(timestamp),
+            l2_block_number_proof: storage_proof(U256::from(10u64)),
+            l2_state_root_proof: storage_proof(U256::ZERO),
+            l2_ibc_contract_proof: InclusionProof {
+                key: vec![].into(),
+                leaf_index: 0,
+                proof: merkle_path(account),
+            },
+        }
+    }
+
+    fn account(storage_root: H256) -> Vec<u8> {
+        ZkAccount {
+            storage_root,
+            ..Default::default()
+        }
+        .into_bytes()
+    }
+
+    /// Mock the raw storage of the ibc host with the given `(key, value)` pairs.
+    fn deps(states: Vec<(Vec<u8>, Vec<u8>)>) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Raw { key, .. } => SystemResult::Ok(ContractResult::Ok(
+                states
+                    .iter()
+                    .find(|(k, _)| k.as_slice() == key.as_slice())
+                    .map(|(_, state)| to_json_binary(&Binary::from(state.clone())).unwrap())
+                    .unwrap_or_default(),
+            )),
+            _ => panic!("unexpected query: {query:?}"),
+        });
+        deps
+    }
+
+    fn client_state_entry() -> (Vec<u8>, Vec<u8>) {
+        (
+            CLIENT_STATES.key(CLIENT_ID).to_vec(),
+            client_state().encode_as::<Bincode>(),
+        )
+    }
+
+    fn consensus_state_entry(client_id: u32, height: u64, state: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
+        (
+            CLIENT_CONSENSUS_STATES.key((client_id, height)).to_vec(),
+            state,
+        )
+    }
+
+    fn ctx(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>) -> IbcClientCtx<LineaLightClient> {
+        IbcClientCtx::new(
+            CLIENT_ID,
+            Addr::unchecked("ibchost"),
+            deps.as_ref(),
+            mock_env(),
+        )
+    }
+
+    fn consensus_state_entry_at_l1_height() -> (Vec<u8>, Vec<u8>) {
+        consensus_state_entry(
+            CLIENT_ID,
+            L1_HEIGHT,
+            ConsensusState {
+                ibc_storage_root: H256::default(),
+                timestamp: 1,
+            }
+            .encode_as::<EthAbi>(),
+        )
+    }
+
+    #[test]
+    fn verify_header_fails_without_l1_consensus_state() {
+        let deps = deps(vec![client_state_entry()]);
+
+        let err = LineaLightClient::verify_header(
+            ctx(&deps),
+            header(account(H256::default()), U256::from(1u64)),
+            Addr::unchecked("relayer"),
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            IbcClientError::ClientSpecific(Error::EvmIbcClient(_))
+        ));
+    }
+
+    #[test]
+    fn verify_header_fails_on_invalid_rollup_proof() {
+        let deps = deps(vec![
+            client_state_entry(),
+            consensus_state_entry(
+                L1_CLIENT_ID,
+                L1_HEIGHT,
+                l1_consensus_state().encode_as::<EthAbi>(),
+            ),
+        ]);
+
+        let err = LineaLightClient::verify_header(
+            ctx(&deps),
+            header(account(H256::default()), U256::from(1u64)),
+            Addr::unchecked("relayer"),
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            IbcClientError::ClientSpecific(Error::Verify(
+                linea_verifier::Error::InvalidRollupContractProof(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn update_state_ok() {
+        let storage_root = H256::new([0xAA; 32]);
+
+        let (height, client_state, consensus_state) = update_state(
+            client_state(),
+            &header(account(storage_root), U256::from(7u64)),
+        )
+        .unwrap();
+
+        assert_eq!(height, L1_HEIGHT);
+        assert_eq!(client_state.l1_latest_height, L1_HEIGHT);
+        assert_eq!(consensus_state.ibc_storage_root, storage_root);
+        assert_eq!(consensus_state.timestamp, 7_000_000_000);
+    }
+
+    #[test]
+    fn update_state_keeps_latest_height() {
+        let mut state = client_state();
+        state.l1_latest_height = L1_HEIGHT + 1;
+
+        let (height, client_state, _) =
+            update_state(state, &header(account(H256::default()), U256::from(7u64))).unwrap();
+
+        assert_eq!(height, L1_HEIGHT);
+        assert_eq!(client_state.l1_latest_height, L1_HEIGHT + 1);
+    }
+
+    #[test]
+    fn update_state_fails_on_invalid_account() {
+        let mut account = account(H256::default());
+        account.pop();
+
+        let err = update_state(client_state(), &header(account, U256::from(7u64))).unwrap_err();
+
+        assert!(matches!(err, Error::InvalidL2AccountProof(_)));
+    }
+
+    #[test]
+    fn update_state_fails_on_timestamp_overflow() {
+        let err = update_state(
+            client_state(),
+            &header(
+                account(H256::default()),
+                U256::from(u64::MAX) + U256::from(1u64),
+            ),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidL2Timestamp(_)));
+    }
+
+    fn inclusion_proof(key: U256, value: Vec<u8>) -> InclusionProof {
+        InclusionProof {
+            key: key.to_be_bytes().to_vec().into(),
+            leaf_index: 0,
+            proof: merkle_path(value),
+        }
+    }
+
+    fn noninclusion_proof(key: U256) -> NonInclusionProof {
+        NonInclusionProof {
+            key: key.to_be_bytes().to_vec().into(),
+            left_leaf_index: 0,
+            left_proof: merkle_path(vec![]),
+            right_leaf_index: 1,
+            right_proof: merkle_path(vec![]),
+        }
+    }
+
+    #[test]
+    fn verify_membership_requires_inclusion_proof() {
+        let deps = deps(vec![consensus_state_entry_at_l1_height()]);
+        let path = H256::new([1; 32]);
+
+        let err = LineaLightClient::verify_membership(
+            ctx(&deps),
+            L1_HEIGHT,
+            path.get().to_vec(),
+            MerkleProof::NonInclusion(noninclusion_proof(ibc_commitment_key(path))),
+            vec![1; 32],
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            IbcClientError::ClientSpecific(Error::ExpectedInclusionProof)
+        ));
+    }
+
+    #[test]
+    fn verify_membership_fails_on_key_mismatch() {
+        let path = H256::new([1; 32]);
+
+        let err = do_verify_membership(
+            path.get().to_vec(),
+            H256::default(),
+            inclusion_proof(U256::from(1u64), vec![1; 32]),
+            vec![1; 32],
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::Evm(_)));
+    }
+
+    #[test]
+    fn verify_membership_fails_on_value_mismatch() {
+        let path = H256::new([1; 32]);
+
+        let err = do_verify_membership(
+            path.get().to_vec(),
+            H256::default(),
+            inclusion_proof(ibc_commitment_key(path), vec![2; 32]),
+            vec![1; 32],
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::StoredValueMismatch { expected, stored }
+                if expected == H256::new([1; 32]) && stored == H256::new([2; 32])
+        ));
+    }
+
+    #[test]
+    fn verify_membership_fails_on_invalid_proof() {
+        let path = H256::new([1; 32]);
+
+        let err = do_verify_membership(
+            path.get().to_vec(),
+            H256::default(),
+            inclusion_proof(ibc_commitment_key(path), vec![1; 32]),
+            vec![1; 32],
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidMembershipProof(_)));
+    }
+
+    #[test]
+    fn verify_non_membership_requires_noninclusion_proof() {
+        let deps = deps(vec![consensus_state_entry_at_l1_height()]);
+        let path = H256::new([1; 32]);
+
+        let err = LineaLightClient::verify_non_membership(
+            ctx(&deps),
+            L1_HEIGHT,
+            path.get().to_vec(),
+            MerkleProof::Inclusion(inclusion_proof(ibc_commitment_key(path), vec![1; 32])),
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            IbcClientError::ClientSpecific(Error::ExpectedNonInclusionProof)
+        ));
+    }
+
+    #[test]
+    fn verify_non_membership_fails_on_key_mismatch() {
+        let path = H256::new([1; 32]);
+
+        let err = do_verify_non_membership(
+            path.get().to_vec(),
+            H256::default(),
+            noninclusion_proof(U256::from(1u64)),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::Evm(_)));
+    }
+
+    #[test]
+    fn verify_non_membership_fails_on_invalid_proof() {
+        let path = H256::new([1; 32]);
+
+        let err = do_verify_non_membership(
+            path.get().to_vec(),
+            H256::default(),
+            noninclusion_proof(ibc_commitment_key(path)),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidMembershipProof(_)));
+    }
+}
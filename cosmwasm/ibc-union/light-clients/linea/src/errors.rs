@@ -1,77 +1,60 @@
-use ethereum_light_client::errors::{CanonicalizeStoredValueError, InvalidCommitmentKey};
-use ics008_wasm_client::IbcClientError;
-use linea_light_client_types::{ClientState, ConsensusState};
+use ethereum_light_client::client::EthereumLightClient;
+use ibc_union_light_client::IbcClientError;
 use unionlabs::{
-    encoding::{DecodeErrorOf, Proto},
+    errors::InvalidLength,
     ibc::core::client::height::Height,
-    ics24::PathParseError,
-    linea::proof::InclusionProof,
-    primitives::H256,
+    primitives::{H256, U256},
 };
 
 use crate::client::LineaLightClient;
 
-#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("unable to decode storage proof")]
-    InclusionProofDecode(#[source] DecodeErrorOf<Proto, InclusionProof>),
-
-    // #[error("unable to decode counterparty's stored cometbls client state")]
-    // CometblsClientStateDecode(
-    //     #[source] DecodeErrorOf<Proto, Any<cometbls::client_state::ClientState>>,
-    // ),
-    // #[error("unable to decode counterparty's stored cometbls consensus state")]
-    // CometblsConsensusStateDecode(
-    //     #[source]
-    //     DecodeErrorOf<
-    //         Proto,
-    //         Any<wasm::consensus_state::ConsensusState<cometbls::consensus_state::ConsensusState>>,
-    //     >,
-    // ),
-    #[error("unable to decode client state")]
-    ClientStateDecode(#[source] DecodeErrorOf<Proto, ClientState>),
-    #[error("unable to decode consensus state")]
-    ConsensusStateDecode(#[source] DecodeErrorOf<Proto, ConsensusState>),
-
     #[error(transparent)]
-    CanonicalizeStoredValue(#[from] CanonicalizeStoredValueError),
+    Evm(#[from] ethereum_light_client::errors::Error),
 
     // REVIEW: Move this variant to IbcClientError?
     #[error("consensus state not found at height {0}")]
     ConsensusStateNotFound(Height),
 
-    #[error("IBC path is empty")]
-    EmptyIbcPath,
-
-    #[error(transparent)]
-    InvalidCommitmentKey(#[from] InvalidCommitmentKey),
-
-    #[error("proof is empty")]
-    EmptyProof,
-
-    #[error("batching proofs are not supported")]
-    BatchingProofsNotSupported,
-
     #[error("expected value ({expected}) and stored value ({stored}) don't match")]
     StoredValueMismatch { expected: H256, stored: H256 },
 
-    #[error("unable to parse ics24 path")]
-    PathParse(#[from] PathParseError),
-
     #[error("failed to verify linea header: {0}")]
     Verify(#[from] linea_verifier::Error),
 
     #[error("the operation has not been implemented yet")]
     Unimplemented,
 
-    #[error("error while calling custom query: {0}")]
-    CustomQuery(#[from] unionlabs::cosmwasm::wasm::union::custom_query::Error),
+    #[error("invalid L2 IBC contract account in the account proof")]
+    InvalidL2AccountProof(#[source] InvalidLength),
+
+    #[error("membership can only be verified with an inclusion proof")]
+    ExpectedInclusionProof,
 
-    #[error("L2 account proof must be an inclusion proof")]
-    InvalidL2AccountProof,
+    #[error("non-membership can only be verified with a non-inclusion proof")]
+    ExpectedNonInclusionProof,
+
+    #[error("commitment key must be 32 bytes but we got: {0:?}")]
+    InvalidCommitmentKeyLength(Vec<u8>),
+
+    #[error("commitment value must be 32 bytes but we got: {0:?}")]
+    InvalidCommitmentValueLength(Vec<u8>),
+
+    #[error("proof key must be at most 32 bytes but we got: {0:?}")]
+    InvalidProofKeyLength(Vec<u8>),
+
+    #[error("proof value must be 32 bytes but we got: {0:?}")]
+    InvalidProofValueLength(Vec<u8>),
+
+    #[error("l2 timestamp {0} does not fit in a u64")]
+    InvalidL2Timestamp(U256),
 
     #[error("failed to verify linea membership proof {0}")]
     InvalidMembershipProof(#[from] linea_zktrie::verify::Error),
+
+    #[error(transparent)]
+    EvmIbcClient(#[from] IbcClientError<EthereumLightClient>),
 }
 
 impl From<Error> for IbcClientError<LineaLightClient> {
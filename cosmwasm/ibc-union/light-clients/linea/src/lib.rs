@@ -1,3 +1,4 @@
 pub mod client;
+#[cfg(any(test, not(feature = "library")))]
 pub mod contract;
 pub mod errors;
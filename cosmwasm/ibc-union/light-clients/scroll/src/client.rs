@@ -1,11 +1,11 @@
-use cosmwasm_std::Empty;
+use cosmwasm_std::{Addr, Empty};
 use ethereum_light_client::client::{check_commitment_key, EthereumLightClient};
 use ethereum_light_client_types::StorageProof;
 use ibc_union_light_client::{IbcClient, IbcClientCtx, IbcClientError};
 use ibc_union_msg::lightclient::Status;
 use scroll_codec::batch_header::BatchHeaderV3;
 use scroll_light_client_types::{ClientState, ConsensusState, Header};
-use unionlabs::{encoding::Proto, primitives::H256};
+use unionlabs::{encoding::Bincode, primitives::H256};
 
 use crate::errors::Error;
 
@@ -26,7 +26,7 @@ impl IbcClient for ScrollLightClient {
 
     type StorageProof = StorageProof;
 
-    type Encoding = Proto;
+    type Encoding = Bincode;
 
     fn verify_membership(
         ctx: IbcClientCtx<Self>,
@@ -87,7 +87,7 @@ impl IbcClient for ScrollLightClient {
         )
         .map_err(Into::<Error>::into)?;
 
-        todo!()
+        Ok(())
     }
 
     fn get_timestamp(consensus_state: &Self::ConsensusState) -> u64 {
@@ -98,6 +98,10 @@ impl IbcClient for ScrollLightClient {
         client_state.latest_slot
     }
 
+    fn get_counterparty_chain_id(client_state: &Self::ClientState) -> String {
+        client_state.chain_id.to_string()
+    }
+
     fn status(client_state: &Self::ClientState) -> Status {
         if client_state.frozen_height.height() == 0 {
             Status::Active
@@ -116,6 +120,7 @@ impl IbcClient for ScrollLightClient {
     fn verify_header(
         ctx: IbcClientCtx<Self>,
         header: Header,
+        _caller: Addr,
     ) -> Result<(u64, Self::ClientState, Self::ConsensusState), IbcClientError<Self>> {
         let mut client_state = ctx.read_self_client_state()?;
         verify_header(&ctx, &client_state, &header)?;
@@ -145,7 +150,7 @@ impl IbcClient for ScrollLightClient {
         _ctx: IbcClientCtx<Self>,
         _misbehaviour: Self::Misbehaviour,
     ) -> Result<Self::ClientState, IbcClientError<Self>> {
-        todo!()
+        Err(Error::Unimplemented.into())
     }
 }
 
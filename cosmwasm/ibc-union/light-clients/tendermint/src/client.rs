@@ -6,29 +6,44 @@ use cosmwasm_std::Empty;
 use ibc_union_light_client::{IbcClient, IbcClientCtx, IbcClientError};
 use ibc_union_msg::lightclient::Status;
 use ics23::ibc_api::SDK_SPECS;
-use tendermint_light_client_types::{ClientState, ConsensusState, Header};
+use sha2::{Digest, Sha256};
+use tendermint_light_client_types::{ClientState, ConsensusState, Header, Misbehaviour};
 use tendermint_verifier::types::{HostFns, SignatureVerifier};
 use unionlabs::{
     bounded::BoundedI64,
-    encoding::Bincode,
-    google::protobuf::{duration::Duration, timestamp::Timestamp},
+    encoding::{Bincode, EncodeAs, Proto},
+    google::protobuf::{any::Any, duration::Duration, timestamp::Timestamp},
     ibc::core::{
         client::height::Height,
         commitment::{merkle_proof::MerkleProof, merkle_root::MerkleRoot},
     },
     primitives::{encoding::HexUnprefixed, H256},
+    prost::Message,
+    TypeUrl,
 };
 
 use crate::{
     errors::{
         Error, IbcHeightTooLargeForTendermintHeight, InvalidChainId, InvalidHeaderError,
-        MathOverflow, RevisionNumberMismatch, TrustedValidatorsMismatch,
+        MathOverflow, MigrateClientStoreError, RevisionNumberMismatch, TrustedValidatorsMismatch,
     },
     verifier::{Bls12Verifier, Ed25519Verifier},
 };
 
 pub struct TendermintLightClient;
 
+/// Key under which the cosmos-sdk upgrade module stores the upgraded client state, below
+/// `{upgrade_path}/{height}`.
+pub const UPGRADED_CLIENT_KEY: &str = "upgradedClient";
+
+/// Key under which the cosmos-sdk upgrade module stores the upgraded consensus state, below
+/// `{upgrade_path}/{height}`.
+pub const UPGRADED_CONSENSUS_STATE_KEY: &str = "upgradedConsState";
+
+/// The root of an upgraded consensus state is unknown until the client is updated, this value is
+/// used in place of it so that no proof can be verified against it.
+pub const SENTINEL_ROOT: &[u8] = b"sentinel_root";
+
 impl IbcClient for TendermintLightClient {
     type Error = Error;
 
@@ -36,8 +51,7 @@ impl IbcClient for TendermintLightClient {
 
     type Header = Header;
 
-    // TODO(aeryz): Change this to appropriate misbehavior type when it is implemented
-    type Misbehaviour = Header;
+    type Misbehaviour = Misbehaviour;
 
     type ClientState = ClientState;
 
@@ -117,10 +131,44 @@ impl IbcClient for TendermintLightClient {
     }
 
     fn misbehaviour(
-        _ctx: IbcClientCtx<Self>,
-        _misbehaviour: Self::Misbehaviour,
+        ctx: IbcClientCtx<Self>,
+        misbehaviour: Self::Misbehaviour,
     ) -> Result<Self::ClientState, IbcClientError<Self>> {
-        Err(Error::Unimplemented.into())
+        let mut client_state = ctx.read_self_client_state()?;
+        let consensus_state_a =
+            ctx.read_self_consensus_state(misbehaviour.header_a.trusted_height.height())?;
+        let consensus_state_b =
+            ctx.read_self_consensus_state(misbehaviour.header_b.trusted_height.height())?;
+
+        match misbehaviour
+            .header_a
+            .validator_set
+            .validators
+            .first()
+            .map(|v| &v.pub_key)
+        {
+            Some(PublicKey::Bls12_381(_)) => verify_misbehaviour(
+                &client_state,
+                consensus_state_a,
+                consensus_state_b,
+                misbehaviour,
+                ctx.env.block.time,
+                &SignatureVerifier::new(Bls12Verifier::new(ctx.deps)),
+            )?,
+            Some(PublicKey::Ed25519(_)) => verify_misbehaviour(
+                &client_state,
+                consensus_state_a,
+                consensus_state_b,
+                misbehaviour,
+                ctx.env.block.time,
+                &SignatureVerifier::new(Ed25519Verifier::new(ctx.deps)),
+            )?,
+            _ => return Err(Error::InvalidValidatorSet.into()),
+        }
+
+        client_state.frozen_height = Some(Height::new(1));
+
+        Ok(client_state)
     }
 
     fn status(client_state: &Self::ClientState) -> Status {
@@ -160,6 +208,188 @@ impl IbcClient for TendermintLightClient {
     ) -> Result<(), IbcClientError<Self>> {
         Ok(())
     }
+
+    fn verify_upgrade(
+        ctx: IbcClientCtx<Self>,
+        upgrade_client_state: Self::ClientState,
+        upgrade_consensus_state: Self::ConsensusState,
+        proof_upgrade_client: Self::StorageProof,
+        proof_upgrade_consensus_state: Self::StorageProof,
+    ) -> Result<(u64, Self::ClientState, Self::ConsensusState), IbcClientError<Self>> {
+        let client_state = ctx.read_self_client_state()?;
+        // the upgrade plan must be proven against the latest consensus state
+        let consensus_state = ctx.read_self_consensus_state(client_state.latest_height.height())?;
+
+        Ok(verify_upgrade(
+            client_state,
+            consensus_state,
+            upgrade_client_state,
+            upgrade_consensus_state,
+            proof_upgrade_client,
+            proof_upgrade_consensus_state,
+        )?)
+    }
+
+    fn recover(
+        ctx: IbcClientCtx<Self>,
+        substitute_client_id: u32,
+    ) -> Result<(u64, Self::ClientState, Self::ConsensusState), IbcClientError<Self>> {
+        let client_state = ctx.read_self_client_state()?;
+        let substitute_client_state = ctx.read_client_state::<Self>(substitute_client_id)?;
+        let substitute_consensus_state = ctx.read_consensus_state::<Self>(
+            substitute_client_id,
+            substitute_client_state.latest_height.height(),
+        )?;
+
+        let client_state = recover(client_state, substitute_client_state)?;
+
+        Ok((
+            client_state.latest_height.height(),
+            client_state,
+            substitute_consensus_state,
+        ))
+    }
+}
+
+/// Verify that the counterparty committed to `upgrade_client_state` and `upgrade_consensus_state`
+/// under its upgrade path at the latest height of the client. The parameters chosen by the client
+/// are kept, the ones chosen by the chain are taken from the upgraded client state.
+pub fn verify_upgrade(
+    client_state: ClientState,
+    consensus_state: ConsensusState,
+    upgrade_client_state: ClientState,
+    upgrade_consensus_state: ConsensusState,
+    proof_upgrade_client: MerkleProof,
+    proof_upgrade_consensus_state: MerkleProof,
+) -> Result<(u64, ClientState, ConsensusState), Error> {
+    if client_state.upgrade_path.is_empty() {
+        return Err(Error::UpgradePathNotSet);
+    }
+
+    if upgrade_client_state.latest_height <= client_state.latest_height {
+        return Err(Error::UpgradedHeightNotGreater {
+            upgraded_height: upgrade_client_state.latest_height,
+            latest_height: client_state.latest_height,
+        });
+    }
+
+    let last_height = client_state.latest_height.height();
+
+    // the chain only commits to the fields it chooses
+    let mut committed_client_state =
+        protos::ibc::lightclients::tendermint::v1::ClientState::from(upgrade_client_state.clone());
+    committed_client_state.trust_level = None;
+    committed_client_state.trusting_period = None;
+    committed_client_state.max_clock_drift = None;
+    committed_client_state.frozen_height = None;
+
+    ics23::ibc_api::verify_membership(
+        &proof_upgrade_client,
+        &SDK_SPECS,
+        &consensus_state.root,
+        &upgrade_merkle_path(&client_state.upgrade_path, last_height, UPGRADED_CLIENT_KEY),
+        protos::google::protobuf::Any {
+            type_url: <ClientState as TypeUrl>::type_url(),
+            value: committed_client_state.encode_to_vec().into(),
+        }
+        .encode_to_vec(),
+    )
+    .map_err(Error::VerifyMembership)?;
+
+    ics23::ibc_api::verify_membership(
+        &proof_upgrade_consensus_state,
+        &SDK_SPECS,
+        &consensus_state.root,
+        &upgrade_merkle_path(
+            &client_state.upgrade_path,
+            last_height,
+            UPGRADED_CONSENSUS_STATE_KEY,
+        ),
+        Any(upgrade_consensus_state.clone()).encode_as::<Proto>(),
+    )
+    .map_err(Error::VerifyMembership)?;
+
+    let client_state = ClientState {
+        chain_id: upgrade_client_state.chain_id,
+        unbonding_period: upgrade_client_state.unbonding_period,
+        latest_height: upgrade_client_state.latest_height,
+        proof_specs: upgrade_client_state.proof_specs,
+        upgrade_path: upgrade_client_state.upgrade_path,
+        frozen_height: None,
+        ..client_state
+    };
+
+    let consensus_state = ConsensusState {
+        root: MerkleRoot {
+            hash: <[u8; 32]>::from(Sha256::digest(SENTINEL_ROOT)).into(),
+        },
+        ..upgrade_consensus_state
+    };
+
+    Ok((
+        client_state.latest_height.height(),
+        client_state,
+        consensus_state,
+    ))
+}
+
+/// Build the merkle path `[..upgrade_path, {last key}/{height}/{key}]` the upgrade module stores
+/// the upgraded states under.
+pub fn upgrade_merkle_path(upgrade_path: &[String], height: u64, key: &str) -> Vec<Vec<u8>> {
+    let mut path = upgrade_path
+        .iter()
+        .map(|key| key.as_bytes().to_vec())
+        .collect::<Vec<_>>();
+
+    if let Some(last) = path.last_mut() {
+        last.extend_from_slice(format!("/{height}/{key}").as_bytes());
+    }
+
+    path
+}
+
+/// Check that `substitute_client_state` only differs from `client_state` in the fields that are
+/// allowed to change and is ahead of it, and return the recovered client state.
+pub fn recover(
+    client_state: ClientState,
+    substitute_client_state: ClientState,
+) -> Result<ClientState, Error> {
+    if substitute_client_state
+        .frozen_height
+        .unwrap_or_default()
+        .height()
+        != 0
+    {
+        return Err(MigrateClientStoreError::SubstituteClientFrozen.into());
+    }
+
+    let substitute_client_state = ClientState {
+        frozen_height: None,
+        ..substitute_client_state
+    };
+
+    let recovered_client_state = ClientState {
+        chain_id: substitute_client_state.chain_id.clone(),
+        trusting_period: substitute_client_state.trusting_period,
+        latest_height: substitute_client_state.latest_height,
+        frozen_height: None,
+        ..client_state
+    };
+
+    if recovered_client_state != substitute_client_state {
+        return Err(MigrateClientStoreError::MigrateFieldsChanged.into());
+    }
+
+    // recovering must not move the client back to a height it has already been updated past
+    if substitute_client_state.latest_height <= client_state.latest_height {
+        return Err(MigrateClientStoreError::SubstituteHeightNotGreater {
+            substitute_height: substitute_client_state.latest_height,
+            subject_height: client_state.latest_height,
+        }
+        .into());
+    }
+
+    Ok(recovered_client_state)
 }
 
 pub fn verify_header<V: HostFns>(
@@ -267,6 +497,61 @@ pub fn verify_header<V: HostFns>(
     ))
 }
 
+/// Verifies that both headers of the misbehaviour are valid updates for the client, and that
+/// together they prove the counterparty chain misbehaved.
+pub fn verify_misbehaviour<V: HostFns>(
+    client_state: &ClientState,
+    consensus_state_a: ConsensusState,
+    consensus_state_b: ConsensusState,
+    misbehaviour: Misbehaviour,
+    block_timestamp: cosmwasm_std::Timestamp,
+    signature_verifier: &SignatureVerifier<V>,
+) -> Result<(), Error> {
+    check_misbehaviour(&misbehaviour.header_a, &misbehaviour.header_b)?;
+
+    // without this, anyone would be able to freeze the client with fabricated headers
+    verify_header(
+        client_state.clone(),
+        consensus_state_a,
+        misbehaviour.header_a,
+        block_timestamp,
+        signature_verifier,
+    )?;
+    verify_header(
+        client_state.clone(),
+        consensus_state_b,
+        misbehaviour.header_b,
+        block_timestamp,
+        signature_verifier,
+    )?;
+
+    Ok(())
+}
+
+/// Checks whether two headers are evidence of misbehaviour, without verifying the headers
+/// themselves. This is the case if either:
+///
+/// - two different blocks were committed at the same height (duplicate vote), or
+/// - the higher block does not have a later timestamp than the lower one (BFT time violation).
+pub fn check_misbehaviour(header_a: &Header, header_b: &Header) -> Result<(), Error> {
+    let height_a = header_a.signed_header.header.height;
+    let height_b = header_b.signed_header.header.height;
+
+    if height_a < height_b {
+        return Err(Error::InvalidMisbehaviourHeaderSequence);
+    }
+
+    if height_a == height_b {
+        if header_a.signed_header.commit.block_id != header_b.signed_header.commit.block_id {
+            return Ok(());
+        }
+    } else if header_a.signed_header.header.time <= header_b.signed_header.header.time {
+        return Ok(());
+    }
+
+    Err(Error::MisbehaviourNotFound)
+}
+
 pub fn set_total_voting_power(validator_set: &mut ValidatorSet) -> Result<(), MathOverflow> {
     validator_set.total_voting_power =
         validator_set
@@ -404,6 +689,260 @@ pub fn verify_non_membership(
     .map_err(Error::VerifyMembership)
 }
 
+#[cfg(test)]
+mod tests {
+    use std::{fs, num::NonZeroU64};
+
+    use cosmwasm_std::testing::mock_dependencies;
+    use tendermint_light_client_types::Fraction;
+
+    use super::*;
+
+    fn header(height: u64) -> Header {
+        serde_json::from_str(&fs::read_to_string(format!("src/test/{height}.json")).unwrap())
+            .unwrap()
+    }
+
+    fn client_state() -> ClientState {
+        ClientState {
+            chain_id: "simd-devnet-1".to_owned(),
+            trust_level: Fraction {
+                numerator: 1,
+                denominator: NonZeroU64::new(3).unwrap(),
+            },
+            trusting_period: Duration::new(1_209_600, 0).unwrap(),
+            unbonding_period: Duration::new(1_814_400, 0).unwrap(),
+            max_clock_drift: Duration::new(10, 0).unwrap(),
+            frozen_height: None,
+            latest_height: Height::new_with_revision(1, 288),
+            proof_specs: vec![],
+            upgrade_path: vec![],
+            contract_address: H256::default(),
+        }
+    }
+
+    fn consensus_state(header: &Header) -> ConsensusState {
+        ConsensusState {
+            timestamp: header.signed_header.header.time,
+            root: MerkleRoot {
+                hash: (*header.signed_header.header.app_hash.get()).into(),
+            },
+            next_validators_hash: header.signed_header.header.next_validators_hash,
+        }
+    }
+
+    fn block_timestamp(header: &Header) -> cosmwasm_std::Timestamp {
+        cosmwasm_std::Timestamp::from_nanos(header.signed_header.header.time.as_unix_nanos())
+    }
+
+    #[test]
+    fn check_misbehaviour_identical_headers() {
+        assert_eq!(
+            check_misbehaviour(&header(291), &header(291)),
+            Err(Error::MisbehaviourNotFound)
+        );
+    }
+
+    #[test]
+    fn check_misbehaviour_duplicate_vote() {
+        let mut conflicting_header = header(291);
+        conflicting_header.signed_header.commit.block_id.hash = Some(H256::new([0xAA; 32]));
+
+        assert_eq!(
+            check_misbehaviour(&header(291), &conflicting_header),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_misbehaviour_bft_time_violation() {
+        let header_a = header(291);
+
+        let mut header_b = header(288);
+        header_b.signed_header.header.time = header_a.signed_header.header.time;
+
+        assert_eq!(check_misbehaviour(&header_a, &header_b), Ok(()));
+    }
+
+    #[test]
+    fn check_misbehaviour_monotonic_time() {
+        assert_eq!(
+            check_misbehaviour(&header(291), &header(288)),
+            Err(Error::MisbehaviourNotFound)
+        );
+    }
+
+    #[test]
+    fn check_misbehaviour_invalid_header_sequence() {
+        assert_eq!(
+            check_misbehaviour(&header(288), &header(291)),
+            Err(Error::InvalidMisbehaviourHeaderSequence)
+        );
+    }
+
+    #[test]
+    fn verify_header_works() {
+        let deps = mock_dependencies();
+
+        let trusted_header = header(288);
+        let update_header = header(291);
+
+        let (height, _, consensus_state) = verify_header(
+            client_state(),
+            consensus_state(&trusted_header),
+            update_header.clone(),
+            block_timestamp(&update_header),
+            &SignatureVerifier::new(Ed25519Verifier::new(deps.as_ref())),
+        )
+        .unwrap();
+
+        assert_eq!(height, 291);
+        assert_eq!(
+            consensus_state.timestamp,
+            update_header.signed_header.header.time
+        );
+    }
+
+    #[test]
+    fn verify_header_fails_when_trusted_header_expired() {
+        let deps = mock_dependencies();
+
+        let trusted_header = header(288);
+        let update_header = header(291);
+
+        let mut client_state = client_state();
+        client_state.trusting_period = Duration::new(1, 0).unwrap();
+
+        assert!(matches!(
+            verify_header(
+                client_state,
+                consensus_state(&trusted_header),
+                update_header.clone(),
+                block_timestamp(&update_header),
+                &SignatureVerifier::new(Ed25519Verifier::new(deps.as_ref())),
+            ),
+            Err(Error::TendermintVerify(
+                tendermint_verifier::error::Error::HeaderExpired { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn verify_misbehaviour_rejects_forged_header() {
+        let deps = mock_dependencies();
+
+        let trusted_header = header(288);
+        let update_header = header(291);
+
+        let mut forged_header = update_header.clone();
+        forged_header.signed_header.commit.block_id.hash = Some(H256::new([0xAA; 32]));
+
+        assert!(matches!(
+            verify_misbehaviour(
+                &client_state(),
+                consensus_state(&trusted_header),
+                consensus_state(&trusted_header),
+                Misbehaviour {
+                    header_a: update_header.clone(),
+                    header_b: forged_header,
+                },
+                block_timestamp(&update_header),
+                &SignatureVerifier::new(Ed25519Verifier::new(deps.as_ref())),
+            ),
+            Err(Error::TendermintVerify(_))
+        ));
+    }
+
+    #[test]
+    fn recover_works() {
+        let mut subject_client_state = client_state();
+        subject_client_state.frozen_height = Some(Height::new(1));
+
+        let mut substitute_client_state = client_state();
+        substitute_client_state.latest_height = Height::new_with_revision(1, 300);
+        substitute_client_state.trusting_period = Duration::new(1_000_000, 0).unwrap();
+
+        assert_eq!(
+            recover(subject_client_state, substitute_client_state.clone()),
+            Ok(substitute_client_state)
+        );
+    }
+
+    #[test]
+    fn recover_fails_when_substitute_frozen() {
+        let mut substitute_client_state = client_state();
+        substitute_client_state.frozen_height = Some(Height::new(1));
+
+        assert_eq!(
+            recover(client_state(), substitute_client_state),
+            Err(MigrateClientStoreError::SubstituteClientFrozen.into())
+        );
+    }
+
+    #[test]
+    fn recover_fails_when_invalid_change() {
+        let mut substitute_client_state = client_state();
+        substitute_client_state.max_clock_drift = Duration::new(20, 0).unwrap();
+
+        assert_eq!(
+            recover(client_state(), substitute_client_state),
+            Err(MigrateClientStoreError::MigrateFieldsChanged.into())
+        );
+    }
+
+    #[test]
+    fn recover_fails_when_substitute_not_ahead() {
+        let mut subject_client_state = client_state();
+        subject_client_state.latest_height = Height::new_with_revision(1, 300);
+
+        for height in [288, 300] {
+            let mut substitute_client_state = client_state();
+            substitute_client_state.latest_height = Height::new_with_revision(1, height);
+
+            assert_eq!(
+                recover(subject_client_state.clone(), substitute_client_state),
+                Err(MigrateClientStoreError::SubstituteHeightNotGreater {
+                    substitute_height: Height::new_with_revision(1, height),
+                    subject_height: Height::new_with_revision(1, 300),
+                }
+                .into())
+            );
+        }
+    }
+
+    #[test]
+    fn upgrade_merkle_path_works() {
+        assert_eq!(
+            upgrade_merkle_path(
+                &["upgrade".to_owned(), "upgradedIBCState".to_owned()],
+                288,
+                UPGRADED_CLIENT_KEY
+            ),
+            vec![
+                b"upgrade".to_vec(),
+                b"upgradedIBCState/288/upgradedClient".to_vec()
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_upgrade_fails_without_upgrade_path() {
+        let header = header(288);
+
+        assert_eq!(
+            verify_upgrade(
+                client_state(),
+                consensus_state(&header),
+                client_state(),
+                consensus_state(&header),
+                MerkleProof { proofs: vec![] },
+                MerkleProof { proofs: vec![] },
+            ),
+            Err(Error::UpgradePathNotSet)
+        );
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use std::fs;
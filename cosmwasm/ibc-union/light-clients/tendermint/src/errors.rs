@@ -2,7 +2,7 @@ use ibc_union_light_client::IbcClientError;
 use tendermint_light_client_types::{ClientState, Header};
 use unionlabs::{
     encoding::{DecodeErrorOf, Proto},
-    ibc::core::commitment::merkle_proof::MerkleProof,
+    ibc::core::{client::height::Height, commitment::merkle_proof::MerkleProof},
     primitives::H256,
 };
 
@@ -60,6 +60,23 @@ pub enum Error {
 
     #[error("invalid or empty validator set, supported keys are: bls12381 and ed25519")]
     InvalidValidatorSet,
+
+    #[error("header_a.height should be greater than or equal to header_b.height")]
+    InvalidMisbehaviourHeaderSequence,
+
+    #[error("given headers don't prove a misbehaviour")]
+    MisbehaviourNotFound,
+
+    #[error("the client has no upgrade path and can't be upgraded")]
+    UpgradePathNotSet,
+
+    #[error(
+        "upgraded height ({upgraded_height}) must be greater than the latest height ({latest_height})"
+    )]
+    UpgradedHeightNotGreater {
+        upgraded_height: Height,
+        latest_height: Height,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, thiserror::Error)]
@@ -92,6 +109,15 @@ pub enum MigrateClientStoreError {
 
     #[error("forbidden fields have been changed during state migration")]
     MigrateFieldsChanged,
+
+    #[error(
+        "substitute client height ({substitute_height}) must be greater than the subject client \
+        height ({subject_height})"
+    )]
+    SubstituteHeightNotGreater {
+        substitute_height: Height,
+        subject_height: Height,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, thiserror::Error)]
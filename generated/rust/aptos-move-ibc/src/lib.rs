@@ -11,6 +11,56 @@
 pub mod channel_handshake {
     pub trait ClientExt {
         fn client(&self) -> &::move_bindgen::aptos_rest_client::Client;
+        fn channel_close_confirm(
+            &self,
+            contract_address: ::move_bindgen::aptos_types::account_address::AccountAddress,
+            (_0, _1, _2, _3): (
+                ::move_bindgen::aptos_types::account_address::AccountAddress,
+                u32,
+                Vec<u8>,
+                u64,
+            ),
+            (t0,): (impl Into<::move_bindgen::move_core_types::language_storage::TypeTag>,),
+        ) -> ::move_bindgen::aptos_types::transaction::EntryFunction {
+            ::move_bindgen::aptos_types::transaction::EntryFunction::new(
+                ::move_bindgen::aptos_rest_client::aptos_api_types::MoveModuleId {
+                    address: contract_address.into(),
+                    name: stringify!(channel_handshake).parse().unwrap(),
+                }
+                .into(),
+                stringify!(channel_close_confirm).parse().unwrap(),
+                vec![t0.into().into()],
+                vec![
+                    ::move_bindgen::bcs::to_bytes(&_0).unwrap(),
+                    ::move_bindgen::bcs::to_bytes(&_1).unwrap(),
+                    ::move_bindgen::bcs::to_bytes(&_2).unwrap(),
+                    ::move_bindgen::bcs::to_bytes(&_3).unwrap(),
+                ],
+            )
+        }
+        fn channel_close_init(
+            &self,
+            contract_address: ::move_bindgen::aptos_types::account_address::AccountAddress,
+            (_0, _1): (
+                ::move_bindgen::aptos_types::account_address::AccountAddress,
+                u32,
+            ),
+            (t0,): (impl Into<::move_bindgen::move_core_types::language_storage::TypeTag>,),
+        ) -> ::move_bindgen::aptos_types::transaction::EntryFunction {
+            ::move_bindgen::aptos_types::transaction::EntryFunction::new(
+                ::move_bindgen::aptos_rest_client::aptos_api_types::MoveModuleId {
+                    address: contract_address.into(),
+                    name: stringify!(channel_handshake).parse().unwrap(),
+                }
+                .into(),
+                stringify!(channel_close_init).parse().unwrap(),
+                vec![t0.into().into()],
+                vec![
+                    ::move_bindgen::bcs::to_bytes(&_0).unwrap(),
+                    ::move_bindgen::bcs::to_bytes(&_1).unwrap(),
+                ],
+            )
+        }
         fn channel_open_ack(
             &self,
             contract_address: ::move_bindgen::aptos_types::account_address::AccountAddress,
@@ -1269,6 +1319,44 @@ pub mod ibc {
         ::move_bindgen::MoveOutputType,
     )]
     #[serde(crate = "::move_bindgen::serde")]
+    pub struct ChannelCloseInit {
+        pub port_id: String,
+        pub channel_id: u32,
+        pub counterparty_port_id: Vec<u8>,
+        pub counterparty_channel_id: u32,
+        pub connection_id: u32,
+    }
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        ::move_bindgen::serde::Serialize,
+        ::move_bindgen::serde::Deserialize,
+        ::move_bindgen::MoveOutputType,
+    )]
+    #[serde(crate = "::move_bindgen::serde")]
+    pub struct ChannelCloseConfirm {
+        pub port_id: String,
+        pub channel_id: u32,
+        pub counterparty_port_id: Vec<u8>,
+        pub counterparty_channel_id: u32,
+        pub connection_id: u32,
+    }
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        ::move_bindgen::serde::Serialize,
+        ::move_bindgen::serde::Deserialize,
+        ::move_bindgen::MoveOutputType,
+    )]
+    #[serde(crate = "::move_bindgen::serde")]
     pub struct ConnectionOpenConfirm {
         pub connection_id: u32,
         pub client_id: u32,
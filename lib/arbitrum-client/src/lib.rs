@@ -0,0 +1,218 @@
+//! Helpers for reading the state of an Arbitrum rollup as it is settled on its L1.
+//!
+//! The arbitrum light client tracks the L2 through the rollup contract on the L1: it is updated to
+//! L1 heights, and the L2 state at an L1 height is the state of the L2 block asserted by the latest
+//! confirmed node (`_latestConfirmed`) of the rollup contract at that height.
+
+use alloy::{
+    providers::{Provider, RootProvider},
+    rpc::types::{Block, BlockTransactionsKind, Filter},
+    sol_types::SolEvent,
+    transports::{BoxTransport, TransportError},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::{
+    bounded::BoundedU32,
+    primitives::{H160, H256, U256},
+};
+
+/// The location of the rollup state in the rollup contract on the L1. These must be the same
+/// values as are stored in the arbitrum client state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RollupConfig {
+    /// The address of the rollup contract on the L1.
+    pub l1_contract_address: H160,
+    /// The slot of `_latestConfirmed` in the rollup contract.
+    pub l1_next_node_num_slot: U256,
+    /// The offset of `_latestConfirmed` in [`Self::l1_next_node_num_slot`], since it is packed
+    /// with other values.
+    pub l1_next_node_num_slot_offset_bytes: BoundedU32<0, 24>,
+    /// The slot of the `_nodes` mapping in the rollup contract.
+    pub l1_nodes_slot: U256,
+    /// The offset of `confirmData` in the `Node` struct.
+    pub l1_nodes_confirm_data_offset: U256,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("error reading the rollup contract storage")]
+    Storage(#[source] TransportError),
+    #[error("error fetching the `NodeCreated` logs")]
+    Logs(#[source] TransportError),
+    #[error("expected exactly one `NodeCreated` event for node {node_num}, found {found}")]
+    NodeCreatedNotFound { node_num: u64, found: usize },
+    #[error("invalid `NodeCreated` event")]
+    InvalidNodeCreated(#[source] alloy::sol_types::Error),
+    #[error("error fetching L2 block {0}")]
+    L2Block(H256, #[source] TransportError),
+    #[error("L2 block {0} not found")]
+    L2BlockNotFound(H256),
+}
+
+/// Read `_latestConfirmed` from the rollup contract at `l1_height`.
+#[instrument(
+    skip_all,
+    level = "trace",
+    fields(
+        %l1_height,
+        %config.l1_contract_address,
+        %config.l1_next_node_num_slot,
+        %config.l1_next_node_num_slot_offset_bytes,
+    )
+)]
+pub async fn next_node_num(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    l1_height: u64,
+) -> Result<u64, Error> {
+    let raw_slot = l1_provider
+        .get_storage_at(
+            config.l1_contract_address.into(),
+            alloy::primitives::U256::from_be_bytes(config.l1_next_node_num_slot.to_be_bytes()),
+        )
+        .block_id(l1_height.into())
+        .await
+        .map_err(Error::Storage)?
+        .to_be_bytes::<32>();
+
+    debug!(raw_slot = %H256::new(raw_slot));
+
+    let next_node_num = parse_next_node_num(
+        raw_slot,
+        config.l1_next_node_num_slot_offset_bytes.inner() as usize,
+    );
+
+    debug!("l1 height {l1_height} is next node num {next_node_num}");
+
+    Ok(next_node_num)
+}
+
+/// Extract `_latestConfirmed` from the raw value of the slot it is packed in. This must be kept in
+/// sync with the arbitrum verifier.
+fn parse_next_node_num(raw_slot: [u8; 32], slot_offset_bytes: usize) -> u64 {
+    u64::from_be_bytes(
+        raw_slot[slot_offset_bytes..slot_offset_bytes + 8]
+            .try_into()
+            .expect("size is correct; qed;"),
+    )
+}
+
+/// Fetch the `NodeCreated` event of `node_num`, emitted at or before `l1_height`.
+#[instrument(skip_all, level = "trace", fields(%node_num, %l1_height))]
+pub async fn node_created(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    node_num: u64,
+    l1_height: u64,
+) -> Result<NodeCreated, Error> {
+    // NOTE: any modifications made here should also be made to the hubble arbitrum consensus
+    // height indexer
+    let logs = l1_provider
+        .get_logs(
+            &Filter::new()
+                .address(alloy::primitives::Address::from(config.l1_contract_address))
+                .event_signature(NodeCreated::SIGNATURE_HASH)
+                .topic1(alloy::primitives::B256::from(
+                    U256::from(node_num).to_be_bytes(),
+                ))
+                .from_block(0)
+                .to_block(l1_height),
+        )
+        .await
+        .map_err(Error::Logs)?;
+
+    let [log] = <[_; 1]>::try_from(logs).map_err(|logs| Error::NodeCreatedNotFound {
+        node_num,
+        found: logs.len(),
+    })?;
+
+    let node_created = NodeCreated::decode_log(&log.inner, true)
+        .map_err(Error::InvalidNodeCreated)?
+        .data;
+
+    debug!(
+        "node {node_num} was created in L1 block {:?}",
+        log.block_number
+    );
+
+    Ok(node_created)
+}
+
+/// Fetch the L2 block that is settled on the L1 at `l1_height`, i.e. the block asserted by the
+/// latest confirmed node of the rollup.
+#[instrument(skip_all, fields(%l1_height))]
+pub async fn settled_l2_block(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    l2_provider: &RootProvider<BoxTransport>,
+    l1_height: u64,
+) -> Result<Block, Error> {
+    let next_node_num = next_node_num(config, l1_provider, l1_height).await?;
+
+    let node_created = node_created(config, l1_provider, next_node_num, l1_height).await?;
+
+    let l2_block_hash = node_created.assertion.afterState.globalState.bytes32Vals[0];
+
+    let block = l2_provider
+        .get_block_by_hash(l2_block_hash, BlockTransactionsKind::Hashes)
+        .await
+        .map_err(|err| Error::L2Block(l2_block_hash.into(), err))?
+        .ok_or(Error::L2BlockNotFound(l2_block_hash.into()))?;
+
+    debug!("l1 height {l1_height} is l2 height {}", block.header.number);
+
+    Ok(block)
+}
+
+alloy::sol! {
+    // https://github.com/OffchainLabs/nitro-contracts/blob/90037b996509312ef1addb3f9352457b8a99d6a6/src/state/GlobalState.sol
+    struct GlobalState {
+        bytes32[2] bytes32Vals;
+        uint64[2] u64Vals;
+    }
+
+    // https://github.com/OffchainLabs/nitro-contracts/blob/90037b996509312ef1addb3f9352457b8a99d6a6/src/rollup/Node.sol#L10
+    struct ExecutionState {
+        GlobalState globalState;
+        // solidity enums are abi encoded as uint8
+        uint8 machineStatus;
+    }
+
+    // https://github.com/OffchainLabs/nitro-contracts/blob/90037b996509312ef1addb3f9352457b8a99d6a6/src/rollup/Node.sol#L15
+    struct Assertion {
+        ExecutionState beforeState;
+        ExecutionState afterState;
+        uint64 numBlocks;
+    }
+
+    // https://github.com/OffchainLabs/nitro-contracts/blob/90037b996509312ef1addb3f9352457b8a99d6a6/src/rollup/IRollupCore.sol
+    event NodeCreated(
+        uint64 indexed nodeNum,
+        bytes32 indexed parentNodeHash,
+        bytes32 indexed nodeHash,
+        bytes32 executionHash,
+        Assertion assertion,
+        bytes32 afterInboxBatchAcc,
+        bytes32 wasmModuleRoot,
+        uint256 inboxMaxCount
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    #[test]
+    fn parse_next_node_num_at_offset() {
+        // two packed uint64s, 0x3b2 and 0x3c0
+        let raw_slot = hex!("0000000000000000000000000000000000000000000003b200000000000003c0");
+
+        assert_eq!(parse_next_node_num(raw_slot, 24), 0x3c0);
+        assert_eq!(parse_next_node_num(raw_slot, 16), 0x3b2);
+        assert_eq!(parse_next_node_num(raw_slot, 0), 0);
+    }
+}
@@ -10,7 +10,9 @@ use unionlabs::{
 
 use crate::{
     cosmos_sdk::{CosmosKeyring, CosmosSdkChain, CosmosSdkChainRpcs, GasConfig},
-    keyring::{ChainKeyring, ConcurrentKeyring, KeyringConfig, KeyringEntry, SignerBalance},
+    keyring::{
+        ChainKeyring, ConcurrentKeyring, KeyringConfig, KeyringEntry, KeyringError, SignerBalance,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -50,6 +52,7 @@ impl ChainKeyring for Cosmos {
             self.grpc_url.clone(),
         )
         .await
+        .expect("error fetching balances")
     }
 }
 
@@ -89,6 +92,8 @@ pub enum CosmosInitError {
         #[source]
         source: Option<ParseIntError>,
     },
+    #[error("unable to load key")]
+    Keyring(#[from] KeyringError),
 }
 
 impl Cosmos {
@@ -136,17 +141,19 @@ impl Cosmos {
                     // TODO: Make this configurable or fetch it from the chain
                     .map(|entry| {
                         let signer = CosmosSigner::new(
-                            ecdsa::SigningKey::from_bytes(entry.value().as_slice().into())
+                            ecdsa::SigningKey::from_bytes(entry.value()?.as_slice().into())
                                 .expect("invalid private key"),
                             prefix.clone(),
                         );
 
-                        KeyringEntry {
+                        Ok(KeyringEntry {
                             name: entry.name(),
                             address: signer.to_string(),
                             signer,
-                        }
-                    }),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, KeyringError>>()?
+                    .into_iter(),
             ),
             tm_client,
             chain_id,
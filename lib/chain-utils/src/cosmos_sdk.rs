@@ -30,6 +30,7 @@ use unionlabs::{
 use crate::{
     cosmos_sdk::cosmos_sdk_error::CosmosSdkError,
     keyring::{ConcurrentKeyring, SignerBalance},
+    BoxDynError,
 };
 
 pub type CosmosKeyring = ConcurrentKeyring<String, CosmosSigner>;
@@ -457,15 +458,14 @@ pub trait CosmosSdkChainExt: CosmosSdkChainRpcs {
     }
 }
 
-pub async fn fetch_balances(
-    keyring: &CosmosKeyring,
+/// Fetch the balances of `gas_denom` of all keys in `keyring`.
+pub async fn fetch_balances<S: 'static>(
+    keyring: &ConcurrentKeyring<String, S>,
     gas_denom: String,
     grpc_url: String,
-) -> Vec<SignerBalance<String>> {
+) -> Result<Vec<SignerBalance<String>>, BoxDynError> {
     let mut query_client =
-        protos::cosmos::bank::v1beta1::query_client::QueryClient::connect(grpc_url.clone())
-            .await
-            .unwrap();
+        protos::cosmos::bank::v1beta1::query_client::QueryClient::connect(grpc_url.clone()).await?;
 
     // couldn't get fancy stream stuff to work so this will have to do
     let mut out_vec = vec![];
@@ -476,13 +476,11 @@ pub async fn fetch_balances(
                 address: address.clone(),
                 denom: gas_denom.clone(),
             })
-            .await
-            .unwrap()
+            .await?
             .into_inner()
             .balance
-            .unwrap()
-            .try_into()
-            .unwrap();
+            .ok_or("balance missing from response")?
+            .try_into()?;
 
         out_vec.push(SignerBalance {
             key_name: name.to_owned(),
@@ -492,7 +490,7 @@ pub async fn fetch_balances(
         });
     }
 
-    out_vec
+    Ok(out_vec)
 }
 
 fn u128_saturating_mul_f64(u: u128, f: f64) -> u128 {
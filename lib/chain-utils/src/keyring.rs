@@ -1,10 +1,22 @@
-use std::{collections::HashMap, fmt::Display, hash::Hash, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap, fmt::Display, hash::Hash, num::NonZeroU64, path::PathBuf, sync::Arc,
+    time::Duration,
+};
 
+use bip32::secp256k1::ecdsa::{self, SigningKey};
 use crossbeam_queue::ArrayQueue;
+use dashmap::{DashMap, DashSet};
 use futures::Future;
 use rand::prelude::SliceRandom;
 use serde::{Deserialize, Serialize};
-use tracing::{info_span, warn, Instrument};
+use tracing::{error, info, info_span, warn, Instrument};
+use unionlabs::{option_unwrap, ErrorReporter};
+
+use crate::{
+    remote_signer::{RemoteSigner, RemoteSignerError},
+    signer::Secp256k1Signer,
+    BoxDynError,
+};
 
 pub trait ChainKeyring {
     type Address: Hash + Eq + Clone + Display + Send + Sync;
@@ -36,6 +48,26 @@ pub struct ConcurrentKeyring<A: Hash + Eq, S> {
     addresses_buffer: Arc<ArrayQueue<A>>,
 
     signers: Arc<HashMap<A, S>>,
+
+    /// The most recently observed balance of each key, see [`Self::update_balances`].
+    balances: Arc<DashMap<A, SignerBalance<A>>>,
+
+    /// Keys that have been taken out of rotation due to a low balance. These are skipped by
+    /// [`Self::with`] until their balance is topped back up.
+    out_of_rotation: Arc<DashSet<A>>,
+}
+
+/// The reason that [`ConcurrentKeyring::with`] was unable to provide a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum KeyringUnavailable {
+    /// Every key that is in rotation is currently in use. A key will likely be available again
+    /// shortly.
+    #[error("all keys in the keyring are in use")]
+    Busy,
+    /// Every key in the keyring has been taken out of rotation, see
+    /// [`ConcurrentKeyring::update_balances`]. No key will be available until one is topped up.
+    #[error("no key in the keyring is in rotation")]
+    NoKeyInRotation,
 }
 
 pub struct KeyringEntry<A, S> {
@@ -76,6 +108,8 @@ impl<A: Hash + Eq + Clone + Display, S: 'static> ConcurrentKeyring<A, S> {
             key_to_address: Arc::new(key_to_address),
             addresses_buffer: Arc::new(addresses_buffer),
             signers: Arc::new(signers),
+            balances: Arc::new(DashMap::new()),
+            out_of_rotation: Arc::new(DashSet::new()),
         }
     }
 
@@ -83,36 +117,109 @@ impl<A: Hash + Eq + Clone + Display, S: 'static> ConcurrentKeyring<A, S> {
         self.key_to_address.iter().map(|(a, b)| (a.as_str(), b))
     }
 
+    /// Whether the key with the address `address` is currently in rotation.
+    pub fn in_rotation(&self, address: &A) -> bool {
+        !self.out_of_rotation.contains(address)
+    }
+
+    /// The most recently observed balances of all keys in this keyring, as recorded by
+    /// [`Self::update_balances`].
+    pub fn balances(&self) -> Vec<SignerBalance<A>> {
+        self.balances
+            .iter()
+            .map(|balance| balance.value().clone())
+            .collect()
+    }
+
+    /// Record the balances of the keys in this keyring. If `min_balance` is set, keys with a
+    /// balance below it are taken out of rotation until their balance is back above it.
+    pub fn update_balances(
+        &self,
+        balances: impl IntoIterator<Item = SignerBalance<A>>,
+        min_balance: Option<u128>,
+    ) {
+        for balance in balances {
+            let address = balance.address.clone();
+
+            if !self.signers.contains_key(&address) {
+                warn!(keyring = %self.name, %address, "balance reported for unknown key");
+                continue;
+            }
+
+            if let Some(min_balance) = min_balance.filter(|min| balance.balance < *min) {
+                if self.out_of_rotation.insert(address.clone()) {
+                    error!(
+                        keyring = %self.name,
+                        key_name = %balance.key_name,
+                        %address,
+                        balance = %balance.balance,
+                        denom = %balance.denom,
+                        %min_balance,
+                        "signer balance is below the minimum, taking key out of rotation"
+                    );
+                }
+            } else if self.out_of_rotation.remove(&address).is_some() {
+                info!(
+                    keyring = %self.name,
+                    key_name = %balance.key_name,
+                    %address,
+                    balance = %balance.balance,
+                    denom = %balance.denom,
+                    "signer balance has been topped up, putting key back into rotation"
+                );
+            }
+
+            self.balances.insert(address, balance);
+        }
+    }
+
     pub async fn with<'a, F: FnOnce(&'a S) -> Fut + 'a, Fut: Future<Output: 'a> + 'a>(
         &'a self,
         f: F,
-    ) -> Option<Fut::Output> {
-        let Some(address) = self.addresses_buffer.pop() else {
-            warn!(keyring = %self.name, "high traffic in keyring");
-            return None;
-        };
+    ) -> Result<Fut::Output, KeyringUnavailable> {
+        // every key is looked at at most once; keys that are out of rotation are pushed straight
+        // back to the end of the buffer
+        for _ in 0..self.signers.len() {
+            let Some(address) = self.addresses_buffer.pop() else {
+                warn!(keyring = %self.name, "high traffic in keyring");
+                return Err(KeyringUnavailable::Busy);
+            };
+
+            if !self.in_rotation(&address) {
+                self.addresses_buffer
+                    .push(address)
+                    .ok()
+                    .expect("no additional items are added; qed;");
+
+                continue;
+            }
+
+            let key_name = self
+                .address_to_key
+                .get(&address)
+                .expect("key is present; qed;");
+            let secret = self.signers.get(&address).expect("key is present; qed;");
+
+            let r = f(secret)
+                .instrument(info_span!(
+                    "using signer",
+                    keyring = %self.name,
+                    %key_name,
+                    %address
+                ))
+                .await;
 
-        let key_name = self
-            .address_to_key
-            .get(&address)
-            .expect("key is present; qed;");
-        let secret = self.signers.get(&address).expect("key is present; qed;");
-
-        let r = f(secret)
-            .instrument(info_span!(
-                "using signer",
-                keyring = %self.name,
-                %key_name,
-                %address
-            ))
-            .await;
-
-        self.addresses_buffer
-            .push(address)
-            .ok()
-            .expect("no additional items are added; qed;");
-
-        Some(r)
+            self.addresses_buffer
+                .push(address)
+                .ok()
+                .expect("no additional items are added; qed;");
+
+            return Ok(r);
+        }
+
+        error!(keyring = %self.name, "no keys in keyring are in rotation");
+
+        Err(KeyringUnavailable::NoKeyInRotation)
     }
 }
 
@@ -124,22 +231,111 @@ pub struct KeyringConfig {
     pub keys: Vec<KeyringConfigEntry>,
 }
 
+/// Configuration for monitoring the balances of the keys in a keyring.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BalanceMonitorConfig {
+    /// Keys with a balance below this amount (in the smallest unit of the gas denom) are taken
+    /// out of rotation until they are topped back up. If unset, balances are only reported.
+    #[serde(default, with = "::serde_utils::string_opt")]
+    pub min_balance: Option<u128>,
+    /// How often to fetch the balances of all keys.
+    #[serde(default = "default_interval_seconds")]
+    pub interval_seconds: NonZeroU64,
+}
+
+impl Default for BalanceMonitorConfig {
+    fn default() -> Self {
+        Self {
+            min_balance: None,
+            interval_seconds: default_interval_seconds(),
+        }
+    }
+}
+
+#[must_use]
+#[inline]
+pub const fn default_interval_seconds() -> NonZeroU64 {
+    option_unwrap!(NonZeroU64::new(60))
+}
+
+/// Periodically fetch the balances of all keys in `keyring` with `fetch`, recording them with
+/// [`ConcurrentKeyring::update_balances`]. This never returns, and is intended to be spawned as a
+/// background task.
+pub async fn monitor_balances<A, S, F, Fut>(
+    keyring: ConcurrentKeyring<A, S>,
+    config: BalanceMonitorConfig,
+    fetch: F,
+) where
+    A: Hash + Eq + Clone + Display,
+    S: 'static,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Vec<SignerBalance<A>>, BoxDynError>>,
+{
+    loop {
+        match fetch().await {
+            Ok(balances) => keyring.update_balances(balances, config.min_balance),
+            Err(err) => warn!(
+                keyring = %keyring.name,
+                err = %ErrorReporter(&*err),
+                "unable to fetch signer balances"
+            ),
+        }
+
+        tokio::time::sleep(Duration::from_secs(config.interval_seconds.get())).await;
+    }
+}
+
 impl KeyringConfigEntry {
-    pub fn value(&self) -> Vec<u8> {
+    pub fn name(&self) -> String {
         match &self {
-            KeyringConfigEntry::File { path: _ } => {
-                panic!("file keyring is currently unimplemented")
-            }
-            KeyringConfigEntry::Raw { name: _, key } => key.clone(),
+            KeyringConfigEntry::File { name, .. }
+            | KeyringConfigEntry::Raw { name, .. }
+            | KeyringConfigEntry::Remote { name, .. } => name.clone(),
         }
     }
 
-    pub fn name(&self) -> String {
+    /// The raw private key of this entry, decrypting it if it is stored in a keystore.
+    ///
+    /// Keys held by a remote signer can't be exported, and will return
+    /// [`KeyringError::RemoteKey`].
+    pub fn value(&self) -> Result<Vec<u8>, KeyringError> {
         match &self {
-            KeyringConfigEntry::File { path: _ } => {
-                panic!("file keyring is currently unimplemented")
+            KeyringConfigEntry::File {
+                name: _,
+                path,
+                password_env,
+            } => {
+                let password =
+                    std::env::var(password_env).map_err(|source| KeyringError::Password {
+                        password_env: password_env.clone(),
+                        source,
+                    })?;
+
+                eth_keystore::decrypt_key(path, password).map_err(|source| KeyringError::Keystore {
+                    path: path.clone(),
+                    source,
+                })
             }
-            KeyringConfigEntry::Raw { name, key: _ } => name.clone(),
+            KeyringConfigEntry::Raw { name: _, key } => Ok(key.clone()),
+            KeyringConfigEntry::Remote { name, .. } => Err(KeyringError::RemoteKey(name.clone())),
+        }
+    }
+
+    /// Load this entry as a secp256k1 signer. For remote entries, this connects to the signer
+    /// and fetches the public key of the configured key.
+    pub async fn secp256k1_signer(&self) -> Result<Secp256k1Signer, KeyringError> {
+        match &self {
+            KeyringConfigEntry::Remote {
+                name: _,
+                url,
+                key_id,
+            } => Ok(Secp256k1Signer::Remote(
+                RemoteSigner::connect(url, key_id.clone()).await?,
+            )),
+            _ => Ok(Secp256k1Signer::Local(
+                SigningKey::from_slice(&self.value()?).map_err(KeyringError::InvalidKey)?,
+            )),
         }
     }
 }
@@ -147,12 +343,158 @@ impl KeyringConfigEntry {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum KeyringConfigEntry {
+    /// A key stored in an encrypted Ethereum V3 JSON keystore.
     File {
+        name: String,
         path: PathBuf,
+        /// The environment variable to read the password of the keystore from.
+        password_env: String,
     },
     Raw {
         name: String,
         #[serde(with = "::serde_utils::hex_string")]
         key: Vec<u8>,
     },
+    /// A key held by a remote signer, see [`RemoteSigner`].
+    Remote {
+        name: String,
+        /// The JSON-RPC endpoint of the signer.
+        url: String,
+        /// The id of the key on the signer.
+        key_id: String,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeyringError {
+    #[error("unable to read keystore password from ${password_env}")]
+    Password {
+        password_env: String,
+        #[source]
+        source: std::env::VarError,
+    },
+    #[error("unable to decrypt keystore {}", path.display())]
+    Keystore {
+        path: PathBuf,
+        #[source]
+        source: eth_keystore::KeystoreError,
+    },
+    #[error("key {0} is held by a remote signer and can't be exported")]
+    RemoteKey(String),
+    #[error("invalid secp256k1 private key")]
+    InvalidKey(#[source] ecdsa::Error),
+    #[error(transparent)]
+    RemoteSigner(#[from] RemoteSignerError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_entry_decrypts_keystore() {
+        let dir = std::env::temp_dir().join("chain-utils-keyring-test");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let key = [0x42; 32];
+
+        eth_keystore::encrypt_key(
+            &dir,
+            &mut rand::thread_rng(),
+            key,
+            "hunter2",
+            Some("keystore.json"),
+        )
+        .unwrap();
+
+        std::env::set_var("CHAIN_UTILS_KEYRING_TEST_PASSWORD", "hunter2");
+
+        let entry = KeyringConfigEntry::File {
+            name: "key".to_owned(),
+            path: dir.join("keystore.json"),
+            password_env: "CHAIN_UTILS_KEYRING_TEST_PASSWORD".to_owned(),
+        };
+
+        assert_eq!(entry.value().unwrap(), key);
+
+        std::env::set_var("CHAIN_UTILS_KEYRING_TEST_PASSWORD", "hunter3");
+
+        assert!(matches!(
+            entry.value().unwrap_err(),
+            KeyringError::Keystore { .. }
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn balance(key_name: &str, address: u8, balance: u128) -> SignerBalance<u8> {
+        SignerBalance {
+            key_name: key_name.to_owned(),
+            address,
+            balance,
+            denom: "muno".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_below_min_balance_are_taken_out_of_rotation() {
+        let keyring = ConcurrentKeyring::new(
+            "test",
+            [("alice", 1), ("bob", 2)]
+                .into_iter()
+                .map(|(name, address)| KeyringEntry {
+                    name: name.to_owned(),
+                    address,
+                    signer: name,
+                }),
+        );
+
+        keyring.update_balances([balance("alice", 1, 5), balance("bob", 2, 100)], Some(10));
+
+        assert!(!keyring.in_rotation(&1));
+        assert!(keyring.in_rotation(&2));
+
+        for _ in 0..4 {
+            assert_eq!(keyring.with(|s| async move { *s }).await, Ok("bob"));
+        }
+
+        keyring.update_balances([balance("bob", 2, 5)], Some(10));
+
+        assert_eq!(
+            keyring.with(|s| async move { *s }).await,
+            Err(KeyringUnavailable::NoKeyInRotation)
+        );
+
+        // topping up a key puts it back into rotation
+        keyring.update_balances([balance("alice", 1, 10)], Some(10));
+
+        assert!(keyring.in_rotation(&1));
+        assert_eq!(keyring.with(|s| async move { *s }).await, Ok("alice"));
+
+        let mut balances = keyring.balances();
+        balances.sort_by_key(|b| b.address);
+
+        assert_eq!(
+            balances.iter().map(|b| b.balance).collect::<Vec<_>>(),
+            [10, 5]
+        );
+    }
+
+    #[test]
+    fn balance_monitor_interval_must_be_non_zero() {
+        let config = |interval_seconds: u64| {
+            serde_json::from_value::<BalanceMonitorConfig>(serde_json::json!({
+                "interval_seconds": interval_seconds,
+            }))
+        };
+
+        assert!(config(0).is_err());
+        assert_eq!(config(10).unwrap().interval_seconds.get(), 10);
+        assert_eq!(
+            serde_json::from_value::<BalanceMonitorConfig>(serde_json::json!({}))
+                .unwrap()
+                .interval_seconds,
+            default_interval_seconds()
+        );
+    }
 }
@@ -13,4 +13,8 @@ pub mod private_key;
 
 pub mod keyring;
 
+pub mod remote_signer;
+
+pub mod signer;
+
 pub type BoxDynError = Box<dyn core::error::Error + Send + Sync + 'static>;
@@ -0,0 +1,244 @@
+//! A secp256k1 signer that holds its keys outside of the relayer, accessed over JSON-RPC.
+//!
+//! The relayer only ever sees the public key and the signatures returned by the signer, the
+//! private key never enters its memory. Any service implementing [`RemoteSignerRpcServer`] can be
+//! used as a signer.
+
+use bip32::secp256k1::ecdsa::{self, RecoveryId, Signature, VerifyingKey};
+use jsonrpsee::{
+    core::{client, RpcResult},
+    http_client::{HttpClient, HttpClientBuilder},
+    proc_macros::rpc,
+};
+use unionlabs::primitives::{Bytes, H256};
+
+#[rpc(client, server, namespace = "signer")]
+pub trait RemoteSignerRpc {
+    /// The SEC1 encoded public key of the key `key_id`.
+    #[method(name = "publicKey")]
+    async fn public_key(&self, key_id: String) -> RpcResult<Bytes>;
+
+    /// Sign `prehash` with the key `key_id`.
+    ///
+    /// The returned signature is 65 bytes: `r || s`, followed by the recovery id.
+    #[method(name = "signPrehash")]
+    async fn sign_prehash(&self, key_id: String, prehash: H256) -> RpcResult<Bytes>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteSignerError {
+    #[error("unable to build client for remote signer at {url}")]
+    Client {
+        url: String,
+        #[source]
+        source: client::Error,
+    },
+    #[error("error calling remote signer")]
+    Rpc(#[from] client::Error),
+    #[error("remote signer returned an invalid public key")]
+    InvalidPublicKey(#[source] ecdsa::Error),
+    #[error("remote signer returned an invalid signature")]
+    InvalidSignature(#[source] ecdsa::Error),
+    #[error("remote signer returned a signature of length {0}, expected 65")]
+    InvalidSignatureLength(usize),
+    #[error("remote signer returned an invalid recovery id {0}")]
+    InvalidRecoveryId(u8),
+    #[error("remote signer returned a signature that was not produced by key {key_id}")]
+    SignatureMismatch { key_id: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    client: HttpClient,
+    key_id: String,
+    public_key: VerifyingKey,
+}
+
+impl RemoteSigner {
+    /// Connect to the remote signer at `url`, fetching the public key of `key_id`.
+    pub async fn connect(url: &str, key_id: String) -> Result<Self, RemoteSignerError> {
+        let client = HttpClientBuilder::default().build(url).map_err(|source| {
+            RemoteSignerError::Client {
+                url: url.to_owned(),
+                source,
+            }
+        })?;
+
+        let public_key = VerifyingKey::from_sec1_bytes(&client.public_key(key_id.clone()).await?)
+            .map_err(RemoteSignerError::InvalidPublicKey)?;
+
+        Ok(Self {
+            client,
+            key_id,
+            public_key,
+        })
+    }
+
+    #[must_use]
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    #[must_use]
+    pub fn public_key(&self) -> &VerifyingKey {
+        &self.public_key
+    }
+
+    /// Sign `prehash` with the remote key. The returned signature is normalized to a low `s`.
+    pub async fn sign_prehash(
+        &self,
+        prehash: [u8; 32],
+    ) -> Result<(Signature, RecoveryId), RemoteSignerError> {
+        let bz = self
+            .client
+            .sign_prehash(self.key_id.clone(), prehash.into())
+            .await?;
+
+        if bz.len() != 65 {
+            return Err(RemoteSignerError::InvalidSignatureLength(bz.len()));
+        }
+
+        let signature =
+            Signature::from_slice(&bz[..64]).map_err(RemoteSignerError::InvalidSignature)?;
+        let recovery_id =
+            RecoveryId::from_byte(bz[64]).ok_or(RemoteSignerError::InvalidRecoveryId(bz[64]))?;
+
+        // normalizing s flips the parity of the y coordinate of R
+        let (signature, recovery_id) = match signature.normalize_s() {
+            Some(signature) => (
+                signature,
+                RecoveryId::new(!recovery_id.is_y_odd(), recovery_id.is_x_reduced()),
+            ),
+            None => (signature, recovery_id),
+        };
+
+        // a signature from a different key would otherwise only be caught once it is rejected on
+        // chain
+        let recovered = VerifyingKey::recover_from_prehash(&prehash, &signature, recovery_id)
+            .map_err(RemoteSignerError::InvalidSignature)?;
+
+        if recovered != self.public_key {
+            return Err(RemoteSignerError::SignatureMismatch {
+                key_id: self.key_id.clone(),
+            });
+        }
+
+        Ok((signature, recovery_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bip32::secp256k1::ecdsa::SigningKey;
+    use jsonrpsee::{core::async_trait, server::Server, types::ErrorObject};
+
+    use super::*;
+
+    /// A signer that holds its keys in memory. If `wrong_key` is set, it signs with that key
+    /// instead of the requested one.
+    struct MockSigner {
+        keys: HashMap<String, SigningKey>,
+        wrong_key: Option<SigningKey>,
+    }
+
+    #[async_trait]
+    impl RemoteSignerRpcServer for MockSigner {
+        async fn public_key(&self, key_id: String) -> RpcResult<Bytes> {
+            Ok(self
+                .key(&key_id)?
+                .verifying_key()
+                .to_sec1_bytes()
+                .to_vec()
+                .into())
+        }
+
+        async fn sign_prehash(&self, key_id: String, prehash: H256) -> RpcResult<Bytes> {
+            let key = self.wrong_key.as_ref().map_or(self.key(&key_id), Ok)?;
+
+            let (signature, recovery_id) = key
+                .sign_prehash_recoverable(prehash.get())
+                .map_err(|err| ErrorObject::owned(-1, err.to_string(), None::<()>))?;
+
+            Ok(signature
+                .to_bytes()
+                .into_iter()
+                .chain([recovery_id.to_byte()])
+                .collect::<Vec<_>>()
+                .into())
+        }
+    }
+
+    impl MockSigner {
+        fn key(&self, key_id: &str) -> RpcResult<&SigningKey> {
+            self.keys
+                .get(key_id)
+                .ok_or_else(|| ErrorObject::owned(-1, format!("unknown key {key_id}"), None::<()>))
+        }
+    }
+
+    async fn mock_signer(wrong_key: Option<SigningKey>) -> (String, SigningKey) {
+        let key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+
+        let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", server.local_addr().unwrap());
+
+        let handle = server.start(
+            MockSigner {
+                keys: [("relayer".to_owned(), key.clone())].into_iter().collect(),
+                wrong_key,
+            }
+            .into_rpc(),
+        );
+
+        // keep the server running for the rest of the test
+        tokio::spawn(handle.stopped());
+
+        (url, key)
+    }
+
+    #[tokio::test]
+    async fn sign_prehash() {
+        let (url, key) = mock_signer(None).await;
+
+        let signer = RemoteSigner::connect(&url, "relayer".to_owned())
+            .await
+            .unwrap();
+
+        assert_eq!(signer.public_key(), key.verifying_key());
+
+        let prehash = [0xaa; 32];
+
+        let (signature, recovery_id) = signer.sign_prehash(prehash).await.unwrap();
+
+        assert_eq!(
+            (signature, recovery_id),
+            key.sign_prehash_recoverable(&prehash).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_key() {
+        let (url, _) = mock_signer(None).await;
+
+        assert!(matches!(
+            RemoteSigner::connect(&url, "unknown".to_owned()).await,
+            Err(RemoteSignerError::Rpc(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn signature_from_wrong_key_is_rejected() {
+        let (url, _) = mock_signer(Some(SigningKey::from_slice(&[0x43; 32]).unwrap())).await;
+
+        let signer = RemoteSigner::connect(&url, "relayer".to_owned())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            signer.sign_prehash([0xaa; 32]).await,
+            Err(RemoteSignerError::SignatureMismatch { .. })
+        ));
+    }
+}
@@ -221,7 +221,7 @@ impl Scroll {
     }
 
     pub async fn scroll_height_of_batch_index(&self, batch_index: u64) -> u64 {
-        let batch = self.scroll_api_client.batch(batch_index).await.batch;
+        let batch = self.scroll_api_client.batch(batch_index).await.unwrap().batch;
 
         debug!(
             "batch index {batch_index} is scroll height range {}..={}",
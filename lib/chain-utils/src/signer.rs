@@ -0,0 +1,97 @@
+use std::fmt::Display;
+
+use bip32::{
+    secp256k1::ecdsa::{self, RecoveryId, Signature, SigningKey, VerifyingKey},
+    PublicKey,
+};
+use sha2::Digest;
+use unionlabs::signer::cosmos_address;
+
+use crate::remote_signer::{RemoteSigner, RemoteSignerError};
+
+/// A secp256k1 key, either held in memory or by a [`RemoteSigner`].
+#[derive(Debug, Clone)]
+pub enum Secp256k1Signer {
+    Local(SigningKey),
+    Remote(RemoteSigner),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error("error signing with local key")]
+    Local(#[from] ecdsa::Error),
+    #[error("error signing with remote signer")]
+    Remote(#[from] RemoteSignerError),
+}
+
+impl Secp256k1Signer {
+    #[must_use]
+    pub fn verifying_key(&self) -> VerifyingKey {
+        match self {
+            Secp256k1Signer::Local(signing_key) => *signing_key.verifying_key(),
+            Secp256k1Signer::Remote(remote_signer) => *remote_signer.public_key(),
+        }
+    }
+
+    /// The SEC1 compressed public key.
+    #[must_use]
+    pub fn public_key(&self) -> [u8; 33] {
+        self.verifying_key().to_bytes()
+    }
+
+    /// Sign `prehash`, returning the signature (normalized to a low `s`) and the recovery id.
+    pub async fn sign_prehash(
+        &self,
+        prehash: [u8; 32],
+    ) -> Result<(Signature, RecoveryId), SignerError> {
+        match self {
+            Secp256k1Signer::Local(signing_key) => {
+                Ok(signing_key.sign_prehash_recoverable(&prehash)?)
+            }
+            Secp256k1Signer::Remote(remote_signer) => {
+                Ok(remote_signer.sign_prehash(prehash).await?)
+            }
+        }
+    }
+}
+
+/// A [`Secp256k1Signer`] for a cosmos sdk account, displayed as the bech32 address of the account.
+///
+/// Unlike [`CosmosSigner`](unionlabs::signer::CosmosSigner), this supports keys held by a remote
+/// signer, and as such signing is async.
+#[derive(Debug, Clone)]
+pub struct CosmosSdkSigner {
+    signer: Secp256k1Signer,
+    address: String,
+}
+
+impl CosmosSdkSigner {
+    #[must_use]
+    pub fn new(signer: Secp256k1Signer, prefix: &str) -> Self {
+        Self {
+            address: cosmos_address(prefix, &signer.public_key()),
+            signer,
+        }
+    }
+
+    #[must_use]
+    pub fn public_key(&self) -> [u8; 33] {
+        self.signer.public_key()
+    }
+
+    /// Sign the sha256 hash of `bytes`, as is done for cosmos sdk transactions.
+    pub async fn try_sign(&self, bytes: &[u8]) -> Result<Signature, SignerError> {
+        let (signature, _) = self
+            .signer
+            .sign_prehash(sha2::Sha256::digest(bytes).into())
+            .await?;
+
+        Ok(signature)
+    }
+}
+
+impl Display for CosmosSdkSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.address)
+    }
+}
@@ -11,7 +11,9 @@ use unionlabs::{
 
 use crate::{
     cosmos_sdk::{CosmosKeyring, CosmosSdkChain, CosmosSdkChainRpcs, GasConfig},
-    keyring::{ChainKeyring, ConcurrentKeyring, KeyringConfig, KeyringEntry, SignerBalance},
+    keyring::{
+        ChainKeyring, ConcurrentKeyring, KeyringConfig, KeyringEntry, KeyringError, SignerBalance,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -51,6 +53,7 @@ impl ChainKeyring for Union {
             self.grpc_url.clone(),
         )
         .await
+        .expect("error fetching balances")
     }
 }
 
@@ -66,6 +69,8 @@ pub enum UnionInitError {
         #[source]
         source: Option<ParseIntError>,
     },
+    #[error("unable to load key")]
+    Keyring(#[from] KeyringError),
 }
 
 impl Union {
@@ -103,17 +108,19 @@ impl Union {
                     // TODO: Make this configurable or fetch it from the chain
                     .map(|entry| {
                         let signer = CosmosSigner::new(
-                            ecdsa::SigningKey::from_bytes(entry.value().as_slice().into())
+                            ecdsa::SigningKey::from_bytes(entry.value()?.as_slice().into())
                                 .expect("invalid private key"),
                             "union".to_owned(),
                         );
 
-                        KeyringEntry {
+                        Ok(KeyringEntry {
                             name: entry.name(),
                             address: signer.to_string(),
                             signer,
-                        }
-                    }),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, KeyringError>>()?
+                    .into_iter(),
             ),
             tm_client,
             chain_id,
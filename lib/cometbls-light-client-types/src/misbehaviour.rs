@@ -47,10 +47,49 @@ pub mod proto {
     }
 }
 
+#[cfg(feature = "ethabi")]
+pub mod ethabi {
+    use alloy::sol_types::SolValue;
+    use unionlabs::{
+        encoding::{Decode, Encode, EthAbi},
+        TryFromEthAbiBytesErrorAlloy,
+    };
+
+    use crate::{
+        header::ethabi::{Error, SolHeader},
+        misbehaviour::Misbehaviour,
+    };
+
+    // the solidity client decodes this as `struct Misbehaviour { Header headerA; Header headerB; }`
+    impl Encode<EthAbi> for Misbehaviour {
+        fn encode(self) -> Vec<u8> {
+            (
+                SolHeader::from(self.header_a),
+                SolHeader::from(self.header_b),
+            )
+                .abi_encode_params()
+        }
+    }
+
+    impl Decode<EthAbi> for Misbehaviour {
+        type Error = TryFromEthAbiBytesErrorAlloy<Error>;
+
+        fn decode(bytes: &[u8]) -> Result<Self, Self::Error> {
+            let (header_a, header_b) = <(SolHeader, SolHeader)>::abi_decode_params(bytes, false)
+                .map_err(TryFromEthAbiBytesErrorAlloy::Decode)?;
+
+            Ok(Self {
+                header_a: header_a.try_into()?,
+                header_b: header_b.try_into()?,
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use unionlabs::{
-        encoding::{Bcs, Bincode, Json, Proto},
+        encoding::{Bcs, Bincode, EthAbi, Json, Proto},
         google::protobuf::timestamp::Timestamp,
         ibc::core::client::height::Height,
         primitives::H256,
@@ -93,11 +132,10 @@ mod tests {
         }
     }
 
-    // TODO: Implement misbehaviour on the solidity stack
-    // #[test]
-    // fn ethabi_iso() {
-    //     assert_codec_iso::<_, EthAbi>(&mk_misbehaviour());
-    // }
+    #[test]
+    fn ethabi_iso() {
+        assert_codec_iso::<_, EthAbi>(&mk_misbehaviour());
+    }
 
     #[test]
     fn bincode_iso() {
@@ -2,7 +2,7 @@ use enumorph::Enumorph;
 use unionlabs::{ibc::core::client::height::Height, primitives::Bytes};
 use voyager_core::ClientType;
 
-use crate::types::{Channel, ChannelId, ClientId, ConnectionId, Packet};
+use crate::types::{Channel, ChannelId, ChannelOrder, ClientId, ConnectionId, Packet};
 
 /// All datagrams that are a part of the IBC union specification.
 #[derive(Debug, Clone, PartialEq, Eq, Enumorph)]
@@ -15,6 +15,7 @@ use crate::types::{Channel, ChannelId, ClientId, ConnectionId, Packet};
 pub enum Datagram {
     CreateClient(MsgCreateClient),
     UpdateClient(MsgUpdateClient),
+    SubmitMisbehaviour(MsgSubmitMisbehaviour),
     ConnectionOpenInit(MsgConnectionOpenInit),
     ConnectionOpenTry(MsgConnectionOpenTry),
     ConnectionOpenAck(MsgConnectionOpenAck),
@@ -40,6 +41,7 @@ impl Datagram {
         match self {
             Self::CreateClient(_) => None,
             Self::UpdateClient(_) => None,
+            Self::SubmitMisbehaviour(_) => None,
             Self::ConnectionOpenInit(_) => None,
             Self::ConnectionOpenTry(msg) => Some(Height::new(msg.proof_height)),
             Self::ConnectionOpenAck(msg) => Some(Height::new(msg.proof_height)),
@@ -48,14 +50,14 @@ impl Datagram {
             Self::ChannelOpenTry(msg) => Some(Height::new(msg.proof_height)),
             Self::ChannelOpenAck(msg) => Some(Height::new(msg.proof_height)),
             Self::ChannelOpenConfirm(msg) => Some(Height::new(msg.proof_height)),
-            Self::ChannelCloseInit(_msg) => todo!(),
-            Self::ChannelCloseConfirm(_msg) => todo!(),
+            Self::ChannelCloseInit(_) => None,
+            Self::ChannelCloseConfirm(msg) => Some(Height::new(msg.proof_height)),
             Self::PacketRecv(msg) => Some(Height::new(msg.proof_height)),
             Self::PacketAcknowledgement(msg) => Some(Height::new(msg.proof_height)),
-            Self::PacketTimeout(_msg) => todo!(),
-            Self::IntentPacketRecv(_msg) => todo!(),
-            Self::BatchSend(_msg) => todo!(),
-            Self::BatchAcks(_msg) => todo!(),
+            Self::PacketTimeout(msg) => Some(Height::new(msg.proof_height)),
+            Self::IntentPacketRecv(_) => None,
+            Self::BatchSend(_) => None,
+            Self::BatchAcks(_) => None,
         }
     }
 
@@ -63,6 +65,7 @@ impl Datagram {
         match self {
             Self::CreateClient(_) => "create_client",
             Self::UpdateClient(_) => "update_client",
+            Self::SubmitMisbehaviour(_) => "submit_misbehaviour",
             Self::ConnectionOpenInit(_) => "connection_open_init",
             Self::ConnectionOpenTry(_) => "connection_open_try",
             Self::ConnectionOpenAck(_) => "connection_open_ack",
@@ -108,6 +111,18 @@ pub struct MsgUpdateClient {
     pub client_message: Bytes,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+pub struct MsgSubmitMisbehaviour {
+    pub client_id: ClientId,
+    pub misbehaviour: Bytes,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(
@@ -174,6 +189,8 @@ pub struct MsgChannelOpenInit {
     pub counterparty_port_id: Bytes,
     pub connection_id: ConnectionId,
     pub version: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ordering: ChannelOrder,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -187,6 +204,8 @@ pub struct MsgChannelOpenTry {
     pub port_id: Bytes,
     pub channel: Channel,
     pub counterparty_version: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ordering: ChannelOrder,
     pub proof_init: Bytes,
     pub proof_height: u64,
 }
@@ -226,7 +245,9 @@ pub struct MsgChannelOpenConfirm {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgChannelCloseInit {}
+pub struct MsgChannelCloseInit {
+    pub channel_id: ChannelId,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -235,7 +256,11 @@ pub struct MsgChannelCloseInit {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgChannelCloseConfirm {}
+pub struct MsgChannelCloseConfirm {
+    pub channel_id: ChannelId,
+    pub proof_init: Bytes,
+    pub proof_height: u64,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -272,7 +297,11 @@ pub struct MsgPacketAcknowledgement {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgPacketTimeout {}
+pub struct MsgPacketTimeout {
+    pub packet: Packet,
+    pub proof: Bytes,
+    pub proof_height: u64,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -281,7 +310,10 @@ pub struct MsgPacketTimeout {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgIntentPacketRecv {}
+pub struct MsgIntentPacketRecv {
+    pub packets: Vec<Packet>,
+    pub market_maker_msgs: Vec<Bytes>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -290,7 +322,10 @@ pub struct MsgIntentPacketRecv {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgBatchSend {}
+pub struct MsgBatchSend {
+    pub source_channel_id: ChannelId,
+    pub packets: Vec<Packet>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -299,4 +334,8 @@ pub struct MsgBatchSend {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgBatchAcks {}
+pub struct MsgBatchAcks {
+    pub source_channel_id: ChannelId,
+    pub packets: Vec<Packet>,
+    pub acks: Vec<Bytes>,
+}
@@ -50,8 +50,8 @@ impl FullEvent {
             Self::ChannelOpenTry(event) => Some(event.connection.counterparty_client_id),
             Self::ChannelOpenAck(event) => Some(event.connection.counterparty_client_id),
             Self::ChannelOpenConfirm(event) => Some(event.connection.counterparty_client_id),
-            Self::ChannelCloseInit(_) => todo!(),
-            Self::ChannelCloseConfirm(_) => todo!(),
+            Self::ChannelCloseInit(event) => Some(event.connection.counterparty_client_id),
+            Self::ChannelCloseConfirm(event) => Some(event.connection.counterparty_client_id),
             Self::PacketSend(event) => Some(event.packet.destination_channel.connection.client_id),
             Self::PacketRecv(event) => Some(event.packet.source_channel.connection.client_id),
             Self::IntentPacketRecv(event) => Some(event.packet.source_channel.connection.client_id),
@@ -237,7 +237,14 @@ pub struct ChannelOpenConfirm {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct ChannelCloseInit {}
+pub struct ChannelCloseInit {
+    pub port_id: Bytes,
+    pub channel_id: ChannelId,
+    pub counterparty_port_id: Bytes,
+    pub counterparty_channel_id: ChannelId,
+    pub connection: Connection,
+    pub version: String,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -246,7 +253,14 @@ pub struct ChannelCloseInit {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct ChannelCloseConfirm {}
+pub struct ChannelCloseConfirm {
+    pub port_id: Bytes,
+    pub channel_id: ChannelId,
+    pub counterparty_port_id: Bytes,
+    pub counterparty_channel_id: ChannelId,
+    pub connection: Connection,
+    pub version: String,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
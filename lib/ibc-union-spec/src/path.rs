@@ -4,7 +4,7 @@ use unionlabs::primitives::{Bytes, H256, U256};
 use voyager_core::IbcStorePathKey;
 
 use super::IbcUnion;
-use crate::types::{Channel, ChannelId, ClientId, Connection, ConnectionId};
+use crate::types::{Channel, ChannelId, ChannelOrder, ClientId, Connection, ConnectionId};
 
 /// 0x0100000000000000000000000000000000000000000000000000000000000000
 pub const COMMITMENT_MAGIC: H256 = {
@@ -21,6 +21,24 @@ pub const CONNECTIONS: U256 = U256::from_limbs([2, 0, 0, 0]);
 pub const CHANNELS: U256 = U256::from_limbs([3, 0, 0, 0]);
 pub const PACKETS: U256 = U256::from_limbs([4, 0, 0, 0]);
 pub const PACKET_ACKS: U256 = U256::from_limbs([5, 0, 0, 0]);
+pub const CHANNEL_ORDERS: U256 = U256::from_limbs([6, 0, 0, 0]);
+pub const NEXT_SEQUENCE_SEND: U256 = U256::from_limbs([7, 0, 0, 0]);
+pub const NEXT_SEQUENCE_RECV: U256 = U256::from_limbs([8, 0, 0, 0]);
+pub const NEXT_SEQUENCE_ACK: U256 = U256::from_limbs([9, 0, 0, 0]);
+pub const PACKET_SEQUENCES: U256 = U256::from_limbs([10, 0, 0, 0]);
+
+/// The value committed under a [`NextSequenceSendPath`], [`NextSequenceRecvPath`] or
+/// [`NextSequenceAckPath`].
+#[must_use]
+pub fn commit_sequence(sequence: u64) -> H256 {
+    H256::new(U256::from(sequence).to_be_bytes())
+}
+
+/// The value committed under a [`ChannelOrderPath`].
+#[must_use]
+pub fn commit_channel_order(order: ChannelOrder) -> H256 {
+    H256::new(U256::from(order as u32).to_be_bytes())
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Enumorph)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -36,6 +54,11 @@ pub enum StorePath {
     Channel(ChannelPath),
     BatchReceipts(BatchReceiptsPath),
     BatchPackets(BatchPacketsPath),
+    ChannelOrder(ChannelOrderPath),
+    NextSequenceSend(NextSequenceSendPath),
+    NextSequenceRecv(NextSequenceRecvPath),
+    NextSequenceAck(NextSequenceAckPath),
+    PacketSequence(PacketSequencePath),
 }
 
 impl StorePath {
@@ -48,6 +71,11 @@ impl StorePath {
             StorePath::Channel(path) => path.key(),
             StorePath::BatchReceipts(path) => path.key(),
             StorePath::BatchPackets(path) => path.key(),
+            StorePath::ChannelOrder(path) => path.key(),
+            StorePath::NextSequenceSend(path) => path.key(),
+            StorePath::NextSequenceRecv(path) => path.key(),
+            StorePath::NextSequenceAck(path) => path.key(),
+            StorePath::PacketSequence(path) => path.key(),
         }
     }
 }
@@ -225,3 +253,151 @@ impl IbcStorePathKey for BatchPacketsPath {
 
     type Value = H256;
 }
+
+/// The ordering of a channel. Channels opened before ordering was introduced have no
+/// value committed under this path and are [`ChannelOrder::Unordered`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+pub struct ChannelOrderPath {
+    pub channel_id: ChannelId,
+}
+
+impl ChannelOrderPath {
+    #[must_use]
+    pub fn key(&self) -> H256 {
+        Keccak256::new()
+            .chain_update(CHANNEL_ORDERS.to_be_bytes())
+            .chain_update(U256::from(self.channel_id).to_be_bytes())
+            .finalize()
+            .into()
+    }
+}
+
+impl IbcStorePathKey for ChannelOrderPath {
+    type Spec = IbcUnion;
+
+    type Value = ChannelOrder;
+}
+
+/// The sequence that will be assigned to the next packet sent on an ordered channel.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+pub struct NextSequenceSendPath {
+    pub channel_id: ChannelId,
+}
+
+impl NextSequenceSendPath {
+    #[must_use]
+    pub fn key(&self) -> H256 {
+        Keccak256::new()
+            .chain_update(NEXT_SEQUENCE_SEND.to_be_bytes())
+            .chain_update(U256::from(self.channel_id).to_be_bytes())
+            .finalize()
+            .into()
+    }
+}
+
+impl IbcStorePathKey for NextSequenceSendPath {
+    type Spec = IbcUnion;
+
+    type Value = u64;
+}
+
+/// The sequence of the next packet expected to be received on an ordered channel.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+pub struct NextSequenceRecvPath {
+    pub channel_id: ChannelId,
+}
+
+impl NextSequenceRecvPath {
+    #[must_use]
+    pub fn key(&self) -> H256 {
+        Keccak256::new()
+            .chain_update(NEXT_SEQUENCE_RECV.to_be_bytes())
+            .chain_update(U256::from(self.channel_id).to_be_bytes())
+            .finalize()
+            .into()
+    }
+}
+
+impl IbcStorePathKey for NextSequenceRecvPath {
+    type Spec = IbcUnion;
+
+    type Value = u64;
+}
+
+/// The sequence of the next packet expected to be acknowledged on an ordered channel.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+pub struct NextSequenceAckPath {
+    pub channel_id: ChannelId,
+}
+
+impl NextSequenceAckPath {
+    #[must_use]
+    pub fn key(&self) -> H256 {
+        Keccak256::new()
+            .chain_update(NEXT_SEQUENCE_ACK.to_be_bytes())
+            .chain_update(U256::from(self.channel_id).to_be_bytes())
+            .finalize()
+            .into()
+    }
+}
+
+impl IbcStorePathKey for NextSequenceAckPath {
+    type Spec = IbcUnion;
+
+    type Value = u64;
+}
+
+/// The commitment of the packet sent with `sequence` on an ordered channel.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+pub struct PacketSequencePath {
+    pub channel_id: ChannelId,
+    pub sequence: u64,
+}
+
+impl PacketSequencePath {
+    #[must_use]
+    pub fn key(&self) -> H256 {
+        Keccak256::new()
+            .chain_update(PACKET_SEQUENCES.to_be_bytes())
+            .chain_update(U256::from(self.channel_id).to_be_bytes())
+            .chain_update(U256::from(self.sequence).to_be_bytes())
+            .finalize()
+            .into()
+    }
+}
+
+impl IbcStorePathKey for PacketSequencePath {
+    type Spec = IbcUnion;
+
+    type Value = H256;
+}
@@ -1,5 +1,5 @@
 pub use crate::types::{
-    channel::{Channel, ChannelState},
+    channel::{Channel, ChannelOrder, ChannelState},
     connection::{Connection, ConnectionState},
     packet::Packet,
 };
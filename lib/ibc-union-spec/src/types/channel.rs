@@ -61,6 +61,36 @@ impl Borrow<u8> for &ChannelState {
     }
 }
 
+/// The delivery guarantee of a channel.
+///
+/// Packets sent over an [`ChannelOrder::Ordered`] channel are sequenced by the
+/// source chain and must be received, acknowledged and timed out in that order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+#[repr(u8)]
+pub enum ChannelOrder {
+    #[default]
+    Unordered = 1,
+    Ordered = 2,
+}
+
+impl TryFrom<u8> for ChannelOrder {
+    type Error = UnknownEnumVariant<u8>;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Unordered),
+            2 => Ok(Self::Ordered),
+            _ => Err(UnknownEnumVariant(value)),
+        }
+    }
+}
+
 #[cfg(feature = "ethabi")]
 pub mod ethabi {
     use std::borrow::Cow;
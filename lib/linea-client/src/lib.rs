@@ -0,0 +1,185 @@
+//! Helpers for reading the state of the Linea rollup as it is finalized on its L1.
+//!
+//! The linea light client tracks the L2 through the rollup contract on the L1: it is updated to L1
+//! heights, and the L2 state at an L1 height is the state of the L2 block finalized in the rollup
+//! contract (`currentL2BlockNumber`) at that height.
+
+use alloy::{
+    providers::{Provider, RootProvider},
+    transports::{BoxTransport, TransportError},
+};
+use linea_types::proof::GetProof;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::primitives::{H160, H256, U256};
+
+/// The location of the rollup state in the rollup contract on the L1. These must be the same
+/// values as are stored in the linea client state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RollupConfig {
+    /// The address of the rollup contract on the L1.
+    pub l1_rollup_contract_address: H160,
+    /// The slot of `currentL2BlockNumber` in the rollup contract.
+    pub l1_rollup_current_l2_block_number_slot: U256,
+    /// The slot of `currentTimestamp` in the rollup contract.
+    pub l1_rollup_current_l2_timestamp_slot: U256,
+    /// The slot of the `stateRootHashes` mapping in the rollup contract.
+    pub l1_rollup_l2_state_root_hashes_slot: U256,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("error reading the rollup contract storage")]
+    Storage(#[source] TransportError),
+    #[error("value of slot {slot} ({value}) does not fit in a u64")]
+    SlotOverflow { slot: U256, value: U256 },
+    #[error("error fetching proof")]
+    Proof(#[source] TransportError),
+}
+
+/// Read `currentL2BlockNumber` from the rollup contract at `l1_height`, i.e. the latest L2 block
+/// that is finalized on the L1 at `l1_height`.
+#[instrument(skip_all, fields(%l1_height))]
+pub async fn finalized_l2_block_number(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    l1_height: u64,
+) -> Result<u64, Error> {
+    let l2_block_number = read_u64_slot(
+        config,
+        l1_provider,
+        config.l1_rollup_current_l2_block_number_slot,
+        l1_height,
+    )
+    .await?;
+
+    debug!("l1 height {l1_height} is l2 height {l2_block_number}");
+
+    Ok(l2_block_number)
+}
+
+/// Read `currentTimestamp` from the rollup contract at `l1_height`, i.e. the timestamp of the L2
+/// block returned by [`finalized_l2_block_number`] at the same height.
+#[instrument(skip_all, fields(%l1_height))]
+pub async fn finalized_l2_timestamp(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    l1_height: u64,
+) -> Result<u64, Error> {
+    read_u64_slot(
+        config,
+        l1_provider,
+        config.l1_rollup_current_l2_timestamp_slot,
+        l1_height,
+    )
+    .await
+}
+
+#[instrument(
+    skip_all,
+    level = "trace",
+    fields(%l1_height, %config.l1_rollup_contract_address, %slot)
+)]
+async fn read_u64_slot(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    slot: U256,
+    l1_height: u64,
+) -> Result<u64, Error> {
+    let raw_slot = l1_provider
+        .get_storage_at(
+            config.l1_rollup_contract_address.into(),
+            alloy::primitives::U256::from_be_bytes(slot.to_be_bytes()),
+        )
+        .block_id(l1_height.into())
+        .await
+        .map_err(Error::Storage)?
+        .to_be_bytes::<32>();
+
+    parse_u64_slot(slot, raw_slot)
+}
+
+fn parse_u64_slot(slot: U256, raw_slot: [u8; 32]) -> Result<u64, Error> {
+    let value = U256::from_be_bytes(raw_slot);
+
+    u64::try_from(value).map_err(|_| Error::SlotOverflow { slot, value })
+}
+
+/// Fetch the sparse merkle proofs of `address` and the storage `keys` of `address` in the L2 state
+/// at `l2_height` with `linea_getProof`, since linea does not store its state in an MPT and as such
+/// does not support `eth_getProof`.
+#[instrument(skip_all, fields(%address, %l2_height))]
+pub async fn get_proof(
+    l2_provider: &RootProvider<BoxTransport>,
+    address: H160,
+    keys: impl IntoIterator<Item = U256>,
+    l2_height: u64,
+) -> Result<GetProof, Error> {
+    l2_provider
+        .raw_request(
+            "linea_getProof".into(),
+            get_proof_params(address, keys, l2_height),
+        )
+        .await
+        .map_err(Error::Proof)
+}
+
+fn get_proof_params(
+    address: H160,
+    keys: impl IntoIterator<Item = U256>,
+    l2_height: u64,
+) -> (H160, Vec<H256>, String) {
+    (
+        address,
+        keys.into_iter()
+            .map(|key| H256::new(key.to_be_bytes()))
+            .collect(),
+        format!("0x{l2_height:x}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    #[test]
+    fn parse_u64_slot_in_range() {
+        let raw_slot = hex!("00000000000000000000000000000000000000000000000000000000009e8f5c");
+
+        assert_eq!(
+            parse_u64_slot(U256::from(0x119_u64), raw_slot).unwrap(),
+            0x9e8f5c
+        );
+    }
+
+    #[test]
+    fn parse_u64_slot_overflow() {
+        let raw_slot = hex!("0000000000000000000000000000000000000000000000010000000000000000");
+
+        assert!(matches!(
+            parse_u64_slot(U256::from(0x119_u64), raw_slot),
+            Err(Error::SlotOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn get_proof_params_format() {
+        let params = get_proof_params(
+            H160::new(hex!("5ff137d4b0fdcd49dca30c7cf57e578a026d2789")),
+            [U256::from(1_u64)],
+            620681,
+        );
+
+        assert_eq!(
+            serde_json::to_value(params).unwrap(),
+            serde_json::json!([
+                "0x5ff137d4b0fdcd49dca30c7cf57e578a026d2789",
+                ["0x0000000000000000000000000000000000000000000000000000000000000001"],
+                "0x97889"
+            ])
+        );
+    }
+}
@@ -5,11 +5,12 @@ use unionlabs::{
 
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct ClientState {
     pub chain_id: U256,
     // TODO: This should be ClientId
-    pub l1_client_id: String,
-    pub l1_latest_height: Height,
+    pub l1_client_id: u32,
+    pub l1_latest_height: u64,
     pub l1_rollup_contract_address: H160,
     pub l1_rollup_current_l2_timestamp_slot: U256,
     pub l1_rollup_current_l2_block_number_slot: U256,
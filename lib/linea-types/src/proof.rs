@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use unionlabs::primitives::Bytes;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 #[serde(rename_all = "camelCase")]
 pub struct MerklePath {
     pub value: Bytes,
@@ -9,6 +10,7 @@ pub struct MerklePath {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 #[serde(rename_all = "camelCase")]
 pub struct InclusionProof {
     pub key: Bytes,
@@ -17,6 +19,7 @@ pub struct InclusionProof {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 #[serde(rename_all = "camelCase")]
 pub struct NonInclusionProof {
     pub key: Bytes,
@@ -27,6 +30,7 @@ pub struct NonInclusionProof {
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 #[serde(untagged)]
 pub enum MerkleProof {
     Inclusion(InclusionProof),
@@ -0,0 +1,462 @@
+//! High-availability mode, allowing multiple instances to share the same queue.
+//!
+//! Coordination is done entirely through postgres [advisory locks], held on a dedicated connection
+//! per instance:
+//!
+//! - One instance holds the leader lock, and is the only instance that should run work that must
+//!   not be done concurrently (optimizer passes, recurring ops, pruning, etc). See
+//!   [`PgQueue::is_leader`].
+//! - Ready items are assigned to one of [`HaConfig::shards`] shards by the chain they are addressed
+//!   to, and each shard is owned by exactly one instance. An instance only dequeues items in the
+//!   shards it owns (or items that can't be assigned to a chain), such that all of the work for a
+//!   chain (including event sources and transaction submission) is done by a single instance and
+//!   signer nonces don't collide. Shards are balanced evenly across all live instances.
+//!
+//! If an instance dies, postgres releases all of it's locks once the connection is closed, and the
+//! remaining instances will pick up the leadership and the orphaned shards on their next
+//! [`PgQueue::rebalance`]. Note that for an instance that is partitioned from the database rather
+//! than killed, this depends on the server noticing that the connection is gone (see
+//! `tcp_keepalives_idle`).
+//!
+//! [advisory locks]: https://www.postgresql.org/docs/current/explicit-locking.html#ADVISORY-LOCKS
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, PgConnection};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::{
+    metrics::{IS_LEADER, OWNED_SHARD_COUNT},
+    PgQueue,
+};
+
+/// The first key of the leader and shard advisory locks.
+const LOCK_NAMESPACE: i32 = 0x766f_7961;
+
+/// The first key of the membership advisory locks, which every instance holds (keyed by it's
+/// backend pid) so that the live instances can be counted.
+const MEMBER_LOCK_NAMESPACE: i32 = 0x766f_7962;
+
+/// The second key of the leader lock. Shard `n` is locked with a second key of `n + 1`.
+const LEADER_LOCK_KEY: i32 = 0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HaConfig {
+    /// The amount of shards to split the ready items into. This must be the same for all instances
+    /// sharing a queue, and should be comfortably larger than the amount of instances.
+    #[serde(default = "default_shards")]
+    pub shards: u32,
+    /// How often to check for leadership and rebalance the shards. This is also the upper bound on
+    /// how long it takes to fail over after an instance dies.
+    #[serde(default = "default_rebalance_interval_seconds")]
+    pub rebalance_interval_seconds: u64,
+}
+
+#[must_use]
+#[inline]
+pub const fn default_shards() -> u32 {
+    64
+}
+
+#[must_use]
+#[inline]
+pub const fn default_rebalance_interval_seconds() -> u64 {
+    5
+}
+
+impl HaConfig {
+    #[must_use]
+    pub fn rebalance_interval(&self) -> Duration {
+        Duration::from_secs(self.rebalance_interval_seconds)
+    }
+}
+
+/// The high-availability state of a single instance.
+#[derive(Debug, Clone)]
+pub(crate) struct Ha {
+    pub(crate) config: HaConfig,
+    /// The connection all of the advisory locks are held on. If this is `None`, no locks are held.
+    conn: Arc<Mutex<Option<PgConnection>>>,
+    state: Arc<RwLock<HaState>>,
+}
+
+#[derive(Debug, Default)]
+struct HaState {
+    is_leader: bool,
+    /// The shards that items are currently being dequeued from.
+    owned: BTreeSet<i32>,
+    /// Shards that are no longer dequeued from, but are still locked. These are unlocked on the
+    /// first rebalance after all of the items from these shards that were still being processed
+    /// have finished, such that another instance never processes items from a shard concurrently
+    /// with this one.
+    releasing: BTreeSet<i32>,
+    /// The amount of items currently being processed, by shard. Shards without any items in flight
+    /// are not present.
+    in_flight: BTreeMap<i32, usize>,
+}
+
+impl Ha {
+    pub(crate) fn new(config: HaConfig) -> Self {
+        Self {
+            config,
+            conn: Arc::new(Mutex::new(None)),
+            state: Arc::new(RwLock::new(HaState::default())),
+        }
+    }
+
+    pub(crate) fn is_leader(&self) -> bool {
+        self.state.read().unwrap().is_leader
+    }
+
+    /// The shards owned by this instance, to be bound to the dequeue query.
+    pub(crate) fn owned_shards(&self) -> Vec<i32> {
+        self.state.read().unwrap().owned.iter().copied().collect()
+    }
+
+    pub(crate) fn shards(&self) -> i32 {
+        i32::try_from(self.config.shards.max(1)).unwrap_or(i32::MAX)
+    }
+
+    /// Record that an item from `shard` is being processed until the returned guard is dropped.
+    pub(crate) fn track_in_flight(&self, shard: i32) -> InFlight {
+        *self
+            .state
+            .write()
+            .unwrap()
+            .in_flight
+            .entry(shard)
+            .or_default() += 1;
+
+        InFlight {
+            state: self.state.clone(),
+            shard,
+        }
+    }
+
+    /// Stop releasing the shards that don't have any items in flight anymore, returning them such
+    /// that they can be unlocked.
+    fn take_releasable(&self) -> Vec<i32> {
+        let mut state = self.state.write().unwrap();
+
+        let releasable = state
+            .releasing
+            .iter()
+            .filter(|shard| !state.in_flight.contains_key(shard))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for shard in &releasable {
+            state.releasing.remove(shard);
+        }
+
+        releasable
+    }
+
+    /// Forget all of the held locks. Once the connection is dropped, postgres releases them.
+    fn reset(&self) {
+        let mut state = self.state.write().unwrap();
+
+        if state.is_leader {
+            warn!("lost leadership");
+        }
+
+        // items that are still being processed will still decrement their shard once done
+        *state = HaState {
+            in_flight: std::mem::take(&mut state.in_flight),
+            ..HaState::default()
+        };
+
+        IS_LEADER.set(0);
+        OWNED_SHARD_COUNT.set(0);
+    }
+}
+
+/// Guard for an item that is being processed, see [`Ha::track_in_flight`].
+#[derive(Debug)]
+pub(crate) struct InFlight {
+    state: Arc<RwLock<HaState>>,
+    shard: i32,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut state = self.state.write().unwrap();
+
+        if let Some(count) = state.in_flight.get_mut(&self.shard) {
+            *count -= 1;
+
+            if *count == 0 {
+                state.in_flight.remove(&self.shard);
+            }
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct Locked {
+    locked: bool,
+}
+
+#[derive(FromRow)]
+struct Members {
+    members: i64,
+}
+
+impl<T> PgQueue<T> {
+    #[must_use]
+    pub fn ha(&self) -> Option<&HaConfig> {
+        self.ha.as_ref().map(|ha| &ha.config)
+    }
+
+    /// Whether this instance is the leader. This is always `true` if high-availability mode is not
+    /// enabled.
+    #[must_use]
+    pub fn is_leader(&self) -> bool {
+        self.ha.as_ref().is_none_or(Ha::is_leader)
+    }
+
+    /// Try to acquire the leadership and rebalance the shards across all live instances. This
+    /// should be run every [`HaConfig::rebalance_interval_seconds`], and is a noop if
+    /// high-availability mode is not enabled.
+    ///
+    /// Each instance owns at most `ceil(shards / instances)` shards. Instances owning more than
+    /// that (for example, after a new instance has joined) release their excess shards, and
+    /// instances owning less (for example, after an instance has died) pick up any unowned shards.
+    ///
+    /// If any error occurs, the coordination connection is dropped and this instance gives up the
+    /// leadership and all of it's shards, since it can no longer be sure that it still holds the
+    /// locks.
+    pub async fn rebalance(&self) -> Result<(), sqlx::Error> {
+        let Some(ha) = &self.ha else {
+            return Ok(());
+        };
+
+        let mut conn = ha.conn.lock().await;
+
+        let res = async {
+            if conn.is_none() {
+                let mut new_conn = self.client.acquire().await?.detach();
+
+                sqlx::query("SELECT pg_advisory_lock($1, pg_backend_pid())")
+                    .bind(MEMBER_LOCK_NAMESPACE)
+                    .execute(&mut new_conn)
+                    .await?;
+
+                debug!("opened coordination connection");
+
+                *conn = Some(new_conn);
+            }
+
+            rebalance_locks(
+                ha,
+                conn.as_mut().expect("connection was opened above; qed;"),
+            )
+            .await
+        }
+        .await;
+
+        if res.is_err() {
+            *conn = None;
+            ha.reset();
+        }
+
+        res
+    }
+}
+
+async fn rebalance_locks(ha: &Ha, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    for shard in ha.take_releasable() {
+        unlock(conn, shard + 1).await?;
+        debug!(%shard, "released shard");
+    }
+
+    if !ha.is_leader() && try_lock(conn, LEADER_LOCK_KEY).await? {
+        info!("acquired leadership");
+        ha.state.write().unwrap().is_leader = true;
+        IS_LEADER.set(1);
+    }
+
+    let members = sqlx::query(
+        r#"
+        SELECT
+            COUNT(*) AS members
+        FROM
+            pg_locks
+        WHERE
+            locktype = 'advisory'
+            AND database = (SELECT oid FROM pg_database WHERE datname = current_database())
+            AND classid::INT8 = $1
+            AND objsubid = 2
+            AND granted
+        "#,
+    )
+    .bind(i64::from(MEMBER_LOCK_NAMESPACE))
+    .try_map(|row| Members::from_row(&row))
+    .fetch_one(&mut *conn)
+    .await?
+    .members
+    .max(1);
+
+    let shards = ha.shards();
+    let fair_share = usize::try_from(i64::from(shards).div_ceil(members)).unwrap_or(usize::MAX);
+    let shard_count = usize::try_from(shards).unwrap_or_default();
+
+    let owned = ha.state.read().unwrap().owned.clone();
+
+    if owned.len() > fair_share {
+        let excess = owned.iter().rev().take(owned.len() - fair_share).copied();
+
+        let mut state = ha.state.write().unwrap();
+        for shard in excess {
+            state.owned.remove(&shard);
+            state.releasing.insert(shard);
+        }
+    } else {
+        let mut acquired = vec![];
+
+        // start at a different shard on every instance to reduce contention on the locks
+        let start = usize::try_from(std::process::id()).unwrap_or_default() % shard_count;
+
+        for shard in (0..shards)
+            .cycle()
+            .skip(start)
+            .take(shard_count)
+            .filter(|shard| !owned.contains(shard))
+        {
+            if owned.len() + acquired.len() >= fair_share {
+                break;
+            }
+
+            if try_lock(conn, shard + 1).await? {
+                acquired.push(shard);
+            }
+        }
+
+        if !acquired.is_empty() {
+            debug!(?acquired, "acquired shards");
+            ha.state.write().unwrap().owned.extend(acquired);
+        }
+    }
+
+    let owned = ha.state.read().unwrap().owned.len();
+
+    debug!(%members, %fair_share, %owned, "rebalanced shards");
+
+    OWNED_SHARD_COUNT.set(i64::try_from(owned).unwrap_or(i64::MAX));
+
+    Ok(())
+}
+
+async fn try_lock(conn: &mut PgConnection, key: i32) -> Result<bool, sqlx::Error> {
+    Ok(sqlx::query("SELECT pg_try_advisory_lock($1, $2) AS locked")
+        .bind(LOCK_NAMESPACE)
+        .bind(key)
+        .try_map(|row| Locked::from_row(&row))
+        .fetch_one(conn)
+        .await?
+        .locked)
+}
+
+async fn unlock(conn: &mut PgConnection, key: i32) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_unlock($1, $2)")
+        .bind(LOCK_NAMESPACE)
+        .bind(key)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ha(shards: u32) -> Ha {
+        Ha::new(HaConfig {
+            shards,
+            rebalance_interval_seconds: default_rebalance_interval_seconds(),
+        })
+    }
+
+    fn release(ha: &Ha, shards: impl IntoIterator<Item = i32>) {
+        let mut state = ha.state.write().unwrap();
+        for shard in shards {
+            state.owned.remove(&shard);
+            state.releasing.insert(shard);
+        }
+    }
+
+    #[test]
+    fn shards_is_at_least_one() {
+        assert_eq!(ha(0).shards(), 1);
+        assert_eq!(ha(64).shards(), 64);
+        assert_eq!(ha(u32::MAX).shards(), i32::MAX);
+    }
+
+    #[test]
+    fn releasing_waits_for_in_flight_items() {
+        let ha = ha(4);
+
+        ha.state.write().unwrap().owned.extend([0, 1, 2]);
+
+        let a = ha.track_in_flight(1);
+        let b = ha.track_in_flight(1);
+        let c = ha.track_in_flight(2);
+
+        release(&ha, [0, 1]);
+
+        assert_eq!(ha.owned_shards(), [2]);
+
+        // shard 0 has no items in flight
+        assert_eq!(ha.take_releasable(), [0]);
+        assert_eq!(ha.take_releasable(), Vec::<i32>::new());
+
+        drop(a);
+        assert_eq!(ha.take_releasable(), Vec::<i32>::new());
+
+        // items in flight in owned shards don't affect releasing
+        drop(c);
+        assert_eq!(ha.take_releasable(), Vec::<i32>::new());
+
+        drop(b);
+        assert_eq!(ha.take_releasable(), [1]);
+        assert!(ha.state.read().unwrap().in_flight.is_empty());
+    }
+
+    #[test]
+    fn reset_keeps_in_flight_items() {
+        let ha = ha(4);
+
+        ha.state.write().unwrap().owned.insert(1);
+
+        let in_flight = ha.track_in_flight(1);
+
+        ha.reset();
+
+        assert!(ha.owned_shards().is_empty());
+        assert_eq!(ha.state.read().unwrap().in_flight.get(&1), Some(&1));
+
+        drop(in_flight);
+
+        assert!(ha.state.read().unwrap().in_flight.is_empty());
+    }
+
+    #[test]
+    fn config_defaults() {
+        assert_eq!(
+            serde_json::from_str::<HaConfig>("{}").unwrap(),
+            HaConfig {
+                shards: default_shards(),
+                rebalance_interval_seconds: default_rebalance_interval_seconds(),
+            }
+        );
+    }
+}
@@ -12,15 +12,30 @@ use sqlx::{postgres::PgPoolOptions, prelude::FromRow, types::Json, Either, Execu
 use tracing::{debug, debug_span, info_span, instrument, trace, Instrument};
 use voyager_vm::{
     filter::{FilterResult, InterestFilter},
+    history::{walk_history, HistoryItem, ItemStatus},
+    inspect::{ItemQuery, ItemRecord},
     pass::{Pass, PassResult},
+    priority::{resolve_priority, Lanes, PriorityConfig},
     Captures, ItemId, Op, QueueMessage,
 };
 
-use crate::metrics::{ITEM_PROCESSING_DURATION, OPTIMIZE_ITEM_COUNT, OPTIMIZE_PROCESSING_DURATION};
+use crate::{
+    ha::{Ha, HaConfig},
+    metrics::{
+        ITEM_PROCESSING_DURATION, OLDEST_ITEM_AGE, OPTIMIZE_ITEM_COUNT,
+        OPTIMIZE_PROCESSING_DURATION, QUEUE_DEPTH,
+    },
+    retention::RetentionConfig,
+};
 
+pub mod ha;
 pub mod metrics;
+pub mod retention;
+
+/// How often [`PgQueue::update_metrics`] should be run.
+pub const METRICS_UPDATE_INTERVAL: Duration = Duration::from_secs(15);
 
-/// A fifo queue backed by a postgres table. Not suitable for high-throughput, but enough for ~1k items/sec.
+/// A priority queue backed by a postgres table. Not suitable for high-throughput, but enough for ~1k items/sec.
 ///
 /// The queue assumes the following database schema:
 ///
@@ -33,6 +48,9 @@ pub mod metrics;
 #[derive(DebugNoBound, CloneNoBound)]
 pub struct PgQueue<T> {
     client: PgPool,
+    retention: Option<RetentionConfig>,
+    lanes: Lanes,
+    ha: Option<Ha>,
     __marker: PhantomData<fn() -> T>,
 }
 
@@ -44,6 +62,15 @@ pub struct PgQueueConfig {
     pub min_connections: Option<u32>,
     pub idle_timeout: Option<Duration>,
     pub max_lifetime: Option<Duration>,
+    /// Retention policy for the `done` table. If unset, rows are kept forever.
+    #[serde(default)]
+    pub retention: Option<RetentionConfig>,
+    #[serde(default)]
+    pub priority: PriorityConfig,
+    /// Enable high-availability mode, allowing multiple instances to share this queue. See
+    /// [`ha`] for more information.
+    #[serde(default)]
+    pub ha: Option<HaConfig>,
 }
 
 impl PgQueueConfig {
@@ -68,7 +95,40 @@ struct Record {
     id: i64,
     parents: Vec<i64>,
     item: String,
+    priority: i32,
     created_at: sqlx::types::time::OffsetDateTime,
+    /// The shard the item is in, only returned when dequeueing in high-availability mode.
+    #[sqlx(default)]
+    shard: Option<i32>,
+}
+
+#[derive(Debug, FromRow)]
+struct ListRecord {
+    id: i64,
+    parents: Option<Vec<i64>>,
+    item: String,
+    tag: Option<String>,
+    priority: Option<i32>,
+    message: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct QueueStateRecord {
+    status: String,
+    tag: String,
+    op_type: Option<String>,
+    chain_id: String,
+    count: i64,
+    oldest: Option<f64>,
+}
+
+#[derive(Debug, FromRow)]
+struct HistoryRecord {
+    id: i64,
+    parents: Option<Vec<i64>>,
+    item: String,
+    status: String,
+    message: Option<String>,
 }
 
 #[derive(Debug, FromRow, Serialize)]
@@ -152,6 +212,213 @@ impl<T: QueueMessage> PgQueue<T> {
         .await?
         .transpose()
     }
+
+    /// The history of the item `id`, as walked by [`walk_history`].
+    pub async fn history(
+        &self,
+        id: i64,
+        max_depth: u32,
+    ) -> Result<Vec<HistoryItem<T>>, sqlx::Error> {
+        walk_history(id, max_depth, |ids| async move {
+            sqlx::query(
+                r#"
+                SELECT id, parents, item::TEXT, 'ready'::TEXT AS status, NULL::TEXT AS message
+                FROM queue WHERE id = ANY($1)
+                UNION ALL
+                SELECT id, parents, item::TEXT, 'optimize'::TEXT, NULL::TEXT
+                FROM optimize WHERE id = ANY($1)
+                UNION ALL
+                SELECT id, parents, item::TEXT, 'done'::TEXT, NULL::TEXT
+                FROM done WHERE id = ANY($1)
+                UNION ALL
+                SELECT id, parents, item::TEXT, 'failed'::TEXT, message
+                FROM failed WHERE id = ANY($1)
+                "#,
+            )
+            .bind(ids)
+            .try_map(|row| HistoryRecord::from_row(&row))
+            .fetch_all(&self.client)
+            .await?
+            .into_iter()
+            .map(|record| {
+                Ok::<_, sqlx::Error>(HistoryItem {
+                    id: record.id,
+                    parents: record.parents.unwrap_or_default(),
+                    status: record
+                        .status
+                        .parse()
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    item: de(&record.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    message: record.message,
+                })
+            })
+            .collect()
+        })
+        .await
+    }
+
+    /// Returns the items with the given status that match `query`, ordered by id.
+    pub async fn list(
+        &self,
+        status: ItemStatus,
+        query: &ItemQuery,
+    ) -> Result<Vec<ItemRecord<T>>, sqlx::Error> {
+        let (table, tag, priority, message) = match status {
+            ItemStatus::Ready => ("queue", "NULL::TEXT", "priority", "NULL::TEXT"),
+            ItemStatus::Optimize => ("optimize", "tag", "priority", "NULL::TEXT"),
+            ItemStatus::Done => ("done", "NULL::TEXT", "NULL::INT", "NULL::TEXT"),
+            ItemStatus::Failed => ("failed", "NULL::TEXT", "NULL::INT", "message"),
+        };
+
+        // default to all-inclusive filter if none are provided
+        let item_filters = if query.item_filters.is_empty() {
+            vec!["%".to_owned()]
+        } else {
+            query.item_filters.clone()
+        };
+
+        sqlx::query(&format!(
+            r#"
+            SELECT
+                id,
+                parents,
+                item::TEXT,
+                {tag} AS tag,
+                {priority} AS priority,
+                {message} AS message
+            FROM
+                {table}
+            WHERE
+                ($1::TEXT IS NULL OR {tag} = $1)
+                AND item::TEXT LIKE ANY($2)
+                AND (cardinality($3::TEXT[]) = 0 OR {message} LIKE ANY($3))
+            ORDER BY
+                id ASC
+            LIMIT
+                $4
+            OFFSET
+                $5
+            "#
+        ))
+        .bind(&query.tag)
+        .bind(item_filters)
+        .bind(&query.message_filters)
+        .bind(query.per_page)
+        .bind(query.offset())
+        .try_map(|row| ListRecord::from_row(&row))
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok::<_, sqlx::Error>(ItemRecord {
+                id: record.id,
+                parents: record.parents.unwrap_or_default(),
+                status,
+                tag: record.tag,
+                priority: record.priority,
+                item: de(&record.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                message: record.message,
+            })
+        })
+        .collect()
+    }
+
+    /// Set the priority of the item `id`, if it is ready or waiting to be optimized. Returns
+    /// whether the item was found.
+    ///
+    /// If the item is currently being processed, this will wait until processing is finished (at
+    /// which point the item is no longer in the queue).
+    pub async fn set_priority(&self, id: i64, priority: i32) -> Result<bool, sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            WITH ready AS (
+                UPDATE queue SET priority = $2 WHERE id = $1 RETURNING id
+            ), optimize AS (
+                UPDATE optimize SET priority = $2 WHERE id = $1 RETURNING id
+            )
+            SELECT id FROM ready UNION ALL SELECT id FROM optimize
+            "#,
+        )
+        .bind(id)
+        .bind(priority)
+        .try_map(|row| Id::from_row(&row))
+        .fetch_all(&self.client)
+        .await?
+        .len();
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Update the [`QUEUE_DEPTH`] and [`OLDEST_ITEM_AGE`] gauges from the current state of the
+    /// `queue`, `optimize` and `failed` tables.
+    ///
+    /// This scans all of the tables, and as such should only be run periodically (see
+    /// [`METRICS_UPDATE_INTERVAL`]). The chain id of each item is read from the generated
+    /// `chain_id` column, rather than being extracted from the item on every update.
+    pub async fn update_metrics(&self) -> Result<(), sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                'ready'::TEXT AS status,
+                ''::TEXT AS tag,
+                item->>'@type' AS op_type,
+                chain_id,
+                COUNT(*) AS count,
+                EXTRACT(EPOCH FROM now() - MIN(created_at))::FLOAT8 AS oldest
+            FROM
+                queue
+            GROUP BY
+                3, 4
+            UNION ALL
+            SELECT
+                'optimize'::TEXT,
+                tag,
+                item->>'@type',
+                chain_id,
+                COUNT(*),
+                EXTRACT(EPOCH FROM now() - MIN(created_at))::FLOAT8
+            FROM
+                optimize
+            GROUP BY
+                2, 3, 4
+            UNION ALL
+            SELECT
+                'failed'::TEXT,
+                ''::TEXT,
+                item->>'@type',
+                chain_id,
+                COUNT(*),
+                EXTRACT(EPOCH FROM now() - MIN(created_at))::FLOAT8
+            FROM
+                failed
+            GROUP BY
+                3, 4
+            "#,
+        )
+        .try_map(|row| QueueStateRecord::from_row(&row))
+        .fetch_all(&self.client)
+        .await?;
+
+        // reset the gauges so that label sets that no longer have any items are removed
+        QUEUE_DEPTH.reset();
+        OLDEST_ITEM_AGE.reset();
+
+        for row in rows {
+            let labels = [
+                row.status.as_str(),
+                row.tag.as_str(),
+                row.op_type.as_deref().unwrap_or_default(),
+                row.chain_id.as_str(),
+            ];
+
+            QUEUE_DEPTH.with_label_values(&labels).set(row.count);
+            OLDEST_ITEM_AGE
+                .with_label_values(&labels)
+                .set(row.oldest.unwrap_or_default());
+        }
+
+        Ok(())
+    }
 }
 
 impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
@@ -171,6 +438,16 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
         //     }
         // });
 
+        if let Some(retention) = &config.retention {
+            retention
+                .validate()
+                .map_err(|err| sqlx::Error::Configuration(err.into()))?;
+        }
+
+        let retention = config.retention.clone();
+        let lanes = Lanes::new(&config.priority);
+        let ha = config.ha.clone().map(Ha::new);
+
         let pool = config.into_pg_pool().await?;
 
         pool.execute_many(
@@ -179,6 +456,8 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                 id BIGSERIAL PRIMARY KEY,
                 item JSONB NOT NULL,
                 parents BIGINT[] DEFAULT '{}',
+                priority INTEGER NOT NULL DEFAULT 0,
+                not_before timestamptz,
                 created_at timestamptz NOT NULL DEFAULT now()
             );
 
@@ -188,9 +467,17 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                 item JSONB NOT NULL,
                 tag text NOT NULL,
                 parents BIGINT[] DEFAULT '{}',
+                priority INTEGER NOT NULL DEFAULT 0,
                 created_at timestamptz NOT NULL DEFAULT now()
             );
 
+            -- tables created before priorities were introduced
+            ALTER TABLE queue ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE optimize ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
+
+            -- tables created before scheduled items were introduced
+            ALTER TABLE queue ADD COLUMN IF NOT EXISTS not_before timestamptz;
+
             CREATE TABLE IF NOT EXISTS done(
                 id BIGINT,
                 item JSONB NOT NULL,
@@ -207,7 +494,21 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                 created_at timestamptz NOT NULL DEFAULT now()
             );
 
+            -- the first chain id in an item, extracted once on insert so that the metrics don't
+            -- have to search through every item. note that adding this to an existing table
+            -- rewrites it once.
+            ALTER TABLE queue ADD COLUMN IF NOT EXISTS chain_id TEXT GENERATED ALWAYS AS (
+                COALESCE(jsonb_path_query_first(item, '$.**.chain_id') #>> '{}', '')
+            ) STORED;
+            ALTER TABLE optimize ADD COLUMN IF NOT EXISTS chain_id TEXT GENERATED ALWAYS AS (
+                COALESCE(jsonb_path_query_first(item, '$.**.chain_id') #>> '{}', '')
+            ) STORED;
+            ALTER TABLE failed ADD COLUMN IF NOT EXISTS chain_id TEXT GENERATED ALWAYS AS (
+                COALESCE(jsonb_path_query_first(item, '$.**.chain_id') #>> '{}', '')
+            ) STORED;
+
             CREATE INDEX IF NOT EXISTS index_queue_id ON queue(id);
+            CREATE INDEX IF NOT EXISTS index_queue_priority ON queue(priority DESC, id ASC);
             "#,
         )
         .try_for_each(|result| async move {
@@ -217,8 +518,30 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
         .instrument(info_span!("init"))
         .await?;
 
+        // adding a generated column rewrites the whole table, so only do so if it's needed
+        if ha.is_some() {
+            pool.execute(
+                r#"
+                -- the chain an item is addressed to, used to shard items in high-availability mode.
+                -- this is the chain id of the plugin the item is sent to (plugin names are of the
+                -- form `<plugin>/<chain_id>/...`), falling back to the first chain id in the item.
+                ALTER TABLE queue ADD COLUMN IF NOT EXISTS shard_key TEXT GENERATED ALWAYS AS (
+                    COALESCE(
+                        substring(jsonb_path_query_first(item, '$.**.plugin') #>> '{}' FROM '^[^/]+/([^/]+)'),
+                        jsonb_path_query_first(item, '$.**.chain_id') #>> '{}'
+                    )
+                ) STORED;
+                "#,
+            )
+            .instrument(info_span!("init_ha"))
+            .await?;
+        }
+
         Ok(Self {
             client: pool,
+            retention,
+            lanes,
+            ha,
             __marker: PhantomData,
         })
     }
@@ -226,24 +549,42 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
     async fn enqueue<'a>(&'a self, op: Op<T>, filter: &'a T::Filter) -> Result<(), Self::Error> {
         trace!("enqueue");
 
-        let (optimize, ready): (Vec<_>, Vec<_>) =
-            op.normalize()
-                .into_iter()
-                .partition_map(|op| match filter.check_interest(&op) {
-                    FilterResult::Interest(tag) => Either::Left((op, tag)),
-                    FilterResult::NoInterest => Either::Right(op),
-                });
+        let (optimize, ready): (Vec<_>, Vec<_>) = op
+            .normalize()
+            .into_iter()
+            .map(|op| {
+                let (carried, op) = op.take_priority();
+                let priority = resolve_priority(carried, filter.check_priority(&op), None);
+                (op, priority)
+            })
+            .partition_map(|(op, priority)| match filter.check_interest(&op) {
+                FilterResult::Interest(tag) => Either::Left((op, priority, tag)),
+                FilterResult::NoInterest => Either::Right((op, priority)),
+            });
 
         let mut tx = self.client.begin().await?;
 
         let ready_ids = sqlx::query(
             "
-            INSERT INTO queue (item)
-            SELECT * FROM UNNEST($1::JSONB[])
+            INSERT INTO queue (item, priority, not_before)
+            SELECT item, priority, to_timestamp(not_before)
+            FROM UNNEST($1::JSONB[], $2::INT[], $3::BIGINT[]) AS t(item, priority, not_before)
             RETURNING id
             ",
         )
-        .bind(ready.into_iter().map(Json).collect::<Vec<_>>())
+        .bind(ready.iter().map(|(op, _)| Json(op)).collect::<Vec<_>>())
+        .bind(
+            ready
+                .iter()
+                .map(|(_, priority)| *priority)
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            ready
+                .iter()
+                .map(|(op, _)| not_before(op))
+                .collect::<Vec<_>>(),
+        )
         .try_map(|x| Id::from_row(&x))
         .fetch_all(tx.as_mut())
         .await?;
@@ -254,19 +595,24 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
 
         let optimize_further_ids = sqlx::query(
             "
-            INSERT INTO optimize (item, tag)
-            SELECT * FROM UNNEST($1::JSONB[], $2::TEXT[])
+            INSERT INTO optimize (item, priority, tag)
+            SELECT * FROM UNNEST($1::JSONB[], $2::INT[], $3::TEXT[])
             RETURNING id
             ",
         )
         .bind(
             optimize
-                .clone()
-                .into_iter()
-                .map(|x| Json(x.0))
+                .iter()
+                .map(|(op, _, _)| Json(op))
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            optimize
+                .iter()
+                .map(|(_, priority, _)| *priority)
                 .collect::<Vec<_>>(),
         )
-        .bind(optimize.into_iter().map(|x| x.1).collect::<Vec<_>>())
+        .bind(optimize.iter().map(|(_, _, tag)| *tag).collect::<Vec<_>>())
         .try_map(|x| Id::from_row(&x))
         .fetch_all(tx.as_mut())
         .await?;
@@ -293,9 +639,25 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
     {
         trace!("process");
 
+        let order = if self.lanes.next_is_fifo() {
+            "id ASC"
+        } else {
+            "priority DESC, id ASC"
+        };
+
+        // in high-availability mode, only dequeue items in the shards owned by this instance. the
+        // `shard_key` column only exists in high-availability mode.
+        let (shard_filter, shard) = match &self.ha {
+            Some(_) => (
+                "AND (shard_key IS NULL OR mod(abs(hashtext(shard_key)::BIGINT), $1) = ANY($2::INT[]))",
+                "mod(abs(hashtext(shard_key)::BIGINT), $1)::INT",
+            ),
+            None => ("", "NULL::INT"),
+        };
+
         let mut tx = self.client.begin().await?;
 
-        let row = sqlx::query(
+        let mut query = sqlx::query(&format!(
             r#"
             DELETE FROM
               queue
@@ -305,8 +667,11 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                   id
                 FROM
                   queue
+                WHERE
+                  (not_before IS NULL OR not_before <= now())
+                  {shard_filter}
                 ORDER BY
-                  id ASC
+                  {order}
                 FOR UPDATE
                   SKIP LOCKED
                 LIMIT 1)
@@ -314,23 +679,40 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
               id,
               parents,
               item::text,
-              created_at
+              priority,
+              created_at,
+              {shard} AS shard
             "#,
-        )
-        .try_map(|x| Record::from_row(&x))
-        .fetch_optional(tx.as_mut())
-        .await?;
+        ));
+
+        if let Some(ha) = &self.ha {
+            query = query.bind(ha.shards()).bind(ha.owned_shards());
+        }
+
+        let row = query
+            .try_map(|x| Record::from_row(&x))
+            .fetch_optional(tx.as_mut())
+            .await?;
 
         match row {
             Some(row) => {
+                // the shard must not be released to another instance until this item is done
+                let _in_flight = self
+                    .ha
+                    .as_ref()
+                    .zip(row.shard)
+                    .map(|(ha, shard)| ha.track_in_flight(shard));
+
                 let span = info_span!("processing item", id = row.id);
 
                 trace!(%row.item);
 
                 // really don't feel like defining a new error type right now
-                let op = de(&row.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                let op: Op<T> = de(&row.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
 
-                let timer = ITEM_PROCESSING_DURATION.start_timer();
+                let timer = ITEM_PROCESSING_DURATION
+                    .with_label_values(&[op.kind()])
+                    .start_timer();
                 let (r, res) = f(op, ItemId::new(row.id).unwrap()).instrument(span).await;
                 let _ = timer.stop_and_record();
 
@@ -377,29 +759,62 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                             let (optimize, ready): (Vec<_>, Vec<_>) = ops
                                 .into_iter()
                                 .flat_map(Op::normalize)
-                                .partition_map(|op| match filter.check_interest(&op) {
-                                    FilterResult::Interest(tag) => Either::Left((op, tag)),
-                                    FilterResult::NoInterest => Either::Right(op),
+                                .map(|op| {
+                                    let (carried, op) = op.take_priority();
+                                    let priority = resolve_priority(
+                                        carried,
+                                        filter.check_priority(&op),
+                                        [row.priority],
+                                    );
+                                    (op, priority)
+                                })
+                                .partition_map(|(op, priority)| match filter.check_interest(&op) {
+                                    FilterResult::Interest(tag) => {
+                                        Either::Left((op, priority, tag))
+                                    }
+                                    FilterResult::NoInterest => Either::Right((op, priority)),
                                 });
 
                             sqlx::query(
                                 "
-                                INSERT INTO queue (item)
-                                SELECT * FROM UNNEST($1::JSONB[])
+                                INSERT INTO queue (item, priority, not_before, parents)
+                                SELECT item, priority, to_timestamp(not_before), ARRAY[$4::BIGINT]
+                                FROM UNNEST($1::JSONB[], $2::INT[], $3::BIGINT[])
+                                  AS t(item, priority, not_before)
                                 ",
                             )
-                            .bind(ready.into_iter().map(Json).collect::<Vec<_>>())
+                            .bind(ready.iter().map(|(op, _)| Json(op)).collect::<Vec<_>>())
+                            .bind(
+                                ready
+                                    .iter()
+                                    .map(|(_, priority)| *priority)
+                                    .collect::<Vec<_>>(),
+                            )
+                            .bind(
+                                ready
+                                    .iter()
+                                    .map(|(op, _)| not_before(op))
+                                    .collect::<Vec<_>>(),
+                            )
+                            .bind(row.id)
                             .execute(tx.as_mut())
                             .await?;
 
                             sqlx::query(
                                 "
-                                INSERT INTO optimize (item, tag)
-                                SELECT * FROM UNNEST($1::JSONB[], $2::TEXT[])
+                                INSERT INTO optimize (item, priority, tag, parents)
+                                SELECT *, ARRAY[$4::BIGINT] FROM UNNEST($1::JSONB[], $2::INT[], $3::TEXT[])
                                 ",
                             )
-                            .bind(optimize.iter().map(|(op, _)| Json(op)).collect::<Vec<_>>())
-                            .bind(optimize.iter().map(|(_, tag)| *tag).collect::<Vec<_>>())
+                            .bind(optimize.iter().map(|(op, _, _)| Json(op)).collect::<Vec<_>>())
+                            .bind(
+                                optimize
+                                    .iter()
+                                    .map(|(_, priority, _)| *priority)
+                                    .collect::<Vec<_>>(),
+                            )
+                            .bind(optimize.iter().map(|(_, _, tag)| *tag).collect::<Vec<_>>())
+                            .bind(row.id)
                             .execute(tx.as_mut())
                             .await?;
                         }
@@ -424,6 +839,7 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
     async fn optimize<'a, O: Pass<T>>(
         &'a self,
         tag: &'a str,
+        filter: &'a T::Filter,
         optimizer: &'a O,
     ) -> Result<(), Either<Self::Error, O::Error>> {
         trace!(%tag, "optimize");
@@ -456,6 +872,7 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
               id,
               parents,
               item::text,
+              priority,
               created_at
             "#,
         )
@@ -471,6 +888,26 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
             return Ok(());
         }
 
+        // keep the optimized items around so that the history of the new items can be traced
+        for r in &msgs {
+            sqlx::query(
+                "
+                INSERT INTO
+                done   (id, parents, item,      created_at)
+                VALUES ($1, $2,      $3::JSONB, $4        )
+                ",
+            )
+            .bind(r.id)
+            .bind(&r.parents)
+            .bind(&r.item)
+            .bind(r.created_at)
+            .execute(tx.as_mut())
+            .await
+            .map_err(Either::Left)?;
+        }
+
+        let priorities = msgs.iter().map(|r| r.priority).collect::<Vec<_>>();
+
         let (ids, msgs) = msgs
             .into_iter()
             .map(|r| {
@@ -482,8 +919,12 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
             .collect::<Result<(Vec<_>, Vec<_>), sqlx::Error>>()
             .map_err(Either::Left)?;
 
-        OPTIMIZE_ITEM_COUNT.observe(msgs.len() as f64);
-        let timer = OPTIMIZE_PROCESSING_DURATION.start_timer();
+        OPTIMIZE_ITEM_COUNT
+            .with_label_values(&[tag])
+            .observe(msgs.len() as f64);
+        let timer = OPTIMIZE_PROCESSING_DURATION
+            .with_label_values(&[tag])
+            .start_timer();
 
         let PassResult {
             optimize_further,
@@ -515,20 +956,32 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                 .collect::<Vec<_>>()
         };
 
+        let get_priority = |parent_idxs: &[usize], new_msg: Op<T>| {
+            let (carried, new_msg) = new_msg.take_priority();
+            let priority = resolve_priority(
+                carried,
+                filter.check_priority(&new_msg),
+                parent_idxs.iter().map(|&idx| priorities[idx]),
+            );
+            (new_msg, priority)
+        };
+
         for (parent_idxs, new_msg, tag) in optimize_further {
             let parents = get_parent_ids(&parent_idxs);
-            trace!(parent_idxs = ?&parent_idxs, parents = ?&parents);
+            let (new_msg, priority) = get_priority(&parent_idxs, new_msg);
+            trace!(parent_idxs = ?&parent_idxs, parents = ?&parents, %priority);
 
             let new_row = sqlx::query(
                 "
-                INSERT INTO optimize (item, parents, tag)
+                INSERT INTO optimize (item, parents, priority, tag)
                 VALUES
-                    ($1::JSONB, $2, $3)
+                    ($1::JSONB, $2, $3, $4)
                 RETURNING id
                 ",
             )
             .bind(Json(new_msg))
             .bind(&parents)
+            .bind(priority)
             .bind(tag)
             .try_map(|row| Id::from_row(&row))
             .fetch_one(tx.as_mut())
@@ -540,18 +993,21 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
 
         for (parent_idxs, new_msg) in ready {
             let parents = get_parent_ids(&parent_idxs);
-            trace!(parent_idxs = ?&parent_idxs, parents = ?&parents);
+            let (new_msg, priority) = get_priority(&parent_idxs, new_msg);
+            trace!(parent_idxs = ?&parent_idxs, parents = ?&parents, %priority);
 
             let new_row = sqlx::query(
                 "
-                INSERT INTO queue (item, parents)
+                INSERT INTO queue (item, parents, priority, not_before)
                 VALUES
-                    ($1::JSONB, $2)
+                    ($1::JSONB, $2, $3, to_timestamp($4::BIGINT))
                 RETURNING id
                 ",
             )
-            .bind(Json(new_msg))
+            .bind(Json(&new_msg))
             .bind(&parents)
+            .bind(priority)
+            .bind(not_before(&new_msg))
             .try_map(|x| Id::from_row(&x))
             .fetch_one(tx.as_mut())
             .await
@@ -573,6 +1029,13 @@ pub enum EnqueueStatus {
     Optimize,
 }
 
+/// The time at which a ready item becomes available, as a unix timestamp in seconds. See
+/// [`Op::not_before`].
+fn not_before<T: QueueMessage>(op: &Op<T>) -> Option<i64> {
+    op.not_before()
+        .map(|not_before| i64::try_from(not_before).unwrap_or(i64::MAX))
+}
+
 fn de<T: DeserializeOwned>(s: &str) -> Result<T, serde_json::Error> {
     let mut deserializer = serde_json::Deserializer::from_str(s);
     deserializer.disable_recursion_limit();
@@ -1,30 +1,80 @@
 use std::sync::LazyLock;
 
-use prometheus::{register_histogram, Histogram};
+use prometheus::{
+    register_gauge_vec, register_histogram_vec, register_int_counter, register_int_gauge,
+    register_int_gauge_vec, GaugeVec, HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+};
 
-pub static ITEM_PROCESSING_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
-    register_histogram!(
+pub static ITEM_PROCESSING_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
         "pg_queue_item_processing_duration_seconds",
         "The time it takes to process an item in the queue.",
+        &["op_type"],
     )
     .unwrap()
 });
 
-// TODO: Add tag to this
-pub static OPTIMIZE_PROCESSING_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
-    register_histogram!(
+pub static OPTIMIZE_PROCESSING_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
         "pg_queue_optimize_processing_duration_seconds",
         "The time it takes to run a pass over the optimize queue.",
+        &["tag"],
     )
     .unwrap()
 });
 
-// TODO: Add tag to this
-pub static OPTIMIZE_ITEM_COUNT: LazyLock<Histogram> = LazyLock::new(|| {
-    register_histogram!(
+pub static OPTIMIZE_ITEM_COUNT: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
         "pg_queue_optimize_item_count",
         "The amount of items processed in an optimize pass.",
+        &["tag"],
         vec![1.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0],
     )
     .unwrap()
 });
+
+pub static PRUNED_ITEM_COUNT: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!(
+        "pg_queue_pruned_item_count",
+        "The amount of items pruned from the done table.",
+    )
+    .unwrap()
+});
+
+/// Labels for the queue state gauges. `tag` is empty for items that are not waiting to be
+/// optimized, and `chain_id` is empty if the item does not contain a chain id.
+const QUEUE_STATE_LABELS: &[&str] = &["status", "tag", "op_type", "chain_id"];
+
+pub static QUEUE_DEPTH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "pg_queue_depth",
+        "The amount of items in the ready, optimize and failed tables.",
+        QUEUE_STATE_LABELS,
+    )
+    .unwrap()
+});
+
+pub static OLDEST_ITEM_AGE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "pg_queue_oldest_item_age_seconds",
+        "The age of the oldest item in the ready, optimize and failed tables.",
+        QUEUE_STATE_LABELS,
+    )
+    .unwrap()
+});
+
+pub static IS_LEADER: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "pg_queue_is_leader",
+        "Whether this instance is the leader in high-availability mode.",
+    )
+    .unwrap()
+});
+
+pub static OWNED_SHARD_COUNT: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "pg_queue_owned_shard_count",
+        "The amount of shards owned by this instance in high-availability mode.",
+    )
+    .unwrap()
+});
@@ -0,0 +1,348 @@
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use tracing::{debug, info, trace};
+
+use crate::{metrics::PRUNED_ITEM_COUNT, PgQueue};
+
+/// Retention policy for the `done` table.
+///
+/// A row is pruned once it is older than `max_age_days` (if set), unless `keep_referenced` is set
+/// and the row is an ancestor of an item that is still in the `queue`, `optimize` or `failed`
+/// tables. At least one of `max_age_days` and `keep_referenced` must be set, see
+/// [`RetentionConfig::validate`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// Prune rows older than this many days. If unset, rows are pruned regardless of their age,
+    /// which requires `keep_referenced` to be set.
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// Keep rows that are still needed to trace the history of live or failed items, regardless
+    /// of their age.
+    #[serde(default)]
+    pub keep_referenced: bool,
+    /// The maximum amount of rows to delete in a single transaction.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u32,
+    /// How often to prune the table.
+    #[serde(default = "default_interval_seconds")]
+    pub interval_seconds: u64,
+    /// If set, pruned rows will be written to gzip-compressed JSONL files in this directory before
+    /// they are deleted.
+    #[serde(default)]
+    pub archive_dir: Option<PathBuf>,
+}
+
+impl RetentionConfig {
+    /// Check that this policy doesn't prune every row in the `done` table, which would make it
+    /// impossible to trace the history of any item.
+    pub fn validate(&self) -> Result<(), UnboundedRetentionError> {
+        if self.max_age_days.is_none() && !self.keep_referenced {
+            return Err(UnboundedRetentionError);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error(
+    "retention policy would prune all done rows, at least one of `max_age_days` or \
+    `keep_referenced` must be set"
+)]
+pub struct UnboundedRetentionError;
+
+#[must_use]
+#[inline]
+pub const fn default_batch_size() -> u32 {
+    1000
+}
+
+#[must_use]
+#[inline]
+pub const fn default_interval_seconds() -> u64 {
+    60 * 60
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PruneError {
+    #[error("error pruning rows")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("error archiving pruned rows")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, FromRow)]
+struct PrunedRecord {
+    id: i64,
+    /// The `parents` column is nullable, even though it defaults to an empty array.
+    parents: Option<Vec<i64>>,
+    item: String,
+    created_at: String,
+}
+
+impl<T> PgQueue<T> {
+    #[must_use]
+    pub fn retention(&self) -> Option<&RetentionConfig> {
+        self.retention.as_ref()
+    }
+
+    /// Prune the `done` table according to the configured [`RetentionConfig`], returning the
+    /// total amount of rows that were pruned. This is a noop if no retention is configured.
+    ///
+    /// Rows are deleted in batches of [`RetentionConfig::batch_size`], each in their own
+    /// transaction, until there are no more rows to prune. If archival is enabled, each batch is
+    /// only committed once it has been written to disk.
+    pub async fn prune_done(&self) -> Result<u64, PruneError> {
+        let Some(retention) = &self.retention else {
+            return Ok(0);
+        };
+
+        // the ancestors of all items that are not done
+        let referenced = if retention.keep_referenced {
+            r#"
+            WITH RECURSIVE referenced(id) AS (
+                SELECT UNNEST(parents) FROM queue
+                UNION
+                SELECT UNNEST(parents) FROM optimize
+                UNION
+                SELECT UNNEST(parents) FROM failed
+                UNION
+                SELECT UNNEST(done.parents) FROM done JOIN referenced ON done.id = referenced.id
+            )
+            "#
+        } else {
+            ""
+        };
+
+        let referenced_filter = if retention.keep_referenced {
+            "AND NOT EXISTS (SELECT 1 FROM referenced WHERE referenced.id = done.id)"
+        } else {
+            ""
+        };
+
+        let query = format!(
+            r#"
+            {referenced}
+            DELETE FROM
+              done
+            WHERE
+              ctid = ANY(ARRAY(
+                SELECT
+                  ctid
+                FROM
+                  done
+                WHERE
+                  ($2::INT IS NULL OR created_at < now() - make_interval(days => $2::INT))
+                  {referenced_filter}
+                ORDER BY
+                  id ASC
+                LIMIT $1))
+            RETURNING
+              id,
+              parents,
+              item::TEXT,
+              created_at::TEXT
+            "#
+        );
+
+        let mut total = 0;
+
+        loop {
+            let mut tx = self.client.begin().await?;
+
+            let rows = sqlx::query(&query)
+                .bind(i64::from(retention.batch_size))
+                .bind(
+                    retention
+                        .max_age_days
+                        .map(|days| i32::try_from(days).unwrap_or(i32::MAX)),
+                )
+                .try_map(|row| PrunedRecord::from_row(&row))
+                .fetch_all(tx.as_mut())
+                .await?;
+
+            if rows.is_empty() {
+                trace!("no rows to prune");
+                break;
+            }
+
+            if let Some(archive_dir) = &retention.archive_dir {
+                archive(archive_dir, &rows).await?;
+            }
+
+            tx.commit().await?;
+
+            let pruned = rows.len() as u64;
+            total += pruned;
+            PRUNED_ITEM_COUNT.inc_by(pruned);
+
+            debug!(%pruned, "pruned done rows");
+
+            if pruned < u64::from(retention.batch_size) {
+                break;
+            }
+        }
+
+        if total > 0 {
+            info!(%total, "pruned done table");
+        }
+
+        Ok(total)
+    }
+}
+
+/// Write the rows to a new gzip-compressed JSONL file in `archive_dir`, named after the current
+/// timestamp and the range of ids it contains.
+async fn archive(archive_dir: &Path, rows: &[PrunedRecord]) -> Result<(), PruneError> {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+
+    for row in rows {
+        // the item is already valid json, splice it in directly instead of round-tripping it
+        // through serde_json (which would also enforce a recursion limit)
+        writeln!(
+            encoder,
+            r#"{{"id":{},"parents":{},"created_at":{},"item":{}}}"#,
+            row.id,
+            serde_json::to_string(&row.parents).expect("serialization is infallible; qed;"),
+            serde_json::to_string(&row.created_at).expect("serialization is infallible; qed;"),
+            row.item,
+        )?;
+    }
+
+    let bz = encoder.finish()?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("the current timestamp must be greater than the unix epoch")
+        .as_secs();
+
+    let first = rows.iter().map(|r| r.id).min().unwrap_or_default();
+    let last = rows.iter().map(|r| r.id).max().unwrap_or_default();
+
+    tokio::fs::create_dir_all(archive_dir).await?;
+
+    let path = archive_dir.join(format!("done-{now}-{first}-{last}.jsonl.gz"));
+
+    tokio::fs::write(&path, bz).await?;
+
+    debug!(path = %path.display(), rows = rows.len(), "archived pruned rows");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    fn config(max_age_days: Option<u32>, keep_referenced: bool) -> RetentionConfig {
+        RetentionConfig {
+            max_age_days,
+            keep_referenced,
+            batch_size: default_batch_size(),
+            interval_seconds: default_interval_seconds(),
+            archive_dir: None,
+        }
+    }
+
+    #[test]
+    fn validate() {
+        assert_eq!(config(Some(7), false).validate(), Ok(()));
+        assert_eq!(config(Some(7), true).validate(), Ok(()));
+        assert_eq!(config(None, true).validate(), Ok(()));
+        assert_eq!(config(None, false).validate(), Err(UnboundedRetentionError));
+    }
+
+    #[test]
+    fn deserialize_defaults() {
+        assert_eq!(
+            serde_json::from_str::<RetentionConfig>(r#"{"max_age_days":7}"#).unwrap(),
+            config(Some(7), false)
+        );
+    }
+
+    #[tokio::test]
+    async fn archive_rows() {
+        let archive_dir = std::env::temp_dir().join(format!(
+            "pg-queue-archive-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+
+        archive(
+            &archive_dir,
+            &[
+                PrunedRecord {
+                    id: 2,
+                    parents: Some(vec![1]),
+                    item: r#"{"data":{"a":1}}"#.to_owned(),
+                    created_at: "2024-01-01 00:00:00+00".to_owned(),
+                },
+                PrunedRecord {
+                    id: 3,
+                    parents: None,
+                    item: r#"{"noop":null}"#.to_owned(),
+                    created_at: "2024-01-02 00:00:00+00".to_owned(),
+                },
+            ],
+        )
+        .await
+        .unwrap();
+
+        let [entry] = std::fs::read_dir(&archive_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+
+        let file_name = entry.file_name().unwrap().to_str().unwrap().to_owned();
+        assert!(file_name.starts_with("done-"), "{file_name}");
+        assert!(file_name.ends_with("-2-3.jsonl.gz"), "{file_name}");
+
+        let mut contents = String::new();
+        GzDecoder::new(std::fs::File::open(&entry).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+
+        std::fs::remove_dir_all(&archive_dir).unwrap();
+
+        let lines = contents
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            [
+                serde_json::json!({
+                    "id": 2,
+                    "parents": [1],
+                    "created_at": "2024-01-01 00:00:00+00",
+                    "item": { "data": { "a": 1 } },
+                }),
+                serde_json::json!({
+                    "id": 3,
+                    "parents": null,
+                    "created_at": "2024-01-02 00:00:00+00",
+                    "item": { "noop": null },
+                }),
+            ]
+        );
+    }
+}
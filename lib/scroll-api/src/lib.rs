@@ -18,15 +18,14 @@ impl ScrollClient {
     }
 
     #[instrument(level = "debug", skip(self))]
-    pub async fn batch(&self, batch: u64) -> BatchResponse {
+    pub async fn batch(&self, batch: u64) -> Result<BatchResponse, reqwest::Error> {
         self.client
             .get(format!("{}/api/batch?index={batch}", self.base_url))
             .send()
-            .await
-            .unwrap()
+            .await?
+            .error_for_status()?
             .json()
             .await
-            .unwrap()
     }
 
     // #[instrument(level = "debug", skip(self))]
@@ -0,0 +1,239 @@
+//! Helpers for reading the state of the Scroll rollup as it is finalized on its L1.
+//!
+//! The scroll light client tracks the L2 through the `ScrollChain` contract on the L1: it is updated
+//! to L1 heights, and the L2 state at an L1 height is the state root of the last finalized batch
+//! (`lastFinalizedBatchIndex`) of the rollup contract at that height.
+
+use alloy::{
+    consensus::Transaction as _,
+    providers::{Provider, RootProvider},
+    rpc::types::Filter,
+    sol_types::{SolCall, SolEvent},
+    transports::{BoxTransport, TransportError},
+};
+use scroll_api::ScrollClient;
+use scroll_codec::{finalizeBundleCall, finalizeBundleWithProofCall};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::primitives::{H160, H256, U256};
+
+/// The location of the rollup state in the `ScrollChain` contract on the L1. These must be the
+/// same values as are stored in the scroll client state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RollupConfig {
+    /// The address of the `ScrollChain` contract on the L1.
+    pub rollup_contract_address: H160,
+    /// The slot of `lastFinalizedBatchIndex` in the rollup contract.
+    pub rollup_last_finalized_batch_index_slot: U256,
+    /// The slot of the `finalizedStateRoots` mapping in the rollup contract.
+    pub rollup_finalized_state_roots_slot: U256,
+    /// The slot of the `committedBatches` mapping in the rollup contract.
+    pub rollup_committed_batches_slot: U256,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("error reading the rollup contract storage")]
+    Storage(#[source] TransportError),
+    #[error("batch index {0} does not fit in a u64")]
+    BatchIndexOverflow(U256),
+    #[error("error fetching batch {0} from the scroll api")]
+    Api(u64, #[source] reqwest::Error),
+    #[error("error fetching the `FinalizeBatch` logs")]
+    Logs(#[source] TransportError),
+    #[error("expected exactly one `FinalizeBatch` event for batch {batch_index}, found {found}")]
+    FinalizeBatchNotFound { batch_index: u64, found: usize },
+    #[error("`FinalizeBatch` event for batch {0} has no transaction hash")]
+    MissingTransactionHash(u64),
+    #[error("error fetching finalize transaction {0}")]
+    FinalizeTx(H256, #[source] TransportError),
+    #[error("finalize transaction {0} not found")]
+    FinalizeTxNotFound(H256),
+    #[error(
+        "finalize transaction {0} is not a call to `finalizeBundle` or `finalizeBundleWithProof`"
+    )]
+    InvalidFinalizeCalldata(H256),
+}
+
+/// Read `lastFinalizedBatchIndex` from the rollup contract at `l1_height`.
+#[instrument(
+    skip_all,
+    level = "trace",
+    fields(
+        %l1_height,
+        %config.rollup_contract_address,
+        %config.rollup_last_finalized_batch_index_slot,
+    )
+)]
+pub async fn last_finalized_batch_index(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    l1_height: u64,
+) -> Result<u64, Error> {
+    let batch_index = U256::from_be_bytes(
+        l1_provider
+            .get_storage_at(
+                config.rollup_contract_address.into(),
+                alloy::primitives::U256::from_be_bytes(
+                    config.rollup_last_finalized_batch_index_slot.to_be_bytes(),
+                ),
+            )
+            .block_id(l1_height.into())
+            .await
+            .map_err(Error::Storage)?
+            .to_be_bytes::<32>(),
+    );
+
+    let batch_index =
+        u64::try_from(batch_index).map_err(|_| Error::BatchIndexOverflow(batch_index))?;
+
+    debug!("l1 height {l1_height} is batch index {batch_index}");
+
+    Ok(batch_index)
+}
+
+/// Fetch the L2 height that is finalized on the L1 at `l1_height`, i.e. the last block of the last
+/// finalized batch of the rollup.
+#[instrument(skip_all, fields(%l1_height))]
+pub async fn settled_l2_height(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    scroll_api: &ScrollClient,
+    l1_height: u64,
+) -> Result<u64, Error> {
+    let batch_index = last_finalized_batch_index(config, l1_provider, l1_height).await?;
+
+    let l2_height = scroll_api
+        .batch(batch_index)
+        .await
+        .map_err(|err| Error::Api(batch_index, err))?
+        .batch
+        .end_block_number;
+
+    debug!("l1 height {l1_height} is l2 height {l2_height}");
+
+    Ok(l2_height)
+}
+
+/// Fetch the header of the finalized batch `batch_index`, as it was passed to the rollup contract
+/// when the batch was finalized at or before `l1_height`. This is the preimage of
+/// `committedBatches[batch_index]`.
+#[instrument(skip_all, fields(%batch_index, %l1_height))]
+pub async fn finalized_batch_header(
+    config: &RollupConfig,
+    l1_provider: &RootProvider<BoxTransport>,
+    batch_index: u64,
+    l1_height: u64,
+) -> Result<Vec<u8>, Error> {
+    let logs = l1_provider
+        .get_logs(
+            &Filter::new()
+                .address(alloy::primitives::Address::from(
+                    config.rollup_contract_address,
+                ))
+                .event_signature(FinalizeBatch::SIGNATURE_HASH)
+                .topic1(alloy::primitives::B256::from(
+                    U256::from(batch_index).to_be_bytes(),
+                ))
+                .from_block(0)
+                .to_block(l1_height),
+        )
+        .await
+        .map_err(Error::Logs)?;
+
+    let [log] = <[_; 1]>::try_from(logs).map_err(|logs| Error::FinalizeBatchNotFound {
+        batch_index,
+        found: logs.len(),
+    })?;
+
+    let tx_hash = log
+        .transaction_hash
+        .ok_or(Error::MissingTransactionHash(batch_index))?;
+
+    debug!("batch {batch_index} was finalized in transaction {tx_hash}");
+
+    let tx = l1_provider
+        .get_transaction_by_hash(tx_hash)
+        .await
+        .map_err(|err| Error::FinalizeTx(tx_hash.into(), err))?
+        .ok_or(Error::FinalizeTxNotFound(tx_hash.into()))?;
+
+    batch_header_from_calldata(tx.inner.input())
+        .ok_or(Error::InvalidFinalizeCalldata(tx_hash.into()))
+}
+
+/// Extract `_batchHeader` from the calldata of a call to one of the bundle finalization functions.
+/// Each bundle is finalized with the header of the last batch in the bundle, which is also the
+/// batch that `FinalizeBatch` is emitted for.
+fn batch_header_from_calldata(input: &[u8]) -> Option<Vec<u8>> {
+    let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+
+    match selector {
+        finalizeBundleWithProofCall::SELECTOR => {
+            finalizeBundleWithProofCall::abi_decode(input, true)
+                .ok()
+                .map(|call| call._batchHeader.to_vec())
+        }
+        finalizeBundleCall::SELECTOR => finalizeBundleCall::abi_decode(input, true)
+            .ok()
+            .map(|call| call._batchHeader.to_vec()),
+        _ => None,
+    }
+}
+
+alloy::sol! {
+    // https://github.com/scroll-tech/scroll-contracts/blob/7bb751f9cf1b5fdde95297049e3407ce23d56ac6/src/L1/rollup/IScrollChain.sol#L31
+    event FinalizeBatch(
+        uint256 indexed batchIndex,
+        bytes32 indexed batchHash,
+        bytes32 stateRoot,
+        bytes32 withdrawRoot
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_header_from_finalize_bundle_with_proof() {
+        let input = finalizeBundleWithProofCall {
+            _batchHeader: vec![3, 1, 2, 3].into(),
+            _postStateRoot: Default::default(),
+            _withdrawRoot: Default::default(),
+            _aggrProof: vec![0xaa; 64].into(),
+        }
+        .abi_encode();
+
+        assert_eq!(batch_header_from_calldata(&input), Some(vec![3, 1, 2, 3]));
+    }
+
+    #[test]
+    fn batch_header_from_finalize_bundle() {
+        let input = finalizeBundleCall {
+            _batchHeader: vec![3, 4, 5, 6].into(),
+            _postStateRoot: Default::default(),
+            _withdrawRoot: Default::default(),
+        }
+        .abi_encode();
+
+        assert_eq!(batch_header_from_calldata(&input), Some(vec![3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn batch_header_from_unknown_calldata() {
+        let mut input = finalizeBundleCall {
+            _batchHeader: vec![3, 4, 5, 6].into(),
+            _postStateRoot: Default::default(),
+            _withdrawRoot: Default::default(),
+        }
+        .abi_encode();
+
+        input[0] ^= 0xff;
+
+        assert_eq!(batch_header_from_calldata(&input), None);
+        assert_eq!(batch_header_from_calldata(&input[..3]), None);
+        assert_eq!(batch_header_from_calldata(&[]), None);
+    }
+}
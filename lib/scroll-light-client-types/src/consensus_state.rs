@@ -7,3 +7,41 @@ pub struct ConsensusState {
     pub timestamp: u64,
     pub ibc_storage_root: H256,
 }
+
+#[cfg(feature = "ethabi")]
+pub mod ethabi {
+    use alloy::sol_types::SolValue;
+    use unionlabs::impl_ethabi_via_try_from_into;
+
+    use super::*;
+
+    impl_ethabi_via_try_from_into!(ConsensusState => SolConsensusState);
+
+    alloy::sol! {
+        struct SolConsensusState {
+            bytes32 state_root;
+            uint64 timestamp;
+            bytes32 ibc_storage_root;
+        }
+    }
+
+    impl From<ConsensusState> for SolConsensusState {
+        fn from(value: ConsensusState) -> Self {
+            Self {
+                state_root: value.state_root.get().into(),
+                timestamp: value.timestamp,
+                ibc_storage_root: value.ibc_storage_root.get().into(),
+            }
+        }
+    }
+
+    impl From<SolConsensusState> for ConsensusState {
+        fn from(value: SolConsensusState) -> Self {
+            Self {
+                state_root: H256::new(value.state_root.0),
+                timestamp: value.timestamp,
+                ibc_storage_root: H256::new(value.ibc_storage_root.0),
+            }
+        }
+    }
+}
@@ -0,0 +1,1051 @@
+use std::{future::Future, marker::PhantomData, path::PathBuf, time::Duration};
+
+use frame_support_procedural::{CloneNoBound, DebugNoBound};
+use futures_util::TryStreamExt;
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::{
+    prelude::FromRow,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    types::Json,
+    Either, Executor, QueryBuilder, Sqlite, SqlitePool, Transaction,
+};
+use tracing::{debug, debug_span, error, info_span, instrument, trace, Instrument};
+use voyager_vm::{
+    filter::{FilterResult, InterestFilter},
+    history::{walk_history, HistoryItem, ItemStatus},
+    inspect::{ItemQuery, ItemRecord},
+    pass::{Pass, PassResult},
+    priority::{resolve_priority, Lanes, PriorityConfig},
+    Captures, ItemId, Op, QueueMessage,
+};
+
+/// A priority queue backed by an embedded sqlite database, with the same semantics as `pg_queue::PgQueue`.
+///
+/// Since sqlite only allows a single writer at a time, items are claimed before they are
+/// processed instead of being held in an open transaction. Any claimed items are released when
+/// the queue is created, so a database file must only be used by one running voyager instance.
+#[derive(DebugNoBound, CloneNoBound)]
+pub struct SqliteQueue<T> {
+    client: SqlitePool,
+    lanes: Lanes,
+    __marker: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SqliteQueueConfig {
+    /// Path to the database file. This will be created if it does not exist.
+    pub path: PathBuf,
+    pub max_connections: Option<u32>,
+    pub busy_timeout: Option<Duration>,
+    #[serde(default)]
+    pub priority: PriorityConfig,
+}
+
+impl SqliteQueueConfig {
+    pub async fn into_sqlite_pool(self) -> sqlx::Result<SqlitePool> {
+        SqlitePoolOptions::new()
+            .max_connections(self.max_connections.unwrap_or(10))
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(self.path)
+                    .create_if_missing(true)
+                    .journal_mode(SqliteJournalMode::Wal)
+                    .busy_timeout(self.busy_timeout.unwrap_or(Duration::from_secs(5))),
+            )
+            .await
+    }
+}
+
+#[derive(FromRow)]
+struct Id {
+    id: i64,
+}
+
+#[derive(Debug, FromRow)]
+struct Record {
+    id: i64,
+    parents: Json<Vec<i64>>,
+    item: String,
+    priority: i32,
+    created_at: String,
+}
+
+#[derive(Debug, FromRow)]
+struct ListRecord {
+    id: i64,
+    parents: Json<Vec<i64>>,
+    item: String,
+    tag: Option<String>,
+    priority: Option<i32>,
+    message: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct HistoryRecord {
+    id: i64,
+    parents: Json<Vec<i64>>,
+    item: String,
+    status: String,
+    message: Option<String>,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+#[serde(bound(serialize = ""))]
+pub struct FailedRecord<T: QueueMessage> {
+    pub id: i64,
+    pub parents: Json<Vec<i64>>,
+    pub item: Json<Op<T>>,
+    pub message: String,
+}
+
+impl<T: QueueMessage> SqliteQueue<T> {
+    /// Open the queue database, creating the tables if they do not exist yet.
+    ///
+    /// Unlike [`Queue::new`](voyager_vm::Queue::new), this does not release claimed items, and
+    /// as such is safe to use while another voyager instance is running against the same database.
+    pub async fn open(config: SqliteQueueConfig) -> Result<Self, sqlx::Error> {
+        let lanes = Lanes::new(&config.priority);
+
+        let pool = config.into_sqlite_pool().await?;
+
+        pool.execute_many(
+            r#"
+            CREATE TABLE IF NOT EXISTS item_id(
+                id INTEGER NOT NULL
+            );
+
+            INSERT INTO item_id (id) SELECT 0 WHERE NOT EXISTS (SELECT 1 FROM item_id);
+
+            CREATE TABLE IF NOT EXISTS queue(
+                id INTEGER PRIMARY KEY,
+                item TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                priority INTEGER NOT NULL DEFAULT 0,
+                -- unix timestamp in seconds
+                not_before INTEGER,
+                claimed INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS optimize(
+                id INTEGER PRIMARY KEY,
+                item TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                priority INTEGER NOT NULL DEFAULT 0,
+                claimed INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS done(
+                id INTEGER PRIMARY KEY,
+                item TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS failed(
+                id INTEGER PRIMARY KEY,
+                item TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                message TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS index_queue_claimed ON queue(claimed, id);
+            CREATE INDEX IF NOT EXISTS index_queue_priority ON queue(claimed, priority DESC, id);
+            CREATE INDEX IF NOT EXISTS index_optimize_tag ON optimize(tag, claimed);
+            "#,
+        )
+        .try_for_each(|result| async move {
+            trace!("rows affected: {}", result.rows_affected());
+            Ok(())
+        })
+        .instrument(info_span!("init"))
+        .await?;
+
+        Ok(Self {
+            client: pool,
+            lanes,
+            __marker: PhantomData,
+        })
+    }
+
+    pub async fn query_failed(
+        &self,
+        page: i64,
+        per_page: i64,
+        mut item_filters: Vec<String>,
+        mut message_filters: Vec<String>,
+    ) -> Result<Vec<FailedRecord<T>>, sqlx::Error> {
+        // default to all-inclusive filter if none are provided
+        if item_filters.is_empty() {
+            item_filters.push("%".to_owned())
+        }
+
+        if message_filters.is_empty() {
+            message_filters.push("%".to_owned())
+        }
+
+        let mut query =
+            QueryBuilder::<Sqlite>::new("SELECT id, parents, item, message FROM failed WHERE (");
+
+        let mut item_query = query.separated(" OR ");
+        for filter in item_filters {
+            item_query.push("item LIKE ").push_bind_unseparated(filter);
+        }
+
+        query.push(") AND (");
+
+        let mut message_query = query.separated(" OR ");
+        for filter in message_filters {
+            message_query
+                .push("message LIKE ")
+                .push_bind_unseparated(filter);
+        }
+
+        query
+            .push(") ORDER BY id DESC LIMIT ")
+            .push_bind(per_page)
+            .push(" OFFSET ")
+            .push_bind((page - 1) * per_page);
+
+        query
+            .build()
+            .try_map(|row| FailedRecord::<T>::from_row(&row))
+            .fetch_all(&self.client)
+            .await
+    }
+
+    pub async fn query_failed_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<FailedRecord<T>>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT
+               id,
+               parents,
+               item,
+               message
+            FROM
+               failed
+            WHERE
+               id = $1
+            "#,
+        )
+        .bind(id)
+        .try_map(|row| FailedRecord::<T>::from_row(&row))
+        .fetch_optional(&self.client)
+        .await
+    }
+
+    /// Returns the items with the given status that match `query`, ordered by id.
+    pub async fn list(
+        &self,
+        status: ItemStatus,
+        query: &ItemQuery,
+    ) -> Result<Vec<ItemRecord<T>>, sqlx::Error> {
+        let (table, tag, priority, message) = match status {
+            ItemStatus::Ready => ("queue", "NULL", "priority", "NULL"),
+            ItemStatus::Optimize => ("optimize", "tag", "priority", "NULL"),
+            ItemStatus::Done => ("done", "NULL", "NULL", "NULL"),
+            ItemStatus::Failed => ("failed", "NULL", "NULL", "message"),
+        };
+
+        let mut builder = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT id, parents, item, {tag} AS tag, {priority} AS priority, {message} AS message \
+            FROM {table} WHERE TRUE"
+        ));
+
+        if let Some(query_tag) = &query.tag {
+            builder
+                .push(format_args!(" AND {tag} = "))
+                .push_bind(query_tag.clone());
+        }
+
+        for (column, filters) in [
+            ("item", &query.item_filters),
+            (message, &query.message_filters),
+        ] {
+            if filters.is_empty() {
+                continue;
+            }
+
+            builder.push(" AND (");
+            let mut filters_query = builder.separated(" OR ");
+            for filter in filters {
+                filters_query
+                    .push(format_args!("{column} LIKE "))
+                    .push_bind_unseparated(filter.clone());
+            }
+            builder.push(")");
+        }
+
+        builder
+            .push(" ORDER BY id ASC LIMIT ")
+            .push_bind(query.per_page)
+            .push(" OFFSET ")
+            .push_bind(query.offset());
+
+        builder
+            .build()
+            .try_map(|row| ListRecord::from_row(&row))
+            .fetch_all(&self.client)
+            .await?
+            .into_iter()
+            .map(|record| {
+                Ok::<_, sqlx::Error>(ItemRecord {
+                    id: record.id,
+                    parents: record.parents.0,
+                    status,
+                    tag: record.tag,
+                    priority: record.priority,
+                    item: de(&record.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    message: record.message,
+                })
+            })
+            .collect()
+    }
+
+    /// Set the priority of the item `id`, if it is ready or waiting to be optimized. Returns
+    /// whether the item was found.
+    pub async fn set_priority(&self, id: i64, priority: i32) -> Result<bool, sqlx::Error> {
+        let mut tx = self.client.begin().await?;
+
+        let mut rows_affected = 0;
+
+        for table in ["queue", "optimize"] {
+            rows_affected +=
+                sqlx::query(&format!("UPDATE {table} SET priority = $1 WHERE id = $2"))
+                    .bind(priority)
+                    .bind(id)
+                    .execute(tx.as_mut())
+                    .await?
+                    .rows_affected();
+        }
+
+        tx.commit().await?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// The history of the item `id`, as walked by [`walk_history`].
+    pub async fn history(
+        &self,
+        id: i64,
+        max_depth: u32,
+    ) -> Result<Vec<HistoryItem<T>>, sqlx::Error> {
+        walk_history(id, max_depth, |ids| async move {
+            let mut query = QueryBuilder::<Sqlite>::new("");
+
+            for (idx, (table, status, message)) in [
+                ("queue", "ready", "NULL"),
+                ("optimize", "optimize", "NULL"),
+                ("done", "done", "NULL"),
+                ("failed", "failed", "message"),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                if idx != 0 {
+                    query.push(" UNION ALL ");
+                }
+
+                query.push(format_args!(
+                    "SELECT id, parents, item, '{status}' AS status, {message} AS message \
+                    FROM {table} WHERE id IN ("
+                ));
+
+                let mut ids_query = query.separated(", ");
+                for id in &ids {
+                    ids_query.push_bind(*id);
+                }
+
+                query.push(")");
+            }
+
+            query
+                .build()
+                .try_map(|row| HistoryRecord::from_row(&row))
+                .fetch_all(&self.client)
+                .await?
+                .into_iter()
+                .map(|record| {
+                    Ok::<_, sqlx::Error>(HistoryItem {
+                        id: record.id,
+                        parents: record.parents.0,
+                        status: record
+                            .status
+                            .parse()
+                            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                        item: de(&record.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                        message: record.message,
+                    })
+                })
+                .collect()
+        })
+        .await
+    }
+}
+
+impl<T: QueueMessage> voyager_vm::Queue<T> for SqliteQueue<T> {
+    type Config = SqliteQueueConfig;
+    type Error = sqlx::Error;
+
+    async fn new(config: Self::Config) -> Result<Self, Self::Error> {
+        let queue = Self::open(config).await?;
+
+        // release any items that were being processed when the previous instance exited
+        queue
+            .client
+            .execute_many(
+                r#"
+                UPDATE queue SET claimed = 0 WHERE claimed = 1;
+                UPDATE optimize SET claimed = 0 WHERE claimed = 1;
+                "#,
+            )
+            .try_for_each(|result| async move {
+                debug!("released {} claimed items", result.rows_affected());
+                Ok(())
+            })
+            .await?;
+
+        Ok(queue)
+    }
+
+    async fn enqueue<'a>(&'a self, op: Op<T>, filter: &'a T::Filter) -> Result<(), Self::Error> {
+        trace!("enqueue");
+
+        let mut tx = self.client.begin().await?;
+
+        for op in op.normalize() {
+            insert(&mut tx, filter, op, &[], None).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn process<'a, F, Fut, R>(
+        &'a self,
+        filter: &'a T::Filter,
+        f: F,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        F: (FnOnce(Op<T>, ItemId) -> Fut) + Send + Captures<'a>,
+        Fut: Future<Output = (R, Result<Vec<Op<T>>, String>)> + Send + Captures<'a>,
+        R: Send + Sync + 'static,
+    {
+        trace!("process");
+
+        let order = if self.lanes.next_is_fifo() {
+            "id ASC"
+        } else {
+            "priority DESC, id ASC"
+        };
+
+        // claim the item outside of a transaction, sqlite only allows for one writer at a time and
+        // we don't want to hold the lock while the item is being processed
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE
+              queue
+            SET
+              claimed = 1
+            WHERE
+              id = (
+                SELECT
+                  id
+                FROM
+                  queue
+                WHERE
+                  claimed = 0
+                  AND (
+                    not_before IS NULL
+                    OR not_before <= CAST(strftime('%s', 'now') AS INTEGER)
+                  )
+                ORDER BY
+                  {order}
+                LIMIT 1)
+            RETURNING
+              id,
+              parents,
+              item,
+              priority,
+              created_at
+            "#,
+        ))
+        .try_map(|x| Record::from_row(&x))
+        .fetch_optional(&self.client)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let span = info_span!("processing item", id = row.id);
+
+                trace!(%row.item);
+
+                let op = match de(&row.item) {
+                    Ok(op) => op,
+                    Err(err) => {
+                        fail_undecodable(&self.client, "queue", &row, &err).await?;
+
+                        // really don't feel like defining a new error type right now
+                        return Err(sqlx::Error::Decode(Box::new(err)));
+                    }
+                };
+
+                let (r, res) = f(op, ItemId::new(row.id).unwrap()).instrument(span).await;
+
+                let mut tx = self.client.begin().await?;
+
+                sqlx::query("DELETE FROM queue WHERE id = $1")
+                    .bind(row.id)
+                    .execute(tx.as_mut())
+                    .await?;
+
+                match res {
+                    Err(error) => {
+                        // insert error message and the op into failed
+                        sqlx::query(
+                            r#"
+                            INSERT INTO
+                            failed (id, parents, item, created_at, message)
+                            VALUES ($1, $2,      $3,   $4,         $5     )
+                            "#,
+                        )
+                        .bind(row.id)
+                        .bind(&row.parents)
+                        .bind(&row.item)
+                        .bind(&row.created_at)
+                        .bind(error)
+                        .execute(tx.as_mut())
+                        .await?;
+                    }
+                    Ok(ops) => {
+                        // insert the op we just processed into done
+                        sqlx::query(
+                            "
+                            INSERT INTO
+                            done   (id, parents, item, created_at)
+                            VALUES ($1, $2,      $3,   $4        )
+                            ",
+                        )
+                        .bind(row.id)
+                        .bind(&row.parents)
+                        .bind(&row.item)
+                        .bind(&row.created_at)
+                        .execute(tx.as_mut())
+                        .await?;
+
+                        for op in ops.into_iter().flat_map(Op::normalize) {
+                            insert(&mut tx, filter, op, &[row.id], Some(row.priority)).await?;
+                        }
+                    }
+                }
+
+                tx.commit().await?;
+
+                Ok(Some(r))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn optimize<'a, O: Pass<T>>(
+        &'a self,
+        tag: &'a str,
+        filter: &'a T::Filter,
+        optimizer: &'a O,
+    ) -> Result<(), Either<Self::Error, O::Error>> {
+        trace!(%tag, "optimize");
+
+        let mut records = sqlx::query(
+            r#"
+            UPDATE
+              optimize
+            SET
+              claimed = 1
+            WHERE
+              tag = $1
+              AND claimed = 0
+            RETURNING
+              id,
+              parents,
+              item,
+              priority,
+              created_at
+            "#,
+        )
+        .bind(tag)
+        .try_map(|x| Record::from_row(&x))
+        .fetch_all(&self.client)
+        .await
+        .map_err(Either::Left)?;
+
+        if records.is_empty() {
+            trace!("optimizer queue is empty");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            return Ok(());
+        }
+
+        // RETURNING does not guarantee any ordering
+        records.sort_by_key(|r| r.id);
+
+        let mut ids = vec![];
+        let mut priorities = vec![];
+        let mut msgs = vec![];
+
+        for record in records {
+            match de(&record.item) {
+                Ok(msg) => {
+                    ids.push(record.id);
+                    priorities.push(record.priority);
+                    msgs.push(msg);
+                }
+                Err(err) => fail_undecodable(&self.client, "optimize", &record, &err)
+                    .await
+                    .map_err(Either::Left)?,
+            }
+        }
+
+        if msgs.is_empty() {
+            return Ok(());
+        }
+
+        let PassResult {
+            optimize_further,
+            ready,
+        } = optimizer
+            .run_pass(msgs)
+            .instrument(debug_span!(
+                "optimizing items",
+                ids = ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ))
+            .await
+            .map_err(Either::Right)?;
+
+        trace!(
+            ready = ready.len(),
+            optimize_further = optimize_further.len(),
+            "optimized items"
+        );
+
+        let get_parent_ids = |parent_idxs: &[usize]| {
+            ids.iter()
+                .enumerate()
+                .filter_map(|(idx, id)| parent_idxs.contains(&idx).then_some(*id))
+                .collect::<Vec<_>>()
+        };
+
+        let mut tx = self.client.begin().await.map_err(Either::Left)?;
+
+        for id in &ids {
+            // keep the optimized items around so that the history of the new items can be traced
+            sqlx::query(
+                "
+                INSERT INTO done (id, parents, item, created_at)
+                SELECT id, parents, item, created_at FROM optimize WHERE id = $1
+                ",
+            )
+            .bind(id)
+            .execute(tx.as_mut())
+            .await
+            .map_err(Either::Left)?;
+
+            sqlx::query("DELETE FROM optimize WHERE id = $1")
+                .bind(id)
+                .execute(tx.as_mut())
+                .await
+                .map_err(Either::Left)?;
+        }
+
+        let get_priority = |parent_idxs: &[usize], new_msg: Op<T>| {
+            let (carried, new_msg) = new_msg.take_priority();
+            let priority = resolve_priority(
+                carried,
+                filter.check_priority(&new_msg),
+                parent_idxs.iter().map(|&idx| priorities[idx]),
+            );
+            (new_msg, priority)
+        };
+
+        for (parent_idxs, new_msg, tag) in optimize_further {
+            let parents = get_parent_ids(&parent_idxs);
+            let (new_msg, priority) = get_priority(&parent_idxs, new_msg);
+            trace!(parent_idxs = ?&parent_idxs, parents = ?&parents, %priority);
+
+            let id = insert_optimize(&mut tx, &new_msg, &tag, &parents, priority)
+                .await
+                .map_err(Either::Left)?;
+
+            debug!(%id, "inserted new optimizer message");
+        }
+
+        for (parent_idxs, new_msg) in ready {
+            let parents = get_parent_ids(&parent_idxs);
+            let (new_msg, priority) = get_priority(&parent_idxs, new_msg);
+            trace!(parent_idxs = ?&parent_idxs, parents = ?&parents, %priority);
+
+            let id = insert_ready(&mut tx, &new_msg, &parents, priority)
+                .await
+                .map_err(Either::Left)?;
+
+            debug!(%id, "inserted new message");
+        }
+
+        tx.commit().await.map_err(Either::Left)?;
+
+        Ok(())
+    }
+}
+
+/// Move a claimed item that can't be decoded from `table` into `failed`. Otherwise, it would stay
+/// claimed (and as such never be picked up again) until the queue is restarted, only to fail to
+/// decode again.
+async fn fail_undecodable(
+    client: &SqlitePool,
+    table: &str,
+    record: &Record,
+    err: &serde_json::Error,
+) -> Result<(), sqlx::Error> {
+    error!(id = record.id, %table, %err, "unable to decode item, moving it to failed");
+
+    let mut tx = client.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO
+        failed (id, parents, item, created_at, message)
+        VALUES ($1, $2,      $3,   $4,         $5     )
+        "#,
+    )
+    .bind(record.id)
+    .bind(&record.parents)
+    .bind(&record.item)
+    .bind(&record.created_at)
+    .bind(format!("unable to decode item: {err}"))
+    .execute(tx.as_mut())
+    .await?;
+
+    sqlx::query(&format!("DELETE FROM {table} WHERE id = $1"))
+        .bind(record.id)
+        .execute(tx.as_mut())
+        .await?;
+
+    tx.commit().await
+}
+
+/// Allocate a new item id. Ids are shared between the `queue` and `optimize` tables, such that an
+/// item keeps a unique id as it moves through the queue.
+async fn next_id(tx: &mut Transaction<'_, Sqlite>) -> Result<i64, sqlx::Error> {
+    sqlx::query("UPDATE item_id SET id = id + 1 RETURNING id")
+        .try_map(|x| Id::from_row(&x))
+        .fetch_one(tx.as_mut())
+        .await
+        .map(|x| x.id)
+}
+
+/// Insert a normalized op into either the `queue` or `optimize` table, as decided by `filter`.
+async fn insert<T: QueueMessage>(
+    tx: &mut Transaction<'_, Sqlite>,
+    filter: &T::Filter,
+    op: Op<T>,
+    parents: &[i64],
+    parent_priority: Option<i32>,
+) -> Result<i64, sqlx::Error> {
+    let (carried, op) = op.take_priority();
+    let priority = resolve_priority(carried, filter.check_priority(&op), parent_priority);
+
+    match filter.check_interest(&op) {
+        FilterResult::Interest(tag) => {
+            let id = insert_optimize(tx, &op, tag, parents, priority).await?;
+            debug!(%id, %priority, "enqueued optimize item");
+            Ok(id)
+        }
+        FilterResult::NoInterest => {
+            let id = insert_ready(tx, &op, parents, priority).await?;
+            debug!(%id, %priority, "enqueued ready item");
+            Ok(id)
+        }
+    }
+}
+
+async fn insert_ready<T: QueueMessage>(
+    tx: &mut Transaction<'_, Sqlite>,
+    op: &Op<T>,
+    parents: &[i64],
+    priority: i32,
+) -> Result<i64, sqlx::Error> {
+    let id = next_id(tx).await?;
+
+    sqlx::query(
+        "
+        INSERT INTO queue (id, item, parents, priority, not_before)
+        VALUES ($1, $2, $3, $4, $5)
+        ",
+    )
+    .bind(id)
+    .bind(Json(op))
+    .bind(Json(parents))
+    .bind(priority)
+    .bind(
+        op.not_before()
+            .map(|not_before| i64::try_from(not_before).unwrap_or(i64::MAX)),
+    )
+    .execute(tx.as_mut())
+    .await?;
+
+    Ok(id)
+}
+
+async fn insert_optimize<T: QueueMessage>(
+    tx: &mut Transaction<'_, Sqlite>,
+    op: &Op<T>,
+    tag: &str,
+    parents: &[i64],
+    priority: i32,
+) -> Result<i64, sqlx::Error> {
+    let id = next_id(tx).await?;
+
+    sqlx::query(
+        "
+        INSERT INTO optimize (id, item, tag, parents, priority)
+        VALUES ($1, $2, $3, $4, $5)
+        ",
+    )
+    .bind(id)
+    .bind(Json(op))
+    .bind(tag)
+    .bind(Json(parents))
+    .bind(priority)
+    .execute(tx.as_mut())
+    .await?;
+
+    Ok(id)
+}
+
+fn de<T: DeserializeOwned>(s: &str) -> Result<T, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(s);
+    deserializer.disable_recursion_limit();
+    let json = T::deserialize(&mut deserializer)?;
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use tempfile::TempDir;
+    use voyager_vm::{
+        defer, noop, now, priority_op, schedule, CallT, CallbackT, Context, Queue, QueueError,
+    };
+
+    use super::*;
+
+    enum UnitMessage {}
+
+    impl QueueMessage for UnitMessage {
+        type Data = ();
+        type Call = ();
+        type Callback = ();
+
+        type Filter = ();
+
+        type Context = ();
+    }
+
+    impl CallT<UnitMessage> for () {
+        async fn process(self, _: Context<&()>) -> Result<Op<UnitMessage>, QueueError> {
+            Ok(noop())
+        }
+    }
+
+    impl CallbackT<UnitMessage> for () {
+        async fn process(
+            self,
+            _: Context<&()>,
+            _: VecDeque<()>,
+        ) -> Result<Op<UnitMessage>, QueueError> {
+            Ok(noop())
+        }
+    }
+
+    fn config(dir: &TempDir) -> SqliteQueueConfig {
+        SqliteQueueConfig {
+            path: dir.path().join("queue.db"),
+            ..Default::default()
+        }
+    }
+
+    async fn process_all(queue: &SqliteQueue<UnitMessage>) -> Vec<Op<UnitMessage>> {
+        let mut processed = vec![];
+        while let Some(op) = queue
+            .process(&(), |op, _| async move { (op, Ok(vec![])) })
+            .await
+            .unwrap()
+        {
+            processed.push(op);
+        }
+        processed
+    }
+
+    async fn list(
+        queue: &SqliteQueue<UnitMessage>,
+        status: ItemStatus,
+    ) -> Vec<(i64, Vec<i64>, Op<UnitMessage>)> {
+        queue
+            .list(status, &ItemQuery::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| (record.id, record.parents, record.item))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn processed_items_are_done() {
+        let dir = TempDir::new().unwrap();
+        let queue = SqliteQueue::<UnitMessage>::new(config(&dir)).await.unwrap();
+
+        queue.enqueue(defer(1), &()).await.unwrap();
+
+        queue
+            .process(&(), |_, _| async move { ((), Ok(vec![defer(2)])) })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            list(&queue, ItemStatus::Done).await,
+            [(1, vec![], defer(1))]
+        );
+        assert_eq!(
+            list(&queue, ItemStatus::Ready).await,
+            [(2, vec![1], defer(2))]
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_items_are_failed() {
+        let dir = TempDir::new().unwrap();
+        let queue = SqliteQueue::<UnitMessage>::new(config(&dir)).await.unwrap();
+
+        queue.enqueue(defer(1), &()).await.unwrap();
+
+        queue
+            .process(&(), |_, _| async move { ((), Err("oops".to_owned())) })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(list(&queue, ItemStatus::Ready).await, []);
+
+        let failed = queue.query_failed_by_id(1).await.unwrap().unwrap();
+        assert_eq!(failed.item.0, defer(1));
+        assert_eq!(failed.message, "oops");
+    }
+
+    #[tokio::test]
+    async fn dequeues_by_priority() {
+        let dir = TempDir::new().unwrap();
+        let queue = SqliteQueue::<UnitMessage>::new(config(&dir)).await.unwrap();
+
+        for op in [
+            defer(1),
+            priority_op(5, defer(2)),
+            priority_op(-1, defer(3)),
+            defer(4),
+        ] {
+            queue.enqueue(op, &()).await.unwrap();
+        }
+
+        assert_eq!(
+            process_all(&queue).await,
+            [defer(2), defer(1), defer(4), defer(3)]
+        );
+    }
+
+    #[tokio::test]
+    async fn holds_scheduled_items() {
+        let dir = TempDir::new().unwrap();
+        let queue = SqliteQueue::<UnitMessage>::new(config(&dir)).await.unwrap();
+
+        queue
+            .enqueue(schedule(now() + 1000, noop()), &())
+            .await
+            .unwrap();
+        queue.enqueue(defer(1), &()).await.unwrap();
+
+        assert_eq!(process_all(&queue).await, [defer(1)]);
+        assert_eq!(list(&queue, ItemStatus::Ready).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn new_releases_claimed_items() {
+        let dir = TempDir::new().unwrap();
+        let config = config(&dir);
+
+        let queue = SqliteQueue::<UnitMessage>::new(config.clone())
+            .await
+            .unwrap();
+
+        queue.enqueue(defer(1), &()).await.unwrap();
+
+        // simulate an instance that exited while processing the item
+        sqlx::query("UPDATE queue SET claimed = 1")
+            .execute(&queue.client)
+            .await
+            .unwrap();
+
+        assert_eq!(process_all(&queue).await, []);
+
+        // opening the database does not release the item, since another instance may be running
+        let opened = SqliteQueue::<UnitMessage>::open(config.clone())
+            .await
+            .unwrap();
+        assert_eq!(process_all(&opened).await, []);
+
+        let queue = SqliteQueue::<UnitMessage>::new(config).await.unwrap();
+        assert_eq!(process_all(&queue).await, [defer(1)]);
+    }
+
+    #[tokio::test]
+    async fn undecodable_items_are_failed() {
+        let dir = TempDir::new().unwrap();
+        let queue = SqliteQueue::<UnitMessage>::new(config(&dir)).await.unwrap();
+
+        sqlx::query("INSERT INTO queue (id, item, parents) VALUES (100, '{\"invalid\":1}', '[]')")
+            .execute(&queue.client)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            queue
+                .process(&(), |op, _| async move { (op, Ok(vec![])) })
+                .await,
+            Err(sqlx::Error::Decode(_))
+        ));
+
+        assert_eq!(list(&queue, ItemStatus::Ready).await, []);
+
+        let message = sqlx::query_scalar::<_, String>("SELECT message FROM failed WHERE id = 100")
+            .fetch_one(&queue.client)
+            .await
+            .unwrap();
+        assert!(message.starts_with("unable to decode item"), "{message}");
+
+        // the queue is not blocked by the item
+        queue.enqueue(defer(2), &()).await.unwrap();
+        assert_eq!(process_all(&queue).await, [defer(2)]);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::header::Header;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct Misbehaviour {
+    pub header_a: Header,
+    pub header_b: Header,
+}
+
+#[cfg(feature = "proto")]
+pub mod proto {
+    use unionlabs::{errors::MissingField, impl_proto_via_try_from_into, required};
+
+    use crate::{header, misbehaviour::Misbehaviour};
+
+    impl_proto_via_try_from_into!(Misbehaviour => protos::ibc::lightclients::tendermint::v1::Misbehaviour);
+
+    impl From<Misbehaviour> for protos::ibc::lightclients::tendermint::v1::Misbehaviour {
+        fn from(value: Misbehaviour) -> Self {
+            #[allow(deprecated)]
+            Self {
+                client_id: Default::default(),
+                header_1: Some(value.header_a.into()),
+                header_2: Some(value.header_b.into()),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, thiserror::Error)]
+    pub enum Error {
+        #[error(transparent)]
+        MissingField(#[from] MissingField),
+        #[error("invalid header")]
+        Header(#[from] header::proto::Error),
+    }
+
+    impl TryFrom<protos::ibc::lightclients::tendermint::v1::Misbehaviour> for Misbehaviour {
+        type Error = Error;
+
+        fn try_from(
+            value: protos::ibc::lightclients::tendermint::v1::Misbehaviour,
+        ) -> Result<Self, Self::Error> {
+            Ok(Self {
+                header_a: required!(value.header_1)?.try_into()?,
+                header_b: required!(value.header_2)?.try_into()?,
+            })
+        }
+    }
+}
@@ -80,13 +80,82 @@ pub fn validators_hash(validator_set: &ValidatorSet) -> H256 {
 }
 
 #[must_use]
-pub fn header_expired(_h: &SignedHeader, _trusting_period: Duration, _now: Timestamp) -> bool {
-    // TODO: Re-enable
-    // let Some(expiration_time) = h.header.time.checked_add(trusting_period) else {
-    //     return false;
-    // };
+pub fn header_expired(h: &SignedHeader, trusting_period: Duration, now: Timestamp) -> bool {
+    let Some(expiration_time) = h.header.time.checked_add(trusting_period) else {
+        return false;
+    };
+
+    expiration_time <= now
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tendermint_light_client_types::Header;
+    use unionlabs::google::protobuf::duration::DURATION_MAX_SECONDS;
+
+    use super::*;
+
+    fn trusted_header() -> SignedHeader {
+        serde_json::from_str::<Header>(&fs::read_to_string("src/test/288.json").unwrap())
+            .unwrap()
+            .signed_header
+    }
+
+    #[test]
+    fn header_not_expired_within_trusting_period() {
+        let header = trusted_header();
+
+        let now = header
+            .header
+            .time
+            .checked_add(Duration::new(99, 0).unwrap())
+            .unwrap();
+
+        assert!(!header_expired(
+            &header,
+            Duration::new(100, 0).unwrap(),
+            now
+        ));
+    }
+
+    #[test]
+    fn header_expired_at_end_of_trusting_period() {
+        let header = trusted_header();
+
+        let now = header
+            .header
+            .time
+            .checked_add(Duration::new(100, 0).unwrap())
+            .unwrap();
+
+        assert!(header_expired(&header, Duration::new(100, 0).unwrap(), now));
+    }
+
+    #[test]
+    fn header_expired_after_trusting_period() {
+        let header = trusted_header();
+
+        let now = header
+            .header
+            .time
+            .checked_add(Duration::new(100, 1).unwrap())
+            .unwrap();
+
+        assert!(header_expired(&header, Duration::new(100, 0).unwrap(), now));
+    }
+
+    #[test]
+    fn header_not_expired_when_expiration_overflows() {
+        let header = trusted_header();
 
-    // expiration_time <= now
+        let now = header.header.time;
 
-    false
+        assert!(!header_expired(
+            &header,
+            Duration::new(DURATION_MAX_SECONDS, 0).unwrap(),
+            now
+        ));
+    }
 }
@@ -52,22 +52,25 @@ impl CosmosSigner {
 impl Display for CosmosSigner {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // TODO: benchmark this, and consider caching it in the struct
-        // bech32(prefix, ripemd(sha256(pubkey)))
-        let encoded = subtle_encoding::bech32::encode(
+        f.write_str(&cosmos_address(
             &self.prefix,
-            ripemd::Ripemd160::new()
-                .chain_update(
-                    sha2::Sha256::new()
-                        .chain_update(self.signing_key.public_key().to_bytes())
-                        .finalize(),
-                )
-                .finalize(),
-        );
-
-        f.write_str(&encoded)
+            &self.signing_key.public_key().to_bytes(),
+        ))
     }
 }
 
+/// The bech32 encoded cosmos account address of a compressed secp256k1 public key:
+/// `bech32(prefix, ripemd(sha256(pubkey)))`.
+#[must_use]
+pub fn cosmos_address(prefix: &str, public_key: &[u8; 33]) -> String {
+    subtle_encoding::bech32::encode(
+        prefix,
+        ripemd::Ripemd160::new()
+            .chain_update(sha2::Sha256::new().chain_update(public_key).finalize())
+            .finalize(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use hex_literal::hex;
@@ -102,6 +102,12 @@ impl ClientType {
     /// [Arbitrum]: https://github.com/OffchainLabs/nitro-contracts
     pub const ARBITRUM: &'static str = "arbitrum";
 
+    /// A client tracking the state of the [Linea] zkEVM L2, settling on
+    /// Ethereum, verified by verifying the L2 settlement on the L1.
+    ///
+    /// [Linea]: https://github.com/Consensys/linea-contracts
+    pub const LINEA: &'static str = "linea";
+
     /// A client tracking the state of a [BeaconKit] chain, verified by verifying the underlying
     /// [CometBFT] consensus.
     ///
@@ -134,7 +140,7 @@ impl ClientType {
     /// [Aptos]: https://github.com/aptos-labs/aptos-core
     pub const STATE_LENS_ICS23_SMT: &'static str = "state-lens/ics23/smt";
 
-    // lots more to come - near, linea, polygon - stay tuned
+    // lots more to come - near, polygon - stay tuned
 }
 
 /// Newtype for consensus types. A consensus is verifiable by potentially many [`ClientType`]s.
@@ -167,6 +173,11 @@ impl ConsensusType {
     /// [Arbitrum]: https://github.com/OffchainLabs/nitro-contracts
     pub const ARBITRUM: &'static str = "arbitrum";
 
+    /// [Linea] zkEVM L2, settling on Ethereum.
+    ///
+    /// [Linea]: https://github.com/Consensys/linea-contracts
+    pub const LINEA: &'static str = "linea";
+
     /// [BeaconKit] consensus.
     ///
     /// [BeaconKit]: https://github.com/berachain/beacon-kit
@@ -177,7 +188,7 @@ impl ConsensusType {
     /// [Movement]: https://github.com/movementlabsxyz/movement
     pub const MOVEMENT: &'static str = "movement";
 
-    // lots more to come - near, linea, polygon - stay tuned
+    // lots more to come - near, polygon - stay tuned
 }
 
 #[cfg(feature = "serde")]
@@ -86,17 +86,16 @@ impl CallbackT<VoyagerMessage> for Callback {
                     .await
                     .map_err(error_object_to_queue_error)?;
 
-                let client_module = ctx
+                let modules = ctx
                     .rpc_server
                     .modules()
-                    .map_err(error_object_to_queue_error)?
+                    .map_err(error_object_to_queue_error)?;
+
+                let client_module = modules
                     .client_module(&client_type, &ibc_interface, &ibc_spec_id)?
                     .with_id(Some(ctx.id()));
 
-                let ibc_spec_handler = ctx
-                    .rpc_server
-                    .modules()
-                    .map_err(error_object_to_queue_error)?
+                let ibc_spec_handler = modules
                     .ibc_spec_handlers
                     .get(&ibc_spec_id)
                     .map_err(error_object_to_queue_error)?;
@@ -3,25 +3,26 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     process::Stdio,
-    sync::Arc,
+    sync::{Arc, RwLock},
     time::Duration,
 };
 
 use anyhow::anyhow;
-use futures::{
-    future,
-    stream::{self, FuturesUnordered},
-    Future, FutureExt, StreamExt, TryStreamExt,
-};
+use futures::{stream::FuturesUnordered, Future, FutureExt, StreamExt};
 use jsonrpsee::{
-    core::client::ClientT,
+    core::{
+        async_trait,
+        client::{BatchResponse, ClientT},
+        params::BatchRequestBuilder,
+        traits::ToRpcParams,
+    },
     server::middleware::rpc::RpcServiceT,
     types::{ErrorObject, ErrorObjectOwned},
 };
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
-use tokio::time::sleep;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{value::RawValue, Map, Value};
+use tokio::{sync::Mutex, time::sleep};
 use tokio_util::sync::CancellationToken;
 use tracing::{
     debug, debug_span, error, info, info_span, instrument, instrument::Instrumented, trace, warn,
@@ -34,7 +35,10 @@ use voyager_core::{ConsensusType, IbcSpecId};
 use voyager_vm::{ItemId, QueueError};
 
 use crate::{
-    context::ibc_spec_handler::IbcSpecHandlers,
+    context::{
+        ibc_spec_handler::IbcSpecHandlers,
+        recording::{RecordedCall, Recording},
+    },
     core::{ChainId, ClientType, IbcInterface},
     module::{
         ClientBootstrapModuleInfo, ClientModuleInfo, ConsensusModuleInfo, PluginInfo,
@@ -49,18 +53,131 @@ pub const STARTUP_ERROR_EXIT_CODE: u8 = 14;
 
 pub mod equivalent_chain_ids;
 pub mod ibc_spec_handler;
+pub mod recording;
 
 #[derive(Debug)]
 pub struct Context {
     pub rpc_server: Server,
 
-    plugins: HashMap<String, ModuleRpcClient>,
+    plugins: RwLock<HashMap<String, LoadedPlugin>>,
+
+    /// The processes of all loaded plugins and modules, keyed by name. This is held for the
+    /// entire duration of a (re)load, such that only one can happen at a time.
+    processes: Mutex<HashMap<String, Process>>,
+
+    equivalent_chain_ids: equivalent_chain_ids::EquivalentChainIds,
 
-    interest_filters: HashMap<String, String>,
+    register_ibc_spec_handlers: fn(&mut IbcSpecHandlers),
+
+    recording: Option<Recording>,
 
     pub cancellation_token: CancellationToken,
 }
 
+#[derive(Debug, Clone)]
+struct LoadedPlugin {
+    client: ModuleRpcClient,
+    interest_filter: String,
+}
+
+/// A running plugin or module.
+#[derive(Debug)]
+struct Process {
+    config: ProcessConfig,
+    client: ModuleRpcClient,
+    /// Cancelled when the plugin or module is removed, stopping both the rpc server for the
+    /// process and the process itself.
+    server_cancellation_token: CancellationToken,
+    /// Cancelled when the process is restarted or removed.
+    process_cancellation_token: CancellationToken,
+    /// Cancelled if the process exits due to an invalid config. This is replaced whenever the
+    /// process is restarted.
+    invalid_config_cancellation_token: CancellationToken,
+}
+
+/// The config a plugin or module process was started with. The process is restarted on reload if
+/// this changes.
+#[derive(Debug, Clone, PartialEq)]
+enum ProcessConfig {
+    Plugin(PluginConfig),
+    Module(ModuleConfig<Value>),
+}
+
+impl ProcessConfig {
+    fn spawn(
+        self,
+        name: String,
+        cancellation_token: CancellationToken,
+        invalid_config_cancellation_token: CancellationToken,
+    ) {
+        match self {
+            ProcessConfig::Plugin(plugin_config) => tokio::spawn(plugin_child_process(
+                name,
+                plugin_config,
+                cancellation_token,
+                invalid_config_cancellation_token,
+            )),
+            ProcessConfig::Module(module_config) => tokio::spawn(module_child_process(
+                name,
+                module_config,
+                cancellation_token,
+                invalid_config_cancellation_token,
+            )),
+        };
+    }
+}
+
+/// How long to wait for newly started or restarted processes to exit due to an invalid config
+/// before considering them successfully (re)started.
+const INVALID_CONFIG_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// The changes made to the loaded plugins and modules by [`Context::reload`], by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReloadSummary {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub restarted: Vec<String>,
+    pub unchanged: Vec<String>,
+    /// Plugins and modules that were to be added or restarted, but exited due to an invalid
+    /// config. These have been stopped.
+    #[serde(default)]
+    pub failed: Vec<String>,
+}
+
+impl ReloadSummary {
+    /// Compute the changes required to go from the `running` processes to the `desired` ones.
+    fn diff(
+        running: &HashMap<String, ProcessConfig>,
+        desired: &HashMap<String, ProcessConfig>,
+    ) -> Self {
+        let mut summary = Self::default();
+
+        for name in running.keys() {
+            if !desired.contains_key(name) {
+                summary.removed.push(name.clone());
+            }
+        }
+
+        for (name, config) in desired {
+            match running.get(name) {
+                Some(running_config) if running_config == config => {
+                    summary.unchanged.push(name.clone())
+                }
+                Some(_) => summary.restarted.push(name.clone()),
+                None => summary.added.push(name.clone()),
+            }
+        }
+
+        summary.added.sort();
+        summary.removed.sort();
+        summary.restarted.sort();
+        summary.unchanged.sort();
+
+        summary
+    }
+}
+
 #[derive(macros::Debug)]
 pub struct Modules {
     state_modules: HashMap<(ChainId, IbcSpecId), ModuleRpcClient>,
@@ -88,12 +205,12 @@ impl voyager_vm::ContextT for Context {}
 pub struct ModuleRpcClient {
     #[debug(skip)]
     client: reconnecting_jsonrpc_ws_client::Client,
-    #[allow(dead_code)]
     name: String,
+    recording: Option<Recording>,
 }
 
 impl ModuleRpcClient {
-    fn new(name: &str) -> Self {
+    fn new(name: &str, recording: Option<Recording>) -> Self {
         let socket = Self::make_socket_path(name);
 
         let client = reconnecting_jsonrpc_ws_client::Client::new({
@@ -115,6 +232,7 @@ impl ModuleRpcClient {
         Self {
             client,
             name: name.to_owned(),
+            recording,
         }
     }
 
@@ -137,6 +255,178 @@ impl ModuleRpcClient {
     pub fn client(&self) -> &reconnecting_jsonrpc_ws_client::Client {
         &self.client
     }
+
+    /// Make a request through `recording`, either recording the response or replaying a
+    /// previously recorded one.
+    async fn recorded_request(
+        &self,
+        recording: &Recording,
+        method: &str,
+        params: Option<Box<RawValue>>,
+    ) -> Result<Result<Value, ErrorObjectOwned>, jsonrpsee::core::client::Error> {
+        let recorded_params = match params
+            .as_deref()
+            .map(|params| serde_json::from_str::<Value>(params.get()))
+            .transpose()?
+        {
+            // the item id is not stable across runs, don't include it in the recording
+            Some(Value::Object(mut params))
+                if params.len() == 2 && params.contains_key("item_id") =>
+            {
+                params.remove("params").unwrap_or_default()
+            }
+            params => params.unwrap_or_default(),
+        };
+
+        if recording.is_replay() {
+            return recording
+                .take(&self.name, method, &recorded_params)
+                .ok_or_else(|| {
+                    jsonrpsee::core::client::Error::Custom(format!(
+                        "no recorded response for `{method}` on `{}`",
+                        self.name
+                    ))
+                });
+        }
+
+        let response = match self
+            .client
+            .request::<Value, _>(method, RawParams(params))
+            .await
+        {
+            Ok(value) => Ok(value),
+            Err(jsonrpsee::core::client::Error::Call(error)) => Err(error),
+            // transport errors are not part of the module's behaviour, don't record them
+            Err(error) => return Err(error),
+        };
+
+        recording.push(RecordedCall {
+            module: self.name.clone(),
+            method: method.to_owned(),
+            params: recorded_params,
+            response: response.clone(),
+        });
+
+        Ok(response)
+    }
+}
+
+/// Already serialized params, to be forwarded as-is.
+struct RawParams(Option<Box<RawValue>>);
+
+impl ToRpcParams for RawParams {
+    fn to_rpc_params(self) -> Result<Option<Box<RawValue>>, serde_json::Error> {
+        Ok(self.0)
+    }
+}
+
+#[async_trait]
+impl ClientT for ModuleRpcClient {
+    async fn notification<Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<(), jsonrpsee::core::client::Error>
+    where
+        Params: ToRpcParams + Send,
+    {
+        self.client.notification(method, params).await
+    }
+
+    async fn request<R, Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<R, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        let Some(recording) = &self.recording else {
+            return self.client.request(method, params).await;
+        };
+
+        match self
+            .recorded_request(recording, method, params.to_rpc_params()?)
+            .await?
+        {
+            Ok(value) => Ok(serde_json::from_value(value)?),
+            Err(error) => Err(jsonrpsee::core::client::Error::Call(error)),
+        }
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned + std::fmt::Debug + 'a,
+    {
+        let Some(recording) = &self.recording else {
+            return self.client.batch_request(batch).await;
+        };
+
+        // each request in the batch is recorded individually, so that they can be replayed
+        // regardless of how they are batched
+        let mut responses = Vec::with_capacity(batch.len());
+        let mut successful_calls = 0;
+        let mut failed_calls = 0;
+
+        for (method, params) in batch {
+            match self.recorded_request(recording, method, params).await? {
+                Ok(value) => {
+                    successful_calls += 1;
+                    responses.push(Ok(serde_json::from_value(value)?));
+                }
+                Err(error) => {
+                    failed_calls += 1;
+                    responses.push(Err(error));
+                }
+            }
+        }
+
+        Ok(BatchResponse::new(
+            successful_calls,
+            responses,
+            failed_calls,
+        ))
+    }
+}
+
+#[async_trait]
+impl ClientT for &ModuleRpcClient {
+    async fn notification<Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<(), jsonrpsee::core::client::Error>
+    where
+        Params: ToRpcParams + Send,
+    {
+        <ModuleRpcClient as ClientT>::notification(*self, method, params).await
+    }
+
+    async fn request<R, Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<R, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        <ModuleRpcClient as ClientT>::request(*self, method, params).await
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned + std::fmt::Debug + 'a,
+    {
+        <ModuleRpcClient as ClientT>::batch_request(*self, batch).await
+    }
 }
 
 pub(crate) trait WithId: Sized + ClientT + Send + Sync
@@ -262,20 +552,158 @@ const fn default_enabled() -> bool {
 }
 
 impl Context {
-    #[instrument(name = "context_new", skip_all)]
     pub async fn new(
         plugin_configs: Vec<PluginConfig>,
         module_configs: ModulesConfig,
         equivalent_chain_ids: equivalent_chain_ids::EquivalentChainIds,
         register_ibc_spec_handlers: fn(&mut IbcSpecHandlers),
     ) -> anyhow::Result<Self> {
-        let cancellation_token = CancellationToken::new();
+        Self::new_with_recording(
+            plugin_configs,
+            module_configs,
+            equivalent_chain_ids,
+            register_ibc_spec_handlers,
+            None,
+        )
+        .await
+    }
+
+    /// Same as [`Context::new`], but records all requests made to the modules and plugins, or
+    /// replays them from a previous recording. See [`Recording`] for more information.
+    ///
+    /// If the recording is being replayed, the modules and plugins are not started.
+    #[instrument(name = "context_new", skip_all)]
+    pub async fn new_with_recording(
+        plugin_configs: Vec<PluginConfig>,
+        module_configs: ModulesConfig,
+        equivalent_chain_ids: equivalent_chain_ids::EquivalentChainIds,
+        register_ibc_spec_handlers: fn(&mut IbcSpecHandlers),
+        recording: Option<Recording>,
+    ) -> anyhow::Result<Self> {
+        let replay = recording.as_ref().is_some_and(Recording::is_replay);
+
+        let this = Self {
+            rpc_server: Server::new(),
+            plugins: RwLock::default(),
+            processes: Mutex::default(),
+            equivalent_chain_ids,
+            register_ibc_spec_handlers,
+            recording,
+            cancellation_token: CancellationToken::new(),
+        };
+
+        info!("spawning {} plugins", plugin_configs.len());
+
+        let modules = match this.load(plugin_configs, module_configs).await {
+            Ok((modules, summary)) if summary.failed.is_empty() => modules,
+            Ok((_, summary)) => {
+                this.cancellation_token.cancel();
+                return Err(anyhow!("invalid config for {}", summary.failed.join(", ")));
+            }
+            Err(err) => {
+                this.cancellation_token.cancel();
+                return Err(err);
+            }
+        };
+
+        this.rpc_server.start(Arc::new(modules));
+
+        if !replay {
+            info!("checking for plugin health...");
+
+            let plugins = this
+                .plugins
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(name, plugin)| (name.clone(), plugin.client.clone()))
+                .collect::<Vec<_>>();
+
+            let mut futures = plugins
+                .iter()
+                .map(|(name, client)| async move {
+                    match client
+                        .client
+                        .wait_until_connected(Duration::from_secs(10))
+                        .instrument(debug_span!("health check", %name))
+                        .await
+                    {
+                        Ok(_) => {
+                            info!("plugin {name} connected")
+                        }
+                        Err(_) => {
+                            warn!("plugin {name} failed to connect after 10 seconds")
+                        }
+                    }
+                })
+                .collect::<FuturesUnordered<_>>();
+
+            match this
+                .cancellation_token
+                .run_until_cancelled(async { while let Some(()) = futures.next().await {} })
+                .await
+            {
+                Some(()) => {}
+                None => return Err(anyhow!("startup error")),
+            }
+        }
+
+        Ok(this)
+    }
+
+    /// Reload the plugins and modules with a new config, without interrupting any other work.
+    ///
+    /// Plugins and modules are identified by their name. Only the processes of plugins and
+    /// modules that were added, removed, or whose config changed are started, stopped, or
+    /// restarted respectively; all others are left running. Requests to modules that are
+    /// already in flight finish against the previously loaded modules.
+    ///
+    /// The new config is fully validated and the rpc servers for any added plugins and modules
+    /// are started before any processes are touched, so an invalid config leaves the currently
+    /// loaded plugins and modules as they are. Plugins and modules that exit due to an invalid
+    /// config after being (re)started are stopped, unregistered, and reported in
+    /// [`ReloadSummary::failed`]; this does not affect any other plugins or modules. Note that the equivalent chain ids can not
+    /// be reloaded.
+    #[instrument(name = "context_reload", skip_all)]
+    pub async fn reload(
+        &self,
+        plugin_configs: Vec<PluginConfig>,
+        module_configs: ModulesConfig,
+    ) -> anyhow::Result<ReloadSummary> {
+        let (modules, summary) = self.load(plugin_configs, module_configs).await?;
+
+        self.rpc_server.reload(Arc::new(modules));
+
+        info!(
+            added = summary.added.len(),
+            removed = summary.removed.len(),
+            restarted = summary.restarted.len(),
+            unchanged = summary.unchanged.len(),
+            failed = summary.failed.len(),
+            "reloaded plugins and modules"
+        );
+
+        Ok(summary)
+    }
+
+    /// Load the plugins and modules in the provided configs, starting, stopping, and restarting
+    /// processes as required. The plugins are replaced immediately, but the returned modules
+    /// still need to be passed to the rpc server.
+    #[allow(clippy::too_many_lines)]
+    async fn load(
+        &self,
+        plugin_configs: Vec<PluginConfig>,
+        module_configs: ModulesConfig,
+    ) -> anyhow::Result<(Modules, ReloadSummary)> {
+        let replay = self.recording.as_ref().is_some_and(Recording::is_replay);
+
+        let mut processes = self.processes.lock().await;
 
         let mut ibc_spec_handlers = IbcSpecHandlers {
             handlers: Default::default(),
         };
 
-        register_ibc_spec_handlers(&mut ibc_spec_handlers);
+        (self.register_ibc_spec_handlers)(&mut ibc_spec_handlers);
 
         let mut modules = Modules {
             state_modules: Default::default(),
@@ -290,90 +718,70 @@ impl Context {
 
         let mut plugins = HashMap::default();
 
-        let mut interest_filters = HashMap::default();
-
-        let main_rpc_server = Server::new();
+        // the processes that should be running once everything is loaded
+        let mut desired = HashMap::<String, (ProcessConfig, ModuleRpcClient)>::default();
 
-        info!("spawning {} plugins", plugin_configs.len());
-
-        stream::iter(plugin_configs)
-            .filter(|plugin_config| {
-                future::ready(if !plugin_config.enabled {
-                    info!(
-                        module_path = %plugin_config.path.to_string_lossy(),
-                        "module is not enabled, skipping"
-                    );
-                    false
-                } else {
-                    true
-                })
-            })
-            .zip(stream::repeat(main_rpc_server.clone()))
-            .map(Ok)
-            .try_filter_map(|(plugin_config, server)| async move {
-                if !plugin_config.enabled {
-                    info!(
-                        module_path = %plugin_config.path.to_string_lossy(),
-                        "module is not enabled, skipping"
-                    );
-                    Ok(None)
-                } else {
-                    let plugin_info = get_plugin_info(&plugin_config)?;
+        // the socket path of a process only depends on it's name, so clients can be reused
+        // across restarts
+        let client = |name: &str| {
+            processes.get(name).map_or_else(
+                || ModuleRpcClient::new(name, self.recording.clone()),
+                |process| process.client.clone(),
+            )
+        };
 
-                    debug!("starting rpc server for plugin {}", plugin_info.name);
-                    tokio::spawn(module_rpc_server(&plugin_info.name, server).await?);
+        for plugin_config in plugin_configs {
+            if !plugin_config.enabled {
+                info!(
+                    module_path = %plugin_config.path.to_string_lossy(),
+                    "module is not enabled, skipping"
+                );
+                continue;
+            }
 
-                    Ok(Some((plugin_config, plugin_info)))
-                }
-            })
-            .try_for_each_concurrent(
-                None,
-                |(
-                    plugin_config,
-                    PluginInfo {
-                        name,
-                        interest_filter,
-                    },
-                )| {
-                    info!("registering plugin {}", name);
-
-                    tokio::spawn(plugin_child_process(
-                        name.clone(),
-                        plugin_config.clone(),
-                        cancellation_token.clone(),
-                    ));
+            let PluginInfo {
+                name,
+                interest_filter,
+            } = get_plugin_info(&plugin_config)?;
 
-                    let rpc_client = ModuleRpcClient::new(&name);
+            info!("registering plugin {}", name);
 
-                    let prev = plugins.insert(name.clone(), rpc_client.clone());
+            let rpc_client = client(&name);
 
-                    if prev.is_some() {
-                        return future::ready(Err(anyhow!(
-                            "multiple plugins configured with name `{name}`"
-                        )));
-                    }
+            let prev = desired.insert(
+                name.clone(),
+                (ProcessConfig::Plugin(plugin_config), rpc_client.clone()),
+            );
 
-                    info!("registered plugin {name}");
+            if prev.is_some() {
+                return Err(anyhow!("multiple plugins configured with name `{name}`"));
+            }
 
-                    interest_filters.insert(name, interest_filter);
+            info!("registered plugin {name}");
 
-                    future::ready(Ok(()))
+            plugins.insert(
+                name,
+                LoadedPlugin {
+                    client: rpc_client,
+                    interest_filter,
                 },
-            )
-            .await?;
+            );
+        }
 
-        module_startup(
+        register_modules(
             module_configs.state,
-            cancellation_token.clone(),
-            main_rpc_server.clone(),
+            &mut desired,
+            &client,
             |info| info.id(),
             |StateModuleInfo {
                  chain_id,
                  ibc_spec_id,
              },
              rpc_client| {
-                for equivalent_chain_id in
-                    equivalent_chain_ids.equivalents(chain_id).chain([chain_id])
+                for equivalent_chain_id in self
+                    .equivalent_chain_ids
+                    .equivalents(chain_id)
+                    .chain([chain_id])
                 {
                     let prev = modules.state_modules.insert(
                         (equivalent_chain_id.clone(), ibc_spec_id.clone()),
@@ -390,21 +798,22 @@ impl Context {
 
                 Ok(())
             },
-        )
-        .await?;
+        )?;
 
-        module_startup(
+        register_modules(
             module_configs.proof,
-            cancellation_token.clone(),
-            main_rpc_server.clone(),
+            &mut desired,
+            &client,
             |info| info.id(),
             |ProofModuleInfo {
                  chain_id,
                  ibc_spec_id,
              },
              rpc_client| {
-                for equivalent_chain_id in
-                    equivalent_chain_ids.equivalents(chain_id).chain([chain_id])
+                for equivalent_chain_id in self
+                    .equivalent_chain_ids
+                    .equivalents(chain_id)
+                    .chain([chain_id])
                 {
                     let prev = modules.proof_modules.insert(
                         (equivalent_chain_id.clone(), ibc_spec_id.clone()),
@@ -421,21 +830,22 @@ impl Context {
 
                 Ok(())
             },
-        )
-        .await?;
+        )?;
 
-        module_startup(
+        register_modules(
             module_configs.consensus,
-            cancellation_token.clone(),
-            main_rpc_server.clone(),
+            &mut desired,
+            &client,
             |info| info.id(),
             |ConsensusModuleInfo {
                  chain_id,
                  consensus_type,
              },
              rpc_client| {
-                for equivalent_chain_id in
-                    equivalent_chain_ids.equivalents(chain_id).chain([chain_id])
+                for equivalent_chain_id in self
+                    .equivalent_chain_ids
+                    .equivalents(chain_id)
+                    .chain([chain_id])
                 {
                     let prev = modules
                         .consensus_modules
@@ -458,13 +868,12 @@ impl Context {
 
                 Ok(())
             },
-        )
-        .await?;
+        )?;
 
-        module_startup(
+        register_modules(
             module_configs.client,
-            cancellation_token.clone(),
-            main_rpc_server.clone(),
+            &mut desired,
+            &client,
             |info| info.id(),
             |ClientModuleInfo {
                  client_type,
@@ -512,21 +921,22 @@ impl Context {
 
                 Ok(())
             },
-        )
-        .await?;
+        )?;
 
-        module_startup(
+        register_modules(
             module_configs.client_bootstrap,
-            cancellation_token.clone(),
-            main_rpc_server.clone(),
+            &mut desired,
+            &client,
             |info| info.id(),
             |ClientBootstrapModuleInfo {
                  client_type,
                  chain_id,
              },
              rpc_client| {
-                for equivalent_chain_id in
-                    equivalent_chain_ids.equivalents(chain_id).chain([chain_id])
+                for equivalent_chain_id in self
+                    .equivalent_chain_ids
+                    .equivalents(chain_id)
+                    .chain([chain_id])
                 {
                     let prev = modules.client_bootstrap_modules.insert(
                         (equivalent_chain_id.clone(), client_type.clone()),
@@ -559,89 +969,192 @@ impl Context {
 
                 Ok(())
             },
-        )
-        .await?;
+        )?;
 
-        main_rpc_server.start(Arc::new(modules));
+        let mut summary = ReloadSummary::diff(
+            &processes
+                .iter()
+                .map(|(name, process)| (name.clone(), process.config.clone()))
+                .collect(),
+            &desired
+                .iter()
+                .map(|(name, (config, _))| (name.clone(), config.clone()))
+                .collect(),
+        );
 
-        info!("checking for plugin health...");
+        // start the rpc servers for all added processes before touching any of the running ones,
+        // such that if any of them fail to start, everything is left as it was
+        let mut servers = HashMap::new();
 
-        {
-            let mut futures = plugins
-                .iter()
-                .map(|(name, client)| async move {
-                    match client
-                        .client
-                        .wait_until_connected(Duration::from_secs(10))
-                        .instrument(debug_span!("health check", %name))
-                        .await
-                    {
-                        Ok(_) => {
-                            info!("plugin {name} connected")
-                        }
-                        Err(_) => {
-                            warn!("plugin {name} failed to connect after 10 seconds")
-                        }
+        if !replay {
+            for name in &summary.added {
+                debug!("starting rpc server for {name}");
+
+                servers.insert(
+                    name.clone(),
+                    module_rpc_server(name, self.rpc_server.clone()).await?,
+                );
+            }
+        }
+
+        for name in &summary.removed {
+            let process = processes.remove(name).expect("process exists; qed;");
+
+            info!("stopping {name}");
+
+            process.server_cancellation_token.cancel();
+            process.client.client.shutdown();
+        }
+
+        for name in &summary.restarted {
+            let (config, _) = desired.remove(name).expect("process is desired; qed;");
+            let process = processes.get_mut(name).expect("process exists; qed;");
+
+            info!("config for {name} changed, restarting");
+
+            process.process_cancellation_token.cancel();
+            process.process_cancellation_token = process.server_cancellation_token.child_token();
+            process.invalid_config_cancellation_token = CancellationToken::new();
+
+            if !replay {
+                config.clone().spawn(
+                    name.clone(),
+                    process.process_cancellation_token.clone(),
+                    process.invalid_config_cancellation_token.clone(),
+                );
+            }
+
+            process.config = config;
+        }
+
+        for name in &summary.added {
+            let (config, client) = desired.remove(name).expect("process is desired; qed;");
+
+            let server_cancellation_token = self.cancellation_token.child_token();
+            let process_cancellation_token = server_cancellation_token.child_token();
+            let invalid_config_cancellation_token = CancellationToken::new();
+
+            if let Some(server) = servers.remove(name) {
+                tokio::spawn({
+                    let server_cancellation_token = server_cancellation_token.clone();
+                    async move {
+                        server_cancellation_token.run_until_cancelled(server).await;
                     }
-                })
-                .collect::<FuturesUnordered<_>>();
+                });
 
-            match cancellation_token
-                .run_until_cancelled(async { while let Some(()) = futures.next().await {} })
-                .await
-            {
-                Some(()) => {}
-                None => return Err(anyhow!("startup error")),
+                config.clone().spawn(
+                    name.clone(),
+                    process_cancellation_token.clone(),
+                    invalid_config_cancellation_token.clone(),
+                );
             }
+
+            processes.insert(
+                name.clone(),
+                Process {
+                    config,
+                    client,
+                    server_cancellation_token,
+                    process_cancellation_token,
+                    invalid_config_cancellation_token,
+                },
+            );
         }
 
-        Ok(Self {
-            rpc_server: main_rpc_server,
-            plugins,
-            interest_filters,
-            cancellation_token,
-        })
+        if !replay {
+            summary.failed = wait_for_invalid_configs(
+                summary
+                    .added
+                    .iter()
+                    .chain(&summary.restarted)
+                    .map(|name| (name, &processes[name])),
+            )
+            .await;
+
+            for name in &summary.failed {
+                let process = processes.remove(name).expect("process exists; qed;");
+
+                error!("{name} exited due to an invalid config, stopping");
+
+                process.server_cancellation_token.cancel();
+                process.client.client.shutdown();
+
+                plugins.remove(name);
+            }
+
+            modules.remove(&summary.failed);
+
+            summary.added.retain(|name| !summary.failed.contains(name));
+            summary
+                .restarted
+                .retain(|name| !summary.failed.contains(name));
+        }
+
+        *self.plugins.write().unwrap() = plugins;
+
+        Ok((modules, summary))
     }
 
     pub async fn shutdown(self) {
         self.cancellation_token.cancel();
 
-        for (name, client) in self.plugins {
-            debug!("shutting down plugin client for {name}");
-            client.client.shutdown();
+        for (name, process) in self.processes.into_inner() {
+            debug!("shutting down client for {name}");
+            process.client.client.shutdown();
         }
     }
 
-    pub fn plugin(
-        &self,
-        name: impl AsRef<str>,
-    ) -> Result<&reconnecting_jsonrpc_ws_client::Client, PluginNotFound> {
-        Ok(self
-            .plugins
+    pub fn plugin(&self, name: impl AsRef<str>) -> Result<ModuleRpcClient, PluginNotFound> {
+        self.plugins
+            .read()
+            .unwrap()
             .get(name.as_ref())
+            .map(|plugin| plugin.client.clone())
             .ok_or_else(|| PluginNotFound {
                 name: name.as_ref().into(),
-            })?
-            .client())
+            })
     }
 
     pub fn plugin_client_raw(
         &self,
         name: impl AsRef<str>,
-    ) -> Result<&ModuleRpcClient, PluginNotFound> {
-        self.plugins
-            .get(name.as_ref())
-            .ok_or_else(|| PluginNotFound {
-                name: name.as_ref().into(),
-            })
+    ) -> Result<ModuleRpcClient, PluginNotFound> {
+        self.plugin(name)
     }
 
-    pub fn interest_filters(&self) -> &HashMap<String, String> {
-        &self.interest_filters
+    /// The interest filters of all currently loaded plugins, keyed by plugin name.
+    pub fn interest_filters(&self) -> HashMap<String, String> {
+        self.plugins
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, plugin)| (name.clone(), plugin.interest_filter.clone()))
+            .collect()
     }
 }
 
 impl Modules {
+    /// Remove all modules with one of the provided names, along with the consensus types that are
+    /// no longer registered by any module.
+    fn remove(&mut self, names: &[String]) {
+        let keep = |client: &ModuleRpcClient| !names.contains(&client.name);
+
+        self.state_modules.retain(|_, client| keep(client));
+        self.proof_modules.retain(|_, client| keep(client));
+        self.consensus_modules.retain(|_, client| keep(client));
+        self.client_modules.retain(|_, client| keep(client));
+        self.client_bootstrap_modules
+            .retain(|_, client| keep(client));
+
+        self.chain_consensus_types
+            .retain(|chain_id, _| self.consensus_modules.contains_key(chain_id));
+        self.client_consensus_types.retain(|client_type, _| {
+            self.client_modules
+                .keys()
+                .any(|(module_client_type, _, _)| module_client_type == client_type)
+        });
+    }
+
     pub fn info(&self) -> LoadedModulesInfo {
         let state = self
             .state_modules
@@ -729,15 +1242,13 @@ impl Modules {
         chain_id: &ChainId,
         ibc_spec_id: &IbcSpecId,
         // ) -> Result<&'a (impl RawStateModuleClient + 'a), StateModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, StateModuleNotFound> {
-        Ok(self
-            .state_modules
+    ) -> Result<&'a ModuleRpcClient, StateModuleNotFound> {
+        self.state_modules
             .get(&(chain_id.clone(), ibc_spec_id.clone()))
             .ok_or_else(|| StateModuleNotFound {
                 chain_id: chain_id.clone(),
                 ibc_spec_id: ibc_spec_id.clone(),
-            })?
-            .client())
+            })
     }
 
     pub fn proof_module<'a, 'b, 'c: 'a>(
@@ -745,27 +1256,23 @@ impl Modules {
         chain_id: &ChainId,
         ibc_spec_id: &IbcSpecId,
         // ) -> Result<&'a (impl RawProofModuleClient + 'a), ProofModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, ProofModuleNotFound> {
-        Ok(self
-            .proof_modules
+    ) -> Result<&'a ModuleRpcClient, ProofModuleNotFound> {
+        self.proof_modules
             .get(&(chain_id.clone(), ibc_spec_id.clone()))
             .ok_or_else(|| ProofModuleNotFound {
                 chain_id: chain_id.clone(),
                 ibc_spec_id: ibc_spec_id.clone(),
-            })?
-            .client())
+            })
     }
 
     pub fn consensus_module<'a, 'b, 'c: 'a>(
         &'a self,
         chain_id: &ChainId,
         // ) -> Result<&'a (impl jsonrpsee::core::client::ClientT + 'a), ConsensusModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, ConsensusModuleNotFound> {
-        Ok(self
-            .consensus_modules
+    ) -> Result<&'a ModuleRpcClient, ConsensusModuleNotFound> {
+        self.consensus_modules
             .get(chain_id)
-            .ok_or_else(|| ConsensusModuleNotFound(chain_id.clone()))?
-            .client())
+            .ok_or_else(|| ConsensusModuleNotFound(chain_id.clone()))
     }
 
     pub fn client_module<'a, 'b, 'c: 'a>(
@@ -774,13 +1281,13 @@ impl Modules {
         ibc_interface: &IbcInterface,
         ibc_spec_id: &IbcSpecId,
         // ) -> Result<&'a (impl ClientModuleClient + 'a), ClientModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, ClientModuleNotFound> {
+    ) -> Result<&'a ModuleRpcClient, ClientModuleNotFound> {
         match self.client_modules.get(&(
             client_type.clone(),
             ibc_interface.clone(),
             ibc_spec_id.clone(),
         )) {
-            Some(client_module) => Ok(client_module.client()),
+            Some(client_module) => Ok(client_module),
             None => Err(ClientModuleNotFound::NotFound {
                 client_type: client_type.clone(),
                 ibc_interface: ibc_interface.clone(),
@@ -794,15 +1301,13 @@ impl Modules {
         chain_id: &ChainId,
         client_type: &ClientType,
         // ) -> Result<&'a (impl jsonrpsee::core::client::ClientT + 'a), ConsensusModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, ClientBootstrapModuleNotFound> {
-        Ok(self
-            .client_bootstrap_modules
+    ) -> Result<&'a ModuleRpcClient, ClientBootstrapModuleNotFound> {
+        self.client_bootstrap_modules
             .get(&(chain_id.clone(), client_type.clone()))
             .ok_or_else(|| ClientBootstrapModuleNotFound {
                 chain_id: chain_id.clone(),
                 client_type: client_type.clone(),
-            })?
-            .client())
+            })
     }
 }
 
@@ -821,6 +1326,7 @@ async fn plugin_child_process(
     plugin_name: String,
     module_config: PluginConfig,
     cancellation_token: CancellationToken,
+    invalid_config_cancellation_token: CancellationToken,
 ) {
     let client_socket = ModuleRpcClient::make_socket_path(&plugin_name);
     let server_socket = make_module_rpc_server_socket_path(&plugin_name);
@@ -842,6 +1348,7 @@ async fn plugin_child_process(
             &module_config.config.to_string(),
         ],
         cancellation_token,
+        invalid_config_cancellation_token,
     )
     .await
 }
@@ -851,6 +1358,7 @@ async fn module_child_process<Info: Serialize>(
     module_name: String,
     module_config: ModuleConfig<Info>,
     cancellation_token: CancellationToken,
+    invalid_config_cancellation_token: CancellationToken,
 ) {
     let client_socket = ModuleRpcClient::make_socket_path(&module_name);
     let server_socket = make_module_rpc_server_socket_path(&module_name);
@@ -867,11 +1375,48 @@ async fn module_child_process<Info: Serialize>(
             &serde_json::to_string(&module_config.info).unwrap(),
         ],
         cancellation_token,
+        invalid_config_cancellation_token,
     )
     .await
 }
 
-async fn lazarus_pit(cmd: &Path, args: &[&str], cancellation_token: CancellationToken) {
+/// Wait up to [`INVALID_CONFIG_GRACE_PERIOD`] for any of the provided processes to exit due to an
+/// invalid config, returning the (sorted) names of those that did.
+async fn wait_for_invalid_configs<'a>(
+    processes: impl Iterator<Item = (&'a String, &'a Process)>,
+) -> Vec<String> {
+    let mut futures = processes
+        .map(|(name, process)| {
+            let token = process.invalid_config_cancellation_token.clone();
+            async move {
+                tokio::select! {
+                    _ = token.cancelled() => Some(name.clone()),
+                    _ = sleep(INVALID_CONFIG_GRACE_PERIOD) => None,
+                }
+            }
+        })
+        .collect::<FuturesUnordered<_>>();
+
+    let mut failed = vec![];
+
+    while let Some(res) = futures.next().await {
+        failed.extend(res);
+    }
+
+    failed.sort();
+
+    failed
+}
+
+/// Run `cmd`, restarting it whenever it exits until `cancellation_token` is cancelled. If the
+/// process exits due to an invalid config, `invalid_config_cancellation_token` is cancelled and
+/// the process is not restarted.
+async fn lazarus_pit(
+    cmd: &Path,
+    args: &[&str],
+    cancellation_token: CancellationToken,
+    invalid_config_cancellation_token: CancellationToken,
+) {
     let mut attempt = 0;
 
     loop {
@@ -934,7 +1479,8 @@ async fn lazarus_pit(cmd: &Path, args: &[&str], cancellation_token: Cancellation
                             .is_some_and(|c| c == INVALID_CONFIG_EXIT_CODE as i32)
                         {
                             error!(%id, "invalid config for plugin or module");
-                            cancellation_token.cancel();
+                            invalid_config_cancellation_token.cancel();
+                            break;
                         }
                     }
                     Err(err) => {
@@ -1072,64 +1618,123 @@ pub fn get_plugin_info(module_config: &PluginConfig) -> anyhow::Result<PluginInf
     Ok(serde_json::from_slice(&output.stdout).unwrap())
 }
 
-async fn module_startup<Info: Serialize + Clone + Unpin + Send + 'static>(
+/// Register the enabled modules in `configs` with `push_f`, adding them to the processes that
+/// should be running.
+fn register_modules<Info: Serialize>(
     configs: Vec<ModuleConfig<Info>>,
-    cancellation_token: CancellationToken,
-    main_rpc_server: Server,
+    desired: &mut HashMap<String, (ProcessConfig, ModuleRpcClient)>,
+    client_f: impl Fn(&str) -> ModuleRpcClient,
     id_f: fn(&Info) -> String,
     mut push_f: impl FnMut(&Info, ModuleRpcClient) -> anyhow::Result<()>,
 ) -> anyhow::Result<()> {
-    stream::iter(configs)
-        .filter(|module_config| {
-            future::ready(if !module_config.enabled {
-                info!(
-                    module_path = %module_config.path.to_string_lossy(),
-                    "module is not enabled, skipping"
-                );
-                false
-            } else {
-                true
-            })
-        })
-        .zip(stream::repeat(main_rpc_server.clone()))
-        .map::<anyhow::Result<_>, _>(anyhow::Result::Ok)
-        .try_filter_map(|(module_config, server)| async move {
-            if !module_config.enabled {
-                info!(
-                    module_path = %module_config.path.to_string_lossy(),
-                    "module is not enabled, skipping"
-                );
-                anyhow::Result::Ok(None)
-            } else {
-                debug!(
-                    "starting rpc server for module {}",
-                    id_f(&module_config.info)
-                );
-                tokio::spawn(module_rpc_server(&id_f(&module_config.info), server).await?);
+    for module_config in configs {
+        if !module_config.enabled {
+            info!(
+                module_path = %module_config.path.to_string_lossy(),
+                "module is not enabled, skipping"
+            );
+            continue;
+        }
 
-                anyhow::Result::Ok(Some(module_config))
-            }
-        })
-        .try_collect::<FuturesUnordered<_>>()
-        .await?
-        .into_iter()
-        .try_for_each(|module_config| {
-            let id = id_f(&module_config.info);
+        let id = id_f(&module_config.info);
+
+        info!("registering module {}", id);
+
+        let rpc_client = client_f(&id);
 
-            info!("registering module {}", id);
+        push_f(&module_config.info, rpc_client.clone())?;
 
-            tokio::spawn(module_child_process(
-                id.clone(),
-                module_config.clone(),
-                cancellation_token.clone(),
+        let prev = desired.insert(
+            id.clone(),
+            (
+                ProcessConfig::Module(ModuleConfig {
+                    path: module_config.path,
+                    info: serde_json::to_value(&module_config.info)?,
+                    config: module_config.config,
+                    enabled: module_config.enabled,
+                }),
+                rpc_client,
+            ),
+        );
+
+        if prev.is_some() {
+            return Err(anyhow!(
+                "multiple plugins or modules configured with name `{id}`"
             ));
+        }
 
-            let rpc_client = ModuleRpcClient::new(&id);
+        info!("registered module {id}");
+    }
+
+    Ok(())
+}
 
-            push_f(&module_config.info, rpc_client)?;
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
 
-            info!("registered module {id}");
+    use super::*;
 
-            Ok(())
+    fn plugin(path: &str, config: Value) -> ProcessConfig {
+        ProcessConfig::Plugin(PluginConfig {
+            path: path.into(),
+            config,
+            enabled: true,
         })
+    }
+
+    #[test]
+    fn reload_summary_diff() {
+        let running = [
+            ("unchanged".to_owned(), plugin("a", json!({}))),
+            ("config-changed".to_owned(), plugin("b", json!({ "x": 1 }))),
+            ("path-changed".to_owned(), plugin("c", json!({}))),
+            ("removed".to_owned(), plugin("d", json!({}))),
+        ]
+        .into_iter()
+        .collect();
+
+        let desired = [
+            ("unchanged".to_owned(), plugin("a", json!({}))),
+            ("config-changed".to_owned(), plugin("b", json!({ "x": 2 }))),
+            ("path-changed".to_owned(), plugin("c2", json!({}))),
+            ("added".to_owned(), plugin("e", json!({}))),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            ReloadSummary::diff(&running, &desired),
+            ReloadSummary {
+                added: vec!["added".to_owned()],
+                removed: vec!["removed".to_owned()],
+                restarted: vec!["config-changed".to_owned(), "path-changed".to_owned()],
+                unchanged: vec!["unchanged".to_owned()],
+                failed: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn reload_summary_diff_empty() {
+        let running = [("a".to_owned(), plugin("a", json!({})))]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            ReloadSummary::diff(&running, &HashMap::new()),
+            ReloadSummary {
+                removed: vec!["a".to_owned()],
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(
+            ReloadSummary::diff(&HashMap::new(), &running),
+            ReloadSummary {
+                added: vec!["a".to_owned()],
+                ..Default::default()
+            }
+        );
+    }
 }
@@ -0,0 +1,275 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use jsonrpsee::types::ErrorObjectOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+/// A single request made to a module or plugin, along with it's response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordedCall {
+    /// The name of the module or plugin the request was sent to.
+    pub module: String,
+    pub method: String,
+    /// The params of the request, without the id of the item that made the request (which is
+    /// not stable across runs).
+    pub params: Value,
+    pub response: Result<Value, ErrorObjectOwned>,
+}
+
+/// Records the responses of all requests made to modules and plugins, or replays previously
+/// recorded responses instead of making the requests.
+///
+/// This is used to reproduce the processing of an op offline, without access to the chains that
+/// the modules and plugins interact with (see `voyager dry-run`). Note that requests made by
+/// plugins directly to their chains are not recorded, only the requests that pass through
+/// voyager.
+#[derive(Debug, Clone)]
+pub enum Recording {
+    /// Forward all requests to the modules and plugins, recording the responses.
+    Record(Arc<Mutex<Vec<RecordedCall>>>),
+    /// Don't start any modules or plugins, instead answering all requests from a previous
+    /// recording. Each recorded response is only returned once.
+    Replay(Arc<Mutex<Vec<Option<RecordedCall>>>>),
+}
+
+impl Recording {
+    #[must_use]
+    pub fn record() -> Self {
+        Self::Record(Arc::default())
+    }
+
+    /// Load a recording previously written with [`Recording::save`], to be replayed.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        let calls = serde_json::from_slice::<Vec<RecordedCall>>(
+            &std::fs::read(path)
+                .with_context(|| format!("unable to read recording at {}", path.display()))?,
+        )
+        .with_context(|| format!("unable to parse recording at {}", path.display()))?;
+
+        debug!(calls = calls.len(), path = %path.display(), "loaded recording");
+
+        Ok(Self::Replay(Arc::new(Mutex::new(
+            calls.into_iter().map(Some).collect(),
+        ))))
+    }
+
+    /// Write all of the recorded calls to `path` as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+
+        let calls = match self {
+            Recording::Record(calls) => calls.lock().unwrap().clone(),
+            Recording::Replay(calls) => calls.lock().unwrap().iter().flatten().cloned().collect(),
+        };
+
+        std::fs::write(
+            path,
+            serde_json::to_vec_pretty(&calls).expect("serialization is infallible; qed;"),
+        )
+        .with_context(|| format!("unable to write recording to {}", path.display()))?;
+
+        debug!(calls = calls.len(), path = %path.display(), "saved recording");
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn is_replay(&self) -> bool {
+        matches!(self, Self::Replay(_))
+    }
+
+    pub(crate) fn push(&self, call: RecordedCall) {
+        match self {
+            Recording::Record(calls) => calls.lock().unwrap().push(call),
+            Recording::Replay(_) => {}
+        }
+    }
+
+    /// Take the recorded response for a request.
+    ///
+    /// Requests are matched on their module, method and params, in the order they were recorded.
+    /// If there is no exact match (for example, if the params contain a timestamp), the first
+    /// unused response for the same module and method is returned instead.
+    pub(crate) fn take(
+        &self,
+        module: &str,
+        method: &str,
+        params: &Value,
+    ) -> Option<Result<Value, ErrorObjectOwned>> {
+        let Recording::Replay(calls) = self else {
+            return None;
+        };
+
+        let mut calls = calls.lock().unwrap();
+
+        let matches = |call: &Option<RecordedCall>, exact: bool| {
+            call.as_ref().is_some_and(|call| {
+                call.module == module && call.method == method && (!exact || &call.params == params)
+            })
+        };
+
+        let idx = match calls.iter().position(|call| matches(call, true)) {
+            Some(idx) => idx,
+            None => {
+                let idx = calls.iter().position(|call| matches(call, false))?;
+                warn!(
+                    %module,
+                    %method,
+                    %params,
+                    "no recorded response with matching params, using the next response for \
+                    this method instead"
+                );
+                idx
+            }
+        };
+
+        calls[idx].take().map(|call| call.response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn recorded(module: &str, method: &str, params: Value, response: Value) -> RecordedCall {
+        RecordedCall {
+            module: module.to_owned(),
+            method: method.to_owned(),
+            params,
+            response: Ok(response),
+        }
+    }
+
+    fn replay(calls: Vec<RecordedCall>) -> Recording {
+        Recording::Replay(Arc::new(Mutex::new(calls.into_iter().map(Some).collect())))
+    }
+
+    #[test]
+    fn take_exact_match() {
+        let recording = replay(vec![
+            recorded("a", "m", json!(1), json!("a1")),
+            recorded("a", "m", json!(2), json!("a2")),
+            recorded("b", "m", json!(1), json!("b1")),
+        ]);
+
+        assert_eq!(
+            recording.take("a", "m", &json!(2)).unwrap().unwrap(),
+            json!("a2")
+        );
+        assert_eq!(
+            recording.take("b", "m", &json!(1)).unwrap().unwrap(),
+            json!("b1")
+        );
+        assert_eq!(
+            recording.take("a", "m", &json!(1)).unwrap().unwrap(),
+            json!("a1")
+        );
+    }
+
+    #[test]
+    fn take_in_recorded_order() {
+        let recording = replay(vec![
+            recorded("a", "m", json!(1), json!("first")),
+            recorded("a", "m", json!(1), json!("second")),
+        ]);
+
+        assert_eq!(
+            recording.take("a", "m", &json!(1)).unwrap().unwrap(),
+            json!("first")
+        );
+        assert_eq!(
+            recording.take("a", "m", &json!(1)).unwrap().unwrap(),
+            json!("second")
+        );
+        // each response is only returned once
+        assert!(recording.take("a", "m", &json!(1)).is_none());
+    }
+
+    #[test]
+    fn take_falls_back_to_method() {
+        let recording = replay(vec![
+            recorded("a", "other", json!(1), json!("other")),
+            recorded("a", "m", json!({ "timestamp": 1 }), json!("a1")),
+            recorded("a", "m", json!({ "timestamp": 2 }), json!("a2")),
+        ]);
+
+        assert_eq!(
+            recording
+                .take("a", "m", &json!({ "timestamp": 3 }))
+                .unwrap()
+                .unwrap(),
+            json!("a1")
+        );
+        // exact matches are still preferred over earlier responses
+        assert_eq!(
+            recording
+                .take("a", "m", &json!({ "timestamp": 2 }))
+                .unwrap()
+                .unwrap(),
+            json!("a2")
+        );
+        assert!(recording
+            .take("a", "m", &json!({ "timestamp": 3 }))
+            .is_none());
+        assert!(recording.take("b", "other", &json!(1)).is_none());
+    }
+
+    #[test]
+    fn take_recorded_error() {
+        let recording = replay(vec![RecordedCall {
+            module: "a".to_owned(),
+            method: "m".to_owned(),
+            params: json!(1),
+            response: Err(ErrorObjectOwned::owned(-1, "error", None::<()>)),
+        }]);
+
+        assert_eq!(
+            recording.take("a", "m", &json!(1)).unwrap().unwrap_err(),
+            ErrorObjectOwned::owned(-1, "error", None::<()>)
+        );
+    }
+
+    #[test]
+    fn take_while_recording() {
+        let recording = Recording::record();
+
+        recording.push(recorded("a", "m", json!(1), json!("a1")));
+
+        assert!(recording.take("a", "m", &json!(1)).is_none());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!(
+            "voyager-recording-{}-{}.json",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+
+        let recording = Recording::record();
+        recording.push(recorded("a", "m", json!(1), json!("a1")));
+        recording.save(&path).unwrap();
+
+        let replay = Recording::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(replay.is_replay());
+        assert_eq!(
+            replay.take("a", "m", &json!(1)).unwrap().unwrap(),
+            json!("a1")
+        );
+    }
+}
@@ -1,8 +1,14 @@
-use std::rc::Rc;
+use std::{
+    collections::BTreeSet,
+    rc::Rc,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+};
 
 use anyhow::anyhow;
 use jaq_interpret::{Ctx, Filter, FilterT, ParseCtx, RcIter, Val};
-use tracing::{error, instrument, trace};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug_span, error, instrument, trace};
 use unionlabs::ErrorReporter;
 use voyager_vm::{
     filter::{FilterResult, InterestFilter},
@@ -11,20 +17,116 @@ use voyager_vm::{
 
 use crate::{module::PluginInfo, VoyagerMessage};
 
+/// An [`InterestFilter`] built from the interest filters of the loaded plugins.
+///
+/// All clones of this filter share the same filters, such that the filters can be replaced in
+/// place (see [`JaqInterestFilter::replace`]) when the plugins are reloaded, without restarting
+/// the workers or draining the queue.
 #[derive(Debug, Clone)]
 pub struct JaqInterestFilter {
-    pub filters: Vec<(Filter, String)>,
+    inner: Arc<RwLock<Filters>>,
+}
+
+#[derive(Debug)]
+struct Filters {
+    interest: Vec<(Filter, &'static str)>,
+    priority_rules: Vec<(Filter, i32)>,
+}
+
+/// Assigns a priority to all ops matching `filter`, unless the op carries a priority itself. The
+/// first matching rule is used.
+///
+/// `filter` is a jaq filter that is run on the JSON representation of the op, and must return a
+/// single boolean value. `$PLUGIN_NAME` is bound to an empty string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PriorityRule {
+    pub filter: String,
+    pub priority: i32,
 }
 
 impl JaqInterestFilter {
     pub fn new(filters: Vec<PluginInfo>) -> anyhow::Result<Self> {
         Ok(Self {
-            filters: filters
-                .into_iter()
-                .map(make_filter)
-                .collect::<anyhow::Result<_>>()?,
+            inner: Arc::new(RwLock::new(Filters {
+                interest: compile_interest_filters(filters)?,
+                priority_rules: vec![],
+            })),
         })
     }
+
+    pub fn with_priority_rules(self, rules: Vec<PriorityRule>) -> anyhow::Result<Self> {
+        self.inner.write().unwrap().priority_rules = compile_priority_rules(rules)?;
+
+        Ok(self)
+    }
+
+    /// Replace the interest filters and priority rules of this filter and all of it's clones.
+    ///
+    /// All of the filters are compiled before any are replaced, so if any of them are invalid
+    /// the current filters are left untouched.
+    pub fn replace(
+        &self,
+        filters: Vec<PluginInfo>,
+        priority_rules: Vec<PriorityRule>,
+    ) -> anyhow::Result<()> {
+        let interest = compile_interest_filters(filters)?;
+        let priority_rules = compile_priority_rules(priority_rules)?;
+
+        *self.inner.write().unwrap() = Filters {
+            interest,
+            priority_rules,
+        };
+
+        Ok(())
+    }
+
+    /// The names of all plugins that this filter currently checks the interest of.
+    pub fn plugin_names(&self) -> Vec<String> {
+        self.inner
+            .read()
+            .unwrap()
+            .interest
+            .iter()
+            .map(|(_, plugin_name)| (*plugin_name).to_owned())
+            .collect()
+    }
+}
+
+fn compile_interest_filters(
+    filters: Vec<PluginInfo>,
+) -> anyhow::Result<Vec<(Filter, &'static str)>> {
+    filters
+        .into_iter()
+        .map(|plugin_info| {
+            make_filter(plugin_info).map(|(filter, plugin_name)| (filter, intern(plugin_name)))
+        })
+        .collect()
+}
+
+fn compile_priority_rules(rules: Vec<PriorityRule>) -> anyhow::Result<Vec<(Filter, i32)>> {
+    rules
+        .into_iter()
+        .map(|rule| Ok((compile_filter(&rule.filter)?, rule.priority)))
+        .collect()
+}
+
+/// [`InterestFilter::check_interest`] returns the tag borrowed from the filter, which doesn't
+/// work if the filters can be replaced while the tag is in use. Plugin names are instead interned
+/// for the lifetime of the process, which is fine since there are only ever a handful of them.
+fn intern(name: String) -> &'static str {
+    static NAMES: LazyLock<Mutex<BTreeSet<&'static str>>> = LazyLock::new(Default::default);
+
+    let mut names = NAMES.lock().unwrap();
+
+    match names.get(name.as_str()) {
+        Some(name) => name,
+        None => {
+            let name = Box::leak(name.into_boxed_str());
+            names.insert(name);
+            name
+        }
+    }
 }
 
 pub fn make_filter(
@@ -33,12 +135,16 @@ pub fn make_filter(
         interest_filter,
     }: PluginInfo,
 ) -> anyhow::Result<(Filter, String)> {
+    Ok((compile_filter(&interest_filter)?, name))
+}
+
+fn compile_filter(filter: &str) -> anyhow::Result<Filter> {
     let mut ctx = ParseCtx::new(["PLUGIN_NAME".to_owned()].into());
     ctx.insert_natives(jaq_core::core());
     ctx.insert_defs(jaq_std::std());
 
     // parse the filter
-    let lexed = jaq_syn::Lexer::new(&interest_filter).lex().map_err(|es| {
+    let lexed = jaq_syn::Lexer::new(filter).lex().map_err(|es| {
         anyhow!(es
             .iter()
             .map(|(expect, s)| format!("({}: {s})", expect.as_str()))
@@ -62,7 +168,7 @@ pub fn make_filter(
         })?;
 
     // compile the filter in the context of the given definitions
-    let filter = ctx.compile(f.conv(&interest_filter));
+    let filter = ctx.compile(f.conv(filter));
 
     assert!(
         ctx.errs.is_empty(),
@@ -73,15 +179,15 @@ pub fn make_filter(
             .collect::<Vec<_>>()
     );
 
-    Ok((filter, name))
+    Ok(filter)
 }
 
 impl InterestFilter<VoyagerMessage> for JaqInterestFilter {
     fn check_interest<'a>(&'a self, op: &Op<VoyagerMessage>) -> FilterResult<'a> {
         let msg_json = Val::from(serde_json::to_value(op.clone()).unwrap());
 
-        for (filter, plugin_name) in &self.filters {
-            match run_filter(filter, plugin_name, msg_json.clone()) {
+        for (filter, plugin_name) in &self.inner.read().unwrap().interest {
+            match run_filter(filter, *plugin_name, msg_json.clone()) {
                 Ok(interest @ FilterResult::Interest(_)) => return interest,
                 Ok(FilterResult::NoInterest) => {}
                 Err(_) => {}
@@ -90,6 +196,27 @@ impl InterestFilter<VoyagerMessage> for JaqInterestFilter {
 
         FilterResult::NoInterest
     }
+
+    fn check_priority(&self, op: &Op<VoyagerMessage>) -> Option<i32> {
+        let filters = self.inner.read().unwrap();
+
+        if filters.priority_rules.is_empty() {
+            return None;
+        }
+
+        let msg_json = Val::from(serde_json::to_value(op.clone()).unwrap());
+
+        filters
+            .priority_rules
+            .iter()
+            .enumerate()
+            .find_map(|(idx, (filter, priority))| {
+                debug_span!("checking priority rule", %idx, %priority)
+                    .in_scope(|| run_predicate(filter, "", msg_json.clone()))
+                    .is_ok_and(|matched| matched)
+                    .then_some(*priority)
+            })
+    }
 }
 
 #[instrument(
@@ -103,6 +230,19 @@ pub fn run_filter<'a>(
     plugin_name: &'a str,
     msg_json: Val,
 ) -> Result<FilterResult<'a>, ()> {
+    if run_predicate(filter, plugin_name, msg_json)? {
+        trace!("interest");
+
+        Ok(FilterResult::Interest(plugin_name))
+    } else {
+        trace!("no interest");
+
+        Ok(FilterResult::NoInterest)
+    }
+}
+
+/// Run a filter that is expected to return a single boolean value.
+fn run_predicate(filter: &Filter, plugin_name: &str, msg_json: Val) -> Result<bool, ()> {
     let inputs = RcIter::new(core::iter::empty());
     let mut out = filter
         .run((
@@ -142,16 +282,7 @@ pub fn run_filter<'a>(
         Err(())
     } else {
         match result {
-            Val::Bool(true) => {
-                trace!("interest");
-
-                Ok(FilterResult::Interest(plugin_name))
-            }
-            Val::Bool(false) => {
-                trace!("no interest");
-
-                Ok(FilterResult::NoInterest)
-            }
+            Val::Bool(b) => Ok(b),
             _ => {
                 error!("filter returned a non-boolean value: {result:?}");
 
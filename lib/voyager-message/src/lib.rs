@@ -275,6 +275,12 @@ pub trait Plugin: PluginServer<Self::Call, Self::Callback> + Sized {
 
     async fn cmd(config: Self::Config, cmd: Self::Cmd);
 
+    /// Build the RPC module served by this plugin. Plugins that serve additional namespaces, such
+    /// as [`KeyringServer`](crate::module::KeyringServer), can override this to merge them in.
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        PluginServer::into_rpc(self)
+    }
+
     async fn run() {
         init_log();
 
@@ -298,7 +304,7 @@ pub trait Plugin: PluginServer<Self::Call, Self::Callback> + Sized {
                     config,
                     socket,
                     Self::new,
-                    Self::into_rpc,
+                    Self::into_rpc_module,
                 )
                 .instrument(debug_span!("run_plugin_server", %name))
                 .await
@@ -663,6 +669,56 @@ impl VoyagerClient {
         Ok(proof)
     }
 
+    #[instrument(
+        skip_all,
+        name = "voyager_client_encode_header",
+        fields(
+            %client_type,
+            %ibc_interface,
+            %header
+        )
+    )]
+    pub async fn encode_header<V: IbcSpec>(
+        &self,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        header: Value,
+    ) -> RpcResult<Bytes> {
+        let header = self
+            .0
+            .encode_header(client_type, ibc_interface, V::ID, header)
+            .await
+            .map_err(json_rpc_error_to_error_object)?;
+
+        Ok(header)
+    }
+
+    #[instrument(
+        skip_all,
+        name = "voyager_client_encode_misbehaviour",
+        fields(
+            %client_type,
+            %ibc_interface,
+            %header_a,
+            %header_b
+        )
+    )]
+    pub async fn encode_misbehaviour<V: IbcSpec>(
+        &self,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        header_a: Bytes,
+        header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        let misbehaviour = self
+            .0
+            .encode_misbehaviour(client_type, ibc_interface, V::ID, header_a, header_b)
+            .await
+            .map_err(json_rpc_error_to_error_object)?;
+
+        Ok(misbehaviour)
+    }
+
     pub async fn decode_client_state<V: IbcSpec>(
         &self,
         client_type: ClientType,
@@ -678,6 +734,21 @@ impl VoyagerClient {
         Ok(client_state)
     }
 
+    pub async fn decode_consensus_state<V: IbcSpec>(
+        &self,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        consensus_state_bytes: Bytes,
+    ) -> RpcResult<Value> {
+        let consensus_state = self
+            .0
+            .decode_consensus_state(client_type, ibc_interface, V::ID, consensus_state_bytes)
+            .await
+            .map_err(json_rpc_error_to_error_object)?;
+
+        Ok(consensus_state)
+    }
+
     pub async fn query_ibc_state<P: IbcStorePathKey>(
         &self,
         chain_id: ChainId,
@@ -312,6 +312,28 @@ pub trait Plugin<C: Member, Cb: Member> {
     async fn callback(&self, aggregate: Cb, data: VecDeque<Data>) -> RpcResult<Op<VoyagerMessage>>;
 }
 
+/// The balance of a single key in the keyring of a plugin.
+#[model]
+pub struct SignerBalance {
+    pub key_name: String,
+    pub address: String,
+    #[serde(with = "::serde_utils::string")]
+    pub balance: u128,
+    pub denom: String,
+    /// Whether this key is currently in rotation. Keys with a balance below the configured
+    /// minimum are taken out of rotation until they are topped back up.
+    pub enabled: bool,
+}
+
+/// Plugins that submit transactions can implement this to expose the balances of the keys in
+/// their keyring to voyager.
+#[rpc(client, server, namespace = "keyring")]
+pub trait Keyring {
+    /// The most recently observed balances of all keys in this plugin's keyring.
+    #[method(name = "signerBalances")]
+    async fn signer_balances(&self) -> RpcResult<Vec<SignerBalance>>;
+}
+
 #[rpc(
     client,
     server,
@@ -403,6 +425,12 @@ pub trait ClientModule {
     /// Encode the proof, provided as JSON.
     #[method(name = "encodeProof", with_extensions)]
     async fn encode_proof(&self, proof: Value) -> RpcResult<Bytes>;
+
+    /// Encode evidence of misbehaviour from two conflicting headers, both
+    /// encoded as returned by `encodeHeader` (or as they were submitted to
+    /// the client).
+    #[method(name = "encodeMisbehaviour", with_extensions)]
+    async fn encode_misbehaviour(&self, header_a: Bytes, header_b: Bytes) -> RpcResult<Bytes>;
 }
 
 /// Client modules provide functionality for interacting with a specific chain
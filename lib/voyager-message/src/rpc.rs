@@ -16,6 +16,7 @@ use crate::{
     RawClientId, FATAL_JSONRPC_ERROR_CODE,
 };
 
+pub mod cache;
 pub mod server;
 
 #[rpc(
@@ -116,6 +117,25 @@ pub trait VoyagerRpc {
         proof: Value,
     ) -> RpcResult<Bytes>;
 
+    #[method(name = "encodeHeader", with_extensions)]
+    async fn encode_header(
+        &self,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        ibc_spec_id: IbcSpecId,
+        header: Value,
+    ) -> RpcResult<Bytes>;
+
+    #[method(name = "encodeMisbehaviour", with_extensions)]
+    async fn encode_misbehaviour(
+        &self,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        ibc_spec_id: IbcSpecId,
+        header_a: Bytes,
+        header_b: Bytes,
+    ) -> RpcResult<Bytes>;
+
     #[method(name = "decodeClientStateMeta", with_extensions)]
     async fn decode_client_state_meta(
         &self,
@@ -0,0 +1,431 @@
+//! An opt-in cache for the queries that the [`Server`](super::server::Server) forwards to the
+//! modules.
+//!
+//! Only queries whose result can't change are cached indefinitely: state at a height that is known
+//! to be finalized, client info (the type and interface of a client never change), and decoded
+//! client states. Queries that depend on the tip of the chain (the latest height, and state at a
+//! height that is not yet known to be finalized) are cached for [`CacheConfig::latest_ttl`].
+//!
+//! Errors are never cached.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    num::NonZeroUsize,
+    sync::{LazyLock, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use jsonrpsee::core::RpcResult;
+use lru::LruCache;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tracing::trace;
+use unionlabs::{ibc::core::client::height::Height, primitives::Bytes};
+use voyager_core::IbcSpecId;
+
+use crate::core::{ChainId, ClientType, IbcInterface};
+
+pub static CACHE_HITS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "voyager_rpc_cache_hits",
+        "The amount of module queries that were answered from the cache.",
+        &["method"],
+    )
+    .unwrap()
+});
+
+pub static CACHE_MISSES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "voyager_rpc_cache_misses",
+        "The amount of cacheable module queries that were not in the cache.",
+        &["method"],
+    )
+    .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CacheConfig {
+    /// The maximum amount of entries to keep in the cache. Once the cache is full, the least
+    /// recently used entries are evicted.
+    #[serde(default = "default_capacity")]
+    pub capacity: NonZeroUsize,
+    /// How long to cache the results of queries at the tip of the chain for.
+    #[serde(default = "default_latest_ttl_milliseconds")]
+    pub latest_ttl_milliseconds: u64,
+}
+
+#[must_use]
+#[inline]
+pub fn default_capacity() -> NonZeroUsize {
+    NonZeroUsize::new(10_000).expect("10_000 is non-zero; qed;")
+}
+
+#[must_use]
+#[inline]
+pub const fn default_latest_ttl_milliseconds() -> u64 {
+    1000
+}
+
+impl CacheConfig {
+    #[must_use]
+    pub fn latest_ttl(&self) -> Duration {
+        Duration::from_millis(self.latest_ttl_milliseconds)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum CacheKey {
+    IbcState {
+        chain_id: ChainId,
+        ibc_spec_id: IbcSpecId,
+        height: Height,
+        /// The JSON encoded path.
+        path: String,
+    },
+    ClientInfo {
+        chain_id: ChainId,
+        ibc_spec_id: IbcSpecId,
+        /// The JSON encoded client id.
+        client_id: String,
+    },
+    LatestHeight {
+        chain_id: ChainId,
+        finalized: bool,
+    },
+    DecodeClientState {
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        ibc_spec_id: IbcSpecId,
+        client_state: Bytes,
+    },
+}
+
+impl CacheKey {
+    /// The method this key caches the results of, used as the label for the metrics.
+    fn method(&self) -> &'static str {
+        match self {
+            CacheKey::IbcState { .. } => "query_ibc_state",
+            CacheKey::ClientInfo { .. } => "client_info",
+            CacheKey::LatestHeight { .. } => "query_latest_height",
+            CacheKey::DecodeClientState { .. } => "decode_client_state",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Value,
+    /// `None` if this entry never expires.
+    expires_at: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct Cache {
+    config: CacheConfig,
+    entries: Mutex<LruCache<CacheKey, Entry>>,
+    /// The latest finalized height seen for each chain. State at or below this height can't
+    /// change anymore, and as such can be cached indefinitely.
+    finalized_heights: RwLock<HashMap<ChainId, Height>>,
+}
+
+impl Cache {
+    #[must_use]
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(config.capacity)),
+            finalized_heights: RwLock::default(),
+            config,
+        }
+    }
+
+    /// Remove all entries and forget all finalized heights.
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+        self.finalized_heights.write().unwrap().clear();
+    }
+
+    /// Record that `height` is finalized on `chain_id`.
+    pub(crate) fn observe_finalized_height(&self, chain_id: &ChainId, height: Height) {
+        let mut finalized_heights = self.finalized_heights.write().unwrap();
+
+        match finalized_heights.get_mut(chain_id) {
+            Some(finalized_height) => {
+                if height > *finalized_height {
+                    *finalized_height = height;
+                }
+            }
+            None => {
+                finalized_heights.insert(chain_id.clone(), height);
+            }
+        }
+    }
+
+    /// How long state at `height` on `chain_id` can be cached for, or `None` if it can be cached
+    /// indefinitely.
+    pub(crate) fn state_ttl(&self, chain_id: &ChainId, height: Height) -> Option<Duration> {
+        match self.finalized_heights.read().unwrap().get(chain_id) {
+            Some(finalized_height) if height <= *finalized_height => None,
+            _ => Some(self.config.latest_ttl()),
+        }
+    }
+
+    /// The TTL for queries at the tip of the chain.
+    pub(crate) fn latest_ttl(&self) -> Duration {
+        self.config.latest_ttl()
+    }
+
+    /// Return the cached value for `key`, or fetch it with `f` and insert it into the cache with
+    /// the provided `ttl` (or indefinitely, if `ttl` is `None`).
+    pub(crate) async fn get_or_fetch<T, Fut>(
+        &self,
+        key: CacheKey,
+        ttl: Option<Duration>,
+        f: impl FnOnce() -> Fut,
+    ) -> RpcResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        Fut: Future<Output = RpcResult<T>>,
+    {
+        let method = key.method();
+
+        // a value that fails to deserialize would only happen if two methods somehow share a key,
+        // just treat it as a miss and let the entry be overwritten
+        if let Some(value) = self
+            .get(&key)
+            .and_then(|value| serde_json::from_value(value).ok())
+        {
+            trace!(%method, "cache hit");
+            CACHE_HITS.with_label_values(&[method]).inc();
+
+            return Ok(value);
+        }
+
+        trace!(%method, "cache miss");
+        CACHE_MISSES.with_label_values(&[method]).inc();
+
+        let value = f().await?;
+
+        self.entries.lock().unwrap().put(
+            key,
+            Entry {
+                value: serde_json::to_value(&value).expect("serialization is infallible; qed;"),
+                expires_at: ttl.map(|ttl| Instant::now() + ttl),
+            },
+        );
+
+        Ok(value)
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap();
+
+        let entry = entries.get(key)?;
+
+        if entry
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+        {
+            entries.pop(key);
+            None
+        } else {
+            Some(entry.value.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn cache(capacity: usize) -> Cache {
+        Cache::new(CacheConfig {
+            capacity: NonZeroUsize::new(capacity).unwrap(),
+            latest_ttl_milliseconds: 60_000,
+        })
+    }
+
+    fn chain_id() -> ChainId {
+        ChainId::new("chain")
+    }
+
+    fn latest_height_key(finalized: bool) -> CacheKey {
+        CacheKey::LatestHeight {
+            chain_id: chain_id(),
+            finalized,
+        }
+    }
+
+    fn state_key(height: u64) -> CacheKey {
+        CacheKey::IbcState {
+            chain_id: chain_id(),
+            ibc_spec_id: IbcSpecId::new(IbcSpecId::UNION),
+            height: Height::new(height),
+            path: "path".to_owned(),
+        }
+    }
+
+    /// Fetch `key` through the cache, returning the value and whether it was fetched.
+    async fn fetch(cache: &Cache, key: CacheKey, ttl: Option<Duration>, value: u64) -> (u64, bool) {
+        let fetched = AtomicUsize::new(0);
+
+        let value = cache
+            .get_or_fetch(key, ttl, || async {
+                fetched.fetch_add(1, Ordering::SeqCst);
+                Ok(value)
+            })
+            .await
+            .unwrap();
+
+        (value, fetched.load(Ordering::SeqCst) == 1)
+    }
+
+    #[tokio::test]
+    async fn ttl_expiry() {
+        let cache = cache(10);
+
+        assert_eq!(
+            fetch(&cache, latest_height_key(true), Some(Duration::ZERO), 1).await,
+            (1, true)
+        );
+        // expired immediately
+        assert_eq!(
+            fetch(&cache, latest_height_key(true), Some(Duration::ZERO), 2).await,
+            (2, true)
+        );
+
+        assert_eq!(
+            fetch(
+                &cache,
+                latest_height_key(false),
+                Some(cache.latest_ttl()),
+                1
+            )
+            .await,
+            (1, true)
+        );
+        assert_eq!(
+            fetch(
+                &cache,
+                latest_height_key(false),
+                Some(cache.latest_ttl()),
+                2
+            )
+            .await,
+            (1, false)
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_are_not_cached() {
+        let cache = cache(10);
+
+        let res = cache
+            .get_or_fetch::<u64, _>(latest_height_key(true), None, || async {
+                Err(jsonrpsee::types::ErrorObject::owned(
+                    -1, "error", None::<()>,
+                ))
+            })
+            .await;
+        assert!(res.is_err());
+
+        assert_eq!(
+            fetch(&cache, latest_height_key(true), None, 1).await,
+            (1, true)
+        );
+    }
+
+    #[test]
+    fn finalized_heights_are_immutable() {
+        let cache = cache(10);
+
+        // nothing is known to be finalized yet
+        assert_eq!(
+            cache.state_ttl(&chain_id(), Height::new(1)),
+            Some(cache.latest_ttl())
+        );
+
+        cache.observe_finalized_height(&chain_id(), Height::new(10));
+
+        assert_eq!(cache.state_ttl(&chain_id(), Height::new(1)), None);
+        assert_eq!(cache.state_ttl(&chain_id(), Height::new(10)), None);
+        assert_eq!(
+            cache.state_ttl(&chain_id(), Height::new(11)),
+            Some(cache.latest_ttl())
+        );
+
+        // a lower finalized height (i.e. from a lagging rpc) doesn't make finalized state mutable
+        // again
+        cache.observe_finalized_height(&chain_id(), Height::new(5));
+        assert_eq!(cache.state_ttl(&chain_id(), Height::new(10)), None);
+
+        // other chains are unaffected
+        assert_eq!(
+            cache.state_ttl(&ChainId::new("other"), Height::new(1)),
+            Some(cache.latest_ttl())
+        );
+    }
+
+    #[tokio::test]
+    async fn lru_eviction() {
+        let cache = cache(2);
+
+        fetch(&cache, state_key(1), None, 1).await;
+        fetch(&cache, state_key(2), None, 2).await;
+
+        // make 1 the most recently used entry, such that 2 is evicted
+        assert_eq!(fetch(&cache, state_key(1), None, 0).await, (1, false));
+
+        fetch(&cache, state_key(3), None, 3).await;
+
+        assert_eq!(fetch(&cache, state_key(1), None, 0).await, (1, false));
+        assert_eq!(fetch(&cache, state_key(3), None, 0).await, (3, false));
+        assert_eq!(fetch(&cache, state_key(2), None, 4).await, (4, true));
+    }
+
+    #[tokio::test]
+    async fn hit_and_miss_counters() {
+        let cache = cache(10);
+
+        // this is the only test using this method, such that the counters aren't affected by
+        // tests running concurrently
+        let key = CacheKey::ClientInfo {
+            chain_id: chain_id(),
+            ibc_spec_id: IbcSpecId::new(IbcSpecId::UNION),
+            client_id: "1".to_owned(),
+        };
+
+        let hits = || CACHE_HITS.with_label_values(&["client_info"]).get();
+        let misses = || CACHE_MISSES.with_label_values(&["client_info"]).get();
+
+        let (hits_before, misses_before) = (hits(), misses());
+
+        fetch(&cache, key.clone(), None, 1).await;
+        fetch(&cache, key.clone(), None, 1).await;
+        fetch(&cache, key, None, 1).await;
+
+        assert_eq!(hits() - hits_before, 2);
+        assert_eq!(misses() - misses_before, 1);
+    }
+
+    #[tokio::test]
+    async fn clear() {
+        let cache = cache(10);
+
+        cache.observe_finalized_height(&chain_id(), Height::new(10));
+        fetch(&cache, state_key(1), None, 1).await;
+
+        cache.clear();
+
+        assert_eq!(fetch(&cache, state_key(1), None, 2).await, (2, true));
+        assert_eq!(
+            cache.state_ttl(&chain_id(), Height::new(1)),
+            Some(cache.latest_ttl())
+        );
+    }
+}
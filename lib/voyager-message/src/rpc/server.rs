@@ -1,6 +1,8 @@
 use std::{
     fmt::Debug,
-    sync::{Arc, OnceLock},
+    future::Future,
+    sync::{Arc, OnceLock, RwLock},
+    time::Duration,
 };
 
 use jsonrpsee::{
@@ -8,6 +10,7 @@ use jsonrpsee::{
     types::{ErrorObject, ErrorObjectOwned},
     Extensions,
 };
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 use tracing::{debug, info_span, instrument, trace};
 use unionlabs::{ibc::core::client::height::Height, primitives::Bytes, ErrorReporter};
@@ -25,6 +28,7 @@ use crate::{
         RawProofModuleClient, RawStateModuleClient,
     },
     rpc::{
+        cache::{Cache, CacheConfig, CacheKey},
         json_rpc_error_to_error_object, IbcProof, IbcState, SelfClientState, SelfConsensusState,
         VoyagerRpcServer,
     },
@@ -37,9 +41,13 @@ pub struct Server {
     item_id: Option<ItemId>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct ServerInner {
-    modules: OnceLock<Arc<Modules>>,
+    /// The currently loaded modules. This is `None` until the server has been started, and is
+    /// replaced whenever the configuration is reloaded.
+    modules: RwLock<Option<Arc<Modules>>>,
+    /// The cache for module queries, if enabled. See [`Server::enable_cache`].
+    cache: OnceLock<Cache>,
 }
 
 impl Server {
@@ -47,18 +55,114 @@ impl Server {
     pub fn new() -> Self {
         Server {
             inner: Arc::new(ServerInner {
-                modules: OnceLock::new(),
+                modules: RwLock::new(None),
+                cache: OnceLock::new(),
             }),
             item_id: None,
         }
     }
 
     pub fn start(&self, modules: Arc<Modules>) {
-        let was_not_already_started = self.inner.modules.set(modules).is_ok();
+        let was_not_already_started = self
+            .inner
+            .modules
+            .write()
+            .unwrap()
+            .replace(modules)
+            .is_none();
 
         assert!(was_not_already_started, "server has already been started");
     }
 
+    /// Replace the loaded modules of a started server. Requests that are already in flight will
+    /// finish with the previous modules.
+    ///
+    /// The cache is cleared, since the new modules may be configured against different chains or
+    /// endpoints than the ones the cached results were fetched from.
+    pub fn reload(&self, modules: Arc<Modules>) {
+        let was_started = self
+            .inner
+            .modules
+            .write()
+            .unwrap()
+            .replace(modules)
+            .is_some();
+
+        assert!(was_started, "server has not been started");
+
+        if let Some(cache) = self.cache() {
+            cache.clear();
+        }
+    }
+
+    /// Cache the results of module queries that are immutable or at the tip of the chain. See
+    /// [`cache`](crate::rpc::cache) for more information.
+    ///
+    /// This affects all clones of this server.
+    pub fn enable_cache(&self, config: CacheConfig) {
+        let was_not_already_enabled = self.inner.cache.set(Cache::new(config)).is_ok();
+
+        assert!(was_not_already_enabled, "cache has already been enabled");
+    }
+
+    fn cache(&self) -> Option<&Cache> {
+        self.inner.cache.get()
+    }
+
+    /// Fetch a value with `f`, or return it from the cache if the cache is enabled and contains
+    /// `key`. `key` is `None` if the cache is not enabled, and is paired with the TTL to use for
+    /// the value (or `None` to never expire it).
+    async fn cached<T, Fut>(
+        &self,
+        key: Option<(CacheKey, Option<Duration>)>,
+        f: impl FnOnce() -> Fut,
+    ) -> RpcResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        Fut: Future<Output = RpcResult<T>>,
+    {
+        match (self.cache(), key) {
+            (Some(cache), Some((key, ttl))) => cache.get_or_fetch(key, ttl, f).await,
+            _ => f().await,
+        }
+    }
+
+    /// Query the raw ibc state at `path`, going through the cache if it is enabled.
+    async fn query_ibc_state_cached(
+        &self,
+        chain_id: &ChainId,
+        ibc_spec_id: &IbcSpecId,
+        height: Height,
+        path: Value,
+    ) -> RpcResult<Value> {
+        let cache_key = self.cache().map(|cache| {
+            (
+                CacheKey::IbcState {
+                    chain_id: chain_id.clone(),
+                    ibc_spec_id: ibc_spec_id.clone(),
+                    height,
+                    path: path.to_string(),
+                },
+                cache.state_ttl(chain_id, height),
+            )
+        });
+
+        self.cached(cache_key, || async {
+            let state = self
+                .inner
+                .modules()?
+                .state_module(chain_id, ibc_spec_id)
+                .map_err(fatal_error)?
+                .with_id(self.item_id)
+                .query_ibc_state_raw(height, path)
+                .await
+                .map_err(json_rpc_error_to_error_object)?;
+
+            Ok(state)
+        })
+        .await
+    }
+
     pub fn with_id(&self, item_id: Option<ItemId>) -> Server {
         Server {
             inner: self.inner.clone(),
@@ -74,7 +178,7 @@ impl Server {
     }
 
     /// Returns the contained modules, if they have been loaded.
-    pub fn modules(&self) -> RpcResult<&Modules> {
+    pub fn modules(&self) -> RpcResult<Arc<Modules>> {
         self.inner.modules()
     }
 
@@ -82,28 +186,14 @@ impl Server {
     pub async fn query_height(&self, chain_id: &ChainId, height: QueryHeight) -> RpcResult<Height> {
         match height {
             QueryHeight::Latest => {
-                let latest_height = self
-                    .modules()?
-                    .consensus_module(chain_id)
-                    .map_err(fatal_error)?
-                    .with_id(self.item_id)
-                    .query_latest_height(false)
-                    .await
-                    .map_err(json_rpc_error_to_error_object)?;
+                let latest_height = self.query_latest_height(chain_id, false).await?;
 
                 debug!(%latest_height, finalized = false, "queried latest height");
 
                 Ok(latest_height)
             }
             QueryHeight::Finalized => {
-                let latest_height = self
-                    .modules()?
-                    .consensus_module(chain_id)
-                    .map_err(fatal_error)?
-                    .with_id(self.item_id)
-                    .query_latest_height(true)
-                    .await
-                    .map_err(json_rpc_error_to_error_object)?;
+                let latest_height = self.query_latest_height(chain_id, true).await?;
 
                 debug!(%latest_height, finalized = true, "queried latest height");
 
@@ -116,10 +206,11 @@ impl Server {
 
 impl ServerInner {
     /// Returns the contained modules, if they have been loaded.
-    fn modules(&self) -> RpcResult<&Modules> {
+    fn modules(&self) -> RpcResult<Arc<Modules>> {
         self.modules
-            .get()
-            .map(|x| &**x)
+            .read()
+            .unwrap()
+            .clone()
             .ok_or_else(|| ErrorObject::owned(-2, "server has not started", None::<()>))
     }
 }
@@ -135,15 +226,37 @@ impl Server {
             .in_scope(|| async {
                 trace!("querying latest height");
 
+                let cache_key = self.cache().map(|cache| {
+                    (
+                        CacheKey::LatestHeight {
+                            chain_id: chain_id.clone(),
+                            finalized,
+                        },
+                        Some(cache.latest_ttl()),
+                    )
+                });
+
                 let latest_height = self
-                    .inner
-                    .modules()?
-                    .consensus_module(chain_id)
-                    .map_err(fatal_error)?
-                    .with_id(self.item_id)
-                    .query_latest_height(finalized)
-                    .await
-                    .map_err(json_rpc_error_to_error_object)?;
+                    .cached(cache_key, || async {
+                        let latest_height = self
+                            .inner
+                            .modules()?
+                            .consensus_module(chain_id)
+                            .map_err(fatal_error)?
+                            .with_id(self.item_id)
+                            .query_latest_height(finalized)
+                            .await
+                            .map_err(json_rpc_error_to_error_object)?;
+
+                        Ok(latest_height)
+                    })
+                    .await?;
+
+                if finalized {
+                    if let Some(cache) = self.cache() {
+                        cache.observe_finalized_height(chain_id, latest_height);
+                    }
+                }
 
                 trace!(
                     %latest_height,
@@ -196,15 +309,33 @@ impl Server {
             .in_scope(|| async {
                 trace!("fetching client info");
 
+                // the type and interface of a client never change, so this can always be cached
+                let cache_key = self.cache().map(|_| {
+                    (
+                        CacheKey::ClientInfo {
+                            chain_id: chain_id.clone(),
+                            ibc_spec_id: ibc_spec_id.clone(),
+                            client_id: client_id.0.to_string(),
+                        },
+                        None,
+                    )
+                });
+
                 let client_info = self
-                    .inner
-                    .modules()?
-                    .state_module(chain_id, ibc_spec_id)
-                    .map_err(fatal_error)?
-                    .with_id(self.item_id)
-                    .client_info_raw(client_id.clone())
-                    .await
-                    .map_err(json_rpc_error_to_error_object)?;
+                    .cached(cache_key, || async {
+                        let client_info = self
+                            .inner
+                            .modules()?
+                            .state_module(chain_id, ibc_spec_id)
+                            .map_err(fatal_error)?
+                            .with_id(self.item_id)
+                            .client_info_raw(client_id.clone())
+                            .await
+                            .map_err(json_rpc_error_to_error_object)?;
+
+                        Ok(client_info)
+                    })
+                    .await?;
 
                 trace!(
                     %client_info.ibc_interface,
@@ -233,20 +364,16 @@ impl Server {
 
                 let modules = self.inner.modules()?;
 
-                let state_module = modules
-                    .state_module(chain_id, ibc_spec_id)?
-                    .with_id(self.item_id);
-
-                let client_info = state_module
-                    .client_info_raw(client_id.clone())
-                    .await
-                    .map_err(json_rpc_error_to_error_object)?;
+                let client_info = self
+                    .client_info(chain_id, ibc_spec_id, client_id.clone())
+                    .await?;
 
-                let client_state = state_module
-                    .query_ibc_state_raw(
+                let client_state = self
+                    .query_ibc_state_cached(
+                        chain_id,
+                        ibc_spec_id,
                         height,
-                        (self
-                            .modules()?
+                        (modules
                             .ibc_spec_handlers
                             .handlers
                             .get(ibc_spec_id)
@@ -254,8 +381,7 @@ impl Server {
                             .client_state_path)(client_id.clone())
                         .unwrap(),
                     )
-                    .await
-                    .map_err(json_rpc_error_to_error_object)?;
+                    .await?;
 
                 trace!(%client_state);
 
@@ -298,17 +424,9 @@ impl Server {
 
                 debug!("fetching ibc state");
 
-                let state_module = self
-                    .inner
-                    .modules()?
-                    .state_module(&chain_id, &ibc_spec_id)
-                    .map_err(fatal_error)?
-                    .with_id(self.item_id);
-
-                let state = state_module
-                    .query_ibc_state_raw(height, path)
-                    .await
-                    .map_err(json_rpc_error_to_error_object)?;
+                let state = self
+                    .query_ibc_state_cached(&chain_id, &ibc_spec_id, height, path)
+                    .await?;
 
                 // TODO: Use valuable here
                 debug!(%state, "fetched ibc state");
@@ -332,9 +450,9 @@ impl Server {
 
                 debug!("fetching ibc proof");
 
-                let proof_module = self
-                    .inner
-                    .modules()?
+                let modules = self.inner.modules()?;
+
+                let proof_module = modules
                     .proof_module(&chain_id, &ibc_spec_id)
                     .map_err(fatal_error)?
                     .with_id(self.item_id);
@@ -363,17 +481,14 @@ impl Server {
             .in_scope(|| async {
                 trace!("fetching ibc state");
 
-                let state_module = self
-                    .inner
-                    .modules()?
-                    .state_module(chain_id, &P::Spec::ID)
-                    .map_err(fatal_error)?
-                    .with_id(self.item_id);
-
-                let state = state_module
-                    .query_ibc_state_raw(height, into_value(path.clone()))
-                    .await
-                    .map_err(json_rpc_error_to_error_object)?;
+                let state = self
+                    .query_ibc_state_cached(
+                        chain_id,
+                        &P::Spec::ID,
+                        height,
+                        into_value(path.clone()),
+                    )
+                    .await?;
 
                 // TODO: Use valuable here
                 trace!(%state, "fetched ibc state");
@@ -405,9 +520,9 @@ impl Server {
             .in_scope(|| async {
                 trace!("fetching ibc state");
 
-                let proof_module = self
-                    .inner
-                    .modules()?
+                let modules = self.inner.modules()?;
+
+                let proof_module = modules
                     .proof_module(chain_id, &P::Spec::ID)
                     .map_err(fatal_error)?
                     .with_id(self.item_id);
@@ -436,9 +551,9 @@ impl Server {
             .in_scope(|| async {
                 trace!("querying self client state");
 
-                let client_bootstrap_module = self
-                    .inner
-                    .modules()?
+                let modules = self.inner.modules()?;
+
+                let client_bootstrap_module = modules
                     .client_bootstrap_module(&chain_id, &client_type)
                     .map_err(fatal_error)?
                     .with_id(self.item_id);
@@ -467,9 +582,9 @@ impl Server {
             .in_scope(|| async {
                 trace!("querying self consensus state");
 
-                let client_bootstrap_module = self
-                    .inner
-                    .modules()?
+                let modules = self.inner.modules()?;
+
+                let client_bootstrap_module = modules
                     .client_bootstrap_module(&chain_id, &client_type)
                     .map_err(fatal_error)?
                     .with_id(self.item_id);
@@ -489,6 +604,70 @@ impl Server {
             .await
     }
 
+    // TODO: Use valuable here
+    #[instrument(skip_all, fields(%client_type, %ibc_interface, %ibc_spec_id, %header))]
+    pub async fn encode_header(
+        &self,
+        client_type: &ClientType,
+        ibc_interface: &IbcInterface,
+        ibc_spec_id: &IbcSpecId,
+        header: Value,
+    ) -> RpcResult<Bytes> {
+        self.span()
+            .in_scope(|| async {
+                trace!("encoding header");
+
+                let modules = self.inner.modules()?;
+
+                let client_module = modules
+                    .client_module(client_type, ibc_interface, ibc_spec_id)
+                    .map_err(fatal_error)?
+                    .with_id(self.item_id);
+
+                let header = client_module
+                    .encode_header(header)
+                    .await
+                    .map_err(json_rpc_error_to_error_object)?;
+
+                trace!(%header, "encoded header");
+
+                Ok(header)
+            })
+            .await
+    }
+
+    #[instrument(skip_all, fields(%client_type, %ibc_interface, %ibc_spec_id))]
+    pub async fn encode_misbehaviour(
+        &self,
+        client_type: &ClientType,
+        ibc_interface: &IbcInterface,
+        ibc_spec_id: &IbcSpecId,
+        header_a: Bytes,
+        header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        self.span()
+            .in_scope(|| async {
+                trace!(%header_a, %header_b, "encoding misbehaviour");
+
+                let modules = self.inner.modules()?;
+
+                let client_module = modules
+                    .client_module(client_type, ibc_interface, ibc_spec_id)
+                    .map_err(fatal_error)?
+                    .with_id(self.item_id);
+
+                let misbehaviour = client_module
+                    .encode_misbehaviour(header_a, header_b)
+                    .await
+                    .map_err(json_rpc_error_to_error_object)?;
+
+                trace!(%misbehaviour, "encoded misbehaviour");
+
+                Ok(misbehaviour)
+            })
+            .await
+    }
+
     // TODO: Use valuable here
     #[instrument(skip_all, fields(%client_type, %ibc_interface, %ibc_spec_id, %proof))]
     pub async fn encode_proof(
@@ -502,9 +681,9 @@ impl Server {
             .in_scope(|| async {
                 trace!("encoding proof");
 
-                let client_module = self
-                    .inner
-                    .modules()?
+                let modules = self.inner.modules()?;
+
+                let client_module = modules
                     .client_module(client_type, ibc_interface, ibc_spec_id)
                     .map_err(fatal_error)?
                     .with_id(self.item_id);
@@ -534,9 +713,9 @@ impl Server {
             .in_scope(|| async {
                 trace!("decoding client state meta");
 
-                let client_module = self
-                    .inner
-                    .modules()?
+                let modules = self.inner.modules()?;
+
+                let client_module = modules
                     .client_module(client_type, ibc_interface, ibc_spec_id)
                     .map_err(fatal_error)?
                     .with_id(self.item_id);
@@ -567,14 +746,32 @@ impl Server {
     ) -> RpcResult<Value> {
         self.span()
             .in_scope(|| async {
-                self.inner
-                    .modules()?
-                    .client_module(client_type, ibc_interface, ibc_spec_id)
-                    .map_err(fatal_error)?
-                    .with_id(self.item_id)
-                    .decode_client_state(client_state)
-                    .await
-                    .map_err(json_rpc_error_to_error_object)
+                let cache_key = self.cache().map(|_| {
+                    (
+                        CacheKey::DecodeClientState {
+                            client_type: client_type.clone(),
+                            ibc_interface: ibc_interface.clone(),
+                            ibc_spec_id: ibc_spec_id.clone(),
+                            client_state: client_state.clone(),
+                        },
+                        None,
+                    )
+                });
+
+                self.cached(cache_key, || async {
+                    let client_state = self
+                        .inner
+                        .modules()?
+                        .client_module(client_type, ibc_interface, ibc_spec_id)
+                        .map_err(fatal_error)?
+                        .with_id(self.item_id)
+                        .decode_client_state(client_state)
+                        .await
+                        .map_err(json_rpc_error_to_error_object)?;
+
+                    Ok(client_state)
+                })
+                .await
             })
             .await
     }
@@ -745,6 +942,39 @@ impl VoyagerRpcServer for Server {
             .await
     }
 
+    async fn encode_header(
+        &self,
+        e: &Extensions,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        ibc_spec_id: IbcSpecId,
+        header: Value,
+    ) -> RpcResult<Bytes> {
+        self.with_id(e.try_get().ok().cloned())
+            .encode_header(&client_type, &ibc_interface, &ibc_spec_id, header)
+            .await
+    }
+
+    async fn encode_misbehaviour(
+        &self,
+        e: &Extensions,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        ibc_spec_id: IbcSpecId,
+        header_a: Bytes,
+        header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        self.with_id(e.try_get().ok().cloned())
+            .encode_misbehaviour(
+                &client_type,
+                &ibc_interface,
+                &ibc_spec_id,
+                header_a,
+                header_b,
+            )
+            .await
+    }
+
     // TODO: Use valuable here
     async fn decode_client_state_meta(
         &self,
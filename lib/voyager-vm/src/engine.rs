@@ -1,16 +1,77 @@
 use std::{future::Future, time::Duration};
 
 use futures::{stream, FutureExt, Stream, StreamExt};
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use tokio::time::sleep;
-use tracing::error;
+use tracing::{error, warn};
 use unionlabs::ErrorReporter;
 
-use crate::{defer, now, seq, BoxDynError, Captures, Context, Queue, QueueError, QueueMessage};
+use crate::{now, retry, BoxDynError, Captures, Context, Op, Queue, QueueError, QueueMessage};
 
 pub struct Engine<'a, T: QueueMessage, Q: Queue<T>> {
     store: &'a T::Context,
     queue: &'a Q,
     optimizer: &'a T::Filter,
+    retry: RetryConfig,
+}
+
+/// Controls how messages that fail with a [retryable error](QueueError::Retry) are requeued.
+///
+/// The delay before attempt `n` is `min(base_delay_seconds * multiplier^(n - 1), max_delay_seconds)`,
+/// with up to `jitter` of that delay randomly added or subtracted. If `max_attempts` is set, once a
+/// message has failed that many times in a row it is treated as a fatal error and moved out of the
+/// queue; otherwise, it is retried indefinitely.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_attempts: Option<u32>,
+    pub base_delay_seconds: u64,
+    pub max_delay_seconds: u64,
+    pub multiplier: u32,
+    /// Fraction of the computed delay (in the range `0.0..=1.0`) to randomly add or subtract.
+    pub jitter: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: None,
+            base_delay_seconds: 3,
+            max_delay_seconds: 300,
+            multiplier: 2,
+            jitter: 0.1,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// The delay (in seconds) before the message is retried for the `attempt`th time. `attempt`
+    /// starts at 1.
+    #[must_use]
+    pub fn delay(&self, attempt: u32) -> u64 {
+        let delay = u64::from(self.multiplier)
+            .saturating_pow(attempt.saturating_sub(1))
+            .saturating_mul(self.base_delay_seconds)
+            .min(self.max_delay_seconds);
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+
+        if jitter == 0.0 || delay == 0 {
+            return delay;
+        }
+
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        {
+            let spread = delay as f64 * jitter;
+            (delay as f64 + rand::thread_rng().gen_range(-spread..=spread)).round() as u64
+        }
+    }
 }
 
 impl<'a, T: QueueMessage, Q: Queue<T>> Engine<'a, T, Q> {
@@ -19,9 +80,16 @@ impl<'a, T: QueueMessage, Q: Queue<T>> Engine<'a, T, Q> {
             store,
             queue,
             optimizer: filter,
+            retry: RetryConfig::default(),
         }
     }
 
+    #[must_use]
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
     pub fn run(self) -> impl Stream<Item = Result<T::Data, BoxDynError>> + Send + Captures<'a> {
         futures::stream::try_unfold(self, |this| async move {
             sleep(Duration::from_millis(10)).await;
@@ -50,11 +118,40 @@ impl<'a, T: QueueMessage, Q: Queue<T>> Engine<'a, T, Q> {
                                 error!(error = %full_err, "fatal error");
                                 (None, Err(full_err.to_string()))
                             }
-                            Err(QueueError::Retry(retry)) => {
-                                // TODO: Add some backoff logic here based on `full_err`?
-                                let full_err = ErrorReporter(&*retry);
-                                error!(error = %full_err, "retryable error");
-                                (None, Ok(vec![seq([defer(now() + 3), op])]))
+                            Err(QueueError::Retry(err)) => {
+                                let full_err = ErrorReporter(&*err);
+
+                                let (attempt, op) = match op {
+                                    Op::Retry { attempt, op, .. } => (attempt.saturating_add(1), *op),
+                                    op => (1, op),
+                                };
+
+                                if self
+                                    .retry
+                                    .max_attempts
+                                    .is_some_and(|max_attempts| attempt >= max_attempts)
+                                {
+                                    error!(
+                                        error = %full_err,
+                                        %attempt,
+                                        "retryable error, retry budget exhausted"
+                                    );
+                                    return (
+                                        None,
+                                        Err(format!(
+                                            "retry budget exhausted after {attempt} attempts: {full_err}"
+                                        )),
+                                    );
+                                }
+
+                                let delay = self.retry.delay(attempt);
+                                warn!(
+                                    error = %full_err,
+                                    %attempt,
+                                    %delay,
+                                    "retryable error"
+                                );
+                                (None, Ok(vec![retry(attempt, now() + delay, op)]))
                             }
                         })
                 })
@@ -3,6 +3,15 @@ use crate::{Op, QueueMessage};
 /// A filter to run on [`Op`]s before they're pushed into the queue.
 pub trait InterestFilter<T: QueueMessage>: Send + Sync + Sized + 'static {
     fn check_interest<'a>(&'a self, op: &Op<T>) -> FilterResult<'a>;
+
+    /// The priority to enqueue `op` with. This is only used if `op` does not carry a priority
+    /// itself (see [`Op::Priority`]), and if `None` is returned, the priority is inherited from
+    /// the parents of the op.
+    fn check_priority(&self, op: &Op<T>) -> Option<i32> {
+        let _ = op;
+
+        None
+    }
 }
 
 /// The result of running an [`InterestFilter`] on an [`Op`].
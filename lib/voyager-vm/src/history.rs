@@ -0,0 +1,217 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+use frame_support_procedural::{CloneNoBound, DebugNoBound};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Op, QueueMessage};
+
+/// Where an item currently lives in the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    /// The item is ready to be processed.
+    Ready,
+    /// The item is waiting to be optimized.
+    Optimize,
+    /// The item has been processed or optimized.
+    Done,
+    /// Processing the item failed.
+    Failed,
+}
+
+impl ItemStatus {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ready => "ready",
+            Self::Optimize => "optimize",
+            Self::Done => "done",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for ItemStatus {
+    type Err = UnknownItemStatus;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ready" => Ok(Self::Ready),
+            "optimize" => Ok(Self::Optimize),
+            "done" => Ok(Self::Done),
+            "failed" => Ok(Self::Failed),
+            _ => Err(UnknownItemStatus(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown item status `{0}`")]
+pub struct UnknownItemStatus(pub String);
+
+/// An item in the history of an op, along with the ids of the items it was produced from.
+#[derive(DebugNoBound, CloneNoBound, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = ""), deny_unknown_fields)]
+pub struct HistoryItem<T: QueueMessage> {
+    pub id: i64,
+    pub parents: Vec<i64>,
+    pub status: ItemStatus,
+    pub item: Op<T>,
+    /// The error message, if this item failed.
+    pub message: Option<String>,
+}
+
+/// Walk the parent links of the item `id`, up to `max_depth` levels deep, with `fetch` returning
+/// all of the items with the requested ids. This returns the item along with all of its
+/// ancestors, and every item is only returned once, even if it is the ancestor of multiple items.
+pub async fn walk_history<T: QueueMessage, E, Fut>(
+    id: i64,
+    max_depth: u32,
+    mut fetch: impl FnMut(Vec<i64>) -> Fut,
+) -> Result<Vec<HistoryItem<T>>, E>
+where
+    Fut: std::future::Future<Output = Result<Vec<HistoryItem<T>>, E>>,
+{
+    let mut history = vec![];
+    let mut seen = HashSet::new();
+    let mut ids = vec![id];
+
+    for _ in 0..=max_depth {
+        if ids.is_empty() {
+            break;
+        }
+
+        seen.extend(ids.iter().copied());
+
+        let items = fetch(ids).await?;
+
+        ids = items
+            .iter()
+            .flat_map(|item| item.parents.iter().copied())
+            .filter(|id| !seen.contains(id))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        ids.sort_unstable();
+
+        history.extend(items);
+    }
+
+    Ok(history)
+}
+
+/// Render the history of the item `root` as a tree, with the parents of each item as it's
+/// children.
+///
+/// ```txt
+/// 12 [failed] call/plugin/voyager-plugin-transaction-batch: error message
+/// └── 9 [done] seq[2]
+///     ├── 4 [done] data/ibc_event
+///     └── 7 [done] call/fetch_blocks
+/// ```
+#[must_use]
+pub fn render_history<T: QueueMessage>(root: i64, history: &[HistoryItem<T>]) -> String {
+    fn go<T: QueueMessage>(
+        out: &mut String,
+        items: &BTreeMap<i64, &HistoryItem<T>>,
+        rendered: &mut HashSet<i64>,
+        id: i64,
+        prefix: &str,
+        connector: &str,
+        child_prefix: &str,
+    ) {
+        let Some(item) = items.get(&id) else {
+            let _ = writeln!(out, "{prefix}{connector}{id} [missing]");
+            return;
+        };
+
+        let _ = write!(
+            out,
+            "{prefix}{connector}{id} [{}] {}",
+            item.status.as_str(),
+            summarize(&item.item)
+        );
+
+        if let Some(message) = &item.message {
+            let _ = write!(out, ": {message}");
+        }
+
+        if !rendered.insert(id) {
+            let _ = writeln!(out, " (see above)");
+            return;
+        }
+
+        let _ = writeln!(out);
+
+        let prefix = format!("{prefix}{child_prefix}");
+
+        for (idx, parent) in item.parents.iter().enumerate() {
+            let last = idx == item.parents.len() - 1;
+
+            go(
+                out,
+                items,
+                rendered,
+                *parent,
+                &prefix,
+                if last { "└── " } else { "├── " },
+                if last { "    " } else { "│   " },
+            );
+        }
+    }
+
+    let items = history
+        .iter()
+        .map(|item| (item.id, item))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut out = String::new();
+
+    go(&mut out, &items, &mut HashSet::new(), root, "", "", "");
+
+    out
+}
+
+/// A short, single line description of an op, built from the `@type` tags of it's outermost
+/// values (and the plugin name, for plugin messages).
+fn summarize<T: QueueMessage>(op: &Op<T>) -> String {
+    let mut value = serde_json::to_value(op).expect("serialization is infallible; qed;");
+
+    let mut parts = vec![];
+
+    loop {
+        match value {
+            Value::Object(mut object) => {
+                if let Some(Value::String(ty)) = object.remove("@type") {
+                    match object.remove("@value") {
+                        Some(Value::Array(values)) => {
+                            parts.push(format!("{ty}[{}]", values.len()));
+                            break;
+                        }
+                        Some(inner) => {
+                            parts.push(ty);
+                            value = inner;
+                        }
+                        None => {
+                            parts.push(ty);
+                            break;
+                        }
+                    }
+                } else if let Some(Value::String(plugin)) = object.remove("plugin") {
+                    parts.push(plugin);
+                    value = object.remove("message").unwrap_or_default();
+                } else {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    parts.join("/")
+}
@@ -1,5 +1,7 @@
 use std::{
-    collections::BTreeMap,
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
     future::Future,
     sync::{
         atomic::{AtomicU32, Ordering},
@@ -9,40 +11,338 @@ use std::{
 
 use either::Either;
 use frame_support_procedural::{CloneNoBound, DebugNoBound};
-use tracing::{debug, info_span, warn, Instrument};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info_span, warn, Instrument};
 
 use crate::{
     filter::{FilterResult, InterestFilter},
+    history::{walk_history, HistoryItem, ItemStatus},
+    inspect::{ItemQuery, ItemRecord},
+    now,
     pass::Pass,
+    priority::{resolve_priority, Lanes, PriorityConfig},
     Captures, ItemId, Op, Queue, QueueMessage,
 };
 
 #[derive(DebugNoBound, CloneNoBound)]
 pub struct InMemoryQueue<T: QueueMessage> {
     idx: Arc<AtomicU32>,
-    ready: Arc<Mutex<BTreeMap<u32, Item<T>>>>,
+    ready: Arc<Mutex<ReadyQueue<T>>>,
     done: Arc<Mutex<BTreeMap<u32, Item<T>>>>,
+    failed: Arc<Mutex<BTreeMap<u32, FailedItem<T>>>>,
     #[allow(clippy::type_complexity)]
     optimizer_queue: Arc<Mutex<BTreeMap<String, BTreeMap<u32, Item<T>>>>>,
+    lanes: Lanes,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InMemoryQueueConfig {
+    #[serde(default)]
+    pub priority: PriorityConfig,
 }
 
 #[derive(DebugNoBound, CloneNoBound)]
 pub(crate) struct Item<T: QueueMessage> {
-    #[allow(dead_code)] // used in debug
     parents: Vec<u32>,
+    priority: i32,
     op: Op<T>,
 }
 
+#[derive(DebugNoBound, CloneNoBound)]
+pub(crate) struct FailedItem<T: QueueMessage> {
+    item: Item<T>,
+    message: String,
+}
+
+/// The items that are ready to be processed, indexed such that the next item can be dequeued
+/// without scanning the whole queue.
+#[derive(DebugNoBound)]
+pub(crate) struct ReadyQueue<T: QueueMessage> {
+    items: BTreeMap<u32, Item<T>>,
+    /// The ids of the items that can be dequeued now, in fifo order.
+    available: BTreeSet<u32>,
+    /// The items that can be dequeued now, highest priority first and then in fifo order.
+    by_priority: BTreeSet<(Reverse<i32>, u32)>,
+    /// The items that can't be dequeued before their scheduled time, keyed by that time.
+    scheduled: BTreeSet<(u64, u32)>,
+}
+
+impl<T: QueueMessage> Default for ReadyQueue<T> {
+    fn default() -> Self {
+        Self {
+            items: BTreeMap::new(),
+            available: BTreeSet::new(),
+            by_priority: BTreeSet::new(),
+            scheduled: BTreeSet::new(),
+        }
+    }
+}
+
+impl<T: QueueMessage> ReadyQueue<T> {
+    fn insert(&mut self, id: u32, item: Item<T>) {
+        match item.op.not_before() {
+            Some(not_before) => {
+                self.scheduled.insert((not_before, id));
+            }
+            None => {
+                self.available.insert(id);
+                self.by_priority.insert((Reverse(item.priority), id));
+            }
+        }
+
+        self.items.insert(id, item);
+    }
+
+    fn get(&self, id: &u32) -> Option<&Item<T>> {
+        self.items.get(id)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&u32, &Item<T>)> {
+        self.items.iter()
+    }
+
+    /// Set the priority of the item `id`, returning whether it was found.
+    fn set_priority(&mut self, id: u32, priority: i32) -> bool {
+        let Some(item) = self.items.get_mut(&id) else {
+            return false;
+        };
+
+        if self.by_priority.remove(&(Reverse(item.priority), id)) {
+            self.by_priority.insert((Reverse(priority), id));
+        }
+
+        item.priority = priority;
+
+        true
+    }
+
+    /// Remove the next item to process, either the oldest (if `fifo` is set) or the one with the
+    /// highest priority, out of the items that are not scheduled after `now`.
+    fn pop(&mut self, fifo: bool, now: u64) -> Option<(u32, Item<T>)> {
+        // scheduled items become available once their time has come
+        while let Some(&(not_before, id)) = self.scheduled.first() {
+            if not_before > now {
+                break;
+            }
+
+            self.scheduled.pop_first();
+            self.available.insert(id);
+            self.by_priority
+                .insert((Reverse(self.items[&id].priority), id));
+        }
+
+        let id = if fifo {
+            self.available.first().copied()
+        } else {
+            self.by_priority.first().map(|(_, id)| *id)
+        }?;
+
+        let item = self.items.remove(&id).expect("indexed items exist; qed;");
+
+        self.available.remove(&id);
+        self.by_priority.remove(&(Reverse(item.priority), id));
+
+        Some((id, item))
+    }
+}
+
+impl<T: QueueMessage> InMemoryQueue<T> {
+    /// The history of the item `id`, as walked by [`walk_history`].
+    pub async fn history(
+        &self,
+        id: i64,
+        max_depth: u32,
+    ) -> Result<Vec<HistoryItem<T>>, Infallible> {
+        walk_history(id, max_depth, |ids| futures::future::ok(self.items(&ids))).await
+    }
+
+    /// Returns the items with the given status that match `query`, ordered by id.
+    pub async fn list(
+        &self,
+        status: ItemStatus,
+        query: &ItemQuery,
+    ) -> Result<Vec<ItemRecord<T>>, Infallible> {
+        let record = |id: u32, item: &Item<T>, tag: Option<&str>, message: Option<&str>| {
+            let serialized =
+                serde_json::to_string(&item.op).expect("serialization is infallible; qed;");
+
+            query
+                .matches(tag, &serialized, message)
+                .then(|| ItemRecord {
+                    id: i64::from(id),
+                    parents: item.parents.iter().copied().map(i64::from).collect(),
+                    status,
+                    tag: tag.map(ToOwned::to_owned),
+                    priority: matches!(status, ItemStatus::Ready | ItemStatus::Optimize)
+                        .then_some(item.priority),
+                    item: item.op.clone(),
+                    message: message.map(ToOwned::to_owned),
+                })
+        };
+
+        let mut records = match status {
+            ItemStatus::Ready => self
+                .ready
+                .lock()
+                .expect("mutex is poisoned")
+                .iter()
+                .filter_map(|(id, item)| record(*id, item, None, None))
+                .collect::<Vec<_>>(),
+            ItemStatus::Optimize => self
+                .optimizer_queue
+                .lock()
+                .expect("mutex is poisoned")
+                .iter()
+                .flat_map(|(tag, items)| {
+                    items
+                        .iter()
+                        .filter_map(|(id, item)| record(*id, item, Some(tag.as_str()), None))
+                })
+                .collect(),
+            ItemStatus::Done => {
+                let done = self.done.lock().expect("mutex is poisoned");
+                let failed = self.failed.lock().expect("mutex is poisoned");
+
+                done.iter()
+                    .filter(|(id, _)| !failed.contains_key(id))
+                    .filter_map(|(id, item)| record(*id, item, None, None))
+                    .collect()
+            }
+            ItemStatus::Failed => self
+                .failed
+                .lock()
+                .expect("mutex is poisoned")
+                .iter()
+                .filter_map(|(id, failed)| record(*id, &failed.item, None, Some(&failed.message)))
+                .collect(),
+        };
+
+        records.sort_by_key(|record| record.id);
+
+        Ok(records
+            .into_iter()
+            .skip(usize::try_from(query.offset()).unwrap_or(usize::MAX))
+            .take(usize::try_from(query.per_page).unwrap_or_default())
+            .collect())
+    }
+
+    /// Set the priority of the item `id`, if it is ready or waiting to be optimized. Returns
+    /// whether the item was found.
+    pub async fn set_priority(&self, id: i64, priority: i32) -> Result<bool, Infallible> {
+        let Ok(id) = u32::try_from(id) else {
+            return Ok(false);
+        };
+
+        let mut optimizer_queue = self.optimizer_queue.lock().expect("mutex is poisoned");
+        let mut ready = self.ready.lock().expect("mutex is poisoned");
+
+        if ready.set_priority(id, priority) {
+            return Ok(true);
+        }
+
+        let item = optimizer_queue
+            .values_mut()
+            .find_map(|tagged_optimizer_queue| tagged_optimizer_queue.get_mut(&id));
+
+        match item {
+            Some(item) => {
+                item.priority = priority;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Normalize `op` and insert the resulting ops into either the ready or optimizer queue, as
+    /// decided by `filter`.
+    fn insert(
+        &self,
+        optimizer_queue: &mut BTreeMap<String, BTreeMap<u32, Item<T>>>,
+        ready: &mut ReadyQueue<T>,
+        filter: &T::Filter,
+        op: Op<T>,
+        parents: &[u32],
+        parent_priority: Option<i32>,
+    ) {
+        for op in op.normalize() {
+            let (carried, op) = op.take_priority();
+
+            let item = Item {
+                parents: parents.to_vec(),
+                priority: resolve_priority(carried, filter.check_priority(&op), parent_priority),
+                op,
+            };
+
+            match filter.check_interest(&item.op) {
+                FilterResult::Interest(tag) => {
+                    optimizer_queue
+                        .entry(tag.to_owned())
+                        .or_default()
+                        .insert(self.idx.fetch_add(1, Ordering::SeqCst), item);
+                }
+                FilterResult::NoInterest => {
+                    ready.insert(self.idx.fetch_add(1, Ordering::SeqCst), item);
+                }
+            }
+        }
+    }
+
+    fn items(&self, ids: &[i64]) -> Vec<HistoryItem<T>> {
+        // same lock order as `process` and `optimize`
+        let optimizer_queue = self.optimizer_queue.lock().expect("mutex is poisoned");
+        let ready = self.ready.lock().expect("mutex is poisoned");
+        let done = self.done.lock().expect("mutex is poisoned");
+        let failed = self.failed.lock().expect("mutex is poisoned");
+
+        let history_item = |id: u32, item: &Item<T>, status, message| HistoryItem {
+            id: i64::from(id),
+            parents: item.parents.iter().copied().map(i64::from).collect(),
+            status,
+            item: item.op.clone(),
+            message,
+        };
+
+        ids.iter()
+            .filter_map(|&id| u32::try_from(id).ok())
+            .filter_map(|id| {
+                // items are inserted into done before they are processed, so failed must be checked first
+                if let Some(failed) = failed.get(&id) {
+                    Some(history_item(
+                        id,
+                        &failed.item,
+                        ItemStatus::Failed,
+                        Some(failed.message.clone()),
+                    ))
+                } else if let Some(item) = ready.get(&id) {
+                    Some(history_item(id, item, ItemStatus::Ready, None))
+                } else if let Some(item) = optimizer_queue
+                    .values()
+                    .find_map(|tagged_optimizer_queue| tagged_optimizer_queue.get(&id))
+                {
+                    Some(history_item(id, item, ItemStatus::Optimize, None))
+                } else {
+                    done.get(&id)
+                        .map(|item| history_item(id, item, ItemStatus::Done, None))
+                }
+            })
+            .collect()
+    }
+}
+
 impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
     type Error = std::convert::Infallible;
-    type Config = ();
+    type Config = InMemoryQueueConfig;
 
-    fn new(_cfg: Self::Config) -> impl Future<Output = Result<Self, Self::Error>> {
+    fn new(config: Self::Config) -> impl Future<Output = Result<Self, Self::Error>> {
         futures::future::ok(Self {
             idx: Arc::new(AtomicU32::default()),
             done: Arc::new(Mutex::new(BTreeMap::default())),
-            ready: Arc::new(Mutex::new(BTreeMap::default())),
+            failed: Arc::new(Mutex::new(BTreeMap::default())),
+            ready: Arc::new(Mutex::new(ReadyQueue::default())),
             optimizer_queue: Arc::new(Mutex::new(BTreeMap::default())),
+            lanes: Lanes::new(&config.priority),
         })
     }
 
@@ -56,28 +356,7 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
         let mut optimizer_queue = self.optimizer_queue.lock().expect("mutex is poisoned");
         let mut ready = self.ready.lock().expect("mutex is poisoned");
 
-        for op in op.normalize() {
-            match filter.check_interest(&op) {
-                FilterResult::Interest(tag) => {
-                    optimizer_queue.entry(tag.to_owned()).or_default().insert(
-                        self.idx.fetch_add(1, Ordering::SeqCst),
-                        Item {
-                            parents: vec![],
-                            op,
-                        },
-                    );
-                }
-                FilterResult::NoInterest => {
-                    ready.insert(
-                        self.idx.fetch_add(1, Ordering::SeqCst),
-                        Item {
-                            parents: vec![],
-                            op,
-                        },
-                    );
-                }
-            }
-        }
+        self.insert(&mut optimizer_queue, &mut ready, filter, op, &[], None);
 
         debug!("enqueued new item");
 
@@ -94,14 +373,11 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
         Fut: Future<Output = (R, Result<Vec<Op<T>>, String>)> + Send + Captures<'a>,
         R: Send + Sync + 'static,
     {
-        let op = {
-            let mut queue = self.ready.lock().expect("mutex is poisoned");
-            let op = queue.pop_first();
-
-            drop(queue);
-
-            op
-        };
+        let op = self
+            .ready
+            .lock()
+            .expect("mutex is poisoned")
+            .pop(self.lanes.next_is_fifo(), now());
 
         match op {
             Some((id, item)) => {
@@ -124,32 +400,29 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
                             self.optimizer_queue.lock().expect("mutex is poisoned");
                         let mut ready = self.ready.lock().expect("mutex is poisoned");
 
-                        for op in ops.into_iter().flat_map(Op::normalize) {
-                            match filter.check_interest(&op) {
-                                FilterResult::Interest(tag) => {
-                                    optimizer_queue.entry(tag.to_owned()).or_default().insert(
-                                        self.idx.fetch_add(1, Ordering::SeqCst),
-                                        Item {
-                                            parents: vec![id],
-                                            op,
-                                        },
-                                    );
-                                }
-                                FilterResult::NoInterest => {
-                                    ready.insert(
-                                        self.idx.fetch_add(1, Ordering::SeqCst),
-                                        Item {
-                                            parents: vec![id],
-                                            op,
-                                        },
-                                    );
-                                }
-                            }
+                        for op in ops {
+                            self.insert(
+                                &mut optimizer_queue,
+                                &mut ready,
+                                filter,
+                                op,
+                                &[id],
+                                Some(item.priority),
+                            );
                         }
 
                         Ok(Some(r))
                     }
-                    Err(why) => panic!("{why}"),
+                    Err(why) => {
+                        error!(%id, %why, "item failed");
+
+                        self.failed
+                            .lock()
+                            .expect("mutex is poisoned")
+                            .insert(id, FailedItem { item, message: why });
+
+                        Ok(Some(r))
+                    }
                 }
             }
             None => {
@@ -166,6 +439,7 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
     fn optimize<'a, O: Pass<T>>(
         &'a self,
         tag: &'a str,
+        filter: &'a T::Filter,
         optimizer: &'a O,
     ) -> impl Future<Output = Result<(), Either<Self::Error, O::Error>>> + 'a {
         async move {
@@ -181,10 +455,10 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
                 tagged_optimizer_queue
             };
 
-            let (ids, ops): (Vec<_>, Vec<_>) = tagged_optimizer_queue.clone().into_iter().unzip();
+            let (ids, items): (Vec<_>, Vec<_>) = tagged_optimizer_queue.clone().into_iter().unzip();
 
             let res = optimizer
-                .run_pass(ops.into_iter().map(|item| item.op).collect())
+                .run_pass(items.iter().map(|item| item.op.clone()).collect())
                 .await
                 .map_err(Either::Right)?;
 
@@ -194,23 +468,31 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
 
             done.append(&mut tagged_optimizer_queue.clone());
 
+            let item = |parents_idxs: Vec<usize>, op: Op<T>| {
+                let (carried, op) = op.take_priority();
+
+                Item {
+                    parents: parents_idxs.iter().map(|&i| &ids[i]).copied().collect(),
+                    priority: resolve_priority(
+                        carried,
+                        filter.check_priority(&op),
+                        parents_idxs.iter().map(|&i| items[i].priority),
+                    ),
+                    op,
+                }
+            };
+
             for (parents_idxs, op) in res.ready {
                 ready.insert(
                     self.idx.fetch_add(1, Ordering::SeqCst),
-                    Item {
-                        parents: parents_idxs.iter().map(|&i| &ids[i]).copied().collect(),
-                        op,
-                    },
+                    item(parents_idxs, op),
                 );
             }
 
             for (parents_idxs, op, tag) in res.optimize_further {
                 optimizer_queue.entry(tag.clone()).or_default().insert(
                     self.idx.fetch_add(1, Ordering::SeqCst),
-                    Item {
-                        parents: parents_idxs.iter().map(|&i| &ids[i]).copied().collect(),
-                        op,
-                    },
+                    item(parents_idxs, op),
                 );
             }
 
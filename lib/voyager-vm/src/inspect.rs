@@ -0,0 +1,112 @@
+use frame_support_procedural::{CloneNoBound, DebugNoBound};
+use serde::{Deserialize, Serialize};
+
+use crate::{history::ItemStatus, Op, QueueMessage};
+
+/// An item in the queue, as returned by the `list` methods on the queue implementations.
+#[derive(DebugNoBound, CloneNoBound, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = ""), deny_unknown_fields)]
+pub struct ItemRecord<T: QueueMessage> {
+    pub id: i64,
+    pub parents: Vec<i64>,
+    pub status: ItemStatus,
+    /// The optimizer tag, if this item is waiting to be optimized.
+    pub tag: Option<String>,
+    /// The priority of this item, if it is ready or waiting to be optimized.
+    #[serde(default)]
+    pub priority: Option<i32>,
+    pub item: Op<T>,
+    /// The error message, if this item failed.
+    pub message: Option<String>,
+}
+
+/// Filters for listing items in the queue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemQuery {
+    /// Only return items waiting to be optimized with this tag.
+    pub tag: Option<String>,
+    /// SQL `LIKE` patterns run on the serialized item. An item is returned if any pattern
+    /// matches, or if there are no patterns.
+    pub item_filters: Vec<String>,
+    /// SQL `LIKE` patterns run on the error message of failed items. An item is returned if any
+    /// pattern matches, or if there are no patterns.
+    pub message_filters: Vec<String>,
+    /// 1-indexed page of results to return.
+    pub page: i64,
+    pub per_page: i64,
+}
+
+impl Default for ItemQuery {
+    fn default() -> Self {
+        Self {
+            tag: None,
+            item_filters: vec![],
+            message_filters: vec![],
+            page: 1,
+            per_page: 100,
+        }
+    }
+}
+
+impl ItemQuery {
+    /// The amount of items to skip before the requested page.
+    #[must_use]
+    pub fn offset(&self) -> i64 {
+        (self.page.max(1) - 1).saturating_mul(self.per_page)
+    }
+
+    /// Check whether an item matches this query's filters, for queue implementations that can't
+    /// push the filtering down into a database.
+    #[must_use]
+    pub fn matches(&self, tag: Option<&str>, item: &str, message: Option<&str>) -> bool {
+        self.tag.as_deref().is_none_or(|t| Some(t) == tag)
+            && (self.item_filters.is_empty()
+                || self.item_filters.iter().any(|pattern| like(pattern, item)))
+            && (self.message_filters.is_empty()
+                || message.is_some_and(|message| {
+                    self.message_filters
+                        .iter()
+                        .any(|pattern| like(pattern, message))
+                }))
+    }
+}
+
+/// A minimal implementation of SQL `LIKE`, where `%` matches any sequence of characters and `_`
+/// matches any single character. Escapes are not supported.
+#[must_use]
+pub fn like(pattern: &str, s: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let s = s.chars().collect::<Vec<_>>();
+
+    // index into `pattern` and `s` to backtrack to on mismatch, set when a `%` is encountered
+    let mut backtrack = None;
+    let (mut p, mut i) = (0, 0);
+
+    while i < s.len() {
+        match pattern.get(p) {
+            Some('%') => {
+                backtrack = Some((p, i));
+                p += 1;
+            }
+            Some('_') => {
+                p += 1;
+                i += 1;
+            }
+            Some(c) if *c == s[i] => {
+                p += 1;
+                i += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bi)) => {
+                    backtrack = Some((bp, bi + 1));
+                    p = bp + 1;
+                    i = bi + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '%')
+}
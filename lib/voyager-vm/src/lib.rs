@@ -28,8 +28,12 @@ use crate::{filter::InterestFilter, pass::Pass};
 
 pub mod engine;
 pub mod filter;
+pub mod history;
 pub mod in_memory;
+pub mod inspect;
 pub mod pass;
+pub mod priority;
+pub mod recurring;
 
 #[cfg(test)]
 mod tests;
@@ -65,9 +69,12 @@ pub trait Queue<T: QueueMessage>: Debug + Clone + Send + Sync + Sized + 'static
         Fut: Future<Output = (R, Result<Vec<Op<T>>, String>)> + Send + Captures<'a>,
         R: Send + Sync + 'static;
 
+    /// Run `optimizer` on all of the items waiting to be optimized under `tag`. `filter` is used
+    /// to assign priorities to the resulting items.
     fn optimize<'a, O: Pass<T>>(
         &'a self,
         tag: &'a str,
+        filter: &'a T::Filter,
         optimizer: &'a O,
     ) -> impl Future<Output = Result<(), Either<Self::Error, O::Error>>> + Send + 'a;
 }
@@ -114,6 +121,11 @@ pub enum Op<T: QueueMessage> {
     Data(T::Data),
     /// Execute an action.
     Call(T::Call),
+    /// Wait until the unix timestamp `until` (in seconds) has been reached.
+    ///
+    /// If this is the first message of a top-level message (see [`Op::not_before`]), the queue
+    /// will not hand out the item until then. Otherwise, the message is requeued until the
+    /// timestamp is hit.
     Defer {
         until: u64,
     },
@@ -142,6 +154,23 @@ pub enum Op<T: QueueMessage> {
     Promise(Promise<T>),
     /// Handle the contained message, voiding any returned `Data` messages that it returns.
     Void(Box<Self>),
+    /// Handle the contained message once `until` has been reached. This is produced by the
+    /// [`Engine`](crate::engine::Engine) when a message fails with a retryable error, with
+    /// `attempt` being the number of consecutive times the contained message has failed. The
+    /// wrapper is dropped as soon as the contained message makes progress.
+    Retry {
+        attempt: u32,
+        until: u64,
+        op: Box<Self>,
+    },
+    /// Enqueue the contained message with the given priority, see
+    /// [`PriorityConfig`](crate::priority::PriorityConfig). This only has an effect when the message
+    /// is enqueued as a top-level message, in which case the wrapper is removed by the queue. When
+    /// nested within another message, the wrapper is dropped as soon as it is handled.
+    Priority {
+        priority: i32,
+        op: Box<Self>,
+    },
     Noop,
 }
 
@@ -179,7 +208,7 @@ pub trait Visit<T: QueueMessage> {
                 queue.iter_mut().for_each(|op| self.visit_op(op));
                 data.iter_mut().for_each(|data| self.visit_data(data));
             }
-            Op::Void(op) => self.visit_op(op),
+            Op::Void(op) | Op::Retry { op, .. } | Op::Priority { op, .. } => self.visit_op(op),
         }
     }
 
@@ -235,6 +264,57 @@ impl<T> Context<T> {
 pub type BoxDynError = Box<dyn Error + Send + Sync + 'static>;
 
 impl<T: QueueMessage> Op<T> {
+    /// The name of this op's variant, as used in it's serialized `@type` tag.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Op::Data(_) => "data",
+            Op::Call(_) => "call",
+            Op::Defer { .. } => "defer",
+            Op::Seq(_) => "seq",
+            Op::Conc(_) => "conc",
+            Op::Promise(_) => "promise",
+            Op::Void(_) => "void",
+            Op::Retry { .. } => "retry",
+            Op::Priority { .. } => "priority",
+            Op::Noop => "noop",
+        }
+    }
+
+    /// The earliest time (as a unix timestamp in seconds) at which this op can make progress.
+    /// Queues must not hand out an item before this time.
+    ///
+    /// This is the timestamp of the [`Op::Defer`] or [`Op::Retry`] at the front of this op, if
+    /// there is one.
+    #[must_use]
+    pub fn not_before(&self) -> Option<u64> {
+        match self {
+            Op::Defer { until } | Op::Retry { until, .. } => Some(*until),
+            Op::Seq(seq) => seq.front().and_then(Op::not_before),
+            Op::Void(op) | Op::Priority { op, .. } => op.not_before(),
+            _ => None,
+        }
+    }
+
+    /// Remove any [`Op::Priority`] wrappers from this op, returning the priority of the outermost
+    /// wrapper (if any) along with the contained op.
+    #[must_use]
+    pub fn take_priority(self) -> (Option<i32>, Self) {
+        let mut priority = None;
+        let mut op = self;
+
+        while let Op::Priority {
+            priority: inner_priority,
+            op: inner,
+        } = op
+        {
+            priority = priority.or(Some(inner_priority));
+            op = *inner;
+        }
+
+        (priority, op)
+    }
+
     // NOTE: Box is required bc recursion
     #[allow(clippy::type_complexity)]
     pub fn process<'a>(
@@ -344,6 +424,31 @@ impl<T: QueueMessage> Op<T> {
                         op => void(op),
                     }))
                 }
+                Op::Retry { attempt, until, op } => {
+                    let current_ts_seconds = now();
+                    if current_ts_seconds < until {
+                        trace!(
+                            %attempt,
+                            %current_ts_seconds,
+                            %until,
+                            delta = %until - current_ts_seconds,
+                            "retry timestamp not hit yet"
+                        );
+
+                        sleep(Duration::from_millis(10)).await;
+
+                        Ok(Some(retry(attempt, until, *op)))
+                    } else {
+                        debug!(%attempt, "retrying message");
+
+                        op.process(store, depth + 1).await
+                    }
+                }
+                Op::Priority { priority, op } => {
+                    trace!(%priority, "dropping nested priority");
+
+                    op.process(store, depth + 1).await
+                }
                 Op::Noop => Ok(None),
             }
         };
@@ -425,6 +530,13 @@ impl<T: QueueMessage> Op<T> {
                     receiver,
                 })],
                 Op::Void(op) => vec![Op::Void(op)],
+                Op::Retry { attempt, until, op } => vec![Op::Retry { attempt, until, op }],
+                // distribute the priority across all of the normalized messages, with the
+                // outermost priority taking precedence
+                Op::Priority { priority, op } => go(*op)
+                    .into_iter()
+                    .map(|op| priority_op(priority, op.take_priority().1))
+                    .collect(),
                 Op::Noop => vec![],
             }
         }
@@ -435,6 +547,13 @@ impl<T: QueueMessage> Op<T> {
                 // flatten conc to multiple messages
                 match op {
                     Op::Conc(ops) => ops.into_iter().collect(),
+                    Op::Priority { priority, op } => match *op {
+                        Op::Conc(ops) => ops
+                            .into_iter()
+                            .map(|op| priority_op(priority, op))
+                            .collect(),
+                        op => vec![priority_op(priority, op)],
+                    },
                     op => vec![op],
                 }
             })
@@ -516,6 +635,14 @@ pub fn defer<T: QueueMessage>(timestamp: u64) -> Op<T> {
     Op::Defer { until: timestamp }
 }
 
+/// Handle `t` once the unix timestamp `not_before` (in seconds) has been reached. This is a
+/// [`seq`] of a [`defer`] and `t`, and as such will not be handed out by the queue until then.
+#[inline]
+#[must_use = "constructing an instruction has no effect"]
+pub fn schedule<T: QueueMessage>(not_before: u64, t: impl Into<Op<T>>) -> Op<T> {
+    seq([defer(not_before), t.into()])
+}
+
 /// Convenience constructor for [`Op::Call`]
 #[inline]
 #[must_use = "constructing an instruction has no effect"]
@@ -551,6 +678,27 @@ pub fn void<T: QueueMessage>(t: impl Into<Op<T>>) -> Op<T> {
     Op::Void(Box::new(t.into()))
 }
 
+/// Convenience constructor for [`Op::Retry`]
+#[inline]
+#[must_use = "constructing an instruction has no effect"]
+pub fn retry<T: QueueMessage>(attempt: u32, until: u64, t: impl Into<Op<T>>) -> Op<T> {
+    Op::Retry {
+        attempt,
+        until,
+        op: Box::new(t.into()),
+    }
+}
+
+/// Convenience constructor for [`Op::Priority`]
+#[inline]
+#[must_use = "constructing an instruction has no effect"]
+pub fn priority_op<T: QueueMessage>(priority: i32, t: impl Into<Op<T>>) -> Op<T> {
+    Op::Priority {
+        priority,
+        op: Box::new(t.into()),
+    }
+}
+
 #[inline]
 #[must_use = "constructing an instruction has no effect"]
 pub fn noop<T: QueueMessage>() -> Op<T> {
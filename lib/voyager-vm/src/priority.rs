@@ -0,0 +1,84 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Controls the order in which ready items are dequeued.
+///
+/// Items with a higher priority are processed first, with items of equal priority being processed
+/// in the order they were enqueued. To ensure that a constant stream of high priority items can't
+/// starve the rest of the queue, every `fifo_interval`th item is dequeued in plain fifo order,
+/// regardless of it's priority.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriorityConfig {
+    /// Dequeue every `fifo_interval`th item in fifo order. Setting this to 1 disables priorities
+    /// entirely, and setting it to 0 disables starvation protection.
+    pub fifo_interval: u32,
+}
+
+impl Default for PriorityConfig {
+    fn default() -> Self {
+        Self { fifo_interval: 10 }
+    }
+}
+
+/// Decides whether the next item should be dequeued by priority or in fifo order, as configured
+/// by [`PriorityConfig`]. This is cheap to clone, with all clones sharing the same state.
+#[derive(Debug, Clone)]
+pub struct Lanes {
+    fifo_interval: u32,
+    dequeued: Arc<AtomicU32>,
+}
+
+impl Lanes {
+    #[must_use]
+    pub fn new(config: &PriorityConfig) -> Self {
+        Self {
+            fifo_interval: config.fifo_interval,
+            dequeued: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Returns `true` if the next item should be dequeued in fifo order, ignoring priorities.
+    pub fn next_is_fifo(&self) -> bool {
+        if self.fifo_interval == 0 {
+            return false;
+        }
+
+        let dequeued = self
+            .dequeued
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_add(1);
+
+        dequeued % self.fifo_interval == 0
+    }
+}
+
+impl Default for Lanes {
+    fn default() -> Self {
+        Self::new(&PriorityConfig::default())
+    }
+}
+
+/// Resolve the priority of a newly enqueued item.
+///
+/// A priority carried by the op itself (see [`Op::Priority`](crate::Op::Priority)) takes
+/// precedence, followed by the priority assigned by the
+/// [`InterestFilter`](crate::filter::InterestFilter). Otherwise, the item inherits the highest
+/// priority of it's parents, such that all of the items produced by a high priority op are
+/// processed with the same urgency. Items without any parents default to a priority of 0.
+#[must_use]
+pub fn resolve_priority(
+    carried: Option<i32>,
+    assigned: Option<i32>,
+    parents: impl IntoIterator<Item = i32>,
+) -> i32 {
+    carried
+        .or(assigned)
+        .or_else(|| parents.into_iter().max())
+        .unwrap_or_default()
+}
@@ -0,0 +1,44 @@
+use std::num::NonZeroU64;
+
+use frame_support_procedural::{CloneNoBound, DebugNoBound};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{Op, QueueMessage};
+
+/// An op that is enqueued on a fixed schedule.
+///
+/// The schedule is aligned to the unix epoch rather than to when voyager was started, such that
+/// restarts do not cause the op to be enqueued more often than configured. For example, an
+/// interval of 600 seconds will enqueue the op at :00, :10, :20, etc. every hour, and an offset
+/// of 120 seconds will shift that to :02, :12, :22, etc.
+#[derive(DebugNoBound, CloneNoBound, Serialize, Deserialize, JsonSchema)]
+#[serde(bound(serialize = "", deserialize = ""), deny_unknown_fields)]
+#[schemars(bound = "", rename = "RecurringOp")]
+pub struct RecurringOp<T: QueueMessage> {
+    /// A unique name for this schedule, used in logs.
+    pub name: String,
+    pub interval_seconds: NonZeroU64,
+    #[serde(default)]
+    pub offset_seconds: u64,
+    #[schemars(with = "serde_json::Value")]
+    pub op: Op<T>,
+}
+
+impl<T: QueueMessage> RecurringOp<T> {
+    /// The first time (as a unix timestamp in seconds) strictly after `now` at which the op
+    /// should be enqueued.
+    #[must_use]
+    pub fn next_run(&self, now: u64) -> u64 {
+        let interval = self.interval_seconds.get();
+        let offset = self.offset_seconds % interval;
+
+        match now.checked_sub(offset) {
+            Some(since_offset) => (since_offset / interval)
+                .saturating_add(1)
+                .saturating_mul(interval)
+                .saturating_add(offset),
+            None => offset,
+        }
+    }
+}
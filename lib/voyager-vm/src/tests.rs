@@ -1,9 +1,20 @@
+use std::num::NonZeroU64;
+
 use macros::model;
 
 use crate::{
-    call, conc, data, defer, noop, now, promise, seq,
+    call, conc, data, defer,
+    engine::{Engine, RetryConfig},
+    history::{render_history, walk_history, HistoryItem, ItemStatus},
+    in_memory::{InMemoryQueue, InMemoryQueueConfig},
+    inspect::{like, ItemQuery},
+    noop, now,
+    priority::{resolve_priority, Lanes, PriorityConfig},
+    priority_op, promise,
+    recurring::RecurringOp,
+    retry, schedule, seq,
     tests::utils::{BuildPrintAbc, DataA, DataB, DataC, FetchA, FetchB, PrintAbc, SimpleMessage},
-    CallT, CallbackT, Context, Op, QueueError, QueueMessage, VecDeque,
+    CallT, CallbackT, Context, Op, Queue, QueueError, QueueMessage, VecDeque,
 };
 
 pub mod utils;
@@ -36,6 +47,35 @@ impl CallbackT<UnitMessage> for () {
     }
 }
 
+/// A message type whose calls always fail with a retryable error.
+enum RetryMessage {}
+
+impl QueueMessage for RetryMessage {
+    type Data = ();
+    type Call = ();
+    type Callback = ();
+
+    type Filter = ();
+
+    type Context = ();
+}
+
+impl CallT<RetryMessage> for () {
+    async fn process(self, _: Context<&()>) -> Result<Op<RetryMessage>, QueueError> {
+        Err(QueueError::Retry("still failing".into()))
+    }
+}
+
+impl CallbackT<RetryMessage> for () {
+    async fn process(
+        self,
+        _: Context<&()>,
+        _: VecDeque<()>,
+    ) -> Result<Op<RetryMessage>, QueueError> {
+        Ok(noop())
+    }
+}
+
 #[model]
 pub struct SimpleData {}
 #[model]
@@ -287,3 +327,497 @@ fn seq_conc_conc() {
 
     assert_eq!(op.normalize(), expected_output);
 }
+
+#[test]
+fn retry_is_not_normalized() {
+    let op = seq::<UnitMessage>([retry(1, 10, seq([defer(1), seq([defer(2)])]))]);
+    assert_eq!(
+        op.normalize(),
+        vec![retry(1, 10, seq([defer(1), seq([defer(2)])]))]
+    );
+}
+
+#[test]
+fn retry_delay_backoff() {
+    let config = RetryConfig {
+        max_attempts: Some(10),
+        base_delay_seconds: 3,
+        max_delay_seconds: 60,
+        multiplier: 2,
+        jitter: 0.0,
+    };
+
+    assert_eq!(
+        (1..=7)
+            .map(|attempt| config.delay(attempt))
+            .collect::<Vec<_>>(),
+        vec![3, 6, 12, 24, 48, 60, 60]
+    );
+
+    assert_eq!(config.delay(u32::MAX), 60);
+}
+
+#[test]
+fn retry_delay_jitter() {
+    let config = RetryConfig {
+        base_delay_seconds: 100,
+        max_delay_seconds: 100,
+        jitter: 0.1,
+        ..Default::default()
+    };
+
+    for _ in 0..100 {
+        assert!((90..=110).contains(&config.delay(1)));
+    }
+}
+
+/// Step `engine` until the queue is empty or `max_steps` items were processed, returning the
+/// amount of items processed.
+async fn step_engine<Q: Queue<RetryMessage>>(
+    engine: &Engine<'_, RetryMessage, Q>,
+    max_steps: usize,
+) -> usize {
+    let mut steps = 0;
+
+    while steps < max_steps && engine.step().await.unwrap().is_some() {
+        steps += 1;
+    }
+
+    steps
+}
+
+fn no_retry_delay(max_attempts: Option<u32>) -> RetryConfig {
+    RetryConfig {
+        max_attempts,
+        base_delay_seconds: 0,
+        max_delay_seconds: 0,
+        jitter: 0.0,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn engine_fails_item_once_retry_budget_is_exhausted() {
+    let queue = InMemoryQueue::<RetryMessage>::new(InMemoryQueueConfig::default())
+        .await
+        .unwrap();
+
+    queue.enqueue(call(()), &()).await.unwrap();
+
+    let engine = Engine::new(&(), &queue, &()).with_retry_config(no_retry_delay(Some(3)));
+
+    // the initial attempt and 2 retries
+    assert_eq!(step_engine(&engine, 10).await, 3);
+
+    assert!(queue
+        .list(ItemStatus::Ready, &ItemQuery::default())
+        .await
+        .unwrap()
+        .is_empty());
+
+    let failed = queue
+        .list(ItemStatus::Failed, &ItemQuery::default())
+        .await
+        .unwrap();
+
+    assert_eq!(failed.len(), 1);
+    assert!(matches!(failed[0].item, Op::Retry { attempt: 2, .. }));
+    assert!(failed[0]
+        .message
+        .as_deref()
+        .unwrap()
+        .starts_with("retry budget exhausted after 3 attempts"));
+}
+
+#[tokio::test]
+async fn engine_retries_indefinitely_by_default() {
+    assert_eq!(RetryConfig::default().max_attempts, None);
+
+    let queue = InMemoryQueue::<RetryMessage>::new(InMemoryQueueConfig::default())
+        .await
+        .unwrap();
+
+    queue.enqueue(call(()), &()).await.unwrap();
+
+    let engine = Engine::new(&(), &queue, &()).with_retry_config(no_retry_delay(None));
+
+    assert_eq!(step_engine(&engine, 50).await, 50);
+
+    assert!(queue
+        .list(ItemStatus::Failed, &ItemQuery::default())
+        .await
+        .unwrap()
+        .is_empty());
+
+    let ready = queue
+        .list(ItemStatus::Ready, &ItemQuery::default())
+        .await
+        .unwrap();
+
+    assert_eq!(ready.len(), 1);
+    assert!(matches!(ready[0].item, Op::Retry { attempt: 50, .. }));
+}
+
+fn history_items() -> Vec<HistoryItem<UnitMessage>> {
+    vec![
+        HistoryItem {
+            id: 1,
+            parents: vec![],
+            status: ItemStatus::Done,
+            item: defer(1),
+            message: None,
+        },
+        HistoryItem {
+            id: 2,
+            parents: vec![1],
+            status: ItemStatus::Done,
+            item: noop(),
+            message: None,
+        },
+        HistoryItem {
+            id: 3,
+            parents: vec![1, 2],
+            status: ItemStatus::Failed,
+            item: seq([defer(1), noop()]),
+            message: Some("boom".to_owned()),
+        },
+    ]
+}
+
+#[tokio::test]
+async fn walk_history_respects_max_depth() {
+    let items = history_items();
+
+    let fetch = |ids: Vec<i64>| {
+        futures::future::ok::<_, std::convert::Infallible>(
+            items
+                .iter()
+                .filter(|item| ids.contains(&item.id))
+                .cloned()
+                .collect(),
+        )
+    };
+
+    let ids = |history: Vec<HistoryItem<UnitMessage>>| {
+        history.into_iter().map(|item| item.id).collect::<Vec<_>>()
+    };
+
+    assert_eq!(ids(walk_history(3, 0, fetch).await.unwrap()), vec![3]);
+    assert_eq!(ids(walk_history(3, 1, fetch).await.unwrap()), vec![3, 1, 2]);
+    assert_eq!(ids(walk_history(2, 10, fetch).await.unwrap()), vec![2, 1]);
+}
+
+#[test]
+fn render_history_tree() {
+    assert_eq!(
+        render_history(3, &history_items()),
+        "\
+3 [failed] seq[2]: boom
+├── 1 [done] defer
+└── 2 [done] noop
+    └── 1 [done] defer (see above)
+"
+    );
+}
+
+#[test]
+fn like_works() {
+    assert!(like("%", ""));
+    assert!(like("%", "abc"));
+    assert!(like("abc", "abc"));
+    assert!(like("a_c", "abc"));
+    assert!(like("%b%", "abc"));
+    assert!(like("%c", "abcabc"));
+    assert!(like("a%b%c", "axxbyyc"));
+
+    assert!(!like("", "abc"));
+    assert!(!like("ab", "abc"));
+    assert!(!like("%d%", "abc"));
+    assert!(!like("a_", "abc"));
+}
+
+#[test]
+fn item_query_matches() {
+    let query = ItemQuery {
+        tag: Some("tag".to_owned()),
+        message_filters: vec!["%timeout%".to_owned()],
+        ..Default::default()
+    };
+
+    assert!(query.matches(Some("tag"), "{}", Some("rpc timeout")));
+    assert!(!query.matches(None, "{}", Some("rpc timeout")));
+    assert!(!query.matches(Some("tag"), "{}", None));
+    assert!(!query.matches(Some("tag"), "{}", Some("fatal")));
+
+    assert!(ItemQuery::default().matches(None, "{}", None));
+}
+
+#[test]
+fn op_kind_matches_serialized_tag() {
+    for op in [
+        defer::<UnitMessage>(1),
+        seq([noop()]),
+        conc([noop()]),
+        retry(1, 1, noop()),
+        priority_op(1, noop()),
+        noop(),
+    ] {
+        assert_eq!(
+            serde_json::to_value(&op).unwrap()["@type"],
+            serde_json::Value::String(op.kind().to_owned())
+        );
+    }
+}
+
+#[test]
+fn priority_is_distributed_on_normalize() {
+    let op = priority_op::<UnitMessage>(
+        5,
+        conc([
+            defer(1),
+            seq([defer(2), defer(3)]),
+            priority_op(1, defer(4)),
+        ]),
+    );
+
+    assert_eq!(
+        op.normalize(),
+        vec![
+            priority_op(5, defer(1)),
+            priority_op(5, seq([defer(2), defer(3)])),
+            priority_op(5, defer(4)),
+        ]
+    );
+}
+
+#[test]
+fn take_priority_outermost_wins() {
+    assert_eq!(
+        priority_op::<UnitMessage>(1, priority_op(2, defer(1))).take_priority(),
+        (Some(1), defer(1))
+    );
+    assert_eq!(defer::<UnitMessage>(1).take_priority(), (None, defer(1)));
+}
+
+#[test]
+fn resolve_priority_precedence() {
+    assert_eq!(resolve_priority(Some(1), Some(2), [3]), 1);
+    assert_eq!(resolve_priority(None, Some(2), [3]), 2);
+    assert_eq!(resolve_priority(None, None, [3, -1]), 3);
+    assert_eq!(resolve_priority(None, None, []), 0);
+}
+
+#[test]
+fn lanes_fifo_interval() {
+    let lanes = Lanes::new(&PriorityConfig { fifo_interval: 3 });
+    assert_eq!(
+        (0..6).map(|_| lanes.next_is_fifo()).collect::<Vec<_>>(),
+        vec![false, false, true, false, false, true]
+    );
+
+    let lanes = Lanes::new(&PriorityConfig { fifo_interval: 0 });
+    assert!((0..100).all(|_| !lanes.next_is_fifo()));
+
+    let lanes = Lanes::new(&PriorityConfig { fifo_interval: 1 });
+    assert!((0..100).all(|_| lanes.next_is_fifo()));
+}
+
+#[tokio::test]
+async fn in_memory_queue_dequeues_by_priority() {
+    let queue = InMemoryQueue::<UnitMessage>::new(InMemoryQueueConfig::default())
+        .await
+        .unwrap();
+
+    for op in [
+        defer(1),
+        priority_op(5, defer(2)),
+        priority_op(-1, defer(3)),
+        defer(4),
+    ] {
+        queue.enqueue(op, &()).await.unwrap();
+    }
+
+    let mut processed = vec![];
+    while let Some(op) = queue
+        .process(&(), |op, _| async move { (op, Ok(vec![])) })
+        .await
+        .unwrap()
+    {
+        processed.push(op);
+    }
+
+    assert_eq!(processed, vec![defer(2), defer(1), defer(4), defer(3)]);
+}
+
+#[tokio::test]
+async fn in_memory_queue_uses_priority_config() {
+    // every item is dequeued in fifo order
+    let queue = InMemoryQueue::<UnitMessage>::new(InMemoryQueueConfig {
+        priority: PriorityConfig { fifo_interval: 1 },
+    })
+    .await
+    .unwrap();
+
+    for op in [defer(1), priority_op(5, defer(2)), defer(3)] {
+        queue.enqueue(op, &()).await.unwrap();
+    }
+
+    let mut processed = vec![];
+    while let Some(op) = queue
+        .process(&(), |op, _| async move { (op, Ok(vec![])) })
+        .await
+        .unwrap()
+    {
+        processed.push(op);
+    }
+
+    assert_eq!(processed, vec![defer(1), defer(2), defer(3)]);
+}
+
+#[tokio::test]
+async fn in_memory_queue_set_priority_reorders() {
+    let queue = InMemoryQueue::<UnitMessage>::new(InMemoryQueueConfig {
+        priority: PriorityConfig { fifo_interval: 0 },
+    })
+    .await
+    .unwrap();
+
+    for op in [defer(1), defer(2), defer(3)] {
+        queue.enqueue(op, &()).await.unwrap();
+    }
+
+    let id = queue
+        .list(ItemStatus::Ready, &ItemQuery::default())
+        .await
+        .unwrap()[2]
+        .id;
+    assert!(queue.set_priority(id, 1).await.unwrap());
+
+    let mut processed = vec![];
+    while let Some(op) = queue
+        .process(&(), |op, _| async move { (op, Ok(vec![])) })
+        .await
+        .unwrap()
+    {
+        processed.push(op);
+    }
+
+    assert_eq!(processed, vec![defer(3), defer(1), defer(2)]);
+}
+
+#[tokio::test]
+async fn in_memory_queue_priority_is_inherited() {
+    let queue = InMemoryQueue::<UnitMessage>::new(InMemoryQueueConfig::default())
+        .await
+        .unwrap();
+
+    queue.enqueue(priority_op(5, defer(1)), &()).await.unwrap();
+
+    queue
+        .process(&(), |_, _| async move {
+            ((), Ok(vec![defer(2), priority_op(1, defer(3))]))
+        })
+        .await
+        .unwrap();
+
+    let priorities = queue
+        .list(ItemStatus::Ready, &ItemQuery::default())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|record| (record.item, record.priority))
+        .collect::<Vec<_>>();
+
+    assert_eq!(priorities, vec![(defer(2), Some(5)), (defer(3), Some(1))]);
+}
+
+#[test]
+fn not_before() {
+    assert_eq!(defer::<UnitMessage>(10).not_before(), Some(10));
+    assert_eq!(schedule::<UnitMessage>(10, noop()).not_before(), Some(10));
+    assert_eq!(
+        retry::<UnitMessage>(1, 10, defer(20)).not_before(),
+        Some(10)
+    );
+    assert_eq!(
+        priority_op::<UnitMessage>(1, schedule(10, noop())).not_before(),
+        Some(10)
+    );
+
+    assert_eq!(seq::<UnitMessage>([noop(), defer(10)]).not_before(), None);
+    assert_eq!(
+        conc::<UnitMessage>([defer(10), defer(20)]).not_before(),
+        None
+    );
+    assert_eq!(noop::<UnitMessage>().not_before(), None);
+}
+
+#[test]
+fn recurring_op_next_run() {
+    let recurring = RecurringOp::<UnitMessage> {
+        name: "test".to_owned(),
+        interval_seconds: NonZeroU64::new(600).unwrap(),
+        offset_seconds: 120,
+        op: noop(),
+    };
+
+    assert_eq!(recurring.next_run(0), 120);
+    assert_eq!(recurring.next_run(119), 120);
+    assert_eq!(recurring.next_run(120), 720);
+    assert_eq!(recurring.next_run(721), 1320);
+
+    let recurring = RecurringOp::<UnitMessage> {
+        offset_seconds: 0,
+        ..recurring
+    };
+
+    assert_eq!(recurring.next_run(0), 600);
+    assert_eq!(recurring.next_run(599), 600);
+    assert_eq!(recurring.next_run(600), 1200);
+}
+
+#[test]
+fn recurring_op_rejects_zero_interval() {
+    let recurring = |interval_seconds: u64| {
+        serde_json::from_value::<RecurringOp<UnitMessage>>(serde_json::json!({
+            "name": "test",
+            "interval_seconds": interval_seconds,
+            "op": noop::<UnitMessage>(),
+        }))
+    };
+
+    assert!(recurring(0).is_err());
+    assert_eq!(recurring(1).unwrap().interval_seconds.get(), 1);
+}
+
+#[tokio::test]
+async fn in_memory_queue_holds_scheduled_items() {
+    let queue = InMemoryQueue::<UnitMessage>::new(InMemoryQueueConfig::default())
+        .await
+        .unwrap();
+
+    queue
+        .enqueue(schedule(now() + 1000, noop()), &())
+        .await
+        .unwrap();
+    queue.enqueue(defer(1), &()).await.unwrap();
+
+    let mut processed = vec![];
+    while let Some(op) = queue
+        .process(&(), |op, _| async move { (op, Ok(vec![])) })
+        .await
+        .unwrap()
+    {
+        processed.push(op);
+    }
+
+    assert_eq!(processed, vec![defer(1)]);
+    assert_eq!(
+        queue
+            .list(ItemStatus::Ready, &ItemQuery::default())
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
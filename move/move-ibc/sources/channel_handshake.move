@@ -95,4 +95,27 @@ module ibc::channel_handshake {
 
         dispatcher::delete_storage<T>();
     }
+
+    public entry fun channel_close_init<T: key + store + drop>(
+        port_id: address, channel_id: u32
+    ) {
+        ibc::channel_close_init<T>(port_id, channel_id);
+
+        engine::dispatch<T>(helpers::pack_channel_close_init_params(channel_id));
+
+        dispatcher::delete_storage<T>();
+    }
+
+    public entry fun channel_close_confirm<T: key + store + drop>(
+        port_id: address,
+        channel_id: u32,
+        proof_init: vector<u8>,
+        proof_height: u64
+    ) {
+        ibc::channel_close_confirm<T>(port_id, channel_id, proof_init, proof_height);
+
+        engine::dispatch<T>(helpers::pack_channel_close_confirm_params(channel_id));
+
+        dispatcher::delete_storage<T>();
+    }
 }
@@ -143,6 +143,24 @@ module ibc::ibc {
         connection_id: u32
     }
 
+    #[event]
+    struct ChannelCloseInit has copy, drop, store {
+        port_id: String,
+        channel_id: u32,
+        counterparty_port_id: vector<u8>,
+        counterparty_channel_id: u32,
+        connection_id: u32
+    }
+
+    #[event]
+    struct ChannelCloseConfirm has copy, drop, store {
+        port_id: String,
+        channel_id: u32,
+        counterparty_port_id: vector<u8>,
+        counterparty_channel_id: u32,
+        connection_id: u32
+    }
+
     #[event]
     struct ConnectionOpenTry has copy, drop, store {
         connection_id: u32,
@@ -971,6 +989,122 @@ module ibc::ibc {
         );
     }
 
+    /// Execute the init phase of the channel closing handshake. `T` is the witness type of the target module that is
+    /// previously been registered to this contract.
+    ///
+    /// * `port_id`: The address of the IBC app on this chain that owns this channel.
+    /// * `channel_id`: The ID of the channel on this chain.
+    public fun channel_close_init<T: key + store + drop>(
+        port_id: address, channel_id: u32
+    ) acquires IBCStore, Port {
+        let port = borrow_global<Port<T>>(get_vault_addr());
+        assert!(port.port_id == port_id, E_UNAUTHORIZED);
+        let chan =
+            *smart_table::borrow(
+                &borrow_global<IBCStore>(get_vault_addr()).channels,
+                channel_id
+            );
+
+        assert!(channel::state(&chan) == CHAN_STATE_OPEN, E_INVALID_CHANNEL_STATE);
+
+        let port_id = address_to_string(port_id);
+
+        ensure_connection_state(channel::connection_id(&chan));
+
+        channel::set_state(&mut chan, CHAN_STATE_CLOSED);
+
+        smart_table::upsert(
+            &mut borrow_global_mut<IBCStore>(get_vault_addr()).channels,
+            channel_id,
+            chan
+        );
+
+        commit_channel(channel_id, chan);
+
+        event::emit(
+            ChannelCloseInit {
+                port_id,
+                channel_id,
+                counterparty_channel_id: channel::counterparty_channel_id(&chan),
+                counterparty_port_id: *channel::counterparty_port_id(&chan),
+                connection_id: channel::connection_id(&chan)
+            }
+        );
+    }
+
+    /// Execute the confirm phase of the channel closing handshake. `T` is the witness type of the target module that is
+    /// previously been registered to this contract.
+    ///
+    /// * `port_id`: The address of the IBC app on this chain that owns this channel.
+    /// * `channel_id`: The ID of the channel on this chain.
+    /// * `proof_init`: The membership proof of the closed channel state in the counterparty chain. The encoding is defined
+    ///   by the light client (`client_id`).
+    /// * `proof_height`: The height at when `proof_init` was generated.
+    public fun channel_close_confirm<T: key + store + drop>(
+        port_id: address,
+        channel_id: u32,
+        proof_init: vector<u8>,
+        proof_height: u64
+    ) acquires IBCStore, Port {
+        let port = borrow_global<Port<T>>(get_vault_addr());
+        assert!(port.port_id == port_id, E_UNAUTHORIZED);
+        let chan =
+            *smart_table::borrow(
+                &borrow_global<IBCStore>(get_vault_addr()).channels,
+                channel_id
+            );
+
+        assert!(channel::state(&chan) == CHAN_STATE_OPEN, E_INVALID_CHANNEL_STATE);
+
+        let port_id = address_to_string(port_id);
+
+        let connection_id = channel::connection_id(&chan);
+
+        let client_id = ensure_connection_state(connection_id);
+
+        let expected_channel =
+            channel::new(
+                CHAN_STATE_CLOSED,
+                get_counterparty_connection(connection_id),
+                channel_id,
+                bcs::to_bytes(&port.port_id),
+                *channel::version(&chan)
+            );
+
+        let client_type = client_id_to_type(client_id);
+
+        let err =
+            verify_channel_state(
+                client_type,
+                client_id,
+                proof_height,
+                proof_init,
+                channel::counterparty_channel_id(&chan),
+                expected_channel
+            );
+        assert!(err == 0, err);
+
+        channel::set_state(&mut chan, CHAN_STATE_CLOSED);
+
+        smart_table::upsert(
+            &mut borrow_global_mut<IBCStore>(get_vault_addr()).channels,
+            channel_id,
+            chan
+        );
+
+        commit_channel(channel_id, chan);
+
+        event::emit(
+            ChannelCloseConfirm {
+                port_id,
+                channel_id,
+                counterparty_channel_id: channel::counterparty_channel_id(&chan),
+                counterparty_port_id: *channel::counterparty_port_id(&chan),
+                connection_id
+            }
+        );
+    }
+
     /// Used for sending a packet to the counterparty chain. Note that this doesn't send the packet directly, it prepares the packet
     /// and emits a `SendPacket` event such that it's being picked up by a relayer.
     ///
@@ -0,0 +1,157 @@
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    transports::BoxTransport,
+};
+use arbitrum_client::RollupConfig;
+use arbitrum_light_client_types::{ClientState, ConsensusState};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::instrument;
+use unionlabs::{ibc::core::client::height::Height, primitives::H160, ErrorReporter};
+use voyager_message::{
+    core::{ChainId, ClientType},
+    into_value,
+    module::{ClientBootstrapModuleInfo, ClientBootstrapModuleServer},
+    ClientBootstrapModule,
+};
+use voyager_vm::BoxDynError;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+
+    /// The id of the L1 client on the counterparty that the arbitrum client will be verified
+    /// against.
+    pub l1_client_id: u32,
+
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+
+    pub rollup: RollupConfig,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The id of the L1 client on the counterparty that the arbitrum client will be verified
+    /// against.
+    pub l1_client_id: u32,
+
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+
+    /// The rollup contract on the L1.
+    pub rollup: RollupConfig,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The RPC endpoint for the L2 chain.
+    pub rpc_url: String,
+}
+
+impl ClientBootstrapModule for Module {
+    type Config = Config;
+
+    async fn new(
+        config: Self::Config,
+        info: ClientBootstrapModuleInfo,
+    ) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+
+        let l2_provider = ProviderBuilder::new().on_builtin(&config.rpc_url).await?;
+
+        let chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        info.ensure_chain_id(chain_id.to_string())?;
+        info.ensure_client_type(ClientType::ARBITRUM)?;
+
+        Ok(Self {
+            chain_id,
+            l1_client_id: config.l1_client_id,
+            ibc_handler_address: config.ibc_handler_address,
+            rollup: config.rollup,
+            l1_provider,
+            l2_provider,
+        })
+    }
+}
+
+#[async_trait]
+impl ClientBootstrapModuleServer for Module {
+    /// The client state of this chain at the specified L1 `Height`.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height))]
+    async fn self_client_state(&self, _: &Extensions, height: Height) -> RpcResult<Value> {
+        Ok(into_value(ClientState {
+            l1_client_id: self.l1_client_id,
+            chain_id: self
+                .chain_id
+                .as_str()
+                .parse()
+                .expect("self.chain_id is a valid u256"),
+            l1_latest_slot: height.height(),
+            l1_contract_address: self.rollup.l1_contract_address,
+            l1_next_node_num_slot: self.rollup.l1_next_node_num_slot,
+            l1_nodes_slot: self.rollup.l1_nodes_slot,
+            l1_next_node_num_slot_offset_bytes: self.rollup.l1_next_node_num_slot_offset_bytes,
+            l1_nodes_confirm_data_offset: self.rollup.l1_nodes_confirm_data_offset,
+            frozen_height: Height::new(0),
+            l2_ibc_contract_address: self.ibc_handler_address,
+        }))
+    }
+
+    /// The consensus state on this chain at the specified L1 `Height`, i.e. of the L2 block settled
+    /// on the L1 at that height.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height))]
+    async fn self_consensus_state(&self, _: &Extensions, height: Height) -> RpcResult<Value> {
+        let l2_block = arbitrum_client::settled_l2_block(
+            &self.rollup,
+            &self.l1_provider,
+            &self.l2_provider,
+            height.height(),
+        )
+        .await
+        .map_err(|e| {
+            ErrorObject::owned(
+                -1,
+                format!("error fetching settled l2 block: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        })?;
+
+        let ibc_storage_root = self
+            .l2_provider
+            .get_proof(self.ibc_handler_address.into(), vec![])
+            .block_id(l2_block.header.number.into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    format!("error fetching ibc account proof: {}", ErrorReporter(e)),
+                    None::<()>,
+                )
+            })?
+            .storage_hash
+            .into();
+
+        Ok(into_value(ConsensusState {
+            ibc_storage_root,
+            // Normalize to nanos in order to be compliant with cosmos
+            timestamp: l2_block.header.timestamp * 1_000_000_000,
+        }))
+    }
+}
@@ -0,0 +1,182 @@
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::BlockTransactionsKind,
+    transports::BoxTransport,
+};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use scroll_api::ScrollClient;
+use scroll_client::RollupConfig;
+use scroll_light_client_types::{ClientState, ConsensusState};
+use scroll_rpc::BlockId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::instrument;
+use unionlabs::{ibc::core::client::height::Height, primitives::H160, ErrorReporter};
+use voyager_message::{
+    core::{ChainId, ClientType},
+    into_value,
+    module::{ClientBootstrapModuleInfo, ClientBootstrapModuleServer},
+    ClientBootstrapModule,
+};
+use voyager_vm::BoxDynError;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+
+    /// The id of the L1 client on the counterparty that the scroll client will be verified
+    /// against.
+    pub l1_client_id: u32,
+
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+
+    pub rollup: RollupConfig,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+
+    /// Scroll returns zktrie proofs from `eth_getProof`, which can't be read with alloy.
+    pub l2_client: scroll_rpc::JsonRpcClient,
+
+    pub scroll_api: ScrollClient,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The id of the L1 client on the counterparty that the scroll client will be verified
+    /// against.
+    pub l1_client_id: u32,
+
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+
+    /// The rollup contract on the L1.
+    pub rollup: RollupConfig,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The websocket RPC endpoint for the L2 chain.
+    pub rpc_url: String,
+    /// The endpoint of the scroll api, used to map finalized batches to L2 heights.
+    pub scroll_api_url: String,
+}
+
+impl ClientBootstrapModule for Module {
+    type Config = Config;
+
+    async fn new(
+        config: Self::Config,
+        info: ClientBootstrapModuleInfo,
+    ) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+
+        let l2_provider = ProviderBuilder::new().on_builtin(&config.rpc_url).await?;
+
+        let chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        info.ensure_chain_id(chain_id.to_string())?;
+        info.ensure_client_type(ClientType::SCROLL)?;
+
+        Ok(Self {
+            chain_id,
+            l1_client_id: config.l1_client_id,
+            ibc_handler_address: config.ibc_handler_address,
+            rollup: config.rollup,
+            l1_provider,
+            l2_provider,
+            l2_client: scroll_rpc::JsonRpcClient::new(&config.rpc_url).await?,
+            scroll_api: ScrollClient::new(config.scroll_api_url),
+        })
+    }
+}
+
+#[async_trait]
+impl ClientBootstrapModuleServer for Module {
+    /// The client state of this chain at the specified L1 `Height`.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height))]
+    async fn self_client_state(&self, _: &Extensions, height: Height) -> RpcResult<Value> {
+        Ok(into_value(ClientState {
+            l1_client_id: self.l1_client_id,
+            chain_id: self
+                .chain_id
+                .as_str()
+                .parse()
+                .expect("self.chain_id is a valid u256"),
+            latest_slot: height.height(),
+            l2_contract_address: self.rollup.rollup_contract_address,
+            latest_batch_index_slot: self.rollup.rollup_last_finalized_batch_index_slot,
+            l2_finalized_state_roots_slot: self.rollup.rollup_finalized_state_roots_slot,
+            l2_committed_batches_slot: self.rollup.rollup_committed_batches_slot,
+            frozen_height: Height::new(0),
+            ibc_contract_address: self.ibc_handler_address,
+        }))
+    }
+
+    /// The consensus state on this chain at the specified L1 `Height`, i.e. of the last block of
+    /// the last batch finalized on the L1 at that height.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height))]
+    async fn self_consensus_state(&self, _: &Extensions, height: Height) -> RpcResult<Value> {
+        let l2_height = scroll_client::settled_l2_height(
+            &self.rollup,
+            &self.l1_provider,
+            &self.scroll_api,
+            height.height(),
+        )
+        .await
+        .map_err(|e| {
+            ErrorObject::owned(
+                -1,
+                format!("error fetching settled l2 height: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        })?;
+
+        let l2_block = self
+            .l2_provider
+            .get_block_by_number(l2_height.into(), BlockTransactionsKind::Hashes)
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    format!("error fetching l2 block: {}", ErrorReporter(e)),
+                    None::<()>,
+                )
+            })?
+            .ok_or_else(|| {
+                ErrorObject::owned(-1, format!("l2 block {l2_height} not found"), None::<()>)
+            })?;
+
+        let ibc_storage_root = self
+            .l2_client
+            .get_proof(self.ibc_handler_address, [], BlockId::Number(l2_height))
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    format!("error fetching ibc account proof: {}", ErrorReporter(e)),
+                    None::<()>,
+                )
+            })?
+            .storage_hash;
+
+        Ok(into_value(ConsensusState {
+            state_root: l2_block.header.state_root.into(),
+            ibc_storage_root,
+            // Normalize to nanos in order to be compliant with cosmos
+            timestamp: l2_block.header.timestamp * 1_000_000_000,
+        }))
+    }
+}
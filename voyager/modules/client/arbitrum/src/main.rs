@@ -0,0 +1,217 @@
+use arbitrum_light_client_types::{ClientState, ConsensusState, Header};
+use ethereum_light_client_types::StorageProof;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::instrument;
+use unionlabs::{
+    self,
+    encoding::{Bincode, DecodeAs, EncodeAs, EthAbi},
+    ibc::core::client::height::Height,
+    primitives::Bytes,
+    ErrorReporter,
+};
+use voyager_message::{
+    core::{
+        ChainId, ClientStateMeta, ClientType, ConsensusStateMeta, ConsensusType, IbcInterface,
+        Timestamp,
+    },
+    module::{ClientModuleInfo, ClientModuleServer},
+    ClientModule, FATAL_JSONRPC_ERROR_CODE,
+};
+use voyager_vm::BoxDynError;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {}
+
+impl ClientModule for Module {
+    type Config = Config;
+
+    async fn new(Config {}: Self::Config, info: ClientModuleInfo) -> Result<Self, BoxDynError> {
+        info.ensure_client_type(ClientType::ARBITRUM)?;
+        info.ensure_consensus_type(ConsensusType::ARBITRUM)?;
+        info.ensure_ibc_interface(IbcInterface::IBC_COSMWASM)?;
+
+        Ok(Module {})
+    }
+}
+
+type SelfConsensusState = ConsensusState;
+type SelfClientState = ClientState;
+
+impl Module {
+    pub fn decode_consensus_state(consensus_state: &[u8]) -> RpcResult<SelfConsensusState> {
+        SelfConsensusState::decode_as::<EthAbi>(consensus_state).map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to decode consensus state: {}", ErrorReporter(err)),
+                None::<()>,
+            )
+        })
+    }
+
+    pub fn decode_client_state(client_state: &[u8]) -> RpcResult<SelfClientState> {
+        <SelfClientState>::decode_as::<Bincode>(client_state).map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to decode client state: {err}"),
+                None::<()>,
+            )
+        })
+    }
+
+    pub fn make_height(revision_height: u64) -> Height {
+        Height::new(revision_height)
+    }
+}
+
+#[async_trait]
+impl ClientModuleServer for Module {
+    #[instrument]
+    async fn decode_client_state_meta(
+        &self,
+        _: &Extensions,
+        client_state: Bytes,
+    ) -> RpcResult<ClientStateMeta> {
+        let cs = Module::decode_client_state(&client_state)?;
+
+        Ok(ClientStateMeta {
+            chain_id: ChainId::new(cs.chain_id.to_string()),
+            // the arbitrum client is tracked by l1 heights
+            counterparty_height: Module::make_height(cs.l1_latest_slot),
+        })
+    }
+
+    #[instrument]
+    async fn decode_consensus_state_meta(
+        &self,
+        _: &Extensions,
+        consensus_state: Bytes,
+    ) -> RpcResult<ConsensusStateMeta> {
+        let cs = Module::decode_consensus_state(&consensus_state)?;
+
+        Ok(ConsensusStateMeta {
+            timestamp_nanos: Timestamp::from_nanos(cs.timestamp),
+        })
+    }
+
+    #[instrument]
+    async fn decode_client_state(&self, _: &Extensions, client_state: Bytes) -> RpcResult<Value> {
+        Ok(serde_json::to_value(Module::decode_client_state(&client_state)?).unwrap())
+    }
+
+    #[instrument]
+    async fn decode_consensus_state(
+        &self,
+        _: &Extensions,
+        consensus_state: Bytes,
+    ) -> RpcResult<Value> {
+        Ok(serde_json::to_value(Module::decode_consensus_state(&consensus_state)?).unwrap())
+    }
+
+    #[instrument]
+    async fn encode_client_state(
+        &self,
+        _: &Extensions,
+        client_state: Value,
+        metadata: Value,
+    ) -> RpcResult<Bytes> {
+        if !metadata.is_null() {
+            return Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                "metadata was provided, but this client type does not require \
+                metadata for client state encoding",
+                Some(json!({
+                    "provided_metadata": metadata,
+                })),
+            ));
+        }
+
+        serde_json::from_value::<ClientState>(client_state)
+            .map_err(|err| {
+                ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!("unable to deserialize client state: {}", ErrorReporter(err)),
+                    None::<()>,
+                )
+            })
+            .map(|cs| cs.encode_as::<Bincode>())
+            .map(Into::into)
+    }
+
+    #[instrument]
+    async fn encode_consensus_state(
+        &self,
+        _: &Extensions,
+        consensus_state: Value,
+    ) -> RpcResult<Bytes> {
+        serde_json::from_value::<ConsensusState>(consensus_state)
+            .map_err(|err| {
+                ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!(
+                        "unable to deserialize consensus state: {}",
+                        ErrorReporter(err)
+                    ),
+                    None::<()>,
+                )
+            })
+            .map(|cs| cs.encode_as::<EthAbi>())
+            .map(Into::into)
+    }
+
+    #[instrument]
+    async fn encode_header(&self, _: &Extensions, header: Value) -> RpcResult<Bytes> {
+        serde_json::from_value::<Header>(header)
+            .map_err(|err| {
+                ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!("unable to deserialize header: {}", ErrorReporter(err)),
+                    None::<()>,
+                )
+            })
+            .map(|header| header.encode_as::<Bincode>())
+            .map(Into::into)
+    }
+
+    #[instrument]
+    async fn encode_proof(&self, _: &Extensions, proof: Value) -> RpcResult<Bytes> {
+        serde_json::from_value::<StorageProof>(proof)
+            .map_err(|err| {
+                ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!("unable to deserialize proof: {}", ErrorReporter(err)),
+                    None::<()>,
+                )
+            })
+            .map(|storage_proof| storage_proof.encode_as::<Bincode>())
+            .map(Into::into)
+    }
+
+    #[instrument(skip_all)]
+    async fn encode_misbehaviour(
+        &self,
+        _: &Extensions,
+        _header_a: Bytes,
+        _header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        Err(ErrorObject::owned(
+            FATAL_JSONRPC_ERROR_CODE,
+            format!("misbehaviour is not supported for {}", ClientType::ARBITRUM),
+            None::<()>,
+        ))
+    }
+}
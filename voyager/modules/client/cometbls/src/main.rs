@@ -1,6 +1,6 @@
 use alloy::sol_types::SolValue;
 use ark_serialize::{CanonicalSerialize, SerializationError, Valid};
-use cometbls_light_client_types::{ClientState, ConsensusState, Header};
+use cometbls_light_client_types::{ClientState, ConsensusState, Header, Misbehaviour};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     types::ErrorObject,
@@ -127,6 +127,49 @@ impl Module {
         }
     }
 
+    pub fn decode_header(&self, header: &[u8]) -> RpcResult<Header> {
+        match self.ibc_interface {
+            SupportedIbcInterface::IbcSolidity => {
+                Header::decode_as::<EthAbi>(header).map_err(|err| {
+                    ErrorObject::owned(
+                        FATAL_JSONRPC_ERROR_CODE,
+                        format!("unable to decode header: {}", ErrorReporter(err)),
+                        None::<()>,
+                    )
+                })
+            }
+            SupportedIbcInterface::IbcCosmwasm => {
+                Header::decode_as::<Bincode>(header).map_err(|err| {
+                    ErrorObject::owned(
+                        FATAL_JSONRPC_ERROR_CODE,
+                        format!("unable to decode header: {err}"),
+                        None::<()>,
+                    )
+                })
+            }
+            SupportedIbcInterface::IbcGoV8_08Wasm => {
+                <Any<wasm::client_message::ClientMessage<Header>>>::decode_as::<Proto>(header)
+                    .map_err(|err| {
+                        ErrorObject::owned(
+                            FATAL_JSONRPC_ERROR_CODE,
+                            format!("unable to decode header: {}", ErrorReporter(err)),
+                            None::<()>,
+                        )
+                    })
+                    .map(|any| any.0.data)
+            }
+            // headers are submitted with a re-encoded zkp on move, see `encode_header`
+            SupportedIbcInterface::IbcMoveAptos => Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!(
+                    "decoding headers is not supported on {}",
+                    self.ibc_interface.as_str()
+                ),
+                None::<()>,
+            )),
+        }
+    }
+
     pub fn decode_client_state(&self, client_state: &[u8]) -> RpcResult<ClientState> {
         match self.ibc_interface {
             SupportedIbcInterface::IbcSolidity => ClientState::decode_as::<EthAbi>(client_state)
@@ -381,6 +424,39 @@ impl ClientModuleServer for Module {
             })
             .map(Into::into)
     }
+
+    #[instrument(skip_all)]
+    async fn encode_misbehaviour(
+        &self,
+        _: &Extensions,
+        header_a: Bytes,
+        header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        let misbehaviour = Misbehaviour {
+            header_a: self.decode_header(&header_a)?,
+            header_b: self.decode_header(&header_b)?,
+        };
+
+        match self.ibc_interface {
+            SupportedIbcInterface::IbcSolidity => Ok(misbehaviour.encode_as::<EthAbi>()),
+            SupportedIbcInterface::IbcCosmwasm => Ok(misbehaviour.encode_as::<Bincode>()),
+            SupportedIbcInterface::IbcGoV8_08Wasm => {
+                Ok(
+                    Any(wasm::client_message::ClientMessage { data: misbehaviour })
+                        .encode_as::<Proto>(),
+                )
+            }
+            SupportedIbcInterface::IbcMoveAptos => Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!(
+                    "misbehaviour is not supported on {}",
+                    self.ibc_interface.as_str()
+                ),
+                None::<()>,
+            )),
+        }
+        .map(Into::into)
+    }
 }
 
 fn encode_merkle_proof_for_evm(
@@ -518,6 +594,12 @@ struct MoveMembershipProof {
     top_level_proof: ics23::existence_proof::ExistenceProof,
 }
 
+#[model]
+struct MoveNonMembershipProof {
+    sub_proof: ics23::non_existence_proof::NonExistenceProof,
+    top_level_proof: ics23::existence_proof::ExistenceProof,
+}
+
 fn encode_merkle_proof_for_move(proof: ics23::merkle_proof::MerkleProof) -> Vec<u8> {
     match proof {
         ics23::merkle_proof::MerkleProof::Membership(sub_proof, top_level_proof) => {
@@ -525,8 +607,14 @@ fn encode_merkle_proof_for_move(proof: ics23::merkle_proof::MerkleProof) -> Vec<
                 sub_proof,
                 top_level_proof,
             }
+            .encode_as::<Bcs>()
+        }
+        ics23::merkle_proof::MerkleProof::NonMembership(sub_proof, top_level_proof) => {
+            MoveNonMembershipProof {
+                sub_proof,
+                top_level_proof,
+            }
+            .encode_as::<Bcs>()
         }
-        ics23::merkle_proof::MerkleProof::NonMembership(_, _) => todo!(),
     }
-    .encode_as::<Bcs>()
 }
@@ -202,4 +202,18 @@ impl ClientModuleServer for Module {
             .map(|cs| cs.encode_as::<Bincode>())
             .map(Into::into)
     }
+
+    #[instrument(skip_all)]
+    async fn encode_misbehaviour(
+        &self,
+        _: &Extensions,
+        _header_a: Bytes,
+        _header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        Err(ErrorObject::owned(
+            FATAL_JSONRPC_ERROR_CODE,
+            format!("misbehaviour is not supported for {}", ClientType::MOVEMENT),
+            None::<()>,
+        ))
+    }
 }
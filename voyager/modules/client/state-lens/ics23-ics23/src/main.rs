@@ -201,6 +201,23 @@ impl ClientModuleServer for Module {
         })?;
         Ok(encode_merkle_proof_for_evm(proof).into())
     }
+
+    #[instrument(skip_all)]
+    async fn encode_misbehaviour(
+        &self,
+        _: &Extensions,
+        _header_a: Bytes,
+        _header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        Err(ErrorObject::owned(
+            FATAL_JSONRPC_ERROR_CODE,
+            format!(
+                "misbehaviour is not supported for {}",
+                ClientType::STATE_LENS_ICS23_ICS23
+            ),
+            None::<()>,
+        ))
+    }
 }
 
 fn encode_merkle_proof_for_evm(
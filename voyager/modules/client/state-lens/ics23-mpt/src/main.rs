@@ -273,4 +273,21 @@ impl ClientModuleServer for Module {
             SupportedIbcInterface::IbcMoveAptos => Ok(proof.encode_as::<Bcs>().into()),
         }
     }
+
+    #[instrument(skip_all)]
+    async fn encode_misbehaviour(
+        &self,
+        _: &Extensions,
+        _header_a: Bytes,
+        _header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        Err(ErrorObject::owned(
+            FATAL_JSONRPC_ERROR_CODE,
+            format!(
+                "misbehaviour is not supported for {}",
+                ClientType::STATE_LENS_ICS23_MPT
+            ),
+            None::<()>,
+        ))
+    }
 }
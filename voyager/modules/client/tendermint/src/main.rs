@@ -6,7 +6,7 @@ use jsonrpsee::{
 use macros::model;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tendermint_light_client_types::{ClientState, ConsensusState, Header};
+use tendermint_light_client_types::{ClientState, ConsensusState, Header, Misbehaviour};
 use tracing::{debug, instrument};
 use unionlabs::{
     self,
@@ -115,6 +115,29 @@ impl Module {
         }
     }
 
+    pub fn decode_header(&self, header: &[u8]) -> RpcResult<Header> {
+        match self.ibc_interface {
+            SupportedIbcInterface::IbcGoV8Native => <Any<Header>>::decode_as::<Proto>(header)
+                .map_err(|err| {
+                    ErrorObject::owned(
+                        FATAL_JSONRPC_ERROR_CODE,
+                        format!("unable to decode header: {}", ErrorReporter(err)),
+                        None::<()>,
+                    )
+                })
+                .map(|any| any.0),
+            SupportedIbcInterface::IbcCosmwasm => {
+                Header::decode_as::<Bincode>(header).map_err(|err| {
+                    ErrorObject::owned(
+                        FATAL_JSONRPC_ERROR_CODE,
+                        format!("unable to decode header: {err}"),
+                        None::<()>,
+                    )
+                })
+            }
+        }
+    }
+
     pub fn decode_client_state(&self, client_state: &[u8]) -> RpcResult<ClientState> {
         match self.ibc_interface {
             SupportedIbcInterface::IbcGoV8Native => {
@@ -271,4 +294,22 @@ impl ClientModuleServer for Module {
                 SupportedIbcInterface::IbcCosmwasm => cs.encode_as::<Bincode>().into(),
             })
     }
+
+    #[instrument(skip_all)]
+    async fn encode_misbehaviour(
+        &self,
+        _: &Extensions,
+        header_a: Bytes,
+        header_b: Bytes,
+    ) -> RpcResult<Bytes> {
+        let misbehaviour = Misbehaviour {
+            header_a: self.decode_header(&header_a)?,
+            header_b: self.decode_header(&header_b)?,
+        };
+
+        Ok(match self.ibc_interface {
+            SupportedIbcInterface::IbcGoV8Native => Any(misbehaviour).encode_as::<Proto>().into(),
+            SupportedIbcInterface::IbcCosmwasm => misbehaviour.encode_as::<Bincode>().into(),
+        })
+    }
 }
@@ -0,0 +1,153 @@
+use std::fmt::Debug;
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::BlockTransactionsKind,
+    transports::BoxTransport,
+};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use scroll_api::ScrollClient;
+use scroll_client::RollupConfig;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use unionlabs::{ibc::core::client::height::Height, ErrorReporter};
+use voyager_message::{
+    core::{ChainId, ConsensusType, Timestamp},
+    module::{ConsensusModuleInfo, ConsensusModuleServer},
+    ConsensusModule, ExtensionsExt, VoyagerClient,
+};
+use voyager_vm::BoxDynError;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub l1_chain_id: ChainId,
+    pub l2_chain_id: ChainId,
+
+    pub rollup: RollupConfig,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+
+    pub scroll_api: ScrollClient,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub l1_chain_id: ChainId,
+
+    /// The rollup contract on the L1.
+    pub rollup: RollupConfig,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The RPC endpoint for the L2 chain.
+    pub rpc_url: String,
+    /// The endpoint of the scroll api, used to map finalized batches to L2 heights.
+    pub scroll_api_url: String,
+}
+
+impl ConsensusModule for Module {
+    type Config = Config;
+
+    async fn new(config: Self::Config, info: ConsensusModuleInfo) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+
+        let l2_provider = ProviderBuilder::new().on_builtin(&config.rpc_url).await?;
+
+        let l1_chain_id = ChainId::new(l1_provider.get_chain_id().await?.to_string());
+
+        if l1_chain_id != config.l1_chain_id {
+            return Err(format!(
+                "incorrect l1 chain id: expected `{}`, but found `{}`",
+                config.l1_chain_id, l1_chain_id
+            )
+            .into());
+        }
+
+        let l2_chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        info.ensure_chain_id(l2_chain_id.as_str())?;
+        info.ensure_consensus_type(ConsensusType::SCROLL)?;
+
+        Ok(Self {
+            l1_chain_id,
+            l2_chain_id,
+            rollup: config.rollup,
+            l1_provider,
+            l2_provider,
+            scroll_api: ScrollClient::new(config.scroll_api_url),
+        })
+    }
+}
+
+#[async_trait]
+impl ConsensusModuleServer for Module {
+    /// Query the latest height of this chain.
+    ///
+    /// The scroll client is updated to L1 heights, so this is the latest height of the L1; the
+    /// state of the L2 at this height is the state of the last batch finalized on the L1 at this
+    /// height.
+    #[instrument(skip_all, fields(chain_id = %self.l2_chain_id, finalized))]
+    async fn query_latest_height(&self, ext: &Extensions, finalized: bool) -> RpcResult<Height> {
+        let voy_client = ext.try_get::<VoyagerClient>()?;
+
+        voy_client
+            .query_latest_height(self.l1_chain_id.clone(), finalized)
+            .await
+    }
+
+    /// Query the latest timestamp of this chain, i.e. the timestamp of the last block of the latest
+    /// batch finalized on the L1.
+    #[instrument(skip_all, fields(chain_id = %self.l2_chain_id, finalized))]
+    async fn query_latest_timestamp(
+        &self,
+        ext: &Extensions,
+        finalized: bool,
+    ) -> RpcResult<Timestamp> {
+        let latest_height = self.query_latest_height(ext, finalized).await?;
+
+        let l2_height = scroll_client::settled_l2_height(
+            &self.rollup,
+            &self.l1_provider,
+            &self.scroll_api,
+            latest_height.height(),
+        )
+        .await
+        .map_err(|err| {
+            ErrorObject::owned(
+                -1,
+                ErrorReporter(err).with_message("error fetching settled l2 height"),
+                None::<()>,
+            )
+        })?;
+
+        let l2_block = self
+            .l2_provider
+            .get_block_by_number(l2_height.into(), BlockTransactionsKind::Hashes)
+            .await
+            .map_err(|err| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(err).with_message("error fetching l2 block"),
+                    None::<()>,
+                )
+            })?
+            .ok_or_else(|| {
+                ErrorObject::owned(-1, format!("l2 block {l2_height} not found"), None::<()>)
+            })?;
+
+        Ok(Timestamp::from_secs(l2_block.header.timestamp))
+    }
+}
@@ -0,0 +1,156 @@
+#![warn(clippy::unwrap_used)]
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    transports::BoxTransport,
+};
+use arbitrum_client::RollupConfig;
+use ethereum_light_client_types::StorageProof;
+use ibc_union_spec::{path::StorePath, IbcUnion};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, instrument};
+use unionlabs::{
+    ethereum::ibc_commitment_key,
+    ibc::core::client::height::Height,
+    primitives::{H160, U256},
+    ErrorReporter,
+};
+use voyager_message::{
+    core::ChainId,
+    into_value,
+    module::{ProofModuleInfo, ProofModuleServer},
+    ProofModule,
+};
+use voyager_vm::BoxDynError;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+
+    pub ibc_handler_address: H160,
+
+    pub rollup: RollupConfig,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+
+    /// The rollup contract on the L1.
+    pub rollup: RollupConfig,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The RPC endpoint for the L2 chain.
+    pub rpc_url: String,
+}
+
+impl ProofModule<IbcUnion> for Module {
+    type Config = Config;
+
+    async fn new(config: Self::Config, info: ProofModuleInfo) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+
+        let l2_provider = ProviderBuilder::new().on_builtin(&config.rpc_url).await?;
+
+        let chain_id = l2_provider.get_chain_id().await?;
+
+        info.ensure_chain_id(chain_id.to_string())?;
+
+        Ok(Module {
+            chain_id: ChainId::new(chain_id.to_string()),
+            ibc_handler_address: config.ibc_handler_address,
+            rollup: config.rollup,
+            l1_provider,
+            l2_provider,
+        })
+    }
+}
+
+#[async_trait]
+impl ProofModuleServer<IbcUnion> for Module {
+    /// Query a proof of `path` at the L1 height `at`. The proof is against the IBC handler's storage
+    /// root in the L2 block settled on the L1 at this height, which is what the arbitrum client
+    /// stores in its consensus state.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %at, ?path))]
+    async fn query_ibc_proof(
+        &self,
+        _: &Extensions,
+        at: Height,
+        path: StorePath,
+    ) -> RpcResult<Value> {
+        let location = ibc_commitment_key(path.key());
+
+        let l2_block = arbitrum_client::settled_l2_block(
+            &self.rollup,
+            &self.l1_provider,
+            &self.l2_provider,
+            at.height(),
+        )
+        .await
+        .map_err(|e| {
+            ErrorObject::owned(
+                -1,
+                format!("error fetching settled l2 block: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        })?;
+
+        let execution_height = l2_block.header.number;
+
+        debug!(
+            "querying proof for slot {location} for IBC handler contract {} at l2 height \
+            {execution_height}",
+            self.ibc_handler_address
+        );
+
+        let proof = self
+            .l2_provider
+            .get_proof(
+                self.ibc_handler_address.get().into(),
+                vec![location.to_be_bytes().into()],
+            )
+            .block_id(execution_height.into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    format!("error fetching proof: {}", ErrorReporter(e)),
+                    None::<()>,
+                )
+            })?;
+
+        let proof = match <[_; 1]>::try_from(proof.storage_proof) {
+            Ok([proof]) => proof,
+            Err(invalid) => {
+                panic!("received invalid response from eth_getProof, expected length of 1 but got `{invalid:#?}`");
+            }
+        };
+
+        let proof = StorageProof {
+            key: U256::from_be_bytes(proof.key.as_b256().0),
+            value: U256::from_be_bytes(proof.value.to_be_bytes()),
+            proof: proof.proof.into_iter().map(|bytes| bytes.into()).collect(),
+        };
+
+        Ok(into_value(proof))
+    }
+}
@@ -0,0 +1,141 @@
+#![warn(clippy::unwrap_used)]
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    transports::BoxTransport,
+};
+use ibc_union_spec::{path::StorePath, IbcUnion};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use linea_client::RollupConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, instrument};
+use unionlabs::{
+    ethereum::ibc_commitment_key, ibc::core::client::height::Height, primitives::H160,
+    ErrorReporter,
+};
+use voyager_message::{
+    core::ChainId,
+    into_value,
+    module::{ProofModuleInfo, ProofModuleServer},
+    ProofModule,
+};
+use voyager_vm::BoxDynError;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+
+    pub ibc_handler_address: H160,
+
+    pub rollup: RollupConfig,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+
+    /// The rollup contract on the L1.
+    pub rollup: RollupConfig,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The RPC endpoint for the L2 chain.
+    pub rpc_url: String,
+}
+
+impl ProofModule<IbcUnion> for Module {
+    type Config = Config;
+
+    async fn new(config: Self::Config, info: ProofModuleInfo) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+
+        let l2_provider = ProviderBuilder::new().on_builtin(&config.rpc_url).await?;
+
+        let chain_id = l2_provider.get_chain_id().await?;
+
+        info.ensure_chain_id(chain_id.to_string())?;
+
+        Ok(Module {
+            chain_id: ChainId::new(chain_id.to_string()),
+            ibc_handler_address: config.ibc_handler_address,
+            rollup: config.rollup,
+            l1_provider,
+            l2_provider,
+        })
+    }
+}
+
+#[async_trait]
+impl ProofModuleServer<IbcUnion> for Module {
+    /// Query a proof of `path` at the L1 height `at`. The proof is against the IBC handler's storage
+    /// root in the L2 block finalized on the L1 at this height, which is what the linea client
+    /// stores in its consensus state.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %at, ?path))]
+    async fn query_ibc_proof(
+        &self,
+        _: &Extensions,
+        at: Height,
+        path: StorePath,
+    ) -> RpcResult<Value> {
+        let location = ibc_commitment_key(path.key());
+
+        let execution_height =
+            linea_client::finalized_l2_block_number(&self.rollup, &self.l1_provider, at.height())
+                .await
+                .map_err(|e| {
+                    ErrorObject::owned(
+                        -1,
+                        format!("error fetching finalized l2 height: {}", ErrorReporter(e)),
+                        None::<()>,
+                    )
+                })?;
+
+        debug!(
+            "querying proof for slot {location} for IBC handler contract {} at l2 height \
+            {execution_height}",
+            self.ibc_handler_address
+        );
+
+        let proof = linea_client::get_proof(
+            &self.l2_provider,
+            self.ibc_handler_address,
+            [location],
+            execution_height,
+        )
+        .await
+        .map_err(|e| {
+            ErrorObject::owned(
+                -1,
+                format!("error fetching proof: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        })?;
+
+        // either an inclusion or a non-inclusion proof, depending on whether the slot is set
+        let proof = match <[_; 1]>::try_from(proof.storage_proofs) {
+            Ok([proof]) => proof,
+            Err(invalid) => {
+                panic!("received invalid response from linea_getProof, expected length of 1 but got `{invalid:#?}`");
+            }
+        };
+
+        Ok(into_value(proof))
+    }
+}
@@ -11,7 +11,7 @@ use cometbft_rpc::types::abci::response_query::QueryResponse;
 use dashmap::DashMap;
 use ibc_union_spec::{
     path::StorePath,
-    types::{Channel, Connection},
+    types::{Channel, ChannelOrder, Connection},
     IbcUnion,
 };
 use jsonrpsee::{
@@ -263,6 +263,53 @@ impl Module {
 
         Ok(commitment.flatten())
     }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height, %channel_id))]
+    async fn query_channel_order(
+        &self,
+        height: Height,
+        channel_id: u32,
+    ) -> RpcResult<ChannelOrder> {
+        let ordering = self
+            .query_smart::<_, ChannelOrder>(
+                &ibc_union_msg::query::QueryMsg::GetChannelOrder { channel_id },
+                Some(height),
+            )
+            .await?;
+
+        Ok(ordering.unwrap_or_default())
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height))]
+    async fn query_next_sequence(
+        &self,
+        height: Height,
+        query: ibc_union_msg::query::QueryMsg,
+    ) -> RpcResult<u64> {
+        let sequence = self.query_smart::<_, u64>(&query, Some(height)).await?;
+
+        Ok(sequence.unwrap_or_default())
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height, %channel_id, %sequence))]
+    async fn query_packet_sequence(
+        &self,
+        height: Height,
+        channel_id: u32,
+        sequence: u64,
+    ) -> RpcResult<Option<H256>> {
+        let commitment = self
+            .query_smart::<_, Option<H256>>(
+                &ibc_union_msg::query::QueryMsg::GetPacketSequence {
+                    channel_id,
+                    sequence,
+                },
+                Some(height),
+            )
+            .await?;
+
+        Ok(commitment.flatten())
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -328,6 +375,41 @@ impl StateModuleServer<IbcUnion> for Module {
                 .query_batch_receipts(at, path.channel_id, path.batch_hash)
                 .await
                 .map(into_value),
+            StorePath::ChannelOrder(path) => self
+                .query_channel_order(at, path.channel_id)
+                .await
+                .map(into_value),
+            StorePath::NextSequenceSend(path) => self
+                .query_next_sequence(
+                    at,
+                    ibc_union_msg::query::QueryMsg::GetNextSequenceSend {
+                        channel_id: path.channel_id,
+                    },
+                )
+                .await
+                .map(into_value),
+            StorePath::NextSequenceRecv(path) => self
+                .query_next_sequence(
+                    at,
+                    ibc_union_msg::query::QueryMsg::GetNextSequenceRecv {
+                        channel_id: path.channel_id,
+                    },
+                )
+                .await
+                .map(into_value),
+            StorePath::NextSequenceAck(path) => self
+                .query_next_sequence(
+                    at,
+                    ibc_union_msg::query::QueryMsg::GetNextSequenceAck {
+                        channel_id: path.channel_id,
+                    },
+                )
+                .await
+                .map(into_value),
+            StorePath::PacketSequence(path) => self
+                .query_packet_sequence(at, path.channel_id, path.sequence)
+                .await
+                .map(into_value),
         }
     }
 }
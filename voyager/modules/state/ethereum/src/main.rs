@@ -12,7 +12,7 @@ use ibc_solidity::{
 };
 use ibc_union_spec::{
     path::{BatchPacketsPath, BatchReceiptsPath, StorePath},
-    types::{Channel, Connection},
+    types::{Channel, ChannelOrder, Connection},
     IbcUnion,
 };
 use jsonrpsee::{
@@ -364,6 +364,16 @@ impl StateModuleServer<IbcUnion> for Module {
                 .query_batch_packets(at, path.channel_id, path.batch_hash)
                 .await
                 .map(into_value),
+            // the solidity implementation only supports unordered channels
+            StorePath::ChannelOrder(_) => Ok(into_value(ChannelOrder::Unordered)),
+            StorePath::NextSequenceSend(_)
+            | StorePath::NextSequenceRecv(_)
+            | StorePath::NextSequenceAck(_)
+            | StorePath::PacketSequence(_) => Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                "ordered channels are not supported on ethereum",
+                None::<()>,
+            )),
         }
     }
 
@@ -7,7 +7,7 @@ use aptos_rest_client::{aptos_api_types::Address, error::RestError};
 use aptos_types::state_store::state_value::PersistedStateValueMetadata;
 use ibc_union_spec::{
     path::StorePath,
-    types::{Channel, ChannelState, Connection, ConnectionState},
+    types::{Channel, ChannelOrder, ChannelState, Connection, ConnectionState},
     IbcUnion,
 };
 use jsonrpsee::{
@@ -31,7 +31,7 @@ use voyager_message::{
     core::{ChainId, ClientInfo, ClientType, IbcInterface, Timestamp},
     into_value,
     module::{StateModuleInfo, StateModuleServer},
-    StateModule,
+    StateModule, FATAL_JSONRPC_ERROR_CODE,
 };
 use voyager_vm::BoxDynError;
 
@@ -260,6 +260,18 @@ impl StateModuleServer<IbcUnion> for Module {
                     _ => panic!("not a bool??? {commitment:?}"),
                 })
             }
+            // the move implementation only supports unordered channels
+            StorePath::ChannelOrder(_) => into_value(ChannelOrder::Unordered),
+            StorePath::NextSequenceSend(_)
+            | StorePath::NextSequenceRecv(_)
+            | StorePath::NextSequenceAck(_)
+            | StorePath::PacketSequence(_) => {
+                return Err(ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    "ordered channels are not supported on movement",
+                    None::<()>,
+                ))
+            }
         })
     }
 }
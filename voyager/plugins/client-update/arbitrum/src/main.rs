@@ -0,0 +1,468 @@
+use std::{collections::VecDeque, fmt::Debug};
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::{Block, EIP1186StorageProof},
+    transports::BoxTransport,
+};
+use arbitrum_client::RollupConfig;
+use arbitrum_light_client_types::{Header, L2Header};
+use arbitrum_verifier::nodes_confirm_data_mapping_key;
+use ethereum_light_client_types::{AccountProof, StorageProof};
+use ibc_union_spec::IbcUnion;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::{
+    ibc::core::client::height::Height,
+    primitives::{H160, H256, U256},
+    ErrorReporter,
+};
+use voyager_message::{
+    call::{Call, FetchUpdateHeaders, WaitForTrustedHeight},
+    callback::AggregateMsgUpdateClientsFromOrderedHeaders,
+    core::{ChainId, ClientType, IbcSpec, QueryHeight},
+    data::{Data, DecodedHeaderMeta, OrderedHeaders},
+    hook::UpdateHook,
+    into_value,
+    module::{PluginInfo, PluginServer},
+    DefaultCmd, ExtensionsExt, Plugin, PluginMessage, RawClientId, VoyagerClient, VoyagerMessage,
+    FATAL_JSONRPC_ERROR_CODE,
+};
+use voyager_vm::{call, conc, data, pass::PassResult, promise, seq, BoxDynError, Op, Visit};
+
+use crate::{
+    call::{FetchUpdate, FetchUpdateAfterL1Update, ModuleCall},
+    callback::ModuleCallback,
+};
+
+pub mod call;
+pub mod callback;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub l1_client_id: u32,
+    pub l1_chain_id: ChainId,
+    pub l2_chain_id: ChainId,
+    pub ibc_handler_address: H160,
+    pub rollup: RollupConfig,
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The id of the L1 client on the counterparty that the arbitrum client is verified against.
+    pub l1_client_id: u32,
+    pub l1_chain_id: ChainId,
+    pub l2_chain_id: ChainId,
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+    /// The rollup contract on the L1.
+    pub rollup: RollupConfig,
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The RPC endpoint for the L2 chain.
+    pub rpc_url: String,
+}
+
+impl Plugin for Module {
+    type Call = ModuleCall;
+    type Callback = ModuleCallback;
+
+    type Config = Config;
+    type Cmd = DefaultCmd;
+
+    async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+
+        let l2_provider = ProviderBuilder::new().on_builtin(&config.rpc_url).await?;
+
+        let l1_chain_id = ChainId::new(l1_provider.get_chain_id().await?.to_string());
+
+        if l1_chain_id != config.l1_chain_id {
+            return Err(format!(
+                "incorrect l1 chain id: expected `{}`, but found `{}`",
+                config.l1_chain_id, l1_chain_id
+            )
+            .into());
+        }
+
+        let l2_chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        if l2_chain_id != config.l2_chain_id {
+            return Err(format!(
+                "incorrect chain id: expected `{}`, but found `{}`",
+                config.l2_chain_id, l2_chain_id
+            )
+            .into());
+        }
+
+        Ok(Self {
+            l1_client_id: config.l1_client_id,
+            l1_chain_id: config.l1_chain_id,
+            l2_chain_id: config.l2_chain_id,
+            ibc_handler_address: config.ibc_handler_address,
+            rollup: config.rollup,
+            l1_provider,
+            l2_provider,
+        })
+    }
+
+    fn info(config: Self::Config) -> PluginInfo {
+        PluginInfo {
+            name: plugin_name(&config.l2_chain_id),
+            interest_filter: UpdateHook::filter(
+                &config.l2_chain_id,
+                &ClientType::new(ClientType::ARBITRUM),
+            ),
+        }
+    }
+
+    async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
+        match cmd {}
+    }
+}
+
+fn plugin_name(chain_id: &ChainId) -> String {
+    pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
+
+    format!("{PLUGIN_NAME}/{}", chain_id)
+}
+
+impl Module {
+    fn plugin_name(&self) -> String {
+        plugin_name(&self.l2_chain_id)
+    }
+
+    /// Build the header that updates the arbitrum client to `l1_height`. The L1 client on the
+    /// counterparty must have a consensus state at `l1_height`.
+    #[instrument(skip_all, fields(%l1_height))]
+    pub async fn fetch_header(&self, l1_height: Height) -> RpcResult<Header> {
+        let next_node_num =
+            arbitrum_client::next_node_num(&self.rollup, &self.l1_provider, l1_height.height())
+                .await
+                .map_err(|e| {
+                    ErrorObject::owned(
+                        -1,
+                        ErrorReporter(e).with_message("error fetching next node num"),
+                        None::<()>,
+                    )
+                })?;
+
+        let nodes_confirm_data_slot = nodes_confirm_data_mapping_key(
+            self.rollup.l1_nodes_slot,
+            next_node_num,
+            self.rollup.l1_nodes_confirm_data_offset,
+        );
+
+        let l1_proof = self
+            .l1_provider
+            .get_proof(
+                self.rollup.l1_contract_address.into(),
+                vec![
+                    self.rollup.l1_next_node_num_slot.to_be_bytes().into(),
+                    nodes_confirm_data_slot.to_be_bytes().into(),
+                ],
+            )
+            .block_id(l1_height.height().into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching rollup contract proof"),
+                    None::<()>,
+                )
+            })?;
+
+        let [l1_next_node_num_slot_proof, l1_nodes_slot_proof] = match <[_; 2]>::try_from(
+            l1_proof.storage_proof,
+        ) {
+            Ok(proofs) => proofs.map(storage_proof),
+            Err(invalid) => {
+                panic!("received invalid response from eth_getProof, expected length of 2 but got `{invalid:#?}`");
+            }
+        };
+
+        let l2_block = arbitrum_client::settled_l2_block(
+            &self.rollup,
+            &self.l1_provider,
+            &self.l2_provider,
+            l1_height.height(),
+        )
+        .await
+        .map_err(|e| {
+            ErrorObject::owned(
+                -1,
+                ErrorReporter(e).with_message("error fetching settled l2 block"),
+                None::<()>,
+            )
+        })?;
+
+        debug!(
+            "l1 height {l1_height} is l2 height {}",
+            l2_block.header.number
+        );
+
+        let l2_ibc_account_proof = self
+            .fetch_l2_ibc_account_proof(l2_block.header.number)
+            .await?;
+
+        Ok(Header {
+            l1_height,
+            l1_account_proof: AccountProof {
+                storage_root: l1_proof.storage_hash.into(),
+                proof: l1_proof
+                    .account_proof
+                    .into_iter()
+                    .map(|x| x.to_vec())
+                    .collect(),
+            },
+            l2_ibc_account_proof,
+            l1_next_node_num_slot_proof,
+            l1_nodes_slot_proof,
+            l2_header: l2_header(&l2_block)?,
+        })
+    }
+
+    // TODO: deduplicate with eth client update module?
+    pub async fn fetch_l2_ibc_account_proof(&self, block_number: u64) -> RpcResult<AccountProof> {
+        let account_update = self
+            .l2_provider
+            .get_proof(self.ibc_handler_address.into(), vec![])
+            .block_id(block_number.into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching account update"),
+                    None::<()>,
+                )
+            })?;
+
+        Ok(AccountProof {
+            storage_root: account_update.storage_hash.into(),
+            proof: account_update
+                .account_proof
+                .into_iter()
+                .map(|x| x.to_vec())
+                .collect(),
+        })
+    }
+}
+
+fn storage_proof(proof: EIP1186StorageProof) -> StorageProof {
+    StorageProof {
+        key: U256::from_be_bytes(proof.key.as_b256().0),
+        value: U256::from_be_bytes(proof.value.to_be_bytes()),
+        proof: proof.proof.into_iter().map(|bytes| bytes.into()).collect(),
+    }
+}
+
+fn l2_header(block: &Block) -> RpcResult<L2Header> {
+    let header = &block.header;
+
+    Ok(L2Header {
+        parent_hash: header.parent_hash.into(),
+        sha3_uncles: header.ommers_hash.into(),
+        miner: header.beneficiary.into(),
+        state_root: header.state_root.into(),
+        transactions_root: header.transactions_root.into(),
+        receipts_root: header.receipts_root.into(),
+        logs_bloom: Box::new(header.logs_bloom.0.into()),
+        difficulty: U256::from_be_bytes(header.difficulty.to_be_bytes()),
+        number: header.number.into(),
+        gas_limit: header.gas_limit,
+        gas_used: header.gas_used,
+        timestamp: header.timestamp,
+        // arbitrum stores the send root in the extra data
+        extra_data: H256::try_from(&*header.extra_data).map_err(|e| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!(
+                    "invalid extra data in l2 block {}, expected 32 bytes: {}",
+                    header.number,
+                    ErrorReporter(e)
+                ),
+                None::<()>,
+            )
+        })?,
+        mix_hash: header.mix_hash.into(),
+        nonce: header.nonce.into(),
+        base_fee_per_gas: header.base_fee_per_gas.unwrap_or_default().into(),
+    })
+}
+
+#[async_trait]
+impl PluginServer<ModuleCall, ModuleCallback> for Module {
+    #[instrument(skip_all, fields(chain_id = %self.l2_chain_id))]
+    async fn run_pass(
+        &self,
+        _: &Extensions,
+        msgs: Vec<Op<VoyagerMessage>>,
+    ) -> RpcResult<PassResult<VoyagerMessage>> {
+        Ok(PassResult {
+            optimize_further: vec![],
+            ready: msgs
+                .into_iter()
+                .map(|mut op| {
+                    UpdateHook::new(
+                        &self.l2_chain_id,
+                        &ClientType::new(ClientType::ARBITRUM),
+                        |fetch| {
+                            Call::Plugin(PluginMessage::new(
+                                self.plugin_name(),
+                                ModuleCall::from(FetchUpdate {
+                                    counterparty_chain_id: fetch.counterparty_chain_id.clone(),
+                                    update_from: fetch.update_from,
+                                    update_to: fetch.update_to,
+                                }),
+                            ))
+                        },
+                    )
+                    .visit_op(&mut op);
+
+                    op
+                })
+                .enumerate()
+                .map(|(i, op)| (vec![i], op))
+                .collect(),
+        })
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.l2_chain_id))]
+    async fn call(&self, ext: &Extensions, msg: ModuleCall) -> RpcResult<Op<VoyagerMessage>> {
+        match msg {
+            ModuleCall::FetchUpdate(FetchUpdate {
+                counterparty_chain_id,
+                update_from,
+                update_to,
+            }) => {
+                let voyager_client = ext.try_get::<VoyagerClient>()?;
+
+                // the client on the counterparty that is tracking the L1
+                let l1_client_meta = voyager_client
+                    .client_meta::<IbcUnion>(
+                        counterparty_chain_id.clone(),
+                        QueryHeight::Latest,
+                        self.l1_client_id,
+                    )
+                    .await?;
+
+                debug!(?l1_client_meta);
+
+                let continuation = call(PluginMessage::new(
+                    self.plugin_name(),
+                    ModuleCall::from(FetchUpdateAfterL1Update {
+                        counterparty_chain_id: counterparty_chain_id.clone(),
+                        update_from,
+                        update_to,
+                    }),
+                ));
+
+                // if the L1 client is already past update_to, the L2 can be updated directly.
+                if l1_client_meta.counterparty_height >= update_to {
+                    return Ok(continuation);
+                }
+
+                let l1_client_type = voyager_client
+                    .client_info::<IbcUnion>(counterparty_chain_id.clone(), self.l1_client_id)
+                    .await?
+                    .client_type;
+
+                // Recursively dispatch a L1 update before dispatching the L2 update.
+                Ok(conc([
+                    promise(
+                        [call(FetchUpdateHeaders {
+                            client_type: l1_client_type,
+                            chain_id: self.l1_chain_id.clone(),
+                            counterparty_chain_id: counterparty_chain_id.clone(),
+                            client_id: RawClientId::new(self.l1_client_id),
+                            update_from: l1_client_meta.counterparty_height,
+                            update_to,
+                        })],
+                        [],
+                        AggregateMsgUpdateClientsFromOrderedHeaders {
+                            ibc_spec_id: IbcUnion::ID,
+                            chain_id: counterparty_chain_id.clone(),
+                            client_id: RawClientId::new(self.l1_client_id),
+                        },
+                    ),
+                    seq([
+                        call(WaitForTrustedHeight {
+                            chain_id: counterparty_chain_id,
+                            ibc_spec_id: IbcUnion::ID,
+                            // TODO: abstract away the L1 client id and read it from
+                            // the L2 client state (l1_client_id) on the
+                            // `counterparty_chain_id`
+                            client_id: RawClientId::new(self.l1_client_id),
+                            height: update_to,
+                            finalized: true,
+                        }),
+                        continuation,
+                    ]),
+                ]))
+            }
+            ModuleCall::FetchUpdateAfterL1Update(FetchUpdateAfterL1Update {
+                counterparty_chain_id,
+                update_from: _,
+                update_to,
+            }) => {
+                let voyager_client = ext.try_get::<VoyagerClient>()?;
+
+                let l1_client_meta = voyager_client
+                    .client_meta::<IbcUnion>(
+                        counterparty_chain_id,
+                        QueryHeight::Latest,
+                        self.l1_client_id,
+                    )
+                    .await?;
+
+                debug!(?l1_client_meta);
+
+                // the L1 client may have been updated past update_to, and the header must be
+                // verified against an L1 height that the L1 client has a consensus state for
+                let l1_height = l1_client_meta.counterparty_height;
+
+                if l1_height < update_to {
+                    return Err(ErrorObject::owned(
+                        -1,
+                        format!(
+                            "l1 client {} is at height {l1_height} but update_to is {update_to}",
+                            self.l1_client_id
+                        ),
+                        None::<()>,
+                    ));
+                }
+
+                let header = self.fetch_header(l1_height).await?;
+
+                Ok(data(OrderedHeaders {
+                    headers: vec![(DecodedHeaderMeta { height: l1_height }, into_value(header))],
+                }))
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.l2_chain_id))]
+    async fn callback(
+        &self,
+        _: &Extensions,
+        callback: ModuleCallback,
+        _data: VecDeque<Data>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        match callback {}
+    }
+}
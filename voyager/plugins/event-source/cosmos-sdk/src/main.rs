@@ -1507,6 +1507,130 @@ impl Module {
                     event: into_value::<ibc_union_spec::event::FullEvent>(event),
                 }))
             }
+            IbcEvent::WasmChannelCloseInit {
+                port_id,
+                channel_id,
+                counterparty_port_id,
+                counterparty_channel_id,
+            } => {
+                let channel = voyager_client
+                    .query_ibc_state(
+                        self.chain_id.clone(),
+                        QueryHeight::Specific(height),
+                        ibc_union_spec::path::ChannelPath { channel_id },
+                    )
+                    .await?
+                    .state
+                    .unwrap();
+
+                let connection = voyager_client
+                    .query_ibc_state(
+                        self.chain_id.clone(),
+                        QueryHeight::Specific(height),
+                        ibc_union_spec::path::ConnectionPath {
+                            connection_id: channel.connection_id,
+                        },
+                    )
+                    .await?
+                    .state
+                    .unwrap();
+
+                let client_info = voyager_client
+                    .client_info::<IbcUnion>(self.chain_id.clone(), connection.client_id)
+                    .await?;
+
+                let client_meta = voyager_client
+                    .client_meta::<IbcUnion>(
+                        self.chain_id.clone(),
+                        height.into(),
+                        connection.client_id,
+                    )
+                    .await?;
+
+                let event = ibc_union_spec::event::ChannelCloseInit {
+                    port_id: port_id.to_string().into_bytes().into(),
+                    channel_id,
+                    counterparty_port_id: counterparty_port_id.into_encoding(),
+                    counterparty_channel_id,
+                    connection,
+                    version: channel.version,
+                }
+                .into();
+
+                ibc_union_spec::log_event(&event, &self.chain_id);
+
+                Ok(data(ChainEvent {
+                    chain_id: self.chain_id.clone(),
+                    client_info,
+                    counterparty_chain_id: client_meta.chain_id,
+                    tx_hash,
+                    provable_height,
+                    ibc_spec_id: IbcUnion::ID,
+                    event: into_value::<ibc_union_spec::event::FullEvent>(event),
+                }))
+            }
+            IbcEvent::WasmChannelCloseConfirm {
+                port_id,
+                channel_id,
+                counterparty_port_id,
+                counterparty_channel_id,
+            } => {
+                let channel = voyager_client
+                    .query_ibc_state(
+                        self.chain_id.clone(),
+                        QueryHeight::Specific(height),
+                        ibc_union_spec::path::ChannelPath { channel_id },
+                    )
+                    .await?
+                    .state
+                    .unwrap();
+
+                let connection = voyager_client
+                    .query_ibc_state(
+                        self.chain_id.clone(),
+                        QueryHeight::Specific(height),
+                        ibc_union_spec::path::ConnectionPath {
+                            connection_id: channel.connection_id,
+                        },
+                    )
+                    .await?
+                    .state
+                    .unwrap();
+
+                let client_info = voyager_client
+                    .client_info::<IbcUnion>(self.chain_id.clone(), connection.client_id)
+                    .await?;
+
+                let client_meta = voyager_client
+                    .client_meta::<IbcUnion>(
+                        self.chain_id.clone(),
+                        height.into(),
+                        connection.client_id,
+                    )
+                    .await?;
+
+                let event = ibc_union_spec::event::ChannelCloseConfirm {
+                    port_id: port_id.to_string().into_bytes().into(),
+                    channel_id,
+                    counterparty_port_id: counterparty_port_id.into_encoding(),
+                    counterparty_channel_id,
+                    connection,
+                    version: channel.version,
+                }
+                .into();
+
+                ibc_union_spec::log_event(&event, &self.chain_id);
+
+                Ok(data(ChainEvent {
+                    chain_id: self.chain_id.clone(),
+                    client_info,
+                    counterparty_chain_id: client_meta.chain_id,
+                    tx_hash,
+                    provable_height,
+                    ibc_spec_id: IbcUnion::ID,
+                    event: into_value::<ibc_union_spec::event::FullEvent>(event),
+                }))
+            }
             IbcEvent::WasmPacketSend { packet } => {
                 let source_channel = voyager_client
                     .query_ibc_state(
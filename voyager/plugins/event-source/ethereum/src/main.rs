@@ -12,10 +12,10 @@ use beacon_api::client::BeaconApiClient;
 use ibc_solidity::Ibc;
 use ibc_union_spec::{
     event::{
-        ChannelMetadata, ChannelOpenAck, ChannelOpenConfirm, ChannelOpenInit, ChannelOpenTry,
-        ConnectionMetadata, ConnectionOpenAck, ConnectionOpenConfirm, ConnectionOpenInit,
-        ConnectionOpenTry, CreateClient, FullEvent, PacketAck, PacketMetadata, PacketRecv,
-        PacketSend, PacketTimeout, UpdateClient, WriteAck,
+        ChannelCloseConfirm, ChannelCloseInit, ChannelMetadata, ChannelOpenAck, ChannelOpenConfirm,
+        ChannelOpenInit, ChannelOpenTry, ConnectionMetadata, ConnectionOpenAck,
+        ConnectionOpenConfirm, ConnectionOpenInit, ConnectionOpenTry, CreateClient, FullEvent,
+        PacketAck, PacketMetadata, PacketRecv, PacketSend, PacketTimeout, UpdateClient, WriteAck,
     },
     path::{ChannelPath, ConnectionPath},
     IbcUnion,
@@ -43,11 +43,13 @@ use voyager_vm::{call, conc, data, defer, noop, now, pass::PassResult, seq, BoxD
 use crate::{
     call::{FetchGetLogs, IbcEvents, MakeFullEvent, ModuleCall},
     callback::ModuleCallback,
+    settlement::{Settlement, SettlementConfig},
 };
 
 pub mod call;
 pub mod callback;
 pub mod data;
+pub mod settlement;
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() {
@@ -62,6 +64,8 @@ pub struct Module {
 
     pub provider: RootProvider<BoxTransport>,
     pub beacon_api_client: BeaconApiClient,
+
+    pub settlement: Option<Settlement>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +81,12 @@ pub struct Config {
     pub rpc_url: String,
     /// The RPC endpoint for the beacon chain.
     pub beacon_rpc_url: String,
+
+    /// Set if this chain is an L2 whose light client is updated to the heights of its L1. Blocks
+    /// are then only fetched once they are settled on the L1, and events are provable at the L1
+    /// height their block was settled at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settlement: Option<SettlementConfig>,
 }
 
 impl Plugin for Module {
@@ -127,9 +137,69 @@ impl Module {
             ibc_handler_address: config.ibc_handler_address,
             provider,
             beacon_api_client: BeaconApiClient::new(config.beacon_rpc_url).await?,
+            settlement: match config.settlement {
+                Some(settlement) => Some(Settlement::new(settlement).await?),
+                None => None,
+            },
         })
     }
 
+    /// The latest finalized height of this chain. For settled L2s, this is the latest L2 height
+    /// settled on the L1 at the latest finalized L1 height.
+    async fn latest_finalized_height(&self, voyager_client: &VoyagerClient) -> RpcResult<u64> {
+        let latest_height = voyager_client
+            .query_latest_height(self.chain_id.clone(), true)
+            .await?;
+
+        match &self.settlement {
+            Some(settlement) => settlement
+                .settled_height(&self.provider, latest_height.height())
+                .await
+                .map_err(|e| {
+                    ErrorObject::owned(
+                        -1,
+                        format!(
+                            "error fetching the settled height at {latest_height}: {}",
+                            ErrorReporter(&*e)
+                        ),
+                        None::<()>,
+                    )
+                }),
+            None => Ok(latest_height.height()),
+        }
+    }
+
+    /// The height that an event emitted in `block_number` can be proven at, or `None` if the block
+    /// is not yet settled on the L1.
+    async fn provable_height(
+        &self,
+        block_number: u64,
+        voyager_client: &VoyagerClient,
+    ) -> RpcResult<Option<Height>> {
+        let Some(settlement) = &self.settlement else {
+            return Ok(Some(Height::new(block_number)));
+        };
+
+        let latest_height = voyager_client
+            .query_latest_height(self.chain_id.clone(), true)
+            .await?;
+
+        settlement
+            .settling_height(&self.provider, block_number, latest_height.height())
+            .await
+            .map(|height| height.map(Height::new))
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    format!(
+                        "error fetching the settling height of {block_number}: {}",
+                        ErrorReporter(&*e)
+                    ),
+                    None::<()>,
+                )
+            })
+    }
+
     async fn make_packet_metadata(
         &self,
         event_height: Height,
@@ -265,9 +335,26 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                 tx_hash,
                 event,
             }) => {
-                let provable_height = Height::new(block_number);
                 let voyager_client = e.try_get::<VoyagerClient>()?;
 
+                let Some(provable_height) =
+                    self.provable_height(block_number, voyager_client).await?
+                else {
+                    debug!(block_number, "block is not yet settled");
+
+                    return Ok(seq([
+                        defer(now() + 1),
+                        call(Call::Plugin(PluginMessage::new(
+                            self.plugin_name(),
+                            ModuleCall::from(MakeFullEvent {
+                                block_number,
+                                tx_hash,
+                                event,
+                            }),
+                        ))),
+                    ]));
+                };
+
                 match event {
                     IbcEvents::CreateClient(raw_event) => {
                         let client_info = voyager_client
@@ -697,10 +784,124 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                         }))
                     }
 
-                    IbcEvents::ChannelCloseInit(_) | IbcEvents::ChannelCloseConfirm(_) => {
-                        warn!("observed channel close message, these are not handled currently");
+                    IbcEvents::ChannelCloseInit(raw_event) => {
+                        let channel_id = raw_event.channel_id;
 
-                        Ok(noop())
+                        let channel = voyager_client
+                            .query_ibc_state(
+                                self.chain_id.clone(),
+                                provable_height.into(),
+                                ChannelPath { channel_id },
+                            )
+                            .await?
+                            .state
+                            .ok_or_else(missing_state("channel must exist", None))?;
+
+                        let connection = voyager_client
+                            .query_ibc_state(
+                                self.chain_id.clone(),
+                                provable_height.into(),
+                                ConnectionPath {
+                                    connection_id: channel.connection_id,
+                                },
+                            )
+                            .await?
+                            .state
+                            .ok_or_else(missing_state("connection must exist", None))?;
+
+                        let client_info = voyager_client
+                            .client_info::<IbcUnion>(self.chain_id.clone(), connection.client_id)
+                            .await?;
+
+                        let client_meta = voyager_client
+                            .client_meta::<IbcUnion>(
+                                self.chain_id.clone(),
+                                provable_height.into(),
+                                connection.client_id,
+                            )
+                            .await?;
+
+                        let event = ChannelCloseInit {
+                            port_id: raw_event.port_id.into(),
+                            channel_id,
+                            counterparty_port_id: raw_event.counterparty_port_id.into(),
+                            counterparty_channel_id: raw_event.counterparty_channel_id,
+                            connection,
+                            version: channel.version,
+                        }
+                        .into();
+
+                        ibc_union_spec::log_event(&event, &self.chain_id);
+
+                        Ok(data(ChainEvent {
+                            chain_id: self.chain_id.clone(),
+                            client_info,
+                            counterparty_chain_id: client_meta.chain_id,
+                            tx_hash,
+                            provable_height,
+                            ibc_spec_id: IbcUnion::ID,
+                            event: into_value::<FullEvent>(event),
+                        }))
+                    }
+
+                    IbcEvents::ChannelCloseConfirm(raw_event) => {
+                        let channel_id = raw_event.channel_id;
+
+                        let channel = voyager_client
+                            .query_ibc_state(
+                                self.chain_id.clone(),
+                                provable_height.into(),
+                                ChannelPath { channel_id },
+                            )
+                            .await?
+                            .state
+                            .ok_or_else(missing_state("channel must exist", None))?;
+
+                        let connection = voyager_client
+                            .query_ibc_state(
+                                self.chain_id.clone(),
+                                provable_height.into(),
+                                ConnectionPath {
+                                    connection_id: channel.connection_id,
+                                },
+                            )
+                            .await?
+                            .state
+                            .ok_or_else(missing_state("connection must exist", None))?;
+
+                        let client_info = voyager_client
+                            .client_info::<IbcUnion>(self.chain_id.clone(), connection.client_id)
+                            .await?;
+
+                        let client_meta = voyager_client
+                            .client_meta::<IbcUnion>(
+                                self.chain_id.clone(),
+                                provable_height.into(),
+                                connection.client_id,
+                            )
+                            .await?;
+
+                        let event = ChannelCloseConfirm {
+                            port_id: raw_event.port_id.into(),
+                            channel_id,
+                            counterparty_port_id: raw_event.counterparty_port_id.into(),
+                            counterparty_channel_id: raw_event.counterparty_channel_id,
+                            connection,
+                            version: channel.version,
+                        }
+                        .into();
+
+                        ibc_union_spec::log_event(&event, &self.chain_id);
+
+                        Ok(data(ChainEvent {
+                            chain_id: self.chain_id.clone(),
+                            client_info,
+                            counterparty_chain_id: client_meta.chain_id,
+                            tx_hash,
+                            provable_height,
+                            ibc_spec_id: IbcUnion::ID,
+                            event: into_value::<FullEvent>(event),
+                        }))
                     }
 
                     // packet origin is this chain
@@ -910,12 +1111,11 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                     ));
                 }
 
-                let latest_height = e
-                    .try_get::<VoyagerClient>()?
-                    .query_latest_height(self.chain_id.clone(), true)
+                let latest_height = self
+                    .latest_finalized_height(e.try_get::<VoyagerClient>()?)
                     .await?;
 
-                if latest_height.height() < block_number {
+                if latest_height < block_number {
                     debug!(block_number, "block is not yet finalized");
 
                     return Ok(seq([
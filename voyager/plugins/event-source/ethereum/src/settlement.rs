@@ -0,0 +1,247 @@
+//! Support for L2s whose light clients are updated to the heights of the L1 they settle on, rather
+//! than to the heights of the L2 itself.
+//!
+//! For such chains, an event emitted in an L2 block can only be proven once that block has been
+//! settled on the L1, and the height it can be proven at is the L1 height it was settled at.
+
+use std::future::Future;
+
+use alloy::{
+    providers::{ProviderBuilder, RootProvider},
+    transports::BoxTransport,
+};
+use scroll_api::ScrollClient;
+use serde::{Deserialize, Serialize};
+use voyager_vm::BoxDynError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SettlementConfig {
+    /// This chain is an arbitrum rollup.
+    Arbitrum {
+        /// The RPC endpoint for the L1 execution chain.
+        l1_rpc_url: String,
+        /// The rollup contract on the L1.
+        rollup: arbitrum_client::RollupConfig,
+    },
+    /// This chain is linea.
+    Linea {
+        /// The RPC endpoint for the L1 execution chain.
+        l1_rpc_url: String,
+        /// The rollup contract on the L1.
+        rollup: linea_client::RollupConfig,
+    },
+    /// This chain is scroll.
+    Scroll {
+        /// The RPC endpoint for the L1 execution chain.
+        l1_rpc_url: String,
+        /// The endpoint of the scroll api, used to map finalized batches to L2 heights.
+        scroll_api_url: String,
+        /// The rollup contract on the L1.
+        rollup: scroll_client::RollupConfig,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum Settlement {
+    Arbitrum {
+        rollup: arbitrum_client::RollupConfig,
+        l1_provider: RootProvider<BoxTransport>,
+    },
+    Linea {
+        rollup: linea_client::RollupConfig,
+        l1_provider: RootProvider<BoxTransport>,
+    },
+    Scroll {
+        rollup: scroll_client::RollupConfig,
+        l1_provider: RootProvider<BoxTransport>,
+        scroll_api: ScrollClient,
+    },
+}
+
+impl Settlement {
+    pub async fn new(config: SettlementConfig) -> Result<Self, BoxDynError> {
+        match config {
+            SettlementConfig::Arbitrum { l1_rpc_url, rollup } => Ok(Self::Arbitrum {
+                rollup,
+                l1_provider: ProviderBuilder::new().on_builtin(&l1_rpc_url).await?,
+            }),
+            SettlementConfig::Linea { l1_rpc_url, rollup } => Ok(Self::Linea {
+                rollup,
+                l1_provider: ProviderBuilder::new().on_builtin(&l1_rpc_url).await?,
+            }),
+            SettlementConfig::Scroll {
+                l1_rpc_url,
+                scroll_api_url,
+                rollup,
+            } => Ok(Self::Scroll {
+                rollup,
+                l1_provider: ProviderBuilder::new().on_builtin(&l1_rpc_url).await?,
+                scroll_api: ScrollClient::new(scroll_api_url),
+            }),
+        }
+    }
+
+    /// The latest L2 height that is settled on the L1 at `l1_height`.
+    pub async fn settled_height(
+        &self,
+        l2_provider: &RootProvider<BoxTransport>,
+        l1_height: u64,
+    ) -> Result<u64, BoxDynError> {
+        match self {
+            Settlement::Arbitrum {
+                rollup,
+                l1_provider,
+            } => Ok(
+                arbitrum_client::settled_l2_block(rollup, l1_provider, l2_provider, l1_height)
+                    .await?
+                    .header
+                    .number,
+            ),
+            Settlement::Linea {
+                rollup,
+                l1_provider,
+            } => Ok(linea_client::finalized_l2_block_number(rollup, l1_provider, l1_height).await?),
+            Settlement::Scroll {
+                rollup,
+                l1_provider,
+                scroll_api,
+            } => Ok(
+                scroll_client::settled_l2_height(rollup, l1_provider, scroll_api, l1_height)
+                    .await?,
+            ),
+        }
+    }
+
+    /// The first L1 height at which `l2_height` is settled, or `None` if it is not yet settled at
+    /// `latest_l1_height`.
+    pub async fn settling_height(
+        &self,
+        l2_provider: &RootProvider<BoxTransport>,
+        l2_height: u64,
+        latest_l1_height: u64,
+    ) -> Result<Option<u64>, BoxDynError> {
+        first_settling_height(l2_height, latest_l1_height, |l1_height| {
+            self.settled_height(l2_provider, l1_height)
+        })
+        .await
+    }
+}
+
+/// Find the lowest L1 height `<= latest_l1_height` at which `settled_at` is at least `l2_height`,
+/// where `settled_at` is the latest L2 height settled at an L1 height, and as such never
+/// decreases.
+///
+/// Recently emitted events are settled at recent L1 heights, so this first steps back from
+/// `latest_l1_height` in exponentially increasing steps to find an L1 height at which `l2_height`
+/// is not yet settled, and then bisects between the two.
+async fn first_settling_height<F, Fut, E>(
+    l2_height: u64,
+    latest_l1_height: u64,
+    mut settled_at: F,
+) -> Result<Option<u64>, E>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<u64, E>>,
+{
+    if settled_at(latest_l1_height).await? < l2_height {
+        return Ok(None);
+    }
+
+    // settled_at(hi) >= l2_height
+    let mut hi = latest_l1_height;
+    let mut step = 1_u64;
+
+    // settled_at(lo) < l2_height
+    let mut lo = loop {
+        if hi == 0 {
+            return Ok(Some(0));
+        }
+
+        let candidate = latest_l1_height.saturating_sub(step);
+
+        if settled_at(candidate).await? < l2_height {
+            break candidate;
+        }
+
+        hi = candidate;
+        step = step.saturating_mul(2);
+    };
+
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+
+        if settled_at(mid).await? < l2_height {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Ok(Some(hi))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, convert::Infallible};
+
+    use super::*;
+
+    /// The L1 heights at which L2 heights were settled, as (l1 height, settled l2 height).
+    const SETTLEMENTS: &[(u64, u64)] = &[(10, 100), (25, 180), (26, 200), (60, 350), (61, 351)];
+
+    fn settled_at(l1_height: u64) -> u64 {
+        SETTLEMENTS
+            .iter()
+            .rev()
+            .find(|(settled_l1_height, _)| *settled_l1_height <= l1_height)
+            .map_or(0, |(_, l2_height)| *l2_height)
+    }
+
+    async fn search(l2_height: u64, latest_l1_height: u64) -> Option<u64> {
+        first_settling_height(l2_height, latest_l1_height, |l1_height| async move {
+            Ok::<_, Infallible>(settled_at(l1_height))
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn finds_first_settling_height() {
+        assert_eq!(search(1, 100).await, Some(10));
+        assert_eq!(search(100, 100).await, Some(10));
+        assert_eq!(search(101, 100).await, Some(25));
+        assert_eq!(search(180, 100).await, Some(25));
+        assert_eq!(search(181, 100).await, Some(26));
+        assert_eq!(search(201, 100).await, Some(60));
+        assert_eq!(search(351, 100).await, Some(61));
+        assert_eq!(search(351, 61).await, Some(61));
+    }
+
+    #[tokio::test]
+    async fn not_yet_settled() {
+        assert_eq!(search(352, 100).await, None);
+        assert_eq!(search(201, 59).await, None);
+    }
+
+    #[tokio::test]
+    async fn settled_at_genesis() {
+        assert_eq!(search(0, 100).await, Some(0));
+        assert_eq!(search(0, 0).await, Some(0));
+    }
+
+    #[tokio::test]
+    async fn steps_back_exponentially() {
+        let calls = Cell::new(0);
+
+        let res = first_settling_height(351, 1_000_000, |l1_height| {
+            calls.set(calls.get() + 1);
+            async move { Ok::<_, Infallible>(settled_at(l1_height)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(res, Some(61));
+        assert!(calls.get() <= 2 * 64, "{} calls", calls.get());
+    }
+}
@@ -15,6 +15,8 @@ pub enum IbcEvent {
     ChannelOpenTry(ibc::ChannelOpenTry),
     ChannelOpenAck(ibc::ChannelOpenAck),
     ChannelOpenConfirm(ibc::ChannelOpenConfirm),
+    ChannelCloseInit(ibc::ChannelCloseInit),
+    ChannelCloseConfirm(ibc::ChannelCloseConfirm),
     WriteAcknowledgement(ibc::WriteAcknowledgement),
     RecvPacket(ibc::RecvPacket),
     SendPacket(ibc::SendPacket),
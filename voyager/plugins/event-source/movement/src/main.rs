@@ -11,10 +11,10 @@ use aptos_rest_client::{
 };
 use ibc_union_spec::{
     event::{
-        ChannelMetadata, ChannelOpenAck, ChannelOpenConfirm, ChannelOpenInit, ChannelOpenTry,
-        ConnectionMetadata, ConnectionOpenAck, ConnectionOpenConfirm, ConnectionOpenInit,
-        ConnectionOpenTry, CreateClient, FullEvent, PacketAck, PacketMetadata, PacketRecv,
-        PacketSend, UpdateClient, WriteAck,
+        ChannelCloseConfirm, ChannelCloseInit, ChannelMetadata, ChannelOpenAck, ChannelOpenConfirm,
+        ChannelOpenInit, ChannelOpenTry, ConnectionMetadata, ConnectionOpenAck,
+        ConnectionOpenConfirm, ConnectionOpenInit, ConnectionOpenTry, CreateClient, FullEvent,
+        PacketAck, PacketMetadata, PacketRecv, PacketSend, UpdateClient, WriteAck,
     },
     path::{ChannelPath, ConnectionPath},
     types::{Connection, ConnectionState},
@@ -337,6 +337,10 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                             "ChannelOpenTry" => from_raw_event::<ibc::ChannelOpenTry>(data),
                             "ChannelOpenAck" => from_raw_event::<ibc::ChannelOpenAck>(data),
                             "ChannelOpenConfirm" => from_raw_event::<ibc::ChannelOpenConfirm>(data),
+                            "ChannelCloseInit" => from_raw_event::<ibc::ChannelCloseInit>(data),
+                            "ChannelCloseConfirm" => {
+                                from_raw_event::<ibc::ChannelCloseConfirm>(data)
+                            }
                             "WriteAcknowledgement" => {
                                 from_raw_event::<ibc::WriteAcknowledgement>(data)
                             }
@@ -606,6 +610,86 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                             client_id,
                         )
                     }
+                    events::IbcEvent::ChannelCloseInit(event) => {
+                        let ledger_version = self.ledger_version_of_height(height).await;
+
+                        let connection = self
+                            .get_connection(
+                                self.ibc_handler_address.into(),
+                                Some(ledger_version),
+                                (event.connection_id,),
+                            )
+                            .await
+                            .unwrap()
+                            .unwrap();
+
+                        let channel = self
+                            .get_channel(
+                                self.ibc_handler_address.into(),
+                                Some(ledger_version),
+                                (event.channel_id,),
+                            )
+                            .await
+                            .unwrap()
+                            .unwrap();
+
+                        let connection = convert_connection(connection);
+
+                        let client_id = connection.client_id;
+
+                        (
+                            ChannelCloseInit {
+                                port_id: event.port_id.parse().unwrap(),
+                                channel_id: event.channel_id,
+                                counterparty_port_id: event.counterparty_port_id.into(),
+                                counterparty_channel_id: event.counterparty_channel_id,
+                                connection,
+                                version: channel.version,
+                            }
+                            .into(),
+                            client_id,
+                        )
+                    }
+                    events::IbcEvent::ChannelCloseConfirm(event) => {
+                        let ledger_version = self.ledger_version_of_height(height).await;
+
+                        let connection = self
+                            .get_connection(
+                                self.ibc_handler_address.into(),
+                                Some(ledger_version),
+                                (event.connection_id,),
+                            )
+                            .await
+                            .unwrap()
+                            .unwrap();
+
+                        let channel = self
+                            .get_channel(
+                                self.ibc_handler_address.into(),
+                                Some(ledger_version),
+                                (event.channel_id,),
+                            )
+                            .await
+                            .unwrap()
+                            .unwrap();
+
+                        let connection = convert_connection(connection);
+
+                        let client_id = connection.client_id;
+
+                        (
+                            ChannelCloseConfirm {
+                                port_id: event.port_id.parse().unwrap(),
+                                channel_id: event.channel_id,
+                                counterparty_port_id: event.counterparty_port_id.into(),
+                                counterparty_channel_id: event.counterparty_channel_id,
+                                connection,
+                                version: channel.version,
+                            }
+                            .into(),
+                            client_id,
+                        )
+                    }
                     events::IbcEvent::WriteAcknowledgement(event) => {
                         let (
                             _counterparty_chain_id,
@@ -0,0 +1,28 @@
+use enumorph::Enumorph;
+use ibc_union_spec::types::ClientId;
+use macros::model;
+use unionlabs::ibc::core::client::height::Height;
+use voyager_message::core::ChainId;
+use voyager_vm::BoxDynError;
+
+#[model]
+#[derive(Enumorph)]
+pub enum ModuleCall {
+    CheckForMisbehaviour(CheckForMisbehaviour),
+}
+
+/// Compare the latest consensus state stored in `client_id` on `chain_id` against the consensus
+/// state reported by the consensus module of the chain the client is tracking.
+#[model]
+#[derive(clap::Args)]
+pub struct CheckForMisbehaviour {
+    #[arg(value_parser(|s: &str| Ok::<_, BoxDynError>(ChainId::new(s.to_owned()))))]
+    pub chain_id: ChainId,
+    pub client_id: ClientId,
+    /// The last trusted height of the client that was verified to match the counterparty chain.
+    ///
+    /// This is used as the trusted height when building evidence for a conflicting update. If it
+    /// is not known, the latest height of the client before the conflicting update is used.
+    #[arg(skip)]
+    pub last_verified_height: Option<Height>,
+}
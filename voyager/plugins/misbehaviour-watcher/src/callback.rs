@@ -0,0 +1,24 @@
+use enumorph::Enumorph;
+use ibc_union_spec::types::ClientId;
+use macros::model;
+use unionlabs::{ibc::core::client::height::Height, primitives::Bytes};
+use voyager_message::core::ChainId;
+
+#[model]
+#[derive(Enumorph)]
+pub enum ModuleCallback {
+    MakeMisbehaviour(MakeMisbehaviour),
+}
+
+/// Given the [`OrderedHeaders`](voyager_message::data::OrderedHeaders) fetched from the last
+/// verified height up to `height`, submit the honest header at `height` along with the conflicting
+/// header as evidence of misbehaviour to `client_id` on `chain_id`.
+#[model]
+pub struct MakeMisbehaviour {
+    pub chain_id: ChainId,
+    pub client_id: ClientId,
+    /// The height at which the on-chain consensus state conflicts with the counterparty chain.
+    pub height: Height,
+    /// The header that the client was updated to `height` with, as submitted to `chain_id`.
+    pub conflicting_header: Bytes,
+}
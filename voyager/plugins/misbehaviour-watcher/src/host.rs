@@ -0,0 +1,414 @@
+//! Lookup of the client updates submitted to the chains hosting the watched clients.
+//!
+//! Evidence of misbehaviour consists of two conflicting headers, one of which is the header that
+//! the client was (mis)updated with. This header only exists in the transaction that updated the
+//! client, so it has to be read back from the host chain.
+
+use std::num::{NonZeroU32, NonZeroU8};
+
+use alloy::{
+    consensus::Transaction as _,
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::Filter,
+    sol_types::{SolCall, SolEvent},
+    transports::BoxTransport,
+};
+use ibc_solidity::Ibc;
+use ibc_union_spec::types::ClientId;
+use itertools::Itertools;
+use prost::{Message, Name};
+use protos::{
+    cosmos::tx::v1beta1::{TxBody, TxRaw},
+    cosmwasm::wasm::v1::MsgExecuteContract,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::{
+    bech32::Bech32,
+    option_unwrap,
+    primitives::{Bytes, H160, H256},
+};
+use voyager_vm::BoxDynError;
+
+/// The maximum number of transactions to inspect for an update of a client to a single height.
+const TX_SEARCH_PER_PAGE: NonZeroU8 = option_unwrap!(NonZeroU8::new(100));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum HostConfig {
+    /// The clients are hosted by the IBC handler contract on an EVM chain.
+    Ethereum {
+        rpc_url: String,
+        ibc_handler_address: H160,
+        /// The maximum range of blocks to fetch logs for in a single request.
+        #[serde(default = "default_max_log_range")]
+        max_log_range: u64,
+        /// How many blocks before the latest height to search for client updates. Updates that
+        /// were included before this are not found, and so their misbehaviour is not reported.
+        #[serde(default = "default_max_lookback")]
+        max_lookback: u64,
+    },
+    /// The clients are hosted by the ibc-union contract on a cosmos-sdk chain.
+    Cosmos {
+        rpc_url: String,
+        ibc_host_contract_address: Bech32<H256>,
+    },
+}
+
+fn default_max_log_range() -> u64 {
+    10_000
+}
+
+fn default_max_lookback() -> u64 {
+    100_000
+}
+
+#[derive(Debug, Clone)]
+pub enum Host {
+    Ethereum {
+        provider: RootProvider<BoxTransport>,
+        ibc_handler_address: H160,
+        max_log_range: u64,
+        max_lookback: u64,
+    },
+    Cosmos {
+        cometbft_client: cometbft_rpc::Client,
+        ibc_host_contract_address: Bech32<H256>,
+    },
+}
+
+/// An update of a client, as submitted to the host chain.
+#[derive(Debug, Clone)]
+pub struct ClientUpdate {
+    /// The height of the host chain that the update was included at.
+    pub height: u64,
+    /// The client message that the client was updated with.
+    pub client_message: Bytes,
+}
+
+impl Host {
+    pub async fn new(config: HostConfig) -> Result<Self, BoxDynError> {
+        match config {
+            HostConfig::Ethereum {
+                rpc_url,
+                ibc_handler_address,
+                max_log_range,
+                max_lookback,
+            } => {
+                if max_log_range == 0 {
+                    return Err("max_log_range must be non-zero".into());
+                }
+
+                Ok(Self::Ethereum {
+                    provider: ProviderBuilder::new().on_builtin(&rpc_url).await?,
+                    ibc_handler_address,
+                    max_log_range,
+                    max_lookback,
+                })
+            }
+            HostConfig::Cosmos {
+                rpc_url,
+                ibc_host_contract_address,
+            } => Ok(Self::Cosmos {
+                cometbft_client: cometbft_rpc::Client::new(rpc_url).await?,
+                ibc_host_contract_address,
+            }),
+        }
+    }
+
+    /// Find the update of `client_id` to `counterparty_height` that was included at or before
+    /// `latest_height` of the host chain, returning the most recent one if there are several.
+    #[instrument(skip_all, fields(%client_id, %counterparty_height, %latest_height))]
+    pub async fn client_update(
+        &self,
+        client_id: ClientId,
+        counterparty_height: u64,
+        latest_height: u64,
+    ) -> Result<Option<ClientUpdate>, BoxDynError> {
+        match self {
+            Host::Ethereum {
+                provider,
+                ibc_handler_address,
+                max_log_range,
+                max_lookback,
+            } => {
+                // the client was most likely updated recently, so search backwards from the latest
+                // height
+                for (from, to) in log_ranges(latest_height, *max_log_range, *max_lookback) {
+                    debug!(%from, %to, "fetching update client logs");
+
+                    let logs = provider
+                        .get_logs(
+                            &Filter::new()
+                                .address(alloy::primitives::Address::from(*ibc_handler_address))
+                                .event_signature(Ibc::UpdateClient::SIGNATURE_HASH)
+                                .from_block(from)
+                                .to_block(to),
+                        )
+                        .await?;
+
+                    for log in logs.into_iter().rev() {
+                        let event = Ibc::UpdateClient::decode_log(&log.inner, true)?.data;
+
+                        if event.client_id != client_id || event.height != counterparty_height {
+                            continue;
+                        }
+
+                        let tx_hash = log
+                            .transaction_hash
+                            .ok_or("update client log has no transaction hash")?;
+                        let height = log
+                            .block_number
+                            .ok_or("update client log has no block number")?;
+
+                        debug!(%tx_hash, %height, "found update client log");
+
+                        let tx = provider
+                            .get_transaction_by_hash(tx_hash)
+                            .await?
+                            .ok_or_else(|| format!("transaction {tx_hash} not found"))?;
+
+                        let client_message = client_message_from_calldata(
+                            tx.inner.input(),
+                            *ibc_handler_address,
+                            client_id,
+                        )
+                        .ok_or_else(|| {
+                            format!(
+                                "unable to find a single update of client {client_id} in \
+                                transaction {tx_hash}"
+                            )
+                        })?;
+
+                        return Ok(Some(ClientUpdate {
+                            height,
+                            client_message,
+                        }));
+                    }
+                }
+
+                debug!(%max_lookback, "no update found within the lookback");
+
+                Ok(None)
+            }
+            Host::Cosmos {
+                cometbft_client,
+                ibc_host_contract_address,
+            } => {
+                let response = cometbft_client
+                    .tx_search(
+                        format!(
+                            "wasm-update_client._contract_address='{ibc_host_contract_address}' \
+                            AND wasm-update_client.client_id='{client_id}' \
+                            AND wasm-update_client.counterparty_height='{counterparty_height}' \
+                            AND tx.height<={latest_height}"
+                        ),
+                        false,
+                        NonZeroU32::MIN,
+                        TX_SEARCH_PER_PAGE,
+                        cometbft_rpc::rpc_types::Order::Desc,
+                    )
+                    .await?;
+
+                let Some(txr) = response.txs.into_iter().next() else {
+                    return Ok(None);
+                };
+
+                let height = txr
+                    .height
+                    .ok_or_else(|| format!("transaction {} has no height", txr.hash))?
+                    .get();
+
+                debug!(tx_hash = %txr.hash, %height, "found update client transaction");
+
+                let client_message =
+                    client_message_from_tx(&txr.tx, ibc_host_contract_address, client_id)?
+                        .ok_or_else(|| {
+                            format!(
+                                "unable to find a single update of client {client_id} in \
+                                transaction {}",
+                                txr.hash
+                            )
+                        })?;
+
+                Ok(Some(ClientUpdate {
+                    height,
+                    client_message,
+                }))
+            }
+        }
+    }
+}
+
+/// The inclusive block ranges to fetch logs for, from the most recent to the oldest. Each range
+/// spans at most `max_log_range` blocks, and the ranges cover the `max_lookback` blocks before
+/// `latest_height` (along with `latest_height` itself).
+fn log_ranges(
+    latest_height: u64,
+    max_log_range: u64,
+    max_lookback: u64,
+) -> impl Iterator<Item = (u64, u64)> {
+    let earliest_height = latest_height.saturating_sub(max_lookback);
+
+    std::iter::successors(
+        Some((
+            latest_height
+                .saturating_sub(max_log_range - 1)
+                .max(earliest_height),
+            latest_height,
+        )),
+        move |&(from, _)| {
+            (from > earliest_height).then(|| {
+                let to = from - 1;
+                (
+                    to.saturating_sub(max_log_range - 1).max(earliest_height),
+                    to,
+                )
+            })
+        },
+    )
+}
+
+/// Extract the client message of the update of `client_id` from the calldata of a transaction
+/// sent either directly to the IBC handler or through the multicall contract.
+fn client_message_from_calldata(
+    input: &[u8],
+    ibc_handler_address: H160,
+    client_id: ClientId,
+) -> Option<Bytes> {
+    let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+
+    match selector {
+        Ibc::updateClientCall::SELECTOR => {
+            let call = Ibc::updateClientCall::abi_decode(input, true).ok()?;
+
+            (call.msg_.client_id == client_id).then(|| call.msg_.client_message.to_vec().into())
+        }
+        multicall::multicallCall::SELECTOR => multicall::multicallCall::abi_decode(input, true)
+            .ok()?
+            .calls
+            .into_iter()
+            .filter(|call| call.target == alloy::primitives::Address::from(ibc_handler_address))
+            .filter_map(|call| {
+                client_message_from_calldata(&call.callData, ibc_handler_address, client_id)
+            })
+            .exactly_one()
+            .ok(),
+        _ => None,
+    }
+}
+
+/// Extract the client message of the update of `client_id` from a raw cosmos-sdk transaction.
+fn client_message_from_tx(
+    tx: &[u8],
+    ibc_host_contract_address: &Bech32<H256>,
+    client_id: ClientId,
+) -> Result<Option<Bytes>, BoxDynError> {
+    let tx = TxRaw::decode(tx)?;
+    let body = TxBody::decode(&*tx.body_bytes)?;
+
+    let ibc_host_contract_address = ibc_host_contract_address.to_string();
+
+    Ok(body
+        .messages
+        .into_iter()
+        .filter(|any| any.type_url == MsgExecuteContract::type_url())
+        .map(|any| MsgExecuteContract::decode(&*any.value))
+        .filter_ok(|msg| msg.contract == ibc_host_contract_address)
+        .map_ok(|msg| serde_json::from_slice::<ibc_union_msg::msg::ExecuteMsg>(&msg.msg).ok())
+        .filter_map_ok(|msg| match msg {
+            Some(ibc_union_msg::msg::ExecuteMsg::UpdateClient(msg))
+                if msg.client_id == client_id =>
+            {
+                Some(msg.client_message)
+            }
+            _ => None,
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .exactly_one()
+        .ok())
+}
+
+mod multicall {
+    alloy::sol! {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        function multicall(Call3[] calldata calls);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IBC_HANDLER: H160 = H160::new([0xaa; 20]);
+
+    fn update_client(client_id: ClientId, client_message: &[u8]) -> Vec<u8> {
+        Ibc::updateClientCall {
+            msg_: ibc_solidity::MsgUpdateClient {
+                client_id,
+                client_message: client_message.to_vec().into(),
+                relayer: Default::default(),
+            },
+        }
+        .abi_encode()
+    }
+
+    #[test]
+    fn log_ranges_are_bounded_by_lookback() {
+        assert_eq!(
+            log_ranges(100, 30, 75).collect::<Vec<_>>(),
+            [(71, 100), (41, 70), (25, 40)]
+        );
+        assert_eq!(
+            log_ranges(100, 50, 1_000).collect::<Vec<_>>(),
+            [(51, 100), (1, 50), (0, 0)]
+        );
+        assert_eq!(log_ranges(100, 10, 0).collect::<Vec<_>>(), [(100, 100)]);
+    }
+
+    #[test]
+    fn client_message_from_update_client_calldata() {
+        assert_eq!(
+            client_message_from_calldata(&update_client(1, &[1, 2, 3]), IBC_HANDLER, 1),
+            Some(vec![1, 2, 3].into())
+        );
+        assert_eq!(
+            client_message_from_calldata(&update_client(2, &[1, 2, 3]), IBC_HANDLER, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn client_message_from_multicall_calldata() {
+        let call = |target: H160, call_data: Vec<u8>| multicall::Call3 {
+            target: target.into(),
+            allowFailure: true,
+            callData: call_data.into(),
+        };
+
+        let input = multicall::multicallCall {
+            calls: vec![
+                call(IBC_HANDLER, update_client(2, &[4, 5, 6])),
+                call(H160::new([0xbb; 20]), update_client(1, &[7, 8, 9])),
+                call(IBC_HANDLER, update_client(1, &[1, 2, 3])),
+            ],
+        }
+        .abi_encode();
+
+        assert_eq!(
+            client_message_from_calldata(&input, IBC_HANDLER, 1),
+            Some(vec![1, 2, 3].into())
+        );
+        assert_eq!(
+            client_message_from_calldata(&input, IBC_HANDLER, 2),
+            Some(vec![4, 5, 6].into())
+        );
+        assert_eq!(client_message_from_calldata(&input, IBC_HANDLER, 3), None);
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use ibc_union_spec::{
+    datagram::{Datagram, MsgSubmitMisbehaviour},
+    path::ConsensusStatePath,
+    types::ClientId,
+    IbcUnion,
+};
+use itertools::Itertools;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info, instrument};
+use unionlabs::{ibc::core::client::height::Height, ErrorReporter};
+use voyager_message::{
+    call::{FetchUpdateHeaders, SubmitTx},
+    core::{ChainId, QueryHeight},
+    data::{Data, IbcDatagram, OrderedHeaders},
+    into_value,
+    module::{PluginInfo, PluginServer},
+    rpc::missing_state,
+    ExtensionsExt, Plugin, PluginMessage, RawClientId, VoyagerClient, VoyagerMessage,
+    FATAL_JSONRPC_ERROR_CODE,
+};
+use voyager_vm::{call, defer, now, pass::PassResult, promise, seq, BoxDynError, Op};
+
+use crate::{
+    call::{CheckForMisbehaviour, ModuleCall},
+    callback::{MakeMisbehaviour, ModuleCallback},
+    host::{Host, HostConfig},
+};
+
+pub mod call;
+pub mod callback;
+pub mod host;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+pub struct Module {
+    pub check_interval: u64,
+    pub hosts: HashMap<ChainId, Host>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// How often (in seconds) each watched client is checked for misbehaviour.
+    #[serde(default = "default_check_interval")]
+    pub check_interval: u64,
+    /// The chains hosting the watched clients, used to read back the conflicting client updates
+    /// when building evidence of misbehaviour.
+    #[serde(default)]
+    pub hosts: BTreeMap<ChainId, HostConfig>,
+}
+
+fn default_check_interval() -> u64 {
+    60
+}
+
+impl Plugin for Module {
+    type Call = ModuleCall;
+    type Callback = ModuleCallback;
+
+    type Config = Config;
+    type Cmd = Cmd;
+
+    async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
+        let mut hosts = HashMap::new();
+
+        for (chain_id, host) in config.hosts {
+            hosts.insert(chain_id, Host::new(host).await?);
+        }
+
+        Ok(Self {
+            check_interval: config.check_interval,
+            hosts,
+        })
+    }
+
+    fn info(_: Self::Config) -> PluginInfo {
+        PluginInfo {
+            name: plugin_name(),
+            // never interested in any messages since this plugin does not utilize a queue
+            interest_filter: "false".to_owned(),
+        }
+    }
+
+    async fn cmd(_: Self::Config, cmd: Self::Cmd) {
+        match cmd {
+            Cmd::MakeMessage(CheckForMisbehaviour {
+                chain_id,
+                client_id,
+                last_verified_height,
+            }) => {
+                let op = call::<VoyagerMessage>(PluginMessage::new(
+                    plugin_name(),
+                    ModuleCall::CheckForMisbehaviour(CheckForMisbehaviour {
+                        chain_id,
+                        client_id,
+                        last_verified_height,
+                    }),
+                ));
+
+                println!("{}", into_value(op));
+            }
+        }
+    }
+}
+
+#[derive(clap::Parser)]
+pub enum Cmd {
+    MakeMessage(CheckForMisbehaviour),
+}
+
+fn plugin_name() -> String {
+    pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
+
+    PLUGIN_NAME.to_owned()
+}
+
+impl Module {
+    fn plugin_name(&self) -> String {
+        plugin_name()
+    }
+
+    fn check_again(
+        &self,
+        chain_id: ChainId,
+        client_id: ClientId,
+        last_verified_height: Option<Height>,
+    ) -> Op<VoyagerMessage> {
+        seq([
+            defer(now() + self.check_interval),
+            call(PluginMessage::new(
+                self.plugin_name(),
+                ModuleCall::CheckForMisbehaviour(CheckForMisbehaviour {
+                    chain_id,
+                    client_id,
+                    last_verified_height,
+                }),
+            )),
+        ])
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            %chain_id,
+            %client_id,
+            ?last_verified_height,
+        )
+    )]
+    async fn check_for_misbehaviour(
+        &self,
+        voyager_client: &VoyagerClient,
+        chain_id: ChainId,
+        client_id: ClientId,
+        last_verified_height: Option<Height>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let host = self.hosts.get(&chain_id).ok_or_else(|| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!(
+                    "no host is configured for chain {chain_id}, unable to build evidence of \
+                    misbehaviour for clients on it"
+                ),
+                None::<()>,
+            )
+        })?;
+
+        let client_info = voyager_client
+            .client_info::<IbcUnion>(chain_id.clone(), client_id)
+            .await?;
+
+        let client_meta = voyager_client
+            .client_meta::<IbcUnion>(chain_id.clone(), QueryHeight::Latest, client_id)
+            .await?;
+
+        let trusted_height = client_meta.counterparty_height;
+
+        if last_verified_height == Some(trusted_height) {
+            debug!(%trusted_height, "client has not been updated since the last check");
+
+            return Ok(self.check_again(chain_id, client_id, last_verified_height));
+        }
+
+        let on_chain_consensus_state = voyager_client
+            .query_ibc_state(
+                chain_id.clone(),
+                QueryHeight::Latest,
+                ConsensusStatePath {
+                    client_id,
+                    height: trusted_height.height(),
+                },
+            )
+            .await?
+            .state
+            .ok_or_else(missing_state(
+                "consensus state must exist at the latest trusted height",
+                None,
+            ))?;
+
+        let on_chain_consensus_state = voyager_client
+            .decode_consensus_state::<IbcUnion>(
+                client_info.client_type.clone(),
+                client_info.ibc_interface.clone(),
+                on_chain_consensus_state,
+            )
+            .await?;
+
+        let expected_consensus_state = voyager_client
+            .self_consensus_state(
+                client_meta.chain_id.clone(),
+                client_info.client_type.clone(),
+                QueryHeight::Specific(trusted_height),
+            )
+            .await?
+            .state;
+
+        if on_chain_consensus_state == expected_consensus_state {
+            debug!(%trusted_height, "consensus state matches the counterparty chain");
+
+            return Ok(self.check_again(chain_id, client_id, Some(trusted_height)));
+        }
+
+        error!(
+            %trusted_height,
+            %on_chain_consensus_state,
+            %expected_consensus_state,
+            counterparty_chain_id = %client_meta.chain_id,
+            "consensus state of client conflicts with the consensus of the counterparty chain"
+        );
+
+        let latest_height = voyager_client
+            .query_latest_height(chain_id.clone(), false)
+            .await?;
+
+        let client_update = host
+            .client_update(client_id, trusted_height.height(), latest_height.height())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    format!(
+                        "error fetching the update of the client to {trusted_height}: {}",
+                        ErrorReporter(&*e)
+                    ),
+                    None::<()>,
+                )
+            })?
+            .ok_or_else(|| {
+                ErrorObject::owned(
+                    -1,
+                    format!(
+                        "no update of the client to {trusted_height} was found at or before \
+                        {latest_height}"
+                    ),
+                    None::<()>,
+                )
+            })?;
+
+        info!(
+            update_height = %client_update.height,
+            client_message = %client_update.client_message,
+            "found the conflicting update"
+        );
+
+        let update_from = match last_verified_height {
+            Some(last_verified_height) => last_verified_height,
+            // the latest height of the client right before the conflicting update was included is
+            // the height that the conflicting header was built on top of
+            None => {
+                voyager_client
+                    .client_meta::<IbcUnion>(
+                        chain_id.clone(),
+                        QueryHeight::Specific(Height::new_with_revision(
+                            latest_height.revision(),
+                            client_update.height - 1,
+                        )),
+                        client_id,
+                    )
+                    .await?
+                    .counterparty_height
+            }
+        };
+
+        info!(%update_from, %trusted_height, "fetching evidence of misbehaviour");
+
+        Ok(promise(
+            [call(FetchUpdateHeaders {
+                client_type: client_info.client_type,
+                chain_id: client_meta.chain_id,
+                counterparty_chain_id: chain_id.clone(),
+                client_id: RawClientId::new(client_id),
+                update_from,
+                update_to: trusted_height,
+            })],
+            [],
+            PluginMessage::new(
+                self.plugin_name(),
+                ModuleCallback::from(MakeMisbehaviour {
+                    chain_id,
+                    client_id,
+                    height: trusted_height,
+                    conflicting_header: client_update.client_message,
+                }),
+            ),
+        ))
+    }
+
+    #[instrument(skip_all, fields(%chain_id, %client_id, %height))]
+    async fn make_misbehaviour(
+        &self,
+        voyager_client: &VoyagerClient,
+        MakeMisbehaviour {
+            chain_id,
+            client_id,
+            height,
+            conflicting_header,
+        }: MakeMisbehaviour,
+        datas: VecDeque<Data>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let OrderedHeaders { headers } = datas
+            .into_iter()
+            .exactly_one()
+            .map_err(|found| serde_json::to_string(&found.collect::<Vec<_>>()).unwrap())
+            .and_then(|d| {
+                d.try_into()
+                    .map_err(|found| serde_json::to_string(&found).unwrap())
+            })
+            .map_err(|found| {
+                ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!("OrderedHeaders not present in data queue, found {found}"),
+                    None::<()>,
+                )
+            })?;
+
+        let header = headers
+            .into_iter()
+            .find_map(|(meta, header)| (meta.height == height).then_some(header))
+            .ok_or_else(|| {
+                ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!("no header was fetched for the conflicting height {height}"),
+                    None::<()>,
+                )
+            })?;
+
+        let client_info = voyager_client
+            .client_info::<IbcUnion>(chain_id.clone(), client_id)
+            .await?;
+
+        let header = voyager_client
+            .encode_header::<IbcUnion>(
+                client_info.client_type.clone(),
+                client_info.ibc_interface.clone(),
+                header,
+            )
+            .await?;
+
+        // the conflicting header is header a, since the light clients expect header a to be at a
+        // height greater than or equal to header b
+        let misbehaviour = voyager_client
+            .encode_misbehaviour::<IbcUnion>(
+                client_info.client_type,
+                client_info.ibc_interface,
+                conflicting_header,
+                header,
+            )
+            .await?;
+
+        info!("submitting misbehaviour");
+
+        // the client will be frozen once this is submitted, so there is nothing left to watch
+        Ok(call(SubmitTx {
+            chain_id,
+            datagrams: vec![IbcDatagram::new::<IbcUnion>(Datagram::from(
+                MsgSubmitMisbehaviour {
+                    client_id,
+                    misbehaviour,
+                },
+            ))],
+        }))
+    }
+}
+
+#[async_trait]
+impl PluginServer<ModuleCall, ModuleCallback> for Module {
+    async fn run_pass(
+        &self,
+        _: &Extensions,
+        msgs: Vec<Op<VoyagerMessage>>,
+    ) -> RpcResult<PassResult<VoyagerMessage>> {
+        error!(?msgs, "this plugin does not utilize a queue");
+
+        Ok(PassResult::default())
+    }
+
+    async fn call(&self, e: &Extensions, msg: ModuleCall) -> RpcResult<Op<VoyagerMessage>> {
+        match msg {
+            ModuleCall::CheckForMisbehaviour(CheckForMisbehaviour {
+                chain_id,
+                client_id,
+                last_verified_height,
+            }) => {
+                self.check_for_misbehaviour(e.try_get()?, chain_id, client_id, last_verified_height)
+                    .await
+            }
+        }
+    }
+
+    async fn callback(
+        &self,
+        e: &Extensions,
+        cb: ModuleCallback,
+        datas: VecDeque<Data>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        match cb {
+            ModuleCallback::MakeMisbehaviour(cb) => {
+                self.make_misbehaviour(e.try_get()?, cb, datas).await
+            }
+        }
+    }
+}
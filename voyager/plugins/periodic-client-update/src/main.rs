@@ -16,7 +16,7 @@ use voyager_message::{
     module::{PluginInfo, PluginServer},
     ExtensionsExt, Plugin, PluginMessage, RawClientId, VoyagerClient, VoyagerMessage,
 };
-use voyager_vm::{call, conc, defer, now, pass::PassResult, promise, seq, BoxDynError, Op};
+use voyager_vm::{call, conc, now, pass::PassResult, promise, schedule, seq, BoxDynError, Op};
 
 use crate::call::{CheckForClientAge, ModuleCall};
 
@@ -173,8 +173,8 @@ impl Module {
                 ]),
             ]))
         } else {
-            Ok(seq([
-                defer(now() + 60),
+            Ok(schedule(
+                now() + 60,
                 call(PluginMessage::new(
                     self.plugin_name(),
                     ModuleCall::CheckForClientAge(CheckForClientAge {
@@ -184,7 +184,7 @@ impl Module {
                         max_age,
                     }),
                 )),
-            ]))
+            ))
         }
     }
 }
@@ -1,24 +1,38 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::sol_types::SolValue;
 use enumorph::Enumorph;
 use ibc_classic_spec::IbcClassic;
-use ibc_union_spec::IbcUnion;
+use ibc_solidity::Packet;
+use ibc_union_spec::{
+    datagram::{Datagram, MsgBatchAcks, MsgBatchSend},
+    event::{PacketSend, PacketTimeout},
+    path::{BatchPacketsPath, BatchReceiptsPath, COMMITMENT_NULL},
+    types::ClientId,
+    IbcUnion,
+};
 use jsonrpsee::{core::RpcResult, types::ErrorObject};
 use macros::model;
 use serde_json::json;
-use tracing::info;
-use unionlabs::ibc::core::client::height::Height;
+use tracing::{debug, info, instrument};
+use unionlabs::{ethereum::keccak256, ibc::core::client::height::Height};
 use voyager_message::{
-    call::FetchUpdateHeaders,
+    call::{FetchUpdateHeaders, SubmitTx, WaitForHeight, WaitForTimestamp},
     callback::AggregateMsgUpdateClientsFromOrderedHeaders,
-    core::{ChainId, QueryHeight},
+    core::{ChainId, QueryHeight, Timestamp},
+    data::IbcDatagram,
     PluginMessage, RawClientId, VoyagerClient, VoyagerMessage, FATAL_JSONRPC_ERROR_CODE,
 };
-use voyager_vm::{now, promise, Op};
+use voyager_vm::{data, defer, noop, now, promise, seq, Op};
 
 use crate::{
     call,
     callback::{make_msgs, MakeBatchTransaction, MakeIbcMessagesFromUpdate, ModuleCallback},
-    data::BatchableEvent,
-    IbcSpecExt, Module,
+    data::{
+        mk_packet, BatchAcks, BatchSend, BatchableEvent, CommitmentBatch, EventBatch, EventUnion,
+        ModuleData,
+    },
+    plugin_name, IbcSpecExt, Module,
 };
 
 #[model]
@@ -29,6 +43,11 @@ pub enum ModuleCall {
 
     MakeMsgV1(MakeMsg<IbcClassic>),
     MakeMsgUnion(MakeMsg<IbcUnion>),
+
+    CheckPacketTimeout(CheckPacketTimeout),
+
+    CommitBatch(CommitBatch),
+    WaitForBatchCommitment(WaitForBatchCommitment),
 }
 
 /// Constructs multiple batch transactions, where all of the batches are provable at the new consensus height.
@@ -153,3 +172,470 @@ pub struct MakeMsg<V: IbcSpecExt> {
     /// The original event that was emitted on the origin chain.
     pub event: V::BatchableEvent,
 }
+
+/// Check if a packet sent to this chain has timed out.
+///
+/// If the packet has already been received, this is a noop. If it has not yet timed out, this will wait until the timeout height or timestamp is reached on this chain and then check again. Once the packet can no longer be received on this chain, it is handed off as an [`EventUnion::PacketTimeout`] to the instance of this plugin running for the chain the packet was sent from, where it will be batched into a `MsgPacketTimeout`.
+#[model]
+pub struct CheckPacketTimeout {
+    /// The chain that the packet was sent from. The timeout will be submitted to this chain.
+    pub origin_chain_id: ChainId,
+    /// The original event that was emitted on the origin chain.
+    pub event: PacketSend,
+}
+
+impl CheckPacketTimeout {
+    #[instrument(
+        skip_all,
+        fields(
+            chain_id = %module.chain_id,
+            origin_chain_id = %self.origin_chain_id,
+            source_channel_id = self.event.packet.source_channel.channel_id,
+            destination_channel_id = self.event.packet.destination_channel.channel_id,
+            timeout_height = self.event.packet.timeout_height,
+            timeout_timestamp = self.event.packet.timeout_timestamp,
+        )
+    )]
+    pub async fn call(
+        self,
+        module: &Module,
+        voyager_client: &VoyagerClient,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let timeout_height = self.event.packet.timeout_height;
+        let timeout_timestamp = self.event.packet.timeout_timestamp;
+
+        let latest_height = voyager_client
+            .query_latest_height(module.chain_id.clone(), true)
+            .await?;
+
+        let latest_timestamp = voyager_client
+            .query_latest_timestamp(module.chain_id.clone(), true)
+            .await?;
+
+        let packet = Packet {
+            source_channel_id: self.event.packet.source_channel.channel_id,
+            destination_channel_id: self.event.packet.destination_channel.channel_id,
+            data: self.event.packet_data.clone().into(),
+            timeout_height,
+            timeout_timestamp,
+        };
+
+        let receipt = voyager_client
+            .query_ibc_state(
+                module.chain_id.clone(),
+                QueryHeight::Specific(latest_height),
+                BatchReceiptsPath {
+                    channel_id: self.event.packet.destination_channel.channel_id,
+                    batch_hash: keccak256(packet.abi_encode()),
+                },
+            )
+            .await?
+            .state;
+
+        if receipt != COMMITMENT_NULL {
+            debug!(%receipt, "packet has been received, it cannot time out");
+
+            return Ok(noop());
+        }
+
+        let timed_out = (timeout_height != 0 && latest_height.height() >= timeout_height)
+            || (timeout_timestamp != 0 && latest_timestamp.as_nanos() >= timeout_timestamp);
+
+        if timed_out {
+            info!(
+                %latest_height,
+                %latest_timestamp,
+                "packet has timed out"
+            );
+
+            let first_seen_at: u64 = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis()
+                .try_into()
+                .expect("how many milliseconds can there be man");
+
+            // the timeout is relayed by the plugin instance for the chain the packet was sent from, since that's where the client that needs to be updated lives
+            Ok(data(PluginMessage::new(
+                plugin_name(&self.origin_chain_id),
+                ModuleData::from(EventBatch::<IbcUnion> {
+                    // the client tracking this chain on the origin chain
+                    client_id: self.event.packet.source_channel.connection.client_id,
+                    events: vec![BatchableEvent {
+                        first_seen_at,
+                        provable_height: latest_height,
+                        event: EventUnion::PacketTimeout(PacketTimeout {
+                            packet_data: self.event.packet_data,
+                            packet: self.event.packet,
+                        }),
+                    }],
+                }),
+            )))
+        } else {
+            // prefer waiting on the timestamp, since that is generally the timeout that will be hit first
+            let wait = if timeout_timestamp != 0 {
+                call(WaitForTimestamp {
+                    chain_id: module.chain_id.clone(),
+                    timestamp: Timestamp::from_nanos(timeout_timestamp),
+                    finalized: true,
+                })
+            } else {
+                call(WaitForHeight {
+                    chain_id: module.chain_id.clone(),
+                    height: Height::new(timeout_height),
+                    finalized: true,
+                })
+            };
+
+            Ok(seq([
+                wait,
+                call(PluginMessage::new(
+                    module.plugin_name(),
+                    ModuleCall::from(self),
+                )),
+            ]))
+        }
+    }
+}
+
+/// Commit to a batch of packets or acknowledgements on the origin chain, with `MsgBatchSend` or `MsgBatchAcks` respectively.
+///
+/// Packets that have since been received (or acknowledged, for acknowledgements) on this chain are dropped from the batch first. If less than two remain, the batch can't be committed to and the remaining events are handed back to be sent individually.
+#[model]
+pub struct CommitBatch {
+    /// The chain that the packets were sent from or the acknowledgements were written on. The batch is committed to on this chain.
+    pub origin_chain_id: ChainId,
+    /// The client tracking the origin chain on this chain.
+    pub client_id: ClientId,
+    /// The earliest time any of the events in the batch were first seen by this plugin.
+    pub first_seen_at: u64,
+    pub batch: CommitmentBatch,
+}
+
+impl CommitBatch {
+    #[instrument(
+        skip_all,
+        fields(
+            chain_id = %module.chain_id,
+            origin_chain_id = %self.origin_chain_id,
+            client_id = self.client_id,
+            origin_channel_id = self.batch.origin_channel_id(),
+        )
+    )]
+    pub async fn call(
+        self,
+        module: &Module,
+        voyager_client: &VoyagerClient,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let origin_height = voyager_client
+            .query_latest_height(self.origin_chain_id.clone(), true)
+            .await?;
+
+        let (pending, datagram) = match self.batch {
+            CommitmentBatch::Packets(BatchSend { events }) => {
+                let mut pending = vec![];
+
+                for event in events {
+                    let packet_hash =
+                        keccak256(mk_packet(&event.packet_data, &event.packet).abi_encode());
+
+                    let commitment = voyager_client
+                        .query_ibc_state(
+                            self.origin_chain_id.clone(),
+                            QueryHeight::Specific(origin_height),
+                            BatchPacketsPath {
+                                channel_id: event.packet.source_channel.channel_id,
+                                batch_hash: packet_hash,
+                            },
+                        )
+                        .await?
+                        .state;
+
+                    let receipt = voyager_client
+                        .query_ibc_state(
+                            module.chain_id.clone(),
+                            QueryHeight::Latest,
+                            BatchReceiptsPath {
+                                channel_id: event.packet.destination_channel.channel_id,
+                                batch_hash: packet_hash,
+                            },
+                        )
+                        .await?
+                        .state;
+
+                    if commitment == COMMITMENT_NULL || receipt != COMMITMENT_NULL {
+                        debug!(%packet_hash, "packet is no longer pending, dropping it from the batch");
+                    } else {
+                        pending.push(event);
+                    }
+                }
+
+                let batch = BatchSend { events: pending };
+
+                let datagram = (batch.events.len() >= 2).then(|| {
+                    Datagram::from(MsgBatchSend {
+                        source_channel_id: batch.events[0].packet.source_channel.channel_id,
+                        packets: batch.packets().into_iter().map(Into::into).collect(),
+                    })
+                });
+
+                (
+                    batch
+                        .events
+                        .iter()
+                        .cloned()
+                        .map(EventUnion::PacketSend)
+                        .collect::<Vec<_>>(),
+                    datagram.map(|datagram| (datagram, CommitmentBatch::from(batch))),
+                )
+            }
+            CommitmentBatch::Acks(BatchAcks { events }) => {
+                let mut pending = vec![];
+
+                for event in events {
+                    let packet_hash =
+                        keccak256(mk_packet(&event.packet_data, &event.packet).abi_encode());
+
+                    // the packet commitment is deleted once the packet is acknowledged
+                    let commitment = voyager_client
+                        .query_ibc_state(
+                            module.chain_id.clone(),
+                            QueryHeight::Latest,
+                            BatchPacketsPath {
+                                channel_id: event.packet.source_channel.channel_id,
+                                batch_hash: packet_hash,
+                            },
+                        )
+                        .await?
+                        .state;
+
+                    if commitment == COMMITMENT_NULL {
+                        debug!(%packet_hash, "packet has already been acknowledged, dropping it from the batch");
+                    } else {
+                        pending.push(event);
+                    }
+                }
+
+                let batch = BatchAcks { events: pending };
+
+                let datagram = (batch.events.len() >= 2).then(|| {
+                    Datagram::from(MsgBatchAcks {
+                        source_channel_id: batch.events[0].packet.destination_channel.channel_id,
+                        packets: batch.packets().into_iter().map(Into::into).collect(),
+                        acks: batch.acks(),
+                    })
+                });
+
+                (
+                    batch
+                        .events
+                        .iter()
+                        .cloned()
+                        .map(EventUnion::WriteAck)
+                        .collect::<Vec<_>>(),
+                    datagram.map(|datagram| (datagram, CommitmentBatch::from(batch))),
+                )
+            }
+        };
+
+        match datagram {
+            Some((datagram, batch)) => {
+                info!(
+                    batch_hash = %batch.batch_hash(),
+                    len = pending.len(),
+                    "committing to batch"
+                );
+
+                Ok(seq([
+                    call(SubmitTx {
+                        chain_id: self.origin_chain_id.clone(),
+                        datagrams: vec![IbcDatagram::new::<IbcUnion>(datagram)],
+                    }),
+                    call(PluginMessage::new(
+                        module.plugin_name(),
+                        ModuleCall::from(WaitForBatchCommitment {
+                            origin_chain_id: self.origin_chain_id,
+                            client_id: self.client_id,
+                            first_seen_at: self.first_seen_at,
+                            committed_at: now(),
+                            attempt: 0,
+                            batch,
+                        }),
+                    )),
+                ]))
+            }
+            None if pending.is_empty() => {
+                info!("no packets in the batch are pending");
+
+                Ok(noop())
+            }
+            None => {
+                info!(
+                    len = pending.len(),
+                    "not enough pending packets to commit to as a batch, sending them individually"
+                );
+
+                // the individual commitments were checked at this height above, so they are provable at it
+                Ok(data(PluginMessage::new(
+                    module.plugin_name(),
+                    ModuleData::from(EventBatch::<IbcUnion> {
+                        client_id: self.client_id,
+                        events: pending
+                            .into_iter()
+                            .map(|event| BatchableEvent {
+                                first_seen_at: self.first_seen_at,
+                                provable_height: origin_height,
+                                event,
+                            })
+                            .collect(),
+                    }),
+                )))
+            }
+        }
+    }
+}
+
+/// The delay before the first poll for a batch commitment, doubled on every subsequent poll.
+const MIN_BATCH_COMMITMENT_POLL_INTERVAL_SECONDS: u64 = 2;
+
+/// The maximum delay between two polls for a batch commitment.
+const MAX_BATCH_COMMITMENT_POLL_INTERVAL_SECONDS: u64 = 60;
+
+/// How long to wait for a batch commitment to be finalized before giving up on it.
+const BATCH_COMMITMENT_TIMEOUT_SECONDS: u64 = 30 * 60;
+
+/// Wait for the commitment of a batch submitted by [`CommitBatch`] to be finalized on the origin chain, and then hand it back to be batched as a single [`EventUnion::BatchSend`] or [`EventUnion::BatchAcks`], provable at the height it was found at.
+///
+/// The commitment is polled with an exponential backoff, and the wait fails if it is not finalized within [`BATCH_COMMITMENT_TIMEOUT_SECONDS`] of the commitment being submitted.
+#[model]
+pub struct WaitForBatchCommitment {
+    pub origin_chain_id: ChainId,
+    pub client_id: ClientId,
+    pub first_seen_at: u64,
+    /// The unix timestamp (in seconds) of when the commitment was submitted.
+    pub committed_at: u64,
+    /// The amount of times the commitment has been polled for.
+    pub attempt: u32,
+    pub batch: CommitmentBatch,
+}
+
+/// The delay before polling for a batch commitment again after `attempt` unsuccessful polls.
+fn batch_commitment_poll_interval(attempt: u32) -> u64 {
+    MIN_BATCH_COMMITMENT_POLL_INTERVAL_SECONDS
+        .saturating_mul(2_u64.saturating_pow(attempt))
+        .min(MAX_BATCH_COMMITMENT_POLL_INTERVAL_SECONDS)
+}
+
+impl WaitForBatchCommitment {
+    #[instrument(
+        skip_all,
+        fields(
+            chain_id = %module.chain_id,
+            origin_chain_id = %self.origin_chain_id,
+            client_id = self.client_id,
+            origin_channel_id = self.batch.origin_channel_id(),
+            batch_hash = %self.batch.batch_hash(),
+        )
+    )]
+    pub async fn call(
+        self,
+        module: &Module,
+        voyager_client: &VoyagerClient,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let origin_height = voyager_client
+            .query_latest_height(self.origin_chain_id.clone(), true)
+            .await?;
+
+        let commitment = match &self.batch {
+            CommitmentBatch::Packets(_) => {
+                voyager_client
+                    .query_ibc_state(
+                        self.origin_chain_id.clone(),
+                        QueryHeight::Specific(origin_height),
+                        BatchPacketsPath {
+                            channel_id: self.batch.origin_channel_id(),
+                            batch_hash: self.batch.batch_hash(),
+                        },
+                    )
+                    .await?
+                    .state
+            }
+            CommitmentBatch::Acks(_) => {
+                voyager_client
+                    .query_ibc_state(
+                        self.origin_chain_id.clone(),
+                        QueryHeight::Specific(origin_height),
+                        BatchReceiptsPath {
+                            channel_id: self.batch.origin_channel_id(),
+                            batch_hash: self.batch.batch_hash(),
+                        },
+                    )
+                    .await?
+                    .state
+            }
+        };
+
+        if commitment == COMMITMENT_NULL {
+            if now().saturating_sub(self.committed_at) >= BATCH_COMMITMENT_TIMEOUT_SECONDS {
+                return Err(ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!(
+                        "batch commitment was not finalized within \
+                        {BATCH_COMMITMENT_TIMEOUT_SECONDS} seconds"
+                    ),
+                    None::<()>,
+                ));
+            }
+
+            let poll_interval = batch_commitment_poll_interval(self.attempt);
+
+            debug!(
+                %origin_height,
+                attempt = self.attempt,
+                poll_interval,
+                "batch commitment is not yet finalized"
+            );
+
+            return Ok(seq([
+                defer(now() + poll_interval),
+                call(PluginMessage::new(
+                    module.plugin_name(),
+                    ModuleCall::from(WaitForBatchCommitment {
+                        attempt: self.attempt + 1,
+                        ..self
+                    }),
+                )),
+            ]));
+        }
+
+        info!(%origin_height, "batch commitment is finalized");
+
+        Ok(data(PluginMessage::new(
+            module.plugin_name(),
+            ModuleData::from(EventBatch::<IbcUnion> {
+                client_id: self.client_id,
+                events: vec![BatchableEvent {
+                    first_seen_at: self.first_seen_at,
+                    provable_height: origin_height,
+                    event: self.batch.into(),
+                }],
+            }),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_commitment_poll_interval_backs_off() {
+        assert_eq!(
+            (0..7)
+                .map(batch_commitment_poll_interval)
+                .collect::<Vec<_>>(),
+            [2, 4, 8, 16, 32, 60, 60]
+        );
+        assert_eq!(batch_commitment_poll_interval(u32::MAX), 60);
+    }
+}
@@ -10,7 +10,7 @@ use tracing::{debug, instrument, warn};
 use unionlabs::ibc::core::client::height::Height;
 use voyager_message::{
     call::{SubmitTx, WaitForTrustedHeight},
-    core::{ChainId, ClientStateMeta, QueryHeight},
+    core::{ClientStateMeta, QueryHeight},
     data::{Data, IbcDatagram, OrderedClientUpdates},
     PluginMessage, RawClientId, VoyagerClient, VoyagerMessage,
 };
@@ -18,7 +18,7 @@ use voyager_vm::{call, conc, noop, promise, seq, Op};
 
 use crate::{
     call::{MakeMsg, ModuleCall},
-    data::BatchableEvent,
+    data::{BatchableEvent, ModuleData, SequencedDatagram},
     IbcSpecExt, Module,
 };
 
@@ -171,35 +171,29 @@ pub struct MakeBatchTransaction<V: IbcSpecExt> {
 }
 
 impl<V: IbcSpecExt> MakeBatchTransaction<V> {
-    #[instrument(skip_all, fields(ibc_spec_id = %V::ID, %chain_id, datas_len = datas.len()))]
-    pub fn call(self, chain_id: ChainId, datas: VecDeque<Data>) -> Op<VoyagerMessage> {
+    #[instrument(skip_all, fields(ibc_spec_id = %V::ID, chain_id = %module.chain_id, datas_len = datas.len()))]
+    pub fn call(self, module: &Module, datas: VecDeque<Data>) -> Op<VoyagerMessage> {
         if datas.is_empty() {
             warn!("no IBC messages in queue! this likely means that all of the IBC messages that were queued to be sent were already sent to the destination chain");
         }
 
-        let mut msgs = datas
+        let chain_id = module.chain_id.clone();
+
+        let msgs = datas
             .into_iter()
-            .map(|d| {
-                IbcDatagram::try_from(d)
-                    .unwrap()
-                    .decode_datagram::<V>()
-                    .unwrap()
-                    .unwrap()
+            .map(|d| match d.as_plugin::<ModuleData>(module.plugin_name()) {
+                Ok(ModuleData::SequencedDatagram(SequencedDatagram { sequence, datagram })) => {
+                    (Some(sequence), datagram)
+                }
+                Ok(data) => panic!("unexpected plugin data: {data:?}"),
+                Err(d) => (None, IbcDatagram::try_from(d).unwrap()),
+            })
+            .map(|(sequence, datagram)| {
+                (sequence, datagram.decode_datagram::<V>().unwrap().unwrap())
             })
-            .peekable();
-
-        // TODO: We may need to sort packet messages when we support ordered channels
-        // msgs.sort_unstable_by(|a, b| match (a, b) {
-        //     (IbcMessage::RecvPacket(_), IbcMessage::RecvPacket(_)) => todo!(),
-        //     (IbcMessage::RecvPacket(_), IbcMessage::AcknowledgePacket(_)) => todo!(),
-        //     (IbcMessage::RecvPacket(_), IbcMessage::TimeoutPacket(_)) => todo!(),
-        //     (IbcMessage::AcknowledgePacket(_), IbcMessage::RecvPacket(_)) => todo!(),
-        //     (IbcMessage::AcknowledgePacket(_), IbcMessage::AcknowledgePacket(_)) => todo!(),
-        //     (IbcMessage::AcknowledgePacket(_), IbcMessage::TimeoutPacket(_)) => todo!(),
-        //     (IbcMessage::TimeoutPacket(_), IbcMessage::RecvPacket(_)) => todo!(),
-        //     (IbcMessage::TimeoutPacket(_), IbcMessage::AcknowledgePacket(_)) => todo!(),
-        //     (IbcMessage::TimeoutPacket(_), IbcMessage::TimeoutPacket(_)) => todo!(),
-        // });
+            .collect::<Vec<_>>();
+
+        let mut msgs = sort_by_sequence(msgs).into_iter().peekable();
 
         match self.updates {
             Some(updates) => call(SubmitTx {
@@ -246,3 +240,61 @@ impl<V: IbcSpecExt> MakeBatchTransaction<V> {
         }
     }
 }
+
+/// Sort the datagrams that have a sequence (packets on ordered channels) by their sequence, leaving all other datagrams in place.
+///
+/// The sequences of packets on different channels are independent, but sorting them together still preserves the order of the packets on each channel.
+fn sort_by_sequence<T>(msgs: Vec<(Option<u64>, T)>) -> Vec<T> {
+    let mut slots = Vec::with_capacity(msgs.len());
+    let mut sequenced = vec![];
+
+    for (sequence, msg) in msgs {
+        match sequence {
+            Some(sequence) => {
+                sequenced.push((sequence, msg));
+                slots.push(None);
+            }
+            None => slots.push(Some(msg)),
+        }
+    }
+
+    sequenced.sort_by_key(|(sequence, _)| *sequence);
+
+    let mut sequenced = sequenced.into_iter().map(|(_, msg)| msg);
+
+    slots
+        .into_iter()
+        .map(|slot| {
+            slot.unwrap_or_else(|| {
+                sequenced
+                    .next()
+                    .expect("there is a sequenced message for every empty slot; qed;")
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_by_sequence_keeps_unsequenced_in_place() {
+        assert_eq!(
+            sort_by_sequence(vec![
+                (None, "a"),
+                (Some(3), "seq 3"),
+                (None, "b"),
+                (Some(1), "seq 1"),
+                (Some(2), "seq 2"),
+                (None, "c"),
+            ]),
+            vec!["a", "seq 1", "b", "seq 2", "seq 3", "c"]
+        );
+    }
+
+    #[test]
+    fn sort_by_sequence_empty() {
+        assert_eq!(sort_by_sequence::<()>(vec![]), vec![]);
+    }
+}
@@ -1,9 +1,20 @@
+use alloy::sol_types::SolValue;
 use enumorph::Enumorph;
 use ibc_classic_spec::IbcClassic;
-use ibc_union_spec::IbcUnion;
+use ibc_solidity::Packet;
+use ibc_union_spec::{
+    event::{PacketMetadata, PacketSend, WriteAck},
+    types::ChannelId,
+    IbcUnion,
+};
 use macros::model;
 use subset_of::SubsetOf;
-use unionlabs::ibc::core::client::height::Height;
+use unionlabs::{
+    ethereum::keccak256,
+    ibc::core::client::height::Height,
+    primitives::{Bytes, H256},
+};
+use voyager_message::data::IbcDatagram;
 
 use crate::IbcSpecExt;
 
@@ -12,6 +23,8 @@ use crate::IbcSpecExt;
 pub enum ModuleData {
     BatchEventsV1(EventBatch<IbcClassic>),
     BatchEventsUnion(EventBatch<IbcUnion>),
+
+    SequencedDatagram(SequencedDatagram),
 }
 
 #[model]
@@ -23,6 +36,15 @@ pub struct EventBatch<V: IbcSpecExt> {
     pub events: Vec<BatchableEvent<V>>,
 }
 
+/// A datagram for a packet on an ordered channel, along with the sequence the packet was sent with.
+///
+/// Packets on ordered channels must be received and acknowledged in the order they were sent in, so these are sorted by their sequence before being submitted (see [`MakeBatchTransaction`](crate::callback::MakeBatchTransaction)).
+#[model]
+pub struct SequencedDatagram {
+    pub sequence: u64,
+    pub datagram: IbcDatagram,
+}
+
 #[model]
 #[serde(bound(serialize = "", deserialize = ""))]
 pub struct BatchableEvent<V: IbcSpecExt> {
@@ -70,8 +92,9 @@ impl TryFrom<ibc_classic_spec::FullEvent> for EventClassic {
     }
 }
 
-// REVIEW: We probably want to add a way to have "a packet timed out" be a variant here as well
 /// A subset of [`FullEvent`], containing only events that cause an action on the counterparty chain.
+///
+/// [`EventUnion::PacketTimeout`] is never emitted directly by an event source; it is constructed by [`CheckPacketTimeout`](crate::call::CheckPacketTimeout) once a packet sent from this chain can no longer be received on the counterparty chain.
 #[model]
 #[derive(Enumorph)]
 pub enum EventUnion {
@@ -83,8 +106,108 @@ pub enum EventUnion {
     ChannelOpenTry(ibc_union_spec::event::ChannelOpenTry),
     ChannelOpenAck(ibc_union_spec::event::ChannelOpenAck),
 
+    ChannelCloseInit(ibc_union_spec::event::ChannelCloseInit),
+
     PacketSend(ibc_union_spec::event::PacketSend),
     WriteAck(ibc_union_spec::event::WriteAck),
+    PacketTimeout(ibc_union_spec::event::PacketTimeout),
+
+    BatchSend(BatchSend),
+    BatchAcks(BatchAcks),
+}
+
+/// Packets sent on the same unordered channel, that have been committed to as a batch on the origin chain with `MsgBatchSend`. These are received with a single `MsgPacketRecv`, proven against the batch commitment.
+///
+/// Like [`EventUnion::PacketTimeout`], this is never emitted by an event source; it is constructed by [`CommitBatch`](crate::call::CommitBatch).
+#[model]
+pub struct BatchSend {
+    pub events: Vec<PacketSend>,
+}
+
+/// Acknowledgements written on the same unordered channel, that have been committed to as a batch on the origin chain with `MsgBatchAcks`. These are acknowledged with a single `MsgPacketAcknowledgement`, proven against the batch commitment.
+///
+/// Like [`EventUnion::PacketTimeout`], this is never emitted by an event source; it is constructed by [`CommitBatch`](crate::call::CommitBatch).
+#[model]
+pub struct BatchAcks {
+    pub events: Vec<WriteAck>,
+}
+
+/// A batch of packets or acknowledgements to be committed to on the chain they were sent from or written on.
+#[model]
+#[derive(Enumorph)]
+pub enum CommitmentBatch {
+    Packets(BatchSend),
+    Acks(BatchAcks),
+}
+
+impl CommitmentBatch {
+    /// The channel on the origin chain that the batch commitment is stored under.
+    pub fn origin_channel_id(&self) -> ChannelId {
+        match self {
+            CommitmentBatch::Packets(batch) => batch.events[0].packet.source_channel.channel_id,
+            CommitmentBatch::Acks(batch) => batch.events[0].packet.destination_channel.channel_id,
+        }
+    }
+
+    /// The hash of the packets in this batch, as used in the `BatchPacketsPath` and `BatchReceiptsPath` commitment keys.
+    pub fn batch_hash(&self) -> H256 {
+        match self {
+            CommitmentBatch::Packets(batch) => batch.batch_hash(),
+            CommitmentBatch::Acks(batch) => batch.batch_hash(),
+        }
+    }
+}
+
+impl From<CommitmentBatch> for EventUnion {
+    fn from(value: CommitmentBatch) -> Self {
+        match value {
+            CommitmentBatch::Packets(batch) => EventUnion::BatchSend(batch),
+            CommitmentBatch::Acks(batch) => EventUnion::BatchAcks(batch),
+        }
+    }
+}
+
+impl BatchSend {
+    pub fn packets(&self) -> Vec<Packet> {
+        self.events
+            .iter()
+            .map(|event| mk_packet(&event.packet_data, &event.packet))
+            .collect()
+    }
+
+    pub fn batch_hash(&self) -> H256 {
+        keccak256(self.packets().abi_encode())
+    }
+}
+
+impl BatchAcks {
+    pub fn packets(&self) -> Vec<Packet> {
+        self.events
+            .iter()
+            .map(|event| mk_packet(&event.packet_data, &event.packet))
+            .collect()
+    }
+
+    pub fn acks(&self) -> Vec<Bytes> {
+        self.events
+            .iter()
+            .map(|event| event.acknowledgement.clone())
+            .collect()
+    }
+
+    pub fn batch_hash(&self) -> H256 {
+        keccak256(self.packets().abi_encode())
+    }
+}
+
+pub fn mk_packet(packet_data: &Bytes, packet: &PacketMetadata) -> Packet {
+    Packet {
+        source_channel_id: packet.source_channel.channel_id,
+        destination_channel_id: packet.destination_channel.channel_id,
+        data: packet_data.clone().into(),
+        timeout_height: packet.timeout_height,
+        timeout_timestamp: packet.timeout_timestamp,
+    }
 }
 
 impl TryFrom<ibc_union_spec::event::FullEvent> for EventUnion {
@@ -104,6 +227,7 @@ impl TryFrom<ibc_union_spec::event::FullEvent> for EventUnion {
             ibc_union_spec::event::FullEvent::ChannelOpenInit(e) => Ok(Self::ChannelOpenInit(e)),
             ibc_union_spec::event::FullEvent::ChannelOpenTry(e) => Ok(Self::ChannelOpenTry(e)),
             ibc_union_spec::event::FullEvent::ChannelOpenAck(e) => Ok(Self::ChannelOpenAck(e)),
+            ibc_union_spec::event::FullEvent::ChannelCloseInit(e) => Ok(Self::ChannelCloseInit(e)),
             ibc_union_spec::event::FullEvent::PacketSend(e) => Ok(Self::PacketSend(e)),
             ibc_union_spec::event::FullEvent::WriteAck(e) => Ok(Self::WriteAck(e)),
             _ => Err(()),
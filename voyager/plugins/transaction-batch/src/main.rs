@@ -1,7 +1,8 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     convert,
     future::Future,
+    ops::Range,
     pin::Pin,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
@@ -12,7 +13,12 @@ use futures::{stream::FuturesOrdered, StreamExt, TryStreamExt};
 use ibc_classic_spec::IbcClassic;
 use ibc_solidity::Packet;
 use ibc_union_spec::{
-    types::{Channel, ChannelState},
+    event::{FullEvent, PacketSend, WriteAck},
+    path::{
+        ChannelOrderPath, NextSequenceAckPath, NextSequenceRecvPath, NextSequenceSendPath,
+        PacketSequencePath, COMMITMENT_NULL,
+    },
+    types::{Channel, ChannelId, ChannelOrder, ChannelState},
     IbcUnion,
 };
 use itertools::Itertools;
@@ -33,7 +39,7 @@ use unionlabs::{
         },
     },
     id::{ClientId, ConnectionId},
-    primitives::Bytes,
+    primitives::{Bytes, H256},
     traits::Member,
     DELAY_PERIOD,
 };
@@ -45,12 +51,17 @@ use voyager_message::{
     DefaultCmd, ExtensionsExt, Plugin, PluginMessage, RawClientId, VoyagerClient, VoyagerMessage,
     FATAL_JSONRPC_ERROR_CODE,
 };
-use voyager_vm::{call, data, pass::PassResult, seq, BoxDynError, Op};
+use voyager_vm::{call, conc, data, noop, pass::PassResult, seq, BoxDynError, Op};
 
 use crate::{
-    call::{MakeMsg, MakeTransactionBatchesWithUpdate, ModuleCall},
+    call::{
+        CheckPacketTimeout, CommitBatch, MakeMsg, MakeTransactionBatchesWithUpdate, ModuleCall,
+    },
     callback::ModuleCallback,
-    data::{BatchableEvent, EventBatch, EventClassic, EventUnion, ModuleData},
+    data::{
+        BatchAcks, BatchSend, BatchableEvent, CommitmentBatch, EventBatch, EventClassic,
+        EventUnion, ModuleData, SequencedDatagram,
+    },
 };
 
 pub mod call;
@@ -66,6 +77,7 @@ async fn main() {
 pub struct Module {
     pub chain_id: ChainId,
     pub client_configs: ClientConfigs,
+    pub batch_commitments: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -79,6 +91,9 @@ pub enum ClientConfigs {
 pub struct Config {
     pub chain_id: ChainId,
     pub client_configs: ClientConfigsSerde,
+    /// Commit to packets sent and acknowledgements written on the same unordered channel as a batch on the origin chain (with `MsgBatchSend` and `MsgBatchAcks`), and relay them with a single proof of the batch commitment. This requires a transaction plugin to be running for the origin chain.
+    #[serde(default)]
+    pub batch_commitments: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -164,8 +179,12 @@ impl IbcSpecExt for IbcUnion {
             EventUnion::ChannelOpenInit(_) => "channel_open_init",
             EventUnion::ChannelOpenTry(_) => "channel_open_try",
             EventUnion::ChannelOpenAck(_) => "channel_open_ack",
+            EventUnion::ChannelCloseInit(_) => "channel_close_init",
             EventUnion::PacketSend(_) => "packet_send",
             EventUnion::WriteAck(_) => "write_ack",
+            EventUnion::PacketTimeout(_) => "packet_timeout",
+            EventUnion::BatchSend(_) => "batch_send",
+            EventUnion::BatchAcks(_) => "batch_acks",
         }
     }
 }
@@ -280,6 +299,9 @@ if ."@type" == "data" then
         ) or (
             $event_type == "channel_open_ack"
             and ($event_data.connection.counterparty_client_id as $client_id | {clients_filter})
+        ) or (
+            $event_type == "channel_close_init"
+            and ($event_data.connection.counterparty_client_id as $client_id | {clients_filter})
         ) or (
             $event_type == "packet_send"
             and ($event_data.packet.destination_channel.connection.client_id as $client_id | {clients_filter})
@@ -289,6 +311,9 @@ if ."@type" == "data" then
         ) or ($data."@type" == "plugin"
             and $data."@value".plugin == "{plugin_name}"
             and $data."@value".message."@type" == "event_batch")
+    # event batches handed off from other instances of this plugin (i.e. timed out packets)
+    elif $data."@type" == "plugin" and $data."@value".plugin == "{plugin_name}" then
+        $data."@value".message."@value".client_id as $client_id | {clients_filter}
     else
         false
     end
@@ -312,9 +337,14 @@ end
 
 pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
 
+/// The name of the instance of this plugin running for `chain_id`.
+pub fn plugin_name(chain_id: &ChainId) -> String {
+    format!("{PLUGIN_NAME}/{chain_id}")
+}
+
 impl Module {
     fn plugin_name(&self) -> String {
-        format!("{PLUGIN_NAME}/{}", self.chain_id)
+        plugin_name(&self.chain_id)
     }
 
     pub fn new(config: Config) -> Self {
@@ -324,6 +354,7 @@ impl Module {
         Self {
             chain_id: config.chain_id,
             client_configs: ClientConfigs::new(config.client_configs),
+            batch_commitments: config.batch_commitments,
         }
     }
 }
@@ -350,7 +381,14 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
             }
             ModuleCall::MakeMsgV1(make_msg_v1) => do_make_msg_v1(voyager_client, make_msg_v1).await,
             ModuleCall::MakeMsgUnion(make_msg_union) => {
-                do_make_msg_union(voyager_client, make_msg_union).await
+                do_make_msg_union(self, voyager_client, make_msg_union).await
+            }
+            ModuleCall::CheckPacketTimeout(check_packet_timeout) => {
+                check_packet_timeout.call(self, voyager_client).await
+            }
+            ModuleCall::CommitBatch(commit_batch) => commit_batch.call(self, voyager_client).await,
+            ModuleCall::WaitForBatchCommitment(wait_for_batch_commitment) => {
+                wait_for_batch_commitment.call(self, voyager_client).await
             }
         }
     }
@@ -369,10 +407,8 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
             ModuleCallback::MakeIbcMessagesFromUpdateUnion(cb) => {
                 cb.call(e.try_get()?, self, datas).await
             }
-            ModuleCallback::MakeBatchTransactionV1(cb) => Ok(cb.call(self.chain_id.clone(), datas)),
-            ModuleCallback::MakeBatchTransactionUnion(cb) => {
-                Ok(cb.call(self.chain_id.clone(), datas))
-            }
+            ModuleCallback::MakeBatchTransactionV1(cb) => Ok(cb.call(self, datas)),
+            ModuleCallback::MakeBatchTransactionUnion(cb) => Ok(cb.call(self, datas)),
         }
     }
 }
@@ -387,6 +423,7 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
     )
 )]
 async fn do_make_msg_union(
+    module: &Module,
     voyager_client: &VoyagerClient,
     MakeMsg {
         origin_chain_id,
@@ -646,6 +683,17 @@ async fn do_make_msg_union(
         }
 
         EventUnion::ChannelOpenInit(event) => {
+            let ordering = voyager_client
+                .query_ibc_state(
+                    origin_chain_id.clone(),
+                    QueryHeight::Specific(origin_chain_proof_height),
+                    ChannelOrderPath {
+                        channel_id: event.channel_id,
+                    },
+                )
+                .await?
+                .state;
+
             let proof_init = voyager_client
                 .query_ibc_proof(
                     origin_chain_id,
@@ -680,6 +728,7 @@ async fn do_make_msg_union(
                             version: event.version.clone(),
                         },
                         counterparty_version: event.version,
+                        ordering,
                         proof_init: encoded_proof_init,
                         proof_height: origin_chain_proof_height.height(),
                     },
@@ -757,6 +806,40 @@ async fn do_make_msg_union(
             )))
         }
 
+        EventUnion::ChannelCloseInit(event) => {
+            let proof_init = voyager_client
+                .query_ibc_proof(
+                    origin_chain_id,
+                    QueryHeight::Specific(origin_chain_proof_height),
+                    ibc_union_spec::path::ChannelPath {
+                        channel_id: event.channel_id,
+                    },
+                )
+                .await?;
+
+            let client_info = voyager_client
+                .client_info::<IbcUnion>(target_chain_id, event.connection.counterparty_client_id)
+                .await?;
+
+            let encoded_proof_init = voyager_client
+                .encode_proof::<IbcUnion>(
+                    client_info.client_type,
+                    client_info.ibc_interface,
+                    proof_init.proof,
+                )
+                .await?;
+
+            Ok(data(IbcDatagram::new::<IbcUnion>(
+                ibc_union_spec::datagram::Datagram::from(
+                    ibc_union_spec::datagram::MsgChannelCloseConfirm {
+                        channel_id: event.counterparty_channel_id,
+                        proof_init: encoded_proof_init,
+                        proof_height: origin_chain_proof_height.height(),
+                    },
+                ),
+            )))
+        }
+
         EventUnion::PacketSend(event) => {
             let packet = Packet {
                 source_channel_id: event.packet.source_channel.channel_id,
@@ -765,16 +848,90 @@ async fn do_make_msg_union(
                 timeout_height: event.packet.timeout_height,
                 timeout_timestamp: event.packet.timeout_timestamp,
             };
-            let proof_try = voyager_client
-                .query_ibc_proof(
-                    origin_chain_id,
+
+            let packet_hash = keccak256(packet.abi_encode());
+
+            let ordering = voyager_client
+                .query_ibc_state(
+                    origin_chain_id.clone(),
                     QueryHeight::Specific(origin_chain_proof_height),
-                    ibc_union_spec::path::BatchPacketsPath {
+                    ChannelOrderPath {
                         channel_id: event.packet.source_channel.channel_id,
-                        batch_hash: keccak256(packet.abi_encode()),
                     },
                 )
-                .await?;
+                .await?
+                .state;
+
+            let (proof_try, sequence) = match ordering {
+                ChannelOrder::Unordered => (
+                    voyager_client
+                        .query_ibc_proof(
+                            origin_chain_id,
+                            QueryHeight::Specific(origin_chain_proof_height),
+                            ibc_union_spec::path::BatchPacketsPath {
+                                channel_id: event.packet.source_channel.channel_id,
+                                batch_hash: packet_hash,
+                            },
+                        )
+                        .await?,
+                    None,
+                ),
+                ChannelOrder::Ordered => {
+                    let next_sequence_recv = voyager_client
+                        .query_ibc_state(
+                            target_chain_id.clone(),
+                            QueryHeight::Latest,
+                            NextSequenceRecvPath {
+                                channel_id: event.packet.destination_channel.channel_id,
+                            },
+                        )
+                        .await?
+                        .state;
+
+                    let next_sequence_send = voyager_client
+                        .query_ibc_state(
+                            origin_chain_id.clone(),
+                            QueryHeight::Specific(origin_chain_proof_height),
+                            NextSequenceSendPath {
+                                channel_id: event.packet.source_channel.channel_id,
+                            },
+                        )
+                        .await?
+                        .state;
+
+                    // the packet may be preceded by other packets that have not yet been received
+                    // (possibly in the same batch), so it can't be assumed to be the next packet
+                    // to be received
+                    let Some(sequence) = find_packet_sequence(
+                        voyager_client,
+                        origin_chain_id.clone(),
+                        QueryHeight::Specific(origin_chain_proof_height),
+                        event.packet.source_channel.channel_id,
+                        next_sequence_recv..next_sequence_send,
+                        packet_hash,
+                    )
+                    .await?
+                    else {
+                        info!(%next_sequence_recv, "packet was already received on the ordered channel");
+
+                        return Ok(noop());
+                    };
+
+                    (
+                        voyager_client
+                            .query_ibc_proof(
+                                origin_chain_id,
+                                QueryHeight::Specific(origin_chain_proof_height),
+                                PacketSequencePath {
+                                    channel_id: event.packet.source_channel.channel_id,
+                                    sequence,
+                                },
+                            )
+                            .await?,
+                        Some(sequence),
+                    )
+                }
+            };
 
             let client_info = voyager_client
                 .client_info::<IbcUnion>(
@@ -791,14 +948,16 @@ async fn do_make_msg_union(
                 )
                 .await?;
 
-            Ok(data(IbcDatagram::new::<IbcUnion>(
+            Ok(sequenced_datagram(
+                module,
+                sequence,
                 ibc_union_spec::datagram::Datagram::from(ibc_union_spec::datagram::MsgPacketRecv {
                     packets: vec![packet.into()],
                     relayer_msgs: vec![vec![].into()],
                     proof: encoded_proof_commitment,
                     proof_height: origin_chain_proof_height.height(),
                 }),
-            )))
+            ))
         }
 
         EventUnion::WriteAck(event) => {
@@ -809,13 +968,72 @@ async fn do_make_msg_union(
                 timeout_height: event.packet.timeout_height,
                 timeout_timestamp: event.packet.timeout_timestamp,
             };
+
+            let packet_hash = keccak256(packet.abi_encode());
+
+            let ordering = voyager_client
+                .query_ibc_state(
+                    target_chain_id.clone(),
+                    QueryHeight::Latest,
+                    ChannelOrderPath {
+                        channel_id: event.packet.source_channel.channel_id,
+                    },
+                )
+                .await?
+                .state;
+
+            // packets on ordered channels must be acknowledged in the order they were sent in
+            let sequence = match ordering {
+                ChannelOrder::Unordered => None,
+                ChannelOrder::Ordered => {
+                    let next_sequence_ack = voyager_client
+                        .query_ibc_state(
+                            target_chain_id.clone(),
+                            QueryHeight::Latest,
+                            NextSequenceAckPath {
+                                channel_id: event.packet.source_channel.channel_id,
+                            },
+                        )
+                        .await?
+                        .state;
+
+                    let next_sequence_send = voyager_client
+                        .query_ibc_state(
+                            target_chain_id.clone(),
+                            QueryHeight::Latest,
+                            NextSequenceSendPath {
+                                channel_id: event.packet.source_channel.channel_id,
+                            },
+                        )
+                        .await?
+                        .state;
+
+                    let Some(sequence) = find_packet_sequence(
+                        voyager_client,
+                        target_chain_id.clone(),
+                        QueryHeight::Latest,
+                        event.packet.source_channel.channel_id,
+                        next_sequence_ack..next_sequence_send,
+                        packet_hash,
+                    )
+                    .await?
+                    else {
+                        info!(%next_sequence_ack, "packet was already acknowledged on the ordered channel");
+
+                        return Ok(noop());
+                    };
+
+                    Some(sequence)
+                }
+            };
+
             let proof_try = voyager_client
                 .query_ibc_proof(
                     origin_chain_id,
                     QueryHeight::Specific(origin_chain_proof_height),
                     ibc_union_spec::path::BatchReceiptsPath {
                         channel_id: event.packet.destination_channel.channel_id,
-                        batch_hash: keccak256(packet.abi_encode()),
+                        batch_hash: packet_hash,
                     },
                 )
                 .await?;
@@ -835,7 +1053,9 @@ async fn do_make_msg_union(
                 )
                 .await?;
 
-            Ok(data(IbcDatagram::new::<IbcUnion>(
+            Ok(sequenced_datagram(
+                module,
+                sequence,
                 ibc_union_spec::datagram::Datagram::from(
                     ibc_union_spec::datagram::MsgPacketAcknowledgement {
                         packets: vec![packet.into()],
@@ -844,11 +1064,282 @@ async fn do_make_msg_union(
                         proof_height: origin_chain_proof_height.height(),
                     },
                 ),
+            ))
+        }
+
+        EventUnion::PacketTimeout(event) => {
+            let packet = Packet {
+                source_channel_id: event.packet.source_channel.channel_id,
+                destination_channel_id: event.packet.destination_channel.channel_id,
+                data: event.packet_data.into(),
+                timeout_height: event.packet.timeout_height,
+                timeout_timestamp: event.packet.timeout_timestamp,
+            };
+
+            let receipt_path = ibc_union_spec::path::BatchReceiptsPath {
+                channel_id: event.packet.destination_channel.channel_id,
+                batch_hash: keccak256(packet.abi_encode()),
+            };
+
+            // the packet may have been received between the timeout being detected and now, in which case it can't be timed out anymore
+            let receipt = voyager_client
+                .query_ibc_state(
+                    origin_chain_id.clone(),
+                    QueryHeight::Specific(origin_chain_proof_height),
+                    receipt_path.clone(),
+                )
+                .await?
+                .state;
+
+            if receipt != COMMITMENT_NULL {
+                info!(%receipt, "packet was received on the counterparty chain, not timing out");
+
+                return Ok(noop());
+            }
+
+            let ordering = voyager_client
+                .query_ibc_state(
+                    target_chain_id.clone(),
+                    QueryHeight::Latest,
+                    ChannelOrderPath {
+                        channel_id: event.packet.source_channel.channel_id,
+                    },
+                )
+                .await?
+                .state;
+
+            let proof_unreceived = match ordering {
+                ChannelOrder::Unordered => {
+                    voyager_client
+                        .query_ibc_proof(
+                            origin_chain_id,
+                            QueryHeight::Specific(origin_chain_proof_height),
+                            receipt_path,
+                        )
+                        .await?
+                }
+                // on ordered channels, non-receipt is proven by the counterparty still
+                // expecting the sequence of the packet
+                ChannelOrder::Ordered => {
+                    let next_sequence_recv_path = NextSequenceRecvPath {
+                        channel_id: event.packet.destination_channel.channel_id,
+                    };
+
+                    let next_sequence_recv = voyager_client
+                        .query_ibc_state(
+                            origin_chain_id.clone(),
+                            QueryHeight::Specific(origin_chain_proof_height),
+                            next_sequence_recv_path.clone(),
+                        )
+                        .await?
+                        .state;
+
+                    debug!(%next_sequence_recv, "timing out packet on ordered channel");
+
+                    voyager_client
+                        .query_ibc_proof(
+                            origin_chain_id,
+                            QueryHeight::Specific(origin_chain_proof_height),
+                            next_sequence_recv_path,
+                        )
+                        .await?
+                }
+            };
+
+            let client_info = voyager_client
+                .client_info::<IbcUnion>(
+                    target_chain_id,
+                    event.packet.source_channel.connection.client_id,
+                )
+                .await?;
+
+            let encoded_proof_unreceived = voyager_client
+                .encode_proof::<IbcUnion>(
+                    client_info.client_type,
+                    client_info.ibc_interface,
+                    proof_unreceived.proof,
+                )
+                .await?;
+
+            Ok(data(IbcDatagram::new::<IbcUnion>(
+                ibc_union_spec::datagram::Datagram::from(
+                    ibc_union_spec::datagram::MsgPacketTimeout {
+                        packet: packet.into(),
+                        proof: encoded_proof_unreceived,
+                        proof_height: origin_chain_proof_height.height(),
+                    },
+                ),
+            )))
+        }
+
+        EventUnion::BatchSend(batch) => {
+            let source_channel_id = batch.events[0].packet.source_channel.channel_id;
+            let client_id = batch.events[0]
+                .packet
+                .destination_channel
+                .connection
+                .client_id;
+
+            let packets = batch.packets();
+
+            let proof_try = voyager_client
+                .query_ibc_proof(
+                    origin_chain_id,
+                    QueryHeight::Specific(origin_chain_proof_height),
+                    ibc_union_spec::path::BatchPacketsPath {
+                        channel_id: source_channel_id,
+                        batch_hash: batch.batch_hash(),
+                    },
+                )
+                .await?;
+
+            let client_info = voyager_client
+                .client_info::<IbcUnion>(target_chain_id, client_id)
+                .await?;
+
+            let encoded_proof_commitment = voyager_client
+                .encode_proof::<IbcUnion>(
+                    client_info.client_type,
+                    client_info.ibc_interface,
+                    proof_try.proof,
+                )
+                .await?;
+
+            Ok(data(IbcDatagram::new::<IbcUnion>(
+                ibc_union_spec::datagram::Datagram::from(ibc_union_spec::datagram::MsgPacketRecv {
+                    relayer_msgs: vec![vec![].into(); packets.len()],
+                    packets: packets.into_iter().map(Into::into).collect(),
+                    proof: encoded_proof_commitment,
+                    proof_height: origin_chain_proof_height.height(),
+                }),
+            )))
+        }
+
+        EventUnion::BatchAcks(batch) => {
+            let destination_channel_id = batch.events[0].packet.destination_channel.channel_id;
+            let client_id = batch.events[0].packet.source_channel.connection.client_id;
+
+            let proof_try = voyager_client
+                .query_ibc_proof(
+                    origin_chain_id,
+                    QueryHeight::Specific(origin_chain_proof_height),
+                    ibc_union_spec::path::BatchReceiptsPath {
+                        channel_id: destination_channel_id,
+                        batch_hash: batch.batch_hash(),
+                    },
+                )
+                .await?;
+
+            let client_info = voyager_client
+                .client_info::<IbcUnion>(target_chain_id, client_id)
+                .await?;
+
+            let encoded_proof_commitment = voyager_client
+                .encode_proof::<IbcUnion>(
+                    client_info.client_type,
+                    client_info.ibc_interface,
+                    proof_try.proof,
+                )
+                .await?;
+
+            Ok(data(IbcDatagram::new::<IbcUnion>(
+                ibc_union_spec::datagram::Datagram::from(
+                    ibc_union_spec::datagram::MsgPacketAcknowledgement {
+                        packets: batch.packets().into_iter().map(Into::into).collect(),
+                        acknowledgements: batch.acks(),
+                        proof: encoded_proof_commitment,
+                        proof_height: origin_chain_proof_height.height(),
+                    },
+                ),
             )))
         }
     }
 }
 
+/// The maximum amount of sequences that [`find_packet_sequence`] searches through.
+const MAX_PACKET_SEQUENCE_SCAN: u64 = 1000;
+
+/// How many packet commitments [`find_packet_sequence`] queries concurrently.
+const PACKET_SEQUENCE_QUERY_CONCURRENCY: usize = 16;
+
+/// Find the sequence that the packet with `packet_hash` was sent with on the ordered channel
+/// `channel_id`, searching through the sequences in `sequences` on `chain_id`. Packets that have
+/// been received, acknowledged or timed out are not found.
+///
+/// At most [`MAX_PACKET_SEQUENCE_SCAN`] sequences from the start of `sequences` are searched. If
+/// the packet is not found within them, an error is returned such that the search is retried once
+/// the preceding packets have been relayed.
+async fn find_packet_sequence(
+    voyager_client: &VoyagerClient,
+    chain_id: ChainId,
+    height: QueryHeight,
+    channel_id: ChannelId,
+    sequences: Range<u64>,
+    packet_hash: H256,
+) -> RpcResult<Option<u64>> {
+    let scanned = sequences.start
+        ..sequences
+            .end
+            .min(sequences.start.saturating_add(MAX_PACKET_SEQUENCE_SCAN));
+
+    let mut commitments = futures::stream::iter(scanned.clone())
+        .map(|sequence| {
+            let chain_id = chain_id.clone();
+            let height = height.clone();
+            async move {
+                voyager_client
+                    .query_ibc_state(
+                        chain_id,
+                        height,
+                        PacketSequencePath {
+                            channel_id,
+                            sequence,
+                        },
+                    )
+                    .await
+                    .map(|state| (sequence, state.state))
+            }
+        })
+        .buffered(PACKET_SEQUENCE_QUERY_CONCURRENCY);
+
+    while let Some((sequence, commitment)) = commitments.try_next().await? {
+        if commitment == packet_hash {
+            return Ok(Some(sequence));
+        }
+    }
+
+    if scanned.end < sequences.end {
+        return Err(ErrorObject::owned(
+            -1,
+            format!(
+                "packet not found in the first {MAX_PACKET_SEQUENCE_SCAN} of the sequences \
+                {}..{} on channel {channel_id}",
+                sequences.start, sequences.end
+            ),
+            None::<()>,
+        ));
+    }
+
+    Ok(None)
+}
+
+/// Wrap `datagram` in a [`SequencedDatagram`] if it is for a packet on an ordered channel.
+fn sequenced_datagram(
+    module: &Module,
+    sequence: Option<u64>,
+    datagram: ibc_union_spec::datagram::Datagram,
+) -> Op<VoyagerMessage> {
+    let datagram = IbcDatagram::new::<IbcUnion>(datagram);
+
+    match sequence {
+        Some(sequence) => data(PluginMessage::new(
+            module.plugin_name(),
+            ModuleData::from(SequencedDatagram { sequence, datagram }),
+        )),
+        None => data(datagram),
+    }
+}
+
 async fn do_make_msg_v1(
     voyager_client: &VoyagerClient,
     MakeMsg {
@@ -1153,6 +1644,7 @@ impl Module {
             let mut batchers_v1 =
                 HashMap::<ClientId, Vec<(usize, BatchableEvent<IbcClassic>)>>::new();
             let mut batchers_union = HashMap::<u32, Vec<(usize, BatchableEvent<IbcUnion>)>>::new();
+            let mut timeout_checks = Vec::<(Vec<usize>, Op<VoyagerMessage>)>::new();
 
             for (idx, msg) in msgs.into_iter().enumerate() {
                 let Op::Data(msg) = msg else {
@@ -1201,6 +1693,20 @@ impl Module {
 
                             trace!(%client_id, "batching event");
 
+                            // every packet sent to this chain is also checked for timeouts, in case it is not received in time
+                            if let FullEvent::PacketSend(packet_send) = &full_ibc_event {
+                                timeout_checks.push((
+                                    vec![idx],
+                                    call(PluginMessage::new(
+                                        self.plugin_name(),
+                                        ModuleCall::from(CheckPacketTimeout {
+                                            origin_chain_id: chain_event.chain_id.clone(),
+                                            event: packet_send.clone(),
+                                        }),
+                                    )),
+                                ));
+                            }
+
                             batchers_union.entry(client_id).or_default().push((
                                 idx,
                                 BatchableEvent {
@@ -1238,6 +1744,9 @@ impl Module {
                                     .or_default()
                                     .extend(message.events.into_iter().map(|event| (idx, event)));
                             }
+                            Ok(ModuleData::SequencedDatagram(datagram)) => {
+                                error!("unexpected message: {datagram:?}");
+                            }
                             Err(msg) => {
                                 error!("unexpected message: {msg:?}");
                             }
@@ -1269,7 +1778,9 @@ impl Module {
                 .into_iter()
                 .into_group_map()
                 .into_iter()
-                .map(|(client_id, events)| mk_ready_ops(client_id, events, self, voyager_client))
+                .map(|(client_id, events)| {
+                    mk_ready_ops_union(client_id, events, self, voyager_client)
+                })
                 .collect::<FuturesOrdered<_>>();
 
             Ok(PassResult {
@@ -1277,7 +1788,14 @@ impl Module {
                     .into_iter()
                     .chain(optimize_further_union)
                     .collect(),
-                ready: ready_v1.chain(ready_union).map(|x| x).try_collect().await?,
+                ready: ready_v1
+                    .chain(ready_union)
+                    .map(|x| x)
+                    .try_collect::<Vec<_>>()
+                    .await?
+                    .into_iter()
+                    .chain(timeout_checks)
+                    .collect(),
             })
         })
     }
@@ -1521,6 +2039,179 @@ where
     ))
 }
 
+/// Like [`mk_ready_ops`], but if [`Module::batch_commitments`] is enabled, packets and acknowledgements on the same unordered channel are first committed to as a batch on the origin chain. Once the batch commitment is finalized, they are handed back to be relayed as a single [`EventUnion::BatchSend`] or [`EventUnion::BatchAcks`] (see [`CommitBatch`]).
+async fn mk_ready_ops_union(
+    client_id: ibc_union_spec::types::ClientId,
+    events: Vec<(Vec<usize>, Vec<BatchableEvent<IbcUnion>>)>,
+    module: &Module,
+    voyager_client: &VoyagerClient,
+) -> RpcResult<(Vec<usize>, Op<VoyagerMessage>)> {
+    if !module.batch_commitments {
+        return mk_ready_ops(client_id, events, module, voyager_client).await;
+    }
+
+    let origin_chain_id = voyager_client
+        .client_meta::<IbcUnion>(module.chain_id.clone(), QueryHeight::Latest, client_id)
+        .await?
+        .chain_id;
+
+    // only packets on unordered channels can be batched
+    let mut unordered_channels = HashSet::new();
+
+    for channel_id in events
+        .iter()
+        .flat_map(|(_, events)| events)
+        .filter_map(|e| match &e.event {
+            EventUnion::PacketSend(event) => Some(event.packet.source_channel.channel_id),
+            EventUnion::WriteAck(event) => Some(event.packet.destination_channel.channel_id),
+            _ => None,
+        })
+        .unique()
+    {
+        let ordering = voyager_client
+            .query_ibc_state(
+                origin_chain_id.clone(),
+                QueryHeight::Latest,
+                ChannelOrderPath { channel_id },
+            )
+            .await?
+            .state;
+
+        if ordering == ChannelOrder::Unordered {
+            unordered_channels.insert(channel_id);
+        }
+    }
+
+    let mut idxs = vec![];
+    let mut remaining = vec![];
+    let mut commit_ops = vec![];
+
+    for (batch_idxs, events) in events {
+        idxs.extend(batch_idxs);
+
+        let (commitment_batches, events) = split_commitment_batches(events, |channel_id| {
+            unordered_channels.contains(&channel_id)
+        });
+
+        commit_ops.extend(
+            commitment_batches
+                .into_iter()
+                .map(|(first_seen_at, batch)| {
+                    call(PluginMessage::new(
+                        module.plugin_name(),
+                        ModuleCall::from(CommitBatch {
+                            origin_chain_id: origin_chain_id.clone(),
+                            client_id,
+                            first_seen_at,
+                            batch,
+                        }),
+                    ))
+                }),
+        );
+
+        if !events.is_empty() {
+            remaining.push((vec![], events));
+        }
+    }
+
+    let ready = if remaining.is_empty() {
+        None
+    } else {
+        Some(
+            mk_ready_ops(client_id, remaining, module, voyager_client)
+                .await?
+                .1,
+        )
+    };
+
+    Ok((idxs, conc(ready.into_iter().chain(commit_ops))))
+}
+
+/// Group the packets sent and acknowledgements written on the channels for which `is_batchable` returns true into [`CommitmentBatch`]es, along with the earliest time any of the events in each batch were first seen. Channels with only a single packet or acknowledgement can't be batched, and are returned along with all other events.
+fn split_commitment_batches(
+    events: Vec<BatchableEvent<IbcUnion>>,
+    is_batchable: impl Fn(ChannelId) -> bool,
+) -> (Vec<(u64, CommitmentBatch)>, Vec<BatchableEvent<IbcUnion>>) {
+    let mut sends = BTreeMap::<ChannelId, Vec<(u64, Height, PacketSend)>>::new();
+    let mut acks = BTreeMap::<ChannelId, Vec<(u64, Height, WriteAck)>>::new();
+    let mut rest = vec![];
+
+    for e in events {
+        match e.event {
+            EventUnion::PacketSend(event)
+                if is_batchable(event.packet.source_channel.channel_id) =>
+            {
+                sends
+                    .entry(event.packet.source_channel.channel_id)
+                    .or_default()
+                    .push((e.first_seen_at, e.provable_height, event));
+            }
+            EventUnion::WriteAck(event)
+                if is_batchable(event.packet.destination_channel.channel_id) =>
+            {
+                acks.entry(event.packet.destination_channel.channel_id)
+                    .or_default()
+                    .push((e.first_seen_at, e.provable_height, event));
+            }
+            event => rest.push(BatchableEvent { event, ..e }),
+        }
+    }
+
+    let mut batches = vec![];
+
+    for events in sends.into_values() {
+        if events.len() < 2 {
+            rest.extend(
+                events
+                    .into_iter()
+                    .map(|(first_seen_at, provable_height, event)| BatchableEvent {
+                        first_seen_at,
+                        provable_height,
+                        event: EventUnion::PacketSend(event),
+                    }),
+            );
+        } else {
+            batches.push((
+                events
+                    .iter()
+                    .map(|e| e.0)
+                    .min()
+                    .expect("events is non-empty; qed;"),
+                CommitmentBatch::from(BatchSend {
+                    events: events.into_iter().map(|e| e.2).collect(),
+                }),
+            ));
+        }
+    }
+
+    for events in acks.into_values() {
+        if events.len() < 2 {
+            rest.extend(
+                events
+                    .into_iter()
+                    .map(|(first_seen_at, provable_height, event)| BatchableEvent {
+                        first_seen_at,
+                        provable_height,
+                        event: EventUnion::WriteAck(event),
+                    }),
+            );
+        } else {
+            batches.push((
+                events
+                    .iter()
+                    .map(|e| e.0)
+                    .min()
+                    .expect("events is non-empty; qed;"),
+                CommitmentBatch::from(BatchAcks {
+                    events: events.into_iter().map(|e| e.2).collect(),
+                }),
+            ));
+        }
+    }
+
+    (batches, rest)
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -1543,4 +2234,110 @@ mod tests {
 
         let _config = serde_json::from_value::<Config>(config_json).unwrap();
     }
+
+    fn packet_metadata(
+        source_channel_id: ChannelId,
+        destination_channel_id: ChannelId,
+    ) -> ibc_union_spec::event::PacketMetadata {
+        let channel = |channel_id| ibc_union_spec::event::ChannelMetadata {
+            channel_id,
+            version: "ucs03-zkgm-0".to_owned(),
+            connection: ibc_union_spec::event::ConnectionMetadata {
+                client_id: 1,
+                connection_id: 1,
+            },
+        };
+
+        ibc_union_spec::event::PacketMetadata {
+            source_channel: channel(source_channel_id),
+            destination_channel: channel(destination_channel_id),
+            timeout_height: 0,
+            timeout_timestamp: 100,
+        }
+    }
+
+    fn send(first_seen_at: u64, source_channel_id: ChannelId) -> PacketSend {
+        PacketSend {
+            packet_data: first_seen_at.to_be_bytes().to_vec().into(),
+            packet: packet_metadata(source_channel_id, 10),
+        }
+    }
+
+    fn ack(first_seen_at: u64, destination_channel_id: ChannelId) -> WriteAck {
+        WriteAck {
+            packet_data: first_seen_at.to_be_bytes().to_vec().into(),
+            packet: packet_metadata(10, destination_channel_id),
+            acknowledgement: vec![1].into(),
+        }
+    }
+
+    fn packet_send(first_seen_at: u64, source_channel_id: ChannelId) -> BatchableEvent<IbcUnion> {
+        BatchableEvent {
+            first_seen_at,
+            provable_height: Height::new(first_seen_at),
+            event: EventUnion::PacketSend(send(first_seen_at, source_channel_id)),
+        }
+    }
+
+    fn write_ack(
+        first_seen_at: u64,
+        destination_channel_id: ChannelId,
+    ) -> BatchableEvent<IbcUnion> {
+        BatchableEvent {
+            first_seen_at,
+            provable_height: Height::new(first_seen_at),
+            event: EventUnion::WriteAck(ack(first_seen_at, destination_channel_id)),
+        }
+    }
+
+    #[test]
+    fn split_commitment_batches_groups_by_channel() {
+        let (batches, rest) = split_commitment_batches(
+            vec![
+                packet_send(3, 1),
+                packet_send(1, 1),
+                packet_send(2, 2),
+                write_ack(5, 1),
+                write_ack(4, 1),
+                write_ack(6, 3),
+            ],
+            |_| true,
+        );
+
+        assert_eq!(
+            batches,
+            vec![
+                (
+                    1,
+                    CommitmentBatch::from(BatchSend {
+                        events: vec![send(3, 1), send(1, 1)],
+                    })
+                ),
+                (
+                    4,
+                    CommitmentBatch::from(BatchAcks {
+                        events: vec![ack(5, 1), ack(4, 1)],
+                    })
+                ),
+            ]
+        );
+
+        // channels with a single packet or ack are not batched
+        assert_eq!(rest, vec![packet_send(2, 2), write_ack(6, 3)]);
+    }
+
+    #[test]
+    fn split_commitment_batches_skips_unbatchable_channels() {
+        let events = vec![
+            packet_send(1, 1),
+            packet_send(2, 1),
+            write_ack(3, 2),
+            write_ack(4, 2),
+        ];
+
+        let (batches, rest) = split_commitment_batches(events.clone(), |_| false);
+
+        assert_eq!(batches, vec![]);
+        assert_eq!(rest, events);
+    }
 }
@@ -7,13 +7,17 @@ use aptos_types::{
     transaction::{EntryFunction, RawTransaction},
 };
 use chain_utils::{
-    keyring::{ConcurrentKeyring, KeyringConfig, KeyringEntry},
+    keyring::{
+        monitor_balances, BalanceMonitorConfig, ConcurrentKeyring, KeyringConfig, KeyringEntry,
+        KeyringError, KeyringUnavailable, SignerBalance,
+    },
     BoxDynError,
 };
 use ibc_union_spec::{datagram::Datagram, IbcUnion};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
-    Extensions,
+    types::{ErrorObject, ErrorObjectOwned},
+    Extensions, RpcModule,
 };
 use move_core_types::{
     identifier::Identifier,
@@ -27,10 +31,10 @@ use voyager_message::{
     core::ChainId,
     data::Data,
     hook::SubmitTxHook,
-    module::{PluginInfo, PluginServer},
-    DefaultCmd, Plugin, PluginMessage, VoyagerMessage,
+    module::{self, KeyringServer, PluginInfo, PluginServer},
+    DefaultCmd, Plugin, PluginMessage, VoyagerMessage, FATAL_JSONRPC_ERROR_CODE,
 };
-use voyager_vm::{call, noop, pass::PassResult, Op, Visit};
+use voyager_vm::{call, defer, noop, now, pass::PassResult, seq, Op, Visit};
 
 use crate::{call::ModuleCall, callback::ModuleCallback};
 
@@ -38,6 +42,8 @@ pub mod call;
 pub mod callback;
 pub mod data;
 
+pub type AptosKeyring = ConcurrentKeyring<AccountAddress, Arc<Ed25519PrivateKey>>;
+
 #[tokio::main(flavor = "multi_thread")]
 async fn main() {
     Module::run().await
@@ -51,7 +57,9 @@ pub struct Module {
 
     pub aptos_client: aptos_rest_client::Client,
 
-    pub keyring: ConcurrentKeyring<AccountAddress, Arc<Ed25519PrivateKey>>,
+    pub keyring: AptosKeyring,
+
+    pub balance_monitor: BalanceMonitorConfig,
 }
 
 impl Plugin for Module {
@@ -66,14 +74,14 @@ impl Plugin for Module {
 
         let chain_id = aptos_client.get_index().await?.inner().chain_id;
 
-        Ok(Self {
-            chain_id: ChainId::new(chain_id.to_string()),
-            ibc_handler_address: config.ibc_handler_address,
-            aptos_client,
-            keyring: ConcurrentKeyring::new(
-                config.keyring.name,
-                config.keyring.keys.into_iter().map(|config| {
-                    let pk = aptos_crypto::ed25519::Ed25519PrivateKey::try_from(&*config.value())
+        let keyring = AptosKeyring::new(
+            config.keyring.name,
+            config
+                .keyring
+                .keys
+                .into_iter()
+                .map(|config| {
+                    let pk = aptos_crypto::ed25519::Ed25519PrivateKey::try_from(&*config.value()?)
                         .unwrap();
 
                     let address = (*<H256>::from(
@@ -85,13 +93,33 @@ impl Plugin for Module {
                     .get())
                     .into();
 
-                    KeyringEntry {
+                    Ok::<_, KeyringError>(KeyringEntry {
                         name: config.name(),
                         address,
                         signer: Arc::new(pk),
-                    }
-                }),
-            ),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter(),
+        );
+
+        tokio::spawn(monitor_balances(
+            keyring.clone(),
+            config.balance_monitor.clone(),
+            {
+                let aptos_client = aptos_client.clone();
+                let keyring = keyring.clone();
+
+                move || fetch_balances(aptos_client.clone(), keyring.clone())
+            },
+        ));
+
+        Ok(Self {
+            chain_id: ChainId::new(chain_id.to_string()),
+            ibc_handler_address: config.ibc_handler_address,
+            aptos_client,
+            keyring,
+            balance_monitor: config.balance_monitor,
         })
     }
 
@@ -105,6 +133,35 @@ impl Plugin for Module {
     async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
         match cmd {}
     }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        let mut module = PluginServer::into_rpc(self.clone());
+        module
+            .merge(KeyringServer::into_rpc(self))
+            .expect("plugin and keyring namespaces are disjoint; qed;");
+        module
+    }
+}
+
+/// Fetch the APT balances of all keys in `keyring`.
+async fn fetch_balances(
+    aptos_client: aptos_rest_client::Client,
+    keyring: AptosKeyring,
+) -> Result<Vec<SignerBalance<AccountAddress>>, BoxDynError> {
+    let mut balances = vec![];
+
+    for (key_name, address) in keyring.keys() {
+        let balance = aptos_client.get_account_balance(*address).await?;
+
+        balances.push(SignerBalance {
+            key_name: key_name.to_owned(),
+            address: *address,
+            balance: balance.inner().get().into(),
+            denom: "octa".to_owned(),
+        });
+    }
+
+    Ok(balances)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +172,9 @@ pub struct Config {
     pub ibc_handler_address: Address,
 
     pub keyring: KeyringConfig,
+
+    #[serde(default)]
+    pub balance_monitor: BalanceMonitorConfig,
 }
 
 impl aptos_move_ibc::ibc::ClientExt for Module {
@@ -141,6 +201,12 @@ impl aptos_move_ibc::channel_handshake::ClientExt for Module {
     }
 }
 
+impl aptos_move_ibc::timeout_packet::ClientExt for Module {
+    fn client(&self) -> &aptos_rest_client::Client {
+        &self.aptos_client
+    }
+}
+
 fn plugin_name(chain_id: &ChainId) -> String {
     pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
 
@@ -153,6 +219,24 @@ impl Module {
     }
 }
 
+#[async_trait]
+impl KeyringServer for Module {
+    async fn signer_balances(&self) -> RpcResult<Vec<module::SignerBalance>> {
+        Ok(self
+            .keyring
+            .balances()
+            .into_iter()
+            .map(|balance| module::SignerBalance {
+                enabled: self.keyring.in_rotation(&balance.address),
+                key_name: balance.key_name,
+                address: balance.address.to_string(),
+                balance: balance.balance,
+                denom: balance.denom,
+            })
+            .collect())
+    }
+}
+
 #[async_trait]
 impl PluginServer<ModuleCall, ModuleCallback> for Module {
     async fn run_pass(
@@ -218,7 +302,8 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                         dbg!(&account);
 
                         let msgs =
-                            process_msgs(self.ibc_handler_address.into(), self, msgs.clone()).await;
+                            process_msgs(self.ibc_handler_address.into(), self, msgs.clone())
+                                .await?;
 
                         let mut txs = vec![];
 
@@ -250,15 +335,25 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
 
                         // res.into_inner().transaction_failures
 
-                        Ok(noop())
+                        Ok::<_, ErrorObjectOwned>(noop())
                     }
                 })
                 .await
-                .unwrap_or_else(|| {
-                    Ok(call(PluginMessage::new(
+                .unwrap_or_else(|err| {
+                    let rewrap_msg = call(PluginMessage::new(
                         self.plugin_name(),
                         ModuleCall::SubmitTransaction(msgs),
-                    )))
+                    ));
+
+                    match err {
+                        KeyringUnavailable::Busy => Ok(rewrap_msg),
+                        // no key will be available until the balance monitor has seen a topped up
+                        // key, so there's no use in trying again before then
+                        KeyringUnavailable::NoKeyInRotation => Ok(seq([
+                            defer(now() + self.balance_monitor.interval_seconds.get()),
+                            rewrap_msg,
+                        ])),
+                    }
                 }),
         }
     }
@@ -288,12 +383,13 @@ async fn process_msgs<
     T: aptos_move_ibc::ibc::ClientExt
         + aptos_move_ibc::recv_packet::ClientExt
         + aptos_move_ibc::acknowledge_packet::ClientExt
-        + aptos_move_ibc::channel_handshake::ClientExt,
+        + aptos_move_ibc::channel_handshake::ClientExt
+        + aptos_move_ibc::timeout_packet::ClientExt,
 >(
     ibc_handler_address: AccountAddress,
     client: &T,
     msgs: Vec<Datagram>,
-) -> Vec<(Datagram, EntryFunction)> {
+) -> RpcResult<Vec<(Datagram, EntryFunction)>> {
     let mut data = vec![];
     for msg in msgs {
         let item = match msg.clone() {
@@ -315,6 +411,13 @@ async fn process_msgs<
                     (data.client_id, data.client_message.into_vec()),
                 ),
             ),
+            Datagram::SubmitMisbehaviour(data) => (
+                msg,
+                client.submit_misbehaviour(
+                    ibc_handler_address,
+                    (data.client_id, data.misbehaviour.into_vec()),
+                ),
+            ),
             Datagram::ConnectionOpenInit(data) => (
                 msg,
                 client.connection_open_init(
@@ -429,6 +532,39 @@ async fn process_msgs<
                     ),
                 )
             }
+            Datagram::ChannelCloseInit(data) => {
+                let port_id = client
+                    .get_module(ibc_handler_address, None, (data.channel_id,))
+                    .await
+                    .unwrap();
+                (
+                    msg,
+                    client.channel_close_init(
+                        ibc_handler_address,
+                        (port_id.into(), data.channel_id),
+                        (ibc_app_witness(port_id.into()),),
+                    ),
+                )
+            }
+            Datagram::ChannelCloseConfirm(data) => {
+                let port_id = client
+                    .get_module(ibc_handler_address, None, (data.channel_id,))
+                    .await
+                    .unwrap();
+                (
+                    msg,
+                    client.channel_close_confirm(
+                        ibc_handler_address,
+                        (
+                            port_id.into(),
+                            data.channel_id,
+                            data.proof_init.into_vec(),
+                            data.proof_height,
+                        ),
+                        (ibc_app_witness(port_id.into()),),
+                    ),
+                )
+            }
             Datagram::PacketRecv(data) => {
                 let (
                     source_channels,
@@ -501,10 +637,42 @@ async fn process_msgs<
                     ),
                 )
             }
-            _ => todo!(),
+            Datagram::PacketTimeout(data) => {
+                let port_id = client
+                    .get_module(ibc_handler_address, None, (data.packet.source_channel_id,))
+                    .await
+                    .unwrap();
+
+                (
+                    msg,
+                    client.timeout_packet(
+                        ibc_handler_address,
+                        (
+                            port_id.into(),
+                            data.packet.source_channel_id,
+                            data.packet.destination_channel_id,
+                            data.packet.data.into_vec(),
+                            data.packet.timeout_height,
+                            data.packet.timeout_timestamp,
+                            data.proof.into_vec(),
+                            data.proof_height,
+                            // next_sequence_recv, unused for unordered channels
+                            0,
+                        ),
+                        (ibc_app_witness(port_id.into()),),
+                    ),
+                )
+            }
+            Datagram::IntentPacketRecv(_) | Datagram::BatchSend(_) | Datagram::BatchAcks(_) => {
+                return Err(ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!("{} is not supported by the aptos ibc handler", msg.name()),
+                    None::<()>,
+                ));
+            }
         };
         data.push(item);
     }
 
-    data
+    Ok(data)
 }
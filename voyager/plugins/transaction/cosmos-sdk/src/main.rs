@@ -5,15 +5,19 @@ use std::collections::VecDeque;
 use chain_utils::{
     cosmos_sdk::{
         cosmos_sdk_error::{ChannelError, ClientError, CosmosSdkError, IbcWasmError, SdkError},
-        CosmosKeyring, GasConfig,
+        GasConfig,
     },
-    keyring::{KeyringConfig, KeyringEntry},
+    keyring::{
+        monitor_balances, BalanceMonitorConfig, ConcurrentKeyring, KeyringConfig, KeyringEntry,
+        KeyringUnavailable,
+    },
+    signer::{CosmosSdkSigner, SignerError},
     BoxDynError,
 };
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     types::ErrorObject,
-    Extensions,
+    Extensions, RpcModule,
 };
 use prost::Message;
 use serde::{Deserialize, Serialize};
@@ -35,17 +39,16 @@ use unionlabs::{
     encoding::{EncodeAs, Proto},
     google::protobuf::any::{mk_any, Any},
     primitives::H256,
-    signer::CosmosSigner,
     ErrorReporter,
 };
 use voyager_message::{
     core::ChainId,
     data::Data,
     hook::SubmitTxHook,
-    module::{PluginInfo, PluginServer},
+    module::{KeyringServer, PluginInfo, PluginServer, SignerBalance},
     DefaultCmd, Plugin, PluginMessage, VoyagerMessage, FATAL_JSONRPC_ERROR_CODE,
 };
-use voyager_vm::{call, conc, noop, pass::PassResult, Op, Visit};
+use voyager_vm::{call, conc, defer, noop, now, pass::PassResult, seq, Op, Visit};
 
 use crate::{
     call::{IbcMessage, ModuleCall},
@@ -56,6 +59,8 @@ pub mod call;
 pub mod callback;
 pub mod data;
 
+pub type CosmosKeyring = ConcurrentKeyring<String, CosmosSdkSigner>;
+
 #[tokio::main(flavor = "multi_thread")]
 async fn main() {
     Module::run().await
@@ -70,6 +75,7 @@ pub struct Module {
     pub grpc_url: String,
     pub gas_config: GasConfig,
     pub bech32_prefix: String,
+    pub balance_monitor: BalanceMonitorConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,6 +87,8 @@ pub struct Config {
     pub rpc_url: String,
     pub grpc_url: String,
     pub gas_config: GasConfig,
+    #[serde(default)]
+    pub balance_monitor: BalanceMonitorConfig,
 }
 
 impl Plugin for Module {
@@ -104,31 +112,49 @@ impl Plugin for Module {
         .into_inner()
         .bech32_prefix;
 
-        Ok(Self {
-            ibc_host_contract_address: config.ibc_host_contract_address,
-            keyring: CosmosKeyring::new(
-                config.keyring.name,
-                config.keyring.keys.into_iter().map(|entry| {
-                    let signer = CosmosSigner::new(
-                        bip32::secp256k1::ecdsa::SigningKey::from_bytes(
-                            entry.value().as_slice().into(),
-                        )
-                        .expect("invalid private key"),
-                        bech32_prefix.clone(),
-                    );
+        let mut keys = vec![];
 
-                    KeyringEntry {
-                        name: entry.name(),
-                        address: signer.to_string(),
-                        signer,
+        for entry in config.keyring.keys {
+            let signer = CosmosSdkSigner::new(entry.secp256k1_signer().await?, &bech32_prefix);
+
+            keys.push(KeyringEntry {
+                name: entry.name(),
+                address: signer.to_string(),
+                signer,
+            });
+        }
+
+        let keyring = CosmosKeyring::new(config.keyring.name, keys.into_iter());
+
+        tokio::spawn(monitor_balances(
+            keyring.clone(),
+            config.balance_monitor.clone(),
+            {
+                let keyring = keyring.clone();
+                let gas_denom = config.gas_config.gas_denom.clone();
+                let grpc_url = config.grpc_url.clone();
+
+                move || {
+                    let keyring = keyring.clone();
+                    let gas_denom = gas_denom.clone();
+                    let grpc_url = grpc_url.clone();
+
+                    async move {
+                        chain_utils::cosmos_sdk::fetch_balances(&keyring, gas_denom, grpc_url).await
                     }
-                }),
-            ),
+                }
+            },
+        ));
+
+        Ok(Self {
+            ibc_host_contract_address: config.ibc_host_contract_address,
+            keyring,
             comtbft_client: tm_client,
             chain_id: ChainId::new(chain_id),
             grpc_url: config.grpc_url,
             gas_config: config.gas_config,
             bech32_prefix,
+            balance_monitor: config.balance_monitor,
         })
     }
 
@@ -142,6 +168,14 @@ impl Plugin for Module {
     async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
         match cmd {}
     }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        let mut module = PluginServer::into_rpc(self.clone());
+        module
+            .merge(KeyringServer::into_rpc(self))
+            .expect("plugin and keyring namespaces are disjoint; qed;");
+        module
+    }
 }
 
 fn plugin_name(chain_id: &ChainId) -> String {
@@ -287,16 +321,22 @@ impl Module {
             || PluginMessage::new(self.plugin_name(), ModuleCall::SubmitTransaction(msgs));
 
         match res {
-            Some(Err(BroadcastTxCommitError::AccountSequenceMismatch(_))) => Ok(call(rewrap_msg())),
-            Some(Err(BroadcastTxCommitError::OutOfGas)) => Ok(call(rewrap_msg())),
-            Some(Err(BroadcastTxCommitError::QueryLatestHeight(err))) => {
+            Ok(Err(BroadcastTxCommitError::AccountSequenceMismatch(_))) => Ok(call(rewrap_msg())),
+            Ok(Err(BroadcastTxCommitError::OutOfGas)) => Ok(call(rewrap_msg())),
+            Ok(Err(BroadcastTxCommitError::QueryLatestHeight(err))) => {
                 error!(error = %ErrorReporter(err), "error querying latest height");
 
                 Ok(call(rewrap_msg()))
             }
-            Some(res) => res.map(|()| noop()),
+            Ok(res) => res.map(|()| noop()),
             // None => Ok(seq([defer_relative(1), effect(WithChainId{chain_id: self.chain_id.clone(), message: msg})])),
-            None => Ok(call(rewrap_msg())),
+            Err(KeyringUnavailable::Busy) => Ok(call(rewrap_msg())),
+            // no key will be available until the balance monitor has seen a topped up key, so
+            // there's no use in trying again before then
+            Err(KeyringUnavailable::NoKeyInRotation) => Ok(seq([
+                defer(now() + self.balance_monitor.interval_seconds.get()),
+                call(rewrap_msg()),
+            ])),
         }
     }
 
@@ -306,22 +346,14 @@ impl Module {
     /// - return (tx_hash, gas_used)
     pub async fn broadcast_tx_commit(
         &self,
-        signer: &CosmosSigner,
+        signer: &CosmosSdkSigner,
         messages: impl IntoIterator<Item = protos::google::protobuf::Any> + Clone,
         memo: String,
     ) -> Result<(H256, BoundedI64<0, { i64::MAX }>), BroadcastTxCommitError> {
         let account = self.account_info(&signer.to_string()).await;
 
         let (tx_body, mut auth_info, simulation_gas_info) =
-            match self.simulate_tx(signer, messages, memo).await {
-                Ok((tx_body, auth_info, simulation_gas_info)) => {
-                    (tx_body, auth_info, simulation_gas_info)
-                }
-                Err((_tx_body, _auth_info, err)) => {
-                    return Err(BroadcastTxCommitError::SimulateTx(err))
-                }
-            };
-        // .map_err(BroadcastTxCommitError::SimulateTx)?;
+            self.simulate_tx(signer, messages, memo).await?;
 
         info!(
             gas_used = %simulation_gas_info.gas_used,
@@ -348,7 +380,7 @@ impl Module {
                 }
                 .encode_as::<Proto>(),
             )
-            .expect("signing failed")
+            .await?
             .to_bytes()
             .to_vec();
 
@@ -474,10 +506,10 @@ impl Module {
 
     pub async fn simulate_tx(
         &self,
-        signer: &CosmosSigner,
+        signer: &CosmosSdkSigner,
         messages: impl IntoIterator<Item = protos::google::protobuf::Any> + Clone,
         memo: String,
-    ) -> Result<(TxBody, AuthInfo, GasInfo), (TxBody, AuthInfo, tonic::Status)> {
+    ) -> Result<(TxBody, AuthInfo, GasInfo), BroadcastTxCommitError> {
         use protos::cosmos::tx;
 
         let account = self.account_info(&signer.to_string()).await;
@@ -521,7 +553,7 @@ impl Module {
                 }
                 .encode_as::<Proto>(),
             )
-            .expect("signing failed")
+            .await?
             .to_bytes()
             .to_vec();
 
@@ -548,7 +580,7 @@ impl Module {
             )),
             Err(err) => {
                 info!(error = %ErrorReporter(&err), "tx simulation failed");
-                Err((tx_body, auth_info, err))
+                Err(BroadcastTxCommitError::SimulateTx(err))
             }
         }
     }
@@ -594,6 +626,26 @@ pub enum BroadcastTxCommitError {
     IbcUnionError(ibc_union::ContractErrorKind),
     #[error("out of gas")]
     OutOfGas,
+    #[error("error signing tx")]
+    Sign(#[from] SignerError),
+}
+
+#[async_trait]
+impl KeyringServer for Module {
+    async fn signer_balances(&self) -> RpcResult<Vec<SignerBalance>> {
+        Ok(self
+            .keyring
+            .balances()
+            .into_iter()
+            .map(|balance| SignerBalance {
+                enabled: self.keyring.in_rotation(&balance.address),
+                key_name: balance.key_name,
+                address: balance.address,
+                balance: balance.balance,
+                denom: balance.denom,
+            })
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -707,7 +759,7 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
 
 fn process_msgs(
     msgs: Vec<IbcMessage>,
-    signer: &CosmosSigner,
+    signer: &CosmosSdkSigner,
     ibc_host_contract_address: Bech32<H256>,
 ) -> Vec<(IbcMessage, protos::google::protobuf::Any)> {
     msgs.into_iter()
@@ -890,6 +942,23 @@ fn process_msgs(
                             funds: vec![],
                         })
                     }
+                    ibc_union_spec::datagram::Datagram::SubmitMisbehaviour(
+                        msg_submit_misbehaviour,
+                    ) => mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                        sender: signer.to_string(),
+                        contract: ibc_host_contract_address.to_string(),
+                        msg: serde_json::to_vec(
+                            &ibc_union_msg::msg::ExecuteMsg::SubmitMisbehaviour(
+                                ibc_union_msg::msg::MsgSubmitMisbehaviour {
+                                    client_id: msg_submit_misbehaviour.client_id,
+                                    misbehaviour: msg_submit_misbehaviour.misbehaviour,
+                                    relayer: signer.to_string(),
+                                },
+                            ),
+                        )
+                        .unwrap(),
+                        funds: vec![],
+                    }),
                     ibc_union_spec::datagram::Datagram::ConnectionOpenInit(
                         msg_connection_open_init,
                     ) => mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
@@ -977,6 +1046,7 @@ fn process_msgs(
                                 counterparty_port_id: msg_channel_open_init.counterparty_port_id,
                                 connection_id: msg_channel_open_init.connection_id,
                                 version: msg_channel_open_init.version,
+                                ordering: msg_channel_open_init.ordering,
                             },
                         );
 
@@ -994,6 +1064,7 @@ fn process_msgs(
                                     .unwrap(),
                                 channel: msg_channel_open_try.channel,
                                 counterparty_version: msg_channel_open_try.counterparty_version,
+                                ordering: msg_channel_open_try.ordering,
                                 proof_init: msg_channel_open_try.proof_init,
                                 proof_height: msg_channel_open_try.proof_height,
                                 relayer: signer.to_string(),
@@ -1048,12 +1119,41 @@ fn process_msgs(
                         })
                     }
                     ibc_union_spec::datagram::Datagram::ChannelCloseInit(
-                        _msg_channel_close_init,
-                    ) => todo!(),
+                        msg_channel_close_init,
+                    ) => {
+                        let channel_close_init = ibc_union_msg::msg::ExecuteMsg::ChannelCloseInit(
+                            ibc_union_msg::msg::MsgChannelCloseInit {
+                                channel_id: msg_channel_close_init.channel_id,
+                                relayer: signer.to_string(),
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&channel_close_init).unwrap(),
+                            funds: vec![],
+                        })
+                    }
                     ibc_union_spec::datagram::Datagram::ChannelCloseConfirm(
-                        _msg_channel_close_confirm,
+                        msg_channel_close_confirm,
                     ) => {
-                        todo!()
+                        let channel_close_confirm =
+                            ibc_union_msg::msg::ExecuteMsg::ChannelCloseConfirm(
+                                ibc_union_msg::msg::MsgChannelCloseConfirm {
+                                    channel_id: msg_channel_close_confirm.channel_id,
+                                    proof_init: msg_channel_close_confirm.proof_init,
+                                    proof_height: msg_channel_close_confirm.proof_height,
+                                    relayer: signer.to_string(),
+                                },
+                            );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&channel_close_confirm).unwrap(),
+                            funds: vec![],
+                        })
                     }
                     ibc_union_spec::datagram::Datagram::PacketRecv(msg_packet_recv) => {
                         let packet_recv = ibc_union_msg::msg::ExecuteMsg::PacketRecv(
@@ -1101,14 +1201,85 @@ fn process_msgs(
                             funds: vec![],
                         })
                     }
-                    ibc_union_spec::datagram::Datagram::PacketTimeout(_msg_packet_timeout) => {
-                        todo!()
+                    ibc_union_spec::datagram::Datagram::PacketTimeout(msg_packet_timeout) => {
+                        let packet_timeout = ibc_union_msg::msg::ExecuteMsg::PacketTimeout(
+                            ibc_union_msg::msg::MsgPacketTimeout {
+                                packet: msg_packet_timeout.packet,
+                                proof: msg_packet_timeout.proof,
+                                proof_height: msg_packet_timeout.proof_height,
+                                relayer: signer.to_string(),
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&packet_timeout).unwrap(),
+                            funds: vec![],
+                        })
                     }
                     ibc_union_spec::datagram::Datagram::IntentPacketRecv(
-                        _msg_intent_packet_recv,
-                    ) => todo!(),
-                    ibc_union_spec::datagram::Datagram::BatchSend(_msg_batch_send) => todo!(),
-                    ibc_union_spec::datagram::Datagram::BatchAcks(_msg_batch_acks) => todo!(),
+                        msg_intent_packet_recv,
+                    ) => {
+                        let intent_packet_recv = ibc_union_msg::msg::ExecuteMsg::IntentPacketRecv(
+                            ibc_union_msg::msg::MsgIntentPacketRecv {
+                                packets: msg_intent_packet_recv
+                                    .packets
+                                    .into_iter()
+                                    .map(Into::into)
+                                    .collect(),
+                                market_maker_msgs: msg_intent_packet_recv.market_maker_msgs,
+                                market_maker: signer.to_string(),
+                                empty_proof: Default::default(),
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&intent_packet_recv).unwrap(),
+                            funds: vec![],
+                        })
+                    }
+                    ibc_union_spec::datagram::Datagram::BatchSend(msg_batch_send) => {
+                        let batch_send = ibc_union_msg::msg::ExecuteMsg::BatchSend(
+                            ibc_union_msg::msg::MsgBatchSend {
+                                source_channel: msg_batch_send.source_channel_id,
+                                packets: msg_batch_send
+                                    .packets
+                                    .into_iter()
+                                    .map(Into::into)
+                                    .collect(),
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&batch_send).unwrap(),
+                            funds: vec![],
+                        })
+                    }
+                    ibc_union_spec::datagram::Datagram::BatchAcks(msg_batch_acks) => {
+                        let batch_acks = ibc_union_msg::msg::ExecuteMsg::BatchAcks(
+                            ibc_union_msg::msg::MsgBatchAcks {
+                                source_channel: msg_batch_acks.source_channel_id,
+                                packets: msg_batch_acks
+                                    .packets
+                                    .into_iter()
+                                    .map(Into::into)
+                                    .collect(),
+                                acks: msg_batch_acks.acks,
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&batch_acks).unwrap(),
+                            funds: vec![],
+                        })
+                    }
                 },
             };
 
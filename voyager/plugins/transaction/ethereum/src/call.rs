@@ -1,3 +1,4 @@
+use alloy::primitives::Address;
 use enumorph::Enumorph;
 use macros::model;
 
@@ -5,4 +6,14 @@ use macros::model;
 #[derive(Enumorph)]
 pub enum ModuleCall {
     SubmitMulticall(Vec<ibc_union_spec::datagram::Datagram>),
+    CheckPendingTx(CheckPendingTx),
+}
+
+/// Check whether the pending transaction of `signer` with `nonce` has been included. This is
+/// queued for transactions that are still stuck after being replaced the maximum amount of times,
+/// instead of waiting on them while holding the signer.
+#[model]
+pub struct CheckPendingTx {
+    pub signer: Address,
+    pub nonce: u64,
 }
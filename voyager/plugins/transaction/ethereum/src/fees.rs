@@ -0,0 +1,524 @@
+use std::time::Duration;
+
+use alloy::{
+    eips::BlockNumberOrTag,
+    providers::{Provider, RootProvider},
+    transports::{BoxTransport, TransportError},
+};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// The minimum fee increase that nodes will accept for a replacement transaction.
+pub const MIN_REPLACEMENT_BUMP_PERCENT: u128 = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeConfig {
+    /// The amount of blocks to query the fee history for when estimating the priority fee.
+    #[serde(default = "default_fee_history_blocks")]
+    pub fee_history_blocks: u64,
+    /// The percentile of the priority fees paid in each block to use. The median of this
+    /// percentile across all blocks in the fee history is used as the priority fee.
+    #[serde(default = "default_reward_percentile")]
+    pub reward_percentile: f64,
+    /// The max fee per gas is set to this percentage of the next block's base fee, plus the
+    /// priority fee. The default of 200% allows for the base fee to double before the transaction
+    /// becomes unincludable.
+    #[serde(default = "default_base_fee_multiplier_percent")]
+    pub base_fee_multiplier_percent: u128,
+    /// Used as the priority fee if none of the blocks in the fee history contain any transactions.
+    #[serde(default)]
+    pub min_priority_fee_per_gas: u128,
+    /// The maximum priority fee per gas to pay, if any.
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// How much to increase the fees by when replacing a stuck transaction. Most nodes require an
+    /// increase of at least 10% to accept a replacement.
+    #[serde(default = "default_bump_percent")]
+    pub bump_percent: u128,
+    /// How long to wait for a transaction to be included before replacing it with a higher fee.
+    #[serde(default = "default_stuck_timeout_seconds")]
+    pub stuck_timeout_seconds: u64,
+    /// The maximum amount of times to replace a stuck transaction. Once reached, the signer is
+    /// released and the transaction is checked on every `stuck_timeout_seconds` without further
+    /// increasing the fees.
+    #[serde(default = "default_max_bumps")]
+    pub max_bumps: u32,
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            fee_history_blocks: default_fee_history_blocks(),
+            reward_percentile: default_reward_percentile(),
+            base_fee_multiplier_percent: default_base_fee_multiplier_percent(),
+            min_priority_fee_per_gas: 0,
+            max_priority_fee_per_gas: None,
+            bump_percent: default_bump_percent(),
+            stuck_timeout_seconds: default_stuck_timeout_seconds(),
+            max_bumps: default_max_bumps(),
+        }
+    }
+}
+
+#[must_use]
+#[inline]
+pub const fn default_fee_history_blocks() -> u64 {
+    10
+}
+
+#[must_use]
+#[inline]
+pub const fn default_reward_percentile() -> f64 {
+    50.0
+}
+
+#[must_use]
+#[inline]
+pub const fn default_base_fee_multiplier_percent() -> u128 {
+    200
+}
+
+#[must_use]
+#[inline]
+pub const fn default_bump_percent() -> u128 {
+    20
+}
+
+#[must_use]
+#[inline]
+pub const fn default_stuck_timeout_seconds() -> u64 {
+    60
+}
+
+#[must_use]
+#[inline]
+pub const fn default_max_bumps() -> u32 {
+    5
+}
+
+impl FeeConfig {
+    #[must_use]
+    pub fn stuck_timeout(&self) -> Duration {
+        Duration::from_secs(self.stuck_timeout_seconds)
+    }
+
+    /// The fees to replace a stuck transaction that was broadcast with `current` fees with, given
+    /// the freshly `estimated` fees. The fees are increased by at least `bump_percent`, limited to
+    /// the configured caps.
+    ///
+    /// Returns `None` if the caps prevent the fees from being increased enough for the
+    /// replacement to be accepted by nodes.
+    #[must_use]
+    pub fn replacement_fees(
+        &self,
+        current: Fees,
+        estimated: Fees,
+        max_gas_price: Option<u128>,
+    ) -> Option<Fees> {
+        let fees = estimated
+            .max(current.bumped(self.bump_percent))
+            .capped(0, max_gas_price, self.max_priority_fee_per_gas)
+            .expect("a base fee of 0 is never above the cap; qed;");
+
+        (fees.max_fee_per_gas()
+            >= current
+                .bumped(MIN_REPLACEMENT_BUMP_PERCENT)
+                .max_fee_per_gas())
+        .then_some(fees)
+    }
+
+    /// Check that the values in this config are within their valid ranges.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=100.0).contains(&self.reward_percentile) {
+            return Err(format!(
+                "reward_percentile must be between 0 and 100, found {}",
+                self.reward_percentile
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// The fees to submit a transaction with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fees {
+    Legacy {
+        gas_price: u128,
+    },
+    Eip1559 {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+}
+
+/// The estimated fees for the next block, along with the base fee (or gas price, for legacy
+/// transactions) they were estimated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimate {
+    pub base_fee: u128,
+    pub fees: Fees,
+}
+
+impl Fees {
+    /// The maximum amount that will be paid per unit of gas.
+    pub fn max_fee_per_gas(&self) -> u128 {
+        match self {
+            Fees::Legacy { gas_price } => *gas_price,
+            Fees::Eip1559 {
+                max_fee_per_gas, ..
+            } => *max_fee_per_gas,
+        }
+    }
+
+    /// Increase all fees by `percent`, rounding up such that the fees always increase.
+    #[must_use]
+    pub fn bumped(self, percent: u128) -> Self {
+        let bump = |fee: u128| fee.saturating_add(fee.saturating_mul(percent).div_ceil(100).max(1));
+
+        match self {
+            Fees::Legacy { gas_price } => Fees::Legacy {
+                gas_price: bump(gas_price),
+            },
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => Fees::Eip1559 {
+                max_fee_per_gas: bump(max_fee_per_gas),
+                max_priority_fee_per_gas: bump(max_priority_fee_per_gas),
+            },
+        }
+    }
+
+    /// The higher of each of the fees in `self` and `other`.
+    #[must_use]
+    pub fn max(self, other: Self) -> Self {
+        match (self, other) {
+            (
+                Fees::Eip1559 {
+                    max_fee_per_gas: a_max_fee,
+                    max_priority_fee_per_gas: a_priority_fee,
+                },
+                Fees::Eip1559 {
+                    max_fee_per_gas: b_max_fee,
+                    max_priority_fee_per_gas: b_priority_fee,
+                },
+            ) => Fees::Eip1559 {
+                max_fee_per_gas: a_max_fee.max(b_max_fee),
+                max_priority_fee_per_gas: a_priority_fee.max(b_priority_fee),
+            },
+            (a, b) => Fees::Legacy {
+                gas_price: a.max_fee_per_gas().max(b.max_fee_per_gas()),
+            },
+        }
+    }
+
+    /// Limit the fees to the provided caps.
+    ///
+    /// Returns `None` if `base_fee` is already above `max_fee_per_gas_cap`, since a transaction
+    /// with capped fees would not be included.
+    #[must_use]
+    pub fn capped(
+        self,
+        base_fee: u128,
+        max_fee_per_gas_cap: Option<u128>,
+        max_priority_fee_per_gas_cap: Option<u128>,
+    ) -> Option<Self> {
+        if max_fee_per_gas_cap.is_some_and(|cap| base_fee > cap) {
+            return None;
+        }
+
+        let max_fee_per_gas_cap = max_fee_per_gas_cap.unwrap_or(u128::MAX);
+        let max_priority_fee_per_gas_cap = max_priority_fee_per_gas_cap.unwrap_or(u128::MAX);
+
+        Some(match self {
+            Fees::Legacy { gas_price } => Fees::Legacy {
+                gas_price: gas_price.min(max_fee_per_gas_cap),
+            },
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                let max_fee_per_gas = max_fee_per_gas.min(max_fee_per_gas_cap);
+
+                Fees::Eip1559 {
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas: max_priority_fee_per_gas
+                        .min(max_priority_fee_per_gas_cap)
+                        .min(max_fee_per_gas),
+                }
+            }
+        })
+    }
+}
+
+/// Estimate the fees for a transaction to be included in the next few blocks.
+///
+/// For EIP-1559 transactions, the priority fee is the median of the configured reward percentile
+/// across the fee history, and the max fee is a multiple of the next block's base fee plus the
+/// priority fee. For legacy transactions, the gas price reported by the node is used.
+pub async fn estimate(
+    provider: &RootProvider<BoxTransport>,
+    config: &FeeConfig,
+    legacy: bool,
+) -> Result<FeeEstimate, TransportError> {
+    if legacy {
+        let gas_price = provider.get_gas_price().await?;
+
+        return Ok(FeeEstimate {
+            base_fee: gas_price,
+            fees: Fees::Legacy { gas_price },
+        });
+    }
+
+    let fee_history = provider
+        .get_fee_history(
+            config.fee_history_blocks,
+            BlockNumberOrTag::Latest,
+            &[config.reward_percentile],
+        )
+        .await?;
+
+    // the last entry is the base fee of the next block
+    let base_fee = fee_history
+        .base_fee_per_gas
+        .last()
+        .copied()
+        .unwrap_or_default();
+
+    let rewards = fee_history.reward.unwrap_or_default();
+
+    let priority_fee = median_reward(&rewards).max(config.min_priority_fee_per_gas);
+
+    let max_fee_per_gas = base_fee
+        .saturating_mul(config.base_fee_multiplier_percent)
+        .div_ceil(100)
+        .saturating_add(priority_fee);
+
+    debug!(
+        %base_fee,
+        %priority_fee,
+        %max_fee_per_gas,
+        blocks = rewards.len(),
+        "estimated fees"
+    );
+
+    Ok(FeeEstimate {
+        base_fee,
+        fees: Fees::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas: priority_fee,
+        },
+    })
+}
+
+/// The median of the rewards at the (single) requested percentile in each block of the fee
+/// history, or 0 if none of the blocks contain any transactions.
+fn median_reward(rewards: &[Vec<u128>]) -> u128 {
+    // empty blocks report a reward of 0, which would skew the estimate down
+    let mut rewards = rewards
+        .iter()
+        .filter_map(|rewards| rewards.first().copied())
+        .filter(|reward| *reward > 0)
+        .collect::<Vec<_>>();
+
+    rewards.sort_unstable();
+
+    rewards.get(rewards.len() / 2).copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EIP1559: Fees = Fees::Eip1559 {
+        max_fee_per_gas: 200,
+        max_priority_fee_per_gas: 10,
+    };
+
+    #[test]
+    fn bumped() {
+        assert_eq!(
+            EIP1559.bumped(20),
+            Fees::Eip1559 {
+                max_fee_per_gas: 240,
+                max_priority_fee_per_gas: 12,
+            }
+        );
+        assert_eq!(
+            Fees::Legacy { gas_price: 100 }.bumped(10),
+            Fees::Legacy { gas_price: 110 }
+        );
+    }
+
+    #[test]
+    fn bumped_rounds_up() {
+        // 10% of 5 is 0.5, which must still increase the fee
+        assert_eq!(
+            Fees::Legacy { gas_price: 5 }.bumped(10),
+            Fees::Legacy { gas_price: 6 }
+        );
+        // the fees always increase, even by 0%
+        assert_eq!(
+            Fees::Legacy { gas_price: 0 }.bumped(0),
+            Fees::Legacy { gas_price: 1 }
+        );
+        assert_eq!(
+            Fees::Legacy {
+                gas_price: u128::MAX
+            }
+            .bumped(10),
+            Fees::Legacy {
+                gas_price: u128::MAX
+            }
+        );
+    }
+
+    #[test]
+    fn max() {
+        assert_eq!(
+            EIP1559.max(Fees::Eip1559 {
+                max_fee_per_gas: 100,
+                max_priority_fee_per_gas: 20,
+            }),
+            Fees::Eip1559 {
+                max_fee_per_gas: 200,
+                max_priority_fee_per_gas: 20,
+            }
+        );
+        assert_eq!(
+            EIP1559.max(Fees::Legacy { gas_price: 150 }),
+            Fees::Legacy { gas_price: 200 }
+        );
+        assert_eq!(
+            Fees::Legacy { gas_price: 250 }.max(EIP1559),
+            Fees::Legacy { gas_price: 250 }
+        );
+    }
+
+    #[test]
+    fn capped() {
+        assert_eq!(EIP1559.capped(100, None, None), Some(EIP1559));
+        assert_eq!(
+            EIP1559.capped(100, Some(150), Some(5)),
+            Some(Fees::Eip1559 {
+                max_fee_per_gas: 150,
+                max_priority_fee_per_gas: 5,
+            })
+        );
+        assert_eq!(
+            Fees::Legacy { gas_price: 100 }.capped(50, Some(80), None),
+            Some(Fees::Legacy { gas_price: 80 })
+        );
+    }
+
+    #[test]
+    fn capped_priority_fee_is_at_most_max_fee() {
+        assert_eq!(
+            EIP1559.capped(0, Some(8), None),
+            Some(Fees::Eip1559 {
+                max_fee_per_gas: 8,
+                max_priority_fee_per_gas: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn capped_base_fee_above_cap() {
+        assert_eq!(EIP1559.capped(151, Some(150), None), None);
+        assert_eq!(
+            EIP1559.capped(150, Some(150), None),
+            Some(Fees::Eip1559 {
+                max_fee_per_gas: 150,
+                max_priority_fee_per_gas: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn replacement_fees_are_bumped() {
+        let config = FeeConfig::default();
+
+        // the estimate is below the bump
+        assert_eq!(
+            config.replacement_fees(EIP1559, EIP1559, None),
+            Some(EIP1559.bumped(config.bump_percent))
+        );
+        // the estimate is above the bump
+        assert_eq!(
+            config.replacement_fees(
+                EIP1559,
+                Fees::Eip1559 {
+                    max_fee_per_gas: 300,
+                    max_priority_fee_per_gas: 5,
+                },
+                None
+            ),
+            Some(Fees::Eip1559 {
+                max_fee_per_gas: 300,
+                max_priority_fee_per_gas: 12,
+            })
+        );
+    }
+
+    #[test]
+    fn replacement_fees_respect_caps() {
+        let config = FeeConfig {
+            max_priority_fee_per_gas: Some(11),
+            ..Default::default()
+        };
+
+        // capped, but still enough of an increase to be accepted
+        assert_eq!(
+            config.replacement_fees(EIP1559, EIP1559, Some(225)),
+            Some(Fees::Eip1559 {
+                max_fee_per_gas: 225,
+                max_priority_fee_per_gas: 11,
+            })
+        );
+        // capped below the minimum replacement bump
+        assert_eq!(config.replacement_fees(EIP1559, EIP1559, Some(219)), None);
+    }
+
+    #[test]
+    fn replacement_fees_until_capped() {
+        let config = FeeConfig::default();
+        let estimated = Fees::Legacy { gas_price: 100 };
+
+        // replace a stuck transaction until the cap is reached, as the submission loop does
+        let replacements = std::iter::successors(Some(estimated), |fees| {
+            config.replacement_fees(*fees, estimated, Some(200))
+        })
+        .map(|fees| fees.max_fee_per_gas())
+        .collect::<Vec<_>>();
+
+        assert_eq!(replacements, [100, 120, 144, 173, 200]);
+    }
+
+    #[test]
+    fn median_reward_ignores_empty_blocks() {
+        assert_eq!(
+            median_reward(&[vec![0], vec![30], vec![10], vec![0], vec![20]]),
+            20
+        );
+        assert_eq!(median_reward(&[vec![5], vec![1]]), 5);
+        assert_eq!(median_reward(&[vec![0], vec![], vec![0]]), 0);
+        assert_eq!(median_reward(&[]), 0);
+    }
+
+    #[test]
+    fn validate_reward_percentile() {
+        let config = |reward_percentile| FeeConfig {
+            reward_percentile,
+            ..Default::default()
+        };
+
+        assert!(config(0.0).validate().is_ok());
+        assert!(config(100.0).validate().is_ok());
+        assert!(config(-1.0).validate().is_err());
+        assert!(config(100.1).validate().is_err());
+        assert!(config(f64::NAN).validate().is_err());
+    }
+}
@@ -1,16 +1,22 @@
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 use alloy::{
     contract::{Error, RawCallBuilder},
     network::EthereumWallet,
     providers::{PendingTransactionError, Provider, ProviderBuilder, RootProvider},
-    signers::local::LocalSigner,
+    rpc::types::TransactionReceipt,
     sol_types::{SolEvent, SolInterface},
     transports::{BoxTransport, Transport, TransportError},
 };
-use bip32::secp256k1::ecdsa::{self, SigningKey};
 use chain_utils::{
-    keyring::{ConcurrentKeyring, KeyringConfig, KeyringEntry},
+    keyring::{
+        monitor_balances, BalanceMonitorConfig, ConcurrentKeyring, KeyringConfig, KeyringEntry,
+        KeyringUnavailable, SignerBalance,
+    },
     BoxDynError,
 };
 use ibc_solidity::Ibc::{self, IbcErrors};
@@ -18,7 +24,7 @@ use ibc_union_spec::{datagram::Datagram, IbcUnion};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     types::{ErrorObject, ErrorObjectOwned},
-    Extensions,
+    Extensions, RpcModule,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, info_span, instrument, trace, warn, Instrument};
@@ -30,20 +36,29 @@ use voyager_message::{
     core::ChainId,
     data::Data,
     hook::SubmitTxHook,
-    module::{PluginInfo, PluginServer},
+    module::{self, KeyringServer, PluginInfo, PluginServer},
     DefaultCmd, Plugin, PluginMessage, VoyagerMessage,
 };
-use voyager_vm::{call, defer, now, pass::PassResult, seq, Op, Visit};
+use voyager_vm::{call, conc, defer, now, pass::PassResult, seq, Op, Visit};
 
 use crate::{
-    call::ModuleCall,
+    call::{CheckPendingTx, ModuleCall},
     callback::ModuleCallback,
+    fees::{FeeConfig, Fees},
     multicall::{Call3, Multicall, MulticallResult},
+    pending::{PendingTx, PendingTxs},
+    signer::EvmSigner,
 };
 
 pub mod call;
 pub mod callback;
 pub mod data;
+pub mod fees;
+pub mod pending;
+pub mod signer;
+
+/// How often to poll for the receipt of a broadcast transaction.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(3);
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() {
@@ -60,10 +75,15 @@ pub struct Module {
 
     pub provider: RootProvider<BoxTransport>,
 
-    pub keyring: ConcurrentKeyring<alloy::primitives::Address, LocalSigner<SigningKey>>,
+    pub keyring: ConcurrentKeyring<alloy::primitives::Address, EvmSigner>,
 
     pub max_gas_price: Option<u128>,
     pub legacy: bool,
+
+    pub fees: FeeConfig,
+    pub pending_txs: PendingTxs,
+
+    pub balance_monitor: BalanceMonitorConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,11 +100,26 @@ pub struct Config {
 
     pub keyring: KeyringConfig,
 
+    /// The maximum fee per gas to pay (or the maximum gas price, for legacy transactions). If the
+    /// base fee is above this, submission is delayed until it drops.
     #[serde(default)]
     pub max_gas_price: Option<u128>,
 
+    /// Submit legacy transactions instead of EIP-1559 transactions.
     #[serde(default)]
     pub legacy: bool,
+
+    #[serde(default)]
+    pub fees: FeeConfig,
+
+    /// The file to persist transactions that are in flight to, such that they can be picked up
+    /// again after a restart. This must be unique per chain. If not set, in flight transactions
+    /// are only tracked in memory.
+    #[serde(default)]
+    pub pending_tx_path: Option<PathBuf>,
+
+    #[serde(default)]
+    pub balance_monitor: BalanceMonitorConfig,
 }
 
 impl Plugin for Module {
@@ -95,6 +130,8 @@ impl Plugin for Module {
     type Cmd = DefaultCmd;
 
     async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
+        config.fees.validate()?;
+
         let provider = ProviderBuilder::new().on_builtin(&config.rpc_url).await?;
 
         let raw_chain_id = provider.get_chain_id().await?;
@@ -108,30 +145,53 @@ impl Plugin for Module {
             .into());
         }
 
+        let mut keys = vec![];
+
+        for entry in config.keyring.keys {
+            let signer = EvmSigner::new(entry.secp256k1_signer().await?);
+
+            keys.push(KeyringEntry {
+                name: entry.name(),
+                address: signer.address(),
+                signer,
+            });
+        }
+
+        let pending_txs = PendingTxs::load(config.pending_tx_path)?;
+
+        for pending_tx in pending_txs.all() {
+            info!(
+                signer = %pending_tx.signer,
+                nonce = pending_tx.nonce,
+                "found pending transaction from a previous run, it will be resumed the next time \
+                this signer is used"
+            );
+        }
+
+        let keyring = ConcurrentKeyring::new(config.keyring.name, keys.into_iter());
+
+        tokio::spawn(monitor_balances(
+            keyring.clone(),
+            config.balance_monitor.clone(),
+            {
+                let provider = provider.clone();
+                let keyring = keyring.clone();
+
+                move || fetch_balances(provider.clone(), keyring.clone())
+            },
+        ));
+
         Ok(Self {
             chain_id,
             ibc_handler_address: config.ibc_handler_address,
             multicall_address: config.multicall_address,
             provider,
-            keyring: ConcurrentKeyring::new(
-                config.keyring.name,
-                config.keyring.keys.into_iter().map(|config| {
-                    let signing_key = <ecdsa::SigningKey as bip32::PrivateKey>::from_bytes(
-                        &config.value().as_slice().try_into().unwrap(),
-                    )
-                    .unwrap();
-
-                    let signer = LocalSigner::from_signing_key(signing_key);
-
-                    KeyringEntry {
-                        name: config.name(),
-                        address: signer.address(),
-                        signer,
-                    }
-                }),
-            ),
+            keyring,
             max_gas_price: config.max_gas_price,
             legacy: config.legacy,
+            fees: config.fees,
+            pending_txs,
+            balance_monitor: config.balance_monitor,
         })
     }
 
@@ -145,6 +205,35 @@ impl Plugin for Module {
     async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
         match cmd {}
     }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        let mut module = PluginServer::into_rpc(self.clone());
+        module
+            .merge(KeyringServer::into_rpc(self))
+            .expect("plugin and keyring namespaces are disjoint; qed;");
+        module
+    }
+}
+
+/// Fetch the native token balances of all keys in `keyring`.
+async fn fetch_balances(
+    provider: RootProvider<BoxTransport>,
+    keyring: ConcurrentKeyring<alloy::primitives::Address, EvmSigner>,
+) -> Result<Vec<SignerBalance<alloy::primitives::Address>>, BoxDynError> {
+    let mut balances = vec![];
+
+    for (key_name, address) in keyring.keys() {
+        let balance = provider.get_balance(*address).await?;
+
+        balances.push(SignerBalance {
+            key_name: key_name.to_owned(),
+            address: *address,
+            balance: balance.saturating_to(),
+            denom: "wei".to_owned(),
+        });
+    }
+
+    Ok(balances)
 }
 
 fn plugin_name(chain_id: &ChainId) -> String {
@@ -171,12 +260,46 @@ pub enum TxSubmitError {
     OutOfGas,
     #[error("0x revert")]
     EmptyRevert(Vec<Datagram>),
+    #[error("0x revert in the pending transaction from a previous run")]
+    ResumedEmptyRevert(Vec<Datagram>),
     #[error("gas price is too high: max {max}, price {price}")]
     GasPriceTooHigh { max: u128, price: u128 },
+    #[error("nonce {0} was used by a transaction that was not submitted by this plugin")]
+    NonceUsed(u64),
+    #[error(
+        "transaction with nonce {nonce} is stuck and has already been replaced the maximum \
+        amount of times"
+    )]
+    Stuck {
+        signer: alloy::primitives::Address,
+        nonce: u64,
+    },
+    #[error("signer is blocked by a stuck transaction with nonce {0}")]
+    SignerBlocked(u64),
+    #[error("transport error")]
+    Transport(#[from] TransportError),
     #[error("rpc error (this is just the IbcDatagram conversion functions but i need to make those errors better)")]
     RpcError(#[from] ErrorObjectOwned),
 }
 
+#[async_trait]
+impl KeyringServer for Module {
+    async fn signer_balances(&self) -> RpcResult<Vec<module::SignerBalance>> {
+        Ok(self
+            .keyring
+            .balances()
+            .into_iter()
+            .map(|balance| module::SignerBalance {
+                enabled: self.keyring.in_rotation(&balance.address),
+                key_name: balance.key_name,
+                address: balance.address.to_string(),
+                balance: balance.balance,
+                denom: balance.denom,
+            })
+            .collect())
+    }
+}
+
 #[async_trait]
 impl PluginServer<ModuleCall, ModuleCallback> for Module {
     async fn run_pass(
@@ -222,10 +345,7 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                     .keyring
                     .with({
                         let msgs = msgs.clone();
-                        move |wallet| -> _ {
-                            // let call = if self.legacy { call.legacy() } else { call };
-                            self.submit_transaction(wallet, msgs)
-                        }
+                        move |wallet| -> _ { self.submit_transaction(wallet, msgs) }
                     })
                     .await;
 
@@ -233,28 +353,50 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                     || PluginMessage::new(self.plugin_name(), ModuleCall::SubmitMulticall(msgs));
 
                 match res {
-                    Some(Ok(())) => Ok(Op::Noop),
-                    Some(Err(TxSubmitError::GasPriceTooHigh { .. })) => {
+                    Ok(Ok(())) => Ok(Op::Noop),
+                    Ok(Err(TxSubmitError::GasPriceTooHigh { .. })) => {
                         Ok(seq([defer(now() + 6), call(rewrap_msg())]))
                     }
-                    Some(Err(TxSubmitError::OutOfGas)) => {
+                    Ok(Err(TxSubmitError::OutOfGas)) => {
                         Ok(seq([defer(now() + 12), call(rewrap_msg())]))
                     }
-                    Some(Err(TxSubmitError::EmptyRevert(msgs))) => Ok(seq([
-                        defer(now() + 12),
+                    Ok(Err(TxSubmitError::EmptyRevert(msgs))) => Ok(self.retry_empty_revert(msgs)),
+                    // the current messages were not submitted, since the signer was used to
+                    // resume the pending transaction
+                    Ok(Err(TxSubmitError::ResumedEmptyRevert(msgs))) => {
+                        Ok(conc([self.retry_empty_revert(msgs), call(rewrap_msg())]))
+                    }
+                    // the messages are in the stuck transaction, so only the transaction needs to
+                    // be checked on again
+                    Ok(Err(TxSubmitError::Stuck { signer, nonce })) => Ok(seq([
+                        defer(now() + self.fees.stuck_timeout_seconds),
                         call(PluginMessage::new(
                             self.plugin_name(),
-                            ModuleCall::SubmitMulticall(msgs),
+                            ModuleCall::from(CheckPendingTx { signer, nonce }),
                         )),
                     ])),
-                    Some(Err(err)) => Err(ErrorObject::owned(
+                    Ok(Err(TxSubmitError::SignerBlocked(_))) => Ok(seq([
+                        defer(now() + self.fees.stuck_timeout_seconds),
+                        call(rewrap_msg()),
+                    ])),
+                    Ok(Err(err)) => Err(ErrorObject::owned(
                         -1,
                         ErrorReporter(err).to_string(),
                         None::<()>,
                     )),
-                    None => Ok(call(rewrap_msg())),
+                    Err(KeyringUnavailable::Busy) => Ok(call(rewrap_msg())),
+                    // no key will be available until the balance monitor has seen a topped up
+                    // key, so there's no use in trying again before then
+                    Err(KeyringUnavailable::NoKeyInRotation) => Ok(seq([
+                        defer(now() + self.balance_monitor.interval_seconds.get()),
+                        call(rewrap_msg()),
+                    ])),
                 }
             }
+            ModuleCall::CheckPendingTx(CheckPendingTx { signer, nonce }) => self
+                .check_pending_tx(signer, nonce)
+                .await
+                .map_err(|err| ErrorObject::owned(-1, ErrorReporter(err).to_string(), None::<()>)),
         }
     }
 
@@ -272,47 +414,88 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
 impl Module {
     async fn submit_transaction(
         &self,
-        wallet: &LocalSigner<SigningKey>,
+        wallet: &EvmSigner,
+        ibc_messages: Vec<Datagram>,
+    ) -> Result<(), TxSubmitError> {
+        // a transaction from a previous run must be resolved before this signer is used again,
+        // otherwise the new transaction would either be stuck behind it or replace it (dropping
+        // its messages)
+        if let Some(pending_tx) = self.pending_txs.get(&wallet.address()) {
+            let nonce = self
+                .provider
+                .get_transaction_count(wallet.address())
+                .latest()
+                .await?;
+
+            if nonce > pending_tx.nonce {
+                info!(
+                    nonce = pending_tx.nonce,
+                    "pending transaction from a previous run was included"
+                );
+
+                self.pending_txs.remove(&wallet.address());
+            } else if pending_tx.bumps >= self.fees.max_bumps {
+                // the transaction can't be replaced anymore, so the current messages have to wait
+                // until it is included (which is also checked on by a queued `CheckPendingTx`)
+                return Err(TxSubmitError::SignerBlocked(pending_tx.nonce));
+            } else {
+                info!(
+                    nonce = pending_tx.nonce,
+                    "resuming pending transaction from a previous run"
+                );
+
+                let datagrams = pending_tx.datagrams.clone();
+
+                match self
+                    .send_multicall(wallet, datagrams, Some(pending_tx))
+                    .await
+                {
+                    Ok(()) => {}
+                    // the messages from the previous run are no longer in the queue, so they are
+                    // requeued along with the current messages
+                    Err(TxSubmitError::EmptyRevert(msgs)) => {
+                        warn!(
+                            count = msgs.len(),
+                            "messages in the pending transaction from a previous run failed, \
+                            retrying them"
+                        );
+
+                        return Err(TxSubmitError::ResumedEmptyRevert(msgs));
+                    }
+                    // the nonce is still blocked by the previous transaction, so the current
+                    // messages can't be submitted yet
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        self.send_multicall(wallet, ibc_messages, None).await
+    }
+
+    /// Submit `ibc_messages` in a multicall and wait for it to be included, replacing it with
+    /// higher fees if it is stuck.
+    ///
+    /// If `resume` is set, the transaction is rebroadcast with the nonce and gas of `resume` and
+    /// fees of at least the next bump of its fees.
+    async fn send_multicall(
+        &self,
+        wallet: &EvmSigner,
         ibc_messages: Vec<Datagram>,
+        resume: Option<PendingTx>,
     ) -> Result<(), TxSubmitError> {
         let signer = ProviderBuilder::new()
             .with_recommended_fillers()
-            // .filler(<NonceFiller>::default())
-            // .filler(ChainIdFiller::default())
             .wallet(EthereumWallet::new(wallet.clone()))
             .on_provider(self.provider.clone());
 
-        if let Some(max_gas_price) = self.max_gas_price {
-            let gas_price = self
-                .provider
-                .get_gas_price()
-                .await
-                .expect("unable to fetch gas price");
-
-            if gas_price > max_gas_price {
-                warn!(%max_gas_price, %gas_price, "gas price is too high");
-
-                return Err(TxSubmitError::GasPriceTooHigh {
-                    max: max_gas_price,
-                    price: gas_price,
-                });
-            }
-        }
-
         let multicall = Multicall::new(self.multicall_address.into(), signer.clone());
 
         let ibc = Ibc::new(self.ibc_handler_address.into(), &self.provider);
 
-        let msgs = process_msgs(&ibc, ibc_messages, wallet.address().0.into())?;
+        let msgs = process_msgs(&ibc, ibc_messages.clone(), wallet.address().0.into())?;
 
         debug!(?msgs);
 
-        let msg_names = msgs
-            .iter()
-            // .map(|x| (x.0.clone(), x.1.function.name.clone()))
-            .map(|x| (x.0.clone(), x.0.name()))
-            .collect::<Vec<_>>();
-
         let call = multicall.multicall(
             msgs.into_iter()
                 .map(|(_, x)| Call3 {
@@ -323,125 +506,421 @@ impl Module {
                 .collect(),
         );
 
-        info!("submitting evm tx");
-
-        let gas_estimate = call.estimate_gas().await.map_err(TxSubmitError::Estimate)?;
-        //     .map_err(|e| {
-        //     ErrorObject::owned(
-        //         -1,
-        //         format!("error estimating gas: {}", ErrorReporter(e), None::<()>),
-        //     )
-        // })?;
-
-        let gas_to_use = gas_estimate + (gas_estimate / 2);
-
-        info!(gas_estimate, gas_to_use, "gas estimatation successful");
-
-        match call.gas(gas_to_use).send().await {
-            Ok(ok) => {
-                let tx_hash = <H256>::from(*ok.tx_hash());
-                async move {
-                    let receipt = ok.get_receipt().await?;
-
-                    info!(%tx_hash, "tx included");
-
-                    let result = MulticallResult::decode_log_data(
-                        receipt
-                            .inner
-                            .logs()
-                            .last()
-                            .expect("multicall event should be last log")
-                            .data(),
-                        true,
-                    )
-                    .expect("unable to decode multicall result log");
-
-                    info!(
-                        gas_used = %receipt.gas_used,
-                        batch.size = msg_names.len(),
-                        "submitted batched evm messages"
-                    );
+        let fees = self.estimate_fees().await?;
 
-                    let mut retry_msgs = vec![];
+        let mut pending_tx = match resume {
+            Some(pending_tx) => {
+                let fees = self.cap_fees(fees.max(pending_tx.fees.bumped(self.fees.bump_percent)));
 
-                    for (idx, (result, (msg, msg_name))) in
-                        result._0.into_iter().zip(msg_names).enumerate()
+                PendingTx {
+                    fees,
+                    bumps: pending_tx.bumps + 1,
+                    ..pending_tx
+                }
+            }
+            None => {
+                info!("submitting evm tx");
+
+                let gas_estimate = call.estimate_gas().await.map_err(TxSubmitError::Estimate)?;
+
+                let gas_to_use = gas_estimate + (gas_estimate / 2);
+
+                info!(gas_estimate, gas_to_use, "gas estimatation successful");
+
+                // the latest (rather than pending) transaction count is used so that a
+                // transaction that was dropped from the mempool is replaced instead of leaving a
+                // gap at its nonce
+                let nonce = self
+                    .provider
+                    .get_transaction_count(wallet.address())
+                    .latest()
+                    .await?;
+
+                PendingTx {
+                    signer: wallet.address(),
+                    nonce,
+                    gas: gas_to_use,
+                    fees,
+                    tx_hashes: vec![],
+                    bumps: 0,
+                    datagrams: ibc_messages.clone(),
+                }
+            }
+        };
+
+        let mut broadcast_fees = Some(pending_tx.fees);
+        let mut last_broadcast = Instant::now();
+
+        let receipt = loop {
+            if let Some(fees) = broadcast_fees.take() {
+                let call = match fees {
+                    Fees::Legacy { gas_price } => call.clone().gas_price(gas_price),
+                    Fees::Eip1559 {
+                        max_fee_per_gas,
+                        max_priority_fee_per_gas,
+                    } => call
+                        .clone()
+                        .max_fee_per_gas(max_fee_per_gas)
+                        .max_priority_fee_per_gas(max_priority_fee_per_gas),
+                };
+
+                match call
+                    .nonce(pending_tx.nonce)
+                    .gas(pending_tx.gas)
+                    .send()
+                    .await
+                {
+                    Ok(ok) => {
+                        let tx_hash = <H256>::from(*ok.tx_hash());
+
+                        info!(
+                            %tx_hash,
+                            nonce = pending_tx.nonce,
+                            bumps = pending_tx.bumps,
+                            ?fees,
+                            "broadcast evm tx"
+                        );
+
+                        pending_tx.fees = fees;
+                        pending_tx.tx_hashes.push(tx_hash);
+                        self.pending_txs.insert(pending_tx.clone());
+                    }
+                    Err(
+                        Error::PendingTransactionError(PendingTransactionError::TransportError(
+                            TransportError::ErrorResp(e),
+                        ))
+                        | Error::TransportError(TransportError::ErrorResp(e)),
+                    ) if e
+                        .message
+                        .contains("insufficient funds for gas * price + value") =>
                     {
-                        if result.success {
-                            info_span!(
-                                "evm tx",
-                                msg = msg_name,
-                                %idx,
-                                data = %serde_json::to_string(&msg).unwrap(),
-                            );
-                        } else if let Ok(known_revert) =
-                            IbcErrors::abi_decode(&result.returnData, true)
-                        {
-                            error!(
-                                msg = %msg_name,
-                                %idx,
-                                revert = ?known_revert,
-                                well_known = true,
-                                data = %serde_json::to_string(&msg).unwrap(),
-                                "evm message failed",
-                            );
-                        } else if result.returnData.is_empty() {
-                            error!(
-                                msg = %msg_name,
-                                %idx,
-                                revert = %result.returnData,
-                                well_known = false,
-                                data = %serde_json::to_string(&msg).unwrap(),
-                                "evm message failed",
-                            );
-
-                            retry_msgs.push((true, msg));
-                        } else {
-                            error!(
-                                msg = %msg_name,
-                                %idx,
-                                revert = %result.returnData,
-                                well_known = false,
-                                data = %serde_json::to_string(&msg).unwrap(),
-                                "evm message failed",
-                            );
-
-                            retry_msgs.push((false, msg));
+                        error!("out of gas");
+
+                        if pending_tx.tx_hashes.is_empty() {
+                            return Err(TxSubmitError::OutOfGas);
                         }
                     }
-
-                    // NOTE: An empty iterator returns false
-                    if retry_msgs
-                        .iter()
-                        .any(|(is_empty_revert, _)| *is_empty_revert)
-                    {
-                        Err(TxSubmitError::EmptyRevert(
-                            retry_msgs.into_iter().map(|(_, msg)| msg).collect(),
-                        ))
-                    } else {
-                        Ok(())
+                    Err(err) if pending_tx.tx_hashes.is_empty() => {
+                        return Err(TxSubmitError::Error(err));
+                    }
+                    // a previous broadcast may have been included in the meantime, which is
+                    // picked up when polling for the receipt
+                    Err(err) => {
+                        warn!(
+                            err = %ErrorReporter(err),
+                            nonce = pending_tx.nonce,
+                            "error broadcasting replacement transaction"
+                        );
                     }
                 }
-                .instrument(info_span!(
-                    "evm tx",
-                    %tx_hash,
-                ))
-                .await
+
+                last_broadcast = Instant::now();
             }
-            Err(
-                Error::PendingTransactionError(PendingTransactionError::TransportError(
-                    TransportError::ErrorResp(e),
+
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+
+            if let Some(receipt) = self.find_receipt(&pending_tx.tx_hashes).await? {
+                break receipt;
+            }
+
+            if last_broadcast.elapsed() < self.fees.stuck_timeout() {
+                continue;
+            }
+
+            // reset the timer so that the checks below are not run on every poll if the
+            // transaction can't be replaced
+            last_broadcast = Instant::now();
+
+            let nonce = self
+                .provider
+                .get_transaction_count(wallet.address())
+                .latest()
+                .await?;
+
+            if nonce > pending_tx.nonce {
+                // one of the broadcasts may have been included between the two queries
+                if let Some(receipt) = self.find_receipt(&pending_tx.tx_hashes).await? {
+                    break receipt;
+                }
+
+                self.pending_txs.remove(&wallet.address());
+
+                return Err(TxSubmitError::NonceUsed(pending_tx.nonce));
+            }
+
+            if pending_tx.bumps >= self.fees.max_bumps {
+                warn!(
+                    nonce = pending_tx.nonce,
+                    bumps = pending_tx.bumps,
+                    "transaction is stuck and has already been replaced the maximum amount of \
+                    times, checking on it again later"
+                );
+
+                // the transaction stays tracked, such that it is picked up again by the check or
+                // the next submission with this signer
+                return Err(TxSubmitError::Stuck {
+                    signer: wallet.address(),
+                    nonce: pending_tx.nonce,
+                });
+            }
+
+            let estimated_fees = match self.estimate_fees().await {
+                Ok(fees) => fees,
+                Err(err) => {
+                    warn!(
+                        err = %ErrorReporter(err),
+                        nonce = pending_tx.nonce,
+                        "unable to replace stuck transaction"
+                    );
+
+                    continue;
+                }
+            };
+
+            let Some(fees) =
+                self.fees
+                    .replacement_fees(pending_tx.fees, estimated_fees, self.max_gas_price)
+            else {
+                warn!(
+                    nonce = pending_tx.nonce,
+                    ?estimated_fees,
+                    "fees are capped, unable to replace stuck transaction"
+                );
+
+                continue;
+            };
+
+            info!(
+                nonce = pending_tx.nonce,
+                old_fees = ?pending_tx.fees,
+                new_fees = ?fees,
+                "transaction is stuck, replacing it with higher fees"
+            );
+
+            pending_tx.bumps += 1;
+            broadcast_fees = Some(fees);
+        };
+
+        self.pending_txs.remove(&wallet.address());
+
+        self.handle_receipt(receipt, ibc_messages).await
+    }
+
+    /// Check on a transaction that was stuck after being replaced the maximum amount of times,
+    /// queueing another check if it is still not included.
+    async fn check_pending_tx(
+        &self,
+        signer: alloy::primitives::Address,
+        nonce: u64,
+    ) -> Result<Op<VoyagerMessage>, TxSubmitError> {
+        let Some(pending_tx) = self
+            .pending_txs
+            .get(&signer)
+            .filter(|pending_tx| pending_tx.nonce == nonce)
+        else {
+            debug!(%signer, %nonce, "pending transaction has already been resolved");
+
+            return Ok(Op::Noop);
+        };
+
+        // the nonce is queried before the receipts, such that a transaction that is included in
+        // between is still found
+        let latest_nonce = self.provider.get_transaction_count(signer).latest().await?;
+
+        if let Some(receipt) = self.find_receipt(&pending_tx.tx_hashes).await? {
+            self.pending_txs.remove(&signer);
+
+            return match self.handle_receipt(receipt, pending_tx.datagrams).await {
+                Ok(()) => Ok(Op::Noop),
+                Err(TxSubmitError::EmptyRevert(msgs)) => Ok(self.retry_empty_revert(msgs)),
+                Err(err) => Err(err),
+            };
+        }
+
+        if latest_nonce > nonce {
+            self.pending_txs.remove(&signer);
+
+            return Err(TxSubmitError::NonceUsed(nonce));
+        }
+
+        warn!(
+            %signer,
+            %nonce,
+            bumps = pending_tx.bumps,
+            "transaction is still stuck, checking on it again later"
+        );
+
+        Ok(seq([
+            defer(now() + self.fees.stuck_timeout_seconds),
+            call(PluginMessage::new(
+                self.plugin_name(),
+                ModuleCall::from(CheckPendingTx { signer, nonce }),
+            )),
+        ]))
+    }
+
+    /// Requeue messages that failed with an empty revert.
+    fn retry_empty_revert(&self, msgs: Vec<Datagram>) -> Op<VoyagerMessage> {
+        seq([
+            defer(now() + 12),
+            call(PluginMessage::new(
+                self.plugin_name(),
+                ModuleCall::SubmitMulticall(msgs),
+            )),
+        ])
+    }
+
+    /// Log the result of each message in the included multicall transaction, returning the
+    /// messages that failed with an empty revert (if any) such that they can be retried.
+    async fn handle_receipt(
+        &self,
+        receipt: TransactionReceipt,
+        ibc_messages: Vec<Datagram>,
+    ) -> Result<(), TxSubmitError> {
+        let msg_names = ibc_messages
+            .into_iter()
+            .map(|msg| {
+                let msg_name = msg.name();
+                (msg, msg_name)
+            })
+            .collect::<Vec<_>>();
+
+        let tx_hash = <H256>::from(receipt.transaction_hash);
+
+        async move {
+            info!(%tx_hash, "tx included");
+
+            let result = MulticallResult::decode_log_data(
+                receipt
+                    .inner
+                    .logs()
+                    .last()
+                    .expect("multicall event should be last log")
+                    .data(),
+                true,
+            )
+            .expect("unable to decode multicall result log");
+
+            info!(
+                gas_used = %receipt.gas_used,
+                batch.size = msg_names.len(),
+                "submitted batched evm messages"
+            );
+
+            let mut retry_msgs = vec![];
+
+            for (idx, (result, (msg, msg_name))) in result._0.into_iter().zip(msg_names).enumerate()
+            {
+                if result.success {
+                    info_span!(
+                        "evm tx",
+                        msg = msg_name,
+                        %idx,
+                        data = %serde_json::to_string(&msg).unwrap(),
+                    );
+                } else if let Ok(known_revert) = IbcErrors::abi_decode(&result.returnData, true) {
+                    error!(
+                        msg = %msg_name,
+                        %idx,
+                        revert = ?known_revert,
+                        well_known = true,
+                        data = %serde_json::to_string(&msg).unwrap(),
+                        "evm message failed",
+                    );
+                } else if result.returnData.is_empty() {
+                    error!(
+                        msg = %msg_name,
+                        %idx,
+                        revert = %result.returnData,
+                        well_known = false,
+                        data = %serde_json::to_string(&msg).unwrap(),
+                        "evm message failed",
+                    );
+
+                    retry_msgs.push((true, msg));
+                } else {
+                    error!(
+                        msg = %msg_name,
+                        %idx,
+                        revert = %result.returnData,
+                        well_known = false,
+                        data = %serde_json::to_string(&msg).unwrap(),
+                        "evm message failed",
+                    );
+
+                    retry_msgs.push((false, msg));
+                }
+            }
+
+            // NOTE: An empty iterator returns false
+            if retry_msgs
+                .iter()
+                .any(|(is_empty_revert, _)| *is_empty_revert)
+            {
+                Err(TxSubmitError::EmptyRevert(
+                    retry_msgs.into_iter().map(|(_, msg)| msg).collect(),
                 ))
-                | Error::TransportError(TransportError::ErrorResp(e)),
-            ) if e
-                .message
-                .contains("insufficient funds for gas * price + value") =>
+            } else {
+                Ok(())
+            }
+        }
+        .instrument(info_span!(
+            "evm tx",
+            %tx_hash,
+        ))
+        .await
+    }
+
+    /// Estimate the fees for a transaction, limited to the configured caps.
+    async fn estimate_fees(&self) -> Result<Fees, TxSubmitError> {
+        let estimate = fees::estimate(&self.provider, &self.fees, self.legacy).await?;
+
+        estimate
+            .fees
+            .capped(
+                estimate.base_fee,
+                self.max_gas_price,
+                self.fees.max_priority_fee_per_gas,
+            )
+            .ok_or_else(|| {
+                warn!(
+                    max_gas_price = ?self.max_gas_price,
+                    base_fee = %estimate.base_fee,
+                    "gas price is too high"
+                );
+
+                TxSubmitError::GasPriceTooHigh {
+                    max: self.max_gas_price.unwrap_or_default(),
+                    price: estimate.base_fee,
+                }
+            })
+    }
+
+    /// Limit `fees` to the configured caps. This is used for fees that were not freshly
+    /// estimated, i.e. bumped fees of a replacement transaction.
+    fn cap_fees(&self, fees: Fees) -> Fees {
+        fees.capped(0, self.max_gas_price, self.fees.max_priority_fee_per_gas)
+            .expect("a base fee of 0 is never above the cap; qed;")
+    }
+
+    /// Find the receipt of any of the provided transaction hashes, if any of them have been
+    /// included.
+    async fn find_receipt(
+        &self,
+        tx_hashes: &[H256],
+    ) -> Result<Option<TransactionReceipt>, TxSubmitError> {
+        // the most recent broadcast is the most likely to be included
+        for tx_hash in tx_hashes.iter().rev() {
+            if let Some(receipt) = self
+                .provider
+                .get_transaction_receipt((*tx_hash).into())
+                .await?
             {
-                error!("out of gas");
-                Err(TxSubmitError::OutOfGas)
+                return Ok(Some(receipt));
             }
-            Err(err) => Err(TxSubmitError::Error(err)),
         }
+
+        Ok(None)
     }
 }
 
@@ -478,6 +957,15 @@ fn process_msgs<T: Transport + Clone, P: Provider<T>>(
                         })
                         .clear_decoder(),
                 ),
+                Datagram::SubmitMisbehaviour(data) => (
+                    msg,
+                    ibc_handler
+                        .misbehaviour(ibc_solidity::MsgMisbehaviour {
+                            client_id: data.client_id,
+                            client_message: data.misbehaviour.into(),
+                        })
+                        .clear_decoder(),
+                ),
                 Datagram::ConnectionOpenInit(data) => (
                     msg,
                     ibc_handler
@@ -573,6 +1061,26 @@ fn process_msgs<T: Transport + Clone, P: Provider<T>>(
                         })
                         .clear_decoder(),
                 ),
+                Datagram::ChannelCloseInit(data) => (
+                    msg,
+                    ibc_handler
+                        .channelCloseInit(ibc_solidity::MsgChannelCloseInit {
+                            channel_id: data.channel_id,
+                            relayer: relayer.into(),
+                        })
+                        .clear_decoder(),
+                ),
+                Datagram::ChannelCloseConfirm(data) => (
+                    msg,
+                    ibc_handler
+                        .channelCloseConfirm(ibc_solidity::MsgChannelCloseConfirm {
+                            channel_id: data.channel_id,
+                            proof_init: data.proof_init.into(),
+                            proof_height: data.proof_height,
+                            relayer: relayer.into(),
+                        })
+                        .clear_decoder(),
+                ),
                 Datagram::PacketRecv(data) => (
                     msg,
                     ibc_handler
@@ -601,18 +1109,51 @@ fn process_msgs<T: Transport + Clone, P: Provider<T>>(
                         })
                         .clear_decoder(),
                 ),
-                // Datagram::TimeoutPacket(data) => (
-                //     msg,
-                //     ibc_handler
-                //         .timeoutPacket(MsgPacketTimeout {
-                //             packet: convert_packet(data.packet)?,
-                //             proof: data.proof_unreceived.into(),
-                //             proofHeight: data.proof_height.height(),
-                //             relayer: relayer.into(),
-                //         })
-                //         .clear_decoder(),
-                // ),
-                _ => todo!(),
+                Datagram::PacketTimeout(data) => (
+                    msg,
+                    ibc_handler
+                        .timeoutPacket(ibc_solidity::MsgPacketTimeout {
+                            packet: data.packet.into(),
+                            proof: data.proof.into(),
+                            proof_height: data.proof_height,
+                            relayer: relayer.into(),
+                        })
+                        .clear_decoder(),
+                ),
+                Datagram::IntentPacketRecv(data) => (
+                    msg,
+                    ibc_handler
+                        .recvIntentPacket(ibc_solidity::MsgIntentPacketRecv {
+                            packets: data.packets.into_iter().map(Into::into).collect(),
+                            market_maker_msgs: data
+                                .market_maker_msgs
+                                .into_iter()
+                                .map(Into::into)
+                                .collect(),
+                            market_maker: relayer.into(),
+                            emptyProof: Default::default(),
+                        })
+                        .clear_decoder(),
+                ),
+                Datagram::BatchSend(data) => (
+                    msg,
+                    ibc_handler
+                        .batchSend(ibc_solidity::MsgBatchSend {
+                            source_channel: data.source_channel_id,
+                            packets: data.packets.into_iter().map(Into::into).collect(),
+                        })
+                        .clear_decoder(),
+                ),
+                Datagram::BatchAcks(data) => (
+                    msg,
+                    ibc_handler
+                        .batchAcks(ibc_solidity::MsgBatchAcks {
+                            source_channel: data.source_channel_id,
+                            packets: data.packets.into_iter().map(Into::into).collect(),
+                            acks: data.acks.into_iter().map(Into::into).collect(),
+                        })
+                        .clear_decoder(),
+                ),
             })
         })
         .collect()
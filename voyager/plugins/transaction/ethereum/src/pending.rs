@@ -0,0 +1,206 @@
+//! Tracking of transactions that have been broadcast but not yet included.
+//!
+//! Each signer has at most one transaction in flight at a time (the keyring ensures a signer is
+//! only used by one submission at a time), so the tracker only needs to store one entry per
+//! signer. If a path is configured, the tracker is persisted on every change so that a transaction
+//! that was in flight when the plugin was stopped is picked up again on the next start, instead of
+//! being left in the mempool and blocking (or leaving a gap at) its nonce.
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use alloy::primitives::Address;
+use chain_utils::BoxDynError;
+use ibc_union_spec::datagram::Datagram;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+use unionlabs::{primitives::H256, ErrorReporter};
+
+use crate::fees::Fees;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingTx {
+    pub signer: Address,
+    pub nonce: u64,
+    pub gas: u64,
+    /// The fees of the most recent broadcast of this transaction.
+    pub fees: Fees,
+    /// The hashes of all broadcasts of this transaction, in the order they were broadcast. Since
+    /// replacements use the same nonce, at most one of them will be included.
+    pub tx_hashes: Vec<H256>,
+    /// How many times this transaction has been replaced with higher fees.
+    pub bumps: u32,
+    /// The datagrams contained in this transaction, such that it can be rebuilt and replaced
+    /// after a restart.
+    pub datagrams: Vec<Datagram>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PendingTxs {
+    path: Option<PathBuf>,
+    txs: Arc<Mutex<Vec<PendingTx>>>,
+}
+
+impl PendingTxs {
+    /// Load the pending transactions from `path`, if it exists. If `path` is `None`, pending
+    /// transactions are only tracked in memory.
+    pub fn load(path: Option<PathBuf>) -> Result<Self, BoxDynError> {
+        let txs = match &path {
+            Some(path) if path.exists() => serde_json::from_slice(&fs::read(path)?)?,
+            _ => vec![],
+        };
+
+        Ok(Self {
+            path,
+            txs: Arc::new(Mutex::new(txs)),
+        })
+    }
+
+    /// All currently tracked transactions.
+    pub fn all(&self) -> Vec<PendingTx> {
+        self.txs.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, signer: &Address) -> Option<PendingTx> {
+        self.txs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|tx| &tx.signer == signer)
+            .cloned()
+    }
+
+    /// Track `tx`, replacing any transaction already tracked for the same signer.
+    pub fn insert(&self, tx: PendingTx) {
+        let mut txs = self.txs.lock().unwrap();
+
+        txs.retain(|t| t.signer != tx.signer);
+        txs.push(tx);
+
+        self.persist(&txs);
+    }
+
+    pub fn remove(&self, signer: &Address) {
+        let mut txs = self.txs.lock().unwrap();
+
+        txs.retain(|t| &t.signer != signer);
+
+        self.persist(&txs);
+    }
+
+    fn persist(&self, txs: &[PendingTx]) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        // write to a temporary file first so that a crash mid-write doesn't corrupt the tracker
+        let tmp_path = path.with_extension("tmp");
+
+        let res = fs::write(
+            &tmp_path,
+            serde_json::to_vec_pretty(txs).expect("serialization is infallible; qed;"),
+        )
+        .and_then(|()| fs::rename(&tmp_path, path));
+
+        match res {
+            Ok(()) => debug!(path = %path.display(), count = txs.len(), "persisted pending txs"),
+            Err(err) => error!(
+                path = %path.display(),
+                err = %ErrorReporter(err),
+                "unable to persist pending txs"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn pending_tx(signer: u8, nonce: u64) -> PendingTx {
+        PendingTx {
+            signer: Address::repeat_byte(signer),
+            nonce,
+            gas: 100_000,
+            fees: Fees::Legacy { gas_price: 100 },
+            tx_hashes: vec![H256::new([nonce as u8; 32])],
+            bumps: 0,
+            datagrams: vec![],
+        }
+    }
+
+    #[test]
+    fn load_missing_file() {
+        let dir = TempDir::new().unwrap();
+
+        let pending_txs = PendingTxs::load(Some(dir.path().join("pending.json"))).unwrap();
+
+        assert_eq!(pending_txs.all(), []);
+    }
+
+    #[test]
+    fn persist_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("pending.json");
+
+        let pending_txs = PendingTxs::load(Some(path.clone())).unwrap();
+        pending_txs.insert(pending_tx(1, 10));
+        pending_txs.insert(pending_tx(2, 20));
+
+        assert_eq!(
+            PendingTxs::load(Some(path.clone())).unwrap().all(),
+            [pending_tx(1, 10), pending_tx(2, 20)]
+        );
+
+        // a replacement for the same signer overwrites the previous transaction
+        let replacement = PendingTx {
+            fees: Fees::Legacy { gas_price: 120 },
+            bumps: 1,
+            ..pending_tx(1, 10)
+        };
+        pending_txs.insert(replacement.clone());
+        pending_txs.remove(&Address::repeat_byte(2));
+
+        let reloaded = PendingTxs::load(Some(path.clone())).unwrap();
+        assert_eq!(reloaded.all(), [replacement.clone()]);
+        assert_eq!(reloaded.get(&Address::repeat_byte(1)), Some(replacement));
+        assert_eq!(reloaded.get(&Address::repeat_byte(2)), None);
+    }
+
+    #[test]
+    fn persist_renames_tmp_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("pending.json");
+        let tmp_path = dir.path().join("pending.tmp");
+
+        // left over from a crash mid-write
+        fs::write(&tmp_path, b"{").unwrap();
+
+        let pending_txs = PendingTxs::load(Some(path.clone())).unwrap();
+        assert_eq!(pending_txs.all(), []);
+
+        pending_txs.insert(pending_tx(1, 10));
+
+        assert!(path.exists());
+        assert!(!tmp_path.exists());
+        assert_eq!(
+            serde_json::from_slice::<Vec<PendingTx>>(&fs::read(&path).unwrap()).unwrap(),
+            [pending_tx(1, 10)]
+        );
+    }
+
+    #[test]
+    fn in_memory_only() {
+        let pending_txs = PendingTxs::load(None).unwrap();
+        pending_txs.insert(pending_tx(1, 10));
+        pending_txs.insert(pending_tx(2, 20));
+        pending_txs.remove(&Address::repeat_byte(1));
+
+        assert_eq!(pending_txs.all(), [pending_tx(2, 20)]);
+    }
+}
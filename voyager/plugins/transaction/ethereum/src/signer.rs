@@ -0,0 +1,52 @@
+use alloy::{
+    consensus::SignableTransaction,
+    network::TxSigner,
+    primitives::{Address, PrimitiveSignature},
+    signers::utils::public_key_to_address,
+};
+use chain_utils::signer::Secp256k1Signer;
+use jsonrpsee::core::async_trait;
+
+/// A transaction signer backed by a [`Secp256k1Signer`], such that keys held by a remote signer
+/// can be used in the same way as local keys.
+#[derive(Debug, Clone)]
+pub struct EvmSigner {
+    signer: Secp256k1Signer,
+    address: Address,
+}
+
+impl EvmSigner {
+    pub fn new(signer: Secp256k1Signer) -> Self {
+        Self {
+            address: public_key_to_address(&signer.verifying_key()),
+            signer,
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+}
+
+#[async_trait]
+impl TxSigner<PrimitiveSignature> for EvmSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<PrimitiveSignature>,
+    ) -> alloy::signers::Result<PrimitiveSignature> {
+        let (signature, recovery_id) = self
+            .signer
+            .sign_prehash(tx.signature_hash().0)
+            .await
+            .map_err(alloy::signers::Error::other)?;
+
+        Ok(PrimitiveSignature::from_signature_and_parity(
+            signature,
+            recovery_id.is_y_odd(),
+        ))
+    }
+}
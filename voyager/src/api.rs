@@ -1,7 +1,7 @@
-use std::net::SocketAddr;
+use std::{collections::BTreeMap, net::SocketAddr};
 
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     routing::{get, post},
     Json,
 };
@@ -11,25 +11,40 @@ use futures::{
 };
 use prometheus::TextEncoder;
 use reqwest::StatusCode;
+use serde::Deserialize;
 use tracing::error;
-use voyager_message::VoyagerMessage;
-use voyager_vm::Op;
+use unionlabs::ErrorReporter;
+use voyager_message::{module::SignerBalance, VoyagerMessage};
+use voyager_vm::{
+    history::ItemStatus,
+    inspect::{ItemQuery, ItemRecord},
+    Op,
+};
+
+use crate::{queue::QueueImpl, signer_balances::SignerBalances};
 
-pub fn run(laddr: &SocketAddr) -> UnboundedReceiver<Op<VoyagerMessage>> {
+pub fn run(
+    laddr: &SocketAddr,
+    queue: QueueImpl,
+    signer_balances: SignerBalances,
+) -> UnboundedReceiver<Op<VoyagerMessage>> {
     let (queue_tx, queue_rx) = unbounded::<Op<VoyagerMessage>>();
 
     let app = axum::Router::new()
         .route("/enqueue", post(enqueue))
         .route("/health", get(|| async move { StatusCode::OK }))
         .route("/metrics", get(metrics))
-        // .route(
-        //     "/signer/balances",
-        //     get({
-        //         let chains = self.chains.clone();
-        //         || async move { Json(signer_balances(&chains).await) }
-        //     }),
-        // )
-        .with_state(queue_tx.clone());
+        .with_state(queue_tx.clone())
+        .merge(
+            axum::Router::new()
+                .route("/queue/:status", get(list_items))
+                .with_state(queue),
+        )
+        .merge(
+            axum::Router::new()
+                .route("/signer/balances", get(list_signer_balances))
+                .with_state(signer_balances),
+        );
 
     tokio::spawn(axum::Server::bind(laddr).serve(app.into_make_service()));
 
@@ -54,3 +69,49 @@ async fn metrics() -> Result<String, StatusCode> {
             StatusCode::INTERNAL_SERVER_ERROR
         })
 }
+
+/// The most recently reported signer balances of all plugins that expose a keyring, keyed by plugin
+/// name.
+async fn list_signer_balances(
+    State(signer_balances): State<SignerBalances>,
+) -> Json<BTreeMap<String, Vec<SignerBalance>>> {
+    Json(signer_balances.read().unwrap().clone())
+}
+
+/// The maximum amount of items that can be requested per page from `GET /queue/:status`.
+const MAX_PER_PAGE: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ListItemsQuery {
+    tag: Option<String>,
+    item_filter: Option<String>,
+    message_filter: Option<String>,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+/// List the items in the queue with the given status, i.e. `GET /queue/failed?message_filter=%25timeout%25`.
+async fn list_items(
+    State(queue): State<QueueImpl>,
+    Path(status): Path<ItemStatus>,
+    Query(query): Query<ListItemsQuery>,
+) -> Result<Json<Vec<ItemRecord<VoyagerMessage>>>, StatusCode> {
+    let default = ItemQuery::default();
+
+    let query = ItemQuery {
+        tag: query.tag,
+        item_filters: query.item_filter.into_iter().collect(),
+        message_filters: query.message_filter.into_iter().collect(),
+        page: query.page.unwrap_or(default.page),
+        per_page: query
+            .per_page
+            .unwrap_or(default.per_page)
+            .clamp(1, MAX_PER_PAGE),
+    };
+
+    queue.list(status, &query).await.map(Json).map_err(|err| {
+        error!(error = %ErrorReporter(&err), "could not list queue items");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
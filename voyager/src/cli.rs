@@ -1,4 +1,4 @@
-use std::{ffi::OsString, str::FromStr};
+use std::{ffi::OsString, path::PathBuf, str::FromStr};
 
 use clap::{self, Parser, Subcommand};
 use unionlabs::{self, bounded::BoundedI64, ibc::core::client::height::Height, result_unwrap};
@@ -7,7 +7,7 @@ use voyager_message::{
     module::{ClientModuleInfo, ConsensusModuleInfo, ProofModuleInfo, StateModuleInfo},
     RawClientId, VoyagerMessage,
 };
-use voyager_vm::{BoxDynError, Op};
+use voyager_vm::{history::ItemStatus, BoxDynError, Op};
 
 use crate::config::default_rpc_laddr;
 
@@ -71,6 +71,27 @@ pub enum Command {
     },
     /// Run Voyager.
     Start,
+    /// Process an item from the queue locally, without submitting any transactions.
+    ///
+    /// The item is loaded directly from the database (and as such this requires either the
+    /// `pg-queue` or `sqlite-queue` backend), and is typically an item that has failed or is
+    /// already done. See `dry-run` for more information.
+    Replay {
+        id: Pg64,
+        #[command(flatten)]
+        args: DryRunArgs,
+    },
+    /// Process an op locally with the configured modules and plugins, without submitting any
+    /// transactions.
+    ///
+    /// Processing stops at transaction submission, and the datagrams that would have been
+    /// submitted are printed along with any data produced and any items that failed.
+    DryRun {
+        #[arg(value_parser(|s: &str| serde_json::from_str::<Op<VoyagerMessage>>(s)))]
+        op: Op<VoyagerMessage>,
+        #[command(flatten)]
+        args: DryRunArgs,
+    },
     /// Query and interact with the queue.
     #[command(subcommand, alias = "q")]
     Queue(QueueCmd),
@@ -96,6 +117,21 @@ pub enum Command {
     Msg(MsgCmd),
 }
 
+#[derive(Debug, clap::Args)]
+pub struct DryRunArgs {
+    /// The maximum amount of items to process and optimization passes to run before stopping.
+    #[arg(long, default_value_t = 1000)]
+    pub max_steps: usize,
+    /// Record all requests made to the modules and plugins to this file, to be replayed later
+    /// with `--from-recording`.
+    #[arg(long, conflicts_with = "from_recording")]
+    pub record: Option<PathBuf>,
+    /// Answer all requests to the modules and plugins from a file written with `--record`,
+    /// instead of starting them. This allows for reproducing a run offline.
+    #[arg(long)]
+    pub from_recording: Option<PathBuf>,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum ConfigCmd {
     /// Print the config being used by voyager.
@@ -104,6 +140,14 @@ pub enum ConfigCmd {
     Default,
     /// Print the JSON Schema for the voyager config, to be used in the top-level `$schema` field.
     Schema,
+    /// Reload the plugins, modules, and priority rules of a running voyager instance from the
+    /// config file it was started with, without draining the queue. The config file passed to
+    /// this command is only used to find the RPC address of the running instance.
+    ///
+    /// Only the plugins and modules that were added, removed, or whose config changed are
+    /// started, stopped, or restarted. Changes to any other fields in the config require a
+    /// restart.
+    Reload,
 }
 
 type Pg64 = BoundedI64<1, { i64::MAX }>;
@@ -115,13 +159,54 @@ pub enum QueueCmd {
     Enqueue {
         #[arg(value_parser(|s: &str| serde_json::from_str::<Op<VoyagerMessage>>(s)))]
         op: Op<VoyagerMessage>,
+        /// Enqueue the op with this priority, overriding any configured priority rules.
+        #[arg(long, allow_negative_numbers = true)]
+        priority: Option<i32>,
+    },
+
+    /// List the items in the queue of a running voyager instance, along with their priorities.
+    ///
+    /// This queries the running voyager instance over JSON-RPC, and as such works with all queue
+    /// backends.
+    List {
+        #[arg(long, default_value = "ready")]
+        status: ItemStatus,
+        /// Only list items waiting to be optimized with this tag.
+        #[arg(long)]
+        tag: Option<String>,
+        /// SQL `LIKE` filters for the item. This can be specified multiple times.
+        #[arg(long = "item-filter", short = 'i')]
+        item_filters: Vec<String>,
+        /// SQL `LIKE` filters for the failure message. This can be specified multiple times.
+        #[arg(long = "message-filter", short = 'm')]
+        message_filters: Vec<String>,
+        #[arg(long, default_value_t = result_unwrap!(Pg64::new_const(1)))]
+        page: Pg64,
+        #[arg(long, default_value_t = result_unwrap!(Pg64::new_const(100)))]
+        per_page: Pg64,
+    },
+
+    /// Set the priority of an item that is ready or waiting to be optimized in the queue of a
+    /// running voyager instance.
+    #[command(alias = "bump")]
+    SetPriority {
+        id: Pg64,
+        #[arg(allow_negative_numbers = true)]
+        priority: i32,
     },
 
-    // History {
-    //     id: PgId,
-    //     #[arg(long, default_value_t = result_unwrap!(Pg32::new(10)))]
-    //     max_depth: Pg32,
-    // },
+    /// Print the history of an item, walking it's parents up to `max_depth` levels deep.
+    ///
+    /// This queries the running voyager instance over JSON-RPC, and as such works with all queue
+    /// backends.
+    History {
+        id: Pg64,
+        #[arg(long, default_value_t = 10)]
+        max_depth: u32,
+        /// Print the raw history items as JSON instead of rendering them as a tree.
+        #[arg(long)]
+        json: bool,
+    },
     /// Query all failed messages.
     QueryFailed {
         #[arg(long, default_value_t = result_unwrap!(Pg64::new_const(1)))]
@@ -139,6 +224,8 @@ pub enum QueueCmd {
         ///  {"a": {"b": "c"}}
         /// ````
         ///
+        /// When using the `sqlite-queue` backend, the filters are run on the fully compact JSON instead.
+        ///
         /// This can be specified multiple times to specify multiple filters.
         #[arg(long = "item-filter", short = 'i')]
         item_filters: Vec<String>,
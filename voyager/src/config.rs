@@ -1,10 +1,20 @@
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+    ffi::OsStr,
+    fs::read_to_string,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::Path,
+};
 
+use anyhow::Context as _;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use voyager_message::context::{
-    equivalent_chain_ids::EquivalentChainIds, ModulesConfig, PluginConfig,
+use voyager_message::{
+    context::{equivalent_chain_ids::EquivalentChainIds, ModulesConfig, PluginConfig},
+    filter::PriorityRule,
+    rpc::cache::CacheConfig,
+    VoyagerMessage,
 };
+use voyager_vm::{engine::RetryConfig, recurring::RecurringOp};
 
 use crate::queue::QueueConfig;
 
@@ -21,6 +31,28 @@ pub struct Config {
     pub voyager: VoyagerConfig,
 }
 
+/// Read and parse the config file at `config_file_path`. Files with a `.jsonc` extension are
+/// parsed as JSON with comments.
+pub fn read_config_file(config_file_path: &Path) -> anyhow::Result<Config> {
+    let s = read_to_string(config_file_path).with_context(|| {
+        format!(
+            "unable to read the config file at `{}`",
+            config_file_path.to_string_lossy()
+        )
+    })?;
+
+    match config_file_path.extension().map(OsStr::as_encoded_bytes) {
+        Some(b"jsonc") => serde_jsonc::from_str::<Config>(&s).map_err(anyhow::Error::from),
+        _ => serde_json::from_str::<Config>(&s).map_err(anyhow::Error::from),
+    }
+    .with_context(|| {
+        format!(
+            "unable to parse the config file at `{}`",
+            config_file_path.to_string_lossy()
+        )
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct VoyagerConfig {
@@ -33,6 +65,22 @@ pub struct VoyagerConfig {
     // TODO: Specify per plugin
     #[serde(default = "default_optimizer_delay_milliseconds")]
     pub optimizer_delay_milliseconds: u64,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// Rules for assigning priorities to ops as they are enqueued, checked in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub priority_rules: Vec<PriorityRule>,
+    /// Ops to enqueue on a fixed schedule.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recurring: Vec<RecurringOp<VoyagerMessage>>,
+    /// Cache the results of module queries that are immutable or at the tip of the chain,
+    /// reducing the load on the nodes the modules query. Disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheConfig>,
+    /// How often to collect the signer balances reported by the transaction plugins, exposed as
+    /// metrics and via `/signer/balances`.
+    #[serde(default = "default_signer_balances_interval_seconds")]
+    pub signer_balances_interval_seconds: u64,
 }
 
 #[must_use]
@@ -52,3 +100,9 @@ pub const fn default_rpc_laddr() -> SocketAddr {
 pub const fn default_optimizer_delay_milliseconds() -> u64 {
     100
 }
+
+#[must_use]
+#[inline]
+pub const fn default_signer_balances_interval_seconds() -> u64 {
+    60
+}
@@ -0,0 +1,550 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tracing::{debug, info, info_span, warn, Instrument};
+use unionlabs::ErrorReporter;
+use voyager_message::{
+    call::{Call, SubmitTx},
+    context::Context,
+    core::ChainId,
+    data::Data,
+    filter::JaqInterestFilter,
+    pass::PluginOptPass,
+    PluginMessage, VoyagerMessage,
+};
+use voyager_vm::{
+    call,
+    filter::{FilterResult, InterestFilter},
+    now,
+    pass::{Pass, PassResult},
+    promise, seq, void, ItemId, Op, Promise,
+};
+
+/// Processes an op locally, without a queue, stopping at transaction submission.
+///
+/// This mirrors what the queue does with the op as closely as possible: items that a plugin is
+/// interested in are run through that plugin's optimization pass, and all other items are
+/// processed with [`Op::process`]. However, any [`Call::SubmitTx`] is collected instead of being
+/// processed (including ones nested within other ops), as is any call to a transaction plugin
+/// (see [`transaction_plugins`]), such that nothing is ever submitted to a chain.
+pub struct DryRun<'a> {
+    context: &'a Context,
+    filter: &'a JaqInterestFilter,
+    transaction_plugins: BTreeSet<String>,
+    max_steps: usize,
+    optimizer_delay: Duration,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DryRunOutput {
+    /// The transactions that would have been submitted.
+    pub submitted: Vec<SubmitTx>,
+    /// The calls to transaction plugins that would have been made, such as submissions that have
+    /// already been optimized by the plugin.
+    pub submitted_plugin_calls: Vec<PluginMessage>,
+    /// Data that was produced outside of an aggregation.
+    pub data: Vec<Data>,
+    /// Items that failed to be processed, along with their error.
+    pub failed: Vec<FailedItem>,
+    /// Items that were not yet processed when the step limit was reached.
+    pub pending: Vec<Op<VoyagerMessage>>,
+    /// The amount of items processed and optimization passes run.
+    pub steps: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailedItem {
+    pub item: Op<VoyagerMessage>,
+    pub error: String,
+}
+
+impl<'a> DryRun<'a> {
+    #[must_use]
+    pub fn new(
+        context: &'a Context,
+        filter: &'a JaqInterestFilter,
+        max_steps: usize,
+        optimizer_delay: Duration,
+    ) -> Self {
+        let transaction_plugins = transaction_plugins(filter, filter.plugin_names());
+
+        debug!(?transaction_plugins, "found transaction plugins");
+
+        Self {
+            context,
+            filter,
+            transaction_plugins,
+            max_steps,
+            optimizer_delay,
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub async fn run(&self, op: Op<VoyagerMessage>) -> DryRunOutput {
+        let mut output = DryRunOutput::default();
+
+        let mut ready = VecDeque::from(op.normalize());
+        let mut optimize = BTreeMap::<String, Vec<Op<VoyagerMessage>>>::new();
+
+        // item ids are only used for tracing and for threading through module requests, so they
+        // just need to be unique within this run
+        let mut next_id = 1;
+
+        while output.steps < self.max_steps {
+            if let Some(op) = ready.pop_front() {
+                output.steps += 1;
+
+                let (_, op) = op.take_priority();
+
+                // we only care about the result, not about the retry policy
+                let op = match op {
+                    Op::Retry { op, .. } => *op,
+                    op => op,
+                };
+
+                let op = match take_next_submission(op, &self.transaction_plugins) {
+                    Ok((submission, op)) => {
+                        match submission {
+                            Submission::SubmitTx(submit_tx) => {
+                                info!(
+                                    chain_id = %submit_tx.chain_id,
+                                    datagrams = submit_tx.datagrams.len(),
+                                    "reached transaction submission, not submitting"
+                                );
+                                output.submitted.push(submit_tx);
+                            }
+                            Submission::PluginCall(msg) => {
+                                info!(
+                                    plugin = %msg.plugin,
+                                    "reached call to transaction plugin, not submitting"
+                                );
+                                output.submitted_plugin_calls.push(msg);
+                            }
+                        }
+                        ready.extend(op.into_iter().flat_map(Op::normalize));
+                        continue;
+                    }
+                    Err(Op::Data(data)) => {
+                        output.data.push(data);
+                        continue;
+                    }
+                    Err(op) => op,
+                };
+
+                if let FilterResult::Interest(tag) = self.filter.check_interest(&op) {
+                    debug!(%tag, "item is waiting to be optimized");
+                    optimize.entry(tag.to_owned()).or_default().push(op);
+                    continue;
+                }
+
+                if let Some(not_before) = op.not_before() {
+                    let delay = not_before.saturating_sub(now());
+                    if delay > 0 {
+                        info!(%delay, "item is scheduled, waiting");
+                        tokio::time::sleep(Duration::from_secs(delay)).await;
+                    }
+                }
+
+                let id = ItemId::new(next_id).expect("id is positive; qed;");
+                next_id += 1;
+
+                match op
+                    .clone()
+                    .process(voyager_vm::Context::new(id, self.context), 0)
+                    .instrument(info_span!("processing item", id = id.raw()))
+                    .await
+                {
+                    Ok(Some(op)) => ready.extend(op.normalize()),
+                    Ok(None) => {}
+                    Err(error) => {
+                        warn!(error = %ErrorReporter(&error), "error processing item");
+                        output.failed.push(FailedItem {
+                            item: op,
+                            error: ErrorReporter(&error).to_string(),
+                        });
+                    }
+                }
+            } else if let Some((tag, ops)) = optimize.pop_first() {
+                output.steps += 1;
+
+                let res = match self.context.plugin(&tag) {
+                    Ok(plugin) => PluginOptPass::new(&plugin)
+                        .run_pass(ops.clone())
+                        .instrument(info_span!("optimizing items", %tag))
+                        .await
+                        .map_err(|error| ErrorReporter(&error).to_string()),
+                    Err(error) => Err(ErrorReporter(&error).to_string()),
+                };
+
+                match res {
+                    Ok(PassResult {
+                        optimize_further,
+                        ready: optimized,
+                    }) => {
+                        let made_progress = !optimized.is_empty();
+
+                        ready.extend(optimized.into_iter().flat_map(|(_, op)| op.normalize()));
+
+                        for (_, op, tag) in optimize_further {
+                            optimize.entry(tag).or_default().push(op);
+                        }
+
+                        // the plugin is waiting for something (i.e. a batch to fill up), give it
+                        // some time before running the pass again
+                        if !made_progress {
+                            tokio::time::sleep(self.optimizer_delay).await;
+                        }
+                    }
+                    Err(error) => {
+                        warn!(%tag, %error, "optimization pass returned with error");
+                        output.failed.extend(ops.into_iter().map(|item| FailedItem {
+                            item,
+                            error: error.clone(),
+                        }));
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+
+        output.pending = ready
+            .into_iter()
+            .chain(optimize.into_values().flatten())
+            .collect();
+
+        if !output.pending.is_empty() {
+            warn!(
+                pending = output.pending.len(),
+                "step limit reached with items still pending"
+            );
+        }
+
+        output
+    }
+}
+
+/// Find the plugins out of `plugins` that submit transactions.
+///
+/// Transaction plugins are named `<plugin>/<chain id>`, and are the plugins that are interested in
+/// a [`Call::SubmitTx`] to the chain in their name. Once such a plugin has optimized a submission,
+/// it calls itself with the optimized transaction, which would then be submitted when processed.
+pub fn transaction_plugins(
+    filter: &impl InterestFilter<VoyagerMessage>,
+    plugins: impl IntoIterator<Item = String>,
+) -> BTreeSet<String> {
+    plugins
+        .into_iter()
+        .filter(|plugin| {
+            let Some((_, chain_id)) = plugin.rsplit_once('/') else {
+                return false;
+            };
+
+            let submit_tx = call(SubmitTx {
+                chain_id: ChainId::new(chain_id.to_owned()),
+                datagrams: vec![],
+            });
+
+            matches!(
+                filter.check_interest(&submit_tx),
+                FilterResult::Interest(tag) if tag == plugin
+            )
+        })
+        .collect()
+}
+
+/// A message that would submit a transaction if it were processed.
+#[derive(Debug, PartialEq)]
+enum Submission {
+    SubmitTx(SubmitTx),
+    /// A call to one of the transaction plugins.
+    PluginCall(PluginMessage),
+}
+
+/// If the next message that [`Op::process`] would handle within `op` would submit a transaction
+/// (see [`Submission`]), take it out of `op`, returning the message along with what remains of
+/// `op` once the transaction is submitted. Otherwise, `op` is returned unchanged.
+///
+/// This follows the same traversal as [`Op::process`], such that transactions are taken in the
+/// same order that they would be submitted in.
+#[allow(clippy::result_large_err)]
+fn take_next_submission(
+    op: Op<VoyagerMessage>,
+    transaction_plugins: &BTreeSet<String>,
+) -> Result<(Submission, Option<Op<VoyagerMessage>>), Op<VoyagerMessage>> {
+    match op {
+        // submitting a transaction always resolves to a noop
+        Op::Call(Call::SubmitTx(submit_tx)) => Ok((Submission::SubmitTx(submit_tx), None)),
+        // the continuation of the call is unknown, so nothing further is processed from it
+        Op::Call(Call::Plugin(msg)) if transaction_plugins.contains(&msg.plugin) => {
+            Ok((Submission::PluginCall(msg), None))
+        }
+        Op::Seq(mut queue) => {
+            let Some(op) = queue.pop_front() else {
+                return Err(Op::Seq(queue));
+            };
+
+            match take_next_submission(op, transaction_plugins) {
+                Ok((submission, op)) => {
+                    if let Some(op) = op {
+                        queue.push_front(op);
+                    }
+
+                    Ok((submission, Some(seq(queue))))
+                }
+                Err(op) => {
+                    queue.push_front(op);
+                    Err(Op::Seq(queue))
+                }
+            }
+        }
+        Op::Conc(mut queue) => {
+            let Some(op) = queue.pop_front() else {
+                return Err(Op::Conc(queue));
+            };
+
+            match take_next_submission(op, transaction_plugins) {
+                Ok((submission, op)) => {
+                    queue.extend(op);
+                    Ok((submission, Some(Op::Conc(queue))))
+                }
+                Err(op) => {
+                    queue.push_front(op);
+                    Err(Op::Conc(queue))
+                }
+            }
+        }
+        Op::Promise(Promise {
+            mut queue,
+            mut data,
+            receiver,
+        }) => {
+            let Some(op) = queue.pop_front() else {
+                return Err(promise(queue, data, receiver));
+            };
+
+            match take_next_submission(op, transaction_plugins) {
+                Ok((submission, op)) => {
+                    match op {
+                        Some(Op::Data(d)) => data.push_back(d),
+                        Some(op) => queue.push_back(op),
+                        None => {}
+                    }
+
+                    Ok((submission, Some(promise(queue, data, receiver))))
+                }
+                Err(op) => {
+                    queue.push_front(op);
+                    Err(promise(queue, data, receiver))
+                }
+            }
+        }
+        Op::Void(op) => match take_next_submission(*op, transaction_plugins) {
+            Ok((submission, op)) => Ok((submission, op.map(void))),
+            Err(op) => Err(void(op)),
+        },
+        // the wrappers are dropped once the contained message is handled
+        Op::Retry { attempt, until, op } => match take_next_submission(*op, transaction_plugins) {
+            Ok(res) => Ok(res),
+            Err(op) => Err(Op::Retry {
+                attempt,
+                until,
+                op: Box::new(op),
+            }),
+        },
+        Op::Priority { priority, op } => match take_next_submission(*op, transaction_plugins) {
+            Ok(res) => Ok(res),
+            Err(op) => Err(Op::Priority {
+                priority,
+                op: Box::new(op),
+            }),
+        },
+        op => Err(op),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use voyager_message::{callback::Callback, core::ChainId, PluginMessage};
+    use voyager_vm::{call, conc, data, defer, priority_op};
+
+    use super::*;
+
+    fn submit_tx(chain_id: &str) -> SubmitTx {
+        SubmitTx {
+            chain_id: ChainId::new(chain_id.to_owned()),
+            datagrams: vec![],
+        }
+    }
+
+    const TRANSACTION_PLUGIN: &str = "voyager-transaction-plugin-ethereum/1";
+
+    /// Take the next submission from `op`, with [`TRANSACTION_PLUGIN`] as the only transaction
+    /// plugin.
+    #[allow(clippy::result_large_err)]
+    fn take(
+        op: Op<VoyagerMessage>,
+    ) -> Result<(Submission, Option<Op<VoyagerMessage>>), Op<VoyagerMessage>> {
+        take_next_submission(op, &[TRANSACTION_PLUGIN.to_owned()].into())
+    }
+
+    fn submit_multicall() -> PluginMessage {
+        PluginMessage::new(TRANSACTION_PLUGIN, json!({ "submit_multicall": [] }))
+    }
+
+    fn plugin_call(n: u64) -> Op<VoyagerMessage> {
+        call(Call::Plugin(PluginMessage::new("plugin", json!(n))))
+    }
+
+    fn plugin_data(n: u64) -> Op<VoyagerMessage> {
+        data(Data::Plugin(PluginMessage::new("plugin", json!(n))))
+    }
+
+    fn callback() -> Callback {
+        Callback::Plugin(PluginMessage::new("plugin", json!({})))
+    }
+
+    #[test]
+    fn top_level_submit_tx() {
+        let (tx, rest) = take(call(submit_tx("a"))).unwrap();
+
+        assert_eq!(tx, Submission::SubmitTx(submit_tx("a")));
+        assert_eq!(rest, None);
+    }
+
+    #[test]
+    fn submit_tx_in_seq() {
+        let op = seq([call(submit_tx("a")), call(submit_tx("b")), plugin_call(1)]);
+
+        let (tx, rest) = take(op).unwrap();
+        assert_eq!(tx, Submission::SubmitTx(submit_tx("a")));
+
+        let (tx, rest) = take(rest.unwrap()).unwrap();
+        assert_eq!(tx, Submission::SubmitTx(submit_tx("b")));
+
+        // the rest of the sequence must be processed as usual
+        assert_eq!(take(rest.unwrap()).unwrap_err(), seq([plugin_call(1)]));
+    }
+
+    #[test]
+    fn submit_tx_after_other_ops_in_seq_is_not_taken() {
+        let op = seq([defer(1), call(submit_tx("a"))]);
+
+        assert_eq!(take(op.clone()).unwrap_err(), op);
+    }
+
+    #[test]
+    fn submit_tx_in_conc() {
+        let op = conc([call(submit_tx("a")), plugin_call(1), plugin_call(2)]);
+
+        let (tx, rest) = take(op).unwrap();
+
+        assert_eq!(tx, Submission::SubmitTx(submit_tx("a")));
+        assert_eq!(rest, Some(conc([plugin_call(1), plugin_call(2)])));
+    }
+
+    #[test]
+    fn submit_tx_in_promise() {
+        let op = promise(
+            [seq([call(submit_tx("a")), plugin_data(1)]), plugin_call(2)],
+            [],
+            callback(),
+        );
+
+        let (tx, rest) = take(op).unwrap();
+
+        assert_eq!(tx, Submission::SubmitTx(submit_tx("a")));
+        assert_eq!(
+            rest,
+            Some(promise(
+                [plugin_call(2), seq([plugin_data(1)])],
+                [],
+                callback()
+            ))
+        );
+    }
+
+    #[test]
+    fn submit_tx_in_wrappers() {
+        let op = priority_op(
+            1,
+            void(seq([
+                Op::Retry {
+                    attempt: 1,
+                    until: 0,
+                    op: Box::new(call(submit_tx("a"))),
+                },
+                plugin_call(1),
+            ])),
+        );
+
+        let (tx, rest) = take(op).unwrap();
+
+        assert_eq!(tx, Submission::SubmitTx(submit_tx("a")));
+        assert_eq!(rest, Some(void(seq([plugin_call(1)]))));
+    }
+
+    #[test]
+    fn optimized_submission_in_seq() {
+        // an item that was already optimized by the transaction plugin, i.e. when replaying an
+        // item from the failed queue
+        let op = seq([call(Call::Plugin(submit_multicall())), plugin_call(1)]);
+
+        let (tx, rest) = take(op).unwrap();
+
+        assert_eq!(tx, Submission::PluginCall(submit_multicall()));
+        assert_eq!(rest, Some(seq([plugin_call(1)])));
+    }
+
+    #[test]
+    fn finds_transaction_plugins() {
+        struct Filter;
+
+        impl InterestFilter<VoyagerMessage> for Filter {
+            fn check_interest<'a>(&'a self, op: &Op<VoyagerMessage>) -> FilterResult<'a> {
+                match op {
+                    Op::Call(Call::SubmitTx(SubmitTx { chain_id, .. }))
+                        if chain_id.as_str() == "1" =>
+                    {
+                        FilterResult::Interest(TRANSACTION_PLUGIN)
+                    }
+                    _ => FilterResult::NoInterest,
+                }
+            }
+        }
+
+        assert_eq!(
+            transaction_plugins(
+                &Filter,
+                [
+                    TRANSACTION_PLUGIN.to_owned(),
+                    "voyager-transaction-plugin-ethereum/2".to_owned(),
+                    "voyager-event-source-plugin-ethereum/1".to_owned(),
+                    "plugin".to_owned(),
+                ]
+            ),
+            [TRANSACTION_PLUGIN.to_owned()].into()
+        );
+    }
+
+    #[test]
+    fn no_submit_tx() {
+        for op in [
+            plugin_call(1),
+            plugin_data(1),
+            seq([plugin_call(1), call(submit_tx("a"))]),
+            conc([plugin_call(1), call(submit_tx("a"))]),
+            promise([], [], callback()),
+            call(Call::Plugin(PluginMessage::new(
+                "voyager-transaction-plugin-ethereum/2",
+                json!({ "submit_multicall": [] }),
+            ))),
+        ] {
+            assert_eq!(take(op.clone()).unwrap_err(), op);
+        }
+    }
+}
@@ -10,8 +10,7 @@
 )]
 
 use std::{
-    collections::HashMap, ffi::OsStr, fmt::Write, fs::read_to_string, iter, net::SocketAddr,
-    path::PathBuf, process::ExitCode,
+    collections::HashMap, fmt::Write, iter, net::SocketAddr, path::PathBuf, process::ExitCode,
 };
 
 use anyhow::{anyhow, Context as _};
@@ -21,6 +20,7 @@ use ibc_union_spec::IbcUnion;
 use pg_queue::PgQueueConfig;
 use schemars::gen::{SchemaGenerator, SchemaSettings};
 use serde::Serialize;
+use sqlx::Either;
 use tikv_jemallocator::Jemalloc;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
@@ -29,22 +29,33 @@ use voyager_message::{
     callback::AggregateMsgUpdateClientsFromOrderedHeaders,
     context::{
         equivalent_chain_ids::EquivalentChainIds, get_plugin_info,
-        ibc_spec_handler::IbcSpecHandler, Context, ModulesConfig,
+        ibc_spec_handler::IbcSpecHandler, recording::Recording, Context, ModulesConfig,
     },
     core::{IbcSpec, QueryHeight},
     filter::{make_filter, run_filter, JaqInterestFilter},
+    module::PluginInfo,
     rpc::{IbcState, VoyagerRpcClient},
     VoyagerMessage,
 };
-use voyager_vm::{call, filter::FilterResult, promise, Op, Queue};
+use voyager_vm::{
+    call, engine::RetryConfig, filter::FilterResult, history::render_history, inspect::ItemQuery,
+    priority::PriorityConfig, priority_op, promise, Op, Queue,
+};
 
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
 use crate::{
-    cli::{AppArgs, Command, ConfigCmd, ModuleCmd, MsgCmd, PluginCmd, QueueCmd, RpcCmd},
-    config::{default_rest_laddr, default_rpc_laddr, Config, VoyagerConfig},
+    cli::{
+        AppArgs, Command, ConfigCmd, DryRunArgs, ModuleCmd, MsgCmd, PluginCmd, QueueCmd, RpcCmd,
+    },
+    config::{
+        default_rest_laddr, default_rpc_laddr, default_signer_balances_interval_seconds,
+        read_config_file, Config, VoyagerConfig,
+    },
+    dry_run::DryRun,
     queue::{QueueConfig, Voyager},
+    rpc::{ConfigRpcClient, QueueRpcClient},
     utils::make_msg_create_client,
 };
 
@@ -57,7 +68,10 @@ compile_error!(
 pub mod api;
 pub mod cli;
 pub mod config;
+pub mod dry_run;
 pub mod queue;
+pub mod rpc;
+pub mod signer_balances;
 
 fn main() -> ExitCode {
     let args = AppArgs::parse();
@@ -105,35 +119,15 @@ fn main() -> ExitCode {
 #[allow(clippy::too_many_lines)]
 // NOTE: This function is a mess, will be cleaned up
 async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
-    let get_voyager_config = || match &args.config_file_path {
-        Some(config_file_path) => {
-            let config_file_path = PathBuf::from(config_file_path);
-            let ext = config_file_path.extension();
-            read_to_string(&config_file_path)
-                .with_context(|| {
-                    format!(
-                        "unable to read the config file at `{}`",
-                        config_file_path.to_string_lossy()
-                    )
-                })
-                .and_then(|s| match ext.map(OsStr::as_encoded_bytes) {
-                    Some(b"jsonc") => serde_jsonc::from_str::<Config>(&s).with_context(|| {
-                        format!(
-                            "unable to parse the config file at `{}`",
-                            config_file_path.to_string_lossy()
-                        )
-                    }),
-                    _ => serde_json::from_str::<Config>(&s).with_context(|| {
-                        format!(
-                            "unable to parse the config file at `{}`",
-                            config_file_path.to_string_lossy()
-                        )
-                    }),
-                })
-        }
-        None => Err(anyhow!("config file must be specified")),
+    let get_voyager_config_file_path = || {
+        args.config_file_path
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow!("config file must be specified"))
     };
 
+    let get_voyager_config = || get_voyager_config_file_path().and_then(|p| read_config_file(&p));
+
     match args.command {
         Command::Config(cmd) => match cmd {
             ConfigCmd::Print => {
@@ -160,10 +154,26 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
                         min_connections: None,
                         idle_timeout: None,
                         max_lifetime: None,
+                        retention: None,
+                        priority: PriorityConfig::default(),
+                        ha: None,
                     }),
                     optimizer_delay_milliseconds: 100,
+                    retry: RetryConfig::default(),
+                    priority_rules: vec![],
+                    recurring: vec![],
+                    cache: None,
+                    signer_balances_interval_seconds: default_signer_balances_interval_seconds(),
                 },
             }),
+            ConfigCmd::Reload => {
+                let config = get_voyager_config()?;
+
+                let voyager_client = jsonrpsee::http_client::HttpClient::builder()
+                    .build(format!("http://{}", config.voyager.rpc_laddr))?;
+
+                print_json(&voyager_client.config_reload().await?);
+            }
             ConfigCmd::Schema => print_json(
                 &SchemaGenerator::new(SchemaSettings::draft2019_09().with(|s| {
                     s.option_nullable = true;
@@ -173,12 +183,51 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
             ),
         },
         Command::Start => {
-            let voyager = Voyager::new(get_voyager_config()?).await?;
+            let config_file_path = get_voyager_config_file_path()?;
+
+            let voyager =
+                Voyager::new(read_config_file(&config_file_path)?, config_file_path).await?;
 
             info!("starting relay service");
 
             voyager.run().await?;
         }
+        Command::Replay { id, args } => {
+            let config = get_voyager_config()?;
+
+            let history = match config.voyager.queue.clone() {
+                QueueConfig::PgQueue(cfg) => {
+                    pg_queue::PgQueue::<VoyagerMessage>::new(cfg)
+                        .await?
+                        .history(id.inner(), 0)
+                        .await?
+                }
+                QueueConfig::SqliteQueue(cfg) => {
+                    sqlite_queue::SqliteQueue::<VoyagerMessage>::open(cfg)
+                        .await?
+                        .history(id.inner(), 0)
+                        .await?
+                }
+                QueueConfig::InMemory(_) => {
+                    return Err(anyhow!(
+                        "no database set in config, replaying an item requires \
+                        either the `pg-queue` or `sqlite-queue` database backend"
+                    ))
+                }
+            };
+
+            let item = history
+                .into_iter()
+                .find(|item| item.id == id.inner())
+                .ok_or(anyhow!("item {id} not found"))?;
+
+            info!(%id, status = item.status.as_str(), "replaying item");
+
+            dry_run(config, item.item, args).await?;
+        }
+        Command::DryRun { op, args } => {
+            dry_run(get_voyager_config()?, op, args).await?;
+        }
         Command::Plugin(cmd) => match cmd {
             PluginCmd::Interest {
                 plugin_name,
@@ -253,67 +302,143 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
             ModuleCmd::Client(_) => todo!(),
         },
         Command::Queue(cli_msg) => {
-            let db = || {
-                Ok(match get_voyager_config()?.voyager.queue {
-                    QueueConfig::PgQueue(cfg) => pg_queue::PgQueue::<VoyagerMessage>::new(cfg),
-                    QueueConfig::InMemory => {
+            let db = || async {
+                Ok::<_, anyhow::Error>(match get_voyager_config()?.voyager.queue {
+                    QueueConfig::PgQueue(cfg) => {
+                        Either::Left(pg_queue::PgQueue::<VoyagerMessage>::new(cfg).await?)
+                    }
+                    QueueConfig::SqliteQueue(cfg) => {
+                        Either::Right(sqlite_queue::SqliteQueue::<VoyagerMessage>::open(cfg).await?)
+                    }
+                    QueueConfig::InMemory(_) => {
                         return Err(anyhow!(
                             "no database set in config, queue commands \
-                            require the `pg-queue` database backend"
+                            require either the `pg-queue` or `sqlite-queue` \
+                            database backend"
                         ))
                     }
                 })
             };
 
             match cli_msg {
-                QueueCmd::Enqueue { op } => {
+                QueueCmd::Enqueue { op, priority } => {
+                    let op = match priority {
+                        Some(priority) => priority_op(priority, op),
+                        None => op,
+                    };
+
                     send_enqueue(&get_voyager_config()?.voyager.rest_laddr, op).await?;
                 }
-                // NOTE: Temporarily disabled until i figure out a better way to implement this with the new queue design
-                // cli::QueueCmd::History { id, max_depth } => {
-                //     // let results = query_as!(
-                //     //     Record,
-                //     //     r#"SELECT id as "id!", parent, item as "item!: Item" FROM get_list($1, $2) ORDER BY id ASC"#,
-                //     //     id.inner(),
-                //     //     max_depth.inner()
-                //     // )
-                //     // .fetch_all(&db)
-                //     // .await
-                //     // .unwrap();
-
-                //     // println!("{}", serde_json::to_string_pretty(&results).unwrap());
-
-                //     todo!();
-                // }
-                QueueCmd::QueryFailed {
-                    page,
-                    per_page,
+                QueueCmd::List {
+                    status,
+                    tag,
                     item_filters,
                     message_filters,
+                    page,
+                    per_page,
                 } => {
-                    let record = db()?
-                        .await?
-                        .query_failed(page.into(), per_page.into(), item_filters, message_filters)
+                    let voyager_client = jsonrpsee::http_client::HttpClient::builder().build(
+                        format!("http://{}", get_voyager_config()?.voyager.rpc_laddr),
+                    )?;
+
+                    let items = voyager_client
+                        .queue_list(
+                            status,
+                            ItemQuery {
+                                tag,
+                                item_filters,
+                                message_filters,
+                                page: page.inner(),
+                                per_page: per_page.inner(),
+                            },
+                        )
                         .await?;
 
-                    print_json(&record);
+                    print_json(&items);
                 }
+                QueueCmd::SetPriority { id, priority } => {
+                    let voyager_client = jsonrpsee::http_client::HttpClient::builder().build(
+                        format!("http://{}", get_voyager_config()?.voyager.rpc_laddr),
+                    )?;
+
+                    if voyager_client
+                        .queue_set_priority(id.inner(), priority)
+                        .await?
+                    {
+                        println!("set priority of item {id} to {priority}");
+                    } else {
+                        return Err(anyhow!("item {id} is not ready or waiting to be optimized"));
+                    }
+                }
+                QueueCmd::History {
+                    id,
+                    max_depth,
+                    json,
+                } => {
+                    let voyager_client = jsonrpsee::http_client::HttpClient::builder().build(
+                        format!("http://{}", get_voyager_config()?.voyager.rpc_laddr),
+                    )?;
+
+                    let history = voyager_client.queue_history(id.inner(), max_depth).await?;
+
+                    if json {
+                        print_json(&history);
+                    } else {
+                        print!("{}", render_history(id.inner(), &history));
+                    }
+                }
+                QueueCmd::QueryFailed {
+                    page,
+                    per_page,
+                    item_filters,
+                    message_filters,
+                } => match db().await? {
+                    Either::Left(q) => print_json(
+                        &q.query_failed(
+                            page.into(),
+                            per_page.into(),
+                            item_filters,
+                            message_filters,
+                        )
+                        .await?,
+                    ),
+                    Either::Right(q) => print_json(
+                        &q.query_failed(
+                            page.into(),
+                            per_page.into(),
+                            item_filters,
+                            message_filters,
+                        )
+                        .await?,
+                    ),
+                },
                 QueueCmd::QueryFailedById { id, requeue } => {
-                    let q = db()?.await?;
+                    let filter = JaqInterestFilter::new(vec![]).expect("empty filter can be built");
 
-                    let record = q.query_failed_by_id(id.inner()).await?;
+                    match db().await? {
+                        Either::Left(q) => {
+                            let record = q.query_failed_by_id(id.inner()).await?;
 
-                    if requeue {
-                        if let Some(record) = record.as_ref().map(|r| r.item.0.clone()) {
-                            q.enqueue(
-                                record,
-                                &JaqInterestFilter::new(vec![]).expect("empty filter can be built"),
-                            )
-                            .await?;
+                            if requeue {
+                                if let Some(record) = record.as_ref().map(|r| r.item.0.clone()) {
+                                    q.enqueue(record, &filter).await?;
+                                }
+                            }
+
+                            print_json(&record);
                         }
-                    }
+                        Either::Right(q) => {
+                            let record = q.query_failed_by_id(id.inner()).await?;
+
+                            if requeue {
+                                if let Some(record) = record.as_ref().map(|r| r.item.0.clone()) {
+                                    q.enqueue(record, &filter).await?;
+                                }
+                            }
 
-                    print_json(&record);
+                            print_json(&record);
+                        }
+                    }
                 }
             }
         }
@@ -628,6 +753,63 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Process `op` locally with [`DryRun`], printing the output.
+async fn dry_run(config: Config, op: Op<VoyagerMessage>, args: DryRunArgs) -> anyhow::Result<()> {
+    let recording = match (&args.record, &args.from_recording) {
+        (Some(_), _) => Some(Recording::record()),
+        (None, Some(path)) => Some(Recording::load(path)?),
+        (None, None) => None,
+    };
+
+    let context = Context::new_with_recording(
+        config.plugins,
+        config.modules,
+        config.equivalent_chain_ids,
+        |h| {
+            h.register::<IbcClassic>();
+            h.register::<IbcUnion>();
+        },
+        recording.clone(),
+    )
+    .await?;
+
+    if !recording.as_ref().is_some_and(Recording::is_replay) {
+        // weird race condition in Context::new that i don't feel like debugging right now
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+
+    let interest_filter = JaqInterestFilter::new(
+        context
+            .interest_filters()
+            .into_iter()
+            .map(|(name, interest_filter)| PluginInfo {
+                name,
+                interest_filter,
+            })
+            .collect(),
+    )?;
+
+    let output = DryRun::new(
+        &context,
+        &interest_filter,
+        args.max_steps,
+        std::time::Duration::from_millis(config.voyager.optimizer_delay_milliseconds),
+    )
+    .run(op)
+    .await;
+
+    if let (Some(path), Some(recording)) = (&args.record, &recording) {
+        recording.save(path)?;
+        info!(path = %path.display(), "saved recording");
+    }
+
+    context.shutdown().await;
+
+    print_json(&output);
+
+    Ok(())
+}
+
 async fn send_enqueue(
     rest_laddr: &SocketAddr,
     op: Op<VoyagerMessage>,
@@ -684,10 +866,10 @@ pub mod utils {
             .query_height(&counterparty_chain_id, height)
             .await?;
 
-        let counterparty_client_bootstrap_module = ctx
-            .rpc_server
-            .modules()?
-            .client_bootstrap_module(&counterparty_chain_id, &client_type)?;
+        let modules = ctx.rpc_server.modules()?;
+
+        let counterparty_client_bootstrap_module =
+            modules.client_bootstrap_module(&counterparty_chain_id, &client_type)?;
 
         let self_client_state = counterparty_client_bootstrap_module
             .self_client_state(height)
@@ -719,10 +901,7 @@ pub mod utils {
         //     ));
         // }
 
-        let client_module =
-            ctx.rpc_server
-                .modules()?
-                .client_module(&client_type, &ibc_interface, &ibc_spec_id)?;
+        let client_module = modules.client_module(&client_type, &ibc_interface, &ibc_spec_id)?;
 
         Ok(call(SubmitTx {
             chain_id,
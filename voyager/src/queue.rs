@@ -1,6 +1,8 @@
 #![allow(clippy::type_complexity)]
 
-use std::{fmt::Debug, net::SocketAddr, panic::AssertUnwindSafe};
+use std::{
+    collections::HashSet, fmt::Debug, net::SocketAddr, panic::AssertUnwindSafe, path::PathBuf,
+};
 
 use anyhow::{bail, Context as _};
 use futures::{future::BoxFuture, stream::FuturesUnordered, Future, FutureExt, StreamExt};
@@ -9,18 +11,37 @@ use ibc_union_spec::IbcUnion;
 use pg_queue::{PgQueue, PgQueueConfig};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sqlite_queue::{SqliteQueue, SqliteQueueConfig};
+use tokio::sync::{mpsc, watch};
 use tracing::{debug, error, info, info_span, trace, trace_span};
 use tracing_futures::Instrument;
 use unionlabs::ErrorReporter;
 use voyager_message::{
-    context::Context, filter::JaqInterestFilter, into_value, module::PluginInfo,
-    pass::PluginOptPass, rpc::VoyagerRpcServer, VoyagerMessage,
+    context::{get_plugin_info, Context, ReloadSummary},
+    filter::{JaqInterestFilter, PriorityRule},
+    into_value,
+    module::PluginInfo,
+    pass::PluginOptPass,
+    rpc::VoyagerRpcServer,
+    VoyagerMessage,
 };
 use voyager_vm::{
-    engine::Engine, in_memory::InMemoryQueue, pass::Pass, BoxDynError, Captures, ItemId, Op, Queue,
+    engine::{Engine, RetryConfig},
+    history::{HistoryItem, ItemStatus},
+    in_memory::{InMemoryQueue, InMemoryQueueConfig},
+    inspect::{ItemQuery, ItemRecord},
+    now,
+    pass::Pass,
+    recurring::RecurringOp,
+    BoxDynError, Captures, ItemId, Op, Queue,
 };
 
-use crate::{api, config::Config};
+use crate::{
+    api,
+    config::{read_config_file, Config},
+    rpc::{ConfigRpcServer, ConfigServer, QueueRpcServer, QueueServer, ReloadRequest},
+    signer_balances::{self, SignerBalances},
+};
 
 #[derive(Debug)]
 pub struct Voyager {
@@ -31,19 +52,27 @@ pub struct Voyager {
     rpc_laddr: SocketAddr,
     queue: QueueImpl,
     optimizer_delay_milliseconds: u64,
+    retry: RetryConfig,
+    priority_rules: Vec<PriorityRule>,
+    recurring: Vec<RecurringOp<VoyagerMessage>>,
+    signer_balances_interval_seconds: u64,
+    /// The config file this instance was started with, re-read on reload.
+    config_file_path: PathBuf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case", tag = "type")]
 pub enum QueueConfig {
-    InMemory,
+    InMemory(InMemoryQueueConfig),
     PgQueue(PgQueueConfig),
+    SqliteQueue(SqliteQueueConfig),
 }
 
 #[derive(Debug, Clone)]
 pub enum QueueImpl {
     InMemory(InMemoryQueue<VoyagerMessage>),
     PgQueue(PgQueue<VoyagerMessage>),
+    SqliteQueue(SqliteQueue<VoyagerMessage>),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -51,6 +80,7 @@ pub enum QueueImpl {
 pub enum AnyQueueError {
     InMemory(std::convert::Infallible),
     PgQueue(sqlx::Error),
+    SqliteQueue(sqlx::Error),
 }
 
 impl Queue<VoyagerMessage> for QueueImpl {
@@ -60,14 +90,19 @@ impl Queue<VoyagerMessage> for QueueImpl {
     fn new(cfg: Self::Config) -> impl Future<Output = Result<Self, Self::Error>> {
         async move {
             Ok(match cfg {
-                QueueConfig::InMemory => Self::InMemory(
-                    InMemoryQueue::new(())
+                QueueConfig::InMemory(cfg) => Self::InMemory(
+                    InMemoryQueue::new(cfg)
                         .await
                         .map_err(AnyQueueError::InMemory)?,
                 ),
                 QueueConfig::PgQueue(cfg) => {
                     Self::PgQueue(PgQueue::new(cfg).await.map_err(AnyQueueError::PgQueue)?)
                 }
+                QueueConfig::SqliteQueue(cfg) => Self::SqliteQueue(
+                    SqliteQueue::new(cfg)
+                        .await
+                        .map_err(AnyQueueError::SqliteQueue)?,
+                ),
             })
         }
     }
@@ -87,6 +122,10 @@ impl Queue<VoyagerMessage> for QueueImpl {
                     .enqueue(item, filter)
                     .await
                     .map_err(AnyQueueError::PgQueue)?,
+                QueueImpl::SqliteQueue(queue) => queue
+                    .enqueue(item, filter)
+                    .await
+                    .map_err(AnyQueueError::SqliteQueue)?,
             };
 
             trace!("queued");
@@ -115,6 +154,10 @@ impl Queue<VoyagerMessage> for QueueImpl {
                     .process(filter, f)
                     .await
                     .map_err(AnyQueueError::PgQueue),
+                QueueImpl::SqliteQueue(queue) => queue
+                    .process(filter, f)
+                    .await
+                    .map_err(AnyQueueError::SqliteQueue),
             };
 
             trace!("processed");
@@ -126,62 +169,268 @@ impl Queue<VoyagerMessage> for QueueImpl {
     async fn optimize<'a, O: Pass<VoyagerMessage>>(
         &'a self,
         tag: &'a str,
+        filter: &'a JaqInterestFilter,
         optimizer: &'a O,
     ) -> Result<(), sqlx::Either<Self::Error, O::Error>> {
         match self {
             QueueImpl::InMemory(queue) => queue
-                .optimize(tag, optimizer)
+                .optimize(tag, filter, optimizer)
                 .await
                 .map_err(|e| e.map_left(AnyQueueError::InMemory)),
             QueueImpl::PgQueue(queue) => queue
-                .optimize(tag, optimizer)
+                .optimize(tag, filter, optimizer)
                 .await
                 .map_err(|e| e.map_left(AnyQueueError::PgQueue)),
+            QueueImpl::SqliteQueue(queue) => queue
+                .optimize(tag, filter, optimizer)
+                .await
+                .map_err(|e| e.map_left(AnyQueueError::SqliteQueue)),
+        }
+    }
+}
+
+impl QueueImpl {
+    /// The history of the item `id`, as served by
+    /// [`QueueRpcServer::queue_history`](crate::rpc::QueueRpcServer::queue_history).
+    pub async fn history(
+        &self,
+        id: i64,
+        max_depth: u32,
+    ) -> Result<Vec<HistoryItem<VoyagerMessage>>, AnyQueueError> {
+        match self {
+            QueueImpl::InMemory(queue) => queue
+                .history(id, max_depth)
+                .await
+                .map_err(AnyQueueError::InMemory),
+            QueueImpl::PgQueue(queue) => queue
+                .history(id, max_depth)
+                .await
+                .map_err(AnyQueueError::PgQueue),
+            QueueImpl::SqliteQueue(queue) => queue
+                .history(id, max_depth)
+                .await
+                .map_err(AnyQueueError::SqliteQueue),
+        }
+    }
+}
+
+impl QueueImpl {
+    /// Returns the items with the given status that match `query`, ordered by id.
+    pub async fn list(
+        &self,
+        status: ItemStatus,
+        query: &ItemQuery,
+    ) -> Result<Vec<ItemRecord<VoyagerMessage>>, AnyQueueError> {
+        match self {
+            QueueImpl::InMemory(queue) => queue
+                .list(status, query)
+                .await
+                .map_err(AnyQueueError::InMemory),
+            QueueImpl::PgQueue(queue) => queue
+                .list(status, query)
+                .await
+                .map_err(AnyQueueError::PgQueue),
+            QueueImpl::SqliteQueue(queue) => queue
+                .list(status, query)
+                .await
+                .map_err(AnyQueueError::SqliteQueue),
+        }
+    }
+
+    /// Set the priority of the item `id`, if it is ready or waiting to be optimized. Returns
+    /// whether the item was found.
+    pub async fn set_priority(&self, id: i64, priority: i32) -> Result<bool, AnyQueueError> {
+        match self {
+            QueueImpl::InMemory(queue) => queue
+                .set_priority(id, priority)
+                .await
+                .map_err(AnyQueueError::InMemory),
+            QueueImpl::PgQueue(queue) => queue
+                .set_priority(id, priority)
+                .await
+                .map_err(AnyQueueError::PgQueue),
+            QueueImpl::SqliteQueue(queue) => queue
+                .set_priority(id, priority)
+                .await
+                .map_err(AnyQueueError::SqliteQueue),
+        }
+    }
+}
+
+impl QueueImpl {
+    /// Whether this instance should run work that must only be done by a single instance sharing
+    /// the queue, such as optimizer passes and recurring ops. This is always `true` unless the
+    /// queue is running in high-availability mode.
+    #[must_use]
+    pub fn is_leader(&self) -> bool {
+        match self {
+            QueueImpl::InMemory(_) | QueueImpl::SqliteQueue(_) => true,
+            QueueImpl::PgQueue(queue) => queue.is_leader(),
         }
     }
 }
 
 impl Voyager {
-    pub async fn new(config: Config) -> anyhow::Result<Self> {
+    pub async fn new(config: Config, config_file_path: PathBuf) -> anyhow::Result<Self> {
         let queue = QueueImpl::new(config.voyager.queue.clone())
             .await
             .context("error initializing queue")?;
 
+        let context = Context::new(
+            config.plugins,
+            config.modules,
+            config.equivalent_chain_ids,
+            |h| {
+                h.register::<IbcClassic>();
+                h.register::<IbcUnion>();
+            },
+        )
+        .await
+        .context("error initializing plugins")?;
+
+        if let Some(cache) = config.voyager.cache {
+            info!(
+                capacity = %cache.capacity,
+                latest_ttl_milliseconds = cache.latest_ttl_milliseconds,
+                "enabling module query cache"
+            );
+
+            context.rpc_server.enable_cache(cache);
+        }
+
         Ok(Self {
-            context: Context::new(
-                config.plugins,
-                config.modules,
-                config.equivalent_chain_ids,
-                |h| {
-                    h.register::<IbcClassic>();
-                    h.register::<IbcUnion>();
-                },
-            )
-            .await
-            .context("error initializing plugins")?,
+            context,
             num_workers: config.voyager.num_workers,
             rest_laddr: config.voyager.rest_laddr,
             rpc_laddr: config.voyager.rpc_laddr,
             queue,
             optimizer_delay_milliseconds: config.voyager.optimizer_delay_milliseconds,
+            retry: config.voyager.retry,
+            priority_rules: config.voyager.priority_rules,
+            recurring: config.voyager.recurring,
+            signer_balances_interval_seconds: config.voyager.signer_balances_interval_seconds,
+            config_file_path,
         })
     }
 
+    fn plugin_infos(&self) -> Vec<PluginInfo> {
+        self.context
+            .interest_filters()
+            .into_iter()
+            .map(|(name, interest_filter)| PluginInfo {
+                name,
+                interest_filter,
+            })
+            .collect()
+    }
+
+    /// Reload the plugins, modules, and priority rules from the config file, and rebuild
+    /// `interest_filter` in place. Items that are already in the queue are not touched.
+    async fn reload(&self, interest_filter: &JaqInterestFilter) -> anyhow::Result<ReloadSummary> {
+        let config = read_config_file(&self.config_file_path)?;
+
+        // check the interest filters and priority rules before reloading anything, since the
+        // reload can't be rolled back
+        let plugin_infos = config
+            .plugins
+            .iter()
+            .filter(|plugin_config| plugin_config.enabled)
+            .map(get_plugin_info)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        JaqInterestFilter::new(plugin_infos)
+            .context("error building interest filters")?
+            .with_priority_rules(config.voyager.priority_rules.clone())
+            .context("error building priority rules")?;
+
+        let summary = self
+            .context
+            .reload(config.plugins, config.modules)
+            .await
+            .context("error reloading plugins and modules")?;
+
+        interest_filter
+            .replace(self.plugin_infos(), config.voyager.priority_rules)
+            .context("error rebuilding interest filter")?;
+
+        Ok(summary)
+    }
+
+    /// Run optimization passes for the plugin `plugin_name` until it is unloaded, returning the
+    /// name of the plugin.
+    async fn optimizer(
+        &self,
+        plugin_name: String,
+        filter: String,
+        interest_filter: &JaqInterestFilter,
+    ) -> String {
+        async {
+            loop {
+                let Ok(client) = self.context.plugin_client_raw(&plugin_name) else {
+                    info!("plugin has been unloaded, stopping optimizer");
+                    break;
+                };
+
+                if !self.queue.is_leader() {
+                    trace!("not the leader, skipping optimization pass");
+                } else {
+                    trace!("optimizing");
+
+                    let res = self
+                        .queue
+                        .optimize(
+                            &plugin_name,
+                            interest_filter,
+                            &PluginOptPass::new(client.client()),
+                        )
+                        .await
+                        .map_err(|e| {
+                            e.map_either::<_, _, BoxDynError, BoxDynError>(
+                                |x| Box::new(x),
+                                |x| Box::new(x),
+                            )
+                            .into_inner()
+                        });
+
+                    if let Err(error) = res {
+                        error!(
+                            error = %ErrorReporter(&*error),
+                            "optimization pass returned with error"
+                        );
+                    }
+                }
+
+                tokio::time::sleep(std::time::Duration::from_millis(
+                    self.optimizer_delay_milliseconds,
+                ))
+                .await;
+            }
+        }
+        .instrument(info_span!("optimize", %plugin_name))
+        .instrument(trace_span!("optimize_verbose", %filter))
+        .await;
+
+        plugin_name
+    }
+
     #[allow(clippy::too_many_lines)]
     pub async fn run(self) -> anyhow::Result<()> {
-        let interest_filter = JaqInterestFilter::new(
-            self.context
-                .interest_filters()
-                .clone()
-                .into_iter()
-                .map(|(name, interest_filter)| PluginInfo {
-                    name,
-                    interest_filter,
-                })
-                .collect(),
-        )?;
+        let interest_filter = JaqInterestFilter::new(self.plugin_infos())?
+            .with_priority_rules(self.priority_rules.clone())
+            .context("error building priority rules")?;
 
-        let queue_rx = api::run(&self.rest_laddr);
+        let signer_balances = SignerBalances::default();
+
+        let queue_rx = api::run(
+            &self.rest_laddr,
+            self.queue.clone(),
+            signer_balances.clone(),
+        );
+
+        let (reload_tx, reload_rx) = mpsc::channel::<ReloadRequest>(1);
+
+        // notifies the optimizer supervisor that the loaded plugins may have changed
+        let (plugins_changed_tx, plugins_changed_rx) = watch::channel(());
 
         {
             let mut tasks =
@@ -193,7 +442,10 @@ impl Voyager {
                         .build(&self.rpc_laddr)
                         .await?;
                     let addr = server.local_addr()?;
-                    let handle = server.start(self.context.rpc_server.clone().into_rpc());
+                    let mut rpc = self.context.rpc_server.clone().into_rpc();
+                    rpc.merge(QueueServer::new(self.queue.clone()).into_rpc())?;
+                    rpc.merge(ConfigServer::new(reload_tx.clone()).into_rpc())?;
+                    let handle = server.start(rpc);
                     info!("rpc listening on {addr}");
                     handle
                         .stopped()
@@ -229,6 +481,7 @@ impl Voyager {
                 tasks.push(Box::pin(
                     AssertUnwindSafe(
                         Engine::new(&self.context, &self.queue, &interest_filter)
+                            .with_retry_config(self.retry.clone())
                             .run()
                             .for_each(|res| async move {
                                 match res {
@@ -253,51 +506,228 @@ impl Voyager {
                 ));
             }
 
-            for (plugin_name, filter) in self.context.interest_filters() {
-                info!(%plugin_name, "spawning optimizer");
+            {
+                let this = &self;
+                let interest_filter = &interest_filter;
+                let mut plugins_changed_rx = plugins_changed_rx;
+
+                info!("spawning optimizer supervisor");
 
                 tasks.push(Box::pin(
                     AssertUnwindSafe(
-                        async {
-                            let plugin_name = plugin_name.clone();
-
-                            let pass = PluginOptPass::new(
-                                self.context
-                                    .plugin_client_raw(&plugin_name)
-                                    .expect("plugin exists")
-                                    .client(),
-                            );
+                        async move {
+                            let mut optimizers = FuturesUnordered::new();
+                            let mut running = HashSet::new();
 
                             loop {
-                                trace!("optimizing");
-
-                                let res =
-                                    self.queue.optimize(&plugin_name, &pass).await.map_err(|e| {
-                                        e.map_either::<_, _, BoxDynError, BoxDynError>(
-                                            |x| Box::new(x),
-                                            |x| Box::new(x),
-                                        )
-                                        .into_inner()
-                                    });
-
-                                if let Err(error) = res {
+                                for (plugin_name, filter) in this.context.interest_filters() {
+                                    if running.insert(plugin_name.clone()) {
+                                        info!(%plugin_name, "spawning optimizer");
+
+                                        optimizers.push(this.optimizer(
+                                            plugin_name,
+                                            filter,
+                                            interest_filter,
+                                        ));
+                                    }
+                                }
+
+                                tokio::select! {
+                                    Some(plugin_name) = optimizers.next() => {
+                                        running.remove(&plugin_name);
+                                    }
+                                    res = plugins_changed_rx.changed() => {
+                                        if res.is_err() {
+                                            break;
+                                        }
+                                    }
+                                }
+                            }
+
+                            Ok(())
+                        }
+                        .instrument(info_span!("optimizer_supervisor")),
+                    )
+                    .catch_unwind(),
+                ));
+            }
+
+            {
+                let this = &self;
+                let interest_filter = &interest_filter;
+                let mut reload_rx = reload_rx;
+
+                tasks.push(Box::pin(
+                    AssertUnwindSafe(
+                        async move {
+                            while let Some(tx) = reload_rx.recv().await {
+                                info!(
+                                    config_file_path = %this.config_file_path.display(),
+                                    "reloading config"
+                                );
+
+                                let res = this.reload(interest_filter).await;
+
+                                plugins_changed_tx.send_replace(());
+
+                                let res = res.map_err(|error| {
                                     error!(
                                         error = %ErrorReporter(&*error),
-                                        "optimization pass returned with error"
+                                        "error reloading config"
                                     );
-                                }
 
-                                tokio::time::sleep(std::time::Duration::from_millis(
-                                    self.optimizer_delay_milliseconds,
+                                    ErrorReporter(&*error).to_string()
+                                });
+
+                                // the caller may have gone away, but the reload is done either way
+                                let _ = tx.send(res);
+                            }
+
+                            Ok(())
+                        }
+                        .instrument(info_span!("config_reload")),
+                    )
+                    .catch_unwind(),
+                ));
+            }
+
+            {
+                let context = &self.context;
+                let signer_balances = &signer_balances;
+                let interval =
+                    std::time::Duration::from_secs(self.signer_balances_interval_seconds);
+
+                info!("spawning signer balances collector");
+
+                tasks.push(Box::pin(
+                    AssertUnwindSafe(
+                        async move {
+                            loop {
+                                signer_balances::update(context, signer_balances).await;
+
+                                tokio::time::sleep(interval).await;
+                            }
+                        }
+                        .instrument(info_span!("signer_balances")),
+                    )
+                    .catch_unwind(),
+                ));
+            }
+
+            for recurring in &self.recurring {
+                info!(name = %recurring.name, "spawning recurring op");
+
+                let queue = &self.queue;
+                let interest_filter = &interest_filter;
+
+                tasks.push(Box::pin(
+                    AssertUnwindSafe(
+                        async move {
+                            loop {
+                                let next_run = recurring.next_run(now());
+
+                                trace!(%next_run, "waiting for next run");
+
+                                tokio::time::sleep(std::time::Duration::from_secs(
+                                    next_run.saturating_sub(now()),
                                 ))
                                 .await;
+
+                                if !queue.is_leader() {
+                                    debug!("not the leader, skipping recurring op");
+                                    continue;
+                                }
+
+                                info!("enqueueing recurring op");
+
+                                if let Err(error) =
+                                    queue.enqueue(recurring.op.clone(), interest_filter).await
+                                {
+                                    error!(
+                                        error = %ErrorReporter(&error),
+                                        "error enqueueing recurring op"
+                                    );
+                                }
+                            }
+                        }
+                        .instrument(info_span!("recurring", name = %recurring.name)),
+                    )
+                    .catch_unwind(),
+                ));
+            }
+
+            if let QueueImpl::PgQueue(queue) = &self.queue {
+                info!("spawning queue metrics updater");
+
+                tasks.push(Box::pin(
+                    AssertUnwindSafe(
+                        async move {
+                            loop {
+                                if let Err(error) = queue.update_metrics().await {
+                                    error!(
+                                        error = %ErrorReporter(&error),
+                                        "error updating queue metrics"
+                                    );
+                                }
+
+                                tokio::time::sleep(pg_queue::METRICS_UPDATE_INTERVAL).await;
                             }
                         }
-                        .instrument(info_span!("optimize", %plugin_name))
-                        .instrument(trace_span!("optimize_verbose", %filter)),
+                        .instrument(info_span!("update_metrics")),
                     )
                     .catch_unwind(),
                 ));
+
+                if let Some(retention) = queue.retention() {
+                    info!("spawning done table pruner");
+
+                    tasks.push(Box::pin(
+                        AssertUnwindSafe(
+                            async move {
+                                loop {
+                                    if !queue.is_leader() {
+                                        trace!("not the leader, skipping pruning");
+                                    } else if let Err(error) = queue.prune_done().await {
+                                        error!(
+                                            error = %ErrorReporter(&error),
+                                            "error pruning done table"
+                                        );
+                                    }
+
+                                    tokio::time::sleep(std::time::Duration::from_secs(
+                                        retention.interval_seconds,
+                                    ))
+                                    .await;
+                                }
+                            }
+                            .instrument(info_span!("prune_done")),
+                        )
+                        .catch_unwind(),
+                    ));
+                }
+
+                if let Some(ha) = queue.ha() {
+                    info!(shards = ha.shards, "spawning high-availability coordinator");
+
+                    tasks.push(Box::pin(
+                        AssertUnwindSafe(
+                            async move {
+                                loop {
+                                    if let Err(error) = queue.rebalance().await {
+                                        error!(
+                                            error = %ErrorReporter(&error),
+                                            "error rebalancing shards, giving up all locks"
+                                        );
+                                    }
+
+                                    tokio::time::sleep(ha.rebalance_interval()).await;
+                                }
+                            }
+                            .instrument(info_span!("ha_coordinator")),
+                        )
+                        .catch_unwind(),
+                    ));
+                }
             }
 
             self.context
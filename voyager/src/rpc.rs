@@ -0,0 +1,147 @@
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::ErrorObject,
+};
+use tokio::sync::{mpsc, oneshot};
+use unionlabs::ErrorReporter;
+use voyager_message::{context::ReloadSummary, VoyagerMessage, FATAL_JSONRPC_ERROR_CODE};
+use voyager_vm::{
+    history::{HistoryItem, ItemStatus},
+    inspect::{ItemQuery, ItemRecord},
+};
+
+use crate::queue::QueueImpl;
+
+/// JSON-RPC methods for inspecting the queue of a running voyager instance. These are served
+/// alongside the [`VoyagerRpc`](voyager_message::rpc::VoyagerRpcServer) methods.
+#[rpc(client, server, namespace = "voyager")]
+pub trait QueueRpc {
+    /// Returns the item `id` along with all of its ancestors, up to `max_depth` levels deep.
+    #[method(name = "queueHistory")]
+    async fn queue_history(
+        &self,
+        id: i64,
+        max_depth: u32,
+    ) -> RpcResult<Vec<HistoryItem<VoyagerMessage>>>;
+
+    /// Returns the items with the given status that match `query`, ordered by id.
+    #[method(name = "queueList")]
+    async fn queue_list(
+        &self,
+        status: ItemStatus,
+        query: ItemQuery,
+    ) -> RpcResult<Vec<ItemRecord<VoyagerMessage>>>;
+
+    /// Set the priority of the item `id`, if it is ready or waiting to be optimized. Returns
+    /// whether the item was found.
+    #[method(name = "queueSetPriority")]
+    async fn queue_set_priority(&self, id: i64, priority: i32) -> RpcResult<bool>;
+}
+
+#[derive(Debug, Clone)]
+pub struct QueueServer {
+    queue: QueueImpl,
+}
+
+impl QueueServer {
+    pub fn new(queue: QueueImpl) -> Self {
+        Self { queue }
+    }
+}
+
+#[async_trait]
+impl QueueRpcServer for QueueServer {
+    async fn queue_history(
+        &self,
+        id: i64,
+        max_depth: u32,
+    ) -> RpcResult<Vec<HistoryItem<VoyagerMessage>>> {
+        self.queue.history(id, max_depth).await.map_err(|e| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("error querying queue history: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        })
+    }
+
+    async fn queue_list(
+        &self,
+        status: ItemStatus,
+        query: ItemQuery,
+    ) -> RpcResult<Vec<ItemRecord<VoyagerMessage>>> {
+        self.queue.list(status, &query).await.map_err(|e| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("error listing queue items: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        })
+    }
+
+    async fn queue_set_priority(&self, id: i64, priority: i32) -> RpcResult<bool> {
+        self.queue.set_priority(id, priority).await.map_err(|e| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("error setting item priority: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        })
+    }
+}
+
+/// JSON-RPC methods for managing the configuration of a running voyager instance.
+#[rpc(client, server, namespace = "voyager")]
+pub trait ConfigRpc {
+    /// Reload the plugins, modules, and priority rules of the running instance from the config
+    /// file it was started with, without draining the queue. Only the plugins and modules that
+    /// were added, removed, or whose config changed are started, stopped, or restarted.
+    ///
+    /// All other fields of the config are ignored, and changing them requires a restart.
+    ///
+    /// This takes no parameters on purpose: the config contains the paths of the plugin and module
+    /// binaries to spawn, as well as keys, neither of which should be accepted from or sent over
+    /// the RPC.
+    #[method(name = "configReload")]
+    async fn config_reload(&self) -> RpcResult<ReloadSummary>;
+}
+
+/// A request to reload the config, to be handled by the running [`Voyager`](crate::queue::Voyager)
+/// instance.
+pub type ReloadRequest = oneshot::Sender<Result<ReloadSummary, String>>;
+
+#[derive(Debug, Clone)]
+pub struct ConfigServer {
+    reload_tx: mpsc::Sender<ReloadRequest>,
+}
+
+impl ConfigServer {
+    pub fn new(reload_tx: mpsc::Sender<ReloadRequest>) -> Self {
+        Self { reload_tx }
+    }
+}
+
+#[async_trait]
+impl ConfigRpcServer for ConfigServer {
+    async fn config_reload(&self) -> RpcResult<ReloadSummary> {
+        let (tx, rx) = oneshot::channel();
+
+        let error = |message: String| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("error reloading config: {message}"),
+                None::<()>,
+            )
+        };
+
+        self.reload_tx
+            .send(tx)
+            .await
+            .map_err(|_| error("voyager is shutting down".to_owned()))?;
+
+        rx.await
+            .map_err(|_| error("voyager is shutting down".to_owned()))?
+            .map_err(error)
+    }
+}
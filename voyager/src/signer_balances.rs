@@ -0,0 +1,101 @@
+//! Collection of the signer balances reported by the transaction plugins.
+//!
+//! Each plugin that implements [`KeyringServer`](voyager_message::module::KeyringServer) tracks
+//! the balances of its own keys (and takes keys with a low balance out of rotation); voyager
+//! periodically collects these so they can be exposed as metrics and via `/signer/balances`.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, LazyLock, RwLock},
+};
+
+use jsonrpsee::{core::client::Error, types::error::METHOD_NOT_FOUND_CODE};
+use prometheus::{register_gauge_vec, register_int_gauge_vec, GaugeVec, IntGaugeVec};
+use tracing::{trace, warn};
+use unionlabs::ErrorReporter;
+use voyager_message::{
+    context::Context,
+    module::{KeyringClient, SignerBalance},
+};
+
+/// The most recently reported signer balances, keyed by plugin name.
+pub type SignerBalances = Arc<RwLock<BTreeMap<String, Vec<SignerBalance>>>>;
+
+const SIGNER_LABELS: &[&str] = &["plugin", "key_name", "address", "denom"];
+
+pub static SIGNER_BALANCE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "voyager_signer_balance",
+        "The balance of each key in the keyring of each transaction plugin.",
+        SIGNER_LABELS,
+    )
+    .unwrap()
+});
+
+pub static SIGNER_ENABLED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "voyager_signer_enabled",
+        "Whether each key in the keyring of each transaction plugin is in rotation.",
+        SIGNER_LABELS,
+    )
+    .unwrap()
+});
+
+/// Fetch the signer balances from all loaded plugins, replacing the contents of `balances` and
+/// updating the signer metrics. Plugins that don't expose a keyring are skipped, and plugins that
+/// fail to respond keep their previously reported balances.
+pub async fn update(context: &Context, balances: &SignerBalances) {
+    let mut new_balances = BTreeMap::new();
+
+    for plugin_name in context.interest_filters().into_keys() {
+        // the plugin may have been unloaded in the meantime
+        let Ok(client) = context.plugin_client_raw(&plugin_name) else {
+            continue;
+        };
+
+        match client.signer_balances().await {
+            Ok(plugin_balances) => {
+                new_balances.insert(plugin_name, plugin_balances);
+            }
+            Err(Error::Call(error)) if error.code() == METHOD_NOT_FOUND_CODE => {
+                trace!(%plugin_name, "plugin does not expose a keyring");
+            }
+            Err(error) => {
+                warn!(
+                    %plugin_name,
+                    error = %ErrorReporter(error),
+                    "error fetching signer balances, keeping the previously reported balances"
+                );
+
+                if let Some(previous) = balances.read().unwrap().get(&plugin_name) {
+                    new_balances.insert(plugin_name, previous.clone());
+                }
+            }
+        }
+    }
+
+    // reset such that keys of unloaded plugins don't linger
+    SIGNER_BALANCE.reset();
+    SIGNER_ENABLED.reset();
+
+    for (plugin_name, plugin_balances) in &new_balances {
+        for balance in plugin_balances {
+            let labels = [
+                plugin_name.as_str(),
+                balance.key_name.as_str(),
+                balance.address.as_str(),
+                balance.denom.as_str(),
+            ];
+
+            #[allow(clippy::cast_precision_loss)] // balances are only approximate in metrics
+            SIGNER_BALANCE
+                .with_label_values(&labels)
+                .set(balance.balance as f64);
+            SIGNER_ENABLED
+                .with_label_values(&labels)
+                .set(balance.enabled.into());
+        }
+    }
+
+    *balances.write().unwrap() = new_balances;
+}